@@ -0,0 +1,38 @@
+//! Primitive deserialization benchmark, measuring the cost of deserializing
+//! many primitives through `&mut dyn Deserializer` rather than directly.
+//!
+//! Every element of the sequence is a primitive deserialized through its own
+//! `InplaceVisitor`, which lives on the stack of the call: the difference with
+//! the direct deserialization is the cost of the virtual calls, not of setting
+//! up the visitor.
+
+use std::hint::black_box;
+
+use criterion::Criterion;
+
+use serde::Deserialize as _;
+use serde::de::value::{Error, SeqDeserializer};
+
+use dyn_serde::Deserializer;
+
+const LEN: u64 = 100_000;
+
+fn main() {
+    Criterion::default()
+        .configure_from_args()
+        .bench_function("direct", |bench| {
+            bench.iter(|| {
+                let de = SeqDeserializer::<_, Error>::new(0..black_box(LEN));
+                black_box(Vec::<u64>::deserialize(de).unwrap())
+            });
+        })
+        .bench_function("dyn-serde", |bench| {
+            bench.iter(|| {
+                let de = SeqDeserializer::<_, Error>::new(0..black_box(LEN));
+                let mut de = <dyn Deserializer>::new(de);
+                let de: &mut dyn Deserializer = &mut de;
+                black_box(Vec::<u64>::deserialize(de).unwrap())
+            });
+        })
+        .final_summary();
+}
@@ -0,0 +1,97 @@
+//! Sequence deserialization benchmark, with and without reserving the size
+//! hint of the format up front.
+//!
+//! The number of allocations of each strategy is printed before the timings.
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::fmt;
+use std::hint::black_box;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use criterion::Criterion;
+
+use serde::de::value::{Error, SeqDeserializer};
+use serde::de::{Deserializer as _, SeqAccess as _};
+
+use dyn_serde::Deserializer;
+use dyn_serde::de::{InplaceSeqAccess, deserialize_seq_into_vec};
+
+/// An allocator counting the allocations, including the reallocations.
+struct Counting;
+
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for Counting {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        unsafe { System.alloc(layout) }
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        unsafe { System.dealloc(ptr, layout) }
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        unsafe { System.realloc(ptr, layout, new_size) }
+    }
+}
+
+#[global_allocator]
+static GLOBAL: Counting = Counting;
+
+const LEN: u64 = 100_000;
+
+/// Collects a sequence, either element by element or reserved up front.
+struct Collect {
+    reserve: bool,
+}
+
+impl<'de> serde::de::Visitor<'de> for Collect {
+    type Value = Vec<u64>;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("a sequence")
+    }
+
+    fn visit_seq<A: serde::de::SeqAccess<'de>>(self, seq: A) -> Result<Vec<u64>, A::Error> {
+        let mut access = InplaceSeqAccess::SeqAccess(seq);
+        if self.reserve {
+            return Ok(deserialize_seq_into_vec(&mut access).unwrap());
+        }
+
+        let mut access = &mut access as &mut dyn dyn_serde::de::SeqAccess;
+        let mut elements = Vec::new();
+        while let Some(element) = access.next_element().unwrap() {
+            elements.push(element);
+        }
+        Ok(elements)
+    }
+}
+
+fn collect(reserve: bool) -> Vec<u64> {
+    let de = SeqDeserializer::<_, Error>::new(0..LEN);
+    let mut de = <dyn Deserializer>::new(de);
+    let de: &mut dyn Deserializer = &mut de;
+    de.deserialize_seq(Collect { reserve }).unwrap()
+}
+
+fn main() {
+    for (name, reserve) in [("push", false), ("reserved", true)] {
+        let before = ALLOCATIONS.load(Ordering::Relaxed);
+        let elements = collect(reserve);
+        let allocations = ALLOCATIONS.load(Ordering::Relaxed) - before;
+        assert_eq!(elements.len() as u64, LEN);
+        println!("{name}: {allocations} allocations for {LEN} elements");
+    }
+
+    Criterion::default()
+        .configure_from_args()
+        .bench_function("push", |bench| {
+            bench.iter(|| black_box(collect(black_box(false))));
+        })
+        .bench_function("reserved", |bench| {
+            bench.iter(|| black_box(collect(black_box(true))));
+        })
+        .final_summary();
+}
@@ -1,4 +1,114 @@
 //! For dynamic deserialization, see [`Deserializer`] and [`DeserializeSeed`].
+//!
+//! For debugging format mismatches, see [`TraceDeserializer`].
+//!
+//! For reading bytes written as sequences, e.g. by `serde_json`, see
+//! [`BytesAsSeq`].
+//!
+//! For reading a struct either from a sequence or from a map, see
+//! [`SeqOrMap`].
+//!
+//! For reading either `"x"` or `["x"]` for the same value, see
+//! [`ScalarOrSeq`].
+//!
+//! For reading a parameter of a query string sent once or several times into
+//! a vector, e.g. `tag=x` or `tag=x&tag=y`, see [`OneOrMany`].
+//!
+//! For telling the elements of sequences their index, e.g. as an `"_index"`
+//! field, see [`IndexElements`].
+//!
+//! For warning about unknown fields rather than rejecting them, see
+//! [`CollectUnknownFields`].
+//!
+//! For rejecting unknown fields in types which do not deny them, see
+//! [`DenyUnknownFields`].
+//!
+//! For warning about deprecated fields while still reading them, see
+//! [`DeprecateFields`].
+//!
+//! For reporting every missing required field at once, see
+//! [`RequireFields`].
+//!
+//! For locating the value which fails to deserialize, see [`PathTracking`].
+//!
+//! For rejecting integers out of range of their type with clear errors, see
+//! [`CheckedNumbers`].
+//!
+//! For requiring the value to be a string, a number or another kind, with
+//! clear errors, see [`TypeConstrained`].
+//!
+//! For discarding the tag field of structs, see [`UntagStructs`].
+//!
+//! For deserializing into an existing value, see [`InPlaceSeed`].
+//!
+//! For reading sentinel strings such as `"N/A"` as `None`, see [`NullLike`].
+//!
+//! For reading empty strings as `None`, e.g. the blank inputs of a form, see
+//! [`EmptyStringAsNone`].
+//!
+//! For reading `null` as an empty sequence or map, e.g. from lenient APIs,
+//! see [`NullAsEmpty`].
+//!
+//! For reading unit enum variants given as their index, see
+//! [`OrdinalVariants`].
+//!
+//! For reading enum variants regardless of their case, e.g. `"ACTIVE"`, see
+//! [`CaseInsensitiveVariants`].
+//!
+//! For reading integer and other scalar map keys from strings, see
+//! [`ParseKeys`].
+//!
+//! For replacing what a visitor states it expects in type errors, see
+//! [`WithExpecting`].
+//!
+//! For overriding whether a deserializer is human-readable, see
+//! [`WithHumanReadable`].
+//!
+//! For reading a record of tabular data, e.g. a row of a CSV file, given its
+//! header, see [`from_record`].
+//!
+//! For deserializing a map while preserving the order of its entries and its
+//! duplicate keys, e.g. HTTP headers, see [`deserialize_ordered_map`].
+//!
+//! For deserializing a sequence into a vector reserved up front, see
+//! [`deserialize_seq_into_vec`].
+//!
+//! For reading legacy field names, see `RenameKeys`, which requires feature
+//! `std`.
+//!
+//! For the time every field of a struct takes to deserialize, see
+//! `FieldTimings`, which requires feature `std`.
+//!
+//! For reading a stream of length-prefixed frames one value at a time, see
+//! `FrameReader`, which requires feature `std`.
+//!
+//! For transforming the whole value before it is deserialized, see
+//! `MapDeserializer`, which requires feature `serde_json`.
+//!
+//! For parsing a value once and deserializing it into several types, see
+//! `Cached`, which requires feature `serde_json`.
+//!
+//! For deserializing from a string of JSON in a single call, see
+//! `from_json_str`, which requires feature `serde_json`.
+//!
+//! For deserializing from JSON with comments and trailing commas, e.g.
+//! configuration files, see `from_relaxed_json_str`, which requires feature
+//! `serde_json`.
+//!
+//! For reading adjacently tagged enums, e.g. `{"t": "Move", "c": {"x": 1}}`,
+//! see `AdjacentlyTagged`, which requires feature `serde_json`.
+//!
+//! For reading flat maps of dotted keys, e.g. `{"a.b.c": 1}`, as nested
+//! values, see `UnflattenDotted`, which requires feature `serde_json`.
+//!
+//! For reading a value from defaults overlaid by overrides, e.g. layered
+//! configuration, see `Overlay`, which requires feature `serde_json`.
+//!
+//! For reading a value from a versioned envelope, upgrading older versions
+//! with migrations, see `Versioned`, which requires feature `serde_json`.
+//!
+//! For reading timestamps in a chosen format, see `TimestampFormat`, which
+//! requires feature `time`.
 
 use core::error::Error;
 use core::fmt::{self, Debug, Display, Formatter};
@@ -13,6 +123,109 @@ use alloc::vec::Vec;
 
 use serde::de::VariantAccess as _;
 
+#[cfg(feature = "serde_json")]
+mod adjacently_tagged;
+mod bytes_as_seq;
+#[cfg(feature = "serde_json")]
+mod cached;
+mod case_insensitive_variants;
+mod checked_numbers;
+mod deprecate_fields;
+mod empty_string_as_none;
+#[cfg(feature = "std")]
+mod field_timings;
+#[cfg(feature = "std")]
+mod frame_reader;
+mod in_place_seed;
+mod index_elements;
+#[cfg(feature = "serde_json")]
+mod json;
+#[cfg(feature = "serde_json")]
+mod json_str;
+#[cfg(feature = "serde_json")]
+mod map_value;
+mod middleware;
+mod null_as_empty;
+mod null_like;
+mod ordered_map;
+mod ordinal_variants;
+#[cfg(feature = "serde_json")]
+mod overlay;
+mod parse_keys;
+mod path_tracking;
+mod record;
+#[cfg(feature = "serde_json")]
+mod relaxed_json;
+#[cfg(feature = "std")]
+mod rename_keys;
+mod require_fields;
+mod scalar_or_seq;
+mod seq_into_vec;
+mod seq_or_map;
+#[cfg(feature = "time")]
+mod timestamp_format;
+mod trace;
+mod type_constrained;
+#[cfg(feature = "serde_json")]
+mod unflatten_dotted;
+mod unknown_fields;
+mod untag_structs;
+#[cfg(feature = "serde_json")]
+mod versioned;
+mod with_expecting;
+mod with_human_readable;
+
+#[cfg(feature = "serde_json")]
+pub use self::adjacently_tagged::AdjacentlyTagged;
+pub use self::bytes_as_seq::BytesAsSeq;
+#[cfg(feature = "serde_json")]
+pub use self::cached::Cached;
+pub use self::case_insensitive_variants::CaseInsensitiveVariants;
+pub use self::checked_numbers::CheckedNumbers;
+pub use self::deprecate_fields::DeprecateFields;
+pub use self::empty_string_as_none::EmptyStringAsNone;
+#[cfg(feature = "std")]
+pub use self::field_timings::FieldTimings;
+#[cfg(feature = "std")]
+pub use self::frame_reader::FrameReader;
+pub use self::in_place_seed::InPlaceSeed;
+pub use self::index_elements::IndexElements;
+#[cfg(feature = "serde_json")]
+pub use self::json::{JsonValueDeserializer, from_json_value};
+#[cfg(feature = "serde_json")]
+pub use self::json_str::from_json_str;
+#[cfg(feature = "serde_json")]
+pub use self::map_value::MapDeserializer;
+pub use self::null_as_empty::NullAsEmpty;
+pub use self::null_like::NullLike;
+pub use self::ordered_map::deserialize_ordered_map;
+pub use self::ordinal_variants::OrdinalVariants;
+#[cfg(feature = "serde_json")]
+pub use self::overlay::Overlay;
+pub use self::parse_keys::ParseKeys;
+pub use self::path_tracking::PathTracking;
+pub use self::record::{RecordDeserializer, from_record};
+#[cfg(feature = "serde_json")]
+pub use self::relaxed_json::{from_relaxed_json_str, relax_json};
+#[cfg(feature = "std")]
+pub use self::rename_keys::RenameKeys;
+pub use self::require_fields::RequireFields;
+pub use self::scalar_or_seq::{OneOrMany, ScalarOrSeq};
+pub use self::seq_into_vec::deserialize_seq_into_vec;
+pub use self::seq_or_map::SeqOrMap;
+#[cfg(feature = "time")]
+pub use self::timestamp_format::TimestampFormat;
+pub use self::trace::TraceDeserializer;
+pub use self::type_constrained::{TypeConstrained, ValueKind};
+#[cfg(feature = "serde_json")]
+pub use self::unflatten_dotted::UnflattenDotted;
+pub use self::unknown_fields::{CollectUnknownFields, DenyUnknownFields};
+pub use self::untag_structs::UntagStructs;
+#[cfg(feature = "serde_json")]
+pub use self::versioned::Versioned;
+pub use self::with_expecting::WithExpecting;
+pub use self::with_human_readable::WithHumanReadable;
+
 /// The result type returned by [`dyn Deserializer`]'s methods.
 ///
 /// [`dyn Deserializer`]: Deserializer
@@ -26,6 +239,50 @@ pub type InplaceDeserializeResult<T> = Result<T, InplaceDeserializeError>;
 /// One should avoid implementing `Deserializer` manually and use
 /// `<dyn Deserializer>::new` to construct an instance instead.
 ///
+/// # Required methods
+///
+/// A manual implementation only has to provide `dyn_deserialize_any`. Every
+/// `dyn_deserialize_*` hint forwards to `dyn_deserialize_any` by default,
+/// which is the right behavior for self-describing formats, and
+/// `dyn_is_human_readable` returns `true` by default. Override only the hints
+/// the format can make use of, e.g. `dyn_deserialize_option` for a format
+/// that distinguishes `None` from `Some(_)` without driving `dyn_visit_none`
+/// and `dyn_visit_some` from `dyn_deserialize_any`.
+///
+/// # Flattened fields
+///
+/// A struct with a `#[serde(flatten)]` field deserializes through the dynamic
+/// layer as it does through the wrapped format: it asks for a map, buffers the
+/// entries its named fields do not take, and reads the flattened field from
+/// them. As the buffered entries are read with `dyn_deserialize_any`, the
+/// format must be self-describing. A catch-all map of `serde_json::Value`
+/// collects the other entries whatever their types:
+///
+/// ```
+/// # use std::collections::HashMap;
+/// # use serde::Deserialize as _;
+/// # use dyn_serde::Deserializer;
+/// #
+/// #[derive(serde::Deserialize)]
+/// struct Item {
+///     id: u32,
+///     #[serde(flatten)]
+///     rest: HashMap<String, serde_json::Value>,
+/// }
+///
+/// let mut deserializer = serde_json::Deserializer::from_str(r#"{"id": 1, "tags": ["a"]}"#);
+/// let mut deserializer = <dyn Deserializer>::new(&mut deserializer);
+///
+/// let item = Item::deserialize(&mut deserializer as &mut dyn Deserializer).unwrap();
+/// assert_eq!(item.id, 1);
+/// assert_eq!(item.rest["tags"], serde_json::json!(["a"]));
+/// ```
+///
+/// As the struct asks for a map, the wrappers acting on the fields of
+/// structs, e.g. [`UntagStructs`] and [`RequireFields`], leave it unchanged.
+/// To read the same entries into several types, parse them once into a
+/// `Cached`, which requires feature `serde_json`.
+///
 /// # Example
 ///
 /// ```
@@ -52,85 +309,113 @@ pub trait Deserializer<'de> {
     fn dyn_deserialize_bool(
         &mut self,
         visitor: &mut dyn Visitor<'de>,
-    ) -> InplaceDeserializeResult<()>;
+    ) -> InplaceDeserializeResult<()> {
+        self.dyn_deserialize_any(visitor)
+    }
 
     /// Hint that the `Deserialize` type is expecting an `i8` value.
     fn dyn_deserialize_i8(
         &mut self,
         visitor: &mut dyn Visitor<'de>,
-    ) -> InplaceDeserializeResult<()>;
+    ) -> InplaceDeserializeResult<()> {
+        self.dyn_deserialize_any(visitor)
+    }
 
     /// Hint that the `Deserialize` type is expecting an `i16` value.
     fn dyn_deserialize_i16(
         &mut self,
         visitor: &mut dyn Visitor<'de>,
-    ) -> InplaceDeserializeResult<()>;
+    ) -> InplaceDeserializeResult<()> {
+        self.dyn_deserialize_any(visitor)
+    }
 
     /// Hint that the `Deserialize` type is expecting an `i32` value.
     fn dyn_deserialize_i32(
         &mut self,
         visitor: &mut dyn Visitor<'de>,
-    ) -> InplaceDeserializeResult<()>;
+    ) -> InplaceDeserializeResult<()> {
+        self.dyn_deserialize_any(visitor)
+    }
 
     /// Hint that the `Deserialize` type is expecting an `i64` value.
     fn dyn_deserialize_i64(
         &mut self,
         visitor: &mut dyn Visitor<'de>,
-    ) -> InplaceDeserializeResult<()>;
+    ) -> InplaceDeserializeResult<()> {
+        self.dyn_deserialize_any(visitor)
+    }
 
     /// Hint that the `Deserialize` type is expecting an `i128` value.
     fn dyn_deserialize_i128(
         &mut self,
         visitor: &mut dyn Visitor<'de>,
-    ) -> InplaceDeserializeResult<()>;
+    ) -> InplaceDeserializeResult<()> {
+        self.dyn_deserialize_any(visitor)
+    }
 
     /// Hint that the `Deserialize` type is expecting a `u8` value.
     fn dyn_deserialize_u8(
         &mut self,
         visitor: &mut dyn Visitor<'de>,
-    ) -> InplaceDeserializeResult<()>;
+    ) -> InplaceDeserializeResult<()> {
+        self.dyn_deserialize_any(visitor)
+    }
 
     /// Hint that the `Deserialize` type is expecting a `u16` value.
     fn dyn_deserialize_u16(
         &mut self,
         visitor: &mut dyn Visitor<'de>,
-    ) -> InplaceDeserializeResult<()>;
+    ) -> InplaceDeserializeResult<()> {
+        self.dyn_deserialize_any(visitor)
+    }
 
     /// Hint that the `Deserialize` type is expecting a `u32` value.
     fn dyn_deserialize_u32(
         &mut self,
         visitor: &mut dyn Visitor<'de>,
-    ) -> InplaceDeserializeResult<()>;
+    ) -> InplaceDeserializeResult<()> {
+        self.dyn_deserialize_any(visitor)
+    }
 
     /// Hint that the `Deserialize` type is expecting a `u64` value.
     fn dyn_deserialize_u64(
         &mut self,
         visitor: &mut dyn Visitor<'de>,
-    ) -> InplaceDeserializeResult<()>;
+    ) -> InplaceDeserializeResult<()> {
+        self.dyn_deserialize_any(visitor)
+    }
 
     /// Hint that the `Deserialize` type is expecting an `u128` value.
     fn dyn_deserialize_u128(
         &mut self,
         visitor: &mut dyn Visitor<'de>,
-    ) -> InplaceDeserializeResult<()>;
+    ) -> InplaceDeserializeResult<()> {
+        self.dyn_deserialize_any(visitor)
+    }
 
     /// Hint that the `Deserialize` type is expecting a `f32` value.
     fn dyn_deserialize_f32(
         &mut self,
         visitor: &mut dyn Visitor<'de>,
-    ) -> InplaceDeserializeResult<()>;
+    ) -> InplaceDeserializeResult<()> {
+        self.dyn_deserialize_any(visitor)
+    }
 
     /// Hint that the `Deserialize` type is expecting a `f64` value.
     fn dyn_deserialize_f64(
         &mut self,
         visitor: &mut dyn Visitor<'de>,
-    ) -> InplaceDeserializeResult<()>;
+    ) -> InplaceDeserializeResult<()> {
+        self.dyn_deserialize_any(visitor)
+    }
 
     /// Hint that the `Deserialize` type is expecting a `char` value.
     fn dyn_deserialize_char(
         &mut self,
         visitor: &mut dyn Visitor<'de>,
-    ) -> InplaceDeserializeResult<()>;
+    ) -> InplaceDeserializeResult<()> {
+        self.dyn_deserialize_any(visitor)
+    }
 
     /// Hint that the `Deserialize` type is expecting a string value and does
     /// not benefit from taking ownership of buffered data owned by the
@@ -138,7 +423,9 @@ pub trait Deserializer<'de> {
     fn dyn_deserialize_str(
         &mut self,
         visitor: &mut dyn Visitor<'de>,
-    ) -> InplaceDeserializeResult<()>;
+    ) -> InplaceDeserializeResult<()> {
+        self.dyn_deserialize_any(visitor)
+    }
 
     /// Hint that the `Deserialize` type is expecting a string value and would
     /// benefit from taking ownership of buffered data owned by the
@@ -146,7 +433,9 @@ pub trait Deserializer<'de> {
     fn dyn_deserialize_string(
         &mut self,
         visitor: &mut dyn Visitor<'de>,
-    ) -> InplaceDeserializeResult<()>;
+    ) -> InplaceDeserializeResult<()> {
+        self.dyn_deserialize_any(visitor)
+    }
 
     /// Hint that the `Deserialize` type is expecting a byte array and does not
     /// benefit from taking ownership of buffered data owned by the
@@ -154,7 +443,9 @@ pub trait Deserializer<'de> {
     fn dyn_deserialize_bytes(
         &mut self,
         visitor: &mut dyn Visitor<'de>,
-    ) -> InplaceDeserializeResult<()>;
+    ) -> InplaceDeserializeResult<()> {
+        self.dyn_deserialize_any(visitor)
+    }
 
     /// Hint that the `Deserialize` type is expecting a byte array and would
     /// benefit from taking ownership of buffered data owned by the
@@ -162,19 +453,25 @@ pub trait Deserializer<'de> {
     fn dyn_deserialize_byte_buf(
         &mut self,
         visitor: &mut dyn Visitor<'de>,
-    ) -> InplaceDeserializeResult<()>;
+    ) -> InplaceDeserializeResult<()> {
+        self.dyn_deserialize_any(visitor)
+    }
 
     /// Hint that the `Deserialize` type is expecting an optional value.
     fn dyn_deserialize_option(
         &mut self,
         visitor: &mut dyn Visitor<'de>,
-    ) -> InplaceDeserializeResult<()>;
+    ) -> InplaceDeserializeResult<()> {
+        self.dyn_deserialize_any(visitor)
+    }
 
     /// Hint that the `Deserialize` type is expecting a unit value.
     fn dyn_deserialize_unit(
         &mut self,
         visitor: &mut dyn Visitor<'de>,
-    ) -> InplaceDeserializeResult<()>;
+    ) -> InplaceDeserializeResult<()> {
+        self.dyn_deserialize_any(visitor)
+    }
 
     /// Hint that the `Deserialize` type is expecting a unit struct with a
     /// particular name.
@@ -182,7 +479,10 @@ pub trait Deserializer<'de> {
         &mut self,
         name: &'static str,
         visitor: &mut dyn Visitor<'de>,
-    ) -> InplaceDeserializeResult<()>;
+    ) -> InplaceDeserializeResult<()> {
+        let _ = name;
+        self.dyn_deserialize_any(visitor)
+    }
 
     /// Hint that the `Deserialize` type is expecting a newtype struct with a
     /// particular name.
@@ -190,13 +490,18 @@ pub trait Deserializer<'de> {
         &mut self,
         name: &'static str,
         visitor: &mut dyn Visitor<'de>,
-    ) -> InplaceDeserializeResult<()>;
+    ) -> InplaceDeserializeResult<()> {
+        let _ = name;
+        self.dyn_deserialize_any(visitor)
+    }
 
     /// Hint that the `Deserialize` type is expecting a sequence of values.
     fn dyn_deserialize_seq(
         &mut self,
         visitor: &mut dyn Visitor<'de>,
-    ) -> InplaceDeserializeResult<()>;
+    ) -> InplaceDeserializeResult<()> {
+        self.dyn_deserialize_any(visitor)
+    }
 
     /// Hint that the `Deserialize` type is expecting a sequence of values and
     /// knows how many values there are without looking at the serialized data.
@@ -204,7 +509,10 @@ pub trait Deserializer<'de> {
         &mut self,
         len: usize,
         visitor: &mut dyn Visitor<'de>,
-    ) -> InplaceDeserializeResult<()>;
+    ) -> InplaceDeserializeResult<()> {
+        let _ = len;
+        self.dyn_deserialize_any(visitor)
+    }
 
     /// Hint that the `Deserialize` type is expecting a tuple struct with a
     /// particular name and number of fields.
@@ -213,13 +521,19 @@ pub trait Deserializer<'de> {
         name: &'static str,
         len: usize,
         visitor: &mut dyn Visitor<'de>,
-    ) -> InplaceDeserializeResult<()>;
+    ) -> InplaceDeserializeResult<()> {
+        let _ = name;
+        let _ = len;
+        self.dyn_deserialize_any(visitor)
+    }
 
     /// Hint that the `Deserialize` type is expecting a map of key-value pairs.
     fn dyn_deserialize_map(
         &mut self,
         visitor: &mut dyn Visitor<'de>,
-    ) -> InplaceDeserializeResult<()>;
+    ) -> InplaceDeserializeResult<()> {
+        self.dyn_deserialize_any(visitor)
+    }
 
     /// Hint that the `Deserialize` type is expecting a struct with a particular
     /// name and fields.
@@ -228,7 +542,11 @@ pub trait Deserializer<'de> {
         name: &'static str,
         fields: &'static [&'static str],
         visitor: &mut dyn Visitor<'de>,
-    ) -> InplaceDeserializeResult<()>;
+    ) -> InplaceDeserializeResult<()> {
+        let _ = name;
+        let _ = fields;
+        self.dyn_deserialize_any(visitor)
+    }
 
     /// Hint that the `Deserialize` type is expecting an enum value with a
     /// particular name and possible variants.
@@ -237,25 +555,37 @@ pub trait Deserializer<'de> {
         name: &'static str,
         variants: &'static [&'static str],
         visitor: &mut dyn Visitor<'de>,
-    ) -> InplaceDeserializeResult<()>;
+    ) -> InplaceDeserializeResult<()> {
+        let _ = name;
+        let _ = variants;
+        self.dyn_deserialize_any(visitor)
+    }
 
     /// Hint that the `Deserialize` type is expecting the name of a struct
     /// field or the discriminant of an enum variant.
     fn dyn_deserialize_identifier(
         &mut self,
         visitor: &mut dyn Visitor<'de>,
-    ) -> InplaceDeserializeResult<()>;
+    ) -> InplaceDeserializeResult<()> {
+        self.dyn_deserialize_any(visitor)
+    }
 
     /// Hint that the `Deserialize` type needs to deserialize a value whose type
     /// doesn't matter because it is ignored.
     fn dyn_deserialize_ignored_any(
         &mut self,
         visitor: &mut dyn Visitor<'de>,
-    ) -> InplaceDeserializeResult<()>;
+    ) -> InplaceDeserializeResult<()> {
+        self.dyn_deserialize_any(visitor)
+    }
 
     /// Determine whether `Deserialize` implementations should expect to
     /// deserialize their human-readable form.
-    fn dyn_is_human_readable(&self) -> bool;
+    ///
+    /// The default implementation returns `true`.
+    fn dyn_is_human_readable(&self) -> bool {
+        true
+    }
 }
 
 impl<'de> dyn Deserializer<'de> + '_ {
@@ -272,7 +602,7 @@ impl<'de> dyn Deserializer<'de> + '_ {
     /// #
     /// let mut deserializer = serde_json::Deserializer::from_str("false");
     /// let mut deserializer = <dyn Deserializer>::new(&mut deserializer);
-    /// let deserializer = &mut deserializer as &mut dyn Deserializer<'_>;
+    /// let deserializer = deserializer.as_mut();
     /// # let _ = deserializer;
     /// ```
     #[must_use]
@@ -380,7 +710,7 @@ pub trait Visitor<'de> {
 
     /// The input contains an optional that is present.
     fn dyn_visit_some(&mut self, deserializer: &mut dyn Deserializer<'de>)
-        -> DeserializeResult<()>;
+    -> DeserializeResult<()>;
 
     /// The input contains a unit `()`.
     fn dyn_visit_unit(&mut self) -> DeserializeResult<()>;
@@ -510,21 +840,39 @@ pub trait VariantAccess<'de> {
 // ----------------------------------------------------------------------------
 /// An implementation of the [`Deserializer`] trait which performs in-place
 /// deserialization.
+///
+/// Each state it moves to once the deserializer is consumed holds whether
+/// the deserializer is human-readable, captured before it is, so that
+/// `dyn_is_human_readable` keeps answering for the format after the end of
+/// the deserialization.
 #[derive(Clone, Debug, Default)]
 pub enum InplaceDeserializer<'de, D: serde::Deserializer<'de>> {
     /// The deserializer is not ready.
     #[default]
     None,
+    /// The deserialization has done successfully.
+    Ok(bool),
     /// The deserialization has done unsuccessfully.
-    Error(D::Error),
+    Error(D::Error, bool),
     /// The deserializer is ready.
     Deserializer(D),
 }
 
 impl<'de, D: serde::Deserializer<'de>> InplaceDeserializer<'de, D> {
+    /// Returns the state of the in-place deserializer, without its content.
+    #[must_use]
+    pub fn state(&self) -> InplaceDeserializerState {
+        match self {
+            InplaceDeserializer::None => InplaceDeserializerState::None,
+            InplaceDeserializer::Ok(_) => InplaceDeserializerState::Value,
+            InplaceDeserializer::Error(..) => InplaceDeserializerState::Error,
+            InplaceDeserializer::Deserializer(_) => InplaceDeserializerState::Deserializer,
+        }
+    }
+
     fn into_result(self, result: DeserializeResult<()>) -> Result<(), D::Error> {
         result.map_err(|error| match self {
-            InplaceDeserializer::Error(error) => error,
+            InplaceDeserializer::Error(error, _) => error,
             _ => error.into_error(),
         })
     }
@@ -533,18 +881,50 @@ impl<'de, D: serde::Deserializer<'de>> InplaceDeserializer<'de, D> {
     where
         F: FnOnce(D) -> Result<(), D::Error>,
     {
-        if let InplaceDeserializer::Deserializer(_) = self {
-            if let InplaceDeserializer::Deserializer(deserializer) = mem::take(self) {
-                return (f)(deserializer).map_err(|error| {
-                    *self = InplaceDeserializer::Error(error);
-                    InplaceDeserializeError::Error
-                });
-            }
+        if let InplaceDeserializer::Deserializer(_) = self
+            && let InplaceDeserializer::Deserializer(deserializer) = mem::take(self)
+        {
+            let human_readable = deserializer.is_human_readable();
+            return match (f)(deserializer) {
+                Ok(()) => {
+                    *self = InplaceDeserializer::Ok(human_readable);
+                    Ok(())
+                }
+                Err(error) => {
+                    *self = InplaceDeserializer::Error(error, human_readable);
+                    Err(InplaceDeserializeError::Error)
+                }
+            };
         }
         Err(InplaceDeserializeError::NotDeserializer)
     }
 }
 
+/// Borrows the in-place deserializer as a [`dyn Deserializer<'de>`], which
+/// saves casting it with `as &mut dyn Deserializer`.
+///
+/// [`dyn Deserializer<'de>`]: Deserializer
+///
+/// # Examples
+///
+/// ```
+/// # use serde::Deserialize as _;
+/// # use dyn_serde::Deserializer;
+/// #
+/// let mut deserializer = serde_json::Deserializer::from_str("[1, 2]");
+/// let mut deserializer = <dyn Deserializer>::new(&mut deserializer);
+/// let value = Vec::<i32>::deserialize(deserializer.as_mut()).unwrap();
+/// assert_eq!(value, [1, 2]);
+/// ```
+impl<'a, 'de: 'a, D> AsMut<dyn Deserializer<'de> + 'a> for InplaceDeserializer<'de, D>
+where
+    D: serde::Deserializer<'de> + 'a,
+{
+    fn as_mut(&mut self) -> &mut (dyn Deserializer<'de> + 'a) {
+        self
+    }
+}
+
 impl<'de, D: serde::Deserializer<'de>> Deserializer<'de> for InplaceDeserializer<'de, D> {
     fn dyn_deserialize_any(
         &mut self,
@@ -773,10 +1153,11 @@ impl<'de, D: serde::Deserializer<'de>> Deserializer<'de> for InplaceDeserializer
     }
 
     fn dyn_is_human_readable(&self) -> bool {
-        if let InplaceDeserializer::Deserializer(deserializer) = self {
-            deserializer.is_human_readable()
-        } else {
-            true
+        match *self {
+            InplaceDeserializer::None => true,
+            InplaceDeserializer::Ok(human_readable)
+            | InplaceDeserializer::Error(_, human_readable) => human_readable,
+            InplaceDeserializer::Deserializer(ref deserializer) => deserializer.is_human_readable(),
         }
     }
 }
@@ -797,6 +1178,16 @@ pub enum InplaceDeserializeSeed<'de, T: serde::de::DeserializeSeed<'de>> {
 }
 
 impl<'de, T: serde::de::DeserializeSeed<'de>> InplaceDeserializeSeed<'de, T> {
+    /// Returns the state of the in-place deserialize seed, without its content.
+    #[must_use]
+    pub fn state(&self) -> InplaceDeserializerState {
+        match self {
+            InplaceDeserializeSeed::None => InplaceDeserializerState::None,
+            InplaceDeserializeSeed::Value(_) => InplaceDeserializerState::Value,
+            InplaceDeserializeSeed::DeserializeSeed(_) => InplaceDeserializerState::DeserializeSeed,
+        }
+    }
+
     fn into_result(self, result: InplaceDeserializeResult<()>) -> DeserializeResult<T::Value> {
         match self {
             InplaceDeserializeSeed::Value(value) => Ok(value),
@@ -831,11 +1222,11 @@ impl<'de, T: serde::de::DeserializeSeed<'de>> DeserializeSeed<'de>
         &mut self,
         deserializer: &mut dyn Deserializer<'de>,
     ) -> DeserializeResult<()> {
-        if let InplaceDeserializeSeed::DeserializeSeed(_) = self {
-            if let InplaceDeserializeSeed::DeserializeSeed(seed) = mem::take(self) {
-                *self = InplaceDeserializeSeed::Value(seed.deserialize(deserializer)?);
-                return Ok(());
-            }
+        if let InplaceDeserializeSeed::DeserializeSeed(_) = self
+            && let InplaceDeserializeSeed::DeserializeSeed(seed) = mem::take(self)
+        {
+            *self = InplaceDeserializeSeed::Value(seed.deserialize(deserializer)?);
+            return Ok(());
         }
         Err(DeserializeError::from(
             InplaceDeserializeError::NotDeserializeSeed,
@@ -847,6 +1238,16 @@ impl<'de, T: serde::de::DeserializeSeed<'de>> DeserializeSeed<'de>
 // ----------------------------------------------------------------------------
 /// An implementation of the [`Visitor`] trait which performs in-place
 /// deserialization.
+///
+/// # Performance
+///
+/// The [`serde::Deserializer`] implementation of `&mut dyn Deserializer`
+/// wraps every visitor into an `InplaceVisitor` on the stack of the call, which
+/// costs no allocation and is inlined with the rest of the generic code of the
+/// caller. There is hence no state worth reusing across calls, even in hot
+/// loops deserializing many primitives: the overhead over a concrete
+/// deserializer is the virtual call of the `dyn_deserialize_*` and
+/// `dyn_visit_*` methods, which `benches/primitives.rs` measures.
 #[derive(Debug, Default)]
 pub enum InplaceVisitor<'de, V: serde::de::Visitor<'de>> {
     /// The visitor is not ready.
@@ -859,6 +1260,16 @@ pub enum InplaceVisitor<'de, V: serde::de::Visitor<'de>> {
 }
 
 impl<'de, V: serde::de::Visitor<'de>> InplaceVisitor<'de, V> {
+    /// Returns the state of the in-place visitor, without its content.
+    #[must_use]
+    pub fn state(&self) -> InplaceDeserializerState {
+        match self {
+            InplaceVisitor::None => InplaceDeserializerState::None,
+            InplaceVisitor::Value(_) => InplaceDeserializerState::Value,
+            InplaceVisitor::Visitor(_) => InplaceDeserializerState::Visitor,
+        }
+    }
+
     fn into_result(self, result: InplaceDeserializeResult<()>) -> DeserializeResult<V::Value> {
         match self {
             InplaceVisitor::Value(value) => Ok(value),
@@ -873,11 +1284,11 @@ impl<'de, V: serde::de::Visitor<'de>> InplaceVisitor<'de, V> {
     where
         F: FnOnce(V) -> DeserializeResult<V::Value>,
     {
-        if let InplaceVisitor::Visitor(_) = self {
-            if let InplaceVisitor::Visitor(visitor) = mem::take(self) {
-                *self = InplaceVisitor::Value((f)(visitor)?);
-                return Ok(());
-            }
+        if let InplaceVisitor::Visitor(_) = self
+            && let InplaceVisitor::Visitor(visitor) = mem::take(self)
+        {
+            *self = InplaceVisitor::Value((f)(visitor)?);
+            return Ok(());
         }
         Err(DeserializeError::from(InplaceDeserializeError::NotVisitor))
     }
@@ -1013,15 +1424,93 @@ impl<'de, V: serde::de::Visitor<'de>> Visitor<'de> for InplaceVisitor<'de, V> {
 // ----------------------------------------------------------------------------
 /// An implementation of the [`SeqAccess`] trait which performs in-place
 /// deserialization.
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Default)]
 pub enum InplaceSeqAccess<'de, A: serde::de::SeqAccess<'de>> {
-    /// The deserialization has done successfully.
+    /// The in-place access is not ready, e.g. once its error is taken.
+    #[default]
+    None,
+    /// The deserialization has done unsuccessfully.
     Error(A::Error),
     /// The deserializer is ready to deserialize the sequence.
     SeqAccess(A),
 }
 
 impl<'de, A: serde::de::SeqAccess<'de>> InplaceSeqAccess<'de, A> {
+    /// Wraps `access`, ready to deserialize the sequence.
+    ///
+    /// This is a shorthand for [`InplaceSeqAccess::SeqAccess`], e.g. to
+    /// hand the access given to a [`serde::de::Visitor`] to dynamic code.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use serde::de::SeqAccess as _;
+    /// # use serde::de::value::{Error, SeqDeserializer};
+    /// # use dyn_serde::de::{InplaceSeqAccess, SeqAccess};
+    /// #
+    /// let access = SeqDeserializer::<_, Error>::new([1, 2].into_iter());
+    /// let mut access = InplaceSeqAccess::new(access);
+    /// let mut access = &mut access as &mut dyn SeqAccess;
+    /// assert_eq!(access.next_element::<i32>().unwrap(), Some(1));
+    /// ```
+    #[must_use]
+    pub fn new(access: A) -> Self {
+        InplaceSeqAccess::SeqAccess(access)
+    }
+
+    /// Returns the error of the wrapped access if the deserialization has
+    /// done unsuccessfully, or the in-place access itself otherwise.
+    ///
+    /// The dynamic methods only tell that an element has failed, while the
+    /// error the wrapped access has raised, with its own type, is kept in the
+    /// in-place access until it is taken this way.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use serde::de::SeqAccess as _;
+    /// # use serde::de::value::{Error, SeqDeserializer};
+    /// # use dyn_serde::de::{InplaceSeqAccess, SeqAccess};
+    /// #
+    /// let access = SeqDeserializer::<_, Error>::new([1].into_iter());
+    /// let mut access = InplaceSeqAccess::new(access);
+    /// let mut dyn_access = &mut access as &mut dyn SeqAccess;
+    /// assert!(dyn_access.next_element::<String>().is_err());
+    ///
+    /// let error = access.try_into_error().unwrap();
+    /// assert_eq!(error.to_string(), "invalid type: integer `1`, expected a string");
+    /// ```
+    pub fn try_into_error(self) -> Result<A::Error, Self> {
+        match self {
+            InplaceSeqAccess::Error(error) => Ok(error),
+            access => Err(access),
+        }
+    }
+
+    /// Takes the error of the wrapped access if the deserialization has done
+    /// unsuccessfully, leaving the in-place access not ready.
+    ///
+    /// This is [`InplaceSeqAccess::try_into_error`] for an access which is only
+    /// borrowed, e.g. by the visitor it was handed to.
+    pub fn take_error(&mut self) -> Option<A::Error> {
+        if let InplaceSeqAccess::Error(_) = self
+            && let InplaceSeqAccess::Error(error) = mem::take(self)
+        {
+            return Some(error);
+        }
+        None
+    }
+
+    /// Returns the state of the in-place access, without its content.
+    #[must_use]
+    pub fn state(&self) -> InplaceDeserializerState {
+        match self {
+            InplaceSeqAccess::None => InplaceDeserializerState::None,
+            InplaceSeqAccess::Error(_) => InplaceDeserializerState::Error,
+            InplaceSeqAccess::SeqAccess(_) => InplaceDeserializerState::SeqAccess,
+        }
+    }
+
     fn into_result(self, result: DeserializeResult<()>) -> Result<(), A::Error> {
         result.map_err(|error| match self {
             InplaceSeqAccess::Error(error) => error,
@@ -1065,15 +1554,93 @@ impl<'de, A: serde::de::SeqAccess<'de>> SeqAccess<'de> for InplaceSeqAccess<'de,
 // ----------------------------------------------------------------------------
 /// An implementation of the [`MapAccess`] trait which performs in-place
 /// deserialization.
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Default)]
 pub enum InplaceMapAccess<'de, A: serde::de::MapAccess<'de>> {
-    /// The deserialization has done successfully.
+    /// The in-place access is not ready, e.g. once its error is taken.
+    #[default]
+    None,
+    /// The deserialization has done unsuccessfully.
     Error(A::Error),
     /// The deserializer is ready to deserialize the map.
     MapAccess(A),
 }
 
 impl<'de, A: serde::de::MapAccess<'de>> InplaceMapAccess<'de, A> {
+    /// Wraps `access`, ready to deserialize the map.
+    ///
+    /// This is a shorthand for [`InplaceMapAccess::MapAccess`], e.g. to
+    /// hand the access given to a [`serde::de::Visitor`] to dynamic code.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use serde::de::MapAccess as _;
+    /// # use serde::de::value::{Error, MapDeserializer};
+    /// # use dyn_serde::de::{InplaceMapAccess, MapAccess};
+    /// #
+    /// let access = MapDeserializer::<_, Error>::new([("a", 1)].into_iter());
+    /// let mut access = InplaceMapAccess::new(access);
+    /// let mut access = &mut access as &mut dyn MapAccess;
+    /// assert_eq!(access.next_entry::<String, i32>().unwrap(), Some(("a".into(), 1)));
+    /// ```
+    #[must_use]
+    pub fn new(access: A) -> Self {
+        InplaceMapAccess::MapAccess(access)
+    }
+
+    /// Returns the error of the wrapped access if the deserialization has
+    /// done unsuccessfully, or the in-place access itself otherwise.
+    ///
+    /// The dynamic methods only tell that an element has failed, while the
+    /// error the wrapped access has raised, with its own type, is kept in the
+    /// in-place access until it is taken this way.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use serde::de::MapAccess as _;
+    /// # use serde::de::value::{Error, MapDeserializer};
+    /// # use dyn_serde::de::{InplaceMapAccess, MapAccess};
+    /// #
+    /// let access = MapDeserializer::<_, Error>::new([("a", 1)].into_iter());
+    /// let mut access = InplaceMapAccess::new(access);
+    /// let mut dyn_access = &mut access as &mut dyn MapAccess;
+    /// assert!(dyn_access.next_key::<i32>().is_err());
+    ///
+    /// let error = access.try_into_error().unwrap();
+    /// assert_eq!(error.to_string(), "invalid type: string \"a\", expected i32");
+    /// ```
+    pub fn try_into_error(self) -> Result<A::Error, Self> {
+        match self {
+            InplaceMapAccess::Error(error) => Ok(error),
+            access => Err(access),
+        }
+    }
+
+    /// Takes the error of the wrapped access if the deserialization has done
+    /// unsuccessfully, leaving the in-place access not ready.
+    ///
+    /// This is [`InplaceMapAccess::try_into_error`] for an access which is only
+    /// borrowed, e.g. by the visitor it was handed to.
+    pub fn take_error(&mut self) -> Option<A::Error> {
+        if let InplaceMapAccess::Error(_) = self
+            && let InplaceMapAccess::Error(error) = mem::take(self)
+        {
+            return Some(error);
+        }
+        None
+    }
+
+    /// Returns the state of the in-place access, without its content.
+    #[must_use]
+    pub fn state(&self) -> InplaceDeserializerState {
+        match self {
+            InplaceMapAccess::None => InplaceDeserializerState::None,
+            InplaceMapAccess::Error(_) => InplaceDeserializerState::Error,
+            InplaceMapAccess::MapAccess(_) => InplaceDeserializerState::MapAccess,
+        }
+    }
+
     fn into_result(self, result: DeserializeResult<()>) -> Result<(), A::Error> {
         result.map_err(|error| match self {
             InplaceMapAccess::Error(error) => error,
@@ -1146,6 +1713,17 @@ pub enum InplaceEnumAccess<'de, A: serde::de::EnumAccess<'de>> {
 }
 
 impl<'de, A: serde::de::EnumAccess<'de>> InplaceEnumAccess<'de, A> {
+    /// Returns the state of the in-place access, without its content.
+    #[must_use]
+    pub fn state(&self) -> InplaceDeserializerState {
+        match self {
+            InplaceEnumAccess::None => InplaceDeserializerState::None,
+            InplaceEnumAccess::Error(_) => InplaceDeserializerState::Error,
+            InplaceEnumAccess::EnumAccess(_) => InplaceDeserializerState::EnumAccess,
+            InplaceEnumAccess::VariantAccess(_) => InplaceDeserializerState::VariantAccess,
+        }
+    }
+
     fn into_result(self, result: DeserializeResult<()>) -> Result<(), A::Error> {
         result.map_err(|error| match self {
             InplaceEnumAccess::Error(error) => error,
@@ -1157,13 +1735,13 @@ impl<'de, A: serde::de::EnumAccess<'de>> InplaceEnumAccess<'de, A> {
     where
         F: FnOnce(A::Variant) -> Result<(), A::Error>,
     {
-        if let InplaceEnumAccess::VariantAccess(_) = self {
-            if let InplaceEnumAccess::VariantAccess(access) = mem::take(self) {
-                return (f)(access).map_err(|error| {
-                    *self = InplaceEnumAccess::Error(error);
-                    InplaceDeserializeError::Error
-                });
-            }
+        if let InplaceEnumAccess::VariantAccess(_) = self
+            && let InplaceEnumAccess::VariantAccess(access) = mem::take(self)
+        {
+            return (f)(access).map_err(|error| {
+                *self = InplaceEnumAccess::Error(error);
+                InplaceDeserializeError::Error
+            });
         }
         Err(InplaceDeserializeError::NotVariantAccess)
     }
@@ -1174,19 +1752,19 @@ impl<'de, A: serde::de::EnumAccess<'de>> EnumAccess<'de> for InplaceEnumAccess<'
         &mut self,
         seed: &mut dyn DeserializeSeed<'de>,
     ) -> InplaceDeserializeResult<&mut dyn VariantAccess<'de>> {
-        if let InplaceEnumAccess::EnumAccess(_) = self {
-            if let InplaceEnumAccess::EnumAccess(access) = mem::take(self) {
-                return match access.variant_seed(seed) {
-                    Ok((_, variant)) => {
-                        *self = InplaceEnumAccess::VariantAccess(variant);
-                        Ok(self)
-                    }
-                    Err(error) => {
-                        *self = InplaceEnumAccess::Error(error);
-                        Err(InplaceDeserializeError::Error)
-                    }
-                };
-            }
+        if let InplaceEnumAccess::EnumAccess(_) = self
+            && let InplaceEnumAccess::EnumAccess(access) = mem::take(self)
+        {
+            return match access.variant_seed(seed) {
+                Ok((_, variant)) => {
+                    *self = InplaceEnumAccess::VariantAccess(variant);
+                    Ok(self)
+                }
+                Err(error) => {
+                    *self = InplaceEnumAccess::Error(error);
+                    Err(InplaceDeserializeError::Error)
+                }
+            };
         }
         Err(InplaceDeserializeError::NotEnumAccess)
     }
@@ -1221,6 +1799,42 @@ impl<'de, A: serde::de::EnumAccess<'de>> VariantAccess<'de> for InplaceEnumAcces
     }
 }
 
+/// The state of an in-place deserialization type, such as
+/// [`InplaceDeserializer`] or [`InplaceVisitor`], without its content.
+///
+/// It is returned by the `state` method of the in-place type to tell which
+/// state it is in, e.g. when it is not ready for a call, see
+/// [`InplaceDeserializeError::in_state`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum InplaceDeserializerState {
+    /// The in-place type is not ready.
+    None,
+    /// The deserialization has done successfully.
+    Value,
+    /// The deserialization has done unsuccessfully.
+    Error,
+    /// The deserializer is ready.
+    Deserializer,
+    /// The deserialize seed is ready.
+    DeserializeSeed,
+    /// The visitor is ready.
+    Visitor,
+    /// The deserializer is ready to deserialize the sequence.
+    SeqAccess,
+    /// The deserializer is ready to deserialize the map.
+    MapAccess,
+    /// The deserializer is ready to deserialize the enum.
+    EnumAccess,
+    /// The deserializer is ready to deserialize the enum variant.
+    VariantAccess,
+}
+
+impl Display for InplaceDeserializerState {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        Debug::fmt(self, f)
+    }
+}
+
 /// An error returned by [`InplaceDeserializer`] when the in-place deserialization
 /// has done unsuccessfully.
 #[repr(u8)]
@@ -1246,6 +1860,59 @@ pub enum InplaceDeserializeError {
     NotVariantAccess,
 }
 
+impl InplaceDeserializeError {
+    /// Returns the state the in-place type has to be in for the failed call,
+    /// if it is not ready.
+    #[must_use]
+    pub fn expected(&self) -> Option<InplaceDeserializerState> {
+        match self {
+            InplaceDeserializeError::Ok | InplaceDeserializeError::Error => None,
+            InplaceDeserializeError::NotDeserializer => {
+                Some(InplaceDeserializerState::Deserializer)
+            }
+            InplaceDeserializeError::NotDeserializeSeed => {
+                Some(InplaceDeserializerState::DeserializeSeed)
+            }
+            InplaceDeserializeError::NotVisitor => Some(InplaceDeserializerState::Visitor),
+            InplaceDeserializeError::NotSeqAccess => Some(InplaceDeserializerState::SeqAccess),
+            InplaceDeserializeError::NotMapAccess => Some(InplaceDeserializerState::MapAccess),
+            InplaceDeserializeError::NotEnumAccess => Some(InplaceDeserializerState::EnumAccess),
+            InplaceDeserializeError::NotVariantAccess => {
+                Some(InplaceDeserializerState::VariantAccess)
+            }
+        }
+    }
+
+    /// Returns a value displaying the error along with the `state` the
+    /// in-place type is actually in, as returned by its `state` method after
+    /// the failed call.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use dyn_serde::Deserializer;
+    /// # use dyn_serde::de::InplaceVisitor;
+    /// #
+    /// let mut deserializer = serde_json::Deserializer::from_str("true");
+    /// let mut deserializer = <dyn Deserializer>::new(&mut deserializer);
+    /// let mut visitor = InplaceVisitor::Visitor(serde::de::IgnoredAny);
+    /// deserializer.dyn_deserialize_any(&mut visitor).unwrap();
+    ///
+    /// let mut visitor = InplaceVisitor::Visitor(serde::de::IgnoredAny);
+    /// let error = deserializer.dyn_deserialize_any(&mut visitor).unwrap_err();
+    /// assert_eq!(
+    ///     error.in_state(deserializer.state()).to_string(),
+    ///     "the deserializer is not ready: expected Deserializer but it is in Value state",
+    /// );
+    /// ```
+    pub fn in_state(self, state: InplaceDeserializerState) -> impl Display {
+        fmt::from_fn(move |f| match self.expected() {
+            Some(expected) => write!(f, "{self}: expected {expected} but it is in {state} state"),
+            None => Display::fmt(&self, f),
+        })
+    }
+}
+
 impl Display for InplaceDeserializeError {
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
         f.write_str(match self {
@@ -1344,7 +2011,7 @@ impl<'de> serde::Deserializer<'de> for &mut (dyn Deserializer<'de> + '_) {
         V: serde::de::Visitor<'de>,
     {
         let mut visitor = InplaceVisitor::Visitor(visitor);
-        let result = self.dyn_deserialize_any(&mut visitor);
+        let result = self.dyn_deserialize_bool(&mut visitor);
         visitor.into_result(result)
     }
 
@@ -1384,6 +2051,15 @@ impl<'de> serde::Deserializer<'de> for &mut (dyn Deserializer<'de> + '_) {
         visitor.into_result(result)
     }
 
+    fn deserialize_i128<V>(self, visitor: V) -> DeserializeResult<V::Value>
+    where
+        V: serde::de::Visitor<'de>,
+    {
+        let mut visitor = InplaceVisitor::Visitor(visitor);
+        let result = self.dyn_deserialize_i128(&mut visitor);
+        visitor.into_result(result)
+    }
+
     fn deserialize_u8<V>(self, visitor: V) -> DeserializeResult<V::Value>
     where
         V: serde::de::Visitor<'de>,
@@ -1420,6 +2096,15 @@ impl<'de> serde::Deserializer<'de> for &mut (dyn Deserializer<'de> + '_) {
         visitor.into_result(result)
     }
 
+    fn deserialize_u128<V>(self, visitor: V) -> DeserializeResult<V::Value>
+    where
+        V: serde::de::Visitor<'de>,
+    {
+        let mut visitor = InplaceVisitor::Visitor(visitor);
+        let result = self.dyn_deserialize_u128(&mut visitor);
+        visitor.into_result(result)
+    }
+
     fn deserialize_f32<V>(self, visitor: V) -> DeserializeResult<V::Value>
     where
         V: serde::de::Visitor<'de>,
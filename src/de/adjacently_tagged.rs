@@ -0,0 +1,317 @@
+//! A [`Deserializer`] wrapper reading adjacently tagged enums, see
+//! [`AdjacentlyTagged`].
+
+use core::cell::Cell;
+use core::fmt::{self, Debug, Formatter};
+
+use serde::Deserialize as _;
+use serde::de::{Error as _, IgnoredAny, MapAccess as _};
+use serde_json::Value;
+
+use crate::de::middleware::{self, Hint, Middleware, VariantCall, Visit};
+use crate::de::untag_structs::KeyVisitor;
+use crate::de::{DeserializeError, DeserializeResult, DeserializeSeed, Deserializer};
+use crate::de::{EnumAccess, InplaceDeserializeError, InplaceDeserializeResult};
+use crate::de::{InplaceDeserializer, MapAccess, VariantAccess, Visitor};
+
+/// A [`Deserializer`] wrapper which reads every enum from a map of two
+/// entries, the name of the variant and its content, e.g.
+/// `{"t": "Move", "c": {"x": 1}}`, whatever the representation of the enum.
+///
+/// This is the counterpart of [`ser::AdjacentlyTagged`], reading adjacently
+/// tagged input into enums deriving `Deserialize` without
+/// `#[serde(tag = "t", content = "c")]`. Unit variants may omit the content
+/// entry, newtype variants read their value from it, tuple variants a
+/// sequence of their fields, and struct variants a map of their fields. The
+/// same applies to the enums at every depth of the value. Entries whose key is
+/// neither the tag nor the content are skipped.
+///
+/// The content is read as it comes when the tag precedes it. Otherwise it is
+/// buffered into a [`serde_json::Value`] until the tag is found, with the same
+/// losses as [`MapDeserializer`].
+///
+/// [`ser::AdjacentlyTagged`]: crate::ser::AdjacentlyTagged
+/// [`MapDeserializer`]: crate::de::MapDeserializer
+///
+/// # Examples
+///
+/// ```
+/// # use serde::Deserialize as _;
+/// # use dyn_serde::Deserializer;
+/// # use dyn_serde::de::AdjacentlyTagged;
+/// #
+/// #[derive(Debug, PartialEq, serde::Deserialize)]
+/// enum Command {
+///     Stop,
+///     Move { x: i32 },
+/// }
+///
+/// let input = r#"[{"t": "Move", "c": {"x": 1}}, {"t": "Stop"}]"#;
+/// let mut deserializer = serde_json::Deserializer::from_str(input);
+/// let mut deserializer = <dyn Deserializer>::new(&mut deserializer);
+/// let mut deserializer = AdjacentlyTagged::new(&mut deserializer, "t", "c");
+///
+/// let commands = Vec::<Command>::deserialize(&mut deserializer as &mut dyn Deserializer).unwrap();
+/// assert_eq!(commands, [Command::Move { x: 1 }, Command::Stop]);
+/// ```
+pub struct AdjacentlyTagged<'a, 'de> {
+    deserializer: &'a mut dyn Deserializer<'de>,
+    adjacent: Adjacent,
+}
+
+impl<'a, 'de> AdjacentlyTagged<'a, 'de> {
+    /// Wraps `deserializer`, reading the name of every variant from the entry
+    /// `tag` and its content from the entry `content`.
+    #[must_use]
+    pub fn new(
+        deserializer: &'a mut dyn Deserializer<'de>,
+        tag: &'static str,
+        content: &'static str,
+    ) -> Self {
+        AdjacentlyTagged {
+            deserializer,
+            adjacent: Adjacent {
+                tag,
+                content,
+                expecting_enum: Cell::new(None),
+            },
+        }
+    }
+
+    fn deserialize_hint(
+        &mut self,
+        hint: Hint,
+        visitor: &mut dyn Visitor<'de>,
+    ) -> InplaceDeserializeResult<()> {
+        middleware::deserialize(&self.adjacent, hint, self.deserializer, visitor)
+    }
+}
+
+impl Debug for AdjacentlyTagged<'_, '_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("AdjacentlyTagged")
+            .field("tag", &self.adjacent.tag)
+            .field("content", &self.adjacent.content)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<'de> Deserializer<'de> for AdjacentlyTagged<'_, 'de> {
+    middleware::forward_to_deserialize_hint!();
+
+    fn dyn_is_human_readable(&self) -> bool {
+        self.deserializer.dyn_is_human_readable()
+    }
+}
+
+struct Adjacent {
+    tag: &'static str,
+    content: &'static str,
+    /// The name of the enum, if the next visit is the one of an enum.
+    expecting_enum: Cell<Option<&'static str>>,
+}
+
+impl<'de> Middleware<'de> for Adjacent {
+    fn deserialize(
+        &self,
+        hint: Hint,
+        deserializer: &mut dyn Deserializer<'de>,
+        visitor: &mut dyn Visitor<'de>,
+    ) -> InplaceDeserializeResult<()> {
+        if let Hint::Enum(name, _) = hint {
+            self.expecting_enum.set(Some(name));
+            return Hint::Map.forward(deserializer, visitor);
+        }
+        self.expecting_enum.set(None);
+        hint.forward(deserializer, visitor)
+    }
+
+    fn visit(
+        &self,
+        visit: Visit<'_, 'de>,
+        visitor: &mut dyn Visitor<'de>,
+    ) -> DeserializeResult<()> {
+        match (visit, self.expecting_enum.take()) {
+            (Visit::Map(access), Some(name)) => {
+                let mut access = AdjacentEnumAccess {
+                    adjacent: self,
+                    name,
+                    inner: access,
+                    content: None,
+                    error: None,
+                };
+                visitor
+                    .dyn_visit_enum(&mut access)
+                    .map_err(|error| access.error.take().unwrap_or(error))
+            }
+            (visit, _) => visit.forward(visitor),
+        }
+    }
+}
+
+/// The map of an enum, read as its variant.
+struct AdjacentEnumAccess<'a, 'de> {
+    adjacent: &'a Adjacent,
+    name: &'static str,
+    inner: &'a mut dyn MapAccess<'de>,
+    /// The content read before the tag, if any.
+    content: Option<Value>,
+    /// The error raised while reading the entries, if any.
+    error: Option<DeserializeError>,
+}
+
+impl<'de> AdjacentEnumAccess<'_, 'de> {
+    /// Reads the entries up to the tag, buffering the content if it comes
+    /// first, and hands the name of the variant to `seed`.
+    fn variant(&mut self, seed: &mut dyn DeserializeSeed<'de>) -> DeserializeResult<()> {
+        let Adjacent { tag, content, .. } = *self.adjacent;
+        loop {
+            let Some(key) = self.inner.next_key_seed(KeyVisitor)? else {
+                return Err(DeserializeError::missing_field(tag));
+            };
+            if key.is(tag) {
+                let variant = self.inner.next_value_seed(KeyVisitor)?;
+                return variant.deserialize(seed);
+            }
+            if key.is(content) && self.content.is_none() {
+                self.content = Some(self.inner.next_value()?);
+            } else {
+                self.inner.next_value::<IgnoredAny>()?;
+            }
+        }
+    }
+
+    /// Reads the content with `call`, then the remaining entries.
+    fn content(&mut self, call: VariantCall<'_, 'de>) -> DeserializeResult<()> {
+        let mut seed = ContentSeed {
+            name: self.name,
+            call: Some(call),
+        };
+        let mut found = false;
+        if let Some(value) = self.content.take() {
+            let mut inner = InplaceDeserializer::Deserializer(value);
+            let result = seed.dyn_deserialize(&mut Through {
+                adjacent: self.adjacent,
+                inner: &mut inner,
+            });
+            inner
+                .into_result(result)
+                .map_err(DeserializeError::custom)?;
+            found = true;
+        }
+
+        let content = self.adjacent.content;
+        while let Some(key) = self.inner.next_key_seed(KeyVisitor)? {
+            if key.is(content) && !found {
+                self.inner.dyn_next_value(&mut seed)?;
+                found = true;
+            } else {
+                self.inner.next_value::<IgnoredAny>()?;
+            }
+        }
+        match seed.call {
+            None | Some(VariantCall::Unit) => Ok(()),
+            Some(_) => Err(DeserializeError::missing_field(content)),
+        }
+    }
+
+    fn catch<T>(&mut self, result: DeserializeResult<T>) -> InplaceDeserializeResult<T> {
+        result.map_err(|error| {
+            self.error.get_or_insert(error);
+            InplaceDeserializeError::Error
+        })
+    }
+}
+
+impl<'de> EnumAccess<'de> for AdjacentEnumAccess<'_, 'de> {
+    fn dyn_variant(
+        &mut self,
+        seed: &mut dyn DeserializeSeed<'de>,
+    ) -> InplaceDeserializeResult<&mut dyn VariantAccess<'de>> {
+        let result = self.variant(seed);
+        self.catch(result)?;
+        Ok(self)
+    }
+}
+
+impl<'de> VariantAccess<'de> for AdjacentEnumAccess<'_, 'de> {
+    fn dyn_unit_variant(&mut self) -> InplaceDeserializeResult<()> {
+        let result = self.content(VariantCall::Unit);
+        self.catch(result)
+    }
+
+    fn dyn_newtype_variant(
+        &mut self,
+        seed: &mut dyn DeserializeSeed<'de>,
+    ) -> InplaceDeserializeResult<()> {
+        let result = self.content(VariantCall::Newtype(seed));
+        self.catch(result)
+    }
+
+    fn dyn_tuple_variant(
+        &mut self,
+        len: usize,
+        visitor: &mut dyn Visitor<'de>,
+    ) -> InplaceDeserializeResult<()> {
+        let result = self.content(VariantCall::Tuple(len, visitor));
+        self.catch(result)
+    }
+
+    fn dyn_struct_variant(
+        &mut self,
+        fields: &'static [&'static str],
+        visitor: &mut dyn Visitor<'de>,
+    ) -> InplaceDeserializeResult<()> {
+        let result = self.content(VariantCall::Struct(fields, visitor));
+        self.catch(result)
+    }
+}
+
+/// Deserializes the content of a variant, consuming the call once done.
+struct ContentSeed<'a, 'de> {
+    /// The name of the enum.
+    name: &'static str,
+    call: Option<VariantCall<'a, 'de>>,
+}
+
+impl<'de> DeserializeSeed<'de> for ContentSeed<'_, 'de> {
+    fn dyn_deserialize(
+        &mut self,
+        deserializer: &mut dyn Deserializer<'de>,
+    ) -> DeserializeResult<()> {
+        match self.call.take() {
+            None | Some(VariantCall::Unit) => IgnoredAny::deserialize(deserializer).map(|_| ()),
+            Some(VariantCall::Newtype(seed)) => seed.dyn_deserialize(deserializer),
+            Some(VariantCall::Tuple(len, visitor)) => {
+                Ok(deserializer.dyn_deserialize_tuple(len, visitor)?)
+            }
+            Some(VariantCall::Struct(fields, visitor)) => {
+                Ok(deserializer.dyn_deserialize_struct(self.name, fields, visitor)?)
+            }
+        }
+    }
+}
+
+/// A buffered content deserialized through the middleware.
+struct Through<'a, 'de> {
+    adjacent: &'a Adjacent,
+    inner: &'a mut dyn Deserializer<'de>,
+}
+
+impl<'de> Through<'_, 'de> {
+    fn deserialize_hint(
+        &mut self,
+        hint: Hint,
+        visitor: &mut dyn Visitor<'de>,
+    ) -> InplaceDeserializeResult<()> {
+        middleware::deserialize(self.adjacent, hint, self.inner, visitor)
+    }
+}
+
+impl<'de> Deserializer<'de> for Through<'_, 'de> {
+    middleware::forward_to_deserialize_hint!();
+
+    fn dyn_is_human_readable(&self) -> bool {
+        self.inner.dyn_is_human_readable()
+    }
+}
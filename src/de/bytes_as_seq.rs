@@ -0,0 +1,141 @@
+//! A [`Deserializer`] wrapper reading byte arrays from sequences, see
+//! [`BytesAsSeq`].
+
+use core::cell::Cell;
+use core::fmt::{self, Debug, Formatter};
+
+#[cfg(not(feature = "std"))]
+use alloc::vec::Vec;
+
+use serde::de::SeqAccess as _;
+
+use crate::de::middleware::{self, Hint, Middleware, Visit};
+use crate::de::{DeserializeResult, Deserializer, InplaceDeserializeResult, SeqAccess, Visitor};
+
+/// A [`Deserializer`] wrapper which accepts a sequence of `u8` where bytes are
+/// expected, e.g. the array of numbers `serde_json` writes for
+/// `serialize_bytes`.
+///
+/// When `dyn_deserialize_bytes` or `dyn_deserialize_byte_buf` is called and
+/// the wrapped deserializer visits a sequence, its elements are deserialized
+/// as `u8` and handed to the visitor at once with `dyn_visit_byte_buf`. Any
+/// other value, including byte arrays supported natively by the format, is
+/// visited unchanged. The same applies at every depth of the value.
+///
+/// # Examples
+///
+/// ```
+/// # use serde::Deserialize as _;
+/// # use dyn_serde::Deserializer;
+/// # use dyn_serde::de::BytesAsSeq;
+/// #
+/// struct Bytes(Vec<u8>);
+///
+/// impl<'de> serde::Deserialize<'de> for Bytes {
+///     fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+///         struct BytesVisitor;
+///
+///         impl serde::de::Visitor<'_> for BytesVisitor {
+///             type Value = Bytes;
+///
+///             fn expecting(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+///                 f.write_str("bytes")
+///             }
+///
+///             fn visit_byte_buf<E>(self, v: Vec<u8>) -> Result<Bytes, E> {
+///                 Ok(Bytes(v))
+///             }
+///         }
+///
+///         deserializer.deserialize_byte_buf(BytesVisitor)
+///     }
+/// }
+///
+/// let mut deserializer = serde_json::Deserializer::from_str("[1, 2, 3]");
+/// let mut deserializer = <dyn Deserializer>::new(&mut deserializer);
+/// let mut deserializer = BytesAsSeq::new(&mut deserializer);
+///
+/// let bytes = Bytes::deserialize(&mut deserializer as &mut dyn Deserializer).unwrap();
+/// assert_eq!(bytes.0, [1, 2, 3]);
+/// ```
+pub struct BytesAsSeq<'a, 'de> {
+    deserializer: &'a mut dyn Deserializer<'de>,
+    middleware: SeqToBytes,
+}
+
+impl<'a, 'de> BytesAsSeq<'a, 'de> {
+    /// Wraps `deserializer`, reading byte arrays from sequences of `u8`.
+    #[must_use]
+    pub fn new(deserializer: &'a mut dyn Deserializer<'de>) -> Self {
+        BytesAsSeq {
+            deserializer,
+            middleware: SeqToBytes {
+                expecting_bytes: Cell::new(false),
+            },
+        }
+    }
+
+    fn deserialize_hint(
+        &mut self,
+        hint: Hint,
+        visitor: &mut dyn Visitor<'de>,
+    ) -> InplaceDeserializeResult<()> {
+        middleware::deserialize(&self.middleware, hint, self.deserializer, visitor)
+    }
+}
+
+impl Debug for BytesAsSeq<'_, '_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("BytesAsSeq").finish_non_exhaustive()
+    }
+}
+
+impl<'de> Deserializer<'de> for BytesAsSeq<'_, 'de> {
+    middleware::forward_to_deserialize_hint!();
+
+    fn dyn_is_human_readable(&self) -> bool {
+        self.deserializer.dyn_is_human_readable()
+    }
+}
+
+struct SeqToBytes {
+    /// Whether the next visit is the one of a value deserialized as bytes.
+    expecting_bytes: Cell<bool>,
+}
+
+impl<'de> Middleware<'de> for SeqToBytes {
+    fn deserialize(
+        &self,
+        hint: Hint,
+        deserializer: &mut dyn Deserializer<'de>,
+        visitor: &mut dyn Visitor<'de>,
+    ) -> InplaceDeserializeResult<()> {
+        self.expecting_bytes
+            .set(matches!(hint, Hint::Bytes | Hint::ByteBuf));
+        hint.forward(deserializer, visitor)
+    }
+
+    fn visit(
+        &self,
+        visit: Visit<'_, 'de>,
+        visitor: &mut dyn Visitor<'de>,
+    ) -> DeserializeResult<()> {
+        match visit {
+            Visit::Seq(access) if self.expecting_bytes.replace(false) => {
+                visitor.dyn_visit_byte_buf(collect_bytes(access)?)
+            }
+            visit => {
+                self.expecting_bytes.set(false);
+                visit.forward(visitor)
+            }
+        }
+    }
+}
+
+fn collect_bytes<'de>(mut access: &mut dyn SeqAccess<'de>) -> DeserializeResult<Vec<u8>> {
+    let mut bytes = Vec::with_capacity(access.size_hint().unwrap_or(0).min(4096));
+    while let Some(byte) = access.next_element::<u8>()? {
+        bytes.push(byte);
+    }
+    Ok(bytes)
+}
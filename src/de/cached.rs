@@ -0,0 +1,114 @@
+//! A value parsed once and deserialized many times, see [`Cached`].
+
+use core::fmt::{self, Debug, Formatter};
+
+use serde::Deserialize as _;
+use serde::de::Error as _;
+use serde_json::Value;
+
+use crate::de::{DeserializeError, DeserializeResult, Deserializer};
+use crate::de::{JsonValueDeserializer, from_json_value};
+
+/// A value read once from a [`Deserializer`], which can then be deserialized
+/// into any number of types without parsing the input again.
+///
+/// Reading the same input into several types, e.g. a header first to decide
+/// which body type to read, otherwise parses the format once per type. The
+/// value is captured into a [`serde_json::Value`] instead, trading memory for
+/// parse cost, and every deserialization walks the value directly with
+/// [`from_json_value`]. The value is read with `deserialize_any`, so the
+/// wrapped format must be self-describing.
+///
+/// As the value goes through the data model of JSON, bytes are read as
+/// sequences of numbers. Strings are borrowed from the cache rather than from
+/// the input.
+///
+/// # Examples
+///
+/// ```
+/// # use dyn_serde::Deserializer;
+/// # use dyn_serde::de::Cached;
+/// #
+/// #[derive(serde::Deserialize)]
+/// struct Header<'a> {
+///     kind: &'a str,
+/// }
+///
+/// #[derive(serde::Deserialize)]
+/// struct Ping {
+///     seq: u32,
+/// }
+///
+/// let mut deserializer = serde_json::Deserializer::from_str(r#"{"kind": "ping", "seq": 7}"#);
+/// let mut deserializer = <dyn Deserializer>::new(&mut deserializer);
+/// let cached = Cached::new(&mut deserializer).unwrap();
+///
+/// let header: Header = cached.deserialize().unwrap();
+/// assert_eq!(header.kind, "ping");
+/// let ping: Ping = cached.deserialize().unwrap();
+/// assert_eq!(ping.seq, 7);
+/// ```
+#[derive(Clone, PartialEq)]
+pub struct Cached {
+    value: Value,
+}
+
+impl Cached {
+    /// Reads the next value of `deserializer` into the cache.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the value fails to deserialize, e.g. if the format
+    /// is not self-describing.
+    pub fn new(deserializer: &mut dyn Deserializer<'_>) -> DeserializeResult<Self> {
+        Ok(Cached::from_value(Value::deserialize(deserializer)?))
+    }
+
+    /// Caches a value which has already been read.
+    #[must_use]
+    pub fn from_value(value: Value) -> Self {
+        Cached { value }
+    }
+
+    /// Returns the cached value.
+    #[must_use]
+    pub fn value(&self) -> &Value {
+        &self.value
+    }
+
+    /// Returns the cached value, consuming the cache.
+    #[must_use]
+    pub fn into_value(self) -> Value {
+        self.value
+    }
+
+    /// Creates a dynamic deserializer reading from the cached value.
+    #[must_use]
+    pub fn deserializer(&self) -> JsonValueDeserializer<'_> {
+        from_json_value(&self.value)
+    }
+
+    /// Deserializes the cached value into a `T`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the value does not match `T`.
+    pub fn deserialize<'de, T>(&'de self) -> DeserializeResult<T>
+    where
+        T: serde::Deserialize<'de>,
+    {
+        let mut deserializer = self.deserializer();
+        T::deserialize(&mut deserializer as &mut dyn Deserializer<'de>).map_err(|error| {
+            match deserializer.error() {
+                Some(error) => DeserializeError::custom(error),
+                None => error,
+            }
+        })
+    }
+}
+
+impl Debug for Cached {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("Cached").field(&self.value).finish()
+    }
+}
@@ -0,0 +1,157 @@
+//! A [`Deserializer`] wrapper matching enum variants regardless of case, see
+//! [`CaseInsensitiveVariants`].
+
+use core::cell::Cell;
+use core::fmt::{self, Debug, Formatter};
+
+use crate::de::middleware::{self, Hint, Middleware, Visit};
+use crate::de::{DeserializeResult, DeserializeSeed, Deserializer, EnumAccess};
+use crate::de::{InplaceDeserializeResult, VariantAccess, Visitor};
+
+/// A [`Deserializer`] wrapper which matches the name of an enum variant
+/// against the declared variants regardless of its case, e.g. reading
+/// `"active"` or `"ACTIVE"` as the variant `Active`.
+///
+/// When the variant of an enum is read from a string or bytes, the name which
+/// equals one of the `variants` passed to `deserialize_enum` ignoring ASCII
+/// case is replaced by that variant before reaching the visitor. A name equal
+/// to a variant is kept as is, so that variants differing only by case still
+/// read as themselves, and a name matching no variant fails as usual. The same
+/// applies to the enums at every depth of the value, whether their variant is
+/// given alone or as the key of a map.
+///
+/// The names of the variants are the ones `Deserialize` declares, i.e. after
+/// `#[serde(rename)]`, and neither the keys of maps nor the names of fields
+/// are affected.
+///
+/// # Examples
+///
+/// ```
+/// # use serde::Deserialize as _;
+/// # use dyn_serde::Deserializer;
+/// # use dyn_serde::de::CaseInsensitiveVariants;
+/// #
+/// #[derive(Debug, PartialEq, serde::Deserialize)]
+/// enum Status {
+///     Active,
+///     Inactive,
+/// }
+///
+/// let mut deserializer = serde_json::Deserializer::from_str(r#"["active", "INACTIVE"]"#);
+/// let mut deserializer = <dyn Deserializer>::new(&mut deserializer);
+/// let mut deserializer = CaseInsensitiveVariants::new(&mut deserializer);
+///
+/// let statuses = Vec::<Status>::deserialize(&mut deserializer as &mut dyn Deserializer).unwrap();
+/// assert_eq!(statuses, [Status::Active, Status::Inactive]);
+/// ```
+pub struct CaseInsensitiveVariants<'a, 'de> {
+    deserializer: &'a mut dyn Deserializer<'de>,
+    folding: Folding,
+}
+
+impl<'a, 'de> CaseInsensitiveVariants<'a, 'de> {
+    /// Wraps `deserializer`, matching the names of enum variants regardless
+    /// of their case.
+    #[must_use]
+    pub fn new(deserializer: &'a mut dyn Deserializer<'de>) -> Self {
+        CaseInsensitiveVariants {
+            deserializer,
+            folding: Folding {
+                expecting_enum: Cell::new(None),
+                expecting_variant: Cell::new(None),
+            },
+        }
+    }
+
+    fn deserialize_hint(
+        &mut self,
+        hint: Hint,
+        visitor: &mut dyn Visitor<'de>,
+    ) -> InplaceDeserializeResult<()> {
+        middleware::deserialize(&self.folding, hint, self.deserializer, visitor)
+    }
+}
+
+impl Debug for CaseInsensitiveVariants<'_, '_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CaseInsensitiveVariants")
+            .finish_non_exhaustive()
+    }
+}
+
+impl<'de> Deserializer<'de> for CaseInsensitiveVariants<'_, 'de> {
+    middleware::forward_to_deserialize_hint!();
+
+    fn dyn_is_human_readable(&self) -> bool {
+        self.deserializer.dyn_is_human_readable()
+    }
+}
+
+type Variants = &'static [&'static str];
+
+struct Folding {
+    /// The variants of the enum being deserialized, until its variant is.
+    expecting_enum: Cell<Option<Variants>>,
+    /// The variants of the enum, if the next visit is the one of its variant.
+    expecting_variant: Cell<Option<Variants>>,
+}
+
+impl<'de> Middleware<'de> for Folding {
+    fn deserialize(
+        &self,
+        hint: Hint,
+        deserializer: &mut dyn Deserializer<'de>,
+        visitor: &mut dyn Visitor<'de>,
+    ) -> InplaceDeserializeResult<()> {
+        self.expecting_enum.set(match hint {
+            Hint::Enum(_, variants) => Some(variants),
+            _ => None,
+        });
+        hint.forward(deserializer, visitor)
+    }
+
+    fn visit(
+        &self,
+        visit: Visit<'_, 'de>,
+        visitor: &mut dyn Visitor<'de>,
+    ) -> DeserializeResult<()> {
+        let Some(variants) = self.expecting_variant.take() else {
+            return visit.forward(visitor);
+        };
+        let name = match &visit {
+            Visit::Str(v) | Visit::BorrowedStr(v) => v.as_bytes(),
+            Visit::String(v) => v.as_bytes(),
+            Visit::Bytes(v) | Visit::BorrowedBytes(v) => v,
+            Visit::ByteBuf(v) => v,
+            _ => return visit.forward(visitor),
+        };
+        match canonical(variants, name) {
+            Some(variant) => Visit::BorrowedStr(variant).forward(visitor),
+            None => visit.forward(visitor),
+        }
+    }
+
+    fn variant<'a>(
+        &self,
+        access: &'a mut dyn EnumAccess<'de>,
+        seed: &mut dyn DeserializeSeed<'de>,
+    ) -> InplaceDeserializeResult<&'a mut dyn VariantAccess<'de>> {
+        self.expecting_variant.set(self.expecting_enum.take());
+        let result = access.dyn_variant(seed);
+        self.expecting_variant.set(None);
+        result
+    }
+}
+
+/// Returns the variant named `name`, or else the first one named `name`
+/// ignoring ASCII case.
+fn canonical(variants: Variants, name: &[u8]) -> Option<&'static str> {
+    let exact = variants.iter().find(|variant| variant.as_bytes() == name);
+    exact
+        .or_else(|| {
+            variants
+                .iter()
+                .find(|variant| variant.as_bytes().eq_ignore_ascii_case(name))
+        })
+        .copied()
+}
@@ -0,0 +1,228 @@
+//! A [`Deserializer`] wrapper rejecting out-of-range integers with clear
+//! errors, see [`CheckedNumbers`].
+
+use core::cell::Cell;
+use core::fmt::{self, Debug, Formatter};
+
+#[cfg(not(feature = "std"))]
+use alloc::format;
+
+use serde::de::{Error as _, Expected, Unexpected};
+
+use crate::de::InplaceDeserializeResult;
+use crate::de::middleware::{self, Hint, Middleware, Visit};
+use crate::de::{DeserializeError, DeserializeResult, Deserializer, Visitor};
+
+/// A [`Deserializer`] wrapper which checks that every integer fits the
+/// integer type it is deserialized into, and rejects it with an error naming
+/// the type, its range and the value otherwise.
+///
+/// When a number overflows its target, e.g. `300` for a `u8`, the visitor of
+/// the target reports it tersely, and a hand-written visitor may not report it
+/// at all. `CheckedNumbers` looks at every integer visited after an integer
+/// hint, at every depth of the value, and returns an `invalid_value` error
+/// such as ``invalid value: integer `300`, expected a u8 between 0 and 255``
+/// when it is out of range. Integers in range, and the other values, are
+/// handed to the visitor unchanged.
+///
+/// Floating-point numbers and values of other types are left to the visitor,
+/// which rejects them as usual.
+///
+/// # Examples
+///
+/// ```
+/// # use serde::Deserialize as _;
+/// # use dyn_serde::Deserializer;
+/// # use dyn_serde::de::{CheckedNumbers, InplaceDeserializer};
+/// #
+/// #[derive(Debug, serde::Deserialize)]
+/// struct Config {
+///     retries: u8,
+/// }
+///
+/// let mut deserializer = serde_json::Deserializer::from_str(r#"{"retries": 300}"#);
+/// let mut inplace = <dyn Deserializer>::new(&mut deserializer);
+/// let mut deserializer = CheckedNumbers::new(&mut inplace);
+///
+/// Config::deserialize(&mut deserializer as &mut dyn Deserializer).unwrap_err();
+/// let InplaceDeserializer::Error(error, _) = inplace else { unreachable!() };
+/// assert_eq!(
+///     error.to_string(),
+///     "invalid value: integer `300`, expected a u8 between 0 and 255 at line 1 column 15",
+/// );
+/// ```
+pub struct CheckedNumbers<'a, 'de> {
+    deserializer: &'a mut dyn Deserializer<'de>,
+    checker: Checker,
+}
+
+impl<'a, 'de> CheckedNumbers<'a, 'de> {
+    /// Wraps `deserializer`, checking that every integer fits its target type.
+    #[must_use]
+    pub fn new(deserializer: &'a mut dyn Deserializer<'de>) -> Self {
+        CheckedNumbers {
+            deserializer,
+            checker: Checker {
+                expecting: Cell::new(None),
+            },
+        }
+    }
+
+    fn deserialize_hint(
+        &mut self,
+        hint: Hint,
+        visitor: &mut dyn Visitor<'de>,
+    ) -> InplaceDeserializeResult<()> {
+        middleware::deserialize(&self.checker, hint, self.deserializer, visitor)
+    }
+}
+
+impl Debug for CheckedNumbers<'_, '_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CheckedNumbers").finish_non_exhaustive()
+    }
+}
+
+impl<'de> Deserializer<'de> for CheckedNumbers<'_, 'de> {
+    middleware::forward_to_deserialize_hint!();
+
+    fn dyn_is_human_readable(&self) -> bool {
+        self.deserializer.dyn_is_human_readable()
+    }
+}
+
+/// An integer type, with its range.
+#[derive(Clone, Copy)]
+struct Range {
+    name: &'static str,
+    min: i128,
+    max: u128,
+}
+
+impl Range {
+    fn of(hint: Hint) -> Option<Range> {
+        macro_rules! range {
+            ($ty:ident) => {
+                Range {
+                    name: stringify!($ty),
+                    min: $ty::MIN as i128,
+                    max: $ty::MAX as u128,
+                }
+            };
+        }
+
+        Some(match hint {
+            Hint::I8 => range!(i8),
+            Hint::I16 => range!(i16),
+            Hint::I32 => range!(i32),
+            Hint::I64 => range!(i64),
+            Hint::I128 => range!(i128),
+            Hint::U8 => range!(u8),
+            Hint::U16 => range!(u16),
+            Hint::U32 => range!(u32),
+            Hint::U64 => range!(u64),
+            Hint::U128 => range!(u128),
+            _ => return None,
+        })
+    }
+
+    /// Checks that the integer `v` is in range.
+    fn check(self, v: Int) -> DeserializeResult<()> {
+        let fits = match v {
+            Int::Signed(v) => v >= self.min && (v < 0 || v.cast_unsigned() <= self.max),
+            Int::Unsigned(v) => v <= self.max,
+        };
+        if fits {
+            return Ok(());
+        }
+
+        let other;
+        let unexpected = match v {
+            Int::Signed(v) => match i64::try_from(v) {
+                Ok(v) => Unexpected::Signed(v),
+                Err(_) => {
+                    other = format!("integer `{v}`");
+                    Unexpected::Other(&other)
+                }
+            },
+            Int::Unsigned(v) => match u64::try_from(v) {
+                Ok(v) => Unexpected::Unsigned(v),
+                Err(_) => {
+                    other = format!("integer `{v}`");
+                    Unexpected::Other(&other)
+                }
+            },
+        };
+        Err(DeserializeError::invalid_value(unexpected, &self))
+    }
+}
+
+impl Expected for Range {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let article = if self.name.starts_with('i') {
+            "an"
+        } else {
+            "a"
+        };
+        write!(
+            f,
+            "{article} {} between {} and {}",
+            self.name, self.min, self.max
+        )
+    }
+}
+
+/// An integer visited, widened.
+#[derive(Clone, Copy)]
+enum Int {
+    Signed(i128),
+    Unsigned(u128),
+}
+
+impl Int {
+    fn of(visit: &Visit<'_, '_>) -> Option<Int> {
+        Some(match *visit {
+            Visit::I8(v) => Int::Signed(v.into()),
+            Visit::I16(v) => Int::Signed(v.into()),
+            Visit::I32(v) => Int::Signed(v.into()),
+            Visit::I64(v) => Int::Signed(v.into()),
+            Visit::I128(v) => Int::Signed(v),
+            Visit::U8(v) => Int::Unsigned(v.into()),
+            Visit::U16(v) => Int::Unsigned(v.into()),
+            Visit::U32(v) => Int::Unsigned(v.into()),
+            Visit::U64(v) => Int::Unsigned(v.into()),
+            Visit::U128(v) => Int::Unsigned(v),
+            _ => return None,
+        })
+    }
+}
+
+struct Checker {
+    /// The range of the integer type of the next visit, if any.
+    expecting: Cell<Option<Range>>,
+}
+
+impl<'de> Middleware<'de> for Checker {
+    fn deserialize(
+        &self,
+        hint: Hint,
+        deserializer: &mut dyn Deserializer<'de>,
+        visitor: &mut dyn Visitor<'de>,
+    ) -> InplaceDeserializeResult<()> {
+        self.expecting.set(Range::of(hint));
+        hint.forward(deserializer, visitor)
+    }
+
+    fn visit(
+        &self,
+        visit: Visit<'_, 'de>,
+        visitor: &mut dyn Visitor<'de>,
+    ) -> DeserializeResult<()> {
+        if let Some(range) = self.expecting.take()
+            && let Some(v) = Int::of(&visit)
+        {
+            range.check(v)?;
+        }
+        visit.forward(visitor)
+    }
+}
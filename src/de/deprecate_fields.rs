@@ -0,0 +1,175 @@
+//! A [`Deserializer`] wrapper reporting the deprecated fields of structs, see
+//! [`DeprecateFields`].
+
+use core::cell::{Cell, RefCell};
+use core::fmt::{self, Debug, Formatter};
+
+#[cfg(not(feature = "std"))]
+use alloc::vec::Vec;
+
+use crate::de::middleware::{self, Hint, Middleware, VariantCall, Visit};
+use crate::de::{DeserializeResult, DeserializeSeed, Deserializer, InplaceDeserializeResult};
+use crate::de::{MapAccess, VariantAccess, Visitor};
+
+/// A [`Deserializer`] wrapper which reports every deprecated field found in a
+/// struct, e.g. to log a warning while consumers migrate off an old field.
+///
+/// The deserialization itself is left unchanged: a deprecated field is still
+/// handed to the visitor of the struct, while its name is passed to the
+/// `warn` callback given to [`DeprecateFields::new`], once per occurrence.
+/// Structs and struct variants are checked at every depth of the value; the
+/// keys of maps which are not structs are never reported. Fields named by
+/// their index rather than by a string are not reported either.
+///
+/// # Examples
+///
+/// ```
+/// # use serde::Deserialize as _;
+/// # use dyn_serde::Deserializer;
+/// # use dyn_serde::de::DeprecateFields;
+/// #
+/// #[derive(serde::Deserialize)]
+/// struct Config {
+///     port: u16,
+///     #[serde(default)]
+///     listen_port: Option<u16>,
+/// }
+///
+/// let mut warnings = Vec::new();
+/// let mut warn = |field: &str| warnings.push(format!("`{field}` is deprecated"));
+/// let mut deserializer = serde_json::Deserializer::from_str(r#"{"port": 80, "listen_port": 80}"#);
+/// let mut deserializer = <dyn Deserializer>::new(&mut deserializer);
+/// let mut deserializer = DeprecateFields::new(&mut deserializer, &["listen_port"], &mut warn);
+///
+/// let config = Config::deserialize(&mut deserializer as &mut dyn Deserializer).unwrap();
+/// assert_eq!(config.listen_port, Some(80));
+/// assert_eq!(warnings, ["`listen_port` is deprecated"]);
+/// ```
+pub struct DeprecateFields<'a, 'de> {
+    deserializer: &'a mut dyn Deserializer<'de>,
+    deprecations: Deprecations<'a>,
+}
+
+impl<'a, 'de> DeprecateFields<'a, 'de> {
+    /// Wraps `deserializer`, passing every field of a struct found in
+    /// `fields` to `warn`.
+    #[must_use]
+    pub fn new(
+        deserializer: &'a mut dyn Deserializer<'de>,
+        fields: &'a [&'a str],
+        warn: &'a mut dyn FnMut(&str),
+    ) -> Self {
+        DeprecateFields {
+            deserializer,
+            deprecations: Deprecations {
+                fields,
+                warn: RefCell::new(warn),
+                expecting_struct: Cell::new(false),
+                maps: RefCell::new(Vec::new()),
+                expecting_key: Cell::new(false),
+            },
+        }
+    }
+
+    fn deserialize_hint(
+        &mut self,
+        hint: Hint,
+        visitor: &mut dyn Visitor<'de>,
+    ) -> InplaceDeserializeResult<()> {
+        middleware::deserialize(&self.deprecations, hint, self.deserializer, visitor)
+    }
+}
+
+impl Debug for DeprecateFields<'_, '_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("DeprecateFields")
+            .field("fields", &self.deprecations.fields)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<'de> Deserializer<'de> for DeprecateFields<'_, 'de> {
+    middleware::forward_to_deserialize_hint!();
+
+    fn dyn_is_human_readable(&self) -> bool {
+        self.deserializer.dyn_is_human_readable()
+    }
+}
+
+struct Deprecations<'a> {
+    fields: &'a [&'a str],
+    warn: RefCell<&'a mut dyn FnMut(&str)>,
+    /// Whether the next visit is the one of a struct.
+    expecting_struct: Cell<bool>,
+    /// Whether each map being visited is a struct, innermost last.
+    maps: RefCell<Vec<bool>>,
+    /// Whether the next visit is the one of a key of a struct.
+    expecting_key: Cell<bool>,
+}
+
+impl Deprecations<'_> {
+    fn check(&self, key: &[u8]) {
+        if let Some(field) = self.fields.iter().find(|field| field.as_bytes() == key) {
+            (self.warn.borrow_mut())(field);
+        }
+    }
+}
+
+impl<'de> Middleware<'de> for Deprecations<'_> {
+    fn deserialize(
+        &self,
+        hint: Hint,
+        deserializer: &mut dyn Deserializer<'de>,
+        visitor: &mut dyn Visitor<'de>,
+    ) -> InplaceDeserializeResult<()> {
+        self.expecting_struct.set(matches!(hint, Hint::Struct(..)));
+        hint.forward(deserializer, visitor)
+    }
+
+    fn visit(
+        &self,
+        visit: Visit<'_, 'de>,
+        visitor: &mut dyn Visitor<'de>,
+    ) -> DeserializeResult<()> {
+        let expecting_struct = self.expecting_struct.replace(false);
+        if self.expecting_key.replace(false) {
+            match &visit {
+                Visit::Str(key) | Visit::BorrowedStr(key) => self.check(key.as_bytes()),
+                Visit::String(key) => self.check(key.as_bytes()),
+                Visit::Bytes(key) | Visit::BorrowedBytes(key) => self.check(key),
+                Visit::ByteBuf(key) => self.check(key),
+                _ => {}
+            }
+        }
+
+        if !matches!(visit, Visit::Map(_)) {
+            return visit.forward(visitor);
+        }
+        self.maps.borrow_mut().push(expecting_struct);
+        let result = visit.forward(visitor);
+        self.maps.borrow_mut().pop();
+        result
+    }
+
+    fn next_key(
+        &self,
+        access: &mut dyn MapAccess<'de>,
+        seed: &mut dyn DeserializeSeed<'de>,
+    ) -> InplaceDeserializeResult<Option<()>> {
+        self.expecting_key
+            .set(self.maps.borrow().last().copied().unwrap_or(false));
+        let result = access.dyn_next_key(seed);
+        self.expecting_key.set(false);
+        result
+    }
+
+    fn variant_access(
+        &self,
+        call: VariantCall<'_, 'de>,
+        access: &mut dyn VariantAccess<'de>,
+    ) -> InplaceDeserializeResult<()> {
+        self.expecting_struct
+            .set(matches!(call, VariantCall::Struct(..)));
+        call.forward(access)
+    }
+}
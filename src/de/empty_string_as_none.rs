@@ -0,0 +1,78 @@
+//! A [`Deserializer`] wrapper reading empty strings as `None`, see
+//! [`EmptyStringAsNone`].
+
+use core::fmt::{self, Debug, Formatter};
+
+use crate::de::middleware::{self, Hint};
+use crate::de::{Deserializer, InplaceDeserializeResult, NullLike, Visitor};
+
+/// A [`Deserializer`] wrapper which reads an empty string as `None` where an
+/// option is expected, e.g. the blank inputs of a submitted form.
+///
+/// This is [`NullLike`] with `""` as its only sentinel: when an option is
+/// present and its value is the empty string, the visitor of the option sees
+/// `None`, at every depth of the value. Looking at the value relies on
+/// `deserialize_any`, so the format must be self-describing.
+///
+/// Values which are not options pass through unchanged, so an empty string
+/// read as a `String` stays empty, and one read as a number still fails with
+/// the usual type error. A field which should fall back to its default when
+/// blank can be declared as an option and unwrapped with `unwrap_or_default`.
+///
+/// # Examples
+///
+/// ```
+/// # use serde::Deserialize as _;
+/// # use dyn_serde::Deserializer;
+/// # use dyn_serde::de::EmptyStringAsNone;
+/// #
+/// #[derive(serde::Deserialize)]
+/// struct Form {
+///     name: String,
+///     email: Option<String>,
+/// }
+///
+/// let mut deserializer = serde_json::Deserializer::from_str(r#"{"name": "", "email": ""}"#);
+/// let mut deserializer = <dyn Deserializer>::new(&mut deserializer);
+/// let mut deserializer = EmptyStringAsNone::new(&mut deserializer);
+///
+/// let form = Form::deserialize(&mut deserializer as &mut dyn Deserializer).unwrap();
+/// assert_eq!(form.name, "");
+/// assert_eq!(form.email, None);
+/// ```
+pub struct EmptyStringAsNone<'a, 'de> {
+    deserializer: NullLike<'a, 'de>,
+}
+
+impl<'a, 'de> EmptyStringAsNone<'a, 'de> {
+    /// Wraps `deserializer`, reading the empty string as `None` where an
+    /// option is expected.
+    #[must_use]
+    pub fn new(deserializer: &'a mut dyn Deserializer<'de>) -> Self {
+        EmptyStringAsNone {
+            deserializer: NullLike::new(deserializer, &[""]),
+        }
+    }
+
+    fn deserialize_hint(
+        &mut self,
+        hint: Hint,
+        visitor: &mut dyn Visitor<'de>,
+    ) -> InplaceDeserializeResult<()> {
+        hint.forward(&mut self.deserializer, visitor)
+    }
+}
+
+impl Debug for EmptyStringAsNone<'_, '_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("EmptyStringAsNone").finish_non_exhaustive()
+    }
+}
+
+impl<'de> Deserializer<'de> for EmptyStringAsNone<'_, 'de> {
+    middleware::forward_to_deserialize_hint!();
+
+    fn dyn_is_human_readable(&self) -> bool {
+        self.deserializer.dyn_is_human_readable()
+    }
+}
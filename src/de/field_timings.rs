@@ -0,0 +1,207 @@
+//! A [`Deserializer`] wrapper measuring the time taken by every field, see
+//! [`FieldTimings`].
+
+use core::cell::{Cell, RefCell};
+use core::fmt::{self, Debug, Formatter};
+use std::collections::BTreeMap;
+use std::time::{Duration, Instant};
+
+use crate::de::middleware::{self, Hint, Middleware, VariantCall, Visit};
+use crate::de::{DeserializeResult, DeserializeSeed, Deserializer, InplaceDeserializeResult};
+use crate::de::{MapAccess, VariantAccess, Visitor};
+
+/// A [`Deserializer`] wrapper which records how long every field of the
+/// top-level struct takes to deserialize, e.g. to find the slow `Deserialize`
+/// implementation in a large document.
+///
+/// The time spent in the `dyn_next_value` call of a field, which reads its
+/// value and runs the `Deserialize` implementation of its type, is added to
+/// the entry of its name in the `timings` map given to [`FieldTimings::new`].
+/// Reading the keys is not measured, while the fields which are ignored are,
+/// under their own name. A field found several times adds up all of its
+/// occurrences.
+///
+/// Only the fields of the top-level struct or struct variant are measured,
+/// possibly behind `Some`, a newtype struct or an enum; nested structs count
+/// towards the field containing them. Structs read from a sequence, as binary
+/// formats commonly write them, have no names to record their fields under.
+///
+/// # Examples
+///
+/// ```
+/// # use std::collections::BTreeMap;
+/// # use serde::Deserialize as _;
+/// # use dyn_serde::Deserializer;
+/// # use dyn_serde::de::FieldTimings;
+/// #
+/// #[derive(serde::Deserialize)]
+/// struct Page {
+///     id: u32,
+///     body: String,
+/// }
+///
+/// let mut timings = BTreeMap::new();
+/// let mut deserializer = serde_json::Deserializer::from_str(r#"{"id": 1, "body": ""}"#);
+/// let mut deserializer = <dyn Deserializer>::new(&mut deserializer);
+/// let mut deserializer = FieldTimings::new(&mut deserializer, &mut timings);
+///
+/// Page::deserialize(&mut deserializer as &mut dyn Deserializer).unwrap();
+/// assert!(timings.keys().eq(["body", "id"]));
+/// ```
+pub struct FieldTimings<'a, 'de> {
+    deserializer: &'a mut dyn Deserializer<'de>,
+    timer: Timer<'a>,
+}
+
+impl<'a, 'de> FieldTimings<'a, 'de> {
+    /// Wraps `deserializer`, adding the time taken by each field of the
+    /// top-level struct to `timings`.
+    #[must_use]
+    pub fn new(
+        deserializer: &'a mut dyn Deserializer<'de>,
+        timings: &'a mut BTreeMap<String, Duration>,
+    ) -> Self {
+        FieldTimings {
+            deserializer,
+            timer: Timer {
+                timings: RefCell::new(timings),
+                expecting_struct: Cell::new(false),
+                depth: Cell::new(0),
+                in_struct: Cell::new(false),
+                expecting_key: Cell::new(false),
+                key: RefCell::new(None),
+            },
+        }
+    }
+
+    fn deserialize_hint(
+        &mut self,
+        hint: Hint,
+        visitor: &mut dyn Visitor<'de>,
+    ) -> InplaceDeserializeResult<()> {
+        middleware::deserialize(&self.timer, hint, self.deserializer, visitor)
+    }
+}
+
+impl Debug for FieldTimings<'_, '_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("FieldTimings")
+            .field("timings", &self.timer.timings.borrow())
+            .finish_non_exhaustive()
+    }
+}
+
+impl<'de> Deserializer<'de> for FieldTimings<'_, 'de> {
+    middleware::forward_to_deserialize_hint!();
+
+    fn dyn_is_human_readable(&self) -> bool {
+        self.deserializer.dyn_is_human_readable()
+    }
+}
+
+struct Timer<'a> {
+    timings: RefCell<&'a mut BTreeMap<String, Duration>>,
+    /// Whether the next visit is the one of a struct.
+    expecting_struct: Cell<bool>,
+    /// The number of sequences and maps being visited.
+    depth: Cell<usize>,
+    /// Whether the outermost map being visited is the top-level struct.
+    in_struct: Cell<bool>,
+    /// Whether the next visit is the one of a key of the top-level struct.
+    expecting_key: Cell<bool>,
+    /// The name of the field whose value is next.
+    key: RefCell<Option<String>>,
+}
+
+impl Timer<'_> {
+    /// Whether the map access being called is the one of the top-level struct.
+    fn at_top(&self) -> bool {
+        self.in_struct.get() && self.depth.get() == 1
+    }
+}
+
+impl<'de> Middleware<'de> for Timer<'_> {
+    fn deserialize(
+        &self,
+        hint: Hint,
+        deserializer: &mut dyn Deserializer<'de>,
+        visitor: &mut dyn Visitor<'de>,
+    ) -> InplaceDeserializeResult<()> {
+        self.expecting_struct.set(matches!(hint, Hint::Struct(..)));
+        hint.forward(deserializer, visitor)
+    }
+
+    fn visit(
+        &self,
+        visit: Visit<'_, 'de>,
+        visitor: &mut dyn Visitor<'de>,
+    ) -> DeserializeResult<()> {
+        let expecting_struct = self.expecting_struct.replace(false);
+        if self.expecting_key.replace(false) {
+            let key = match &visit {
+                Visit::Str(key) | Visit::BorrowedStr(key) => Some((*key).into()),
+                Visit::String(key) => Some(key.clone()),
+                Visit::Bytes(key) | Visit::BorrowedBytes(key) => {
+                    Some(String::from_utf8_lossy(key).into_owned())
+                }
+                Visit::ByteBuf(key) => Some(String::from_utf8_lossy(key).into_owned()),
+                Visit::U64(index) => Some(index.to_string()),
+                _ => None,
+            };
+            *self.key.borrow_mut() = key;
+        }
+
+        if !matches!(visit, Visit::Seq(_) | Visit::Map(_)) {
+            return visit.forward(visitor);
+        }
+        let depth = self.depth.get();
+        if depth == 0 {
+            self.in_struct
+                .set(expecting_struct && matches!(visit, Visit::Map(_)));
+        }
+        self.depth.set(depth + 1);
+        let result = visit.forward(visitor);
+        self.depth.set(depth);
+        result
+    }
+
+    fn next_key(
+        &self,
+        access: &mut dyn MapAccess<'de>,
+        seed: &mut dyn DeserializeSeed<'de>,
+    ) -> InplaceDeserializeResult<Option<()>> {
+        self.expecting_key.set(self.at_top());
+        let result = access.dyn_next_key(seed);
+        self.expecting_key.set(false);
+        result
+    }
+
+    fn next_value(
+        &self,
+        access: &mut dyn MapAccess<'de>,
+        seed: &mut dyn DeserializeSeed<'de>,
+    ) -> InplaceDeserializeResult<()> {
+        let key = if self.at_top() {
+            self.key.borrow_mut().take()
+        } else {
+            None
+        };
+        let Some(key) = key else {
+            return access.dyn_next_value(seed);
+        };
+        let start = Instant::now();
+        let result = access.dyn_next_value(seed);
+        *self.timings.borrow_mut().entry(key).or_default() += start.elapsed();
+        result
+    }
+
+    fn variant_access(
+        &self,
+        call: VariantCall<'_, 'de>,
+        access: &mut dyn VariantAccess<'de>,
+    ) -> InplaceDeserializeResult<()> {
+        self.expecting_struct
+            .set(matches!(call, VariantCall::Struct(..)));
+        call.forward(access)
+    }
+}
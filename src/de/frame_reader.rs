@@ -0,0 +1,102 @@
+//! Readers of length-prefixed frames, see [`FrameReader`].
+
+use std::io::{self, Read as _};
+
+/// A reader splitting a stream written by [`LengthPrefixed`] back into its
+/// frames, so that each value is deserialized on its own.
+///
+/// Every frame is read as a little-endian `u32` length followed by that many
+/// bytes. [`FrameReader::next_frame`] reads the next frame on demand and
+/// returns its bytes, from which the deserializer of the backend the frames
+/// were written with is created, then borrowed as a `&mut dyn Deserializer`.
+/// The deserializer only sees the bytes of its frame, so it cannot read past
+/// the value into the next one, and the next frame is read whether or not the
+/// value has been deserialized.
+///
+/// The bytes of a frame are read into a buffer which is reused by the next
+/// frame, and which only grows as the bytes arrive, so that a corrupted length
+/// does not allocate up front.
+///
+/// [`LengthPrefixed`]: crate::ser::LengthPrefixed
+///
+/// # Examples
+///
+/// ```
+/// # use serde::Deserialize as _;
+/// # use dyn_serde::Deserializer;
+/// # use dyn_serde::de::FrameReader;
+/// #
+/// let stream = b"\x01\0\0\x001\x05\0\0\0\"two\"";
+/// let mut reader = FrameReader::new(&stream[..]);
+///
+/// let frame = reader.next_frame().unwrap().unwrap();
+/// let mut deserializer = serde_json::Deserializer::from_slice(frame);
+/// let mut deserializer = <dyn Deserializer>::new(&mut deserializer);
+/// assert_eq!(u32::deserialize(&mut deserializer as &mut dyn Deserializer).unwrap(), 1);
+///
+/// let frame = reader.next_frame().unwrap().unwrap();
+/// let mut deserializer = serde_json::Deserializer::from_slice(frame);
+/// let mut deserializer = <dyn Deserializer>::new(&mut deserializer);
+/// assert_eq!(String::deserialize(&mut deserializer as &mut dyn Deserializer).unwrap(), "two");
+///
+/// assert!(reader.next_frame().unwrap().is_none());
+/// ```
+#[derive(Debug)]
+pub struct FrameReader<R> {
+    inner: R,
+    /// The bytes of the last frame read.
+    frame: Vec<u8>,
+}
+
+impl<R: io::Read> FrameReader<R> {
+    /// Creates a reader reading length-prefixed frames from `inner`.
+    #[must_use]
+    pub fn new(inner: R) -> Self {
+        FrameReader {
+            inner,
+            frame: Vec::new(),
+        }
+    }
+
+    /// Reads the next frame, returning its bytes, or `None` if the stream has
+    /// ended.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the reader fails to read, or if the stream ends in
+    /// the middle of a frame.
+    pub fn next_frame(&mut self) -> io::Result<Option<&[u8]>> {
+        let mut len = [0; 4];
+        let mut filled = 0;
+        while filled < len.len() {
+            match self.inner.read(&mut len[filled..]) {
+                Ok(0) if filled == 0 => return Ok(None),
+                Ok(0) => return Err(truncated()),
+                Ok(n) => filled += n,
+                Err(error) if error.kind() == io::ErrorKind::Interrupted => {}
+                Err(error) => return Err(error),
+            }
+        }
+
+        let len = u64::from(u32::from_le_bytes(len));
+        self.frame.clear();
+        let read = (&mut self.inner).take(len).read_to_end(&mut self.frame)?;
+        if read as u64 != len {
+            return Err(truncated());
+        }
+        Ok(Some(&self.frame))
+    }
+
+    /// Unwraps the reader.
+    #[must_use]
+    pub fn into_inner(self) -> R {
+        self.inner
+    }
+}
+
+fn truncated() -> io::Error {
+    io::Error::new(
+        io::ErrorKind::UnexpectedEof,
+        "the stream ends in the middle of a frame",
+    )
+}
@@ -0,0 +1,73 @@
+//! A [`DeserializeSeed`] updating an existing value, see [`InPlaceSeed`].
+
+use core::fmt::{self, Debug, Formatter};
+
+use crate::de::{DeserializeResult, DeserializeSeed, Deserializer};
+
+/// A [`DeserializeSeed`] which deserializes into an existing value rather than
+/// creating a new one, reusing its allocations where possible.
+///
+/// The value is updated with [`serde::Deserialize::deserialize_in_place`].
+/// The implementations of serde for `Vec`, `String`, `Option`, tuples and
+/// arrays reuse the buffers of the existing value, so that a loop
+/// deserializing many values into the same place does not allocate once the
+/// buffers are large enough. Types deriving `Deserialize` replace the value
+/// as a whole, unless the `deserialize_in_place` feature of `serde_derive` is
+/// enabled.
+///
+/// On error, the value is left in an unspecified but valid state.
+///
+/// # Examples
+///
+/// ```
+/// # use dyn_serde::Deserializer;
+/// # use dyn_serde::de::{DeserializeSeed, InPlaceSeed};
+/// #
+/// let mut buf: Vec<u32> = Vec::with_capacity(16);
+/// for json in ["[1, 2]", "[3, 4, 5]"] {
+///     let mut deserializer = serde_json::Deserializer::from_str(json);
+///     let mut deserializer = <dyn Deserializer>::new(&mut deserializer);
+///     InPlaceSeed::new(&mut buf).dyn_deserialize(&mut deserializer).unwrap();
+/// }
+/// assert_eq!(buf, [3, 4, 5]);
+/// assert_eq!(buf.capacity(), 16);
+/// ```
+pub struct InPlaceSeed<'a, T> {
+    place: &'a mut T,
+}
+
+impl<'a, T> InPlaceSeed<'a, T> {
+    /// Creates a seed deserializing into `place`.
+    #[must_use]
+    pub fn new(place: &'a mut T) -> Self {
+        InPlaceSeed { place }
+    }
+}
+
+impl<T: Debug> Debug for InPlaceSeed<'_, T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("InPlaceSeed")
+            .field("place", &self.place)
+            .finish()
+    }
+}
+
+impl<'de, T: serde::Deserialize<'de>> DeserializeSeed<'de> for InPlaceSeed<'_, T> {
+    fn dyn_deserialize(
+        &mut self,
+        deserializer: &mut dyn Deserializer<'de>,
+    ) -> DeserializeResult<()> {
+        T::deserialize_in_place(deserializer, self.place)
+    }
+}
+
+impl<'de, T: serde::Deserialize<'de>> serde::de::DeserializeSeed<'de> for InPlaceSeed<'_, T> {
+    type Value = ();
+
+    fn deserialize<D>(self, deserializer: D) -> Result<(), D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        T::deserialize_in_place(deserializer, self.place)
+    }
+}
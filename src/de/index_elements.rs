@@ -0,0 +1,214 @@
+//! A [`Deserializer`] wrapper telling the elements of sequences their index,
+//! see [`IndexElements`].
+
+use core::cell::{Cell, RefCell};
+use core::fmt::{self, Debug, Formatter};
+
+#[cfg(not(feature = "std"))]
+use alloc::vec::Vec;
+
+use serde::de::value::{StrDeserializer, U64Deserializer};
+
+use crate::de::middleware::{self, Hint, Middleware, Visit};
+use crate::de::untag_structs::replay;
+use crate::de::{DeserializeError, DeserializeResult, DeserializeSeed, Deserializer};
+use crate::de::{InplaceDeserializeError, InplaceDeserializeResult};
+use crate::de::{MapAccess, SeqAccess, Visitor};
+
+/// A [`Deserializer`] wrapper which adds a field holding the index of every
+/// element of a sequence which is a map, e.g. `"_index"`, so that the structs
+/// in a list know their position.
+///
+/// When an element of a sequence is visited as a map, the visitor reads an
+/// extra first entry whose key is `field` and whose value is the index of the
+/// element, counted from `0`, before the entries of the element itself. This
+/// applies to the sequences at every depth of the value, each counting its own
+/// elements, and through options and newtype structs. Elements which are not
+/// maps, and maps which are not elements, are left unchanged.
+///
+/// Structs which do not declare the field ignore it, unless they deny unknown
+/// fields. An element which has an entry of the same name reads both, which
+/// fails as a duplicate field of a struct.
+///
+/// # Examples
+///
+/// ```
+/// # use serde::Deserialize as _;
+/// # use dyn_serde::Deserializer;
+/// # use dyn_serde::de::IndexElements;
+/// #
+/// #[derive(Debug, PartialEq, serde::Deserialize)]
+/// struct Item {
+///     #[serde(rename = "_index")]
+///     index: usize,
+///     name: String,
+/// }
+///
+/// let mut deserializer = serde_json::Deserializer::from_str(r#"[{"name": "a"}, {"name": "b"}]"#);
+/// let mut deserializer = <dyn Deserializer>::new(&mut deserializer);
+/// let mut deserializer = IndexElements::new(&mut deserializer, "_index");
+///
+/// let items = Vec::<Item>::deserialize(&mut deserializer as &mut dyn Deserializer).unwrap();
+/// assert_eq!(items[1], Item { index: 1, name: "b".into() });
+/// ```
+pub struct IndexElements<'a, 'de> {
+    deserializer: &'a mut dyn Deserializer<'de>,
+    index: Index,
+}
+
+impl<'a, 'de> IndexElements<'a, 'de> {
+    /// Wraps `deserializer`, adding the index of every element which is a map
+    /// as an entry named `field`.
+    #[must_use]
+    pub fn new(deserializer: &'a mut dyn Deserializer<'de>, field: &'static str) -> Self {
+        IndexElements {
+            deserializer,
+            index: Index {
+                field,
+                counts: RefCell::new(Vec::new()),
+                element: Cell::new(None),
+            },
+        }
+    }
+
+    fn deserialize_hint(
+        &mut self,
+        hint: Hint,
+        visitor: &mut dyn Visitor<'de>,
+    ) -> InplaceDeserializeResult<()> {
+        middleware::deserialize(&self.index, hint, self.deserializer, visitor)
+    }
+}
+
+impl Debug for IndexElements<'_, '_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("IndexElements")
+            .field("field", &self.index.field)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<'de> Deserializer<'de> for IndexElements<'_, 'de> {
+    middleware::forward_to_deserialize_hint!();
+
+    fn dyn_is_human_readable(&self) -> bool {
+        self.deserializer.dyn_is_human_readable()
+    }
+}
+
+struct Index {
+    field: &'static str,
+    /// The number of elements read so far of every sequence being visited.
+    counts: RefCell<Vec<usize>>,
+    /// The index of the element whose visit comes next, if any.
+    element: Cell<Option<usize>>,
+}
+
+impl<'de> Middleware<'de> for Index {
+    fn visit(
+        &self,
+        visit: Visit<'_, 'de>,
+        visitor: &mut dyn Visitor<'de>,
+    ) -> DeserializeResult<()> {
+        match visit {
+            Visit::Seq(access) => {
+                self.element.set(None);
+                self.counts.borrow_mut().push(0);
+                let result = visitor.dyn_visit_seq(access);
+                self.counts.borrow_mut().pop();
+                result
+            }
+            Visit::Map(access) if let Some(index) = self.element.take() => {
+                let mut access = IndexedMapAccess {
+                    inner: access,
+                    field: Some(self.field),
+                    index: Some(index),
+                    error: None,
+                };
+                visitor
+                    .dyn_visit_map(&mut access)
+                    .map_err(|error| access.error.take().unwrap_or(error))
+            }
+            // the element is the value inside.
+            visit @ (Visit::Some(_) | Visit::NewtypeStruct(_)) => visit.forward(visitor),
+            visit => {
+                self.element.set(None);
+                visit.forward(visitor)
+            }
+        }
+    }
+
+    fn next_element(
+        &self,
+        access: &mut dyn SeqAccess<'de>,
+        seed: &mut dyn DeserializeSeed<'de>,
+    ) -> InplaceDeserializeResult<Option<()>> {
+        let index = self.counts.borrow_mut().last_mut().map(|count| {
+            *count += 1;
+            *count - 1
+        });
+        self.element.set(index);
+        let result = access.dyn_next_element(seed);
+        self.element.set(None);
+        result
+    }
+}
+
+/// The map of an element, which starts with the entry of its index.
+struct IndexedMapAccess<'a, 'de> {
+    inner: &'a mut dyn MapAccess<'de>,
+    /// The key of the index, until it is read.
+    field: Option<&'static str>,
+    /// The index, until it is read.
+    index: Option<usize>,
+    /// The error raised while reading the index, if any.
+    error: Option<DeserializeError>,
+}
+
+impl IndexedMapAccess<'_, '_> {
+    fn fail(&mut self, error: DeserializeError) -> InplaceDeserializeError {
+        self.error.get_or_insert(error);
+        InplaceDeserializeError::Error
+    }
+}
+
+impl<'de> MapAccess<'de> for IndexedMapAccess<'_, 'de> {
+    fn dyn_next_key(
+        &mut self,
+        seed: &mut dyn DeserializeSeed<'de>,
+    ) -> InplaceDeserializeResult<Option<()>> {
+        let Some(field) = self.field.take() else {
+            return self.inner.dyn_next_key(seed);
+        };
+        match replay(seed, StrDeserializer::new(field)) {
+            Ok(()) => Ok(Some(())),
+            Err(error) => Err(self.fail(error)),
+        }
+    }
+
+    fn dyn_next_value(
+        &mut self,
+        seed: &mut dyn DeserializeSeed<'de>,
+    ) -> InplaceDeserializeResult<()> {
+        let Some(index) = self.index.take() else {
+            return self.inner.dyn_next_value(seed);
+        };
+        replay(seed, U64Deserializer::new(index as u64)).map_err(|error| self.fail(error))
+    }
+
+    fn dyn_next_entry(
+        &mut self,
+        kseed: &mut dyn DeserializeSeed<'de>,
+        vseed: &mut dyn DeserializeSeed<'de>,
+    ) -> InplaceDeserializeResult<Option<((), ())>> {
+        match self.dyn_next_key(kseed)? {
+            Some(()) => self.dyn_next_value(vseed).map(|()| Some(((), ()))),
+            None => Ok(None),
+        }
+    }
+
+    fn dyn_size_hint(&self) -> Option<usize> {
+        let extra = usize::from(self.index.is_some());
+        self.inner.dyn_size_hint().map(|len| len + extra)
+    }
+}
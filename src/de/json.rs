@@ -0,0 +1,402 @@
+//! Deserializing from a [`serde_json::Value`], see [`from_json_value`].
+
+use core::fmt::{self, Debug, Formatter};
+
+#[cfg(not(feature = "std"))]
+use alloc::string::String;
+use serde::de::value::BorrowedStrDeserializer;
+use serde::de::{Error as _, Unexpected};
+
+use serde_json::{Map, Value};
+
+use crate::de::{DeserializeError, DeserializeSeed, Deserializer, EnumAccess, InplaceDeserializer};
+use crate::de::{InplaceDeserializeError, InplaceDeserializeResult};
+use crate::de::{MapAccess, SeqAccess};
+use crate::de::{VariantAccess, Visitor};
+
+/// Creates a dynamic deserializer reading from a [`serde_json::Value`].
+///
+/// The returned deserializer implements this crate's [`Deserializer`] directly
+/// on top of the value, rather than going through the [`serde::Deserializer`]
+/// implementation of `serde_json`. Strings are borrowed from `value`.
+///
+/// # Examples
+///
+/// ```
+/// # use serde::Deserialize as _;
+/// # use dyn_serde::Deserializer;
+/// #
+/// let value = serde_json::json!(["dyn_serde", [1, 1, 2]]);
+/// let mut deserializer = dyn_serde::de::from_json_value(&value);
+/// let deserializer = &mut deserializer as &mut dyn Deserializer<'_>;
+///
+/// let (name, version) = <(&str, Vec<u8>)>::deserialize(deserializer).unwrap();
+/// assert_eq!(name, "dyn_serde");
+/// assert_eq!(version, [1, 1, 2]);
+/// ```
+#[must_use]
+pub fn from_json_value(value: &Value) -> JsonValueDeserializer<'_> {
+    JsonValueDeserializer { value, error: None }
+}
+
+/// A [`Deserializer`] reading from a [`serde_json::Value`], created by
+/// [`from_json_value`].
+pub struct JsonValueDeserializer<'de> {
+    value: &'de Value,
+    error: Option<DeserializeError>,
+}
+
+impl JsonValueDeserializer<'_> {
+    /// Returns the first error raised while deserializing from the value, if
+    /// any.
+    ///
+    /// The error returned to the caller of the deserialization may only tell
+    /// that the deserialization has done unsuccessfully, while this one tells
+    /// why it has.
+    #[must_use]
+    pub fn error(&self) -> Option<&DeserializeError> {
+        self.error.as_ref()
+    }
+
+    fn visit(
+        &mut self,
+        visit: impl FnOnce(&mut Option<DeserializeError>) -> Result<(), DeserializeError>,
+    ) -> InplaceDeserializeResult<()> {
+        (visit)(&mut self.error).map_err(|error| fail(&mut self.error, error))
+    }
+}
+
+impl Debug for JsonValueDeserializer<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("JsonValueDeserializer")
+            .field("value", self.value)
+            .field("error", &self.error)
+            .finish()
+    }
+}
+
+impl<'de> Deserializer<'de> for JsonValueDeserializer<'de> {
+    fn dyn_deserialize_any(
+        &mut self,
+        visitor: &mut dyn Visitor<'de>,
+    ) -> InplaceDeserializeResult<()> {
+        let value = self.value;
+        self.visit(|error| match value {
+            Value::Null => visitor.dyn_visit_unit(),
+            Value::Bool(v) => visitor.dyn_visit_bool(*v),
+            Value::Number(v) => {
+                if let Some(v) = v.as_u64() {
+                    visitor.dyn_visit_u64(v)
+                } else if let Some(v) = v.as_i64() {
+                    visitor.dyn_visit_i64(v)
+                } else {
+                    visitor.dyn_visit_f64(v.as_f64().unwrap_or(f64::NAN))
+                }
+            }
+            Value::String(v) => visitor.dyn_visit_borrowed_str(v),
+            Value::Array(v) => visit_array(v, visitor, error),
+            Value::Object(v) => visit_object(v, visitor, error),
+        })
+    }
+
+    fn dyn_deserialize_option(
+        &mut self,
+        visitor: &mut dyn Visitor<'de>,
+    ) -> InplaceDeserializeResult<()> {
+        match self.value {
+            Value::Null => self.visit(|_| visitor.dyn_visit_none()),
+            _ => match visitor.dyn_visit_some(self) {
+                Ok(()) => Ok(()),
+                Err(error) => Err(fail(&mut self.error, error)),
+            },
+        }
+    }
+
+    fn dyn_deserialize_newtype_struct(
+        &mut self,
+        name: &'static str,
+        visitor: &mut dyn Visitor<'de>,
+    ) -> InplaceDeserializeResult<()> {
+        let _ = name;
+        match visitor.dyn_visit_newtype_struct(self) {
+            Ok(()) => Ok(()),
+            Err(error) => Err(fail(&mut self.error, error)),
+        }
+    }
+
+    fn dyn_deserialize_enum(
+        &mut self,
+        name: &'static str,
+        variants: &'static [&'static str],
+        visitor: &mut dyn Visitor<'de>,
+    ) -> InplaceDeserializeResult<()> {
+        let _ = (name, variants);
+        let value = self.value;
+        self.visit(|error| {
+            let (variant, value) = match value {
+                Value::String(variant) => (variant, None),
+                Value::Object(map) => {
+                    let mut iter = map.iter();
+                    match (iter.next(), iter.next()) {
+                        (Some((variant, value)), None) => (variant, Some(value)),
+                        _ => {
+                            return Err(DeserializeError::invalid_type(
+                                Unexpected::Map,
+                                &"string or map with a single key",
+                            ));
+                        }
+                    }
+                }
+                value => {
+                    return Err(DeserializeError::invalid_type(
+                        unexpected(value),
+                        &"string or map with a single key",
+                    ));
+                }
+            };
+            visitor.dyn_visit_enum(&mut JsonEnumAccess {
+                variant: Some(variant),
+                value,
+                error,
+            })
+        })
+    }
+}
+
+/// Records `error` unless an error has been recorded, since the error
+/// propagated from a nested value only tells that it has failed.
+#[cold]
+fn fail(slot: &mut Option<DeserializeError>, error: DeserializeError) -> InplaceDeserializeError {
+    slot.get_or_insert(error);
+    InplaceDeserializeError::Error
+}
+
+fn unexpected(value: &Value) -> Unexpected<'_> {
+    match value {
+        Value::Null => Unexpected::Unit,
+        Value::Bool(v) => Unexpected::Bool(*v),
+        Value::Number(v) => {
+            if let Some(v) = v.as_u64() {
+                Unexpected::Unsigned(v)
+            } else if let Some(v) = v.as_i64() {
+                Unexpected::Signed(v)
+            } else {
+                Unexpected::Float(v.as_f64().unwrap_or(f64::NAN))
+            }
+        }
+        Value::String(v) => Unexpected::Str(v),
+        Value::Array(_) => Unexpected::Seq,
+        Value::Object(_) => Unexpected::Map,
+    }
+}
+
+fn visit_array<'de>(
+    array: &'de [Value],
+    visitor: &mut dyn Visitor<'de>,
+    error: &mut Option<DeserializeError>,
+) -> Result<(), DeserializeError> {
+    let mut access = JsonSeqAccess {
+        iter: array.iter(),
+        error,
+    };
+    visitor.dyn_visit_seq(&mut access)?;
+    match access.iter.len() {
+        0 => Ok(()),
+        _ => Err(DeserializeError::invalid_length(
+            array.len(),
+            &"fewer elements in array",
+        )),
+    }
+}
+
+fn visit_object<'de>(
+    object: &'de Map<String, Value>,
+    visitor: &mut dyn Visitor<'de>,
+    error: &mut Option<DeserializeError>,
+) -> Result<(), DeserializeError> {
+    let mut access = JsonMapAccess {
+        iter: object.iter(),
+        value: None,
+        error,
+    };
+    visitor.dyn_visit_map(&mut access)?;
+    match access.iter.len() {
+        0 => Ok(()),
+        _ => Err(DeserializeError::invalid_length(
+            object.len(),
+            &"fewer elements in map",
+        )),
+    }
+}
+
+fn deserialize_value<'de>(
+    seed: &mut dyn DeserializeSeed<'de>,
+    value: &'de Value,
+    error: &mut Option<DeserializeError>,
+) -> InplaceDeserializeResult<()> {
+    let mut deserializer = from_json_value(value);
+    seed.dyn_deserialize(&mut deserializer)
+        .map_err(|e| fail(error, deserializer.error.unwrap_or(e)))
+}
+
+fn deserialize_key<'de>(
+    seed: &mut dyn DeserializeSeed<'de>,
+    key: &'de str,
+    error: &mut Option<DeserializeError>,
+) -> InplaceDeserializeResult<()> {
+    let mut deserializer = InplaceDeserializer::Deserializer(BorrowedStrDeserializer::new(key));
+    seed.dyn_deserialize(&mut deserializer)
+        .map_err(|e| match deserializer {
+            InplaceDeserializer::Error(inner, _) => fail(error, inner),
+            _ => fail(error, e),
+        })
+}
+
+struct JsonSeqAccess<'a, 'de> {
+    iter: core::slice::Iter<'de, Value>,
+    error: &'a mut Option<DeserializeError>,
+}
+
+impl<'de> SeqAccess<'de> for JsonSeqAccess<'_, 'de> {
+    fn dyn_next_element(
+        &mut self,
+        seed: &mut dyn DeserializeSeed<'de>,
+    ) -> InplaceDeserializeResult<Option<()>> {
+        match self.iter.next() {
+            Some(value) => deserialize_value(seed, value, self.error).map(Some),
+            None => Ok(None),
+        }
+    }
+
+    fn dyn_size_hint(&self) -> Option<usize> {
+        Some(self.iter.len())
+    }
+}
+
+struct JsonMapAccess<'a, 'de> {
+    iter: serde_json::map::Iter<'de>,
+    value: Option<&'de Value>,
+    error: &'a mut Option<DeserializeError>,
+}
+
+impl<'de> MapAccess<'de> for JsonMapAccess<'_, 'de> {
+    fn dyn_next_key(
+        &mut self,
+        seed: &mut dyn DeserializeSeed<'de>,
+    ) -> InplaceDeserializeResult<Option<()>> {
+        match self.iter.next() {
+            Some((key, value)) => {
+                self.value = Some(value);
+                deserialize_key(seed, key, self.error).map(Some)
+            }
+            None => Ok(None),
+        }
+    }
+
+    fn dyn_next_value(
+        &mut self,
+        seed: &mut dyn DeserializeSeed<'de>,
+    ) -> InplaceDeserializeResult<()> {
+        match self.value.take() {
+            Some(value) => deserialize_value(seed, value, self.error),
+            None => Err(fail(
+                self.error,
+                DeserializeError::custom("value is missing"),
+            )),
+        }
+    }
+
+    fn dyn_next_entry(
+        &mut self,
+        kseed: &mut dyn DeserializeSeed<'de>,
+        vseed: &mut dyn DeserializeSeed<'de>,
+    ) -> InplaceDeserializeResult<Option<((), ())>> {
+        match self.dyn_next_key(kseed)? {
+            Some(()) => self.dyn_next_value(vseed).map(|()| Some(((), ()))),
+            None => Ok(None),
+        }
+    }
+
+    fn dyn_size_hint(&self) -> Option<usize> {
+        Some(self.iter.len())
+    }
+}
+
+struct JsonEnumAccess<'a, 'de> {
+    variant: Option<&'de str>,
+    value: Option<&'de Value>,
+    error: &'a mut Option<DeserializeError>,
+}
+
+impl JsonEnumAccess<'_, '_> {
+    #[cold]
+    fn invalid_type(&mut self, expected: &str) -> InplaceDeserializeError {
+        let unexpected = self.value.map_or(Unexpected::UnitVariant, unexpected);
+        fail(
+            self.error,
+            DeserializeError::invalid_type(unexpected, &expected),
+        )
+    }
+}
+
+impl<'de> EnumAccess<'de> for JsonEnumAccess<'_, 'de> {
+    fn dyn_variant(
+        &mut self,
+        seed: &mut dyn DeserializeSeed<'de>,
+    ) -> InplaceDeserializeResult<&mut dyn VariantAccess<'de>> {
+        match self.variant.take() {
+            Some(variant) => {
+                deserialize_key(seed, variant, self.error)?;
+                Ok(self)
+            }
+            None => Err(InplaceDeserializeError::NotEnumAccess),
+        }
+    }
+}
+
+impl<'de> VariantAccess<'de> for JsonEnumAccess<'_, 'de> {
+    fn dyn_unit_variant(&mut self) -> InplaceDeserializeResult<()> {
+        match self.value {
+            None | Some(Value::Null) => Ok(()),
+            Some(_) => Err(self.invalid_type("unit variant")),
+        }
+    }
+
+    fn dyn_newtype_variant(
+        &mut self,
+        seed: &mut dyn DeserializeSeed<'de>,
+    ) -> InplaceDeserializeResult<()> {
+        match self.value {
+            Some(value) => deserialize_value(seed, value, self.error),
+            None => Err(self.invalid_type("newtype variant")),
+        }
+    }
+
+    fn dyn_tuple_variant(
+        &mut self,
+        len: usize,
+        visitor: &mut dyn Visitor<'de>,
+    ) -> InplaceDeserializeResult<()> {
+        let _ = len;
+        match self.value {
+            Some(Value::Array(array)) => {
+                visit_array(array, visitor, self.error).map_err(|e| fail(self.error, e))
+            }
+            _ => Err(self.invalid_type("tuple variant")),
+        }
+    }
+
+    fn dyn_struct_variant(
+        &mut self,
+        fields: &'static [&'static str],
+        visitor: &mut dyn Visitor<'de>,
+    ) -> InplaceDeserializeResult<()> {
+        let _ = fields;
+        match self.value {
+            Some(Value::Object(object)) => {
+                visit_object(object, visitor, self.error).map_err(|e| fail(self.error, e))
+            }
+            _ => Err(self.invalid_type("struct variant")),
+        }
+    }
+}
@@ -0,0 +1,58 @@
+//! Deserializing from a string of JSON, see [`from_json_str`].
+
+use crate::de::{Deserializer, InplaceDeserializer};
+
+/// Deserializes an instance of `T` from a string of JSON, through a
+/// [`dyn Deserializer`].
+///
+/// This is the same as [`serde_json::from_str`], except that `T` is handed a
+/// `&mut dyn Deserializer` rather than the deserializer of `serde_json`
+/// itself, which spares wrapping and casting the deserializer by hand when
+/// only the dynamic path matters, e.g. in examples and tests. Strings and bytes
+/// of `T` may be borrowed from `s`, and trailing characters other than
+/// whitespace are rejected.
+///
+/// [`dyn Deserializer`]: Deserializer
+///
+/// # Errors
+///
+/// Returns the error of `serde_json` if `s` is not valid JSON, or if `T`
+/// fails to deserialize itself from it.
+///
+/// # Examples
+///
+/// ```
+/// #[derive(Debug, serde::Deserialize)]
+/// struct User<'a> {
+///     name: &'a str,
+///     admin: bool,
+/// }
+///
+/// let json = String::from(r#"{"name": "ferris", "admin": true}"#);
+/// let user: User<'_> = dyn_serde::de::from_json_str(&json).unwrap();
+/// assert_eq!(user.name, "ferris");
+/// assert!(user.admin);
+///
+/// let error = dyn_serde::de::from_json_str::<User<'_>>(r#"{"name": 1}"#).unwrap_err();
+/// assert_eq!(
+///     error.to_string(),
+///     "invalid type: integer `1`, expected a borrowed string at line 1 column 10",
+/// );
+/// ```
+pub fn from_json_str<'de, T>(s: &'de str) -> Result<T, serde_json::Error>
+where
+    T: serde::Deserialize<'de>,
+{
+    let mut deserializer = serde_json::Deserializer::from_str(s);
+    let mut inplace = <dyn Deserializer>::new(&mut deserializer);
+    let value = match (
+        T::deserialize(&mut inplace as &mut dyn Deserializer<'de>),
+        inplace,
+    ) {
+        (Ok(value), _) => value,
+        (Err(_), InplaceDeserializer::Error(error, _)) => return Err(error),
+        (Err(error), _) => return Err(error.into_error()),
+    };
+    deserializer.end()?;
+    Ok(value)
+}
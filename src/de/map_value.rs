@@ -0,0 +1,122 @@
+//! A [`Deserializer`] wrapper transforming the whole value, see
+//! [`MapDeserializer`].
+
+use core::fmt::{self, Debug, Formatter};
+
+use serde::Deserialize as _;
+use serde::de::Error as _;
+use serde_json::Value;
+
+use crate::de::middleware::{self, Hint};
+use crate::de::{DeserializeError, Deserializer, InplaceDeserializer, Visitor};
+use crate::de::{InplaceDeserializeError, InplaceDeserializeResult};
+
+/// A [`Deserializer`] wrapper which reads the entire value into a
+/// [`serde_json::Value`], transforms it, and then hands the transformed value
+/// to the visitor.
+///
+/// This is the counterpart of [`Buffered`], for preprocessing arbitrary input
+/// without touching the types it is deserialized into, e.g. renaming keys or
+/// coercing values. The value is read with `deserialize_any`, so the wrapped
+/// format must be self-describing. The transform is called once, and only one
+/// value can be deserialized.
+///
+/// As the value goes through the data model of JSON, strings are no longer
+/// borrowed from the input, and bytes are read as sequences of numbers.
+///
+/// The deserialization returns an error telling only that it has done
+/// unsuccessfully, while [`MapDeserializer::error`] tells why it has if the
+/// transformed value is rejected.
+///
+/// # Examples
+///
+/// ```
+/// # use serde::Deserialize as _;
+/// # use dyn_serde::Deserializer;
+/// # use dyn_serde::de::MapDeserializer;
+/// #
+/// let mut deserializer = serde_json::Deserializer::from_str(r#"{"n": "42"}"#);
+/// let mut deserializer = <dyn Deserializer>::new(&mut deserializer);
+/// let mut deserializer = MapDeserializer::new(&mut deserializer, |mut value| {
+///     value["n"] = value["n"].as_str().unwrap().parse::<u32>().unwrap().into();
+///     value
+/// });
+///
+/// #[derive(serde::Deserialize)]
+/// struct Count {
+///     n: u32,
+/// }
+///
+/// let count = Count::deserialize(&mut deserializer as &mut dyn Deserializer).unwrap();
+/// assert_eq!(count.n, 42);
+/// ```
+///
+/// [`Buffered`]: crate::ser::Buffered
+pub struct MapDeserializer<'a, 'de, F> {
+    deserializer: &'a mut dyn Deserializer<'de>,
+    transform: Option<F>,
+    error: Option<DeserializeError>,
+}
+
+impl<'a, 'de, F> MapDeserializer<'a, 'de, F>
+where
+    F: FnOnce(Value) -> Value,
+{
+    /// Wraps `deserializer`, applying `transform` to the value before it is
+    /// handed to the visitor.
+    #[must_use]
+    pub fn new(deserializer: &'a mut dyn Deserializer<'de>, transform: F) -> Self {
+        MapDeserializer {
+            deserializer,
+            transform: Some(transform),
+            error: None,
+        }
+    }
+
+    /// Returns the first error raised while deserializing from the transformed
+    /// value, if any.
+    #[must_use]
+    pub fn error(&self) -> Option<&DeserializeError> {
+        self.error.as_ref()
+    }
+
+    fn deserialize_hint(
+        &mut self,
+        hint: Hint,
+        visitor: &mut dyn Visitor<'de>,
+    ) -> InplaceDeserializeResult<()> {
+        let Some(transform) = self.transform.take() else {
+            return Err(InplaceDeserializeError::NotDeserializer);
+        };
+        let value = Value::deserialize(&mut *self.deserializer).map_err(|error| {
+            self.error.get_or_insert(error);
+            InplaceDeserializeError::Error
+        })?;
+
+        let mut deserializer = InplaceDeserializer::Deserializer((transform)(value));
+        let result = hint.forward(&mut deserializer, visitor);
+        if let InplaceDeserializer::Error(error, _) = deserializer {
+            self.error.get_or_insert(DeserializeError::custom(error));
+        }
+        result
+    }
+}
+
+impl<F> Debug for MapDeserializer<'_, '_, F> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("MapDeserializer")
+            .field("error", &self.error)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<'de, F> Deserializer<'de> for MapDeserializer<'_, 'de, F>
+where
+    F: FnOnce(Value) -> Value,
+{
+    middleware::forward_to_deserialize_hint!();
+
+    fn dyn_is_human_readable(&self) -> bool {
+        self.deserializer.dyn_is_human_readable()
+    }
+}
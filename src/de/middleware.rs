@@ -0,0 +1,982 @@
+//! The plumbing shared by the [`Deserializer`] wrappers of this crate.
+//!
+//! A wrapper implements [`Middleware`] and hands every `dyn_deserialize_*`
+//! call to [`deserialize`]. From then on, every visitor, deserialize seed,
+//! access and nested deserializer reachable from that call is wrapped as
+//! well, so that the hooks of the middleware observe the entire value rather
+//! than its outermost layer only.
+
+use core::fmt::{self, Display, Formatter};
+
+#[cfg(not(feature = "std"))]
+use alloc::string::String;
+#[cfg(not(feature = "std"))]
+use alloc::vec::Vec;
+
+use crate::de::{DeserializeResult, DeserializeSeed, Deserializer, EnumAccess};
+use crate::de::{InplaceDeserializeError, InplaceDeserializeResult, MapAccess, SeqAccess};
+use crate::de::{VariantAccess, Visitor};
+
+/// Implements every `dyn_deserialize_*` method of [`Deserializer`] by calling
+/// `self.deserialize_hint(hint, visitor)` with the corresponding [`Hint`].
+macro_rules! forward_to_deserialize_hint {
+    () => {
+        fn dyn_deserialize_any(
+            &mut self,
+            visitor: &mut dyn $crate::de::Visitor<'de>,
+        ) -> $crate::de::InplaceDeserializeResult<()> {
+            self.deserialize_hint($crate::de::middleware::Hint::Any, visitor)
+        }
+
+        fn dyn_deserialize_bool(
+            &mut self,
+            visitor: &mut dyn $crate::de::Visitor<'de>,
+        ) -> $crate::de::InplaceDeserializeResult<()> {
+            self.deserialize_hint($crate::de::middleware::Hint::Bool, visitor)
+        }
+
+        fn dyn_deserialize_i8(
+            &mut self,
+            visitor: &mut dyn $crate::de::Visitor<'de>,
+        ) -> $crate::de::InplaceDeserializeResult<()> {
+            self.deserialize_hint($crate::de::middleware::Hint::I8, visitor)
+        }
+
+        fn dyn_deserialize_i16(
+            &mut self,
+            visitor: &mut dyn $crate::de::Visitor<'de>,
+        ) -> $crate::de::InplaceDeserializeResult<()> {
+            self.deserialize_hint($crate::de::middleware::Hint::I16, visitor)
+        }
+
+        fn dyn_deserialize_i32(
+            &mut self,
+            visitor: &mut dyn $crate::de::Visitor<'de>,
+        ) -> $crate::de::InplaceDeserializeResult<()> {
+            self.deserialize_hint($crate::de::middleware::Hint::I32, visitor)
+        }
+
+        fn dyn_deserialize_i64(
+            &mut self,
+            visitor: &mut dyn $crate::de::Visitor<'de>,
+        ) -> $crate::de::InplaceDeserializeResult<()> {
+            self.deserialize_hint($crate::de::middleware::Hint::I64, visitor)
+        }
+
+        fn dyn_deserialize_i128(
+            &mut self,
+            visitor: &mut dyn $crate::de::Visitor<'de>,
+        ) -> $crate::de::InplaceDeserializeResult<()> {
+            self.deserialize_hint($crate::de::middleware::Hint::I128, visitor)
+        }
+
+        fn dyn_deserialize_u8(
+            &mut self,
+            visitor: &mut dyn $crate::de::Visitor<'de>,
+        ) -> $crate::de::InplaceDeserializeResult<()> {
+            self.deserialize_hint($crate::de::middleware::Hint::U8, visitor)
+        }
+
+        fn dyn_deserialize_u16(
+            &mut self,
+            visitor: &mut dyn $crate::de::Visitor<'de>,
+        ) -> $crate::de::InplaceDeserializeResult<()> {
+            self.deserialize_hint($crate::de::middleware::Hint::U16, visitor)
+        }
+
+        fn dyn_deserialize_u32(
+            &mut self,
+            visitor: &mut dyn $crate::de::Visitor<'de>,
+        ) -> $crate::de::InplaceDeserializeResult<()> {
+            self.deserialize_hint($crate::de::middleware::Hint::U32, visitor)
+        }
+
+        fn dyn_deserialize_u64(
+            &mut self,
+            visitor: &mut dyn $crate::de::Visitor<'de>,
+        ) -> $crate::de::InplaceDeserializeResult<()> {
+            self.deserialize_hint($crate::de::middleware::Hint::U64, visitor)
+        }
+
+        fn dyn_deserialize_u128(
+            &mut self,
+            visitor: &mut dyn $crate::de::Visitor<'de>,
+        ) -> $crate::de::InplaceDeserializeResult<()> {
+            self.deserialize_hint($crate::de::middleware::Hint::U128, visitor)
+        }
+
+        fn dyn_deserialize_f32(
+            &mut self,
+            visitor: &mut dyn $crate::de::Visitor<'de>,
+        ) -> $crate::de::InplaceDeserializeResult<()> {
+            self.deserialize_hint($crate::de::middleware::Hint::F32, visitor)
+        }
+
+        fn dyn_deserialize_f64(
+            &mut self,
+            visitor: &mut dyn $crate::de::Visitor<'de>,
+        ) -> $crate::de::InplaceDeserializeResult<()> {
+            self.deserialize_hint($crate::de::middleware::Hint::F64, visitor)
+        }
+
+        fn dyn_deserialize_char(
+            &mut self,
+            visitor: &mut dyn $crate::de::Visitor<'de>,
+        ) -> $crate::de::InplaceDeserializeResult<()> {
+            self.deserialize_hint($crate::de::middleware::Hint::Char, visitor)
+        }
+
+        fn dyn_deserialize_str(
+            &mut self,
+            visitor: &mut dyn $crate::de::Visitor<'de>,
+        ) -> $crate::de::InplaceDeserializeResult<()> {
+            self.deserialize_hint($crate::de::middleware::Hint::Str, visitor)
+        }
+
+        fn dyn_deserialize_string(
+            &mut self,
+            visitor: &mut dyn $crate::de::Visitor<'de>,
+        ) -> $crate::de::InplaceDeserializeResult<()> {
+            self.deserialize_hint($crate::de::middleware::Hint::String, visitor)
+        }
+
+        fn dyn_deserialize_bytes(
+            &mut self,
+            visitor: &mut dyn $crate::de::Visitor<'de>,
+        ) -> $crate::de::InplaceDeserializeResult<()> {
+            self.deserialize_hint($crate::de::middleware::Hint::Bytes, visitor)
+        }
+
+        fn dyn_deserialize_byte_buf(
+            &mut self,
+            visitor: &mut dyn $crate::de::Visitor<'de>,
+        ) -> $crate::de::InplaceDeserializeResult<()> {
+            self.deserialize_hint($crate::de::middleware::Hint::ByteBuf, visitor)
+        }
+
+        fn dyn_deserialize_option(
+            &mut self,
+            visitor: &mut dyn $crate::de::Visitor<'de>,
+        ) -> $crate::de::InplaceDeserializeResult<()> {
+            self.deserialize_hint($crate::de::middleware::Hint::Option, visitor)
+        }
+
+        fn dyn_deserialize_unit(
+            &mut self,
+            visitor: &mut dyn $crate::de::Visitor<'de>,
+        ) -> $crate::de::InplaceDeserializeResult<()> {
+            self.deserialize_hint($crate::de::middleware::Hint::Unit, visitor)
+        }
+
+        fn dyn_deserialize_unit_struct(
+            &mut self,
+            name: &'static str,
+            visitor: &mut dyn $crate::de::Visitor<'de>,
+        ) -> $crate::de::InplaceDeserializeResult<()> {
+            self.deserialize_hint($crate::de::middleware::Hint::UnitStruct(name), visitor)
+        }
+
+        fn dyn_deserialize_newtype_struct(
+            &mut self,
+            name: &'static str,
+            visitor: &mut dyn $crate::de::Visitor<'de>,
+        ) -> $crate::de::InplaceDeserializeResult<()> {
+            self.deserialize_hint($crate::de::middleware::Hint::NewtypeStruct(name), visitor)
+        }
+
+        fn dyn_deserialize_seq(
+            &mut self,
+            visitor: &mut dyn $crate::de::Visitor<'de>,
+        ) -> $crate::de::InplaceDeserializeResult<()> {
+            self.deserialize_hint($crate::de::middleware::Hint::Seq, visitor)
+        }
+
+        fn dyn_deserialize_tuple(
+            &mut self,
+            len: usize,
+            visitor: &mut dyn $crate::de::Visitor<'de>,
+        ) -> $crate::de::InplaceDeserializeResult<()> {
+            self.deserialize_hint($crate::de::middleware::Hint::Tuple(len), visitor)
+        }
+
+        fn dyn_deserialize_tuple_struct(
+            &mut self,
+            name: &'static str,
+            len: usize,
+            visitor: &mut dyn $crate::de::Visitor<'de>,
+        ) -> $crate::de::InplaceDeserializeResult<()> {
+            self.deserialize_hint(
+                $crate::de::middleware::Hint::TupleStruct(name, len),
+                visitor,
+            )
+        }
+
+        fn dyn_deserialize_map(
+            &mut self,
+            visitor: &mut dyn $crate::de::Visitor<'de>,
+        ) -> $crate::de::InplaceDeserializeResult<()> {
+            self.deserialize_hint($crate::de::middleware::Hint::Map, visitor)
+        }
+
+        fn dyn_deserialize_struct(
+            &mut self,
+            name: &'static str,
+            fields: &'static [&'static str],
+            visitor: &mut dyn $crate::de::Visitor<'de>,
+        ) -> $crate::de::InplaceDeserializeResult<()> {
+            self.deserialize_hint($crate::de::middleware::Hint::Struct(name, fields), visitor)
+        }
+
+        fn dyn_deserialize_enum(
+            &mut self,
+            name: &'static str,
+            variants: &'static [&'static str],
+            visitor: &mut dyn $crate::de::Visitor<'de>,
+        ) -> $crate::de::InplaceDeserializeResult<()> {
+            self.deserialize_hint($crate::de::middleware::Hint::Enum(name, variants), visitor)
+        }
+
+        fn dyn_deserialize_identifier(
+            &mut self,
+            visitor: &mut dyn $crate::de::Visitor<'de>,
+        ) -> $crate::de::InplaceDeserializeResult<()> {
+            self.deserialize_hint($crate::de::middleware::Hint::Identifier, visitor)
+        }
+
+        fn dyn_deserialize_ignored_any(
+            &mut self,
+            visitor: &mut dyn $crate::de::Visitor<'de>,
+        ) -> $crate::de::InplaceDeserializeResult<()> {
+            self.deserialize_hint($crate::de::middleware::Hint::IgnoredAny, visitor)
+        }
+    };
+}
+
+pub(crate) use forward_to_deserialize_hint;
+
+/// Deserializes a value through `middleware`.
+///
+/// The visitor is wrapped before [`Middleware::deserialize`] is called, so the
+/// hooks also observe everything nested inside the value.
+pub(crate) fn deserialize<'de, M>(
+    middleware: &M,
+    hint: Hint,
+    deserializer: &mut dyn Deserializer<'de>,
+    visitor: &mut dyn Visitor<'de>,
+) -> InplaceDeserializeResult<()>
+where
+    M: Middleware<'de> + ?Sized,
+{
+    let mut visitor = MiddlewareVisitor {
+        inner: visitor,
+        middleware,
+    };
+    middleware.deserialize(hint, deserializer, &mut visitor)
+}
+
+/// The hooks of a [`Deserializer`] wrapper.
+///
+/// Every hook receives the arguments of the intercepted call, which have
+/// already been wrapped, and forwards the call unchanged by default. State is
+/// kept behind interior mutability since the hooks are shared by every layer
+/// of the value.
+pub(crate) trait Middleware<'de> {
+    /// Intercepts a `dyn_deserialize_*` call.
+    fn deserialize(
+        &self,
+        hint: Hint,
+        deserializer: &mut dyn Deserializer<'de>,
+        visitor: &mut dyn Visitor<'de>,
+    ) -> InplaceDeserializeResult<()> {
+        hint.forward(deserializer, visitor)
+    }
+
+    /// Intercepts a `dyn_visit_*` call.
+    fn visit(
+        &self,
+        visit: Visit<'_, 'de>,
+        visitor: &mut dyn Visitor<'de>,
+    ) -> DeserializeResult<()> {
+        visit.forward(visitor)
+    }
+
+    /// Intercepts a `dyn_next_element` call.
+    fn next_element(
+        &self,
+        access: &mut dyn SeqAccess<'de>,
+        seed: &mut dyn DeserializeSeed<'de>,
+    ) -> InplaceDeserializeResult<Option<()>> {
+        access.dyn_next_element(seed)
+    }
+
+    /// Intercepts a `dyn_next_key` call.
+    fn next_key(
+        &self,
+        access: &mut dyn MapAccess<'de>,
+        seed: &mut dyn DeserializeSeed<'de>,
+    ) -> InplaceDeserializeResult<Option<()>> {
+        access.dyn_next_key(seed)
+    }
+
+    /// Intercepts a `dyn_next_value` call.
+    fn next_value(
+        &self,
+        access: &mut dyn MapAccess<'de>,
+        seed: &mut dyn DeserializeSeed<'de>,
+    ) -> InplaceDeserializeResult<()> {
+        access.dyn_next_value(seed)
+    }
+
+    /// Intercepts a `dyn_variant` call.
+    fn variant<'a>(
+        &self,
+        access: &'a mut dyn EnumAccess<'de>,
+        seed: &mut dyn DeserializeSeed<'de>,
+    ) -> InplaceDeserializeResult<&'a mut dyn VariantAccess<'de>> {
+        access.dyn_variant(seed)
+    }
+
+    /// Intercepts a call on the [`VariantAccess`].
+    fn variant_access(
+        &self,
+        call: VariantCall<'_, 'de>,
+        access: &mut dyn VariantAccess<'de>,
+    ) -> InplaceDeserializeResult<()> {
+        call.forward(access)
+    }
+}
+
+// enum Hint
+// ----------------------------------------------------------------------------
+/// A `dyn_deserialize_*` call, without its visitor.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum Hint {
+    Any,
+    Bool,
+    I8,
+    I16,
+    I32,
+    I64,
+    I128,
+    U8,
+    U16,
+    U32,
+    U64,
+    U128,
+    F32,
+    F64,
+    Char,
+    Str,
+    String,
+    Bytes,
+    ByteBuf,
+    Option,
+    Unit,
+    UnitStruct(&'static str),
+    NewtypeStruct(&'static str),
+    Seq,
+    Tuple(usize),
+    TupleStruct(&'static str, usize),
+    Map,
+    Struct(&'static str, &'static [&'static str]),
+    Enum(&'static str, &'static [&'static str]),
+    Identifier,
+    IgnoredAny,
+}
+
+impl Hint {
+    /// Calls the `dyn_deserialize_*` method of `deserializer`.
+    pub(crate) fn forward<'de>(
+        self,
+        deserializer: &mut dyn Deserializer<'de>,
+        visitor: &mut dyn Visitor<'de>,
+    ) -> InplaceDeserializeResult<()> {
+        match self {
+            Hint::Any => deserializer.dyn_deserialize_any(visitor),
+            Hint::Bool => deserializer.dyn_deserialize_bool(visitor),
+            Hint::I8 => deserializer.dyn_deserialize_i8(visitor),
+            Hint::I16 => deserializer.dyn_deserialize_i16(visitor),
+            Hint::I32 => deserializer.dyn_deserialize_i32(visitor),
+            Hint::I64 => deserializer.dyn_deserialize_i64(visitor),
+            Hint::I128 => deserializer.dyn_deserialize_i128(visitor),
+            Hint::U8 => deserializer.dyn_deserialize_u8(visitor),
+            Hint::U16 => deserializer.dyn_deserialize_u16(visitor),
+            Hint::U32 => deserializer.dyn_deserialize_u32(visitor),
+            Hint::U64 => deserializer.dyn_deserialize_u64(visitor),
+            Hint::U128 => deserializer.dyn_deserialize_u128(visitor),
+            Hint::F32 => deserializer.dyn_deserialize_f32(visitor),
+            Hint::F64 => deserializer.dyn_deserialize_f64(visitor),
+            Hint::Char => deserializer.dyn_deserialize_char(visitor),
+            Hint::Str => deserializer.dyn_deserialize_str(visitor),
+            Hint::String => deserializer.dyn_deserialize_string(visitor),
+            Hint::Bytes => deserializer.dyn_deserialize_bytes(visitor),
+            Hint::ByteBuf => deserializer.dyn_deserialize_byte_buf(visitor),
+            Hint::Option => deserializer.dyn_deserialize_option(visitor),
+            Hint::Unit => deserializer.dyn_deserialize_unit(visitor),
+            Hint::UnitStruct(name) => deserializer.dyn_deserialize_unit_struct(name, visitor),
+            Hint::NewtypeStruct(name) => deserializer.dyn_deserialize_newtype_struct(name, visitor),
+            Hint::Seq => deserializer.dyn_deserialize_seq(visitor),
+            Hint::Tuple(len) => deserializer.dyn_deserialize_tuple(len, visitor),
+            Hint::TupleStruct(name, len) => {
+                deserializer.dyn_deserialize_tuple_struct(name, len, visitor)
+            }
+            Hint::Map => deserializer.dyn_deserialize_map(visitor),
+            Hint::Struct(name, fields) => {
+                deserializer.dyn_deserialize_struct(name, fields, visitor)
+            }
+            Hint::Enum(name, variants) => {
+                deserializer.dyn_deserialize_enum(name, variants, visitor)
+            }
+            Hint::Identifier => deserializer.dyn_deserialize_identifier(visitor),
+            Hint::IgnoredAny => deserializer.dyn_deserialize_ignored_any(visitor),
+        }
+    }
+
+    /// Returns the name of the method, without the `dyn_` prefix.
+    pub(crate) fn name(self) -> &'static str {
+        match self {
+            Hint::Any => "deserialize_any",
+            Hint::Bool => "deserialize_bool",
+            Hint::I8 => "deserialize_i8",
+            Hint::I16 => "deserialize_i16",
+            Hint::I32 => "deserialize_i32",
+            Hint::I64 => "deserialize_i64",
+            Hint::I128 => "deserialize_i128",
+            Hint::U8 => "deserialize_u8",
+            Hint::U16 => "deserialize_u16",
+            Hint::U32 => "deserialize_u32",
+            Hint::U64 => "deserialize_u64",
+            Hint::U128 => "deserialize_u128",
+            Hint::F32 => "deserialize_f32",
+            Hint::F64 => "deserialize_f64",
+            Hint::Char => "deserialize_char",
+            Hint::Str => "deserialize_str",
+            Hint::String => "deserialize_string",
+            Hint::Bytes => "deserialize_bytes",
+            Hint::ByteBuf => "deserialize_byte_buf",
+            Hint::Option => "deserialize_option",
+            Hint::Unit => "deserialize_unit",
+            Hint::UnitStruct(_) => "deserialize_unit_struct",
+            Hint::NewtypeStruct(_) => "deserialize_newtype_struct",
+            Hint::Seq => "deserialize_seq",
+            Hint::Tuple(_) => "deserialize_tuple",
+            Hint::TupleStruct(..) => "deserialize_tuple_struct",
+            Hint::Map => "deserialize_map",
+            Hint::Struct(..) => "deserialize_struct",
+            Hint::Enum(..) => "deserialize_enum",
+            Hint::Identifier => "deserialize_identifier",
+            Hint::IgnoredAny => "deserialize_ignored_any",
+        }
+    }
+}
+
+impl Display for Hint {
+    /// Formats the call like `deserialize_struct("Point", ["x", "y"])`.
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())?;
+        match self {
+            Hint::UnitStruct(name) | Hint::NewtypeStruct(name) => write!(f, "({name:?})"),
+            Hint::Tuple(len) => write!(f, "({len})"),
+            Hint::TupleStruct(name, len) => write!(f, "({name:?}, {len})"),
+            Hint::Struct(name, fields) => write!(f, "({name:?}, {fields:?})"),
+            Hint::Enum(name, variants) => write!(f, "({name:?}, {variants:?})"),
+            _ => Ok(()),
+        }
+    }
+}
+
+// enum Visit
+// ----------------------------------------------------------------------------
+/// A `dyn_visit_*` call, without its visitor.
+pub(crate) enum Visit<'a, 'de> {
+    Bool(bool),
+    I8(i8),
+    I16(i16),
+    I32(i32),
+    I64(i64),
+    I128(i128),
+    U8(u8),
+    U16(u16),
+    U32(u32),
+    U64(u64),
+    U128(u128),
+    F32(f32),
+    F64(f64),
+    Char(char),
+    Str(&'a str),
+    BorrowedStr(&'de str),
+    String(String),
+    Bytes(&'a [u8]),
+    BorrowedBytes(&'de [u8]),
+    ByteBuf(Vec<u8>),
+    None,
+    Some(&'a mut dyn Deserializer<'de>),
+    Unit,
+    NewtypeStruct(&'a mut dyn Deserializer<'de>),
+    Seq(&'a mut dyn SeqAccess<'de>),
+    Map(&'a mut dyn MapAccess<'de>),
+    Enum(&'a mut dyn EnumAccess<'de>),
+}
+
+impl<'de> Visit<'_, 'de> {
+    /// Calls the `dyn_visit_*` method of `visitor`.
+    pub(crate) fn forward(self, visitor: &mut dyn Visitor<'de>) -> DeserializeResult<()> {
+        match self {
+            Visit::Bool(v) => visitor.dyn_visit_bool(v),
+            Visit::I8(v) => visitor.dyn_visit_i8(v),
+            Visit::I16(v) => visitor.dyn_visit_i16(v),
+            Visit::I32(v) => visitor.dyn_visit_i32(v),
+            Visit::I64(v) => visitor.dyn_visit_i64(v),
+            Visit::I128(v) => visitor.dyn_visit_i128(v),
+            Visit::U8(v) => visitor.dyn_visit_u8(v),
+            Visit::U16(v) => visitor.dyn_visit_u16(v),
+            Visit::U32(v) => visitor.dyn_visit_u32(v),
+            Visit::U64(v) => visitor.dyn_visit_u64(v),
+            Visit::U128(v) => visitor.dyn_visit_u128(v),
+            Visit::F32(v) => visitor.dyn_visit_f32(v),
+            Visit::F64(v) => visitor.dyn_visit_f64(v),
+            Visit::Char(v) => visitor.dyn_visit_char(v),
+            Visit::Str(v) => visitor.dyn_visit_str(v),
+            Visit::BorrowedStr(v) => visitor.dyn_visit_borrowed_str(v),
+            Visit::String(v) => visitor.dyn_visit_string(v),
+            Visit::Bytes(v) => visitor.dyn_visit_bytes(v),
+            Visit::BorrowedBytes(v) => visitor.dyn_visit_borrowed_bytes(v),
+            Visit::ByteBuf(v) => visitor.dyn_visit_byte_buf(v),
+            Visit::None => visitor.dyn_visit_none(),
+            Visit::Some(deserializer) => visitor.dyn_visit_some(deserializer),
+            Visit::Unit => visitor.dyn_visit_unit(),
+            Visit::NewtypeStruct(deserializer) => visitor.dyn_visit_newtype_struct(deserializer),
+            Visit::Seq(access) => visitor.dyn_visit_seq(access),
+            Visit::Map(access) => visitor.dyn_visit_map(access),
+            Visit::Enum(access) => visitor.dyn_visit_enum(access),
+        }
+    }
+
+    /// Returns the name of the method, without the `dyn_` prefix.
+    pub(crate) fn name(&self) -> &'static str {
+        match self {
+            Visit::Bool(_) => "visit_bool",
+            Visit::I8(_) => "visit_i8",
+            Visit::I16(_) => "visit_i16",
+            Visit::I32(_) => "visit_i32",
+            Visit::I64(_) => "visit_i64",
+            Visit::I128(_) => "visit_i128",
+            Visit::U8(_) => "visit_u8",
+            Visit::U16(_) => "visit_u16",
+            Visit::U32(_) => "visit_u32",
+            Visit::U64(_) => "visit_u64",
+            Visit::U128(_) => "visit_u128",
+            Visit::F32(_) => "visit_f32",
+            Visit::F64(_) => "visit_f64",
+            Visit::Char(_) => "visit_char",
+            Visit::Str(_) => "visit_str",
+            Visit::BorrowedStr(_) => "visit_borrowed_str",
+            Visit::String(_) => "visit_string",
+            Visit::Bytes(_) => "visit_bytes",
+            Visit::BorrowedBytes(_) => "visit_borrowed_bytes",
+            Visit::ByteBuf(_) => "visit_byte_buf",
+            Visit::None => "visit_none",
+            Visit::Some(_) => "visit_some",
+            Visit::Unit => "visit_unit",
+            Visit::NewtypeStruct(_) => "visit_newtype_struct",
+            Visit::Seq(_) => "visit_seq",
+            Visit::Map(_) => "visit_map",
+            Visit::Enum(_) => "visit_enum",
+        }
+    }
+}
+
+impl Display for Visit<'_, '_> {
+    /// Formats the call like `visit_str("x")`.
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())?;
+        match self {
+            Visit::Bool(v) => write!(f, "({v})"),
+            Visit::I8(v) => write!(f, "({v})"),
+            Visit::I16(v) => write!(f, "({v})"),
+            Visit::I32(v) => write!(f, "({v})"),
+            Visit::I64(v) => write!(f, "({v})"),
+            Visit::I128(v) => write!(f, "({v})"),
+            Visit::U8(v) => write!(f, "({v})"),
+            Visit::U16(v) => write!(f, "({v})"),
+            Visit::U32(v) => write!(f, "({v})"),
+            Visit::U64(v) => write!(f, "({v})"),
+            Visit::U128(v) => write!(f, "({v})"),
+            Visit::F32(v) => write!(f, "({v:?})"),
+            Visit::F64(v) => write!(f, "({v:?})"),
+            Visit::Char(v) => write!(f, "({v:?})"),
+            Visit::Str(v) | Visit::BorrowedStr(v) => write!(f, "({v:?})"),
+            Visit::String(v) => write!(f, "({v:?})"),
+            Visit::Bytes(v) | Visit::BorrowedBytes(v) => write!(f, "({v:?})"),
+            Visit::ByteBuf(v) => write!(f, "({v:?})"),
+            _ => Ok(()),
+        }
+    }
+}
+
+// enum VariantCall
+// ----------------------------------------------------------------------------
+/// A call on the [`VariantAccess`], without the access itself.
+pub(crate) enum VariantCall<'a, 'de> {
+    Unit,
+    Newtype(&'a mut dyn DeserializeSeed<'de>),
+    Tuple(usize, &'a mut dyn Visitor<'de>),
+    Struct(&'static [&'static str], &'a mut dyn Visitor<'de>),
+}
+
+impl<'de> VariantCall<'_, 'de> {
+    /// Calls the method of `access`.
+    pub(crate) fn forward(
+        self,
+        access: &mut dyn VariantAccess<'de>,
+    ) -> InplaceDeserializeResult<()> {
+        match self {
+            VariantCall::Unit => access.dyn_unit_variant(),
+            VariantCall::Newtype(seed) => access.dyn_newtype_variant(seed),
+            VariantCall::Tuple(len, visitor) => access.dyn_tuple_variant(len, visitor),
+            VariantCall::Struct(fields, visitor) => access.dyn_struct_variant(fields, visitor),
+        }
+    }
+}
+
+impl Display for VariantCall<'_, '_> {
+    /// Formats the call like `struct_variant(["x", "y"])`.
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            VariantCall::Unit => f.write_str("unit_variant"),
+            VariantCall::Newtype(_) => f.write_str("newtype_variant"),
+            VariantCall::Tuple(len, _) => write!(f, "tuple_variant({len})"),
+            VariantCall::Struct(fields, _) => write!(f, "struct_variant({fields:?})"),
+        }
+    }
+}
+
+// wrappers
+// ----------------------------------------------------------------------------
+struct MiddlewareDeserializer<'a, 'de, M: ?Sized> {
+    inner: &'a mut dyn Deserializer<'de>,
+    middleware: &'a M,
+}
+
+impl<'de, M: Middleware<'de> + ?Sized> MiddlewareDeserializer<'_, 'de, M> {
+    fn deserialize_hint(
+        &mut self,
+        hint: Hint,
+        visitor: &mut dyn Visitor<'de>,
+    ) -> InplaceDeserializeResult<()> {
+        deserialize(self.middleware, hint, self.inner, visitor)
+    }
+}
+
+impl<'de, M: Middleware<'de> + ?Sized> Deserializer<'de> for MiddlewareDeserializer<'_, 'de, M> {
+    forward_to_deserialize_hint!();
+
+    fn dyn_is_human_readable(&self) -> bool {
+        self.inner.dyn_is_human_readable()
+    }
+}
+
+struct MiddlewareSeed<'a, 'de, M: ?Sized> {
+    inner: &'a mut dyn DeserializeSeed<'de>,
+    middleware: &'a M,
+}
+
+impl<'de, M: Middleware<'de> + ?Sized> DeserializeSeed<'de> for MiddlewareSeed<'_, 'de, M> {
+    fn dyn_deserialize(
+        &mut self,
+        deserializer: &mut dyn Deserializer<'de>,
+    ) -> DeserializeResult<()> {
+        self.inner.dyn_deserialize(&mut MiddlewareDeserializer {
+            inner: deserializer,
+            middleware: self.middleware,
+        })
+    }
+}
+
+struct MiddlewareVisitor<'a, 'de, M: ?Sized> {
+    inner: &'a mut dyn Visitor<'de>,
+    middleware: &'a M,
+}
+
+impl<'de, M: Middleware<'de> + ?Sized> MiddlewareVisitor<'_, 'de, M> {
+    fn visit(&mut self, visit: Visit<'_, 'de>) -> DeserializeResult<()> {
+        self.middleware.visit(visit, self.inner)
+    }
+}
+
+impl<'de, M: Middleware<'de> + ?Sized> Visitor<'de> for MiddlewareVisitor<'_, 'de, M> {
+    fn dyn_expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        self.inner.dyn_expecting(formatter)
+    }
+
+    fn dyn_visit_bool(&mut self, v: bool) -> DeserializeResult<()> {
+        self.visit(Visit::Bool(v))
+    }
+
+    fn dyn_visit_i8(&mut self, v: i8) -> DeserializeResult<()> {
+        self.visit(Visit::I8(v))
+    }
+
+    fn dyn_visit_i16(&mut self, v: i16) -> DeserializeResult<()> {
+        self.visit(Visit::I16(v))
+    }
+
+    fn dyn_visit_i32(&mut self, v: i32) -> DeserializeResult<()> {
+        self.visit(Visit::I32(v))
+    }
+
+    fn dyn_visit_i64(&mut self, v: i64) -> DeserializeResult<()> {
+        self.visit(Visit::I64(v))
+    }
+
+    fn dyn_visit_i128(&mut self, v: i128) -> DeserializeResult<()> {
+        self.visit(Visit::I128(v))
+    }
+
+    fn dyn_visit_u8(&mut self, v: u8) -> DeserializeResult<()> {
+        self.visit(Visit::U8(v))
+    }
+
+    fn dyn_visit_u16(&mut self, v: u16) -> DeserializeResult<()> {
+        self.visit(Visit::U16(v))
+    }
+
+    fn dyn_visit_u32(&mut self, v: u32) -> DeserializeResult<()> {
+        self.visit(Visit::U32(v))
+    }
+
+    fn dyn_visit_u64(&mut self, v: u64) -> DeserializeResult<()> {
+        self.visit(Visit::U64(v))
+    }
+
+    fn dyn_visit_u128(&mut self, v: u128) -> DeserializeResult<()> {
+        self.visit(Visit::U128(v))
+    }
+
+    fn dyn_visit_f32(&mut self, v: f32) -> DeserializeResult<()> {
+        self.visit(Visit::F32(v))
+    }
+
+    fn dyn_visit_f64(&mut self, v: f64) -> DeserializeResult<()> {
+        self.visit(Visit::F64(v))
+    }
+
+    fn dyn_visit_char(&mut self, v: char) -> DeserializeResult<()> {
+        self.visit(Visit::Char(v))
+    }
+
+    fn dyn_visit_str(&mut self, v: &str) -> DeserializeResult<()> {
+        self.visit(Visit::Str(v))
+    }
+
+    fn dyn_visit_borrowed_str(&mut self, v: &'de str) -> DeserializeResult<()> {
+        self.visit(Visit::BorrowedStr(v))
+    }
+
+    fn dyn_visit_string(&mut self, v: String) -> DeserializeResult<()> {
+        self.visit(Visit::String(v))
+    }
+
+    fn dyn_visit_bytes(&mut self, v: &[u8]) -> DeserializeResult<()> {
+        self.visit(Visit::Bytes(v))
+    }
+
+    fn dyn_visit_borrowed_bytes(&mut self, v: &'de [u8]) -> DeserializeResult<()> {
+        self.visit(Visit::BorrowedBytes(v))
+    }
+
+    fn dyn_visit_byte_buf(&mut self, v: Vec<u8>) -> DeserializeResult<()> {
+        self.visit(Visit::ByteBuf(v))
+    }
+
+    fn dyn_visit_none(&mut self) -> DeserializeResult<()> {
+        self.visit(Visit::None)
+    }
+
+    fn dyn_visit_some(
+        &mut self,
+        deserializer: &mut dyn Deserializer<'de>,
+    ) -> DeserializeResult<()> {
+        self.visit(Visit::Some(&mut MiddlewareDeserializer {
+            inner: deserializer,
+            middleware: self.middleware,
+        }))
+    }
+
+    fn dyn_visit_unit(&mut self) -> DeserializeResult<()> {
+        self.visit(Visit::Unit)
+    }
+
+    fn dyn_visit_newtype_struct(
+        &mut self,
+        deserializer: &mut dyn Deserializer<'de>,
+    ) -> DeserializeResult<()> {
+        self.visit(Visit::NewtypeStruct(&mut MiddlewareDeserializer {
+            inner: deserializer,
+            middleware: self.middleware,
+        }))
+    }
+
+    fn dyn_visit_seq(&mut self, access: &mut dyn SeqAccess<'de>) -> DeserializeResult<()> {
+        self.visit(Visit::Seq(&mut MiddlewareSeqAccess {
+            inner: access,
+            middleware: self.middleware,
+        }))
+    }
+
+    fn dyn_visit_map(&mut self, access: &mut dyn MapAccess<'de>) -> DeserializeResult<()> {
+        self.visit(Visit::Map(&mut MiddlewareMapAccess {
+            inner: access,
+            middleware: self.middleware,
+        }))
+    }
+
+    fn dyn_visit_enum(&mut self, access: &mut dyn EnumAccess<'de>) -> DeserializeResult<()> {
+        self.visit(Visit::Enum(&mut MiddlewareEnumAccess {
+            inner: Some(access),
+            variant: None,
+            middleware: self.middleware,
+        }))
+    }
+}
+
+struct MiddlewareSeqAccess<'a, 'de, M: ?Sized> {
+    inner: &'a mut dyn SeqAccess<'de>,
+    middleware: &'a M,
+}
+
+impl<'de, M: Middleware<'de> + ?Sized> SeqAccess<'de> for MiddlewareSeqAccess<'_, 'de, M> {
+    fn dyn_next_element(
+        &mut self,
+        seed: &mut dyn DeserializeSeed<'de>,
+    ) -> InplaceDeserializeResult<Option<()>> {
+        let mut seed = MiddlewareSeed {
+            inner: seed,
+            middleware: self.middleware,
+        };
+        self.middleware.next_element(self.inner, &mut seed)
+    }
+
+    fn dyn_size_hint(&self) -> Option<usize> {
+        self.inner.dyn_size_hint()
+    }
+}
+
+struct MiddlewareMapAccess<'a, 'de, M: ?Sized> {
+    inner: &'a mut dyn MapAccess<'de>,
+    middleware: &'a M,
+}
+
+impl<'de, M: Middleware<'de> + ?Sized> MapAccess<'de> for MiddlewareMapAccess<'_, 'de, M> {
+    fn dyn_next_key(
+        &mut self,
+        seed: &mut dyn DeserializeSeed<'de>,
+    ) -> InplaceDeserializeResult<Option<()>> {
+        let mut seed = MiddlewareSeed {
+            inner: seed,
+            middleware: self.middleware,
+        };
+        self.middleware.next_key(self.inner, &mut seed)
+    }
+
+    fn dyn_next_value(
+        &mut self,
+        seed: &mut dyn DeserializeSeed<'de>,
+    ) -> InplaceDeserializeResult<()> {
+        let mut seed = MiddlewareSeed {
+            inner: seed,
+            middleware: self.middleware,
+        };
+        self.middleware.next_value(self.inner, &mut seed)
+    }
+
+    fn dyn_next_entry(
+        &mut self,
+        kseed: &mut dyn DeserializeSeed<'de>,
+        vseed: &mut dyn DeserializeSeed<'de>,
+    ) -> InplaceDeserializeResult<Option<((), ())>> {
+        // goes through the hooks of both the key and the value.
+        match self.dyn_next_key(kseed)? {
+            Some(()) => self.dyn_next_value(vseed).map(|()| Some(((), ()))),
+            None => Ok(None),
+        }
+    }
+
+    fn dyn_size_hint(&self) -> Option<usize> {
+        self.inner.dyn_size_hint()
+    }
+}
+
+struct MiddlewareEnumAccess<'a, 'de, M: ?Sized> {
+    inner: Option<&'a mut dyn EnumAccess<'de>>,
+    variant: Option<&'a mut dyn VariantAccess<'de>>,
+    middleware: &'a M,
+}
+
+impl<'de, M: Middleware<'de> + ?Sized> MiddlewareEnumAccess<'_, 'de, M> {
+    fn variant_access(&mut self, call: VariantCall<'_, 'de>) -> InplaceDeserializeResult<()> {
+        match self.variant.take() {
+            Some(access) => self.middleware.variant_access(call, access),
+            None => Err(InplaceDeserializeError::NotVariantAccess),
+        }
+    }
+}
+
+impl<'de, M: Middleware<'de> + ?Sized> EnumAccess<'de> for MiddlewareEnumAccess<'_, 'de, M> {
+    fn dyn_variant(
+        &mut self,
+        seed: &mut dyn DeserializeSeed<'de>,
+    ) -> InplaceDeserializeResult<&mut dyn VariantAccess<'de>> {
+        let Some(access) = self.inner.take() else {
+            return Err(InplaceDeserializeError::NotEnumAccess);
+        };
+
+        let mut seed = MiddlewareSeed {
+            inner: seed,
+            middleware: self.middleware,
+        };
+        self.variant = Some(self.middleware.variant(access, &mut seed)?);
+        Ok(self)
+    }
+}
+
+impl<'de, M: Middleware<'de> + ?Sized> VariantAccess<'de> for MiddlewareEnumAccess<'_, 'de, M> {
+    fn dyn_unit_variant(&mut self) -> InplaceDeserializeResult<()> {
+        self.variant_access(VariantCall::Unit)
+    }
+
+    fn dyn_newtype_variant(
+        &mut self,
+        seed: &mut dyn DeserializeSeed<'de>,
+    ) -> InplaceDeserializeResult<()> {
+        let mut seed = MiddlewareSeed {
+            inner: seed,
+            middleware: self.middleware,
+        };
+        self.variant_access(VariantCall::Newtype(&mut seed))
+    }
+
+    fn dyn_tuple_variant(
+        &mut self,
+        len: usize,
+        visitor: &mut dyn Visitor<'de>,
+    ) -> InplaceDeserializeResult<()> {
+        let mut visitor = MiddlewareVisitor {
+            inner: visitor,
+            middleware: self.middleware,
+        };
+        self.variant_access(VariantCall::Tuple(len, &mut visitor))
+    }
+
+    fn dyn_struct_variant(
+        &mut self,
+        fields: &'static [&'static str],
+        visitor: &mut dyn Visitor<'de>,
+    ) -> InplaceDeserializeResult<()> {
+        let mut visitor = MiddlewareVisitor {
+            inner: visitor,
+            middleware: self.middleware,
+        };
+        self.variant_access(VariantCall::Struct(fields, &mut visitor))
+    }
+}
@@ -0,0 +1,179 @@
+//! A [`Deserializer`] wrapper reading `null` as an empty collection, see
+//! [`NullAsEmpty`].
+
+use core::cell::Cell;
+use core::fmt::{self, Debug, Formatter};
+
+use serde::de::Error as _;
+
+use crate::de::middleware::{self, Hint, Middleware, Visit};
+use crate::de::{DeserializeError, DeserializeResult, DeserializeSeed, Deserializer};
+use crate::de::{InplaceDeserializeError, InplaceDeserializeResult};
+use crate::de::{MapAccess, SeqAccess, Visitor};
+
+/// A [`Deserializer`] wrapper which reads `null` as an empty sequence where a
+/// sequence is expected, and as an empty map where a map is expected.
+///
+/// Lenient APIs often send `null` for a list or an object without members,
+/// which fails to deserialize into a `Vec` or a `HashMap`. With `NullAsEmpty`
+/// the visitor of the collection sees a sequence or a map without elements
+/// instead, so it yields an empty collection, while any other value is
+/// handed to it unchanged. It is the counterpart of [`ser::EmptyAsNull`], and
+/// applies to the collections at every depth of the value. Structs and
+/// tuples are never read from `null`, nor are options, which still read it as
+/// `None`.
+///
+/// Telling `null` apart relies on `deserialize_any` for the sequences and the
+/// maps, so the format must be self-describing, as JSON is.
+///
+/// [`ser::EmptyAsNull`]: crate::ser::EmptyAsNull
+///
+/// # Examples
+///
+/// ```
+/// # use serde::Deserialize as _;
+/// # use dyn_serde::Deserializer;
+/// # use dyn_serde::de::NullAsEmpty;
+/// #
+/// #[derive(serde::Deserialize)]
+/// struct User {
+///     roles: Vec<String>,
+/// }
+///
+/// let mut deserializer = serde_json::Deserializer::from_str(r#"{"roles": null}"#);
+/// let mut deserializer = <dyn Deserializer>::new(&mut deserializer);
+/// let mut deserializer = NullAsEmpty::new(&mut deserializer);
+///
+/// let user = User::deserialize(&mut deserializer as &mut dyn Deserializer).unwrap();
+/// assert!(user.roles.is_empty());
+/// ```
+pub struct NullAsEmpty<'a, 'de> {
+    deserializer: &'a mut dyn Deserializer<'de>,
+    empty: Empty,
+}
+
+impl<'a, 'de> NullAsEmpty<'a, 'de> {
+    /// Wraps `deserializer`, reading `null` as an empty sequence or map.
+    #[must_use]
+    pub fn new(deserializer: &'a mut dyn Deserializer<'de>) -> Self {
+        NullAsEmpty {
+            deserializer,
+            empty: Empty {
+                expecting: Cell::new(None),
+            },
+        }
+    }
+
+    fn deserialize_hint(
+        &mut self,
+        hint: Hint,
+        visitor: &mut dyn Visitor<'de>,
+    ) -> InplaceDeserializeResult<()> {
+        middleware::deserialize(&self.empty, hint, self.deserializer, visitor)
+    }
+}
+
+impl Debug for NullAsEmpty<'_, '_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("NullAsEmpty").finish_non_exhaustive()
+    }
+}
+
+impl<'de> Deserializer<'de> for NullAsEmpty<'_, 'de> {
+    middleware::forward_to_deserialize_hint!();
+
+    fn dyn_is_human_readable(&self) -> bool {
+        self.deserializer.dyn_is_human_readable()
+    }
+}
+
+struct Empty {
+    /// What the value whose visit comes next is deserialized as, if it is a
+    /// sequence or a map.
+    expecting: Cell<Option<Hint>>,
+}
+
+impl<'de> Middleware<'de> for Empty {
+    fn deserialize(
+        &self,
+        hint: Hint,
+        deserializer: &mut dyn Deserializer<'de>,
+        visitor: &mut dyn Visitor<'de>,
+    ) -> InplaceDeserializeResult<()> {
+        if let Hint::Seq | Hint::Map = hint {
+            self.expecting.set(Some(hint));
+            // formats reject `null` before visiting it.
+            Hint::Any.forward(deserializer, visitor)
+        } else {
+            self.expecting.set(None);
+            hint.forward(deserializer, visitor)
+        }
+    }
+
+    fn visit(
+        &self,
+        visit: Visit<'_, 'de>,
+        visitor: &mut dyn Visitor<'de>,
+    ) -> DeserializeResult<()> {
+        let mut nothing = Nothing { error: None };
+        let result = match (self.expecting.take(), visit) {
+            (Some(Hint::Seq), Visit::Unit | Visit::None) => visitor.dyn_visit_seq(&mut nothing),
+            (Some(Hint::Map), Visit::Unit | Visit::None) => visitor.dyn_visit_map(&mut nothing),
+            (_, visit) => return visit.forward(visitor),
+        };
+        result.map_err(|error| nothing.error.take().unwrap_or(error))
+    }
+}
+
+/// A sequence or a map without elements.
+struct Nothing {
+    /// The error raised when a value is asked for, if any.
+    error: Option<DeserializeError>,
+}
+
+impl<'de> SeqAccess<'de> for Nothing {
+    fn dyn_next_element(
+        &mut self,
+        seed: &mut dyn DeserializeSeed<'de>,
+    ) -> InplaceDeserializeResult<Option<()>> {
+        let _ = seed;
+        Ok(None)
+    }
+
+    fn dyn_size_hint(&self) -> Option<usize> {
+        Some(0)
+    }
+}
+
+impl<'de> MapAccess<'de> for Nothing {
+    fn dyn_next_key(
+        &mut self,
+        seed: &mut dyn DeserializeSeed<'de>,
+    ) -> InplaceDeserializeResult<Option<()>> {
+        let _ = seed;
+        Ok(None)
+    }
+
+    fn dyn_next_value(
+        &mut self,
+        seed: &mut dyn DeserializeSeed<'de>,
+    ) -> InplaceDeserializeResult<()> {
+        let _ = seed;
+        self.error
+            .get_or_insert(DeserializeError::custom("value is missing"));
+        Err(InplaceDeserializeError::Error)
+    }
+
+    fn dyn_next_entry(
+        &mut self,
+        kseed: &mut dyn DeserializeSeed<'de>,
+        vseed: &mut dyn DeserializeSeed<'de>,
+    ) -> InplaceDeserializeResult<Option<((), ())>> {
+        let _ = (kseed, vseed);
+        Ok(None)
+    }
+
+    fn dyn_size_hint(&self) -> Option<usize> {
+        Some(0)
+    }
+}
@@ -0,0 +1,203 @@
+//! A [`Deserializer`] wrapper reading sentinel strings as `None`, see
+//! [`NullLike`].
+
+use core::cell::Cell;
+use core::fmt::{self, Debug, Formatter};
+
+use serde::de::value::{BoolDeserializer, CharDeserializer, UnitDeserializer};
+use serde::de::value::{BorrowedBytesDeserializer, BorrowedStrDeserializer, BytesDeserializer};
+use serde::de::value::{EnumAccessDeserializer, MapAccessDeserializer, SeqAccessDeserializer};
+use serde::de::value::{F32Deserializer, F64Deserializer, StrDeserializer, StringDeserializer};
+use serde::de::value::{I8Deserializer, I16Deserializer, I32Deserializer, I64Deserializer};
+use serde::de::value::{I128Deserializer, U128Deserializer};
+use serde::de::value::{U8Deserializer, U16Deserializer, U32Deserializer, U64Deserializer};
+
+use crate::de::middleware::{self, Hint, Middleware, Visit};
+use crate::de::{DeserializeError, DeserializeResult, Deserializer, Visitor};
+use crate::de::{InplaceDeserializeResult, InplaceDeserializer};
+
+/// A [`Deserializer`] wrapper which reads a configurable set of strings, e.g.
+/// `""`, `"null"` or `"N/A"`, as `None` when deserializing an option.
+///
+/// Some sources write absent values as sentinel strings rather than `null`.
+/// When an option is present, its value is looked at before being handed to
+/// the visitor: if it is a string equal to one of the sentinels, the visitor
+/// sees `None` instead. Any other value is handed to the visitor as `Some`,
+/// unchanged. This applies to the options at every depth of the value, and is
+/// a dynamic alternative to a `deserialize_with` function on each field.
+///
+/// Looking at the value relies on `deserialize_any`, so the format must be
+/// self-describing, as JSON is. Values which are not options are never
+/// affected, even if they are sentinels.
+///
+/// # Examples
+///
+/// ```
+/// # use serde::Deserialize as _;
+/// # use dyn_serde::Deserializer;
+/// # use dyn_serde::de::NullLike;
+/// #
+/// let mut deserializer = serde_json::Deserializer::from_str(r#"["N/A", "1", ""]"#);
+/// let mut deserializer = <dyn Deserializer>::new(&mut deserializer);
+/// let mut deserializer = NullLike::new(&mut deserializer, &["", "N/A"]);
+///
+/// let values = Vec::<Option<String>>::deserialize(&mut deserializer as &mut dyn Deserializer);
+/// assert_eq!(values.unwrap(), [None, Some("1".into()), None]);
+/// ```
+pub struct NullLike<'a, 'de> {
+    deserializer: &'a mut dyn Deserializer<'de>,
+    sentinels: Sentinels<'a>,
+}
+
+impl<'a, 'de> NullLike<'a, 'de> {
+    /// Wraps `deserializer`, reading each string of `sentinels` as `None`
+    /// where an option is expected.
+    #[must_use]
+    pub fn new(deserializer: &'a mut dyn Deserializer<'de>, sentinels: &'a [&'a str]) -> Self {
+        NullLike {
+            deserializer,
+            sentinels: Sentinels {
+                sentinels,
+                expecting_option: Cell::new(false),
+            },
+        }
+    }
+
+    fn deserialize_hint(
+        &mut self,
+        hint: Hint,
+        visitor: &mut dyn Visitor<'de>,
+    ) -> InplaceDeserializeResult<()> {
+        middleware::deserialize(&self.sentinels, hint, self.deserializer, visitor)
+    }
+}
+
+impl Debug for NullLike<'_, '_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("NullLike")
+            .field("sentinels", &self.sentinels.sentinels)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<'de> Deserializer<'de> for NullLike<'_, 'de> {
+    middleware::forward_to_deserialize_hint!();
+
+    fn dyn_is_human_readable(&self) -> bool {
+        self.deserializer.dyn_is_human_readable()
+    }
+}
+
+struct Sentinels<'a> {
+    sentinels: &'a [&'a str],
+    /// Whether the next visit is the one of an option.
+    expecting_option: Cell<bool>,
+}
+
+impl<'de> Middleware<'de> for Sentinels<'_> {
+    fn deserialize(
+        &self,
+        hint: Hint,
+        deserializer: &mut dyn Deserializer<'de>,
+        visitor: &mut dyn Visitor<'de>,
+    ) -> InplaceDeserializeResult<()> {
+        self.expecting_option.set(matches!(hint, Hint::Option));
+        hint.forward(deserializer, visitor)
+    }
+
+    fn visit(
+        &self,
+        visit: Visit<'_, 'de>,
+        visitor: &mut dyn Visitor<'de>,
+    ) -> DeserializeResult<()> {
+        match visit {
+            Visit::Some(deserializer) if self.expecting_option.replace(false) => {
+                let peek = Peek {
+                    sentinels: self.sentinels,
+                    armed: Cell::new(true),
+                };
+                Ok(middleware::deserialize(
+                    &peek,
+                    Hint::Any,
+                    deserializer,
+                    visitor,
+                )?)
+            }
+            visit => {
+                self.expecting_option.set(false);
+                visit.forward(visitor)
+            }
+        }
+    }
+}
+
+/// Looks at the value of a present option, handing it to the visitor of the
+/// option as `None` if it is a sentinel, or as `Some` otherwise.
+struct Peek<'a> {
+    sentinels: &'a [&'a str],
+    /// Whether the value has not been looked at yet, since the nested values
+    /// are also visited through this middleware.
+    armed: Cell<bool>,
+}
+
+impl Peek<'_> {
+    fn is_sentinel(&self, v: &str) -> bool {
+        self.sentinels.contains(&v)
+    }
+}
+
+impl<'de> Middleware<'de> for Peek<'_> {
+    fn visit(
+        &self,
+        visit: Visit<'_, 'de>,
+        visitor: &mut dyn Visitor<'de>,
+    ) -> DeserializeResult<()> {
+        if !self.armed.replace(false) {
+            return visit.forward(visitor);
+        }
+
+        match visit {
+            Visit::Str(v) if self.is_sentinel(v) => visitor.dyn_visit_none(),
+            Visit::BorrowedStr(v) if self.is_sentinel(v) => visitor.dyn_visit_none(),
+            Visit::String(v) if self.is_sentinel(&v) => visitor.dyn_visit_none(),
+            Visit::None => visitor.dyn_visit_none(),
+            Visit::Bool(v) => some(visitor, BoolDeserializer::new(v)),
+            Visit::I8(v) => some(visitor, I8Deserializer::new(v)),
+            Visit::I16(v) => some(visitor, I16Deserializer::new(v)),
+            Visit::I32(v) => some(visitor, I32Deserializer::new(v)),
+            Visit::I64(v) => some(visitor, I64Deserializer::new(v)),
+            Visit::I128(v) => some(visitor, I128Deserializer::new(v)),
+            Visit::U8(v) => some(visitor, U8Deserializer::new(v)),
+            Visit::U16(v) => some(visitor, U16Deserializer::new(v)),
+            Visit::U32(v) => some(visitor, U32Deserializer::new(v)),
+            Visit::U64(v) => some(visitor, U64Deserializer::new(v)),
+            Visit::U128(v) => some(visitor, U128Deserializer::new(v)),
+            Visit::F32(v) => some(visitor, F32Deserializer::new(v)),
+            Visit::F64(v) => some(visitor, F64Deserializer::new(v)),
+            Visit::Char(v) => some(visitor, CharDeserializer::new(v)),
+            Visit::Str(v) => some(visitor, StrDeserializer::new(v)),
+            Visit::BorrowedStr(v) => some(visitor, BorrowedStrDeserializer::new(v)),
+            Visit::String(v) => some(visitor, StringDeserializer::new(v)),
+            Visit::Bytes(v) => some(visitor, BytesDeserializer::new(v)),
+            Visit::BorrowedBytes(v) => some(visitor, BorrowedBytesDeserializer::new(v)),
+            Visit::ByteBuf(v) => some(visitor, BytesDeserializer::new(&v)),
+            Visit::Some(deserializer) | Visit::NewtypeStruct(deserializer) => {
+                some(visitor, deserializer)
+            }
+            Visit::Unit => some(visitor, UnitDeserializer::new()),
+            Visit::Seq(access) => some(visitor, SeqAccessDeserializer::new(access)),
+            Visit::Map(access) => some(visitor, MapAccessDeserializer::new(access)),
+            Visit::Enum(access) => some(visitor, EnumAccessDeserializer::new(access)),
+        }
+    }
+}
+
+/// Hands the value replayed by `deserializer` to `visitor` as `Some`.
+fn some<'de, D>(visitor: &mut dyn Visitor<'de>, deserializer: D) -> DeserializeResult<()>
+where
+    D: serde::Deserializer<'de, Error = DeserializeError>,
+{
+    let mut deserializer = InplaceDeserializer::Deserializer(deserializer);
+    let result = visitor.dyn_visit_some(&mut deserializer);
+    deserializer.into_result(result)
+}
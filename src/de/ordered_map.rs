@@ -0,0 +1,72 @@
+//! Order-preserving map deserialization, see [`deserialize_ordered_map`].
+
+#[cfg(not(feature = "std"))]
+use alloc::vec::Vec;
+
+use serde::de::MapAccess as _;
+
+use crate::de::{DeserializeResult, MapAccess};
+
+/// Deserializes the remaining entries of `access` into a vector of key-value
+/// pairs, in the order they appear in the input.
+///
+/// This is an order-preserving alternative to collecting a map into a
+/// `HashMap` or a `BTreeMap`, without depending on a crate like `indexmap`.
+/// Duplicate keys are kept as they appear, and the caller may look them up
+/// or reject them as it sees fit, which makes it a multimap for the formats
+/// allowing a key several times, e.g. HTTP headers. With `V` a dynamic value
+/// such as `serde_json::Value`, the entries may hold values of any shape.
+///
+/// # Errors
+///
+/// Returns an error if a key or a value fails to deserialize.
+///
+/// # Examples
+///
+/// ```
+/// # use std::fmt;
+/// # use serde::de::Error as _;
+/// # use dyn_serde::de::{InplaceMapAccess, deserialize_ordered_map};
+/// #
+/// struct Headers(Vec<(String, String)>);
+///
+/// impl<'de> serde::Deserialize<'de> for Headers {
+///     fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+///         struct HeadersVisitor;
+///
+///         impl<'de> serde::de::Visitor<'de> for HeadersVisitor {
+///             type Value = Headers;
+///
+///             fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+///                 f.write_str("a map of headers")
+///             }
+///
+///             fn visit_map<A: serde::de::MapAccess<'de>>(self, map: A) -> Result<Headers, A::Error> {
+///                 let mut access = InplaceMapAccess::MapAccess(map);
+///                 deserialize_ordered_map(&mut access).map(Headers).map_err(|error| match access {
+///                     InplaceMapAccess::Error(error) => error,
+///                     _ => A::Error::custom(error),
+///                 })
+///             }
+///         }
+///
+///         deserializer.deserialize_map(HeadersVisitor)
+///     }
+/// }
+///
+/// let headers: Headers = serde_json::from_str(r#"{"b": "1", "a": "2"}"#).unwrap();
+/// assert_eq!(headers.0, [("b".into(), "1".into()), ("a".into(), "2".into())]);
+/// ```
+pub fn deserialize_ordered_map<'de, K, V>(
+    mut access: &mut dyn MapAccess<'de>,
+) -> DeserializeResult<Vec<(K, V)>>
+where
+    K: serde::Deserialize<'de>,
+    V: serde::Deserialize<'de>,
+{
+    let mut entries = Vec::with_capacity(access.size_hint().unwrap_or(0).min(4096));
+    while let Some(entry) = access.next_entry()? {
+        entries.push(entry);
+    }
+    Ok(entries)
+}
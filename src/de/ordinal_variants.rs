@@ -0,0 +1,198 @@
+//! A [`Deserializer`] wrapper reading unit variants from their index, see
+//! [`OrdinalVariants`].
+
+use core::cell::Cell;
+use core::fmt::{self, Debug, Formatter};
+
+use serde::de::Error as _;
+use serde::de::value::{BorrowedStrDeserializer, MapAccessDeserializer};
+use serde::de::value::{StrDeserializer, StringDeserializer};
+use serde::de::{Expected, Unexpected};
+
+use crate::de::Visitor;
+use crate::de::middleware::{self, Hint, Middleware, Visit};
+use crate::de::{DeserializeError, DeserializeResult, Deserializer, InplaceDeserializeResult};
+
+/// A [`Deserializer`] wrapper which reads a unit variant given as its index
+/// among the variants of the enum, e.g. `2` for the third one.
+///
+/// Some wire formats encode enum variants as their index, as
+/// [`ser::OrdinalVariants`] writes them, while the enums deriving
+/// `Deserialize` only read them from their name. When an enum is expected,
+/// `OrdinalVariants` reads an integer as the unit variant at that index in the
+/// `variants` passed to `deserialize_enum`, and an index out of range fails
+/// with an error. A variant given by its name, or as a map of a single entry
+/// from its name to its content, is read as usual. The same applies to the
+/// enums at every depth of the value.
+///
+/// Telling an integer from a name relies on `deserialize_any`, so the format
+/// must be self-describing.
+///
+/// [`ser::OrdinalVariants`]: crate::ser::OrdinalVariants
+///
+/// # Examples
+///
+/// ```
+/// # use serde::Deserialize as _;
+/// # use dyn_serde::Deserializer;
+/// # use dyn_serde::de::OrdinalVariants;
+/// #
+/// #[derive(Debug, PartialEq, serde::Deserialize)]
+/// enum Level {
+///     Low,
+///     Medium,
+///     High,
+/// }
+///
+/// let mut deserializer = serde_json::Deserializer::from_str(r#"[2, "Low"]"#);
+/// let mut deserializer = <dyn Deserializer>::new(&mut deserializer);
+/// let mut deserializer = OrdinalVariants::new(&mut deserializer);
+///
+/// let levels = Vec::<Level>::deserialize(&mut deserializer as &mut dyn Deserializer).unwrap();
+/// assert_eq!(levels, [Level::High, Level::Low]);
+/// ```
+pub struct OrdinalVariants<'a, 'de> {
+    deserializer: &'a mut dyn Deserializer<'de>,
+    ordinals: Ordinals,
+}
+
+impl<'a, 'de> OrdinalVariants<'a, 'de> {
+    /// Wraps `deserializer`, reading integers as the unit variants at their
+    /// index where an enum is expected.
+    #[must_use]
+    pub fn new(deserializer: &'a mut dyn Deserializer<'de>) -> Self {
+        OrdinalVariants {
+            deserializer,
+            ordinals: Ordinals {
+                expecting_enum: Cell::new(None),
+            },
+        }
+    }
+
+    fn deserialize_hint(
+        &mut self,
+        hint: Hint,
+        visitor: &mut dyn Visitor<'de>,
+    ) -> InplaceDeserializeResult<()> {
+        middleware::deserialize(&self.ordinals, hint, self.deserializer, visitor)
+    }
+}
+
+impl Debug for OrdinalVariants<'_, '_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("OrdinalVariants").finish_non_exhaustive()
+    }
+}
+
+impl<'de> Deserializer<'de> for OrdinalVariants<'_, 'de> {
+    middleware::forward_to_deserialize_hint!();
+
+    fn dyn_is_human_readable(&self) -> bool {
+        self.deserializer.dyn_is_human_readable()
+    }
+}
+
+type Enum = (&'static str, &'static [&'static str]);
+
+struct Ordinals {
+    /// The name and the variants of the enum, if the next visit is the one of
+    /// an enum.
+    expecting_enum: Cell<Option<Enum>>,
+}
+
+impl<'de> Middleware<'de> for Ordinals {
+    fn deserialize(
+        &self,
+        hint: Hint,
+        deserializer: &mut dyn Deserializer<'de>,
+        visitor: &mut dyn Visitor<'de>,
+    ) -> InplaceDeserializeResult<()> {
+        if let Hint::Enum(name, variants) = hint {
+            self.expecting_enum.set(Some((name, variants)));
+            return Hint::Any.forward(deserializer, visitor);
+        }
+        self.expecting_enum.set(None);
+        hint.forward(deserializer, visitor)
+    }
+
+    fn visit(
+        &self,
+        visit: Visit<'_, 'de>,
+        visitor: &mut dyn Visitor<'de>,
+    ) -> DeserializeResult<()> {
+        let Some((name, variants)) = self.expecting_enum.take() else {
+            return visit.forward(visitor);
+        };
+        let index = match visit {
+            Visit::U8(v) => Ok(v.into()),
+            Visit::U16(v) => Ok(v.into()),
+            Visit::U32(v) => Ok(v.into()),
+            Visit::U64(v) => Ok(v),
+            Visit::I8(v) => u64::try_from(v).map_err(|_| v.into()),
+            Visit::I16(v) => u64::try_from(v).map_err(|_| v.into()),
+            Visit::I32(v) => u64::try_from(v).map_err(|_| v.into()),
+            Visit::I64(v) => u64::try_from(v).map_err(|_| v),
+            Visit::Str(v) => {
+                let deserializer = StrDeserializer::new(v);
+                return serde::Deserializer::deserialize_enum(
+                    deserializer,
+                    name,
+                    variants,
+                    visitor,
+                );
+            }
+            Visit::BorrowedStr(v) => {
+                let deserializer = BorrowedStrDeserializer::new(v);
+                return serde::Deserializer::deserialize_enum(
+                    deserializer,
+                    name,
+                    variants,
+                    visitor,
+                );
+            }
+            Visit::String(v) => {
+                let deserializer = StringDeserializer::new(v);
+                return serde::Deserializer::deserialize_enum(
+                    deserializer,
+                    name,
+                    variants,
+                    visitor,
+                );
+            }
+            Visit::Map(access) => {
+                let deserializer = MapAccessDeserializer::new(access);
+                return serde::Deserializer::deserialize_enum(
+                    deserializer,
+                    name,
+                    variants,
+                    visitor,
+                );
+            }
+            visit => return visit.forward(visitor),
+        };
+
+        let variant = match index {
+            Ok(index) => usize::try_from(index)
+                .ok()
+                .and_then(|index| variants.get(index))
+                .ok_or_else(|| out_of_range(Unexpected::Unsigned(index), variants)),
+            Err(index) => Err(out_of_range(Unexpected::Signed(index), variants)),
+        }?;
+        let deserializer = BorrowedStrDeserializer::new(variant);
+        serde::Deserializer::deserialize_enum(deserializer, name, variants, visitor)
+    }
+}
+
+#[cold]
+fn out_of_range(index: Unexpected<'_>, variants: &[&str]) -> DeserializeError {
+    DeserializeError::invalid_value(index, &VariantIndex(variants.len()))
+}
+
+/// The expectation of the index of one of the given number of variants.
+struct VariantIndex(usize);
+
+impl Expected for VariantIndex {
+    fn fmt(&self, formatter: &mut Formatter) -> fmt::Result {
+        write!(formatter, "variant index 0 <= i < {}", self.0)
+    }
+}
@@ -0,0 +1,146 @@
+//! A [`Deserializer`] merging two inputs, see [`Overlay`].
+
+use core::fmt::{self, Debug, Formatter};
+
+use serde::Deserialize as _;
+use serde::de::Error as _;
+use serde_json::Value;
+
+use crate::de::middleware::{self, Hint};
+use crate::de::{DeserializeError, Deserializer, InplaceDeserializer, Visitor};
+use crate::de::{InplaceDeserializeError, InplaceDeserializeResult};
+
+/// A [`Deserializer`] which reads a value from a base input overlaid by an
+/// override input, e.g. the defaults of a configuration and the settings of
+/// the user.
+///
+/// Both inputs are read into a [`serde_json::Value`], then merged: the entries
+/// of the override win over the entries of the base with the same key, while
+/// the entries missing from the override fall back to the base. Maps present
+/// in both inputs are merged the same way, at every depth, so that overriding
+/// a nested field keeps its siblings. Any other value of the override,
+/// including sequences and `null`, replaces the value of the base as a whole.
+///
+/// Like [`MapDeserializer`], the inputs must be self-describing, strings are
+/// no longer borrowed from them, and only one value can be deserialized. The
+/// deserialization returns an error telling only that it has done
+/// unsuccessfully, while the wrapped deserializers tell why an input could not
+/// be read, and [`Overlay::error`] why the merged value could not be
+/// deserialized.
+///
+/// [`MapDeserializer`]: crate::de::MapDeserializer
+///
+/// # Examples
+///
+/// ```
+/// # use serde::Deserialize as _;
+/// # use dyn_serde::Deserializer;
+/// # use dyn_serde::de::Overlay;
+/// #
+/// #[derive(serde::Deserialize)]
+/// struct Config {
+///     host: String,
+///     port: u16,
+/// }
+///
+/// let mut base = serde_json::Deserializer::from_str(r#"{"host": "localhost", "port": 80}"#);
+/// let mut base = <dyn Deserializer>::new(&mut base);
+/// let mut user = serde_json::Deserializer::from_str(r#"{"port": 8080}"#);
+/// let mut user = <dyn Deserializer>::new(&mut user);
+/// let mut deserializer = Overlay::new(&mut base, &mut user);
+///
+/// let config = Config::deserialize(&mut deserializer as &mut dyn Deserializer).unwrap();
+/// assert_eq!(config.host, "localhost");
+/// assert_eq!(config.port, 8080);
+/// ```
+pub struct Overlay<'a, 'de> {
+    base: &'a mut dyn Deserializer<'de>,
+    overrides: &'a mut dyn Deserializer<'de>,
+    /// Whether the value has been read.
+    read: bool,
+    error: Option<DeserializeError>,
+}
+
+impl<'a, 'de> Overlay<'a, 'de> {
+    /// Creates a deserializer reading the value of `base` overlaid by the one
+    /// of `overrides`.
+    #[must_use]
+    pub fn new(
+        base: &'a mut dyn Deserializer<'de>,
+        overrides: &'a mut dyn Deserializer<'de>,
+    ) -> Self {
+        Overlay {
+            base,
+            overrides,
+            read: false,
+            error: None,
+        }
+    }
+
+    /// Returns the first error raised while deserializing from the merged
+    /// value, if any.
+    #[must_use]
+    pub fn error(&self) -> Option<&DeserializeError> {
+        self.error.as_ref()
+    }
+
+    #[cold]
+    fn fail(&mut self, error: DeserializeError) -> InplaceDeserializeError {
+        self.error.get_or_insert(error);
+        InplaceDeserializeError::Error
+    }
+
+    fn deserialize_hint(
+        &mut self,
+        hint: Hint,
+        visitor: &mut dyn Visitor<'de>,
+    ) -> InplaceDeserializeResult<()> {
+        if self.read {
+            return Err(InplaceDeserializeError::NotDeserializer);
+        }
+        self.read = true;
+        let mut value = Value::deserialize(&mut *self.base).map_err(|e| self.fail(e))?;
+        let overrides = Value::deserialize(&mut *self.overrides).map_err(|e| self.fail(e))?;
+        merge(&mut value, overrides);
+
+        let mut deserializer = InplaceDeserializer::Deserializer(value);
+        let result = hint.forward(&mut deserializer, visitor);
+        if let InplaceDeserializer::Error(error, _) = deserializer {
+            self.error.get_or_insert(DeserializeError::custom(error));
+        }
+        result
+    }
+}
+
+impl Debug for Overlay<'_, '_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Overlay")
+            .field("error", &self.error)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<'de> Deserializer<'de> for Overlay<'_, 'de> {
+    middleware::forward_to_deserialize_hint!();
+
+    fn dyn_is_human_readable(&self) -> bool {
+        self.base.dyn_is_human_readable()
+    }
+}
+
+/// Overlays `base` with `overrides`, merging the maps present in both.
+fn merge(base: &mut Value, overrides: Value) {
+    match (base, overrides) {
+        (Value::Object(base), Value::Object(overrides)) => {
+            for (key, value) in overrides {
+                match base.get_mut(&key) {
+                    Some(base) => merge(base, value),
+                    None => {
+                        base.insert(key, value);
+                    }
+                }
+            }
+        }
+        (base, overrides) => *base = overrides,
+    }
+}
@@ -0,0 +1,162 @@
+//! A [`Deserializer`] wrapper parsing string map keys into scalars, see
+//! [`ParseKeys`].
+
+use core::cell::Cell;
+use core::fmt::{self, Debug, Formatter};
+use core::str::FromStr;
+
+use crate::de::middleware::{self, Hint, Middleware, Visit};
+use crate::de::{DeserializeResult, DeserializeSeed, Deserializer, InplaceDeserializeResult};
+use crate::de::{MapAccess, Visitor};
+
+/// A [`Deserializer`] wrapper which parses the keys of maps that are strings
+/// into the boolean, integer, float or char the key is deserialized as.
+///
+/// Text formats such as JSON only have string keys, so a map keyed by integers
+/// is written with keys such as `"42"`, e.g. by [`StringifyKeys`]. Some
+/// formats parse such keys themselves when the type of the key asks for an
+/// integer, others hand the string over and the key fails to deserialize.
+/// `ParseKeys` parses the string with the [`FromStr`] implementation of the
+/// type asked for, closing the round trip of `HashMap<u32, T>`.
+///
+/// A string which does not parse is handed over unchanged, so that the key
+/// fails with the usual type error. Keys which are asked for as strings or as
+/// anything else than a scalar are left unchanged too, while the keys of nested
+/// maps are parsed at every depth.
+///
+/// [`StringifyKeys`]: crate::ser::StringifyKeys
+///
+/// # Examples
+///
+/// ```
+/// # use std::collections::HashMap;
+/// # use serde::Deserialize as _;
+/// # use serde::de::value::MapDeserializer;
+/// # use dyn_serde::Deserializer;
+/// # use dyn_serde::de::ParseKeys;
+/// #
+/// let entries = [("1", "a")].into_iter();
+/// let deserializer = MapDeserializer::<_, serde::de::value::Error>::new(entries);
+/// let mut deserializer = <dyn Deserializer>::new(deserializer);
+/// let mut deserializer = ParseKeys::new(&mut deserializer);
+///
+/// let map = HashMap::<u32, String>::deserialize(&mut deserializer as &mut dyn Deserializer).unwrap();
+/// assert_eq!(map, HashMap::from([(1, "a".to_owned())]));
+/// ```
+pub struct ParseKeys<'a, 'de> {
+    deserializer: &'a mut dyn Deserializer<'de>,
+    parser: Parser,
+}
+
+impl<'a, 'de> ParseKeys<'a, 'de> {
+    /// Wraps `deserializer`, parsing string map keys into scalars.
+    #[must_use]
+    pub fn new(deserializer: &'a mut dyn Deserializer<'de>) -> Self {
+        ParseKeys {
+            deserializer,
+            parser: Parser {
+                key: Cell::new(false),
+                scalar: Cell::new(None),
+            },
+        }
+    }
+
+    fn deserialize_hint(
+        &mut self,
+        hint: Hint,
+        visitor: &mut dyn Visitor<'de>,
+    ) -> InplaceDeserializeResult<()> {
+        middleware::deserialize(&self.parser, hint, self.deserializer, visitor)
+    }
+}
+
+impl Debug for ParseKeys<'_, '_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ParseKeys").finish_non_exhaustive()
+    }
+}
+
+impl<'de> Deserializer<'de> for ParseKeys<'_, 'de> {
+    middleware::forward_to_deserialize_hint!();
+
+    fn dyn_is_human_readable(&self) -> bool {
+        self.deserializer.dyn_is_human_readable()
+    }
+}
+
+struct Parser {
+    /// Whether the next call deserializes a key of a map.
+    key: Cell<bool>,
+    /// The scalar the key whose visit comes next is deserialized as, if any.
+    scalar: Cell<Option<Hint>>,
+}
+
+/// Parses `v` into the scalar `hint` asks for, if it does.
+fn parse<'a, 'de>(hint: Hint, v: &str) -> Option<Visit<'a, 'de>> {
+    fn parse<'a, 'de, T: FromStr>(
+        v: &str,
+        visit: fn(T) -> Visit<'a, 'de>,
+    ) -> Option<Visit<'a, 'de>> {
+        v.parse().ok().map(visit)
+    }
+
+    match hint {
+        Hint::Bool => parse(v, Visit::Bool),
+        Hint::I8 => parse(v, Visit::I8),
+        Hint::I16 => parse(v, Visit::I16),
+        Hint::I32 => parse(v, Visit::I32),
+        Hint::I64 => parse(v, Visit::I64),
+        Hint::I128 => parse(v, Visit::I128),
+        Hint::U8 => parse(v, Visit::U8),
+        Hint::U16 => parse(v, Visit::U16),
+        Hint::U32 => parse(v, Visit::U32),
+        Hint::U64 => parse(v, Visit::U64),
+        Hint::U128 => parse(v, Visit::U128),
+        Hint::F32 => parse(v, Visit::F32),
+        Hint::F64 => parse(v, Visit::F64),
+        Hint::Char => parse(v, Visit::Char),
+        _ => None,
+    }
+}
+
+impl<'de> Middleware<'de> for Parser {
+    fn deserialize(
+        &self,
+        hint: Hint,
+        deserializer: &mut dyn Deserializer<'de>,
+        visitor: &mut dyn Visitor<'de>,
+    ) -> InplaceDeserializeResult<()> {
+        if self.key.take() {
+            self.scalar.set(Some(hint));
+        }
+        hint.forward(deserializer, visitor)
+    }
+
+    fn visit(
+        &self,
+        visit: Visit<'_, 'de>,
+        visitor: &mut dyn Visitor<'de>,
+    ) -> DeserializeResult<()> {
+        let Some(hint) = self.scalar.take() else {
+            return visit.forward(visitor);
+        };
+        let parsed = match visit {
+            Visit::Str(v) | Visit::BorrowedStr(v) => parse(hint, v),
+            Visit::String(ref v) => parse(hint, v),
+            _ => None,
+        };
+        parsed.unwrap_or(visit).forward(visitor)
+    }
+
+    fn next_key(
+        &self,
+        access: &mut dyn MapAccess<'de>,
+        seed: &mut dyn DeserializeSeed<'de>,
+    ) -> InplaceDeserializeResult<Option<()>> {
+        self.key.set(true);
+        let result = access.dyn_next_key(seed);
+        self.key.set(false);
+        self.scalar.set(None);
+        result
+    }
+}
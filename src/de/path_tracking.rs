@@ -0,0 +1,275 @@
+//! A [`Deserializer`] wrapper locating the value which fails, see
+//! [`PathTracking`].
+
+use core::cell::{Cell, RefCell};
+use core::fmt::{self, Debug, Formatter, Write as _};
+
+#[cfg(not(feature = "std"))]
+use alloc::string::{String, ToString};
+#[cfg(not(feature = "std"))]
+use alloc::vec::Vec;
+
+use crate::de::middleware::{self, Hint, Middleware, VariantCall, Visit};
+use crate::de::{DeserializeResult, DeserializeSeed, Deserializer, EnumAccess};
+use crate::de::{InplaceDeserializeResult, MapAccess, SeqAccess, VariantAccess, Visitor};
+
+/// A [`Deserializer`] wrapper which keeps track of the path to the value being
+/// deserialized, so that a failure tells where it happened, e.g. `a.g[3].x`.
+///
+/// This is a dynamic counterpart of `serde_path_to_error`. The path is made of
+/// the keys of maps and the fields of structs, separated by `.`, the indices
+/// of sequences, e.g. `[3]`, and the names of enum variants. The value itself
+/// has the path `.`.
+///
+/// The deserialization itself is left unchanged, and its error is raised by
+/// the wrapped deserializer as usual, while [`PathTracking::path`] tells the
+/// path of the value being deserialized when it has failed. Values buffered by
+/// the visitor before being deserialized, e.g. by untagged enums, are located
+/// by the path of the whole buffered value.
+///
+/// # Examples
+///
+/// ```
+/// # use serde::Deserialize as _;
+/// # use dyn_serde::Deserializer;
+/// # use dyn_serde::de::PathTracking;
+/// #
+/// #[derive(serde::Deserialize)]
+/// struct Config {
+///     ports: Vec<u16>,
+/// }
+///
+/// let mut deserializer = serde_json::Deserializer::from_str(r#"{"ports": [80, -1]}"#);
+/// let mut deserializer = <dyn Deserializer>::new(&mut deserializer);
+/// let mut deserializer = PathTracking::new(&mut deserializer);
+///
+/// assert!(Config::deserialize(&mut deserializer as &mut dyn Deserializer).is_err());
+/// assert_eq!(deserializer.path(), Some("ports[1]"));
+/// ```
+pub struct PathTracking<'a, 'de> {
+    deserializer: &'a mut dyn Deserializer<'de>,
+    tracker: Tracker,
+    path: Option<String>,
+}
+
+impl<'a, 'de> PathTracking<'a, 'de> {
+    /// Wraps `deserializer`, keeping track of the path to the value being
+    /// deserialized.
+    #[must_use]
+    pub fn new(deserializer: &'a mut dyn Deserializer<'de>) -> Self {
+        PathTracking {
+            deserializer,
+            tracker: Tracker {
+                segments: RefCell::new(Vec::new()),
+                capture: Cell::new(false),
+                failed: RefCell::new(None),
+            },
+            path: None,
+        }
+    }
+
+    /// Returns the path of the value being deserialized when the last
+    /// deserialization has failed, if it has.
+    #[must_use]
+    pub fn path(&self) -> Option<&str> {
+        self.path.as_deref()
+    }
+
+    fn deserialize_hint(
+        &mut self,
+        hint: Hint,
+        visitor: &mut dyn Visitor<'de>,
+    ) -> InplaceDeserializeResult<()> {
+        self.tracker.segments.borrow_mut().clear();
+        let result = middleware::deserialize(&self.tracker, hint, self.deserializer, visitor);
+        // errors recovered from by the visitor leave no path.
+        self.path = self.tracker.failed.take().filter(|_| result.is_err());
+        result
+    }
+}
+
+impl Debug for PathTracking<'_, '_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PathTracking")
+            .field("path", &self.path)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<'de> Deserializer<'de> for PathTracking<'_, 'de> {
+    middleware::forward_to_deserialize_hint!();
+
+    fn dyn_is_human_readable(&self) -> bool {
+        self.deserializer.dyn_is_human_readable()
+    }
+}
+
+/// A compound being visited.
+enum Segment {
+    /// A sequence, with the index of the element being visited, if any.
+    Seq(Option<usize>),
+    /// A map or an enum, with the key or the variant being visited, if any.
+    Named(Option<String>),
+}
+
+struct Tracker {
+    segments: RefCell<Vec<Segment>>,
+    /// Whether the next visit is a key or a variant to be recorded.
+    capture: Cell<bool>,
+    /// The path of the value being deserialized at the first failure.
+    failed: RefCell<Option<String>>,
+}
+
+impl Tracker {
+    fn path(&self) -> String {
+        let mut path = String::new();
+        for segment in self.segments.borrow().iter() {
+            match segment {
+                Segment::Seq(None) | Segment::Named(None) => {}
+                Segment::Seq(Some(index)) => {
+                    let _ = write!(path, "[{index}]");
+                }
+                Segment::Named(Some(name)) => {
+                    if !path.is_empty() {
+                        path.push('.');
+                    }
+                    path.push_str(name);
+                }
+            }
+        }
+        if path.is_empty() {
+            path.push('.');
+        }
+        path
+    }
+
+    /// Records the current path if `result` is the first failure.
+    fn check<T, E>(&self, result: Result<T, E>) -> Result<T, E> {
+        if result.is_err() && self.failed.borrow().is_none() {
+            *self.failed.borrow_mut() = Some(self.path());
+        }
+        result
+    }
+}
+
+/// Returns the name of a key or a variant from its visit.
+fn name(visit: &Visit<'_, '_>) -> Option<String> {
+    Some(match visit {
+        Visit::Bool(v) => v.to_string(),
+        Visit::I8(v) => v.to_string(),
+        Visit::I16(v) => v.to_string(),
+        Visit::I32(v) => v.to_string(),
+        Visit::I64(v) => v.to_string(),
+        Visit::I128(v) => v.to_string(),
+        Visit::U8(v) => v.to_string(),
+        Visit::U16(v) => v.to_string(),
+        Visit::U32(v) => v.to_string(),
+        Visit::U64(v) => v.to_string(),
+        Visit::U128(v) => v.to_string(),
+        Visit::Char(v) => v.to_string(),
+        Visit::Str(v) | Visit::BorrowedStr(v) => (*v).to_string(),
+        Visit::String(v) => v.clone(),
+        Visit::Bytes(v) | Visit::BorrowedBytes(v) => String::from_utf8_lossy(v).into_owned(),
+        Visit::ByteBuf(v) => String::from_utf8_lossy(v).into_owned(),
+        _ => return None,
+    })
+}
+
+impl<'de> Middleware<'de> for Tracker {
+    fn deserialize(
+        &self,
+        hint: Hint,
+        deserializer: &mut dyn Deserializer<'de>,
+        visitor: &mut dyn Visitor<'de>,
+    ) -> InplaceDeserializeResult<()> {
+        self.check(hint.forward(deserializer, visitor))
+    }
+
+    fn visit(
+        &self,
+        visit: Visit<'_, 'de>,
+        visitor: &mut dyn Visitor<'de>,
+    ) -> DeserializeResult<()> {
+        // a key or a variant is only part of the path once it is accepted.
+        if self.capture.take()
+            && let Some(name) = name(&visit)
+        {
+            let result = self.check(visit.forward(visitor));
+            if result.is_ok()
+                && let Some(Segment::Named(key)) = self.segments.borrow_mut().last_mut()
+            {
+                *key = Some(name);
+            }
+            return result;
+        }
+
+        let segment = match visit {
+            Visit::Seq(_) => Segment::Seq(None),
+            Visit::Map(_) | Visit::Enum(_) => Segment::Named(None),
+            _ => return self.check(visit.forward(visitor)),
+        };
+        self.segments.borrow_mut().push(segment);
+        let result = self.check(visit.forward(visitor));
+        self.segments.borrow_mut().pop();
+        result
+    }
+
+    fn next_element(
+        &self,
+        access: &mut dyn SeqAccess<'de>,
+        seed: &mut dyn DeserializeSeed<'de>,
+    ) -> InplaceDeserializeResult<Option<()>> {
+        if let Some(Segment::Seq(index)) = self.segments.borrow_mut().last_mut() {
+            *index = Some(index.map_or(0, |index| index + 1));
+        }
+        let result = self.check(access.dyn_next_element(seed));
+        // past the end of the sequence, there is no element to locate.
+        if let Ok(None) = result
+            && let Some(Segment::Seq(index)) = self.segments.borrow_mut().last_mut()
+        {
+            *index = None;
+        }
+        result
+    }
+
+    fn next_key(
+        &self,
+        access: &mut dyn MapAccess<'de>,
+        seed: &mut dyn DeserializeSeed<'de>,
+    ) -> InplaceDeserializeResult<Option<()>> {
+        if let Some(Segment::Named(key)) = self.segments.borrow_mut().last_mut() {
+            *key = None;
+        }
+        self.capture.set(true);
+        let result = access.dyn_next_key(seed);
+        self.capture.set(false);
+        self.check(result)
+    }
+
+    fn next_value(
+        &self,
+        access: &mut dyn MapAccess<'de>,
+        seed: &mut dyn DeserializeSeed<'de>,
+    ) -> InplaceDeserializeResult<()> {
+        self.check(access.dyn_next_value(seed))
+    }
+
+    fn variant<'a>(
+        &self,
+        access: &'a mut dyn EnumAccess<'de>,
+        seed: &mut dyn DeserializeSeed<'de>,
+    ) -> InplaceDeserializeResult<&'a mut dyn VariantAccess<'de>> {
+        self.capture.set(true);
+        let result = access.dyn_variant(seed);
+        self.capture.set(false);
+        self.check(result)
+    }
+
+    fn variant_access(
+        &self,
+        call: VariantCall<'_, 'de>,
+        access: &mut dyn VariantAccess<'de>,
+    ) -> InplaceDeserializeResult<()> {
+        self.check(call.forward(access))
+    }
+}
@@ -0,0 +1,285 @@
+//! Deserializing from a record of tabular data, see [`from_record`].
+
+use core::fmt::{self, Debug, Formatter};
+use core::iter::Zip;
+use core::slice::Iter;
+
+use serde::de::Error as _;
+use serde::de::value::BorrowedStrDeserializer;
+
+use crate::de::untag_structs::replay;
+use crate::de::{DeserializeError, DeserializeSeed, Deserializer, MapAccess, Visitor};
+use crate::de::{InplaceDeserializeError, InplaceDeserializeResult};
+
+/// Creates a dynamic deserializer reading a record of tabular data, e.g. a row
+/// of a CSV file, as a map from the names of its columns to its values.
+///
+/// The value in `record` at every position is the entry keyed by the name in
+/// `header` at the same position, so that the record deserializes into a struct
+/// whose fields are named after the columns, or into a map. Columns which are
+/// not fields of the struct are ignored unless it denies unknown fields.
+///
+/// Every value is a string, parsed by the [`FromStr`] implementation of the
+/// boolean, integer, float or char it is deserialized as, read as a unit
+/// variant of an enum, and read as `None` if it is empty and deserialized as
+/// an option. Splitting the lines of the input into records, and unquoting
+/// their values, is left to the caller.
+///
+/// [`FromStr`]: core::str::FromStr
+///
+/// # Examples
+///
+/// ```
+/// # use serde::Deserialize as _;
+/// # use dyn_serde::Deserializer;
+/// #
+/// #[derive(serde::Deserialize)]
+/// struct City {
+///     name: String,
+///     population: u32,
+///     area: Option<f64>,
+/// }
+///
+/// let header = ["name", "population", "area"];
+/// let mut deserializer = dyn_serde::de::from_record(&header, &["Lyon", "522250", ""]);
+/// let deserializer = &mut deserializer as &mut dyn Deserializer<'_>;
+///
+/// let city = City::deserialize(deserializer).unwrap();
+/// assert_eq!(city.name, "Lyon");
+/// assert_eq!(city.population, 522_250);
+/// assert_eq!(city.area, None);
+/// ```
+#[must_use]
+pub fn from_record<'a, 'de>(
+    header: &'a [&'de str],
+    record: &'a [&'de str],
+) -> RecordDeserializer<'a, 'de> {
+    RecordDeserializer {
+        header,
+        record,
+        error: None,
+    }
+}
+
+/// A [`Deserializer`] reading a record of tabular data, created by
+/// [`from_record`].
+pub struct RecordDeserializer<'a, 'de> {
+    header: &'a [&'de str],
+    record: &'a [&'de str],
+    error: Option<DeserializeError>,
+}
+
+impl RecordDeserializer<'_, '_> {
+    /// Returns the first error raised while deserializing from the record, if
+    /// any.
+    ///
+    /// The error returned to the caller of the deserialization may only tell
+    /// that the deserialization has done unsuccessfully, while this one tells
+    /// why it has.
+    #[must_use]
+    pub fn error(&self) -> Option<&DeserializeError> {
+        self.error.as_ref()
+    }
+}
+
+impl Debug for RecordDeserializer<'_, '_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RecordDeserializer")
+            .field("header", &self.header)
+            .field("record", &self.record)
+            .field("error", &self.error)
+            .finish()
+    }
+}
+
+impl<'de> Deserializer<'de> for RecordDeserializer<'_, 'de> {
+    fn dyn_deserialize_any(
+        &mut self,
+        visitor: &mut dyn Visitor<'de>,
+    ) -> InplaceDeserializeResult<()> {
+        if self.record.len() != self.header.len() {
+            let error = DeserializeError::invalid_length(
+                self.record.len(),
+                &"as many values as columns in the header",
+            );
+            return Err(fail(&mut self.error, error));
+        }
+        let mut access = RecordMapAccess {
+            iter: self.header.iter().zip(self.record),
+            value: None,
+            error: &mut self.error,
+        };
+        match visitor.dyn_visit_map(&mut access) {
+            Ok(()) => Ok(()),
+            Err(error) => Err(fail(&mut self.error, error)),
+        }
+    }
+
+    fn dyn_deserialize_option(
+        &mut self,
+        visitor: &mut dyn Visitor<'de>,
+    ) -> InplaceDeserializeResult<()> {
+        match visitor.dyn_visit_some(self) {
+            Ok(()) => Ok(()),
+            Err(error) => Err(fail(&mut self.error, error)),
+        }
+    }
+
+    fn dyn_deserialize_newtype_struct(
+        &mut self,
+        name: &'static str,
+        visitor: &mut dyn Visitor<'de>,
+    ) -> InplaceDeserializeResult<()> {
+        let _ = name;
+        match visitor.dyn_visit_newtype_struct(self) {
+            Ok(()) => Ok(()),
+            Err(error) => Err(fail(&mut self.error, error)),
+        }
+    }
+}
+
+/// Records `error` unless an error has been recorded, since the error
+/// propagated from a nested value only tells that it has failed.
+#[cold]
+fn fail(slot: &mut Option<DeserializeError>, error: DeserializeError) -> InplaceDeserializeError {
+    slot.get_or_insert(error);
+    InplaceDeserializeError::Error
+}
+
+struct RecordMapAccess<'a, 'de> {
+    iter: Zip<Iter<'a, &'de str>, Iter<'a, &'de str>>,
+    value: Option<&'de str>,
+    error: &'a mut Option<DeserializeError>,
+}
+
+impl<'de> MapAccess<'de> for RecordMapAccess<'_, 'de> {
+    fn dyn_next_key(
+        &mut self,
+        seed: &mut dyn DeserializeSeed<'de>,
+    ) -> InplaceDeserializeResult<Option<()>> {
+        let Some((key, value)) = self.iter.next() else {
+            return Ok(None);
+        };
+        self.value = Some(value);
+        match replay(seed, BorrowedStrDeserializer::new(key)) {
+            Ok(()) => Ok(Some(())),
+            Err(error) => Err(fail(self.error, error)),
+        }
+    }
+
+    fn dyn_next_value(
+        &mut self,
+        seed: &mut dyn DeserializeSeed<'de>,
+    ) -> InplaceDeserializeResult<()> {
+        let error = match self.value.take() {
+            Some(value) => match replay(seed, FieldDeserializer(value)) {
+                Ok(()) => return Ok(()),
+                Err(error) => error,
+            },
+            None => DeserializeError::custom("value is missing"),
+        };
+        Err(fail(self.error, error))
+    }
+
+    fn dyn_next_entry(
+        &mut self,
+        kseed: &mut dyn DeserializeSeed<'de>,
+        vseed: &mut dyn DeserializeSeed<'de>,
+    ) -> InplaceDeserializeResult<Option<((), ())>> {
+        match self.dyn_next_key(kseed)? {
+            Some(()) => self.dyn_next_value(vseed).map(|()| Some(((), ()))),
+            None => Ok(None),
+        }
+    }
+
+    fn dyn_size_hint(&self) -> Option<usize> {
+        Some(self.iter.len())
+    }
+}
+
+/// A value of a record, parsed into the scalar it is deserialized as.
+struct FieldDeserializer<'de>(&'de str);
+
+macro_rules! parse_scalar {
+    ($($method:ident => $visit:ident,)*) => {
+        $(
+            fn $method<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+            where
+                V: serde::de::Visitor<'de>,
+            {
+                match self.0.parse() {
+                    Ok(v) => visitor.$visit(v),
+                    Err(_) => visitor.visit_borrowed_str(self.0),
+                }
+            }
+        )*
+    };
+}
+
+impl<'de> serde::Deserializer<'de> for FieldDeserializer<'de> {
+    type Error = DeserializeError;
+
+    fn deserialize_any<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: serde::de::Visitor<'de>,
+    {
+        visitor.visit_borrowed_str(self.0)
+    }
+
+    parse_scalar! {
+        deserialize_bool => visit_bool,
+        deserialize_i8 => visit_i8,
+        deserialize_i16 => visit_i16,
+        deserialize_i32 => visit_i32,
+        deserialize_i64 => visit_i64,
+        deserialize_i128 => visit_i128,
+        deserialize_u8 => visit_u8,
+        deserialize_u16 => visit_u16,
+        deserialize_u32 => visit_u32,
+        deserialize_u64 => visit_u64,
+        deserialize_u128 => visit_u128,
+        deserialize_f32 => visit_f32,
+        deserialize_f64 => visit_f64,
+        deserialize_char => visit_char,
+    }
+
+    fn deserialize_option<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: serde::de::Visitor<'de>,
+    {
+        match self.0 {
+            "" => visitor.visit_none(),
+            _ => visitor.visit_some(self),
+        }
+    }
+
+    fn deserialize_newtype_struct<V>(
+        self,
+        name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: serde::de::Visitor<'de>,
+    {
+        let _ = name;
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_enum<V>(
+        self,
+        name: &'static str,
+        variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: serde::de::Visitor<'de>,
+    {
+        let _ = (name, variants);
+        visitor.visit_enum(BorrowedStrDeserializer::new(self.0))
+    }
+
+    serde::forward_to_deserialize_any! {
+        str string bytes byte_buf unit unit_struct seq tuple tuple_struct map
+        struct identifier ignored_any
+    }
+}
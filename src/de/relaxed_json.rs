@@ -0,0 +1,135 @@
+//! Deserializing from JSON with comments and trailing commas, see
+//! [`from_relaxed_json_str`].
+
+#[cfg(not(feature = "std"))]
+use alloc::string::String;
+
+use crate::de::from_json_str;
+
+/// Deserializes an instance of `T` from a string of JSON which may contain
+/// comments and trailing commas, through a [`dyn Deserializer`].
+///
+/// Configuration files are often written in a relaxed JSON, with `//` and
+/// `/* */` comments and a comma after the last element of arrays and
+/// objects, which strict JSON parsers reject. `s` is first cleaned by
+/// [`relax_json`], which blanks those out, then deserialized as
+/// [`from_json_str`] does.
+///
+/// This is a best-effort preprocessor, not a JSON5 parser: unquoted keys,
+/// single-quoted strings, hexadecimal numbers and the other extensions of
+/// JSON5 are still rejected by `serde_json`. Since the cleaned input is
+/// dropped once deserialized, `T` cannot borrow from it.
+///
+/// [`dyn Deserializer`]: crate::de::Deserializer
+///
+/// # Errors
+///
+/// Returns the error of `serde_json` if `s` is not valid JSON once cleaned,
+/// or if `T` fails to deserialize itself from it. Since the comments and
+/// commas are replaced with spaces, the lines and columns of the error are
+/// those of `s`.
+///
+/// # Examples
+///
+/// ```
+/// #[derive(serde::Deserialize)]
+/// struct Config {
+///     port: u16,
+///     hosts: Vec<String>,
+/// }
+///
+/// let config: Config = dyn_serde::de::from_relaxed_json_str(r#"{
+///     // the port to listen on.
+///     "port": 8080,
+///     "hosts": ["a", "b", /* "c", */],
+/// }"#).unwrap();
+/// assert_eq!(config.port, 8080);
+/// assert_eq!(config.hosts, ["a", "b"]);
+/// ```
+pub fn from_relaxed_json_str<T>(s: &str) -> Result<T, serde_json::Error>
+where
+    T: serde::de::DeserializeOwned,
+{
+    from_json_str(&relax_json(s))
+}
+
+/// Replaces the comments of `s` and the commas after the last element of its
+/// arrays and objects with spaces, leaving the rest of `s` unchanged.
+///
+/// `//` comments end at the end of the line, and `/* */` comments at the
+/// first `*/`, while the line breaks inside them are kept so that the lines of
+/// the output match those of `s`. Comments and commas inside strings are left
+/// alone. Anything else, including invalid JSON, is copied as is, so that it is
+/// reported by the parser reading the output.
+///
+/// # Examples
+///
+/// ```
+/// let json = "[1, // one\n 2,]";
+/// assert_eq!(dyn_serde::de::relax_json(json), "[1,       \n 2 ]");
+/// ```
+#[must_use]
+pub fn relax_json(s: &str) -> String {
+    let mut output = String::with_capacity(s.len());
+    // the position in `output` of the last comma, if it is followed by nothing
+    // but whitespace and comments so far.
+    let mut comma = None;
+    let mut chars = s.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '"' => {
+                comma = None;
+                output.push(c);
+                while let Some(c) = chars.next() {
+                    output.push(c);
+                    match c {
+                        '\\' => output.extend(chars.next()),
+                        '"' => break,
+                        _ => {}
+                    }
+                }
+            }
+            '/' if chars.peek() == Some(&'/') => {
+                output.push(' ');
+                while let Some(&c) = chars.peek() {
+                    if c == '\n' {
+                        break;
+                    }
+                    output.push(' ');
+                    chars.next();
+                }
+            }
+            '/' if chars.peek() == Some(&'*') => {
+                output.push(' ');
+                output.push(' ');
+                chars.next();
+                while let Some(c) = chars.next() {
+                    if c == '*' && chars.peek() == Some(&'/') {
+                        output.push(' ');
+                        output.push(' ');
+                        chars.next();
+                        break;
+                    }
+                    output.push(if c == '\n' { '\n' } else { ' ' });
+                }
+            }
+            ']' | '}' => {
+                if let Some(comma) = comma.take() {
+                    output.replace_range(comma..=comma, " ");
+                }
+                output.push(c);
+            }
+            ',' => {
+                comma = Some(output.len());
+                output.push(c);
+            }
+            c if c.is_ascii_whitespace() => output.push(c),
+            c => {
+                comma = None;
+                output.push(c);
+            }
+        }
+    }
+    output
+}
@@ -0,0 +1,203 @@
+//! A [`Deserializer`] wrapper renaming the fields of structs, see
+//! [`RenameKeys`].
+
+use core::cell::Cell;
+use core::fmt::{self, Debug, Formatter};
+use std::collections::HashMap;
+
+use serde::de::MapAccess as _;
+
+use crate::de::middleware::{self, Hint, Middleware, VariantCall, Visit};
+use crate::de::untag_structs::{Key, KeyVisitor};
+use crate::de::{DeserializeError, DeserializeResult, DeserializeSeed, Deserializer};
+use crate::de::{InplaceDeserializeError, InplaceDeserializeResult};
+use crate::de::{MapAccess, VariantAccess, Visitor};
+
+/// A [`Deserializer`] wrapper which renames the keys of every struct before
+/// they are handed to the visitor, e.g. to read legacy field names.
+///
+/// An API renaming its fields over time otherwise needs a `#[serde(rename)]`
+/// or `#[serde(alias)]` attribute per field and per name, fixed at compile
+/// time. `RenameKeys` takes the renames as a map from the name in the input
+/// to the name of the field instead. When a struct or a struct variant is
+/// visited as a map, every key found in the renames is replaced by its new
+/// name, at every depth of the value. The other keys, and the keys of maps
+/// which are not structs, are handed to the visitor unchanged.
+///
+/// # Examples
+///
+/// ```
+/// # use std::collections::HashMap;
+/// # use serde::Deserialize as _;
+/// # use dyn_serde::Deserializer;
+/// # use dyn_serde::de::RenameKeys;
+/// #
+/// #[derive(serde::Deserialize)]
+/// struct User {
+///     display_name: String,
+/// }
+///
+/// let renames = HashMap::from([("userName", "display_name")]);
+///
+/// let mut deserializer = serde_json::Deserializer::from_str(r#"{"userName": "ferris"}"#);
+/// let mut deserializer = <dyn Deserializer>::new(&mut deserializer);
+/// let mut deserializer = RenameKeys::new(&mut deserializer, renames);
+///
+/// let user = User::deserialize(&mut deserializer as &mut dyn Deserializer).unwrap();
+/// assert_eq!(user.display_name, "ferris");
+/// ```
+pub struct RenameKeys<'a, 'de> {
+    deserializer: &'a mut dyn Deserializer<'de>,
+    rename: Rename<'a>,
+}
+
+impl<'a, 'de> RenameKeys<'a, 'de> {
+    /// Wraps `deserializer`, renaming every key of a struct found in
+    /// `renames` to the name it maps to.
+    #[must_use]
+    pub fn new(
+        deserializer: &'a mut dyn Deserializer<'de>,
+        renames: HashMap<&'a str, &'static str>,
+    ) -> Self {
+        RenameKeys {
+            deserializer,
+            rename: Rename {
+                renames,
+                expecting_struct: Cell::new(false),
+            },
+        }
+    }
+
+    fn deserialize_hint(
+        &mut self,
+        hint: Hint,
+        visitor: &mut dyn Visitor<'de>,
+    ) -> InplaceDeserializeResult<()> {
+        middleware::deserialize(&self.rename, hint, self.deserializer, visitor)
+    }
+}
+
+impl Debug for RenameKeys<'_, '_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RenameKeys")
+            .field("renames", &self.rename.renames)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<'de> Deserializer<'de> for RenameKeys<'_, 'de> {
+    middleware::forward_to_deserialize_hint!();
+
+    fn dyn_is_human_readable(&self) -> bool {
+        self.deserializer.dyn_is_human_readable()
+    }
+}
+
+struct Rename<'a> {
+    renames: HashMap<&'a str, &'static str>,
+    /// Whether the next visit is the one of a struct.
+    expecting_struct: Cell<bool>,
+}
+
+impl<'de> Middleware<'de> for Rename<'_> {
+    fn deserialize(
+        &self,
+        hint: Hint,
+        deserializer: &mut dyn Deserializer<'de>,
+        visitor: &mut dyn Visitor<'de>,
+    ) -> InplaceDeserializeResult<()> {
+        self.expecting_struct.set(matches!(hint, Hint::Struct(..)));
+        hint.forward(deserializer, visitor)
+    }
+
+    fn visit(
+        &self,
+        visit: Visit<'_, 'de>,
+        visitor: &mut dyn Visitor<'de>,
+    ) -> DeserializeResult<()> {
+        match visit {
+            Visit::Map(access) if self.expecting_struct.replace(false) => {
+                let mut access = RenameMapAccess {
+                    inner: access,
+                    renames: &self.renames,
+                    error: None,
+                };
+                visitor
+                    .dyn_visit_map(&mut access)
+                    .map_err(|error| access.error.take().unwrap_or(error))
+            }
+            visit => {
+                self.expecting_struct.set(false);
+                visit.forward(visitor)
+            }
+        }
+    }
+
+    fn variant_access(
+        &self,
+        call: VariantCall<'_, 'de>,
+        access: &mut dyn VariantAccess<'de>,
+    ) -> InplaceDeserializeResult<()> {
+        self.expecting_struct
+            .set(matches!(call, VariantCall::Struct(..)));
+        call.forward(access)
+    }
+}
+
+/// The map of a struct, whose keys are renamed.
+struct RenameMapAccess<'a, 'de> {
+    inner: &'a mut dyn MapAccess<'de>,
+    renames: &'a HashMap<&'a str, &'static str>,
+    /// The error raised while renaming a key, if any.
+    error: Option<DeserializeError>,
+}
+
+impl<'de> RenameMapAccess<'_, 'de> {
+    /// Reads the next key and hands it to `seed`, renamed if it is one of the
+    /// renames.
+    fn next_key(&mut self, seed: &mut dyn DeserializeSeed<'de>) -> DeserializeResult<Option<()>> {
+        let Some(key) = self.inner.next_key_seed(KeyVisitor)? else {
+            return Ok(None);
+        };
+        let renamed = key.as_str().and_then(|key| self.renames.get(key));
+        match renamed {
+            Some(&name) => Key::BorrowedStr(name).deserialize(seed),
+            None => key.deserialize(seed),
+        }
+        .map(Some)
+    }
+}
+
+impl<'de> MapAccess<'de> for RenameMapAccess<'_, 'de> {
+    fn dyn_next_key(
+        &mut self,
+        seed: &mut dyn DeserializeSeed<'de>,
+    ) -> InplaceDeserializeResult<Option<()>> {
+        self.next_key(seed).map_err(|error| {
+            self.error.get_or_insert(error);
+            InplaceDeserializeError::Error
+        })
+    }
+
+    fn dyn_next_value(
+        &mut self,
+        seed: &mut dyn DeserializeSeed<'de>,
+    ) -> InplaceDeserializeResult<()> {
+        self.inner.dyn_next_value(seed)
+    }
+
+    fn dyn_next_entry(
+        &mut self,
+        kseed: &mut dyn DeserializeSeed<'de>,
+        vseed: &mut dyn DeserializeSeed<'de>,
+    ) -> InplaceDeserializeResult<Option<((), ())>> {
+        match self.dyn_next_key(kseed)? {
+            Some(()) => self.dyn_next_value(vseed).map(|()| Some(((), ()))),
+            None => Ok(None),
+        }
+    }
+
+    fn dyn_size_hint(&self) -> Option<usize> {
+        self.inner.dyn_size_hint()
+    }
+}
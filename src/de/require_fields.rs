@@ -0,0 +1,205 @@
+//! A [`Deserializer`] wrapper reporting every missing required field at once,
+//! see [`RequireFields`].
+
+use core::cell::{Cell, RefCell};
+use core::fmt::{self, Debug, Formatter, Write as _};
+
+#[cfg(not(feature = "std"))]
+use alloc::string::String;
+#[cfg(not(feature = "std"))]
+use alloc::vec;
+#[cfg(not(feature = "std"))]
+use alloc::vec::Vec;
+
+use serde::de::Error as _;
+
+use crate::de::middleware::{self, Hint, Middleware, Visit};
+use crate::de::{DeserializeError, DeserializeResult, DeserializeSeed, Deserializer};
+use crate::de::{InplaceDeserializeError, InplaceDeserializeResult, MapAccess, Visitor};
+
+/// A [`Deserializer`] wrapper which checks that the value, a struct or a map,
+/// has every field of a list of required fields, and reports all of the
+/// absent ones at once.
+///
+/// A derived struct stops at its first missing field, so fixing a
+/// configuration file takes one attempt per field. Once the last entry of the
+/// map has been read, `RequireFields` fails with the message ``missing fields
+/// `a`, `b` `` if any required field is absent, before the visitor gets the
+/// chance to report only the first one.
+///
+/// Only the fields of the value itself are checked, matched against the keys
+/// as they appear in the input, i.e. after renaming. Structs and maps nested
+/// inside it, and structs visited as sequences, are left unchanged.
+///
+/// # Examples
+///
+/// ```
+/// # use serde::Deserialize as _;
+/// # use dyn_serde::Deserializer;
+/// # use dyn_serde::de::{InplaceDeserializer, RequireFields};
+/// #
+/// #[derive(serde::Deserialize)]
+/// struct Config {
+///     host: String,
+///     port: u16,
+/// }
+///
+/// let mut deserializer = serde_json::Deserializer::from_str("{}");
+/// let mut deserializer = <dyn Deserializer>::new(&mut deserializer);
+/// let mut required = RequireFields::new(&mut deserializer, &["host", "port"]);
+///
+/// assert!(Config::deserialize(&mut required as &mut dyn Deserializer).is_err());
+/// let InplaceDeserializer::Error(error, _) = deserializer else {
+///     unreachable!();
+/// };
+/// assert!(error.to_string().starts_with("missing fields `host`, `port`"));
+/// ```
+pub struct RequireFields<'a, 'de> {
+    deserializer: &'a mut dyn Deserializer<'de>,
+    checker: Checker<'a>,
+}
+
+impl<'a, 'de> RequireFields<'a, 'de> {
+    /// Wraps `deserializer`, requiring the value to have every field of
+    /// `required`.
+    #[must_use]
+    pub fn new(deserializer: &'a mut dyn Deserializer<'de>, required: &'a [&'a str]) -> Self {
+        RequireFields {
+            deserializer,
+            checker: Checker {
+                required,
+                present: RefCell::new(vec![false; required.len()]),
+                depth: Cell::new(0),
+                key: Cell::new(false),
+                missing: RefCell::new(None),
+            },
+        }
+    }
+
+    fn deserialize_hint(
+        &mut self,
+        hint: Hint,
+        visitor: &mut dyn Visitor<'de>,
+    ) -> InplaceDeserializeResult<()> {
+        middleware::deserialize(&self.checker, hint, self.deserializer, visitor)
+    }
+}
+
+impl Debug for RequireFields<'_, '_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RequireFields")
+            .field("required", &self.checker.required)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<'de> Deserializer<'de> for RequireFields<'_, 'de> {
+    middleware::forward_to_deserialize_hint!();
+
+    fn dyn_is_human_readable(&self) -> bool {
+        self.deserializer.dyn_is_human_readable()
+    }
+}
+
+struct Checker<'a> {
+    required: &'a [&'a str],
+    /// Whether each required field has been read.
+    present: RefCell<Vec<bool>>,
+    /// The number of compounds being visited.
+    depth: Cell<usize>,
+    /// Whether the next visit is a key of the value itself.
+    key: Cell<bool>,
+    /// The message listing the missing fields, once the map has been read.
+    missing: RefCell<Option<String>>,
+}
+
+impl Checker<'_> {
+    fn check(&self, key: &[u8]) {
+        let mut present = self.present.borrow_mut();
+        for (field, present) in self.required.iter().zip(present.iter_mut()) {
+            if field.as_bytes() == key {
+                *present = true;
+            }
+        }
+    }
+
+    /// Returns the message listing the missing fields, if any.
+    fn missing(&self) -> Option<String> {
+        let present = self.present.borrow();
+        let mut missing = self
+            .required
+            .iter()
+            .zip(present.iter())
+            .filter(|(_, present)| !**present)
+            .map(|(field, _)| field)
+            .peekable();
+        let first = missing.next()?;
+
+        let mut message = String::new();
+        if missing.peek().is_none() {
+            let _ = write!(message, "missing field `{first}`");
+        } else {
+            let _ = write!(message, "missing fields `{first}`");
+            for field in missing {
+                let _ = write!(message, ", `{field}`");
+            }
+        }
+        Some(message)
+    }
+}
+
+impl<'de> Middleware<'de> for Checker<'_> {
+    fn visit(
+        &self,
+        visit: Visit<'_, 'de>,
+        visitor: &mut dyn Visitor<'de>,
+    ) -> DeserializeResult<()> {
+        if self.key.take() {
+            match &visit {
+                Visit::Str(key) | Visit::BorrowedStr(key) => self.check(key.as_bytes()),
+                Visit::String(key) => self.check(key.as_bytes()),
+                Visit::Bytes(key) | Visit::BorrowedBytes(key) => self.check(key),
+                Visit::ByteBuf(key) => self.check(key),
+                _ => {}
+            }
+        }
+
+        if !matches!(visit, Visit::Seq(_) | Visit::Map(_) | Visit::Enum(_)) {
+            return visit.forward(visitor);
+        }
+        let depth = self.depth.get();
+        if depth == 0 {
+            self.present.borrow_mut().fill(false);
+        }
+        self.depth.set(depth + 1);
+        let result = visit.forward(visitor);
+        self.depth.set(depth);
+
+        // the failure of the map is replaced by the list of missing fields.
+        match self.missing.take() {
+            Some(missing) if result.is_err() => Err(DeserializeError::custom(missing)),
+            _ => result,
+        }
+    }
+
+    fn next_key(
+        &self,
+        access: &mut dyn MapAccess<'de>,
+        seed: &mut dyn DeserializeSeed<'de>,
+    ) -> InplaceDeserializeResult<Option<()>> {
+        if self.depth.get() != 1 {
+            return access.dyn_next_key(seed);
+        }
+
+        self.key.set(true);
+        let result = access.dyn_next_key(seed);
+        self.key.set(false);
+        if let Ok(None) = result
+            && let Some(missing) = self.missing()
+        {
+            *self.missing.borrow_mut() = Some(missing);
+            return Err(InplaceDeserializeError::Error);
+        }
+        result
+    }
+}
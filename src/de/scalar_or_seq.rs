@@ -0,0 +1,404 @@
+//! [`Deserializer`] wrappers accepting a scalar for a sequence of one
+//! element, see [`ScalarOrSeq`] and [`OneOrMany`].
+
+#[cfg(not(feature = "std"))]
+use alloc::string::String;
+use core::cell::Cell;
+use core::fmt::{self, Debug, Formatter};
+
+use serde::de::{Error as _, IgnoredAny, SeqAccess as _};
+
+use crate::de::middleware::{self, Hint, Middleware, Visit};
+use crate::de::{DeserializeError, DeserializeResult, DeserializeSeed, Deserializer};
+use crate::de::{InplaceDeserializeError, InplaceDeserializeResult};
+use crate::de::{SeqAccess, Visitor};
+
+/// A [`Deserializer`] wrapper which reads a scalar where a sequence is
+/// expected as a sequence of that single element, and a sequence of a single
+/// element where a scalar is expected as that element.
+///
+/// Loose sources often send either `"x"` or `["x"]` for the same field, e.g.
+/// a list which usually holds one value. Deserializing a `Vec<String>` from
+/// `"x"` with `ScalarOrSeq` yields `["x"]`, while deserializing a `String`
+/// from `["x"]` yields `"x"`. A sequence read as a scalar must hold exactly
+/// one element, or the deserialization fails with an invalid length.
+///
+/// The scalars are booleans, integers, floats, chars and strings; bytes are
+/// left unchanged since formats commonly read sequences of integers as bytes.
+/// Both forms are accepted at every depth of the value. Telling the forms
+/// apart relies on `deserialize_any`, so the format must be self-describing,
+/// as JSON is.
+///
+/// # Examples
+///
+/// ```
+/// # use serde::Deserialize as _;
+/// # use dyn_serde::Deserializer;
+/// # use dyn_serde::de::ScalarOrSeq;
+/// #
+/// let mut deserializer = serde_json::Deserializer::from_str(r#""x""#);
+/// let mut deserializer = <dyn Deserializer>::new(&mut deserializer);
+/// let mut deserializer = ScalarOrSeq::new(&mut deserializer);
+///
+/// let values = Vec::<String>::deserialize(&mut deserializer as &mut dyn Deserializer).unwrap();
+/// assert_eq!(values, ["x"]);
+/// ```
+pub struct ScalarOrSeq<'a, 'de> {
+    deserializer: &'a mut dyn Deserializer<'de>,
+    adapt: Adapt,
+}
+
+impl<'a, 'de> ScalarOrSeq<'a, 'de> {
+    /// Wraps `deserializer`, accepting scalars and sequences of a single
+    /// element in place of each other.
+    #[must_use]
+    pub fn new(deserializer: &'a mut dyn Deserializer<'de>) -> Self {
+        let human_readable = deserializer.dyn_is_human_readable();
+        ScalarOrSeq {
+            deserializer,
+            adapt: Adapt {
+                expecting: Cell::new(None),
+                seq_only: false,
+                human_readable,
+            },
+        }
+    }
+
+    fn deserialize_hint(
+        &mut self,
+        hint: Hint,
+        visitor: &mut dyn Visitor<'de>,
+    ) -> InplaceDeserializeResult<()> {
+        middleware::deserialize(&self.adapt, hint, self.deserializer, visitor)
+    }
+}
+
+impl Debug for ScalarOrSeq<'_, '_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ScalarOrSeq").finish_non_exhaustive()
+    }
+}
+
+impl<'de> Deserializer<'de> for ScalarOrSeq<'_, 'de> {
+    middleware::forward_to_deserialize_hint!();
+
+    fn dyn_is_human_readable(&self) -> bool {
+        self.deserializer.dyn_is_human_readable()
+    }
+}
+
+/// A [`Deserializer`] wrapper which reads a scalar where a sequence is
+/// expected as a sequence of that single element, e.g. for the repeated
+/// fields of a query string or a form.
+///
+/// Decoders of query strings and forms commonly give a parameter sent once,
+/// as in `tag=x`, as a string, and a parameter sent several times, as in
+/// `tag=x&tag=y`, as a sequence. `OneOrMany` reads both into a `Vec<String>`,
+/// as `["x"]` and `["x", "y"]`, and a field marked `#[serde(default)]` reads
+/// as an empty vector when the parameter is absent.
+///
+/// Unlike [`ScalarOrSeq`], a sequence is never read where a scalar is
+/// expected, so that a parameter sent several times for a single value still
+/// fails rather than losing the other values. The scalars, the depth and the
+/// need for a self-describing format are the same as for `ScalarOrSeq`.
+///
+/// # Examples
+///
+/// ```
+/// # use serde::Deserialize as _;
+/// # use dyn_serde::Deserializer;
+/// # use dyn_serde::de::OneOrMany;
+/// #
+/// #[derive(serde::Deserialize)]
+/// struct Query {
+///     #[serde(default)]
+///     tag: Vec<String>,
+/// }
+///
+/// let mut deserializer = serde_json::Deserializer::from_str(r#"{"tag": "x"}"#);
+/// let mut deserializer = <dyn Deserializer>::new(&mut deserializer);
+/// let mut deserializer = OneOrMany::new(&mut deserializer);
+///
+/// let query = Query::deserialize(&mut deserializer as &mut dyn Deserializer).unwrap();
+/// assert_eq!(query.tag, ["x"]);
+/// ```
+pub struct OneOrMany<'a, 'de> {
+    deserializer: &'a mut dyn Deserializer<'de>,
+    adapt: Adapt,
+}
+
+impl<'a, 'de> OneOrMany<'a, 'de> {
+    /// Wraps `deserializer`, accepting scalars in place of sequences of a
+    /// single element.
+    #[must_use]
+    pub fn new(deserializer: &'a mut dyn Deserializer<'de>) -> Self {
+        let human_readable = deserializer.dyn_is_human_readable();
+        OneOrMany {
+            deserializer,
+            adapt: Adapt {
+                expecting: Cell::new(None),
+                seq_only: true,
+                human_readable,
+            },
+        }
+    }
+
+    fn deserialize_hint(
+        &mut self,
+        hint: Hint,
+        visitor: &mut dyn Visitor<'de>,
+    ) -> InplaceDeserializeResult<()> {
+        middleware::deserialize(&self.adapt, hint, self.deserializer, visitor)
+    }
+}
+
+impl Debug for OneOrMany<'_, '_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("OneOrMany").finish_non_exhaustive()
+    }
+}
+
+impl<'de> Deserializer<'de> for OneOrMany<'_, 'de> {
+    middleware::forward_to_deserialize_hint!();
+
+    fn dyn_is_human_readable(&self) -> bool {
+        self.deserializer.dyn_is_human_readable()
+    }
+}
+
+struct Adapt {
+    /// What the value whose visit comes next is deserialized as.
+    expecting: Cell<Option<Hint>>,
+    /// Whether only sequences accept the other form, and scalars do not.
+    seq_only: bool,
+    human_readable: bool,
+}
+
+fn is_scalar(hint: Hint) -> bool {
+    matches!(
+        hint,
+        Hint::Bool
+            | Hint::I8
+            | Hint::I16
+            | Hint::I32
+            | Hint::I64
+            | Hint::I128
+            | Hint::U8
+            | Hint::U16
+            | Hint::U32
+            | Hint::U64
+            | Hint::U128
+            | Hint::F32
+            | Hint::F64
+            | Hint::Char
+            | Hint::Str
+            | Hint::String
+    )
+}
+
+impl<'de> Middleware<'de> for Adapt {
+    fn deserialize(
+        &self,
+        hint: Hint,
+        deserializer: &mut dyn Deserializer<'de>,
+        visitor: &mut dyn Visitor<'de>,
+    ) -> InplaceDeserializeResult<()> {
+        self.expecting.set(Some(hint));
+        if (is_scalar(hint) && !self.seq_only) || hint == Hint::Seq {
+            // formats reject the other form before visiting it.
+            Hint::Any.forward(deserializer, visitor)
+        } else {
+            hint.forward(deserializer, visitor)
+        }
+    }
+
+    fn visit(
+        &self,
+        visit: Visit<'_, 'de>,
+        visitor: &mut dyn Visitor<'de>,
+    ) -> DeserializeResult<()> {
+        match (self.expecting.take(), visit) {
+            (Some(hint), Visit::Seq(mut access)) if is_scalar(hint) && !self.seq_only => {
+                // the visitor has its value once visited, so the element is
+                // only replayed once the sequence is known to hold no other.
+                let record = Record {
+                    armed: Cell::new(true),
+                    visit: Cell::new(None),
+                };
+                let mut seed = RecordSeed {
+                    hint,
+                    record: &record,
+                    visitor: &mut *visitor,
+                };
+                if access.dyn_next_element(&mut seed)?.is_none() {
+                    return Err(DeserializeError::invalid_length(0, &"a single element"));
+                }
+                if access.next_element::<IgnoredAny>()?.is_some() {
+                    return Err(DeserializeError::invalid_length(2, &"a single element"));
+                }
+                match record.visit.take() {
+                    Some(visit) => visit.forward(visitor),
+                    None => Ok(()),
+                }
+            }
+            (
+                Some(Hint::Seq),
+                visit @ (Visit::Bool(_)
+                | Visit::I8(_)
+                | Visit::I16(_)
+                | Visit::I32(_)
+                | Visit::I64(_)
+                | Visit::I128(_)
+                | Visit::U8(_)
+                | Visit::U16(_)
+                | Visit::U32(_)
+                | Visit::U64(_)
+                | Visit::U128(_)
+                | Visit::F32(_)
+                | Visit::F64(_)
+                | Visit::Char(_)
+                | Visit::Str(_)
+                | Visit::BorrowedStr(_)
+                | Visit::String(_)),
+            ) => {
+                let mut access = Single {
+                    element: Some(Replay {
+                        visit: Some(visit),
+                        human_readable: self.human_readable,
+                        error: None,
+                    }),
+                    error: None,
+                };
+                visitor
+                    .dyn_visit_seq(&mut access)
+                    .map_err(|error| access.error.take().unwrap_or(error))
+            }
+            (_, visit) => visit.forward(visitor),
+        }
+    }
+}
+
+/// Deserializes the single element of a sequence for the visitor of the
+/// scalar, recording the element if it is a scalar.
+struct RecordSeed<'a, 'de> {
+    hint: Hint,
+    record: &'a Record<'de>,
+    visitor: &'a mut dyn Visitor<'de>,
+}
+
+impl<'de> DeserializeSeed<'de> for RecordSeed<'_, 'de> {
+    fn dyn_deserialize(
+        &mut self,
+        deserializer: &mut dyn Deserializer<'de>,
+    ) -> DeserializeResult<()> {
+        Ok(middleware::deserialize(
+            self.record,
+            self.hint,
+            deserializer,
+            self.visitor,
+        )?)
+    }
+}
+
+struct Record<'de> {
+    /// Whether the next visit is the element itself, rather than a part of it.
+    armed: Cell<bool>,
+    /// The element recorded, if it is a scalar.
+    visit: Cell<Option<Visit<'de, 'de>>>,
+}
+
+impl<'de> Middleware<'de> for Record<'de> {
+    fn visit(
+        &self,
+        visit: Visit<'_, 'de>,
+        visitor: &mut dyn Visitor<'de>,
+    ) -> DeserializeResult<()> {
+        if !self.armed.take() {
+            return visit.forward(visitor);
+        }
+        let visit = match visit {
+            Visit::Bool(v) => Visit::Bool(v),
+            Visit::I8(v) => Visit::I8(v),
+            Visit::I16(v) => Visit::I16(v),
+            Visit::I32(v) => Visit::I32(v),
+            Visit::I64(v) => Visit::I64(v),
+            Visit::I128(v) => Visit::I128(v),
+            Visit::U8(v) => Visit::U8(v),
+            Visit::U16(v) => Visit::U16(v),
+            Visit::U32(v) => Visit::U32(v),
+            Visit::U64(v) => Visit::U64(v),
+            Visit::U128(v) => Visit::U128(v),
+            Visit::F32(v) => Visit::F32(v),
+            Visit::F64(v) => Visit::F64(v),
+            Visit::Char(v) => Visit::Char(v),
+            Visit::Str(v) => Visit::String(String::from(v)),
+            Visit::BorrowedStr(v) => Visit::BorrowedStr(v),
+            Visit::String(v) => Visit::String(v),
+            // anything else fails with the visitor of the scalar right away.
+            visit => return visit.forward(visitor),
+        };
+        self.visit.set(Some(visit));
+        Ok(())
+    }
+}
+
+/// A sequence of the single scalar visited.
+struct Single<'a, 'de> {
+    element: Option<Replay<'a, 'de>>,
+    /// The error raised while deserializing the element, if any.
+    error: Option<DeserializeError>,
+}
+
+impl<'de> SeqAccess<'de> for Single<'_, 'de> {
+    fn dyn_next_element(
+        &mut self,
+        seed: &mut dyn DeserializeSeed<'de>,
+    ) -> InplaceDeserializeResult<Option<()>> {
+        let Some(mut element) = self.element.take() else {
+            return Ok(None);
+        };
+        match seed.dyn_deserialize(&mut element) {
+            Ok(()) => Ok(Some(())),
+            Err(error) => {
+                self.error.get_or_insert(element.error.unwrap_or(error));
+                Err(InplaceDeserializeError::Error)
+            }
+        }
+    }
+
+    fn dyn_size_hint(&self) -> Option<usize> {
+        Some(usize::from(self.element.is_some()))
+    }
+}
+
+/// A deserializer visiting the scalar again, whatever it is asked for.
+struct Replay<'a, 'de> {
+    visit: Option<Visit<'a, 'de>>,
+    human_readable: bool,
+    /// The error raised by the visitor, if any.
+    error: Option<DeserializeError>,
+}
+
+impl<'de> Replay<'_, 'de> {
+    fn deserialize_hint(
+        &mut self,
+        _hint: Hint,
+        visitor: &mut dyn Visitor<'de>,
+    ) -> InplaceDeserializeResult<()> {
+        let Some(visit) = self.visit.take() else {
+            return Err(InplaceDeserializeError::NotDeserializer);
+        };
+        visit.forward(visitor).map_err(|error| {
+            self.error.get_or_insert(error);
+            InplaceDeserializeError::Error
+        })
+    }
+}
+
+impl<'de> Deserializer<'de> for Replay<'_, 'de> {
+    middleware::forward_to_deserialize_hint!();
+
+    fn dyn_is_human_readable(&self) -> bool {
+        self.human_readable
+    }
+}
@@ -0,0 +1,76 @@
+//! Preallocated sequence deserialization, see [`deserialize_seq_into_vec`].
+
+use core::mem;
+
+#[cfg(not(feature = "std"))]
+use alloc::vec::Vec;
+
+use serde::de::SeqAccess as _;
+
+use crate::de::{DeserializeResult, SeqAccess};
+
+/// Deserializes the remaining elements of `access` into a vector, reserving
+/// the number of elements given by its `dyn_size_hint` up front.
+///
+/// A visitor collecting a large sequence element by element would otherwise
+/// grow its vector several times. Formats which know the length of their
+/// sequences, e.g. CBOR or MessagePack, hint at it, while the others, e.g.
+/// JSON, hint at nothing and the vector grows as usual. As the hint comes from
+/// the input, at most 1 MiB is reserved up front, so that a malicious length
+/// does not exhaust the memory.
+///
+/// # Errors
+///
+/// Returns an error if an element fails to deserialize.
+///
+/// # Examples
+///
+/// ```
+/// # use std::fmt;
+/// # use serde::de::Error as _;
+/// # use dyn_serde::de::{InplaceSeqAccess, deserialize_seq_into_vec};
+/// #
+/// struct Samples(Vec<f32>);
+///
+/// impl<'de> serde::Deserialize<'de> for Samples {
+///     fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+///         struct SamplesVisitor;
+///
+///         impl<'de> serde::de::Visitor<'de> for SamplesVisitor {
+///             type Value = Samples;
+///
+///             fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+///                 f.write_str("a sequence of samples")
+///             }
+///
+///             fn visit_seq<A: serde::de::SeqAccess<'de>>(self, seq: A) -> Result<Samples, A::Error> {
+///                 let mut access = InplaceSeqAccess::SeqAccess(seq);
+///                 deserialize_seq_into_vec(&mut access).map(Samples).map_err(|error| match access {
+///                     InplaceSeqAccess::Error(error) => error,
+///                     _ => A::Error::custom(error),
+///                 })
+///             }
+///         }
+///
+///         deserializer.deserialize_seq(SamplesVisitor)
+///     }
+/// }
+///
+/// let samples: Samples = serde_json::from_str("[0.5, 1]").unwrap();
+/// assert_eq!(samples.0, [0.5, 1.0]);
+/// ```
+pub fn deserialize_seq_into_vec<'de, T>(
+    mut access: &mut dyn SeqAccess<'de>,
+) -> DeserializeResult<Vec<T>>
+where
+    T: serde::Deserialize<'de>,
+{
+    const MAX_PREALLOCATED_BYTES: usize = 1024 * 1024;
+
+    let max = MAX_PREALLOCATED_BYTES / mem::size_of::<T>().max(1);
+    let mut elements = Vec::with_capacity(access.dyn_size_hint().unwrap_or(0).min(max));
+    while let Some(element) = access.next_element()? {
+        elements.push(element);
+    }
+    Ok(elements)
+}
@@ -0,0 +1,191 @@
+//! A [`serde::de::Visitor`] accepting fields by position or by name, see
+//! [`SeqOrMap`].
+
+use core::fmt::{self, Debug, Formatter};
+
+#[cfg(not(feature = "std"))]
+use alloc::vec;
+
+use serde::de::{Error as _, IgnoredAny};
+
+use crate::de::{DeserializeResult, Deserializer, InplaceDeserializer};
+
+/// A [`serde::de::Visitor`] which fills the fields of a struct either from a
+/// sequence, by position, or from a map, by name.
+///
+/// Some loose formats send the same struct as an array (e.g. `[1, 2]`) or as
+/// an object (e.g. `{"a": 1, "b": 2}`). Given the names of the fields,
+/// `SeqOrMap` calls `fill` with the index of every field it meets and a
+/// deserializer for its value, whichever form is visited:
+///
+/// * The elements of a sequence fill the fields in order. A sequence may be
+///   shorter than the fields, but not longer.
+/// * The entries of a map fill the fields by name, or by index if the key is
+///   an integer. Unknown keys are ignored and a field given twice is an
+///   error.
+///
+/// Which fields are missing is left to the caller, who usually keeps every
+/// field in an `Option` until the end.
+///
+/// # Examples
+///
+/// ```
+/// # use serde::de::Error as _;
+/// # use dyn_serde::de::SeqOrMap;
+/// #
+/// #[derive(Debug, PartialEq)]
+/// struct Point {
+///     x: i32,
+///     y: i32,
+/// }
+///
+/// impl<'de> serde::Deserialize<'de> for Point {
+///     fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+///         let (mut x, mut y) = (None, None);
+///         deserializer.deserialize_any(SeqOrMap::new(&["x", "y"], &mut |index, value| {
+///             match index {
+///                 0 => x = Some(serde::Deserialize::deserialize(value)?),
+///                 _ => y = Some(serde::Deserialize::deserialize(value)?),
+///             }
+///             Ok(())
+///         }))?;
+///         Ok(Point {
+///             x: x.ok_or_else(|| D::Error::missing_field("x"))?,
+///             y: y.ok_or_else(|| D::Error::missing_field("y"))?,
+///         })
+///     }
+/// }
+///
+/// let point: Point = serde_json::from_str("[1, 2]").unwrap();
+/// assert_eq!(point, Point { x: 1, y: 2 });
+/// let point: Point = serde_json::from_str(r#"{"y": 2, "x": 1}"#).unwrap();
+/// assert_eq!(point, Point { x: 1, y: 2 });
+/// ```
+pub struct SeqOrMap<'a, 'de> {
+    fields: &'static [&'static str],
+    fill: &'a mut dyn FnMut(usize, &mut dyn Deserializer<'de>) -> DeserializeResult<()>,
+}
+
+impl<'a, 'de> SeqOrMap<'a, 'de> {
+    /// Creates a visitor filling the fields named `fields` with `fill`.
+    #[must_use]
+    pub fn new(
+        fields: &'static [&'static str],
+        fill: &'a mut dyn FnMut(usize, &mut dyn Deserializer<'de>) -> DeserializeResult<()>,
+    ) -> Self {
+        SeqOrMap { fields, fill }
+    }
+}
+
+impl Debug for SeqOrMap<'_, '_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SeqOrMap")
+            .field("fields", &self.fields)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<'de> serde::de::Visitor<'de> for SeqOrMap<'_, 'de> {
+    type Value = ();
+
+    fn expecting(&self, formatter: &mut Formatter) -> fmt::Result {
+        write!(formatter, "a sequence or a map of fields {:?}", self.fields)
+    }
+
+    fn visit_seq<A>(self, mut seq: A) -> Result<(), A::Error>
+    where
+        A: serde::de::SeqAccess<'de>,
+    {
+        for index in 0..self.fields.len() {
+            let seed = FieldSeed {
+                index,
+                fill: &mut *self.fill,
+            };
+            if seq.next_element_seed(seed)?.is_none() {
+                return Ok(());
+            }
+        }
+
+        if seq.next_element::<IgnoredAny>()?.is_some() {
+            return Err(A::Error::invalid_length(self.fields.len() + 1, &self));
+        }
+        Ok(())
+    }
+
+    fn visit_map<A>(self, mut map: A) -> Result<(), A::Error>
+    where
+        A: serde::de::MapAccess<'de>,
+    {
+        let mut seen = vec![false; self.fields.len()];
+        while let Some(index) = map.next_key_seed(FieldIndex(self.fields))? {
+            let Some(index) = index else {
+                map.next_value::<IgnoredAny>()?;
+                continue;
+            };
+            if seen[index] {
+                return Err(A::Error::duplicate_field(self.fields[index]));
+            }
+            seen[index] = true;
+            map.next_value_seed(FieldSeed {
+                index,
+                fill: &mut *self.fill,
+            })?;
+        }
+        Ok(())
+    }
+}
+
+/// Deserializes a key into the index of the field it names, if any.
+struct FieldIndex(&'static [&'static str]);
+
+impl<'de> serde::de::DeserializeSeed<'de> for FieldIndex {
+    type Value = Option<usize>;
+
+    fn deserialize<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        deserializer.deserialize_identifier(self)
+    }
+}
+
+impl serde::de::Visitor<'_> for FieldIndex {
+    type Value = Option<usize>;
+
+    fn expecting(&self, formatter: &mut Formatter) -> fmt::Result {
+        formatter.write_str("a field name or index")
+    }
+
+    fn visit_u64<E>(self, v: u64) -> Result<Self::Value, E> {
+        Ok(usize::try_from(v)
+            .ok()
+            .filter(|&index| index < self.0.len()))
+    }
+
+    fn visit_str<E>(self, v: &str) -> Result<Self::Value, E> {
+        Ok(self.0.iter().position(|&field| field == v))
+    }
+
+    fn visit_bytes<E>(self, v: &[u8]) -> Result<Self::Value, E> {
+        Ok(self.0.iter().position(|&field| field.as_bytes() == v))
+    }
+}
+
+/// Deserializes the value of a field with the `fill` function.
+struct FieldSeed<'a, 'b, 'de> {
+    index: usize,
+    fill: &'a mut (dyn FnMut(usize, &mut dyn Deserializer<'de>) -> DeserializeResult<()> + 'b),
+}
+
+impl<'de> serde::de::DeserializeSeed<'de> for FieldSeed<'_, '_, 'de> {
+    type Value = ();
+
+    fn deserialize<D>(self, deserializer: D) -> Result<(), D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let mut deserializer = InplaceDeserializer::Deserializer(deserializer);
+        let result = (self.fill)(self.index, &mut deserializer);
+        deserializer.into_result(result)
+    }
+}
@@ -0,0 +1,96 @@
+//! Timestamps deserialized with a chosen format, see [`TimestampFormat`].
+
+use core::fmt::{self, Debug, Formatter};
+
+use time::OffsetDateTime;
+use time::parsing::Parsable;
+
+/// A [`serde::de::DeserializeSeed`] reading a timestamp from a string in a
+/// chosen format.
+///
+/// This is the counterpart of [`ser::TimestampFormat`]: the format is any of
+/// the well-known formats of `time`, or a format description parsed at
+/// runtime, rather than a `deserialize_with` function per format. The seed is
+/// handed to `next_element_seed` or `next_value_seed` by a visitor, or wrapped
+/// in an [`InplaceDeserializeSeed`] for the dynamic path.
+///
+/// [`ser::TimestampFormat`]: crate::ser::TimestampFormat
+/// [`InplaceDeserializeSeed`]: crate::de::InplaceDeserializeSeed
+///
+/// # Examples
+///
+/// ```
+/// # use serde::de::DeserializeSeed as _;
+/// # use time::format_description::well_known::Rfc3339;
+/// # use time::macros::datetime;
+/// # use dyn_serde::Deserializer;
+/// # use dyn_serde::de::TimestampFormat;
+/// #
+/// let mut deserializer = serde_json::Deserializer::from_str(r#""2024-03-01T12:30:00+01:00""#);
+/// let mut deserializer = <dyn Deserializer>::new(&mut deserializer);
+/// let deserializer = &mut deserializer as &mut dyn Deserializer;
+///
+/// let datetime = TimestampFormat::new(&Rfc3339).deserialize(deserializer).unwrap();
+/// assert_eq!(datetime, datetime!(2024-03-01 12:30:00 +01:00));
+/// ```
+pub struct TimestampFormat<'a, F: ?Sized> {
+    format: &'a F,
+}
+
+impl<'a, F> TimestampFormat<'a, F>
+where
+    F: Parsable + ?Sized,
+{
+    /// Creates a seed reading a timestamp formatted with `format`.
+    #[must_use]
+    pub fn new(format: &'a F) -> Self {
+        TimestampFormat { format }
+    }
+}
+
+impl<F: ?Sized> Clone for TimestampFormat<'_, F> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<F: ?Sized> Copy for TimestampFormat<'_, F> {}
+
+impl<F: ?Sized> Debug for TimestampFormat<'_, F> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TimestampFormat").finish_non_exhaustive()
+    }
+}
+
+impl<'de, F> serde::de::DeserializeSeed<'de> for TimestampFormat<'_, F>
+where
+    F: Parsable + ?Sized,
+{
+    type Value = OffsetDateTime;
+
+    fn deserialize<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        deserializer.deserialize_str(self)
+    }
+}
+
+impl<F> serde::de::Visitor<'_> for TimestampFormat<'_, F>
+where
+    F: Parsable + ?Sized,
+{
+    type Value = OffsetDateTime;
+
+    fn expecting(&self, formatter: &mut Formatter) -> fmt::Result {
+        formatter.write_str("a formatted timestamp")
+    }
+
+    fn visit_str<E>(self, v: &str) -> Result<Self::Value, E>
+    where
+        E: serde::de::Error,
+    {
+        OffsetDateTime::parse(v, self.format)
+            .map_err(|error| E::custom(format_args!("invalid timestamp `{v}`: {error}")))
+    }
+}
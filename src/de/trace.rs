@@ -0,0 +1,166 @@
+//! A [`Deserializer`] wrapper tracing its calls, see [`TraceDeserializer`].
+
+use core::cell::RefCell;
+use core::fmt::{self, Debug, Formatter};
+
+#[cfg(not(feature = "std"))]
+use alloc::string::ToString;
+
+use crate::de::middleware::{self, Hint, Middleware, VariantCall, Visit};
+use crate::de::{DeserializeResult, DeserializeSeed, Deserializer, EnumAccess};
+use crate::de::{
+    InplaceDeserializeError, InplaceDeserializeResult, MapAccess, SeqAccess, VariantAccess, Visitor,
+};
+
+/// A [`Deserializer`] wrapper which reports every call made during the
+/// deserialization, for debugging format mismatches such as `invalid type`
+/// errors.
+///
+/// Each `dyn_deserialize_*` hint is reported with its arguments before it is
+/// delegated to the wrapped deserializer, e.g.
+/// `deserialize_struct("Point", ["x", "y"])`. So are the visitor callbacks
+/// (e.g. `visit_str("x")`), the calls on the sequence, map and enum accesses
+/// (e.g. `next_key`) and the errors raised by the visitor (e.g.
+/// `error: invalid type: ...`), at every depth of the value. Errors raised by
+/// the wrapped deserializer itself are kept by the deserializer and are not
+/// reported.
+///
+/// # Examples
+///
+/// ```
+/// # use serde::Deserialize as _;
+/// # use dyn_serde::Deserializer;
+/// # use dyn_serde::de::TraceDeserializer;
+/// #
+/// let mut trace = Vec::new();
+/// let mut deserializer = serde_json::Deserializer::from_str("[1]");
+/// let mut deserializer = <dyn Deserializer>::new(&mut deserializer);
+/// let mut push = |call: &str| trace.push(call.to_string());
+/// let mut deserializer = TraceDeserializer::new(&mut deserializer, &mut push);
+///
+/// let value = <(u8,)>::deserialize(&mut deserializer as &mut dyn Deserializer).unwrap();
+/// assert_eq!(value, (1,));
+/// assert_eq!(
+///     trace,
+///     ["deserialize_tuple(1)", "visit_seq", "next_element", "deserialize_u8", "visit_u64(1)"],
+/// );
+/// ```
+pub struct TraceDeserializer<'a, 'de> {
+    deserializer: &'a mut dyn Deserializer<'de>,
+    tracer: Tracer<'a>,
+}
+
+impl<'a, 'de> TraceDeserializer<'a, 'de> {
+    /// Wraps `deserializer`, reporting every call to `trace`.
+    #[must_use]
+    pub fn new(
+        deserializer: &'a mut dyn Deserializer<'de>,
+        trace: &'a mut dyn FnMut(&str),
+    ) -> Self {
+        TraceDeserializer {
+            deserializer,
+            tracer: Tracer(RefCell::new(trace)),
+        }
+    }
+
+    fn deserialize_hint(
+        &mut self,
+        hint: Hint,
+        visitor: &mut dyn Visitor<'de>,
+    ) -> InplaceDeserializeResult<()> {
+        middleware::deserialize(&self.tracer, hint, self.deserializer, visitor)
+    }
+}
+
+impl Debug for TraceDeserializer<'_, '_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TraceDeserializer").finish_non_exhaustive()
+    }
+}
+
+impl<'de> Deserializer<'de> for TraceDeserializer<'_, 'de> {
+    middleware::forward_to_deserialize_hint!();
+
+    fn dyn_is_human_readable(&self) -> bool {
+        self.deserializer.dyn_is_human_readable()
+    }
+}
+
+struct Tracer<'a>(RefCell<&'a mut dyn FnMut(&str)>);
+
+impl Tracer<'_> {
+    fn trace(&self, call: &dyn fmt::Display) {
+        (self.0.borrow_mut())(&call.to_string());
+    }
+}
+
+impl<'de> Middleware<'de> for Tracer<'_> {
+    fn deserialize(
+        &self,
+        hint: Hint,
+        deserializer: &mut dyn Deserializer<'de>,
+        visitor: &mut dyn Visitor<'de>,
+    ) -> InplaceDeserializeResult<()> {
+        self.trace(&hint);
+        hint.forward(deserializer, visitor)
+    }
+
+    fn visit(
+        &self,
+        visit: Visit<'_, 'de>,
+        visitor: &mut dyn Visitor<'de>,
+    ) -> DeserializeResult<()> {
+        self.trace(&visit);
+        visit.forward(visitor).inspect_err(|error| {
+            // errors propagated from a nested value have nothing to report.
+            if !matches!(error.0, Err(InplaceDeserializeError::Error)) {
+                self.trace(&format_args!("error: {error}"));
+            }
+        })
+    }
+
+    fn next_element(
+        &self,
+        access: &mut dyn SeqAccess<'de>,
+        seed: &mut dyn DeserializeSeed<'de>,
+    ) -> InplaceDeserializeResult<Option<()>> {
+        self.trace(&"next_element");
+        access.dyn_next_element(seed)
+    }
+
+    fn next_key(
+        &self,
+        access: &mut dyn MapAccess<'de>,
+        seed: &mut dyn DeserializeSeed<'de>,
+    ) -> InplaceDeserializeResult<Option<()>> {
+        self.trace(&"next_key");
+        access.dyn_next_key(seed)
+    }
+
+    fn next_value(
+        &self,
+        access: &mut dyn MapAccess<'de>,
+        seed: &mut dyn DeserializeSeed<'de>,
+    ) -> InplaceDeserializeResult<()> {
+        self.trace(&"next_value");
+        access.dyn_next_value(seed)
+    }
+
+    fn variant<'a>(
+        &self,
+        access: &'a mut dyn EnumAccess<'de>,
+        seed: &mut dyn DeserializeSeed<'de>,
+    ) -> InplaceDeserializeResult<&'a mut dyn VariantAccess<'de>> {
+        self.trace(&"variant");
+        access.dyn_variant(seed)
+    }
+
+    fn variant_access(
+        &self,
+        call: VariantCall<'_, 'de>,
+        access: &mut dyn VariantAccess<'de>,
+    ) -> InplaceDeserializeResult<()> {
+        self.trace(&call);
+        call.forward(access)
+    }
+}
@@ -0,0 +1,232 @@
+//! A [`Deserializer`] wrapper checking the kind of the value, see
+//! [`TypeConstrained`].
+
+use core::cell::Cell;
+use core::fmt::{self, Debug, Display, Formatter};
+
+use serde::de::{Error as _, Expected, Unexpected};
+
+use crate::de::InplaceDeserializeResult;
+use crate::de::middleware::{self, Hint, Middleware, Visit};
+use crate::de::{DeserializeError, DeserializeResult, Deserializer, Visitor};
+
+/// The kinds of values [`TypeConstrained`] can require, named after the types
+/// of JSON Schema.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum ValueKind {
+    /// A string or a char.
+    String,
+    /// An integer or a float.
+    Number,
+    /// A boolean.
+    Bool,
+    /// A map.
+    Object,
+    /// A sequence, or bytes.
+    Array,
+}
+
+impl ValueKind {
+    /// Returns the kind of the visited value, if it has one.
+    fn of(visit: &Visit<'_, '_>) -> Option<Self> {
+        match visit {
+            Visit::Char(_) | Visit::Str(_) | Visit::BorrowedStr(_) | Visit::String(_) => {
+                Some(ValueKind::String)
+            }
+            Visit::I8(_)
+            | Visit::I16(_)
+            | Visit::I32(_)
+            | Visit::I64(_)
+            | Visit::I128(_)
+            | Visit::U8(_)
+            | Visit::U16(_)
+            | Visit::U32(_)
+            | Visit::U64(_)
+            | Visit::U128(_)
+            | Visit::F32(_)
+            | Visit::F64(_) => Some(ValueKind::Number),
+            Visit::Bool(_) => Some(ValueKind::Bool),
+            Visit::Map(_) => Some(ValueKind::Object),
+            Visit::Seq(_) | Visit::Bytes(_) | Visit::BorrowedBytes(_) | Visit::ByteBuf(_) => {
+                Some(ValueKind::Array)
+            }
+            Visit::None
+            | Visit::Some(_)
+            | Visit::Unit
+            | Visit::NewtypeStruct(_)
+            | Visit::Enum(_) => None,
+        }
+    }
+}
+
+impl Display for ValueKind {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            ValueKind::String => "a string",
+            ValueKind::Number => "a number",
+            ValueKind::Bool => "a boolean",
+            ValueKind::Object => "an object",
+            ValueKind::Array => "an array",
+        })
+    }
+}
+
+impl Expected for ValueKind {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        Display::fmt(self, f)
+    }
+}
+
+/// A [`Deserializer`] wrapper which checks that the value is of a given
+/// [`ValueKind`] before it is deserialized, e.g. that a configuration entry is
+/// a number.
+///
+/// The errors of the deserialized type state what the type expects, e.g.
+/// `expected u16` or `expected struct Config`, which is little help to whoever
+/// wrote the input. `TypeConstrained` fails first when the value is of another
+/// kind, with an error stating the kind required, e.g.
+/// `invalid type: string "80", expected a number`. Values of the right kind are
+/// handed to the deserialized type unchanged, which still checks them.
+///
+/// Only the kind of the value itself is checked, not the kinds of the values
+/// nested in it. Options and newtype structs are looked through, while `null`
+/// and enums are handed to the deserialized type unchecked. Looking at the
+/// value relies on `deserialize_any`, so the format must be self-describing,
+/// as JSON is.
+///
+/// # Examples
+///
+/// ```
+/// # use serde::Deserialize as _;
+/// # use dyn_serde::Deserializer;
+/// # use dyn_serde::de::{InplaceDeserializer, TypeConstrained, ValueKind};
+/// #
+/// let mut deserializer = serde_json::Deserializer::from_str(r#""80""#);
+/// let mut inplace = <dyn Deserializer>::new(&mut deserializer);
+/// let mut deserializer = TypeConstrained::new(&mut inplace, ValueKind::Number);
+///
+/// assert!(u16::deserialize(&mut deserializer as &mut dyn Deserializer).is_err());
+/// let InplaceDeserializer::Error(error, _) = inplace else { unreachable!() };
+/// assert_eq!(
+///     error.to_string(),
+///     r#"invalid type: string "80", expected a number at line 1 column 4"#,
+/// );
+/// ```
+pub struct TypeConstrained<'a, 'de> {
+    deserializer: &'a mut dyn Deserializer<'de>,
+    constraint: Constraint,
+}
+
+impl<'a, 'de> TypeConstrained<'a, 'de> {
+    /// Wraps `deserializer`, requiring the value to be of kind `kind`.
+    #[must_use]
+    pub fn new(deserializer: &'a mut dyn Deserializer<'de>, kind: ValueKind) -> Self {
+        TypeConstrained {
+            deserializer,
+            constraint: Constraint {
+                kind,
+                armed: Cell::new(true),
+            },
+        }
+    }
+
+    fn deserialize_hint(
+        &mut self,
+        hint: Hint,
+        visitor: &mut dyn Visitor<'de>,
+    ) -> InplaceDeserializeResult<()> {
+        middleware::deserialize(&self.constraint, hint, self.deserializer, visitor)
+    }
+}
+
+impl Debug for TypeConstrained<'_, '_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TypeConstrained")
+            .field("kind", &self.constraint.kind)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<'de> Deserializer<'de> for TypeConstrained<'_, 'de> {
+    middleware::forward_to_deserialize_hint!();
+
+    fn dyn_is_human_readable(&self) -> bool {
+        self.deserializer.dyn_is_human_readable()
+    }
+}
+
+struct Constraint {
+    kind: ValueKind,
+    /// Whether the value has not been looked at yet, since the nested values
+    /// are also visited through this middleware.
+    armed: Cell<bool>,
+}
+
+impl<'de> Middleware<'de> for Constraint {
+    fn deserialize(
+        &self,
+        hint: Hint,
+        deserializer: &mut dyn Deserializer<'de>,
+        visitor: &mut dyn Visitor<'de>,
+    ) -> InplaceDeserializeResult<()> {
+        match hint {
+            Hint::Option | Hint::NewtypeStruct(_) | Hint::Enum(..) => {
+                hint.forward(deserializer, visitor)
+            }
+            // formats reject a value of another kind before visiting it.
+            _ if self.armed.get() => Hint::Any.forward(deserializer, visitor),
+            _ => hint.forward(deserializer, visitor),
+        }
+    }
+
+    fn visit(
+        &self,
+        visit: Visit<'_, 'de>,
+        visitor: &mut dyn Visitor<'de>,
+    ) -> DeserializeResult<()> {
+        if !self.armed.replace(false) {
+            return visit.forward(visitor);
+        }
+
+        match ValueKind::of(&visit) {
+            Some(kind) if kind != self.kind => Err(DeserializeError::invalid_type(
+                unexpected(&visit),
+                &self.kind,
+            )),
+            None if matches!(visit, Visit::Some(_) | Visit::NewtypeStruct(_)) => {
+                // the value is the one inside.
+                self.armed.set(true);
+                visit.forward(visitor)
+            }
+            _ => visit.forward(visitor),
+        }
+    }
+}
+
+fn unexpected<'a>(visit: &'a Visit<'_, '_>) -> Unexpected<'a> {
+    match *visit {
+        Visit::Bool(v) => Unexpected::Bool(v),
+        Visit::I8(v) => Unexpected::Signed(v.into()),
+        Visit::I16(v) => Unexpected::Signed(v.into()),
+        Visit::I32(v) => Unexpected::Signed(v.into()),
+        Visit::I64(v) => Unexpected::Signed(v),
+        Visit::U8(v) => Unexpected::Unsigned(v.into()),
+        Visit::U16(v) => Unexpected::Unsigned(v.into()),
+        Visit::U32(v) => Unexpected::Unsigned(v.into()),
+        Visit::U64(v) => Unexpected::Unsigned(v),
+        Visit::I128(_) | Visit::U128(_) => Unexpected::Other("integer"),
+        Visit::F32(v) => Unexpected::Float(v.into()),
+        Visit::F64(v) => Unexpected::Float(v),
+        Visit::Char(v) => Unexpected::Char(v),
+        Visit::Str(v) | Visit::BorrowedStr(v) => Unexpected::Str(v),
+        Visit::String(ref v) => Unexpected::Str(v),
+        Visit::Bytes(v) | Visit::BorrowedBytes(v) => Unexpected::Bytes(v),
+        Visit::ByteBuf(ref v) => Unexpected::Bytes(v),
+        Visit::None | Visit::Some(_) => Unexpected::Option,
+        Visit::Unit => Unexpected::Unit,
+        Visit::NewtypeStruct(_) => Unexpected::NewtypeStruct,
+        Visit::Seq(_) => Unexpected::Seq,
+        Visit::Map(_) => Unexpected::Map,
+        Visit::Enum(_) => Unexpected::Enum,
+    }
+}
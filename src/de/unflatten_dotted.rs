@@ -0,0 +1,209 @@
+//! A [`Deserializer`] wrapper reading flat maps of dotted keys as nested
+//! values, see [`UnflattenDotted`].
+
+use core::fmt::{self, Debug, Formatter};
+
+#[cfg(not(feature = "std"))]
+use alloc::string::String;
+#[cfg(not(feature = "std"))]
+use alloc::vec::Vec;
+
+use serde::Deserialize as _;
+use serde::de::Error as _;
+use serde_json::{Map, Value};
+
+use crate::de::middleware::{self, Hint};
+use crate::de::{DeserializeError, Deserializer, InplaceDeserializer, Visitor};
+use crate::de::{InplaceDeserializeError, InplaceDeserializeResult};
+
+/// A [`Deserializer`] wrapper which reads a flat map from dotted paths to
+/// values, e.g. `{"a.b.c": 1}`, as the nested value `{"a": {"b": {"c": 1}}}`.
+///
+/// This is the counterpart of [`FlattenDotted`], for reading configuration
+/// exported to systems which do not support nesting. The keys of the map are
+/// split at their dots, and every value is placed at the end of its path,
+/// creating the maps along the way. The maps whose keys are exactly the
+/// indices from `0` up to their length, as `FlattenDotted` writes sequences,
+/// are then read as sequences, while a value which is not a map is read
+/// unchanged.
+///
+/// A key which is both the path of a value and a prefix of another key, e.g.
+/// `"a"` and `"a.b"`, fails the deserialization with an error naming it. Like
+/// [`MapDeserializer`], the value is first read into a
+/// [`serde_json::Value`], so the wrapped format must be self-describing,
+/// strings are no longer borrowed from the input, and only one value can be
+/// deserialized.
+///
+/// The deserialization returns an error telling only that it has done
+/// unsuccessfully, while [`UnflattenDotted::error`] tells why it has.
+///
+/// [`FlattenDotted`]: crate::ser::FlattenDotted
+/// [`MapDeserializer`]: crate::de::MapDeserializer
+///
+/// # Examples
+///
+/// ```
+/// # use serde::Deserialize as _;
+/// # use dyn_serde::Deserializer;
+/// # use dyn_serde::de::UnflattenDotted;
+/// #
+/// #[derive(serde::Deserialize)]
+/// struct Config {
+///     server: Server,
+///     debug: bool,
+/// }
+///
+/// #[derive(serde::Deserialize)]
+/// struct Server {
+///     host: String,
+///     port: u16,
+/// }
+///
+/// let json = r#"{"server.host": "localhost", "server.port": 80, "debug": false}"#;
+/// let mut deserializer = serde_json::Deserializer::from_str(json);
+/// let mut deserializer = <dyn Deserializer>::new(&mut deserializer);
+/// let mut deserializer = UnflattenDotted::new(&mut deserializer);
+///
+/// let config = Config::deserialize(&mut deserializer as &mut dyn Deserializer).unwrap();
+/// assert_eq!(config.server.host, "localhost");
+/// assert_eq!(config.server.port, 80);
+/// ```
+pub struct UnflattenDotted<'a, 'de> {
+    deserializer: &'a mut dyn Deserializer<'de>,
+    /// Whether the value has been read.
+    read: bool,
+    error: Option<DeserializeError>,
+}
+
+impl<'a, 'de> UnflattenDotted<'a, 'de> {
+    /// Wraps `deserializer`, reading a flat map of dotted keys as a nested
+    /// value.
+    #[must_use]
+    pub fn new(deserializer: &'a mut dyn Deserializer<'de>) -> Self {
+        UnflattenDotted {
+            deserializer,
+            read: false,
+            error: None,
+        }
+    }
+
+    /// Returns the first error raised while rebuilding or deserializing from
+    /// the nested value, if any.
+    #[must_use]
+    pub fn error(&self) -> Option<&DeserializeError> {
+        self.error.as_ref()
+    }
+
+    #[cold]
+    fn fail(&mut self, error: DeserializeError) -> InplaceDeserializeError {
+        self.error.get_or_insert(error);
+        InplaceDeserializeError::Error
+    }
+
+    fn deserialize_hint(
+        &mut self,
+        hint: Hint,
+        visitor: &mut dyn Visitor<'de>,
+    ) -> InplaceDeserializeResult<()> {
+        if self.read {
+            return Err(InplaceDeserializeError::NotDeserializer);
+        }
+        self.read = true;
+        let value = Value::deserialize(&mut *self.deserializer).map_err(|e| self.fail(e))?;
+        let value = match value {
+            Value::Object(flat) => unflatten(flat).map_err(|e| self.fail(e))?,
+            value => value,
+        };
+
+        let mut deserializer = InplaceDeserializer::Deserializer(value);
+        let result = hint.forward(&mut deserializer, visitor);
+        if let InplaceDeserializer::Error(error, _) = deserializer {
+            self.error.get_or_insert(DeserializeError::custom(error));
+        }
+        result
+    }
+}
+
+impl Debug for UnflattenDotted<'_, '_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("UnflattenDotted")
+            .field("error", &self.error)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<'de> Deserializer<'de> for UnflattenDotted<'_, 'de> {
+    middleware::forward_to_deserialize_hint!();
+
+    fn dyn_is_human_readable(&self) -> bool {
+        self.deserializer.dyn_is_human_readable()
+    }
+}
+
+/// Rebuilds the nested value of a flat map of dotted keys.
+fn unflatten(flat: Map<String, Value>) -> Result<Value, DeserializeError> {
+    let mut root = Map::new();
+    for (key, value) in flat {
+        let mut map = &mut root;
+        let mut segments = key.split('.');
+        let mut segment = segments.next().unwrap_or_default();
+        let mut len = segment.len();
+        for next in segments {
+            let entry = map
+                .entry(segment)
+                .or_insert_with(|| Value::Object(Map::new()));
+            let Value::Object(inner) = entry else {
+                return Err(conflict(&key[..len]));
+            };
+            map = inner;
+            segment = next;
+            len += 1 + next.len();
+        }
+        if map.contains_key(segment) {
+            return Err(conflict(&key));
+        }
+        map.insert(segment.into(), value);
+    }
+    Ok(into_seqs(Value::Object(root)))
+}
+
+/// Turns the maps keyed by the indices of their entries into sequences, at
+/// every depth.
+fn into_seqs(value: Value) -> Value {
+    let Value::Object(map) = value else {
+        return value;
+    };
+    let len = map.len();
+    let mut elements = Vec::new();
+    elements.resize_with(len, || None);
+    let mut entries = Map::new();
+    for (key, value) in map {
+        let value = into_seqs(value);
+        match key.parse::<usize>() {
+            Ok(index) if index < len && elements[index].is_none() => {
+                elements[index] = Some((key, value));
+            }
+            _ => {
+                entries.insert(key, value);
+            }
+        }
+    }
+
+    if entries.is_empty() && len > 0 {
+        return elements
+            .into_iter()
+            .flatten()
+            .map(|(_, value)| value)
+            .collect();
+    }
+    // the map is not a sequence, put the entries taken as elements back.
+    entries.extend(elements.into_iter().flatten());
+    Value::Object(entries)
+}
+
+#[cold]
+fn conflict(key: &str) -> DeserializeError {
+    DeserializeError::custom(format_args!(
+        "key `{key}` is both a value and a prefix of another key"
+    ))
+}
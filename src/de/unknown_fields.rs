@@ -0,0 +1,265 @@
+//! [`Deserializer`] wrappers collecting or denying the unknown fields of
+//! structs, see [`CollectUnknownFields`] and [`DenyUnknownFields`].
+
+use core::cell::{Cell, RefCell};
+use core::fmt::{self, Debug, Formatter};
+
+#[cfg(not(feature = "std"))]
+use alloc::string::String;
+#[cfg(not(feature = "std"))]
+use alloc::vec::Vec;
+
+use serde::de::Error as _;
+
+use crate::de::InplaceDeserializeResult;
+use crate::de::middleware::{self, Hint, Middleware, VariantCall, Visit};
+use crate::de::{DeserializeError, DeserializeResult, DeserializeSeed, Deserializer};
+use crate::de::{MapAccess, VariantAccess, Visitor};
+
+type Fields = &'static [&'static str];
+
+/// A [`Deserializer`] wrapper which records the name of every field of a
+/// struct that is not among its declared fields, e.g. to warn about a typo in
+/// a configuration file where `#[serde(deny_unknown_fields)]` would fail.
+///
+/// The deserialization itself is left unchanged: the value of an unknown field
+/// is skipped by the visitor of the struct as usual, while its name is pushed
+/// to the `unknown` vector given to [`CollectUnknownFields::new`]. Structs and
+/// struct variants are checked at every depth of the value. Fields named by
+/// their index rather than by a string are not recorded.
+///
+/// # Examples
+///
+/// ```
+/// # use serde::Deserialize as _;
+/// # use dyn_serde::Deserializer;
+/// # use dyn_serde::de::CollectUnknownFields;
+/// #
+/// #[derive(serde::Deserialize)]
+/// struct Config {
+///     port: u16,
+/// }
+///
+/// let mut unknown = Vec::new();
+/// let mut deserializer = serde_json::Deserializer::from_str(r#"{"port": 80, "prot": 8080}"#);
+/// let mut deserializer = <dyn Deserializer>::new(&mut deserializer);
+/// let mut deserializer = CollectUnknownFields::new(&mut deserializer, &mut unknown);
+///
+/// let config = Config::deserialize(&mut deserializer as &mut dyn Deserializer).unwrap();
+/// assert_eq!(config.port, 80);
+/// assert_eq!(unknown, ["prot"]);
+/// ```
+pub struct CollectUnknownFields<'a, 'de> {
+    deserializer: &'a mut dyn Deserializer<'de>,
+    collector: Collector<'a>,
+}
+
+impl<'a, 'de> CollectUnknownFields<'a, 'de> {
+    /// Wraps `deserializer`, pushing the names of unknown fields to `unknown`.
+    #[must_use]
+    pub fn new(deserializer: &'a mut dyn Deserializer<'de>, unknown: &'a mut Vec<String>) -> Self {
+        CollectUnknownFields {
+            deserializer,
+            collector: Collector {
+                unknown: Unknown::Collect(RefCell::new(unknown)),
+                struct_fields: Cell::new(None),
+                maps: RefCell::new(Vec::new()),
+                key_fields: Cell::new(None),
+            },
+        }
+    }
+
+    fn deserialize_hint(
+        &mut self,
+        hint: Hint,
+        visitor: &mut dyn Visitor<'de>,
+    ) -> InplaceDeserializeResult<()> {
+        middleware::deserialize(&self.collector, hint, self.deserializer, visitor)
+    }
+}
+
+impl Debug for CollectUnknownFields<'_, '_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let mut f = f.debug_struct("CollectUnknownFields");
+        if let Unknown::Collect(unknown) = &self.collector.unknown {
+            f.field("unknown", &unknown.borrow());
+        }
+        f.finish_non_exhaustive()
+    }
+}
+
+impl<'de> Deserializer<'de> for CollectUnknownFields<'_, 'de> {
+    middleware::forward_to_deserialize_hint!();
+
+    fn dyn_is_human_readable(&self) -> bool {
+        self.deserializer.dyn_is_human_readable()
+    }
+}
+
+/// A [`Deserializer`] wrapper which rejects every field of a struct that is
+/// not among its declared fields, as `#[serde(deny_unknown_fields)]` does,
+/// e.g. to enforce strictness on types owned by another crate.
+///
+/// The first unknown field fails the deserialization with the usual
+/// `unknown_field` error, naming the field and the expected ones. Structs and
+/// struct variants are checked at every depth of the value; the keys of maps
+/// which are not structs are never rejected. Fields named by their index
+/// rather than by a string are not checked either.
+///
+/// # Examples
+///
+/// ```
+/// # use serde::Deserialize as _;
+/// # use dyn_serde::Deserializer;
+/// # use dyn_serde::de::{DenyUnknownFields, InplaceDeserializer};
+/// #
+/// #[derive(Debug, serde::Deserialize)]
+/// struct Config {
+///     port: u16,
+/// }
+///
+/// let mut deserializer = serde_json::Deserializer::from_str(r#"{"port": 80, "prot": 8080}"#);
+/// let mut inplace = <dyn Deserializer>::new(&mut deserializer);
+/// let mut deserializer = DenyUnknownFields::new(&mut inplace);
+///
+/// Config::deserialize(&mut deserializer as &mut dyn Deserializer).unwrap_err();
+/// let InplaceDeserializer::Error(error, _) = inplace else { unreachable!() };
+/// assert_eq!(error.to_string(), "unknown field `prot`, expected `port` at line 1 column 19");
+/// ```
+pub struct DenyUnknownFields<'a, 'de> {
+    deserializer: &'a mut dyn Deserializer<'de>,
+    collector: Collector<'a>,
+}
+
+impl<'a, 'de> DenyUnknownFields<'a, 'de> {
+    /// Wraps `deserializer`, failing on the first unknown field.
+    #[must_use]
+    pub fn new(deserializer: &'a mut dyn Deserializer<'de>) -> Self {
+        DenyUnknownFields {
+            deserializer,
+            collector: Collector {
+                unknown: Unknown::Deny,
+                struct_fields: Cell::new(None),
+                maps: RefCell::new(Vec::new()),
+                key_fields: Cell::new(None),
+            },
+        }
+    }
+
+    fn deserialize_hint(
+        &mut self,
+        hint: Hint,
+        visitor: &mut dyn Visitor<'de>,
+    ) -> InplaceDeserializeResult<()> {
+        middleware::deserialize(&self.collector, hint, self.deserializer, visitor)
+    }
+}
+
+impl Debug for DenyUnknownFields<'_, '_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("DenyUnknownFields").finish_non_exhaustive()
+    }
+}
+
+impl<'de> Deserializer<'de> for DenyUnknownFields<'_, 'de> {
+    middleware::forward_to_deserialize_hint!();
+
+    fn dyn_is_human_readable(&self) -> bool {
+        self.deserializer.dyn_is_human_readable()
+    }
+}
+
+/// What becomes of an unknown field.
+enum Unknown<'a> {
+    /// Its name is pushed to the vector.
+    Collect(RefCell<&'a mut Vec<String>>),
+    /// It fails the deserialization.
+    Deny,
+}
+
+struct Collector<'a> {
+    unknown: Unknown<'a>,
+    /// The fields of the struct whose visit comes next, if any.
+    struct_fields: Cell<Option<Fields>>,
+    /// The fields of every map being visited, `None` if it is not a struct.
+    maps: RefCell<Vec<Option<Fields>>>,
+    /// The fields of the struct whose key is visited next, if any.
+    key_fields: Cell<Option<Fields>>,
+}
+
+impl Collector<'_> {
+    fn check(&self, fields: Fields, key: &[u8]) -> DeserializeResult<()> {
+        if fields.iter().any(|field| field.as_bytes() == key) {
+            return Ok(());
+        }
+        let key = String::from_utf8_lossy(key);
+        match &self.unknown {
+            Unknown::Collect(unknown) => unknown.borrow_mut().push(key.into_owned()),
+            Unknown::Deny => return Err(DeserializeError::unknown_field(&key, fields)),
+        }
+        Ok(())
+    }
+}
+
+impl<'de> Middleware<'de> for Collector<'_> {
+    fn deserialize(
+        &self,
+        hint: Hint,
+        deserializer: &mut dyn Deserializer<'de>,
+        visitor: &mut dyn Visitor<'de>,
+    ) -> InplaceDeserializeResult<()> {
+        self.struct_fields.set(match hint {
+            Hint::Struct(_, fields) => Some(fields),
+            _ => None,
+        });
+        hint.forward(deserializer, visitor)
+    }
+
+    fn visit(
+        &self,
+        visit: Visit<'_, 'de>,
+        visitor: &mut dyn Visitor<'de>,
+    ) -> DeserializeResult<()> {
+        let struct_fields = self.struct_fields.take();
+        if let Some(fields) = self.key_fields.take() {
+            match &visit {
+                Visit::Str(key) | Visit::BorrowedStr(key) => self.check(fields, key.as_bytes())?,
+                Visit::String(key) => self.check(fields, key.as_bytes())?,
+                Visit::Bytes(key) | Visit::BorrowedBytes(key) => self.check(fields, key)?,
+                Visit::ByteBuf(key) => self.check(fields, key)?,
+                _ => {}
+            }
+        }
+
+        if !matches!(visit, Visit::Map(_)) {
+            return visit.forward(visitor);
+        }
+        self.maps.borrow_mut().push(struct_fields);
+        let result = visit.forward(visitor);
+        self.maps.borrow_mut().pop();
+        result
+    }
+
+    fn next_key(
+        &self,
+        access: &mut dyn MapAccess<'de>,
+        seed: &mut dyn DeserializeSeed<'de>,
+    ) -> InplaceDeserializeResult<Option<()>> {
+        self.key_fields
+            .set(self.maps.borrow().last().copied().flatten());
+        let result = access.dyn_next_key(seed);
+        self.key_fields.set(None);
+        result
+    }
+
+    fn variant_access(
+        &self,
+        call: VariantCall<'_, 'de>,
+        access: &mut dyn VariantAccess<'de>,
+    ) -> InplaceDeserializeResult<()> {
+        if let VariantCall::Struct(fields, _) = call {
+            self.struct_fields.set(Some(fields));
+        }
+        call.forward(access)
+    }
+}
@@ -0,0 +1,317 @@
+//! A [`Deserializer`] wrapper discarding the tag field of structs, see
+//! [`UntagStructs`].
+
+use core::cell::Cell;
+use core::fmt::{self, Debug, Formatter};
+
+#[cfg(not(feature = "std"))]
+use alloc::string::String;
+#[cfg(not(feature = "std"))]
+use alloc::vec::Vec;
+
+use serde::de::value::{BorrowedBytesDeserializer, BorrowedStrDeserializer, BytesDeserializer};
+use serde::de::value::{StringDeserializer, U64Deserializer};
+use serde::de::{IgnoredAny, MapAccess as _};
+
+use crate::de::middleware::{self, Hint, Middleware, VariantCall, Visit};
+use crate::de::{DeserializeError, DeserializeResult, DeserializeSeed, Deserializer};
+use crate::de::{InplaceDeserializeError, InplaceDeserializeResult, InplaceDeserializer};
+use crate::de::{MapAccess, VariantAccess, Visitor};
+
+/// A [`Deserializer`] wrapper which discards a tag field, e.g. `"type"`, from
+/// every struct, so that the deserialized type does not need to declare it.
+///
+/// This is the counterpart of [`TagStructs`], reading tagged objects such as
+/// `{"type": "Point", "x": 1}` into untagged structs. When a struct or a
+/// struct variant is visited as a map, the first entry whose key is the tag
+/// is consumed and its value is skipped, whatever it is. The other entries are
+/// handed to the visitor unchanged, at every depth of the value. Maps and
+/// structs visited as sequences are left unchanged.
+///
+/// # Examples
+///
+/// ```
+/// # use serde::Deserialize as _;
+/// # use dyn_serde::Deserializer;
+/// # use dyn_serde::de::UntagStructs;
+/// #
+/// #[derive(serde::Deserialize)]
+/// #[serde(deny_unknown_fields)]
+/// struct Point {
+///     x: i32,
+/// }
+///
+/// let mut deserializer = serde_json::Deserializer::from_str(r#"{"type": "Point", "x": 1}"#);
+/// let mut deserializer = <dyn Deserializer>::new(&mut deserializer);
+/// let mut deserializer = UntagStructs::new(&mut deserializer, "type");
+///
+/// let point = Point::deserialize(&mut deserializer as &mut dyn Deserializer).unwrap();
+/// assert_eq!(point.x, 1);
+/// ```
+///
+/// [`TagStructs`]: crate::ser::TagStructs
+pub struct UntagStructs<'a, 'de> {
+    deserializer: &'a mut dyn Deserializer<'de>,
+    untag: Untag,
+}
+
+impl<'a, 'de> UntagStructs<'a, 'de> {
+    /// Wraps `deserializer`, discarding the field `tag` from every struct.
+    #[must_use]
+    pub fn new(deserializer: &'a mut dyn Deserializer<'de>, tag: &'static str) -> Self {
+        UntagStructs {
+            deserializer,
+            untag: Untag {
+                tag,
+                expecting_struct: Cell::new(false),
+            },
+        }
+    }
+
+    fn deserialize_hint(
+        &mut self,
+        hint: Hint,
+        visitor: &mut dyn Visitor<'de>,
+    ) -> InplaceDeserializeResult<()> {
+        middleware::deserialize(&self.untag, hint, self.deserializer, visitor)
+    }
+}
+
+impl Debug for UntagStructs<'_, '_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("UntagStructs")
+            .field("tag", &self.untag.tag)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<'de> Deserializer<'de> for UntagStructs<'_, 'de> {
+    middleware::forward_to_deserialize_hint!();
+
+    fn dyn_is_human_readable(&self) -> bool {
+        self.deserializer.dyn_is_human_readable()
+    }
+}
+
+struct Untag {
+    tag: &'static str,
+    /// Whether the next visit is the one of a struct.
+    expecting_struct: Cell<bool>,
+}
+
+impl<'de> Middleware<'de> for Untag {
+    fn deserialize(
+        &self,
+        hint: Hint,
+        deserializer: &mut dyn Deserializer<'de>,
+        visitor: &mut dyn Visitor<'de>,
+    ) -> InplaceDeserializeResult<()> {
+        self.expecting_struct.set(matches!(hint, Hint::Struct(..)));
+        hint.forward(deserializer, visitor)
+    }
+
+    fn visit(
+        &self,
+        visit: Visit<'_, 'de>,
+        visitor: &mut dyn Visitor<'de>,
+    ) -> DeserializeResult<()> {
+        match visit {
+            Visit::Map(access) if self.expecting_struct.replace(false) => {
+                let mut access = UntagMapAccess {
+                    inner: access,
+                    tag: Some(self.tag),
+                    error: None,
+                };
+                visitor
+                    .dyn_visit_map(&mut access)
+                    .map_err(|error| access.error.take().unwrap_or(error))
+            }
+            visit => {
+                self.expecting_struct.set(false);
+                visit.forward(visitor)
+            }
+        }
+    }
+
+    fn variant_access(
+        &self,
+        call: VariantCall<'_, 'de>,
+        access: &mut dyn VariantAccess<'de>,
+    ) -> InplaceDeserializeResult<()> {
+        self.expecting_struct
+            .set(matches!(call, VariantCall::Struct(..)));
+        call.forward(access)
+    }
+}
+
+/// The map of a struct, whose tag field is skipped.
+struct UntagMapAccess<'a, 'de> {
+    inner: &'a mut dyn MapAccess<'de>,
+    /// The tag, until it is found.
+    tag: Option<&'static str>,
+    /// The error raised while looking for the tag, if any.
+    error: Option<DeserializeError>,
+}
+
+impl<'de> UntagMapAccess<'_, 'de> {
+    /// Reads the next key, skipping the entry of the tag, and hands it to
+    /// `seed`.
+    fn next_key(
+        &mut self,
+        tag: &str,
+        seed: &mut dyn DeserializeSeed<'de>,
+    ) -> DeserializeResult<Option<()>> {
+        let Some(key) = self.inner.next_key_seed(KeyVisitor)? else {
+            return Ok(None);
+        };
+        if !key.is(tag) {
+            return key.deserialize(seed).map(Some);
+        }
+        self.tag = None;
+        self.inner.next_value::<IgnoredAny>()?;
+        self.inner.next_key_seed(seed)
+    }
+}
+
+impl<'de> MapAccess<'de> for UntagMapAccess<'_, 'de> {
+    fn dyn_next_key(
+        &mut self,
+        seed: &mut dyn DeserializeSeed<'de>,
+    ) -> InplaceDeserializeResult<Option<()>> {
+        let Some(tag) = self.tag else {
+            return self.inner.dyn_next_key(seed);
+        };
+        self.next_key(tag, seed).map_err(|error| {
+            self.error.get_or_insert(error);
+            InplaceDeserializeError::Error
+        })
+    }
+
+    fn dyn_next_value(
+        &mut self,
+        seed: &mut dyn DeserializeSeed<'de>,
+    ) -> InplaceDeserializeResult<()> {
+        self.inner.dyn_next_value(seed)
+    }
+
+    fn dyn_next_entry(
+        &mut self,
+        kseed: &mut dyn DeserializeSeed<'de>,
+        vseed: &mut dyn DeserializeSeed<'de>,
+    ) -> InplaceDeserializeResult<Option<((), ())>> {
+        match self.dyn_next_key(kseed)? {
+            Some(()) => self.dyn_next_value(vseed).map(|()| Some(((), ()))),
+            None => Ok(None),
+        }
+    }
+
+    fn dyn_size_hint(&self) -> Option<usize> {
+        self.inner.dyn_size_hint()
+    }
+}
+
+/// A key read ahead to be compared with a tag.
+pub(crate) enum Key<'de> {
+    Str(String),
+    BorrowedStr(&'de str),
+    Bytes(Vec<u8>),
+    BorrowedBytes(&'de [u8]),
+    U64(u64),
+}
+
+impl<'de> Key<'de> {
+    pub(crate) fn is(&self, tag: &str) -> bool {
+        match self {
+            Key::Str(key) => key == tag,
+            Key::BorrowedStr(key) => *key == tag,
+            Key::Bytes(key) => key == tag.as_bytes(),
+            Key::BorrowedBytes(key) => *key == tag.as_bytes(),
+            Key::U64(_) => false,
+        }
+    }
+
+    /// Returns the key as a string, unless it is an index or bytes which are
+    /// not UTF-8.
+    #[cfg(feature = "std")]
+    pub(crate) fn as_str(&self) -> Option<&str> {
+        match self {
+            Key::Str(key) => Some(key),
+            Key::BorrowedStr(key) => Some(key),
+            Key::Bytes(key) => str::from_utf8(key).ok(),
+            Key::BorrowedBytes(key) => str::from_utf8(key).ok(),
+            Key::U64(_) => None,
+        }
+    }
+
+    /// Hands the key to `seed` as it has been read.
+    pub(crate) fn deserialize(self, seed: &mut dyn DeserializeSeed<'de>) -> DeserializeResult<()> {
+        match self {
+            Key::Str(key) => replay(seed, StringDeserializer::new(key)),
+            Key::BorrowedStr(key) => replay(seed, BorrowedStrDeserializer::new(key)),
+            Key::Bytes(key) => replay(seed, BytesDeserializer::new(&key)),
+            Key::BorrowedBytes(key) => replay(seed, BorrowedBytesDeserializer::new(key)),
+            Key::U64(key) => replay(seed, U64Deserializer::new(key)),
+        }
+    }
+}
+
+pub(crate) fn replay<'de, D>(
+    seed: &mut dyn DeserializeSeed<'de>,
+    deserializer: D,
+) -> DeserializeResult<()>
+where
+    D: serde::Deserializer<'de, Error = DeserializeError>,
+{
+    let mut deserializer = InplaceDeserializer::Deserializer(deserializer);
+    let result = seed.dyn_deserialize(&mut deserializer);
+    deserializer.into_result(result)
+}
+
+pub(crate) struct KeyVisitor;
+
+impl<'de> serde::de::DeserializeSeed<'de> for KeyVisitor {
+    type Value = Key<'de>;
+
+    fn deserialize<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        deserializer.deserialize_identifier(self)
+    }
+}
+
+impl<'de> serde::de::Visitor<'de> for KeyVisitor {
+    type Value = Key<'de>;
+
+    fn expecting(&self, formatter: &mut Formatter) -> fmt::Result {
+        formatter.write_str("a field name or index")
+    }
+
+    fn visit_u64<E>(self, v: u64) -> Result<Self::Value, E> {
+        Ok(Key::U64(v))
+    }
+
+    fn visit_str<E>(self, v: &str) -> Result<Self::Value, E> {
+        Ok(Key::Str(v.into()))
+    }
+
+    fn visit_borrowed_str<E>(self, v: &'de str) -> Result<Self::Value, E> {
+        Ok(Key::BorrowedStr(v))
+    }
+
+    fn visit_string<E>(self, v: String) -> Result<Self::Value, E> {
+        Ok(Key::Str(v))
+    }
+
+    fn visit_bytes<E>(self, v: &[u8]) -> Result<Self::Value, E> {
+        Ok(Key::Bytes(v.into()))
+    }
+
+    fn visit_borrowed_bytes<E>(self, v: &'de [u8]) -> Result<Self::Value, E> {
+        Ok(Key::BorrowedBytes(v))
+    }
+
+    fn visit_byte_buf<E>(self, v: Vec<u8>) -> Result<Self::Value, E> {
+        Ok(Key::Bytes(v))
+    }
+}
@@ -0,0 +1,211 @@
+//! A [`Deserializer`] reading a value from a versioned envelope, see
+//! [`Versioned`].
+
+use core::fmt::{self, Debug, Formatter};
+
+#[cfg(not(feature = "std"))]
+use alloc::boxed::Box;
+#[cfg(not(feature = "std"))]
+use alloc::collections::BTreeMap;
+#[cfg(feature = "std")]
+use std::collections::BTreeMap;
+
+use serde::Deserialize as _;
+use serde::de::{Error as _, Unexpected};
+use serde_json::Value;
+
+use crate::de::middleware::{self, Hint};
+use crate::de::{DeserializeError, Deserializer, InplaceDeserializer, Visitor};
+use crate::de::{InplaceDeserializeError, InplaceDeserializeResult};
+
+/// A [`Deserializer`] which reads a value from the envelope written by
+/// [`ser::Versioned`], `{"v": 1, "data": value}`, upgrading the values of
+/// older versions to the current one before deserializing them.
+///
+/// The migrations are registered with [`Versioned::migration`], each one
+/// upgrading the value of a version to the next one. The data of version `v`
+/// goes through the migrations of versions `v`, `v + 1` and so on up to the
+/// current version, so that the types only ever deserialize from the current
+/// shape, and a stored value is never rewritten. A version newer than the
+/// current one, or older without a migration to upgrade it, fails with an
+/// error.
+///
+/// As for [`MapDeserializer`], the envelope is read into a
+/// [`serde_json::Value`] which the migrations transform, so the input must be
+/// self-describing, strings are no longer borrowed from it, and only one value
+/// can be deserialized. The deserialization returns an error telling only that
+/// it has done unsuccessfully, while the wrapped deserializer tells why the
+/// input could not be read, and [`Versioned::error`] why the envelope or the
+/// migrated value could not be deserialized.
+///
+/// [`ser::Versioned`]: crate::ser::Versioned
+/// [`MapDeserializer`]: crate::de::MapDeserializer
+///
+/// # Examples
+///
+/// ```
+/// # use serde::Deserialize as _;
+/// # use dyn_serde::Deserializer;
+/// # use dyn_serde::de::Versioned;
+/// #
+/// #[derive(serde::Deserialize)]
+/// struct User {
+///     name: String,
+///     admin: bool,
+/// }
+///
+/// let json = r#"{"v": 1, "data": {"name": "ferris"}}"#;
+/// let mut deserializer = serde_json::Deserializer::from_str(json);
+/// let mut deserializer = <dyn Deserializer>::new(&mut deserializer);
+/// let mut deserializer = Versioned::new(&mut deserializer, 2);
+/// deserializer.migration(1, |mut user| {
+///     user["admin"] = false.into();
+///     user
+/// });
+///
+/// let user = User::deserialize(&mut deserializer as &mut dyn Deserializer).unwrap();
+/// assert_eq!(user.name, "ferris");
+/// assert!(!user.admin);
+/// assert_eq!(deserializer.version(), Some(1));
+/// ```
+pub struct Versioned<'a, 'de> {
+    deserializer: &'a mut dyn Deserializer<'de>,
+    current: u32,
+    migrations: BTreeMap<u32, Migration<'a>>,
+    /// The version of the envelope, once it has been read.
+    version: Option<u32>,
+    /// Whether the envelope has been read.
+    read: bool,
+    error: Option<DeserializeError>,
+}
+
+type Migration<'a> = Box<dyn FnMut(Value) -> Value + 'a>;
+
+impl<'a, 'de> Versioned<'a, 'de> {
+    /// Creates a deserializer reading the envelope of `deserializer`, whose
+    /// values are upgraded to the `current` version.
+    #[must_use]
+    pub fn new(deserializer: &'a mut dyn Deserializer<'de>, current: u32) -> Self {
+        Versioned {
+            deserializer,
+            current,
+            migrations: BTreeMap::new(),
+            version: None,
+            read: false,
+            error: None,
+        }
+    }
+
+    /// Registers `migrate`, upgrading the value of version `from` to the
+    /// value of version `from + 1`, and replacing any migration of `from`
+    /// registered before.
+    pub fn migration(&mut self, from: u32, migrate: impl FnMut(Value) -> Value + 'a) -> &mut Self {
+        self.migrations.insert(from, Box::new(migrate));
+        self
+    }
+
+    /// Returns the version of the envelope, once it has been read.
+    #[must_use]
+    pub fn version(&self) -> Option<u32> {
+        self.version
+    }
+
+    /// Returns the first error raised while reading the envelope or
+    /// deserializing from the migrated value, if any.
+    #[must_use]
+    pub fn error(&self) -> Option<&DeserializeError> {
+        self.error.as_ref()
+    }
+
+    #[cold]
+    fn fail(&mut self, error: DeserializeError) -> InplaceDeserializeError {
+        self.error.get_or_insert(error);
+        InplaceDeserializeError::Error
+    }
+
+    /// Reads the envelope, returning its data upgraded to the current
+    /// version.
+    fn read(&mut self) -> Result<Value, DeserializeError> {
+        let envelope = Value::deserialize(&mut *self.deserializer)?;
+        let Value::Object(mut envelope) = envelope else {
+            return Err(DeserializeError::invalid_type(
+                unexpected(&envelope),
+                &"a versioned envelope",
+            ));
+        };
+        let version = envelope
+            .remove("v")
+            .ok_or_else(|| DeserializeError::missing_field("v"))?;
+        let version = u32::deserialize(version).map_err(DeserializeError::custom)?;
+        let mut data = envelope
+            .remove("data")
+            .ok_or_else(|| DeserializeError::missing_field("data"))?;
+        self.version = Some(version);
+
+        if version > self.current {
+            return Err(DeserializeError::custom(format_args!(
+                "unsupported version {version}, expected at most {}",
+                self.current
+            )));
+        }
+        for from in version..self.current {
+            let Some(migrate) = self.migrations.get_mut(&from) else {
+                return Err(DeserializeError::custom(format_args!(
+                    "no migration from version {from} to version {}",
+                    from + 1
+                )));
+            };
+            data = (migrate)(data);
+        }
+        Ok(data)
+    }
+
+    fn deserialize_hint(
+        &mut self,
+        hint: Hint,
+        visitor: &mut dyn Visitor<'de>,
+    ) -> InplaceDeserializeResult<()> {
+        if self.read {
+            return Err(InplaceDeserializeError::NotDeserializer);
+        }
+        self.read = true;
+        let data = self.read().map_err(|e| self.fail(e))?;
+
+        let mut deserializer = InplaceDeserializer::Deserializer(data);
+        let result = hint.forward(&mut deserializer, visitor);
+        if let InplaceDeserializer::Error(error, _) = deserializer {
+            self.error.get_or_insert(DeserializeError::custom(error));
+        }
+        result
+    }
+}
+
+impl Debug for Versioned<'_, '_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Versioned")
+            .field("current", &self.current)
+            .field("version", &self.version)
+            .field("error", &self.error)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<'de> Deserializer<'de> for Versioned<'_, 'de> {
+    middleware::forward_to_deserialize_hint!();
+
+    fn dyn_is_human_readable(&self) -> bool {
+        self.deserializer.dyn_is_human_readable()
+    }
+}
+
+/// Describes `value` for the error of an envelope which is not an object.
+fn unexpected(value: &Value) -> Unexpected<'_> {
+    match value {
+        Value::Null => Unexpected::Unit,
+        Value::Bool(v) => Unexpected::Bool(*v),
+        Value::Number(_) => Unexpected::Other("number"),
+        Value::String(v) => Unexpected::Str(v),
+        Value::Array(_) => Unexpected::Seq,
+        Value::Object(_) => Unexpected::Map,
+    }
+}
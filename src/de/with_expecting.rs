@@ -0,0 +1,195 @@
+//! A [`Visitor`] wrapper overriding its expectation, see [`WithExpecting`].
+
+use core::fmt::{self, Debug, Formatter};
+
+#[cfg(not(feature = "std"))]
+use alloc::string::String;
+#[cfg(not(feature = "std"))]
+use alloc::vec::Vec;
+
+use crate::de::{DeserializeResult, Deserializer, EnumAccess, MapAccess, SeqAccess, Visitor};
+
+/// A [`Visitor`] wrapper which states `expecting` as what it expects to
+/// receive, while every `dyn_visit_*` call goes to the wrapped visitor.
+///
+/// The expectation of a visitor is the end of the message of a type error,
+/// e.g. `invalid type: string "http", expected a u16`. For a visitor built by
+/// generic code, it tells little about what the input was meant to be, which
+/// `WithExpecting` replaces with the message given to [`WithExpecting::new`],
+/// e.g. `expected a port number`.
+///
+/// Only the errors raised by the deserializer, which asks the visitor for its
+/// expectation, are affected. The errors raised by the wrapped visitor itself
+/// keep its own message.
+///
+/// # Examples
+///
+/// ```
+/// # use dyn_serde::Deserializer;
+/// # use dyn_serde::de::{InplaceDeserializer, InplaceVisitor, WithExpecting};
+/// #
+/// struct PortVisitor;
+///
+/// impl serde::de::Visitor<'_> for PortVisitor {
+///     type Value = u16;
+///
+///     fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+///         formatter.write_str("a u16")
+///     }
+///
+///     fn visit_u64<E: serde::de::Error>(self, v: u64) -> Result<u16, E> {
+///         u16::try_from(v).map_err(E::custom)
+///     }
+/// }
+///
+/// let mut deserializer = serde_json::Deserializer::from_str(r#""http""#);
+/// let mut deserializer = <dyn Deserializer>::new(&mut deserializer);
+/// let mut visitor = InplaceVisitor::Visitor(PortVisitor);
+/// let mut visitor = WithExpecting::new(&mut visitor, "a port number");
+///
+/// assert!(deserializer.dyn_deserialize_u16(&mut visitor).is_err());
+/// let InplaceDeserializer::Error(error, _) = deserializer else {
+///     unreachable!();
+/// };
+/// assert!(error.to_string().starts_with(r#"invalid type: string "http", expected a port number"#));
+/// ```
+pub struct WithExpecting<'a, 'de> {
+    visitor: &'a mut dyn Visitor<'de>,
+    expecting: &'a str,
+}
+
+impl<'a, 'de> WithExpecting<'a, 'de> {
+    /// Wraps `visitor`, stating `expecting` as what it expects to receive.
+    #[must_use]
+    pub fn new(visitor: &'a mut dyn Visitor<'de>, expecting: &'a str) -> Self {
+        WithExpecting { visitor, expecting }
+    }
+}
+
+impl Debug for WithExpecting<'_, '_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("WithExpecting")
+            .field("expecting", &self.expecting)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<'de> Visitor<'de> for WithExpecting<'_, 'de> {
+    fn dyn_expecting(&self, formatter: &mut Formatter) -> fmt::Result {
+        formatter.write_str(self.expecting)
+    }
+
+    fn dyn_visit_bool(&mut self, v: bool) -> DeserializeResult<()> {
+        self.visitor.dyn_visit_bool(v)
+    }
+
+    fn dyn_visit_i8(&mut self, v: i8) -> DeserializeResult<()> {
+        self.visitor.dyn_visit_i8(v)
+    }
+
+    fn dyn_visit_i16(&mut self, v: i16) -> DeserializeResult<()> {
+        self.visitor.dyn_visit_i16(v)
+    }
+
+    fn dyn_visit_i32(&mut self, v: i32) -> DeserializeResult<()> {
+        self.visitor.dyn_visit_i32(v)
+    }
+
+    fn dyn_visit_i64(&mut self, v: i64) -> DeserializeResult<()> {
+        self.visitor.dyn_visit_i64(v)
+    }
+
+    fn dyn_visit_i128(&mut self, v: i128) -> DeserializeResult<()> {
+        self.visitor.dyn_visit_i128(v)
+    }
+
+    fn dyn_visit_u8(&mut self, v: u8) -> DeserializeResult<()> {
+        self.visitor.dyn_visit_u8(v)
+    }
+
+    fn dyn_visit_u16(&mut self, v: u16) -> DeserializeResult<()> {
+        self.visitor.dyn_visit_u16(v)
+    }
+
+    fn dyn_visit_u32(&mut self, v: u32) -> DeserializeResult<()> {
+        self.visitor.dyn_visit_u32(v)
+    }
+
+    fn dyn_visit_u64(&mut self, v: u64) -> DeserializeResult<()> {
+        self.visitor.dyn_visit_u64(v)
+    }
+
+    fn dyn_visit_u128(&mut self, v: u128) -> DeserializeResult<()> {
+        self.visitor.dyn_visit_u128(v)
+    }
+
+    fn dyn_visit_f32(&mut self, v: f32) -> DeserializeResult<()> {
+        self.visitor.dyn_visit_f32(v)
+    }
+
+    fn dyn_visit_f64(&mut self, v: f64) -> DeserializeResult<()> {
+        self.visitor.dyn_visit_f64(v)
+    }
+
+    fn dyn_visit_char(&mut self, v: char) -> DeserializeResult<()> {
+        self.visitor.dyn_visit_char(v)
+    }
+
+    fn dyn_visit_str(&mut self, v: &str) -> DeserializeResult<()> {
+        self.visitor.dyn_visit_str(v)
+    }
+
+    fn dyn_visit_borrowed_str(&mut self, v: &'de str) -> DeserializeResult<()> {
+        self.visitor.dyn_visit_borrowed_str(v)
+    }
+
+    fn dyn_visit_string(&mut self, v: String) -> DeserializeResult<()> {
+        self.visitor.dyn_visit_string(v)
+    }
+
+    fn dyn_visit_bytes(&mut self, v: &[u8]) -> DeserializeResult<()> {
+        self.visitor.dyn_visit_bytes(v)
+    }
+
+    fn dyn_visit_borrowed_bytes(&mut self, v: &'de [u8]) -> DeserializeResult<()> {
+        self.visitor.dyn_visit_borrowed_bytes(v)
+    }
+
+    fn dyn_visit_byte_buf(&mut self, v: Vec<u8>) -> DeserializeResult<()> {
+        self.visitor.dyn_visit_byte_buf(v)
+    }
+
+    fn dyn_visit_none(&mut self) -> DeserializeResult<()> {
+        self.visitor.dyn_visit_none()
+    }
+
+    fn dyn_visit_some(
+        &mut self,
+        deserializer: &mut dyn Deserializer<'de>,
+    ) -> DeserializeResult<()> {
+        self.visitor.dyn_visit_some(deserializer)
+    }
+
+    fn dyn_visit_unit(&mut self) -> DeserializeResult<()> {
+        self.visitor.dyn_visit_unit()
+    }
+
+    fn dyn_visit_newtype_struct(
+        &mut self,
+        deserializer: &mut dyn Deserializer<'de>,
+    ) -> DeserializeResult<()> {
+        self.visitor.dyn_visit_newtype_struct(deserializer)
+    }
+
+    fn dyn_visit_seq(&mut self, access: &mut dyn SeqAccess<'de>) -> DeserializeResult<()> {
+        self.visitor.dyn_visit_seq(access)
+    }
+
+    fn dyn_visit_map(&mut self, access: &mut dyn MapAccess<'de>) -> DeserializeResult<()> {
+        self.visitor.dyn_visit_map(access)
+    }
+
+    fn dyn_visit_enum(&mut self, access: &mut dyn EnumAccess<'de>) -> DeserializeResult<()> {
+        self.visitor.dyn_visit_enum(access)
+    }
+}
@@ -0,0 +1,85 @@
+//! A [`Deserializer`] wrapper fixing whether it is human-readable, see
+//! [`WithHumanReadable`].
+
+use core::fmt::{self, Debug, Formatter};
+
+use crate::de::middleware::{self, Hint};
+use crate::de::{Deserializer, InplaceDeserializeResult, Visitor};
+
+/// A [`Deserializer`] wrapper which answers the same `dyn_is_human_readable`
+/// whatever the state of the wrapped deserializer.
+///
+/// This overrides the answer of a format with the one given to
+/// [`WithHumanReadable::fixed`], or keeps the answer captured by
+/// [`WithHumanReadable::new`] for a deserializer which does not once it has
+/// finished. An [`InplaceDeserializer`] keeps it by itself.
+///
+/// Only the flag of the wrapper itself is fixed. The values inside the value
+/// being deserialized are deserialized by the wrapped deserializer as usual.
+///
+/// [`InplaceDeserializer`]: crate::de::InplaceDeserializer
+///
+/// # Examples
+///
+/// ```
+/// # use serde::Deserialize as _;
+/// # use dyn_serde::Deserializer;
+/// # use dyn_serde::de::WithHumanReadable;
+/// #
+/// let mut deserializer = serde_json::Deserializer::from_str("[1, 2]");
+/// let mut deserializer = <dyn Deserializer>::new(&mut deserializer);
+/// let mut deserializer = WithHumanReadable::new(&mut deserializer);
+///
+/// Vec::<u8>::deserialize(&mut deserializer as &mut dyn Deserializer).unwrap();
+/// assert!(deserializer.dyn_is_human_readable());
+/// ```
+pub struct WithHumanReadable<'a, 'de> {
+    deserializer: &'a mut dyn Deserializer<'de>,
+    human_readable: bool,
+}
+
+impl<'a, 'de> WithHumanReadable<'a, 'de> {
+    /// Wraps `deserializer`, capturing whether it is human-readable.
+    ///
+    /// The deserializer is expected to be ready, as it may not know
+    /// otherwise.
+    #[must_use]
+    pub fn new(deserializer: &'a mut dyn Deserializer<'de>) -> Self {
+        let human_readable = deserializer.dyn_is_human_readable();
+        WithHumanReadable::fixed(deserializer, human_readable)
+    }
+
+    /// Wraps `deserializer`, answering `human_readable` whether it is
+    /// human-readable.
+    #[must_use]
+    pub fn fixed(deserializer: &'a mut dyn Deserializer<'de>, human_readable: bool) -> Self {
+        WithHumanReadable {
+            deserializer,
+            human_readable,
+        }
+    }
+
+    fn deserialize_hint(
+        &mut self,
+        hint: Hint,
+        visitor: &mut dyn Visitor<'de>,
+    ) -> InplaceDeserializeResult<()> {
+        hint.forward(self.deserializer, visitor)
+    }
+}
+
+impl Debug for WithHumanReadable<'_, '_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("WithHumanReadable")
+            .field("human_readable", &self.human_readable)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<'de> Deserializer<'de> for WithHumanReadable<'_, 'de> {
+    middleware::forward_to_deserialize_hint!();
+
+    fn dyn_is_human_readable(&self) -> bool {
+        self.human_readable
+    }
+}
@@ -7,6 +7,9 @@
 //! * [`Serialize`]
 //! * [`Serializer`]
 //! * [`Deserializer`]
+//!
+//! For testing format backends through the dynamic layer, see `test_util`,
+//! which requires feature `test-util`.
 
 #![cfg_attr(not(feature = "std"), no_std)]
 #![warn(missing_docs)]
@@ -18,6 +21,8 @@ extern crate std;
 
 pub mod de;
 pub mod ser;
+#[cfg(feature = "test-util")]
+pub mod test_util;
 
 // re-exports
 pub use crate::de::Deserializer;
@@ -1,4 +1,123 @@
 //! For dynamic serialization, see [`Serialize`] and [`Serializer`].
+//!
+//! For boxing values of mixed types into `Box<dyn Serialize>`, see [`boxed`]
+//! and [`IntoBoxedSerialize`].
+//!
+//! For canonical JSON, suitable for hashing and signing, see [`canonicalize`].
+//!
+//! For telling which members of a value changed by their hashes, see
+//! [`canonical_hashes`].
+//!
+//! For enforcing a maximum output size, see [`SizeLimited`].
+//!
+//! For enforcing a maximum depth, or checking it up front without a format,
+//! see [`DepthLimited`] and [`check_depth`].
+//!
+//! For running a value through its `Serialize` implementation without
+//! writing anything, see [`NullSerializer`].
+//!
+//! For serializing integers regardless of their width, see [`NormalizeInts`].
+//!
+//! For serializing integers too large for JavaScript as strings, see
+//! [`BigNumAsString`].
+//!
+//! For serializing floats as their shortest round-trip strings, independent
+//! of the format, see [`ShortestFloats`].
+//!
+//! For tagging every struct with an extra field, see [`TagStructs`].
+//!
+//! For writing numeric map keys as integers, see [`MapKeysAsInts`].
+//!
+//! For writing unit enum variants as their index, see [`OrdinalVariants`].
+//!
+//! For writing integer and other scalar map keys as strings, see
+//! [`StringifyKeys`].
+//!
+//! For omitting the entries of maps and structs whose value is `None` or
+//! `()`, see [`OmitUnitValues`].
+//!
+//! For omitting the fields of structs which are `PhantomData` or other unit
+//! structs, see [`OmitUnitStructs`].
+//!
+//! For writing empty sequences and maps as `null` rather than `[]` or `{}`,
+//! see [`EmptyAsNull`].
+//!
+//! For writing repeated objects as references to their first occurrence,
+//! see [`DedupObjects`].
+//!
+//! For calling back after every chunk of elements of a sequence, e.g. to
+//! flush a stream, see [`ChunkedSeq`].
+//!
+//! For the number of bytes every field of a struct contributes to the output,
+//! see [`FieldSizes`].
+//!
+//! For serializing a `Duration` as a number of seconds, see [`DurationSecs`].
+//!
+//! For looking at bytes as chunks of hexadecimal digits, e.g. to debug binary
+//! blobs in JSON, see [`HexChunks`].
+//!
+//! For a field always serializing as the same constant, e.g. a `"$schema"`
+//! URL, see [`Const`].
+//!
+//! For a field computed only when it is serialized, see [`Lazy`].
+//!
+//! For the fields of a struct which are present rather than skipped, see
+//! [`present_fields`].
+//!
+//! For a flat map of dotted keys, e.g. `{"a.b.c": 1}`, for systems which do
+//! not support nesting, see [`FlattenDotted`].
+//!
+//! For pretty-printed JSON written to any `fmt::Write`, see [`PrettyText`].
+//!
+//! For serializing a `Result` as an object of a single entry, see
+//! [`ResultAsObject`].
+//!
+//! For tagging a value with a version given at runtime, as
+//! `{"v": 2, "data": value}`, see [`Versioned`].
+//!
+//! For serializing part of a value in the form of non-human-readable
+//! formats, e.g. a timestamp as a number, see [`Compact`].
+//!
+//! For overriding whether a serializer is human-readable, see
+//! [`WithHumanReadable`].
+//!
+//! For serializing a `Display` value without allocating when it is short, see
+//! [`collect_str`].
+//!
+//! For checking that hand-built enum variants pass the index of their name,
+//! see [`CheckVariantIndices`].
+//!
+//! For checking that hand-built compounds hold as many elements as their
+//! declared length, see [`CheckLengths`].
+//!
+//! For writing every enum variant adjacently tagged, see [`AdjacentlyTagged`].
+//!
+//! For writing the fields of structs in a given order, see [`OrderFields`].
+//!
+//! For transforming the whole value before it is serialized, see `Buffered`,
+//! which requires feature `serde_json`.
+//!
+//! For dropping the fields of structs whose value fails a predicate, see
+//! `FilterFields`, which requires feature `serde_json`.
+//!
+//! For lines of `key=value` pairs written to any `io::Write`, see
+//! `LineSerializer`, which requires feature `std`.
+//!
+//! For framing the output of a binary format between a magic header and a
+//! checksum, see `Framed`, which requires feature `std`.
+//!
+//! For writing a stream of values as frames prefixed with their length, see
+//! `LengthPrefixed`, which requires feature `std`.
+//!
+//! For newline-delimited JSON, see `NdjsonSerializer`, which requires features
+//! `std` and `serde_json`.
+//!
+//! For writing the items of an iterator as lines of JSON one at a time, so that
+//! a large export is never held in memory, see `write_json_lines`, which
+//! requires features `std` and `serde_json`.
+//!
+//! For serializing timestamps in a chosen format, see `TimestampFormat`, which
+//! requires feature `time`.
 
 use core::error::Error;
 use core::fmt::{self, Debug, Display, Formatter};
@@ -17,6 +136,107 @@ use serde::ser::SerializeTuple as _;
 use serde::ser::SerializeTupleStruct as _;
 use serde::ser::SerializeTupleVariant as _;
 
+mod adjacently_tagged;
+mod big_num_string;
+mod boxed;
+#[cfg(feature = "serde_json")]
+mod buffered;
+mod canonical;
+mod captured;
+mod check_lengths;
+mod check_variant_indices;
+mod chunked_seq;
+mod collect_str;
+mod compact;
+mod constant;
+mod counted;
+mod dedup_objects;
+mod depth_limited;
+mod duration_secs;
+mod empty_as_null;
+mod field_sizes;
+#[cfg(feature = "serde_json")]
+mod filter_fields;
+mod flatten_dotted;
+#[cfg(feature = "std")]
+mod framed;
+mod hex_chunks;
+mod lazy;
+#[cfg(feature = "std")]
+mod length_prefixed;
+#[cfg(feature = "std")]
+mod line;
+mod map_iter;
+mod map_keys_as_ints;
+mod middleware;
+#[cfg(all(feature = "std", feature = "serde_json"))]
+mod ndjson;
+mod normalize_ints;
+mod null;
+mod omit_unit_values;
+mod order_fields;
+mod ordinal_variants;
+mod present_fields;
+mod pretty_text;
+mod result_object;
+mod shortest_floats;
+mod size_limited;
+mod stringify_keys;
+mod tag_structs;
+#[cfg(feature = "time")]
+mod timestamp_format;
+mod versioned;
+mod with_human_readable;
+
+pub use self::adjacently_tagged::AdjacentlyTagged;
+pub use self::big_num_string::BigNumAsString;
+pub use self::boxed::{IntoBoxedSerialize, boxed};
+#[cfg(feature = "serde_json")]
+pub use self::buffered::Buffered;
+pub use self::canonical::{CanonicalHashes, canonical_hashes, canonicalize};
+pub use self::check_lengths::CheckLengths;
+pub use self::check_variant_indices::CheckVariantIndices;
+pub use self::chunked_seq::ChunkedSeq;
+pub use self::collect_str::collect_str;
+pub use self::compact::Compact;
+pub use self::constant::Const;
+pub use self::dedup_objects::DedupObjects;
+pub use self::depth_limited::{DepthLimited, check_depth};
+pub use self::duration_secs::DurationSecs;
+pub use self::empty_as_null::EmptyAsNull;
+pub use self::field_sizes::FieldSizes;
+#[cfg(feature = "serde_json")]
+pub use self::filter_fields::FilterFields;
+pub use self::flatten_dotted::FlattenDotted;
+#[cfg(feature = "std")]
+pub use self::framed::Framed;
+pub use self::hex_chunks::HexChunks;
+pub use self::lazy::Lazy;
+#[cfg(feature = "std")]
+pub use self::length_prefixed::LengthPrefixed;
+#[cfg(feature = "std")]
+pub use self::line::LineSerializer;
+pub use self::map_iter::SerializeMapIter;
+pub use self::map_keys_as_ints::MapKeysAsInts;
+#[cfg(all(feature = "std", feature = "serde_json"))]
+pub use self::ndjson::{NdjsonSerializer, write_json_lines};
+pub use self::normalize_ints::NormalizeInts;
+pub use self::null::NullSerializer;
+pub use self::omit_unit_values::{OmitUnitStructs, OmitUnitValues};
+pub use self::order_fields::OrderFields;
+pub use self::ordinal_variants::OrdinalVariants;
+pub use self::present_fields::present_fields;
+pub use self::pretty_text::PrettyText;
+pub use self::result_object::ResultAsObject;
+pub use self::shortest_floats::ShortestFloats;
+pub use self::size_limited::SizeLimited;
+pub use self::stringify_keys::StringifyKeys;
+pub use self::tag_structs::TagStructs;
+#[cfg(feature = "time")]
+pub use self::timestamp_format::TimestampFormat;
+pub use self::versioned::Versioned;
+pub use self::with_human_readable::WithHumanReadable;
+
 /// The result type returned by [`dyn Serializer`]'s methods.
 ///
 /// [`dyn Serializer`]: Serializer
@@ -58,6 +278,18 @@ pub type InplaceSerializeResult<T> = Result<T, InplaceSerializeError>;
 ///
 /// assert_eq!(buf, b"[-3.1415926,\"Hello, world!\",false,[1,2,3],null]");
 /// ```
+///
+/// As the trait object implements [`serde::Serialize`], so do the references,
+/// boxes and tuples of trait objects, through the implementations of `serde`,
+/// which cover tuples of up to 16 elements. A tuple of trait objects
+/// serializes as a tuple, e.g. for a record of fixed length and mixed types.
+///
+/// ```
+/// # use dyn_serde::Serialize;
+/// #
+/// let record: (&dyn Serialize, &dyn Serialize) = (&1, &"one");
+/// assert_eq!(serde_json::to_string(&record).unwrap(), r#"[1,"one"]"#);
+/// ```
 #[diagnostic::on_unimplemented(note = "Consider implementing `serde::Serialize` for `{Self}`")]
 pub trait Serialize {
     /// Serialize the `self` value with the given dynamic `serializer`.
@@ -72,6 +304,30 @@ pub trait Serialize {
 /// One should avoid implementing `Serializer` manually and use
 /// `<dyn Serializer>::new` to construct an instance instead.
 ///
+/// # Required methods
+///
+/// A manual implementation only has to provide the methods that cannot be
+/// expressed in terms of the others:
+///
+/// * `dyn_serialize_bool`, `dyn_serialize_i64`, `dyn_serialize_i128`,
+///   `dyn_serialize_u64`, `dyn_serialize_u128`, `dyn_serialize_f64`,
+///   `dyn_serialize_str` and `dyn_serialize_bytes`;
+/// * `dyn_serialize_none`, `dyn_serialize_some` and `dyn_serialize_unit`;
+/// * `dyn_serialize_unit_variant`, `dyn_serialize_newtype_struct` and
+///   `dyn_serialize_newtype_variant`;
+/// * all methods beginning a compound value, e.g. `dyn_serialize_seq`.
+///
+/// The remaining methods are provided:
+///
+/// * the narrower integers and `f32` are widened to `i64`, `u64` and `f64`;
+/// * `dyn_serialize_char` serializes the character as a `&str`;
+/// * `dyn_serialize_unit_struct` serializes a `()` value;
+/// * `dyn_collect_str` buffers the string and calls `dyn_serialize_str`;
+/// * `dyn_is_human_readable` returns `true`.
+///
+/// Methods added to the trait in the future will be provided as well, so
+/// that implementing the required methods remains sufficient.
+///
 /// # Examples
 ///
 /// ```
@@ -95,7 +351,7 @@ pub trait Serialize {
 /// let result = seq.end();
 /// assert!(result.is_ok());
 ///
-/// // Now `inplace_serializer` becomes `InplaceSerializer::Ok(_)` or `Error(_)`.
+/// // Now `inplace_serializer` becomes `InplaceSerializer::Ok(..)` or `Error(..)`.
 /// // And continue to use `serializer` would cause an error.
 /// assert!(serializer.serialize_i32(0).is_err());
 /// assert!(serializer.serialize_bool(true).is_err());
@@ -108,13 +364,25 @@ pub trait Serializer {
     fn dyn_serialize_bool(&mut self, v: bool) -> InplaceSerializeResult<()>;
 
     /// Serialize an `i8` value.
-    fn dyn_serialize_i8(&mut self, v: i8) -> InplaceSerializeResult<()>;
+    ///
+    /// The default implementation serializes the value as an `i64`.
+    fn dyn_serialize_i8(&mut self, v: i8) -> InplaceSerializeResult<()> {
+        self.dyn_serialize_i64(i64::from(v))
+    }
 
     /// Serialize an `i16` value.
-    fn dyn_serialize_i16(&mut self, v: i16) -> InplaceSerializeResult<()>;
+    ///
+    /// The default implementation serializes the value as an `i64`.
+    fn dyn_serialize_i16(&mut self, v: i16) -> InplaceSerializeResult<()> {
+        self.dyn_serialize_i64(i64::from(v))
+    }
 
     /// Serialize an `i32` value.
-    fn dyn_serialize_i32(&mut self, v: i32) -> InplaceSerializeResult<()>;
+    ///
+    /// The default implementation serializes the value as an `i64`.
+    fn dyn_serialize_i32(&mut self, v: i32) -> InplaceSerializeResult<()> {
+        self.dyn_serialize_i64(i64::from(v))
+    }
 
     /// Serialize an `i64` value.
     fn dyn_serialize_i64(&mut self, v: i64) -> InplaceSerializeResult<()>;
@@ -123,13 +391,25 @@ pub trait Serializer {
     fn dyn_serialize_i128(&mut self, v: i128) -> InplaceSerializeResult<()>;
 
     /// Serialize a `u8` value.
-    fn dyn_serialize_u8(&mut self, v: u8) -> InplaceSerializeResult<()>;
+    ///
+    /// The default implementation serializes the value as a `u64`.
+    fn dyn_serialize_u8(&mut self, v: u8) -> InplaceSerializeResult<()> {
+        self.dyn_serialize_u64(u64::from(v))
+    }
 
     /// Serialize a `u16` value.
-    fn dyn_serialize_u16(&mut self, v: u16) -> InplaceSerializeResult<()>;
+    ///
+    /// The default implementation serializes the value as a `u64`.
+    fn dyn_serialize_u16(&mut self, v: u16) -> InplaceSerializeResult<()> {
+        self.dyn_serialize_u64(u64::from(v))
+    }
 
     /// Serialize a `u32` value.
-    fn dyn_serialize_u32(&mut self, v: u32) -> InplaceSerializeResult<()>;
+    ///
+    /// The default implementation serializes the value as a `u64`.
+    fn dyn_serialize_u32(&mut self, v: u32) -> InplaceSerializeResult<()> {
+        self.dyn_serialize_u64(u64::from(v))
+    }
 
     /// Serialize a `u64` value.
     fn dyn_serialize_u64(&mut self, v: u64) -> InplaceSerializeResult<()>;
@@ -138,13 +418,21 @@ pub trait Serializer {
     fn dyn_serialize_u128(&mut self, v: u128) -> InplaceSerializeResult<()>;
 
     /// Serialize an `f32` value.
-    fn dyn_serialize_f32(&mut self, v: f32) -> InplaceSerializeResult<()>;
+    ///
+    /// The default implementation serializes the value as an `f64`.
+    fn dyn_serialize_f32(&mut self, v: f32) -> InplaceSerializeResult<()> {
+        self.dyn_serialize_f64(f64::from(v))
+    }
 
     /// Serialize an `f64` value.
     fn dyn_serialize_f64(&mut self, v: f64) -> InplaceSerializeResult<()>;
 
     /// Serialize a character.
-    fn dyn_serialize_char(&mut self, v: char) -> InplaceSerializeResult<()>;
+    ///
+    /// The default implementation serializes the character as a `&str`.
+    fn dyn_serialize_char(&mut self, v: char) -> InplaceSerializeResult<()> {
+        self.dyn_serialize_str(v.encode_utf8(&mut [0; 4]))
+    }
 
     /// Serialize a `&str`.
     fn dyn_serialize_str(&mut self, v: &str) -> InplaceSerializeResult<()>;
@@ -156,13 +444,25 @@ pub trait Serializer {
     fn dyn_serialize_none(&mut self) -> InplaceSerializeResult<()>;
 
     /// Serialize a `Some(_)` value.
+    ///
+    /// The value is borrowed immutably, as every value given to a serializer
+    /// is: [`Serialize::dyn_serialize`] takes `&self`, and so does
+    /// [`serde::Serialize::serialize`], which the value is eventually handed
+    /// to. A value needing state while serializing, e.g. one computing its
+    /// content lazily, keeps it behind interior mutability such as a
+    /// `OnceCell`.
     fn dyn_serialize_some(&mut self, value: &dyn Serialize) -> InplaceSerializeResult<()>;
 
     /// Serialize a `()` value.
     fn dyn_serialize_unit(&mut self) -> InplaceSerializeResult<()>;
 
     /// Serialize a unit struct like `struct Unit` or `PhantomData<T>`.
-    fn dyn_serialize_unit_struct(&mut self, name: &'static str) -> InplaceSerializeResult<()>;
+    ///
+    /// The default implementation serializes a `()` value.
+    fn dyn_serialize_unit_struct(&mut self, name: &'static str) -> InplaceSerializeResult<()> {
+        let _ = name;
+        self.dyn_serialize_unit()
+    }
 
     /// Serialize a unit variant like `E::A` in `enum E { A, B }`.
     ///
@@ -287,7 +587,12 @@ pub trait Serializer {
     ) -> InplaceSerializeResult<&mut dyn SerializeStructVariant>;
 
     /// Serialize a string produced by an implementation of [`Display`].
-    fn dyn_collect_str(&mut self, value: &dyn Display) -> InplaceSerializeResult<()>;
+    ///
+    /// The default implementation formats the value into a buffer and then
+    /// serializes the buffer with `dyn_serialize_str`, see [`collect_str`].
+    fn dyn_collect_str(&mut self, value: &dyn Display) -> InplaceSerializeResult<()> {
+        collect_str(self, value)
+    }
 
     /// Determine whether `Serialize` implementations should serialize in
     /// human-readable form.
@@ -296,7 +601,26 @@ pub trait Serializer {
     /// for more information.
     ///
     /// [`is_human_readable`]: serde::Serializer::is_human_readable
-    fn dyn_is_human_readable(&self) -> bool;
+    ///
+    /// The default implementation returns `true`.
+    fn dyn_is_human_readable(&self) -> bool {
+        true
+    }
+
+    /// Returns the number of bytes written to the output so far, if the
+    /// serializer counts them.
+    ///
+    /// This lets a wrapper report the progress of any serializer writing its
+    /// output itself, e.g. [`PrettyText`], without counting the output
+    /// separately. A serializer wrapping a [`serde::Serializer`], such as
+    /// [`InplaceSerializer`], cannot tell. A wrapper of this crate answers for
+    /// the serializer it wraps until it begins a compound, which the
+    /// serializer is lent to, and `None` from then on.
+    ///
+    /// The default implementation returns `None`.
+    fn dyn_bytes_written(&self) -> Option<u64> {
+        None
+    }
 }
 
 impl dyn Serializer + '_ {
@@ -314,7 +638,7 @@ impl dyn Serializer + '_ {
     /// let stdout = std::io::stdout();
     /// let mut serializer = serde_json::Serializer::new(stdout.lock());
     /// let mut serializer = <dyn Serializer>::new(&mut serializer);
-    /// let serializer = &mut serializer as &mut dyn Serializer;
+    /// let serializer = serializer.as_mut();
     /// # let _ = serializer;
     /// ```
     #[must_use]
@@ -413,11 +737,17 @@ pub trait SerializeMap {
     fn dyn_serialize_value(&mut self, value: &dyn Serialize) -> InplaceSerializeResult<()>;
 
     /// Serialize a map entry consisting of a key and a value.
+    ///
+    /// The default implementation calls `dyn_serialize_key` and then
+    /// `dyn_serialize_value`.
     fn dyn_serialize_entry(
         &mut self,
         key: &dyn Serialize,
         value: &dyn Serialize,
-    ) -> InplaceSerializeResult<()>;
+    ) -> InplaceSerializeResult<()> {
+        self.dyn_serialize_key(key)?;
+        self.dyn_serialize_value(value)
+    }
 
     /// Finish serializing a map.
     fn dyn_end(&mut self) -> InplaceSerializeResult<()>;
@@ -441,7 +771,12 @@ pub trait SerializeStruct {
     ) -> InplaceSerializeResult<()>;
 
     /// Indicate that a struct field has been skipped.
-    fn dyn_skip_field(&mut self, key: &'static str) -> InplaceSerializeResult<()>;
+    ///
+    /// The default implementation does nothing.
+    fn dyn_skip_field(&mut self, key: &'static str) -> InplaceSerializeResult<()> {
+        let _ = key;
+        Ok(())
+    }
 
     /// Finish serializing a struct.
     fn dyn_end(&mut self) -> InplaceSerializeResult<()>;
@@ -466,7 +801,12 @@ pub trait SerializeStructVariant {
     ) -> InplaceSerializeResult<()>;
 
     /// Indicate that a struct variant field has been skipped.
-    fn dyn_skip_field(&mut self, key: &'static str) -> InplaceSerializeResult<()>;
+    ///
+    /// The default implementation does nothing.
+    fn dyn_skip_field(&mut self, key: &'static str) -> InplaceSerializeResult<()> {
+        let _ = key;
+        Ok(())
+    }
 
     /// Finish serializing a struct variant.
     fn dyn_end(&mut self) -> InplaceSerializeResult<()>;
@@ -475,6 +815,16 @@ pub trait SerializeStructVariant {
 /// An implementation of the [`Serializer`] trait which performs
 /// in-place serialization so that the result type is unified to
 /// [`InplaceSerializeResult<_>`].
+///
+/// Its `dyn_bytes_written` answers `None`, as a [`serde::Serializer`] does not
+/// report the bytes it writes, nor gives access to its writer. To measure the
+/// output, count it through the writer given to the format instead, as done
+/// with [`SizeLimited`].
+///
+/// Each state it moves to once the serializer is consumed holds whether the
+/// serializer is human-readable, captured before it is, so that
+/// `dyn_is_human_readable` keeps answering for the format until the end of
+/// the serialization.
 #[derive(Clone, Default, Debug)]
 pub enum InplaceSerializer<S>
 where
@@ -484,110 +834,158 @@ where
     #[default]
     None,
     /// The in-place serialization has done successfually.
-    Ok(S::Ok),
+    Ok(S::Ok, bool),
     /// The in-place serialization has done unsuccessfually.
-    Error(S::Error),
+    Error(S::Error, bool),
     /// The in-place serializer is ready.
     Serializer(S),
     /// The in-place serializer is ready to serialize the content of the
     /// sequence.
-    SerializeSeq(S::SerializeSeq),
+    SerializeSeq(S::SerializeSeq, bool),
     /// The in-place serializer is ready to serialize the content of the tuple.
-    SerializeTuple(S::SerializeTuple),
+    SerializeTuple(S::SerializeTuple, bool),
     /// The in-place serializer is ready to serialize the content of the tuple
     /// struct.
-    SerializeTupleStruct(S::SerializeTupleStruct),
+    SerializeTupleStruct(S::SerializeTupleStruct, bool),
     /// The in-place serializer is ready to serialize the content of the tuple
     /// variant.
-    SerializeTupleVariant(S::SerializeTupleVariant),
+    SerializeTupleVariant(S::SerializeTupleVariant, bool),
     /// The in-place serializer is ready to serialize the content of the map.
-    SerializeMap(S::SerializeMap),
+    SerializeMap(S::SerializeMap, bool),
     /// The in-place serializer is ready to serialize the content of the
     /// struct.
-    SerializeStruct(S::SerializeStruct),
+    SerializeStruct(S::SerializeStruct, bool),
     /// The in-place serializer is ready to serialize the content of the
     /// struct variant.
-    SerializeStructVariant(S::SerializeStructVariant),
+    SerializeStructVariant(S::SerializeStructVariant, bool),
 }
 
 impl<S> InplaceSerializer<S>
 where
     S: serde::Serializer,
 {
-    fn take(&mut self) -> InplaceSerializeResult<S> {
-        if let InplaceSerializer::Serializer(_) = self {
-            if let InplaceSerializer::Serializer(serializer) = mem::take(self) {
-                return Ok(serializer);
+    /// Returns the state of the in-place serializer, without its content.
+    #[must_use]
+    pub fn state(&self) -> InplaceSerializerState {
+        match self {
+            InplaceSerializer::None => InplaceSerializerState::None,
+            InplaceSerializer::Ok(..) => InplaceSerializerState::Ok,
+            InplaceSerializer::Error(..) => InplaceSerializerState::Error,
+            InplaceSerializer::Serializer(_) => InplaceSerializerState::Serializer,
+            InplaceSerializer::SerializeSeq(..) => InplaceSerializerState::SerializeSeq,
+            InplaceSerializer::SerializeTuple(..) => InplaceSerializerState::SerializeTuple,
+            InplaceSerializer::SerializeTupleStruct(..) => {
+                InplaceSerializerState::SerializeTupleStruct
+            }
+            InplaceSerializer::SerializeTupleVariant(..) => {
+                InplaceSerializerState::SerializeTupleVariant
+            }
+            InplaceSerializer::SerializeMap(..) => InplaceSerializerState::SerializeMap,
+            InplaceSerializer::SerializeStruct(..) => InplaceSerializerState::SerializeStruct,
+            InplaceSerializer::SerializeStructVariant(..) => {
+                InplaceSerializerState::SerializeStructVariant
             }
         }
+    }
+
+    /// Returns the output of the serialization if it has done successfully,
+    /// or the in-place serializer itself otherwise, e.g. to inspect its error.
+    ///
+    /// This is what `TryFrom<InplaceSerializer<S>> for S::Ok` would be, which
+    /// cannot be implemented since it overlaps with the blanket implementation
+    /// of `TryFrom` in `core`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use dyn_serde::{Serialize, Serializer};
+    /// # use dyn_serde::ser::InplaceSerializer;
+    /// #
+    /// let mut serializer = <dyn Serializer>::new(serde_json::value::Serializer);
+    /// (&[1, 2] as &dyn Serialize).dyn_serialize(&mut serializer).unwrap();
+    /// assert_eq!(serializer.try_into_ok().ok(), Some(serde_json::json!([1, 2])));
+    /// ```
+    pub fn try_into_ok(self) -> Result<S::Ok, Self> {
+        match self {
+            InplaceSerializer::Ok(ok, _) => Ok(ok),
+            serializer => Err(serializer),
+        }
+    }
+
+    fn take(&mut self) -> InplaceSerializeResult<S> {
+        if let InplaceSerializer::Serializer(_) = self
+            && let InplaceSerializer::Serializer(serializer) = mem::take(self)
+        {
+            return Ok(serializer);
+        }
         Err(InplaceSerializeError::NotSerializer)
     }
 
     fn take_seq(&mut self) -> InplaceSerializeResult<S::SerializeSeq> {
-        if let InplaceSerializer::SerializeSeq(_) = self {
-            if let InplaceSerializer::SerializeSeq(serializer) = mem::take(self) {
-                return Ok(serializer);
-            }
+        if let InplaceSerializer::SerializeSeq(..) = self
+            && let InplaceSerializer::SerializeSeq(serializer, _) = mem::take(self)
+        {
+            return Ok(serializer);
         }
         Err(InplaceSerializeError::NotSerializeSeq)
     }
 
     fn take_tuple(&mut self) -> InplaceSerializeResult<S::SerializeTuple> {
-        if let InplaceSerializer::SerializeTuple(_) = self {
-            if let InplaceSerializer::SerializeTuple(serializer) = mem::take(self) {
-                return Ok(serializer);
-            }
+        if let InplaceSerializer::SerializeTuple(..) = self
+            && let InplaceSerializer::SerializeTuple(serializer, _) = mem::take(self)
+        {
+            return Ok(serializer);
         }
         Err(InplaceSerializeError::NotSerializeTuple)
     }
 
     fn take_tuple_struct(&mut self) -> InplaceSerializeResult<S::SerializeTupleStruct> {
-        if let InplaceSerializer::SerializeTupleStruct(_) = self {
-            if let InplaceSerializer::SerializeTupleStruct(serializer) = mem::take(self) {
-                return Ok(serializer);
-            }
+        if let InplaceSerializer::SerializeTupleStruct(..) = self
+            && let InplaceSerializer::SerializeTupleStruct(serializer, _) = mem::take(self)
+        {
+            return Ok(serializer);
         }
         Err(InplaceSerializeError::NotSerializeTupleStruct)
     }
 
     fn take_tuple_variant(&mut self) -> InplaceSerializeResult<S::SerializeTupleVariant> {
-        if let InplaceSerializer::SerializeTupleVariant(_) = self {
-            if let InplaceSerializer::SerializeTupleVariant(serializer) = mem::take(self) {
-                return Ok(serializer);
-            }
+        if let InplaceSerializer::SerializeTupleVariant(..) = self
+            && let InplaceSerializer::SerializeTupleVariant(serializer, _) = mem::take(self)
+        {
+            return Ok(serializer);
         }
         Err(InplaceSerializeError::NotSerializeTupleVariant)
     }
 
     fn take_map(&mut self) -> InplaceSerializeResult<S::SerializeMap> {
-        if let InplaceSerializer::SerializeMap(_) = self {
-            if let InplaceSerializer::SerializeMap(serializer) = mem::take(self) {
-                return Ok(serializer);
-            }
+        if let InplaceSerializer::SerializeMap(..) = self
+            && let InplaceSerializer::SerializeMap(serializer, _) = mem::take(self)
+        {
+            return Ok(serializer);
         }
         Err(InplaceSerializeError::NotSerializeMap)
     }
 
     fn take_struct(&mut self) -> InplaceSerializeResult<S::SerializeStruct> {
-        if let InplaceSerializer::SerializeStruct(_) = self {
-            if let InplaceSerializer::SerializeStruct(serializer) = mem::take(self) {
-                return Ok(serializer);
-            }
+        if let InplaceSerializer::SerializeStruct(..) = self
+            && let InplaceSerializer::SerializeStruct(serializer, _) = mem::take(self)
+        {
+            return Ok(serializer);
         }
         Err(InplaceSerializeError::NotSerializeStruct)
     }
 
     fn take_struct_variant(&mut self) -> InplaceSerializeResult<S::SerializeStructVariant> {
-        if let InplaceSerializer::SerializeStructVariant(_) = self {
-            if let InplaceSerializer::SerializeStructVariant(serializer) = mem::take(self) {
-                return Ok(serializer);
-            }
+        if let InplaceSerializer::SerializeStructVariant(..) = self
+            && let InplaceSerializer::SerializeStructVariant(serializer, _) = mem::take(self)
+        {
+            return Ok(serializer);
         }
         Err(InplaceSerializeError::NotSerializeStructVariant)
     }
 
     fn get_seq(&mut self) -> InplaceSerializeResult<&mut S::SerializeSeq> {
-        if let InplaceSerializer::SerializeSeq(serializer) = self {
+        if let InplaceSerializer::SerializeSeq(serializer, _) = self {
             Ok(serializer)
         } else {
             Err(InplaceSerializeError::NotSerializeSeq)
@@ -595,7 +993,7 @@ where
     }
 
     fn get_tuple(&mut self) -> InplaceSerializeResult<&mut S::SerializeTuple> {
-        if let InplaceSerializer::SerializeTuple(serializer) = self {
+        if let InplaceSerializer::SerializeTuple(serializer, _) = self {
             Ok(serializer)
         } else {
             Err(InplaceSerializeError::NotSerializeTuple)
@@ -603,7 +1001,7 @@ where
     }
 
     fn get_tuple_struct(&mut self) -> InplaceSerializeResult<&mut S::SerializeTupleStruct> {
-        if let InplaceSerializer::SerializeTupleStruct(serializer) = self {
+        if let InplaceSerializer::SerializeTupleStruct(serializer, _) = self {
             Ok(serializer)
         } else {
             Err(InplaceSerializeError::NotSerializeTupleStruct)
@@ -611,7 +1009,7 @@ where
     }
 
     fn get_tuple_variant(&mut self) -> InplaceSerializeResult<&mut S::SerializeTupleVariant> {
-        if let InplaceSerializer::SerializeTupleVariant(serializer) = self {
+        if let InplaceSerializer::SerializeTupleVariant(serializer, _) = self {
             Ok(serializer)
         } else {
             Err(InplaceSerializeError::NotSerializeTupleVariant)
@@ -619,7 +1017,7 @@ where
     }
 
     fn get_map(&mut self) -> InplaceSerializeResult<&mut S::SerializeMap> {
-        if let InplaceSerializer::SerializeMap(serializer) = self {
+        if let InplaceSerializer::SerializeMap(serializer, _) = self {
             Ok(serializer)
         } else {
             Err(InplaceSerializeError::NotSerializeMap)
@@ -627,7 +1025,7 @@ where
     }
 
     fn get_struct(&mut self) -> InplaceSerializeResult<&mut S::SerializeStruct> {
-        if let InplaceSerializer::SerializeStruct(serializer) = self {
+        if let InplaceSerializer::SerializeStruct(serializer, _) = self {
             Ok(serializer)
         } else {
             Err(InplaceSerializeError::NotSerializeStruct)
@@ -635,7 +1033,7 @@ where
     }
 
     fn get_struct_variant(&mut self) -> InplaceSerializeResult<&mut S::SerializeStructVariant> {
-        if let InplaceSerializer::SerializeStructVariant(serializer) = self {
+        if let InplaceSerializer::SerializeStructVariant(serializer, _) = self {
             Ok(serializer)
         } else {
             Err(InplaceSerializeError::NotSerializeStructVariant)
@@ -645,15 +1043,16 @@ where
     fn serialize_with<T, U>(
         &mut self,
         take: impl FnOnce(&mut Self) -> InplaceSerializeResult<T>,
-        then: impl FnOnce(U) -> Self,
+        then: impl FnOnce(U, bool) -> Self,
         serialize: impl FnOnce(T) -> Result<U, S::Error>,
     ) -> InplaceSerializeResult<()> {
+        let human_readable = self.dyn_is_human_readable();
         let serializer = (take)(self)?;
         match (serialize)(serializer) {
             #[allow(clippy::unit_arg)]
-            Ok(ok) => Ok(*self = (then)(ok)),
+            Ok(ok) => Ok(*self = (then)(ok, human_readable)),
             Err(error) => {
-                *self = InplaceSerializer::Error(error);
+                *self = InplaceSerializer::Error(error, human_readable);
                 Err(InplaceSerializeError::Error)
             }
         }
@@ -664,17 +1063,44 @@ where
         take: impl FnOnce(&mut Self) -> InplaceSerializeResult<&mut T>,
         serialize: impl FnOnce(&mut T) -> Result<(), S::Error>,
     ) -> InplaceSerializeResult<()> {
+        let human_readable = self.dyn_is_human_readable();
         let serializer = (take)(self)?;
         match (serialize)(serializer) {
             Ok(ok) => Ok(ok),
             Err(error) => {
-                *self = InplaceSerializer::Error(error);
+                *self = InplaceSerializer::Error(error, human_readable);
                 Err(InplaceSerializeError::Error)
             }
         }
     }
 }
 
+/// Borrows the in-place serializer as a [`dyn Serializer`], which saves
+/// casting it with `as &mut dyn Serializer`.
+///
+/// [`dyn Serializer`]: Serializer
+///
+/// # Examples
+///
+/// ```
+/// # use dyn_serde::{Serialize, Serializer};
+/// #
+/// let mut output = Vec::new();
+/// let mut serializer = serde_json::Serializer::new(&mut output);
+/// let mut serializer = <dyn Serializer>::new(&mut serializer);
+/// let serializer = serializer.as_mut();
+/// (&[1, 2] as &dyn Serialize).dyn_serialize(serializer).unwrap();
+/// assert_eq!(output, b"[1,2]");
+/// ```
+impl<'a, S> AsMut<dyn Serializer + 'a> for InplaceSerializer<S>
+where
+    S: serde::Serializer + 'a,
+{
+    fn as_mut(&mut self) -> &mut (dyn Serializer + 'a) {
+        self
+    }
+}
+
 impl<S> Serializer for InplaceSerializer<S>
 where
     S: serde::Serializer,
@@ -931,10 +1357,18 @@ where
     }
 
     fn dyn_is_human_readable(&self) -> bool {
-        if let InplaceSerializer::Serializer(serializer) = self {
-            serializer.is_human_readable()
-        } else {
-            true
+        match *self {
+            InplaceSerializer::None => true,
+            InplaceSerializer::Serializer(ref serializer) => serializer.is_human_readable(),
+            InplaceSerializer::Ok(_, human_readable)
+            | InplaceSerializer::Error(_, human_readable)
+            | InplaceSerializer::SerializeSeq(_, human_readable)
+            | InplaceSerializer::SerializeTuple(_, human_readable)
+            | InplaceSerializer::SerializeTupleStruct(_, human_readable)
+            | InplaceSerializer::SerializeTupleVariant(_, human_readable)
+            | InplaceSerializer::SerializeMap(_, human_readable)
+            | InplaceSerializer::SerializeStruct(_, human_readable)
+            | InplaceSerializer::SerializeStructVariant(_, human_readable) => human_readable,
         }
     }
 }
@@ -1102,6 +1536,48 @@ where
     }
 }
 
+/// The state of an [`InplaceSerializer`], without its content.
+///
+/// It is returned by [`InplaceSerializer::state`] to tell which state the
+/// in-place serializer is in, e.g. when it is not ready for a call, see
+/// [`InplaceSerializeError::in_state`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum InplaceSerializerState {
+    /// The in-place serializer is not ready.
+    None,
+    /// The in-place serialization has done successfually.
+    Ok,
+    /// The in-place serialization has done unsuccessfually.
+    Error,
+    /// The in-place serializer is ready.
+    Serializer,
+    /// The in-place serializer is ready to serialize the content of the
+    /// sequence.
+    SerializeSeq,
+    /// The in-place serializer is ready to serialize the content of the tuple.
+    SerializeTuple,
+    /// The in-place serializer is ready to serialize the content of the tuple
+    /// struct.
+    SerializeTupleStruct,
+    /// The in-place serializer is ready to serialize the content of the tuple
+    /// variant.
+    SerializeTupleVariant,
+    /// The in-place serializer is ready to serialize the content of the map.
+    SerializeMap,
+    /// The in-place serializer is ready to serialize the content of the
+    /// struct.
+    SerializeStruct,
+    /// The in-place serializer is ready to serialize the content of the
+    /// struct variant.
+    SerializeStructVariant,
+}
+
+impl Display for InplaceSerializerState {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        Debug::fmt(self, f)
+    }
+}
+
 /// An error returned by [`InplaceSerializer`] when the in-place serialization
 /// has done unsuccessfully.
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
@@ -1133,6 +1609,65 @@ pub enum InplaceSerializeError {
     NotSerializeStructVariant,
 }
 
+impl InplaceSerializeError {
+    /// Returns the state the in-place serializer has to be in for the failed
+    /// call, if it is not ready.
+    #[must_use]
+    pub fn expected(&self) -> Option<InplaceSerializerState> {
+        match self {
+            InplaceSerializeError::Error => None,
+            InplaceSerializeError::NotSerializer => Some(InplaceSerializerState::Serializer),
+            InplaceSerializeError::NotSerializeSeq => Some(InplaceSerializerState::SerializeSeq),
+            InplaceSerializeError::NotSerializeTuple => {
+                Some(InplaceSerializerState::SerializeTuple)
+            }
+            InplaceSerializeError::NotSerializeTupleStruct => {
+                Some(InplaceSerializerState::SerializeTupleStruct)
+            }
+            InplaceSerializeError::NotSerializeTupleVariant => {
+                Some(InplaceSerializerState::SerializeTupleVariant)
+            }
+            InplaceSerializeError::NotSerializeMap => Some(InplaceSerializerState::SerializeMap),
+            InplaceSerializeError::NotSerializeStruct => {
+                Some(InplaceSerializerState::SerializeStruct)
+            }
+            InplaceSerializeError::NotSerializeStructVariant => {
+                Some(InplaceSerializerState::SerializeStructVariant)
+            }
+        }
+    }
+
+    /// Returns a value displaying the error along with the `state` the
+    /// in-place serializer is actually in, as returned by
+    /// [`InplaceSerializer::state`] after the failed call.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use dyn_serde::Serializer;
+    /// # use dyn_serde::ser::SerializeSeq;
+    /// #
+    /// let mut serializer = <dyn Serializer>::new(serde_json::value::Serializer);
+    /// serializer.dyn_serialize_map(None).unwrap();
+    ///
+    /// let error = SerializeSeq::dyn_serialize_element(&mut serializer, &0).unwrap_err();
+    /// assert_eq!(
+    ///     error.in_state(serializer.state()).to_string(),
+    ///     "the in-place serializer is not ready to serialize the content of the sequence: \
+    ///      expected SerializeSeq but the serializer is in SerializeMap state",
+    /// );
+    /// ```
+    pub fn in_state(self, state: InplaceSerializerState) -> impl Display {
+        fmt::from_fn(move |f| match self.expected() {
+            Some(expected) => write!(
+                f,
+                "{self}: expected {expected} but the serializer is in {state} state"
+            ),
+            None => Display::fmt(&self, f),
+        })
+    }
+}
+
 impl Display for InplaceSerializeError {
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
         f.write_str(match self {
@@ -1220,8 +1755,8 @@ impl serde::Serialize for dyn Serialize + '_ {
         let mut serializer = InplaceSerializer::Serializer(serializer);
         let result = self.dyn_serialize(&mut serializer);
         match serializer {
-            InplaceSerializer::Ok(ok) => Ok(ok),
-            InplaceSerializer::Error(error) => Err(error),
+            InplaceSerializer::Ok(ok, _) => Ok(ok),
+            InplaceSerializer::Error(error, _) => Err(error),
             // The `unwrap_err` never panics becasue `result` is `Ok(_)` if and
             // only if the `serializer` is `Ok(_)`. And we have checked that it
             // isn't because of the above branch.
@@ -1261,6 +1796,10 @@ impl<'a> serde::Serializer for &'a mut (dyn Serializer + '_) {
         self.dyn_serialize_i64(v).map_err(SerializeError::from)
     }
 
+    fn serialize_i128(self, v: i128) -> SerializeResult<()> {
+        self.dyn_serialize_i128(v).map_err(SerializeError::from)
+    }
+
     fn serialize_u8(self, v: u8) -> SerializeResult<()> {
         self.dyn_serialize_u8(v).map_err(SerializeError::from)
     }
@@ -1277,6 +1816,10 @@ impl<'a> serde::Serializer for &'a mut (dyn Serializer + '_) {
         self.dyn_serialize_u64(v).map_err(SerializeError::from)
     }
 
+    fn serialize_u128(self, v: u128) -> SerializeResult<()> {
+        self.dyn_serialize_u128(v).map_err(SerializeError::from)
+    }
+
     fn serialize_f32(self, v: f32) -> SerializeResult<()> {
         self.dyn_serialize_f32(v).map_err(SerializeError::from)
     }
@@ -1401,6 +1944,10 @@ impl<'a> serde::Serializer for &'a mut (dyn Serializer + '_) {
         self.dyn_serialize_struct_variant(name, variant_index, variant, len)
             .map_err(SerializeError::from)
     }
+
+    fn is_human_readable(&self) -> bool {
+        self.dyn_is_human_readable()
+    }
 }
 
 impl serde::ser::SerializeSeq for &mut (dyn SerializeSeq + '_) {
@@ -0,0 +1,221 @@
+//! A [`Serializer`] wrapper writing every enum variant adjacently tagged, see
+//! [`AdjacentlyTagged`].
+
+use core::cell::RefCell;
+use core::fmt::{self, Debug, Formatter};
+
+#[cfg(not(feature = "std"))]
+use alloc::vec::Vec;
+
+use serde::ser::{SerializeStruct as _, SerializeTuple as _};
+
+use crate::ser::captured::{self, Captured};
+use crate::ser::middleware::{self, Begin, Call, Compound, Element};
+use crate::ser::middleware::{Middleware, MiddlewareSerializer};
+use crate::ser::{Serialize, SerializeResult, Serializer};
+
+/// A [`Serializer`] wrapper which writes every enum variant as a struct of
+/// two fields, the name of the variant and its content, e.g.
+/// `{"t": "Move", "c": {"x": 1}}`, whatever the representation of the enum.
+///
+/// This is a dynamic alternative to `#[serde(tag = "t", content = "c")]`,
+/// where the keys are chosen when serializing rather than on the type, so that
+/// heterogeneous enums share a uniform wire format. Unit variants have no
+/// content field, newtype variants have their value as content, tuple variants
+/// a sequence of their fields, and struct variants a map of their fields. The
+/// same applies to the variants at every depth of the value.
+///
+/// The content of a tuple or a struct variant is only complete once the
+/// variant ends, so each of its fields is buffered meanwhile. The buffer keeps
+/// the whole data model, bytes and 128-bit integers included, and the fields
+/// are buffered in the form the wrapped serializer expects, e.g. an `IpAddr`
+/// as bytes for a binary format. The other values are written as they come.
+///
+/// # Examples
+///
+/// ```
+/// # use dyn_serde::{Serialize, Serializer};
+/// # use dyn_serde::ser::AdjacentlyTagged;
+/// #
+/// #[derive(serde::Serialize)]
+/// enum Command {
+///     Stop,
+///     Move { x: i32 },
+/// }
+///
+/// let mut buf = Vec::new();
+/// let mut serializer = serde_json::Serializer::new(&mut buf);
+/// let mut serializer = <dyn Serializer>::new(&mut serializer);
+/// let mut serializer = AdjacentlyTagged::new(&mut serializer, "t", "c");
+///
+/// let commands = [Command::Move { x: 1 }, Command::Stop];
+/// (&commands as &dyn Serialize).dyn_serialize(&mut serializer).unwrap();
+/// assert_eq!(buf, br#"[{"t":"Move","c":{"x":1}},{"t":"Stop"}]"#);
+/// ```
+pub struct AdjacentlyTagged<'a> {
+    serializer: MiddlewareSerializer<'a, Adjacent>,
+}
+
+impl<'a> AdjacentlyTagged<'a> {
+    /// Wraps `serializer`, writing the name of every variant in the field
+    /// `tag` and its content in the field `content`.
+    #[must_use]
+    pub fn new(
+        serializer: &'a mut dyn Serializer,
+        tag: &'static str,
+        content: &'static str,
+    ) -> Self {
+        let adjacent = Adjacent {
+            tag,
+            content,
+            frames: RefCell::new(Vec::new()),
+        };
+        AdjacentlyTagged {
+            serializer: MiddlewareSerializer::new(serializer, adjacent),
+        }
+    }
+}
+
+impl Debug for AdjacentlyTagged<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let adjacent = self.serializer.middleware();
+        f.debug_struct("AdjacentlyTagged")
+            .field("tag", &adjacent.tag)
+            .field("content", &adjacent.content)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Serializer for AdjacentlyTagged<'_> {
+    middleware::forward_to_middleware_serializer!(serializer);
+}
+
+/// A compound being serialized.
+enum Frame {
+    /// A compound forwarded as is.
+    Forwarded,
+    /// The content of a tuple variant or a struct variant being buffered, and
+    /// whether the wrapped serializer is human-readable.
+    Content(Content, bool),
+}
+
+enum Content {
+    Tuple(Vec<Captured>),
+    Struct(&'static str, Vec<(&'static str, Captured)>),
+}
+
+impl serde::Serialize for Content {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        match self {
+            Content::Tuple(fields) => {
+                let mut tuple = serializer.serialize_tuple(fields.len())?;
+                for value in fields {
+                    tuple.serialize_element(value)?;
+                }
+                tuple.end()
+            }
+            Content::Struct(variant, fields) => {
+                let mut r#struct = serializer.serialize_struct(variant, fields.len())?;
+                for (key, value) in fields {
+                    r#struct.serialize_field(key, value)?;
+                }
+                r#struct.end()
+            }
+        }
+    }
+}
+
+struct Adjacent {
+    tag: &'static str,
+    content: &'static str,
+    frames: RefCell<Vec<Frame>>,
+}
+
+impl Adjacent {
+    /// Buffers `value` as a field of the content being buffered.
+    fn buffer(&self, key: &'static str, value: &dyn Serialize) -> SerializeResult<()> {
+        let human_readable = match self.frames.borrow().last() {
+            Some(Frame::Content(_, human_readable)) => *human_readable,
+            _ => unreachable!("the content is being buffered"),
+        };
+        let value = captured::capture(value, human_readable)?;
+        match self.frames.borrow_mut().last_mut() {
+            Some(Frame::Content(Content::Tuple(fields), _)) => fields.push(value),
+            Some(Frame::Content(Content::Struct(_, fields), _)) => fields.push((key, value)),
+            _ => unreachable!("the content is being buffered"),
+        }
+        Ok(())
+    }
+}
+
+impl Middleware for Adjacent {
+    fn serialize(&self, call: Call<'_>, serializer: &mut dyn Serializer) -> SerializeResult<()> {
+        let (name, variant, value) = match call {
+            Call::UnitVariant(name, _, variant) => (name, variant, None),
+            Call::NewtypeVariant(name, _, variant, value) => (name, variant, Some(value)),
+            call => return Ok(call.forward(serializer)?),
+        };
+
+        let len = if value.is_some() { 2 } else { 1 };
+        let mut compound = Begin::Struct(name, len).forward(serializer)?;
+        Element::StructField(self.tag, &variant).forward(&mut compound)?;
+        if let Some(value) = value {
+            Element::StructField(self.content, value).forward(&mut compound)?;
+        }
+        Ok(compound.end()?)
+    }
+
+    fn begin<'a>(
+        &self,
+        begin: Begin,
+        serializer: &'a mut dyn Serializer,
+    ) -> SerializeResult<Compound<'a>> {
+        let (name, variant, content) = match begin {
+            Begin::TupleVariant(name, _, variant, len) => {
+                (name, variant, Content::Tuple(Vec::with_capacity(len)))
+            }
+            Begin::StructVariant(name, _, variant, len) => (
+                name,
+                variant,
+                Content::Struct(variant, Vec::with_capacity(len)),
+            ),
+            begin => {
+                let compound = begin.forward(serializer)?;
+                self.frames.borrow_mut().push(Frame::Forwarded);
+                return Ok(compound);
+            }
+        };
+
+        let human_readable = serializer.dyn_is_human_readable();
+        let mut compound = Begin::Struct(name, 2).forward(serializer)?;
+        Element::StructField(self.tag, &variant).forward(&mut compound)?;
+        self.frames
+            .borrow_mut()
+            .push(Frame::Content(content, human_readable));
+        Ok(compound)
+    }
+
+    fn element(&self, element: Element<'_>, compound: &mut Compound<'_>) -> SerializeResult<()> {
+        let forwarded = matches!(self.frames.borrow().last(), Some(Frame::Forwarded));
+        if forwarded {
+            return Ok(element.forward(compound)?);
+        }
+        match element {
+            Element::Field(value) => self.buffer("", value),
+            Element::StructField(key, value) => self.buffer(key, value),
+            Element::SkipField(_) => Ok(()),
+            element => Ok(element.forward(compound)?),
+        }
+    }
+
+    fn end(&self, mut compound: Compound<'_>) -> SerializeResult<()> {
+        let frame = self.frames.borrow_mut().pop();
+        if let Some(Frame::Content(content, _)) = frame {
+            Element::StructField(self.content, &content).forward(&mut compound)?;
+        }
+        Ok(compound.end()?)
+    }
+}
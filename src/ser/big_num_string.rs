@@ -0,0 +1,120 @@
+//! Large integers serialized as strings, see [`BigNumAsString`].
+
+use core::fmt::{self, Debug, Display, Formatter};
+
+use crate::ser::middleware::{self, Call, Middleware};
+use crate::ser::{Serialize, SerializeResult, Serializer, collect_str};
+
+/// A dynamically serializable value whose integers serialize as decimal
+/// strings, e.g. `"9007199254740993"`, when they are too large to be read back
+/// exactly as a floating-point number.
+///
+/// JavaScript reads every JSON number as an `f64`, which represents the
+/// integers exactly up to `2^53 - 1` only, so web APIs commonly send larger
+/// identifiers as strings. `BigNumAsString` serializes every integer whose
+/// absolute value is greater than a threshold, at every depth of the value,
+/// through `serialize_str`, and leaves the other integers numeric. The
+/// threshold is [`BigNumAsString::MAX_SAFE_INTEGER`] by default, and can be
+/// set with [`BigNumAsString::with_threshold`].
+///
+/// Floating-point numbers are left unchanged.
+///
+/// # Examples
+///
+/// ```
+/// # use dyn_serde::ser::BigNumAsString;
+/// #
+/// let ids = [1u64, 1 << 53];
+/// let json = serde_json::to_string(&BigNumAsString::new(&ids)).unwrap();
+/// assert_eq!(json, r#"[1,"9007199254740992"]"#);
+/// ```
+#[derive(Clone, Copy)]
+pub struct BigNumAsString<'a> {
+    value: &'a dyn Serialize,
+    big_nums: BigNums,
+}
+
+impl<'a> BigNumAsString<'a> {
+    /// The largest integer JavaScript represents exactly, `2^53 - 1`.
+    pub const MAX_SAFE_INTEGER: u128 = (1 << 53) - 1;
+
+    /// Wraps `value`, serializing its integers greater than
+    /// [`BigNumAsString::MAX_SAFE_INTEGER`] in absolute value as strings.
+    #[must_use]
+    pub fn new(value: &'a dyn Serialize) -> Self {
+        BigNumAsString::with_threshold(value, BigNumAsString::MAX_SAFE_INTEGER)
+    }
+
+    /// Wraps `value`, serializing its integers greater than `threshold` in
+    /// absolute value as strings.
+    #[must_use]
+    pub fn with_threshold(value: &'a dyn Serialize, threshold: u128) -> Self {
+        BigNumAsString {
+            value,
+            big_nums: BigNums { threshold },
+        }
+    }
+
+    /// Returns the largest absolute value of the integers left numeric.
+    #[must_use]
+    pub fn threshold(&self) -> u128 {
+        self.big_nums.threshold
+    }
+}
+
+impl Debug for BigNumAsString<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("BigNumAsString")
+            .field("threshold", &self.big_nums.threshold)
+            .finish_non_exhaustive()
+    }
+}
+
+impl serde::Serialize for BigNumAsString<'_> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        let value = middleware::wrap(self.value, &self.big_nums);
+        serde::Serialize::serialize(&value as &dyn Serialize, serializer)
+    }
+}
+
+#[derive(Clone, Copy)]
+struct BigNums {
+    threshold: u128,
+}
+
+impl BigNums {
+    fn forward(
+        &self,
+        magnitude: u128,
+        v: impl Display,
+        call: Call<'_>,
+        serializer: &mut dyn Serializer,
+    ) -> SerializeResult<()> {
+        if magnitude > self.threshold {
+            Ok(collect_str(serializer, &v)?)
+        } else {
+            Ok(call.forward(serializer)?)
+        }
+    }
+}
+
+impl Middleware for BigNums {
+    fn serialize(&self, call: Call<'_>, serializer: &mut dyn Serializer) -> SerializeResult<()> {
+        match call {
+            Call::I8(v) => self.forward(v.unsigned_abs().into(), v, call, serializer),
+            Call::I16(v) => self.forward(v.unsigned_abs().into(), v, call, serializer),
+            Call::I32(v) => self.forward(v.unsigned_abs().into(), v, call, serializer),
+            Call::I64(v) => self.forward(v.unsigned_abs().into(), v, call, serializer),
+            Call::I128(v) => self.forward(v.unsigned_abs(), v, call, serializer),
+            Call::U8(v) => self.forward(v.into(), v, call, serializer),
+            Call::U16(v) => self.forward(v.into(), v, call, serializer),
+            Call::U32(v) => self.forward(v.into(), v, call, serializer),
+            Call::U64(v) => self.forward(v.into(), v, call, serializer),
+            Call::U128(v) => self.forward(v, v, call, serializer),
+            call => Ok(call.forward(serializer)?),
+        }
+    }
+}
@@ -0,0 +1,53 @@
+//! Boxing values as dynamically serializable trait objects, see [`boxed`] and
+//! [`IntoBoxedSerialize`].
+
+#[cfg(not(feature = "std"))]
+use alloc::boxed::Box;
+
+use crate::ser::Serialize;
+
+/// Boxes `value` as a `Box<dyn Serialize>`.
+///
+/// A `Box<T>` coerces into a `Box<dyn Serialize>` where the target type is
+/// spelled out, but not where it is inferred, e.g. in the first element of a
+/// `vec!` or the arms of a `match`, which then fail to compile with mismatched
+/// types. `boxed` names the target type once. The box borrows whatever `value`
+/// borrows, so that it is `'static` if `value` is.
+///
+/// # Examples
+///
+/// ```
+/// # use dyn_serde::Serialize;
+/// # use dyn_serde::ser::{IntoBoxedSerialize as _, boxed};
+/// #
+/// let values = vec![boxed(1), boxed("two"), boxed([3.0]), (4, 'u').into_boxed_serialize()];
+///
+/// let json = serde_json::to_string(&values).unwrap();
+/// assert_eq!(json, r#"[1,"two",[3.0],[4,"u"]]"#);
+/// ```
+#[must_use]
+pub fn boxed<'a, T>(value: T) -> Box<dyn Serialize + 'a>
+where
+    T: serde::Serialize + 'a,
+{
+    Box::new(value)
+}
+
+/// Converts a value into a `Box<dyn Serialize>`, see [`boxed`].
+///
+/// The trait is implemented by every type implementing [`serde::Serialize`],
+/// so that the conversion chains as a method, e.g.
+/// `value.into_boxed_serialize()`.
+pub trait IntoBoxedSerialize<'a> {
+    /// Boxes `self` as a `Box<dyn Serialize>`.
+    fn into_boxed_serialize(self) -> Box<dyn Serialize + 'a>;
+}
+
+impl<'a, T> IntoBoxedSerialize<'a> for T
+where
+    T: serde::Serialize + 'a,
+{
+    fn into_boxed_serialize(self) -> Box<dyn Serialize + 'a> {
+        boxed(self)
+    }
+}
@@ -0,0 +1,446 @@
+//! A [`Serializer`] wrapper transforming the whole value, see [`Buffered`].
+
+use core::fmt::{self, Debug, Formatter};
+use core::mem;
+
+#[cfg(not(feature = "std"))]
+use alloc::boxed::Box;
+
+use serde::ser::Error as _;
+use serde_json::Value;
+
+use crate::ser::Serializer;
+use crate::ser::middleware::{self, Middleware};
+use crate::ser::{InplaceSerializeError, InplaceSerializeResult, InplaceSerializer};
+use crate::ser::{Serialize, SerializeMap, SerializeSeq, SerializeStruct, SerializeStructVariant};
+use crate::ser::{SerializeError, SerializeTuple, SerializeTupleStruct, SerializeTupleVariant};
+
+/// A [`Serializer`] wrapper which buffers the entire value into a
+/// [`serde_json::Value`], transforms it, and then serializes the transformed
+/// value to the wrapped serializer.
+///
+/// This is an extension point for transformations which need to see the whole
+/// value before anything is written, e.g. computing a checksum field or
+/// reordering members. The transform is called once, when the top-level value
+/// is complete, and nothing reaches the wrapped serializer before.
+///
+/// As the value goes through the data model of JSON, bytes become sequences
+/// of numbers, variants become single-member objects, map keys become strings
+/// and NaN and infinite floats become `null`.
+///
+/// The values at every depth are told whether the format is human-readable as
+/// the wrapped serializer tells, not as JSON does, so that e.g. an `IpAddr`
+/// is buffered as an array of octets when the wrapped format is binary.
+///
+/// The serialization returns an error telling only that it has done
+/// unsuccessfully, while [`Buffered::error`] tells why it has.
+///
+/// # Examples
+///
+/// ```
+/// # use dyn_serde::{Serialize, Serializer};
+/// # use dyn_serde::ser::Buffered;
+/// #
+/// let mut output = Vec::new();
+/// {
+///     let mut serializer = serde_json::Serializer::new(&mut output);
+///     let mut serializer = <dyn Serializer>::new(&mut serializer);
+///     let mut serializer = Buffered::new(&mut serializer, |value| {
+///         value.as_array_mut().unwrap().reverse();
+///     });
+///     (&[1, 2, 3] as &dyn Serialize).dyn_serialize(&mut serializer).unwrap();
+/// }
+/// assert_eq!(output, b"[3,2,1]");
+/// ```
+pub struct Buffered<'a> {
+    serializer: &'a mut dyn Serializer,
+    transform: Option<Transform<'a>>,
+    buffer: InplaceSerializer<serde_json::value::Serializer>,
+    error: Option<SerializeError>,
+}
+
+type Transform<'a> = Box<dyn FnOnce(&mut Value) + 'a>;
+
+impl<'a> Buffered<'a> {
+    /// Wraps `serializer`, applying `transform` to the buffered value before
+    /// serializing it.
+    #[must_use]
+    pub fn new(
+        serializer: &'a mut dyn Serializer,
+        transform: impl FnOnce(&mut Value) + 'a,
+    ) -> Self {
+        Buffered {
+            serializer,
+            transform: Some(Box::new(transform)),
+            buffer: <dyn Serializer>::new(serde_json::value::Serializer),
+            error: None,
+        }
+    }
+
+    /// Returns the first error raised while serializing, if any.
+    #[must_use]
+    pub fn error(&self) -> Option<&SerializeError> {
+        self.error.as_ref()
+    }
+
+    /// Returns the middleware telling the values buffered whether the wrapped
+    /// serializer is human-readable.
+    fn human_readable(&self) -> HumanReadable {
+        HumanReadable(self.serializer.dyn_is_human_readable())
+    }
+
+    /// Checks the result of a call on the buffer, and serializes the buffered
+    /// value once it is complete.
+    fn then(&mut self, result: InplaceSerializeResult<()>) -> InplaceSerializeResult<()> {
+        if let Err(error) = result {
+            if let InplaceSerializer::Error(buffer_error, _) = &self.buffer {
+                self.error
+                    .get_or_insert_with(|| SerializeError::custom(buffer_error));
+            }
+            return Err(error);
+        }
+
+        let mut value = match mem::take(&mut self.buffer) {
+            InplaceSerializer::Ok(value, _) => value,
+            buffer => {
+                self.buffer = buffer;
+                return Ok(());
+            }
+        };
+        if let Some(transform) = self.transform.take() {
+            (transform)(&mut value);
+        }
+        value.dyn_serialize(self.serializer).map_err(|error| {
+            self.error.get_or_insert(error);
+            InplaceSerializeError::Error
+        })
+    }
+}
+
+impl Debug for Buffered<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Buffered")
+            .field("buffer", &self.buffer.state())
+            .field("error", &self.error)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Serializer for Buffered<'_> {
+    fn dyn_serialize_bool(&mut self, v: bool) -> InplaceSerializeResult<()> {
+        let result = self.buffer.dyn_serialize_bool(v);
+        self.then(result)
+    }
+
+    fn dyn_serialize_i64(&mut self, v: i64) -> InplaceSerializeResult<()> {
+        let result = self.buffer.dyn_serialize_i64(v);
+        self.then(result)
+    }
+
+    fn dyn_serialize_i128(&mut self, v: i128) -> InplaceSerializeResult<()> {
+        let result = self.buffer.dyn_serialize_i128(v);
+        self.then(result)
+    }
+
+    fn dyn_serialize_u64(&mut self, v: u64) -> InplaceSerializeResult<()> {
+        let result = self.buffer.dyn_serialize_u64(v);
+        self.then(result)
+    }
+
+    fn dyn_serialize_u128(&mut self, v: u128) -> InplaceSerializeResult<()> {
+        let result = self.buffer.dyn_serialize_u128(v);
+        self.then(result)
+    }
+
+    fn dyn_serialize_f64(&mut self, v: f64) -> InplaceSerializeResult<()> {
+        let result = self.buffer.dyn_serialize_f64(v);
+        self.then(result)
+    }
+
+    fn dyn_serialize_str(&mut self, v: &str) -> InplaceSerializeResult<()> {
+        let result = self.buffer.dyn_serialize_str(v);
+        self.then(result)
+    }
+
+    fn dyn_serialize_bytes(&mut self, v: &[u8]) -> InplaceSerializeResult<()> {
+        let result = self.buffer.dyn_serialize_bytes(v);
+        self.then(result)
+    }
+
+    fn dyn_serialize_none(&mut self) -> InplaceSerializeResult<()> {
+        let result = self.buffer.dyn_serialize_none();
+        self.then(result)
+    }
+
+    fn dyn_serialize_some(&mut self, value: &dyn Serialize) -> InplaceSerializeResult<()> {
+        let human_readable = self.human_readable();
+        let value = middleware::wrap(value, &human_readable);
+        let result = self.buffer.dyn_serialize_some(&value);
+        self.then(result)
+    }
+
+    fn dyn_serialize_unit(&mut self) -> InplaceSerializeResult<()> {
+        let result = self.buffer.dyn_serialize_unit();
+        self.then(result)
+    }
+
+    fn dyn_serialize_unit_variant(
+        &mut self,
+        name: &'static str,
+        variant_index: u32,
+        variant: &'static str,
+    ) -> InplaceSerializeResult<()> {
+        let result = self
+            .buffer
+            .dyn_serialize_unit_variant(name, variant_index, variant);
+        self.then(result)
+    }
+
+    fn dyn_serialize_newtype_struct(
+        &mut self,
+        name: &'static str,
+        value: &dyn Serialize,
+    ) -> InplaceSerializeResult<()> {
+        let human_readable = self.human_readable();
+        let value = middleware::wrap(value, &human_readable);
+        let result = self.buffer.dyn_serialize_newtype_struct(name, &value);
+        self.then(result)
+    }
+
+    fn dyn_serialize_newtype_variant(
+        &mut self,
+        name: &'static str,
+        variant_index: u32,
+        variant: &'static str,
+        value: &dyn Serialize,
+    ) -> InplaceSerializeResult<()> {
+        let human_readable = self.human_readable();
+        let value = middleware::wrap(value, &human_readable);
+        let result =
+            self.buffer
+                .dyn_serialize_newtype_variant(name, variant_index, variant, &value);
+        self.then(result)
+    }
+
+    fn dyn_serialize_seq(
+        &mut self,
+        len: Option<usize>,
+    ) -> InplaceSerializeResult<&mut dyn SerializeSeq> {
+        let result = self.buffer.dyn_serialize_seq(len).map(|_| ());
+        self.then(result)?;
+        Ok(self)
+    }
+
+    fn dyn_serialize_tuple(
+        &mut self,
+        len: usize,
+    ) -> InplaceSerializeResult<&mut dyn SerializeTuple> {
+        let result = self.buffer.dyn_serialize_tuple(len).map(|_| ());
+        self.then(result)?;
+        Ok(self)
+    }
+
+    fn dyn_serialize_tuple_struct(
+        &mut self,
+        name: &'static str,
+        len: usize,
+    ) -> InplaceSerializeResult<&mut dyn SerializeTupleStruct> {
+        let result = self
+            .buffer
+            .dyn_serialize_tuple_struct(name, len)
+            .map(|_| ());
+        self.then(result)?;
+        Ok(self)
+    }
+
+    fn dyn_serialize_tuple_variant(
+        &mut self,
+        name: &'static str,
+        variant_index: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> InplaceSerializeResult<&mut dyn SerializeTupleVariant> {
+        let result = self
+            .buffer
+            .dyn_serialize_tuple_variant(name, variant_index, variant, len)
+            .map(|_| ());
+        self.then(result)?;
+        Ok(self)
+    }
+
+    fn dyn_serialize_map(
+        &mut self,
+        len: Option<usize>,
+    ) -> InplaceSerializeResult<&mut dyn SerializeMap> {
+        let result = self.buffer.dyn_serialize_map(len).map(|_| ());
+        self.then(result)?;
+        Ok(self)
+    }
+
+    fn dyn_serialize_struct(
+        &mut self,
+        name: &'static str,
+        len: usize,
+    ) -> InplaceSerializeResult<&mut dyn SerializeStruct> {
+        let result = self.buffer.dyn_serialize_struct(name, len).map(|_| ());
+        self.then(result)?;
+        Ok(self)
+    }
+
+    fn dyn_serialize_struct_variant(
+        &mut self,
+        name: &'static str,
+        variant_index: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> InplaceSerializeResult<&mut dyn SerializeStructVariant> {
+        let result = self
+            .buffer
+            .dyn_serialize_struct_variant(name, variant_index, variant, len)
+            .map(|_| ());
+        self.then(result)?;
+        Ok(self)
+    }
+
+    fn dyn_is_human_readable(&self) -> bool {
+        self.serializer.dyn_is_human_readable()
+    }
+
+    fn dyn_bytes_written(&self) -> Option<u64> {
+        self.serializer.dyn_bytes_written()
+    }
+}
+
+impl SerializeSeq for Buffered<'_> {
+    fn dyn_serialize_element(&mut self, value: &dyn Serialize) -> InplaceSerializeResult<()> {
+        let human_readable = self.human_readable();
+        let value = middleware::wrap(value, &human_readable);
+        let result = SerializeSeq::dyn_serialize_element(&mut self.buffer, &value);
+        self.then(result)
+    }
+
+    fn dyn_end(&mut self) -> InplaceSerializeResult<()> {
+        let result = SerializeSeq::dyn_end(&mut self.buffer);
+        self.then(result)
+    }
+}
+
+impl SerializeTuple for Buffered<'_> {
+    fn dyn_serialize_element(&mut self, value: &dyn Serialize) -> InplaceSerializeResult<()> {
+        let human_readable = self.human_readable();
+        let value = middleware::wrap(value, &human_readable);
+        let result = SerializeTuple::dyn_serialize_element(&mut self.buffer, &value);
+        self.then(result)
+    }
+
+    fn dyn_end(&mut self) -> InplaceSerializeResult<()> {
+        let result = SerializeTuple::dyn_end(&mut self.buffer);
+        self.then(result)
+    }
+}
+
+impl SerializeTupleStruct for Buffered<'_> {
+    fn dyn_serialize_field(&mut self, value: &dyn Serialize) -> InplaceSerializeResult<()> {
+        let human_readable = self.human_readable();
+        let value = middleware::wrap(value, &human_readable);
+        let result = SerializeTupleStruct::dyn_serialize_field(&mut self.buffer, &value);
+        self.then(result)
+    }
+
+    fn dyn_end(&mut self) -> InplaceSerializeResult<()> {
+        let result = SerializeTupleStruct::dyn_end(&mut self.buffer);
+        self.then(result)
+    }
+}
+
+impl SerializeTupleVariant for Buffered<'_> {
+    fn dyn_serialize_field(&mut self, value: &dyn Serialize) -> InplaceSerializeResult<()> {
+        let human_readable = self.human_readable();
+        let value = middleware::wrap(value, &human_readable);
+        let result = SerializeTupleVariant::dyn_serialize_field(&mut self.buffer, &value);
+        self.then(result)
+    }
+
+    fn dyn_end(&mut self) -> InplaceSerializeResult<()> {
+        let result = SerializeTupleVariant::dyn_end(&mut self.buffer);
+        self.then(result)
+    }
+}
+
+impl SerializeMap for Buffered<'_> {
+    fn dyn_serialize_key(&mut self, key: &dyn Serialize) -> InplaceSerializeResult<()> {
+        let human_readable = self.human_readable();
+        let key = middleware::wrap(key, &human_readable);
+        let result = self.buffer.dyn_serialize_key(&key);
+        self.then(result)
+    }
+
+    fn dyn_serialize_value(&mut self, value: &dyn Serialize) -> InplaceSerializeResult<()> {
+        let human_readable = self.human_readable();
+        let value = middleware::wrap(value, &human_readable);
+        let result = self.buffer.dyn_serialize_value(&value);
+        self.then(result)
+    }
+
+    fn dyn_end(&mut self) -> InplaceSerializeResult<()> {
+        let result = SerializeMap::dyn_end(&mut self.buffer);
+        self.then(result)
+    }
+}
+
+impl SerializeStruct for Buffered<'_> {
+    fn dyn_serialize_field(
+        &mut self,
+        key: &'static str,
+        value: &dyn Serialize,
+    ) -> InplaceSerializeResult<()> {
+        let human_readable = self.human_readable();
+        let value = middleware::wrap(value, &human_readable);
+        let result = SerializeStruct::dyn_serialize_field(&mut self.buffer, key, &value);
+        self.then(result)
+    }
+
+    fn dyn_skip_field(&mut self, key: &'static str) -> InplaceSerializeResult<()> {
+        let result = SerializeStruct::dyn_skip_field(&mut self.buffer, key);
+        self.then(result)
+    }
+
+    fn dyn_end(&mut self) -> InplaceSerializeResult<()> {
+        let result = SerializeStruct::dyn_end(&mut self.buffer);
+        self.then(result)
+    }
+}
+
+impl SerializeStructVariant for Buffered<'_> {
+    fn dyn_serialize_field(
+        &mut self,
+        key: &'static str,
+        value: &dyn Serialize,
+    ) -> InplaceSerializeResult<()> {
+        let human_readable = self.human_readable();
+        let value = middleware::wrap(value, &human_readable);
+        let result = SerializeStructVariant::dyn_serialize_field(&mut self.buffer, key, &value);
+        self.then(result)
+    }
+
+    fn dyn_skip_field(&mut self, key: &'static str) -> InplaceSerializeResult<()> {
+        let result = SerializeStructVariant::dyn_skip_field(&mut self.buffer, key);
+        self.then(result)
+    }
+
+    fn dyn_end(&mut self) -> InplaceSerializeResult<()> {
+        let result = SerializeStructVariant::dyn_end(&mut self.buffer);
+        self.then(result)
+    }
+}
+
+/// Answers whether the wrapped serializer is human-readable in place of the
+/// buffer.
+struct HumanReadable(bool);
+
+impl Middleware for HumanReadable {
+    fn human_readable(&self, human_readable: bool) -> bool {
+        let _ = human_readable;
+        self.0
+    }
+}
@@ -0,0 +1,809 @@
+//! Canonical JSON serialization, see [`canonicalize`] and
+//! [`canonical_hashes`].
+
+use core::mem;
+
+#[cfg(not(feature = "std"))]
+use alloc::collections::BTreeMap;
+#[cfg(not(feature = "std"))]
+use alloc::format;
+#[cfg(not(feature = "std"))]
+use alloc::string::{String, ToString};
+#[cfg(not(feature = "std"))]
+use alloc::vec::Vec;
+
+#[cfg(feature = "std")]
+use std::collections::BTreeMap;
+
+use serde::ser::Error as _;
+
+use crate::ser::{InplaceSerializeError, InplaceSerializeResult, SerializeError, SerializeResult};
+use crate::ser::{Serialize, SerializeMap, SerializeSeq, SerializeStruct, SerializeStructVariant};
+use crate::ser::{SerializeTuple, SerializeTupleStruct, SerializeTupleVariant, Serializer};
+
+/// Serializes `value` into canonical JSON as described by [RFC 8785].
+///
+/// The output contains no insignificant whitespace, the members of every
+/// object are sorted by the UTF-16 code units of their names, strings only
+/// escape what JSON requires, and floating-point numbers are formatted like
+/// ECMAScript's `Number.prototype.toString`. Values that serialize to the same
+/// data model therefore produce byte-identical output, which makes it suitable
+/// for hashing and signing. Integers are written exactly rather than rounded
+/// to `f64`.
+///
+/// # Errors
+///
+/// Returns an error if `value` fails to serialize itself, contains a NaN or an
+/// infinite float, contains a map key that is neither a string, a number, a
+/// boolean nor a unit variant, or contains two members with the same name in
+/// one object.
+///
+/// # Examples
+///
+/// ```
+/// use std::collections::BTreeMap;
+///
+/// let value = BTreeMap::from([("b", 1.0), ("a", 1e-7)]);
+/// let output = dyn_serde::ser::canonicalize(&value).unwrap();
+/// assert_eq!(output, br#"{"a":1e-7,"b":1}"#);
+/// ```
+///
+/// [RFC 8785]: https://www.rfc-editor.org/rfc/rfc8785
+pub fn canonicalize(value: &dyn Serialize) -> SerializeResult<Vec<u8>> {
+    let mut serializer = CanonicalSerializer {
+        output: Vec::new(),
+        frames: Vec::new(),
+        members: None,
+        error: None,
+    };
+
+    match value.dyn_serialize(&mut serializer) {
+        Ok(()) => Ok(serializer.output),
+        Err(error) => Err(serializer.error.unwrap_or(error)),
+    }
+}
+
+/// The hashes of a value and of every member of its top-level object, as
+/// computed by [`canonical_hashes`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CanonicalHashes {
+    /// The hash of the canonical JSON of the whole value.
+    pub value: u64,
+    /// The hash of the canonical JSON of every member of the top-level object,
+    /// by name. Empty if the value is not an object.
+    pub members: BTreeMap<String, u64>,
+}
+
+/// Hashes the canonical JSON of `value`, as written by [`canonicalize`], and
+/// of every member of its top-level object.
+///
+/// Comparing the hashes of two versions of a large value tells which of its
+/// fields, or entries if it is a map, changed without keeping the previous
+/// version around. Since canonical JSON does not depend on the order of the
+/// members, neither do the hashes. The hash is the 64-bit FNV-1a hash of the
+/// canonical JSON, which is stable across platforms and releases but not
+/// cryptographic: use it to detect changes, not to authenticate values.
+///
+/// The members of a struct variant are those of its fields, while the members
+/// of nested objects are only part of the hash of the member containing them.
+///
+/// # Errors
+///
+/// Returns an error if `value` cannot be written as canonical JSON, as
+/// [`canonicalize`] does.
+///
+/// # Examples
+///
+/// ```
+/// #[derive(serde::Serialize)]
+/// struct Page {
+///     title: &'static str,
+///     body: &'static str,
+/// }
+///
+/// let before = Page { title: "Ferris", body: "a crab" };
+/// let after = Page { title: "Ferris", body: "the crab" };
+/// let before = dyn_serde::ser::canonical_hashes(&before).unwrap();
+/// let after = dyn_serde::ser::canonical_hashes(&after).unwrap();
+///
+/// assert_ne!(before.value, after.value);
+/// assert_eq!(before.members["title"], after.members["title"]);
+/// assert_ne!(before.members["body"], after.members["body"]);
+/// ```
+pub fn canonical_hashes(value: &dyn Serialize) -> SerializeResult<CanonicalHashes> {
+    let mut serializer = CanonicalSerializer {
+        output: Vec::new(),
+        frames: Vec::new(),
+        members: Some(BTreeMap::new()),
+        error: None,
+    };
+
+    match value.dyn_serialize(&mut serializer) {
+        Ok(()) => Ok(CanonicalHashes {
+            value: fnv1a(&serializer.output),
+            members: serializer.members.unwrap_or_default(),
+        }),
+        Err(error) => Err(serializer.error.unwrap_or(error)),
+    }
+}
+
+/// The serializer behind [`canonicalize`].
+///
+/// Every compound value is serialized by the serializer itself, using a stack
+/// of frames to track the innermost array or object.
+struct CanonicalSerializer {
+    output: Vec<u8>,
+    frames: Vec<Frame>,
+    /// The hashes of the members of the top-level object, if requested.
+    members: Option<BTreeMap<String, u64>>,
+    error: Option<SerializeError>,
+}
+
+enum Frame {
+    /// An array, wrapped in a single-member object if it is a variant.
+    Array { empty: bool, variant: bool },
+    /// An object, whose members are buffered until the end to be sorted.
+    Object {
+        members: Vec<(String, Vec<u8>)>,
+        key: Option<String>,
+        variant: bool,
+    },
+}
+
+impl CanonicalSerializer {
+    #[cold]
+    fn fail(&mut self, error: SerializeError) -> InplaceSerializeError {
+        self.error.get_or_insert(error);
+        InplaceSerializeError::Error
+    }
+
+    fn serialize_value(&mut self, value: &dyn Serialize) -> InplaceSerializeResult<()> {
+        value.dyn_serialize(self).map_err(|error| self.fail(error))
+    }
+
+    fn begin_variant(&mut self, variant: &'static str) {
+        self.output.push(b'{');
+        write_str(&mut self.output, variant);
+        self.output.push(b':');
+    }
+
+    fn begin_array(&mut self, variant: bool) -> InplaceSerializeResult<&mut Self> {
+        self.output.push(b'[');
+        self.frames.push(Frame::Array {
+            empty: true,
+            variant,
+        });
+        Ok(self)
+    }
+
+    fn begin_object(&mut self, variant: bool) -> InplaceSerializeResult<&mut Self> {
+        self.frames.push(Frame::Object {
+            members: Vec::new(),
+            key: None,
+            variant,
+        });
+        Ok(self)
+    }
+
+    fn element(
+        &mut self,
+        value: &dyn Serialize,
+        error: InplaceSerializeError,
+    ) -> InplaceSerializeResult<()> {
+        let Some(Frame::Array { empty, .. }) = self.frames.last_mut() else {
+            return Err(error);
+        };
+
+        if !mem::replace(empty, false) {
+            self.output.push(b',');
+        }
+        self.serialize_value(value)
+    }
+
+    fn end_array(&mut self, error: InplaceSerializeError) -> InplaceSerializeResult<()> {
+        let Some(&Frame::Array { variant, .. }) = self.frames.last() else {
+            return Err(error);
+        };
+
+        self.frames.pop();
+        self.output.push(b']');
+        if variant {
+            self.output.push(b'}');
+        }
+        Ok(())
+    }
+
+    fn key(&mut self, key: &dyn Serialize) -> InplaceSerializeResult<()> {
+        if !matches!(self.frames.last(), Some(Frame::Object { .. })) {
+            return Err(InplaceSerializeError::NotSerializeMap);
+        }
+
+        let key = serialize_key(key).map_err(|error| self.fail(error))?;
+
+        if let Some(Frame::Object { key: pending, .. }) = self.frames.last_mut() {
+            *pending = Some(key);
+        }
+        Ok(())
+    }
+
+    fn value(
+        &mut self,
+        key: Option<String>,
+        value: &dyn Serialize,
+        error: InplaceSerializeError,
+    ) -> InplaceSerializeResult<()> {
+        let Some(Frame::Object { key: pending, .. }) = self.frames.last_mut() else {
+            return Err(error);
+        };
+        let Some(key) = key.or_else(|| pending.take()) else {
+            let error = SerializeError::custom("serialize_value called before serialize_key");
+            return Err(self.fail(error));
+        };
+
+        // serializes the value into its own buffer, so that the members can be
+        // reordered at the end of the object.
+        let output = mem::take(&mut self.output);
+        let result = self.serialize_value(value);
+        let member = mem::replace(&mut self.output, output);
+        result?;
+
+        if let Some(Frame::Object { members, .. }) = self.frames.last_mut() {
+            members.push((key, member));
+        }
+        Ok(())
+    }
+
+    fn end_object(&mut self, error: InplaceSerializeError) -> InplaceSerializeResult<()> {
+        let (mut members, variant) = match self.frames.pop() {
+            Some(Frame::Object {
+                members, variant, ..
+            }) => (members, variant),
+            frame => {
+                self.frames.extend(frame);
+                return Err(error);
+            }
+        };
+
+        members.sort_by(|(a, _), (b, _)| a.encode_utf16().cmp(b.encode_utf16()));
+        if let Some(pair) = members.windows(2).find(|pair| pair[0].0 == pair[1].0) {
+            let error = SerializeError::custom(format!("duplicate key `{}`", pair[0].0));
+            return Err(self.fail(error));
+        }
+        if self.frames.is_empty()
+            && let Some(hashes) = &mut self.members
+        {
+            for (key, member) in &members {
+                hashes.insert(key.clone(), fnv1a(member));
+            }
+        }
+
+        self.output.push(b'{');
+        for (index, (key, member)) in members.iter().enumerate() {
+            if index != 0 {
+                self.output.push(b',');
+            }
+            write_str(&mut self.output, key);
+            self.output.push(b':');
+            self.output.extend_from_slice(member);
+        }
+        self.output.push(b'}');
+        if variant {
+            self.output.push(b'}');
+        }
+        Ok(())
+    }
+}
+
+impl Serializer for CanonicalSerializer {
+    fn dyn_serialize_bool(&mut self, v: bool) -> InplaceSerializeResult<()> {
+        self.output
+            .extend_from_slice(if v { b"true" } else { b"false" });
+        Ok(())
+    }
+
+    fn dyn_serialize_i64(&mut self, v: i64) -> InplaceSerializeResult<()> {
+        self.output.extend_from_slice(v.to_string().as_bytes());
+        Ok(())
+    }
+
+    fn dyn_serialize_i128(&mut self, v: i128) -> InplaceSerializeResult<()> {
+        self.output.extend_from_slice(v.to_string().as_bytes());
+        Ok(())
+    }
+
+    fn dyn_serialize_u64(&mut self, v: u64) -> InplaceSerializeResult<()> {
+        self.output.extend_from_slice(v.to_string().as_bytes());
+        Ok(())
+    }
+
+    fn dyn_serialize_u128(&mut self, v: u128) -> InplaceSerializeResult<()> {
+        self.output.extend_from_slice(v.to_string().as_bytes());
+        Ok(())
+    }
+
+    fn dyn_serialize_f64(&mut self, v: f64) -> InplaceSerializeResult<()> {
+        match format_f64(v) {
+            Some(v) => {
+                self.output.extend_from_slice(v.as_bytes());
+                Ok(())
+            }
+            None => Err(self.fail(SerializeError::custom(
+                "canonical JSON cannot represent NaN or infinity",
+            ))),
+        }
+    }
+
+    fn dyn_serialize_str(&mut self, v: &str) -> InplaceSerializeResult<()> {
+        write_str(&mut self.output, v);
+        Ok(())
+    }
+
+    fn dyn_serialize_bytes(&mut self, v: &[u8]) -> InplaceSerializeResult<()> {
+        self.output.push(b'[');
+        for (index, byte) in v.iter().enumerate() {
+            if index != 0 {
+                self.output.push(b',');
+            }
+            self.output.extend_from_slice(byte.to_string().as_bytes());
+        }
+        self.output.push(b']');
+        Ok(())
+    }
+
+    fn dyn_serialize_none(&mut self) -> InplaceSerializeResult<()> {
+        self.dyn_serialize_unit()
+    }
+
+    fn dyn_serialize_some(&mut self, value: &dyn Serialize) -> InplaceSerializeResult<()> {
+        self.serialize_value(value)
+    }
+
+    fn dyn_serialize_unit(&mut self) -> InplaceSerializeResult<()> {
+        self.output.extend_from_slice(b"null");
+        Ok(())
+    }
+
+    fn dyn_serialize_unit_variant(
+        &mut self,
+        name: &'static str,
+        variant_index: u32,
+        variant: &'static str,
+    ) -> InplaceSerializeResult<()> {
+        let _ = (name, variant_index);
+        self.dyn_serialize_str(variant)
+    }
+
+    fn dyn_serialize_newtype_struct(
+        &mut self,
+        name: &'static str,
+        value: &dyn Serialize,
+    ) -> InplaceSerializeResult<()> {
+        let _ = name;
+        self.serialize_value(value)
+    }
+
+    fn dyn_serialize_newtype_variant(
+        &mut self,
+        name: &'static str,
+        variant_index: u32,
+        variant: &'static str,
+        value: &dyn Serialize,
+    ) -> InplaceSerializeResult<()> {
+        let _ = (name, variant_index);
+        self.begin_variant(variant);
+        self.serialize_value(value)?;
+        self.output.push(b'}');
+        Ok(())
+    }
+
+    fn dyn_serialize_seq(
+        &mut self,
+        len: Option<usize>,
+    ) -> InplaceSerializeResult<&mut dyn SerializeSeq> {
+        let _ = len;
+        Ok(self.begin_array(false)?)
+    }
+
+    fn dyn_serialize_tuple(
+        &mut self,
+        len: usize,
+    ) -> InplaceSerializeResult<&mut dyn SerializeTuple> {
+        let _ = len;
+        Ok(self.begin_array(false)?)
+    }
+
+    fn dyn_serialize_tuple_struct(
+        &mut self,
+        name: &'static str,
+        len: usize,
+    ) -> InplaceSerializeResult<&mut dyn SerializeTupleStruct> {
+        let _ = (name, len);
+        Ok(self.begin_array(false)?)
+    }
+
+    fn dyn_serialize_tuple_variant(
+        &mut self,
+        name: &'static str,
+        variant_index: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> InplaceSerializeResult<&mut dyn SerializeTupleVariant> {
+        let _ = (name, variant_index, len);
+        self.begin_variant(variant);
+        Ok(self.begin_array(true)?)
+    }
+
+    fn dyn_serialize_map(
+        &mut self,
+        len: Option<usize>,
+    ) -> InplaceSerializeResult<&mut dyn SerializeMap> {
+        let _ = len;
+        Ok(self.begin_object(false)?)
+    }
+
+    fn dyn_serialize_struct(
+        &mut self,
+        name: &'static str,
+        len: usize,
+    ) -> InplaceSerializeResult<&mut dyn SerializeStruct> {
+        let _ = (name, len);
+        Ok(self.begin_object(false)?)
+    }
+
+    fn dyn_serialize_struct_variant(
+        &mut self,
+        name: &'static str,
+        variant_index: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> InplaceSerializeResult<&mut dyn SerializeStructVariant> {
+        let _ = (name, variant_index, len);
+        self.begin_variant(variant);
+        Ok(self.begin_object(true)?)
+    }
+}
+
+impl SerializeSeq for CanonicalSerializer {
+    fn dyn_serialize_element(&mut self, value: &dyn Serialize) -> InplaceSerializeResult<()> {
+        self.element(value, InplaceSerializeError::NotSerializeSeq)
+    }
+
+    fn dyn_end(&mut self) -> InplaceSerializeResult<()> {
+        self.end_array(InplaceSerializeError::NotSerializeSeq)
+    }
+}
+
+impl SerializeTuple for CanonicalSerializer {
+    fn dyn_serialize_element(&mut self, value: &dyn Serialize) -> InplaceSerializeResult<()> {
+        self.element(value, InplaceSerializeError::NotSerializeTuple)
+    }
+
+    fn dyn_end(&mut self) -> InplaceSerializeResult<()> {
+        self.end_array(InplaceSerializeError::NotSerializeTuple)
+    }
+}
+
+impl SerializeTupleStruct for CanonicalSerializer {
+    fn dyn_serialize_field(&mut self, value: &dyn Serialize) -> InplaceSerializeResult<()> {
+        self.element(value, InplaceSerializeError::NotSerializeTupleStruct)
+    }
+
+    fn dyn_end(&mut self) -> InplaceSerializeResult<()> {
+        self.end_array(InplaceSerializeError::NotSerializeTupleStruct)
+    }
+}
+
+impl SerializeTupleVariant for CanonicalSerializer {
+    fn dyn_serialize_field(&mut self, value: &dyn Serialize) -> InplaceSerializeResult<()> {
+        self.element(value, InplaceSerializeError::NotSerializeTupleVariant)
+    }
+
+    fn dyn_end(&mut self) -> InplaceSerializeResult<()> {
+        self.end_array(InplaceSerializeError::NotSerializeTupleVariant)
+    }
+}
+
+impl SerializeMap for CanonicalSerializer {
+    fn dyn_serialize_key(&mut self, key: &dyn Serialize) -> InplaceSerializeResult<()> {
+        self.key(key)
+    }
+
+    fn dyn_serialize_value(&mut self, value: &dyn Serialize) -> InplaceSerializeResult<()> {
+        self.value(None, value, InplaceSerializeError::NotSerializeMap)
+    }
+
+    fn dyn_end(&mut self) -> InplaceSerializeResult<()> {
+        self.end_object(InplaceSerializeError::NotSerializeMap)
+    }
+}
+
+impl SerializeStruct for CanonicalSerializer {
+    fn dyn_serialize_field(
+        &mut self,
+        key: &'static str,
+        value: &dyn Serialize,
+    ) -> InplaceSerializeResult<()> {
+        let key = Some(String::from(key));
+        self.value(key, value, InplaceSerializeError::NotSerializeStruct)
+    }
+
+    fn dyn_end(&mut self) -> InplaceSerializeResult<()> {
+        self.end_object(InplaceSerializeError::NotSerializeStruct)
+    }
+}
+
+impl SerializeStructVariant for CanonicalSerializer {
+    fn dyn_serialize_field(
+        &mut self,
+        key: &'static str,
+        value: &dyn Serialize,
+    ) -> InplaceSerializeResult<()> {
+        let key = Some(String::from(key));
+        self.value(key, value, InplaceSerializeError::NotSerializeStructVariant)
+    }
+
+    fn dyn_end(&mut self) -> InplaceSerializeResult<()> {
+        self.end_object(InplaceSerializeError::NotSerializeStructVariant)
+    }
+}
+
+/// Serializes `key` into a string with a [`KeySerializer`].
+pub(crate) fn serialize_key(key: &dyn Serialize) -> SerializeResult<String> {
+    let mut serializer = KeySerializer {
+        key: None,
+        error: None,
+    };
+    let result = key.dyn_serialize(&mut serializer);
+    match (result, serializer.key) {
+        (Ok(()), Some(key)) => Ok(key),
+        (Ok(()), None) => Err(key_must_be_a_string()),
+        (Err(error), _) => Err(serializer.error.unwrap_or(error)),
+    }
+}
+
+/// Serializes a map key into the name of an object member.
+///
+/// Like `serde_json`, scalar keys are converted into strings, while compound
+/// keys are rejected.
+struct KeySerializer {
+    key: Option<String>,
+    error: Option<SerializeError>,
+}
+
+impl KeySerializer {
+    fn set(&mut self, key: String) -> InplaceSerializeResult<()> {
+        self.key = Some(key);
+        Ok(())
+    }
+
+    #[cold]
+    fn reject(&mut self) -> InplaceSerializeError {
+        self.error.get_or_insert_with(key_must_be_a_string);
+        InplaceSerializeError::Error
+    }
+}
+
+impl Serializer for KeySerializer {
+    fn dyn_serialize_bool(&mut self, v: bool) -> InplaceSerializeResult<()> {
+        self.set(v.to_string())
+    }
+
+    fn dyn_serialize_i64(&mut self, v: i64) -> InplaceSerializeResult<()> {
+        self.set(v.to_string())
+    }
+
+    fn dyn_serialize_i128(&mut self, v: i128) -> InplaceSerializeResult<()> {
+        self.set(v.to_string())
+    }
+
+    fn dyn_serialize_u64(&mut self, v: u64) -> InplaceSerializeResult<()> {
+        self.set(v.to_string())
+    }
+
+    fn dyn_serialize_u128(&mut self, v: u128) -> InplaceSerializeResult<()> {
+        self.set(v.to_string())
+    }
+
+    fn dyn_serialize_f64(&mut self, v: f64) -> InplaceSerializeResult<()> {
+        match format_f64(v) {
+            Some(v) => self.set(v),
+            None => Err(self.reject()),
+        }
+    }
+
+    fn dyn_serialize_str(&mut self, v: &str) -> InplaceSerializeResult<()> {
+        self.set(String::from(v))
+    }
+
+    fn dyn_serialize_bytes(&mut self, v: &[u8]) -> InplaceSerializeResult<()> {
+        let _ = v;
+        Err(self.reject())
+    }
+
+    fn dyn_serialize_none(&mut self) -> InplaceSerializeResult<()> {
+        Err(self.reject())
+    }
+
+    fn dyn_serialize_some(&mut self, value: &dyn Serialize) -> InplaceSerializeResult<()> {
+        let _ = value;
+        Err(self.reject())
+    }
+
+    fn dyn_serialize_unit(&mut self) -> InplaceSerializeResult<()> {
+        Err(self.reject())
+    }
+
+    fn dyn_serialize_unit_variant(
+        &mut self,
+        name: &'static str,
+        variant_index: u32,
+        variant: &'static str,
+    ) -> InplaceSerializeResult<()> {
+        let _ = (name, variant_index);
+        self.set(String::from(variant))
+    }
+
+    fn dyn_serialize_newtype_struct(
+        &mut self,
+        name: &'static str,
+        value: &dyn Serialize,
+    ) -> InplaceSerializeResult<()> {
+        let _ = name;
+        value.dyn_serialize(self).map_err(|error| {
+            self.error.get_or_insert(error);
+            InplaceSerializeError::Error
+        })
+    }
+
+    fn dyn_serialize_newtype_variant(
+        &mut self,
+        name: &'static str,
+        variant_index: u32,
+        variant: &'static str,
+        value: &dyn Serialize,
+    ) -> InplaceSerializeResult<()> {
+        let _ = (name, variant_index, variant, value);
+        Err(self.reject())
+    }
+
+    fn dyn_serialize_seq(
+        &mut self,
+        len: Option<usize>,
+    ) -> InplaceSerializeResult<&mut dyn SerializeSeq> {
+        let _ = len;
+        Err(self.reject())
+    }
+
+    fn dyn_serialize_tuple(
+        &mut self,
+        len: usize,
+    ) -> InplaceSerializeResult<&mut dyn SerializeTuple> {
+        let _ = len;
+        Err(self.reject())
+    }
+
+    fn dyn_serialize_tuple_struct(
+        &mut self,
+        name: &'static str,
+        len: usize,
+    ) -> InplaceSerializeResult<&mut dyn SerializeTupleStruct> {
+        let _ = (name, len);
+        Err(self.reject())
+    }
+
+    fn dyn_serialize_tuple_variant(
+        &mut self,
+        name: &'static str,
+        variant_index: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> InplaceSerializeResult<&mut dyn SerializeTupleVariant> {
+        let _ = (name, variant_index, variant, len);
+        Err(self.reject())
+    }
+
+    fn dyn_serialize_map(
+        &mut self,
+        len: Option<usize>,
+    ) -> InplaceSerializeResult<&mut dyn SerializeMap> {
+        let _ = len;
+        Err(self.reject())
+    }
+
+    fn dyn_serialize_struct(
+        &mut self,
+        name: &'static str,
+        len: usize,
+    ) -> InplaceSerializeResult<&mut dyn SerializeStruct> {
+        let _ = (name, len);
+        Err(self.reject())
+    }
+
+    fn dyn_serialize_struct_variant(
+        &mut self,
+        name: &'static str,
+        variant_index: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> InplaceSerializeResult<&mut dyn SerializeStructVariant> {
+        let _ = (name, variant_index, variant, len);
+        Err(self.reject())
+    }
+}
+
+/// Returns the 64-bit FNV-1a hash of `bytes`.
+fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf2_9ce4_8422_2325, |hash, &byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(0x0100_0000_01b3)
+    })
+}
+
+#[cold]
+fn key_must_be_a_string() -> SerializeError {
+    SerializeError::custom("key must be a string")
+}
+
+/// Writes `v` as a JSON string, escaping only `"`, `\` and control characters.
+fn write_str(output: &mut Vec<u8>, v: &str) {
+    const HEX: &[u8; 16] = b"0123456789abcdef";
+
+    output.push(b'"');
+    for &byte in v.as_bytes() {
+        match byte {
+            b'"' => output.extend_from_slice(b"\\\""),
+            b'\\' => output.extend_from_slice(b"\\\\"),
+            b'\x08' => output.extend_from_slice(b"\\b"),
+            b'\x0C' => output.extend_from_slice(b"\\f"),
+            b'\n' => output.extend_from_slice(b"\\n"),
+            b'\r' => output.extend_from_slice(b"\\r"),
+            b'\t' => output.extend_from_slice(b"\\t"),
+            0x00..=0x1F => output.extend_from_slice(&[
+                b'\\',
+                b'u',
+                b'0',
+                b'0',
+                HEX[usize::from(byte >> 4)],
+                HEX[usize::from(byte & 0xF)],
+            ]),
+            _ => output.push(byte),
+        }
+    }
+    output.push(b'"');
+}
+
+/// Formats `v` like ECMAScript's `Number.prototype.toString`, or returns
+/// `None` if `v` is not finite.
+fn format_f64(v: f64) -> Option<String> {
+    if !v.is_finite() {
+        return None;
+    }
+    if v == 0.0 {
+        // both `0.0` and `-0.0` are written as `0`.
+        return Some(String::from("0"));
+    }
+
+    // `{:e}` yields the shortest digits that round-trip, e.g. `-1.25e-7`.
+    let repr = format!("{v:e}");
+    let (mantissa, exponent) = repr.split_once('e')?;
+    let (sign, mantissa) = match mantissa.strip_prefix('-') {
+        Some(mantissa) => ("-", mantissa),
+        None => ("", mantissa),
+    };
+    let digits = mantissa.replace('.', "");
+    let k = digits.len() as i32;
+    let n = exponent.parse::<i32>().ok()? + 1;
+
+    Some(if k <= n && n <= 21 {
+        format!("{sign}{digits}{}", "0".repeat((n - k) as usize))
+    } else if 0 < n && n <= 21 {
+        let (integral, fractional) = digits.split_at(n as usize);
+        format!("{sign}{integral}.{fractional}")
+    } else if -6 < n && n <= 0 {
+        format!("{sign}0.{}{digits}", "0".repeat(-n as usize))
+    } else {
+        let (first, rest) = digits.split_at(1);
+        let dot = if rest.is_empty() { "" } else { "." };
+        let exponent_sign = if n > 0 { "+" } else { "-" };
+        format!("{sign}{first}{dot}{rest}e{exponent_sign}{}", (n - 1).abs())
+    })
+}
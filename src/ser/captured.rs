@@ -0,0 +1,574 @@
+//! A value captured with the whole serde data model, see [`Captured`].
+
+#[cfg(not(feature = "std"))]
+use alloc::boxed::Box;
+#[cfg(not(feature = "std"))]
+use alloc::string::{String, ToString};
+#[cfg(not(feature = "std"))]
+use alloc::vec::Vec;
+
+use serde::ser::Error as _;
+use serde::ser::{SerializeMap as _, SerializeSeq as _, SerializeStruct as _};
+use serde::ser::{SerializeStructVariant as _, SerializeTuple as _};
+use serde::ser::{SerializeTupleStruct as _, SerializeTupleVariant as _};
+
+use crate::ser::{Serialize, SerializeError};
+
+/// A value captured as it was serialized, to be serialized again later.
+///
+/// Unlike a `serde_json::Value`, it keeps every call of the data model as is:
+/// bytes, 128-bit integers, non-string keys, the names of structs and
+/// variants, the declared lengths and the skipped fields. The value is
+/// captured by a serializer answering the `human_readable` given to
+/// [`capture`] at every depth, so that it is captured in the form the
+/// serializer it is written to later expects.
+pub(crate) enum Captured {
+    Bool(bool),
+    I8(i8),
+    I16(i16),
+    I32(i32),
+    I64(i64),
+    I128(i128),
+    U8(u8),
+    U16(u16),
+    U32(u32),
+    U64(u64),
+    U128(u128),
+    F32(f32),
+    F64(f64),
+    Char(char),
+    Str(String),
+    Bytes(Vec<u8>),
+    None,
+    Some(Box<Captured>),
+    Unit,
+    UnitStruct(&'static str),
+    UnitVariant(&'static str, u32, &'static str),
+    NewtypeStruct(&'static str, Box<Captured>),
+    NewtypeVariant(&'static str, u32, &'static str, Box<Captured>),
+    Seq(Option<usize>, Vec<Captured>),
+    Tuple(Vec<Captured>),
+    TupleStruct(&'static str, Vec<Captured>),
+    TupleVariant(&'static str, u32, &'static str, Vec<Captured>),
+    Map(Option<usize>, Vec<(Captured, Captured)>),
+    /// The fields of a struct, `None` if skipped, and its declared length.
+    Struct(&'static str, usize, Vec<(&'static str, Option<Captured>)>),
+    StructVariant(
+        &'static str,
+        u32,
+        &'static str,
+        usize,
+        Vec<(&'static str, Option<Captured>)>,
+    ),
+}
+
+/// Captures `value`, answering `human_readable` whether the format is
+/// human-readable.
+pub(crate) fn capture(
+    value: &dyn Serialize,
+    human_readable: bool,
+) -> Result<Captured, SerializeError> {
+    serde::Serialize::serialize(value, Capture { human_readable })
+}
+
+impl serde::Serialize for Captured {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        match self {
+            Captured::Bool(v) => serializer.serialize_bool(*v),
+            Captured::I8(v) => serializer.serialize_i8(*v),
+            Captured::I16(v) => serializer.serialize_i16(*v),
+            Captured::I32(v) => serializer.serialize_i32(*v),
+            Captured::I64(v) => serializer.serialize_i64(*v),
+            Captured::I128(v) => serializer.serialize_i128(*v),
+            Captured::U8(v) => serializer.serialize_u8(*v),
+            Captured::U16(v) => serializer.serialize_u16(*v),
+            Captured::U32(v) => serializer.serialize_u32(*v),
+            Captured::U64(v) => serializer.serialize_u64(*v),
+            Captured::U128(v) => serializer.serialize_u128(*v),
+            Captured::F32(v) => serializer.serialize_f32(*v),
+            Captured::F64(v) => serializer.serialize_f64(*v),
+            Captured::Char(v) => serializer.serialize_char(*v),
+            Captured::Str(v) => serializer.serialize_str(v),
+            Captured::Bytes(v) => serializer.serialize_bytes(v),
+            Captured::None => serializer.serialize_none(),
+            Captured::Some(value) => serializer.serialize_some(value),
+            Captured::Unit => serializer.serialize_unit(),
+            Captured::UnitStruct(name) => serializer.serialize_unit_struct(name),
+            Captured::UnitVariant(name, index, variant) => {
+                serializer.serialize_unit_variant(name, *index, variant)
+            }
+            Captured::NewtypeStruct(name, value) => {
+                serializer.serialize_newtype_struct(name, value)
+            }
+            Captured::NewtypeVariant(name, index, variant, value) => {
+                serializer.serialize_newtype_variant(name, *index, variant, value)
+            }
+            Captured::Seq(len, elements) => {
+                let mut seq = serializer.serialize_seq(*len)?;
+                for value in elements {
+                    seq.serialize_element(value)?;
+                }
+                seq.end()
+            }
+            Captured::Tuple(elements) => {
+                let mut tuple = serializer.serialize_tuple(elements.len())?;
+                for value in elements {
+                    tuple.serialize_element(value)?;
+                }
+                tuple.end()
+            }
+            Captured::TupleStruct(name, elements) => {
+                let mut tuple = serializer.serialize_tuple_struct(name, elements.len())?;
+                for value in elements {
+                    tuple.serialize_field(value)?;
+                }
+                tuple.end()
+            }
+            Captured::TupleVariant(name, index, variant, elements) => {
+                let mut tuple =
+                    serializer.serialize_tuple_variant(name, *index, variant, elements.len())?;
+                for value in elements {
+                    tuple.serialize_field(value)?;
+                }
+                tuple.end()
+            }
+            Captured::Map(len, entries) => {
+                let mut map = serializer.serialize_map(*len)?;
+                for (key, value) in entries {
+                    map.serialize_entry(key, value)?;
+                }
+                map.end()
+            }
+            Captured::Struct(name, len, fields) => {
+                let mut r#struct = serializer.serialize_struct(name, *len)?;
+                for (key, value) in fields {
+                    match value {
+                        Some(value) => r#struct.serialize_field(key, value)?,
+                        None => r#struct.skip_field(key)?,
+                    }
+                }
+                r#struct.end()
+            }
+            Captured::StructVariant(name, index, variant, len, fields) => {
+                let mut r#struct =
+                    serializer.serialize_struct_variant(name, *index, variant, *len)?;
+                for (key, value) in fields {
+                    match value {
+                        Some(value) => r#struct.serialize_field(key, value)?,
+                        None => r#struct.skip_field(key)?,
+                    }
+                }
+                r#struct.end()
+            }
+        }
+    }
+}
+
+/// The serializer capturing a value.
+#[derive(Clone, Copy)]
+struct Capture {
+    human_readable: bool,
+}
+
+impl Capture {
+    fn capture<T>(self, value: &T) -> Result<Captured, SerializeError>
+    where
+        T: ?Sized + serde::Serialize,
+    {
+        value.serialize(self)
+    }
+
+    fn compound(self, captured: Captured) -> CaptureCompound {
+        CaptureCompound {
+            capture: self,
+            captured,
+            key: None,
+        }
+    }
+}
+
+impl serde::Serializer for Capture {
+    type Ok = Captured;
+    type Error = SerializeError;
+    type SerializeSeq = CaptureCompound;
+    type SerializeTuple = CaptureCompound;
+    type SerializeTupleStruct = CaptureCompound;
+    type SerializeTupleVariant = CaptureCompound;
+    type SerializeMap = CaptureCompound;
+    type SerializeStruct = CaptureCompound;
+    type SerializeStructVariant = CaptureCompound;
+
+    fn serialize_bool(self, v: bool) -> Result<Captured, SerializeError> {
+        Ok(Captured::Bool(v))
+    }
+
+    fn serialize_i8(self, v: i8) -> Result<Captured, SerializeError> {
+        Ok(Captured::I8(v))
+    }
+
+    fn serialize_i16(self, v: i16) -> Result<Captured, SerializeError> {
+        Ok(Captured::I16(v))
+    }
+
+    fn serialize_i32(self, v: i32) -> Result<Captured, SerializeError> {
+        Ok(Captured::I32(v))
+    }
+
+    fn serialize_i64(self, v: i64) -> Result<Captured, SerializeError> {
+        Ok(Captured::I64(v))
+    }
+
+    fn serialize_i128(self, v: i128) -> Result<Captured, SerializeError> {
+        Ok(Captured::I128(v))
+    }
+
+    fn serialize_u8(self, v: u8) -> Result<Captured, SerializeError> {
+        Ok(Captured::U8(v))
+    }
+
+    fn serialize_u16(self, v: u16) -> Result<Captured, SerializeError> {
+        Ok(Captured::U16(v))
+    }
+
+    fn serialize_u32(self, v: u32) -> Result<Captured, SerializeError> {
+        Ok(Captured::U32(v))
+    }
+
+    fn serialize_u64(self, v: u64) -> Result<Captured, SerializeError> {
+        Ok(Captured::U64(v))
+    }
+
+    fn serialize_u128(self, v: u128) -> Result<Captured, SerializeError> {
+        Ok(Captured::U128(v))
+    }
+
+    fn serialize_f32(self, v: f32) -> Result<Captured, SerializeError> {
+        Ok(Captured::F32(v))
+    }
+
+    fn serialize_f64(self, v: f64) -> Result<Captured, SerializeError> {
+        Ok(Captured::F64(v))
+    }
+
+    fn serialize_char(self, v: char) -> Result<Captured, SerializeError> {
+        Ok(Captured::Char(v))
+    }
+
+    fn serialize_str(self, v: &str) -> Result<Captured, SerializeError> {
+        Ok(Captured::Str(v.to_string()))
+    }
+
+    fn serialize_bytes(self, v: &[u8]) -> Result<Captured, SerializeError> {
+        Ok(Captured::Bytes(v.to_vec()))
+    }
+
+    fn serialize_none(self) -> Result<Captured, SerializeError> {
+        Ok(Captured::None)
+    }
+
+    fn serialize_some<T>(self, value: &T) -> Result<Captured, SerializeError>
+    where
+        T: ?Sized + serde::Serialize,
+    {
+        Ok(Captured::Some(Box::new(self.capture(value)?)))
+    }
+
+    fn serialize_unit(self) -> Result<Captured, SerializeError> {
+        Ok(Captured::Unit)
+    }
+
+    fn serialize_unit_struct(self, name: &'static str) -> Result<Captured, SerializeError> {
+        Ok(Captured::UnitStruct(name))
+    }
+
+    fn serialize_unit_variant(
+        self,
+        name: &'static str,
+        variant_index: u32,
+        variant: &'static str,
+    ) -> Result<Captured, SerializeError> {
+        Ok(Captured::UnitVariant(name, variant_index, variant))
+    }
+
+    fn serialize_newtype_struct<T>(
+        self,
+        name: &'static str,
+        value: &T,
+    ) -> Result<Captured, SerializeError>
+    where
+        T: ?Sized + serde::Serialize,
+    {
+        let value = self.capture(value)?;
+        Ok(Captured::NewtypeStruct(name, Box::new(value)))
+    }
+
+    fn serialize_newtype_variant<T>(
+        self,
+        name: &'static str,
+        variant_index: u32,
+        variant: &'static str,
+        value: &T,
+    ) -> Result<Captured, SerializeError>
+    where
+        T: ?Sized + serde::Serialize,
+    {
+        let value = self.capture(value)?;
+        Ok(Captured::NewtypeVariant(
+            name,
+            variant_index,
+            variant,
+            Box::new(value),
+        ))
+    }
+
+    fn serialize_seq(self, len: Option<usize>) -> Result<CaptureCompound, SerializeError> {
+        let elements = Vec::with_capacity(len.unwrap_or(0));
+        Ok(self.compound(Captured::Seq(len, elements)))
+    }
+
+    fn serialize_tuple(self, len: usize) -> Result<CaptureCompound, SerializeError> {
+        Ok(self.compound(Captured::Tuple(Vec::with_capacity(len))))
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        name: &'static str,
+        len: usize,
+    ) -> Result<CaptureCompound, SerializeError> {
+        let elements = Vec::with_capacity(len);
+        Ok(self.compound(Captured::TupleStruct(name, elements)))
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        name: &'static str,
+        variant_index: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> Result<CaptureCompound, SerializeError> {
+        let elements = Vec::with_capacity(len);
+        Ok(self.compound(Captured::TupleVariant(
+            name,
+            variant_index,
+            variant,
+            elements,
+        )))
+    }
+
+    fn serialize_map(self, len: Option<usize>) -> Result<CaptureCompound, SerializeError> {
+        let entries = Vec::with_capacity(len.unwrap_or(0));
+        Ok(self.compound(Captured::Map(len, entries)))
+    }
+
+    fn serialize_struct(
+        self,
+        name: &'static str,
+        len: usize,
+    ) -> Result<CaptureCompound, SerializeError> {
+        let fields = Vec::with_capacity(len);
+        Ok(self.compound(Captured::Struct(name, len, fields)))
+    }
+
+    fn serialize_struct_variant(
+        self,
+        name: &'static str,
+        variant_index: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> Result<CaptureCompound, SerializeError> {
+        let fields = Vec::with_capacity(len);
+        Ok(self.compound(Captured::StructVariant(
+            name,
+            variant_index,
+            variant,
+            len,
+            fields,
+        )))
+    }
+
+    fn is_human_readable(&self) -> bool {
+        self.human_readable
+    }
+}
+
+/// A compound being captured.
+struct CaptureCompound {
+    capture: Capture,
+    captured: Captured,
+    /// The key of the map entry whose value comes next.
+    key: Option<Captured>,
+}
+
+impl CaptureCompound {
+    fn push_element<T>(&mut self, value: &T) -> Result<(), SerializeError>
+    where
+        T: ?Sized + serde::Serialize,
+    {
+        let value = self.capture.capture(value)?;
+        match &mut self.captured {
+            Captured::Seq(_, elements)
+            | Captured::Tuple(elements)
+            | Captured::TupleStruct(_, elements)
+            | Captured::TupleVariant(_, _, _, elements) => elements.push(value),
+            _ => unreachable!("the compound is a sequence"),
+        }
+        Ok(())
+    }
+
+    fn push_field(
+        &mut self,
+        key: &'static str,
+        value: Option<Captured>,
+    ) -> Result<(), SerializeError> {
+        match &mut self.captured {
+            Captured::Struct(_, _, fields) | Captured::StructVariant(_, _, _, _, fields) => {
+                fields.push((key, value));
+            }
+            _ => unreachable!("the compound is a struct"),
+        }
+        Ok(())
+    }
+}
+
+impl serde::ser::SerializeSeq for CaptureCompound {
+    type Ok = Captured;
+    type Error = SerializeError;
+
+    fn serialize_element<T>(&mut self, value: &T) -> Result<(), SerializeError>
+    where
+        T: ?Sized + serde::Serialize,
+    {
+        self.push_element(value)
+    }
+
+    fn end(self) -> Result<Captured, SerializeError> {
+        Ok(self.captured)
+    }
+}
+
+impl serde::ser::SerializeTuple for CaptureCompound {
+    type Ok = Captured;
+    type Error = SerializeError;
+
+    fn serialize_element<T>(&mut self, value: &T) -> Result<(), SerializeError>
+    where
+        T: ?Sized + serde::Serialize,
+    {
+        self.push_element(value)
+    }
+
+    fn end(self) -> Result<Captured, SerializeError> {
+        Ok(self.captured)
+    }
+}
+
+impl serde::ser::SerializeTupleStruct for CaptureCompound {
+    type Ok = Captured;
+    type Error = SerializeError;
+
+    fn serialize_field<T>(&mut self, value: &T) -> Result<(), SerializeError>
+    where
+        T: ?Sized + serde::Serialize,
+    {
+        self.push_element(value)
+    }
+
+    fn end(self) -> Result<Captured, SerializeError> {
+        Ok(self.captured)
+    }
+}
+
+impl serde::ser::SerializeTupleVariant for CaptureCompound {
+    type Ok = Captured;
+    type Error = SerializeError;
+
+    fn serialize_field<T>(&mut self, value: &T) -> Result<(), SerializeError>
+    where
+        T: ?Sized + serde::Serialize,
+    {
+        self.push_element(value)
+    }
+
+    fn end(self) -> Result<Captured, SerializeError> {
+        Ok(self.captured)
+    }
+}
+
+impl serde::ser::SerializeMap for CaptureCompound {
+    type Ok = Captured;
+    type Error = SerializeError;
+
+    fn serialize_key<T>(&mut self, key: &T) -> Result<(), SerializeError>
+    where
+        T: ?Sized + serde::Serialize,
+    {
+        self.key = Some(self.capture.capture(key)?);
+        Ok(())
+    }
+
+    fn serialize_value<T>(&mut self, value: &T) -> Result<(), SerializeError>
+    where
+        T: ?Sized + serde::Serialize,
+    {
+        let Some(key) = self.key.take() else {
+            return Err(SerializeError::custom(
+                "serialize_value called before serialize_key",
+            ));
+        };
+        let value = self.capture.capture(value)?;
+        match &mut self.captured {
+            Captured::Map(_, entries) => entries.push((key, value)),
+            _ => unreachable!("the compound is a map"),
+        }
+        Ok(())
+    }
+
+    fn end(self) -> Result<Captured, SerializeError> {
+        Ok(self.captured)
+    }
+}
+
+impl serde::ser::SerializeStruct for CaptureCompound {
+    type Ok = Captured;
+    type Error = SerializeError;
+
+    fn serialize_field<T>(&mut self, key: &'static str, value: &T) -> Result<(), SerializeError>
+    where
+        T: ?Sized + serde::Serialize,
+    {
+        let value = self.capture.capture(value)?;
+        self.push_field(key, Some(value))
+    }
+
+    fn skip_field(&mut self, key: &'static str) -> Result<(), SerializeError> {
+        self.push_field(key, None)
+    }
+
+    fn end(self) -> Result<Captured, SerializeError> {
+        Ok(self.captured)
+    }
+}
+
+impl serde::ser::SerializeStructVariant for CaptureCompound {
+    type Ok = Captured;
+    type Error = SerializeError;
+
+    fn serialize_field<T>(&mut self, key: &'static str, value: &T) -> Result<(), SerializeError>
+    where
+        T: ?Sized + serde::Serialize,
+    {
+        let value = self.capture.capture(value)?;
+        self.push_field(key, Some(value))
+    }
+
+    fn skip_field(&mut self, key: &'static str) -> Result<(), SerializeError> {
+        self.push_field(key, None)
+    }
+
+    fn end(self) -> Result<Captured, SerializeError> {
+        Ok(self.captured)
+    }
+}
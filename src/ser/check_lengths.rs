@@ -0,0 +1,158 @@
+//! A [`Serializer`] wrapper checking the lengths of compounds, see
+//! [`CheckLengths`].
+
+use core::cell::RefCell;
+use core::fmt::{self, Debug, Display, Formatter};
+
+#[cfg(not(feature = "std"))]
+use alloc::vec::Vec;
+
+use serde::ser::Error as _;
+
+use crate::ser::middleware::{self, Begin, Compound, Element};
+use crate::ser::middleware::{Middleware, MiddlewareSerializer};
+use crate::ser::{SerializeError, SerializeResult, Serializer};
+
+/// A [`Serializer`] wrapper which checks that every compound holds as many
+/// elements as the length it has been begun with, e.g. that a struct begun
+/// with `dyn_serialize_struct(name, 2)` serializes two fields.
+///
+/// Derived implementations always pass the right length, but serialization
+/// built by hand may not, and formats writing the length up front, such as
+/// most binary formats, then write a corrupt output which only fails once it
+/// is read back. `CheckLengths` counts the fields of structs and struct
+/// variants, the fields skipped with `skip_field` excluded, the elements of
+/// tuples, tuple structs and tuple variants, and the elements of sequences and
+/// the entries of maps begun with a length. When a compound ends with another
+/// number of elements, it fails with the error ``the length of struct `Point`
+/// is 2, not 3`` rather than ending, which is raised by the serializer of the value
+/// being serialized.
+///
+/// The compounds are checked at every depth of the value.
+///
+/// # Examples
+///
+/// ```
+/// # use serde::ser::SerializeStruct as _;
+/// # use dyn_serde::{Serialize, Serializer};
+/// # use dyn_serde::ser::{CheckLengths, InplaceSerializer};
+/// #
+/// struct Handmade;
+///
+/// impl serde::Serialize for Handmade {
+///     fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+///         let mut point = serializer.serialize_struct("Point", 3)?;
+///         point.serialize_field("x", &1)?;
+///         point.serialize_field("y", &2)?;
+///         point.end()
+///     }
+/// }
+///
+/// let mut buf = Vec::new();
+/// let mut serializer = serde_json::Serializer::new(&mut buf);
+/// let mut inplace = <dyn Serializer>::new(&mut serializer);
+/// let mut serializer = CheckLengths::new(&mut inplace);
+///
+/// assert!((&[Handmade] as &dyn Serialize).dyn_serialize(&mut serializer).is_err());
+/// let InplaceSerializer::Error(error, _) = inplace else { unreachable!() };
+/// assert_eq!(error.to_string(), "the length of struct `Point` is 2, not 3");
+/// ```
+pub struct CheckLengths<'a> {
+    serializer: MiddlewareSerializer<'a, Check>,
+}
+
+impl<'a> CheckLengths<'a> {
+    /// Wraps `serializer`, checking the length of every compound.
+    #[must_use]
+    pub fn new(serializer: &'a mut dyn Serializer) -> Self {
+        let check = Check {
+            compounds: RefCell::new(Vec::new()),
+        };
+        CheckLengths {
+            serializer: MiddlewareSerializer::new(serializer, check),
+        }
+    }
+}
+
+impl Debug for CheckLengths<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CheckLengths").finish_non_exhaustive()
+    }
+}
+
+impl Serializer for CheckLengths<'_> {
+    middleware::forward_to_middleware_serializer!(serializer);
+}
+
+struct Check {
+    /// The compounds being serialized, from the outermost one, with the
+    /// number of elements serialized so far.
+    compounds: RefCell<Vec<(Begin, usize)>>,
+}
+
+impl Middleware for Check {
+    fn begin<'a>(
+        &self,
+        begin: Begin,
+        serializer: &'a mut dyn Serializer,
+    ) -> SerializeResult<Compound<'a>> {
+        let compound = begin.forward(serializer)?;
+        self.compounds.borrow_mut().push((begin, 0));
+        Ok(compound)
+    }
+
+    fn element(&self, element: Element<'_>, compound: &mut Compound<'_>) -> SerializeResult<()> {
+        let counted = !matches!(element, Element::Value(_) | Element::SkipField(_));
+        element.forward(compound)?;
+        if counted && let Some((_, count)) = self.compounds.borrow_mut().last_mut() {
+            *count += 1;
+        }
+        Ok(())
+    }
+
+    fn end(&self, compound: Compound<'_>) -> SerializeResult<()> {
+        if let Some((begin, count)) = self.compounds.borrow_mut().pop()
+            && let Some(len) = declared_len(begin)
+            && count != len
+        {
+            return Err(SerializeError::custom(format_args!(
+                "the length of {} is {count}, not {len}",
+                Described(begin),
+            )));
+        }
+        Ok(compound.end()?)
+    }
+}
+
+/// Returns the length `begin` declares, if any.
+fn declared_len(begin: Begin) -> Option<usize> {
+    match begin {
+        Begin::Seq(len) | Begin::Map(len) => len,
+        Begin::Tuple(len)
+        | Begin::TupleStruct(_, len)
+        | Begin::TupleVariant(_, _, _, len)
+        | Begin::Struct(_, len)
+        | Begin::StructVariant(_, _, _, len) => Some(len),
+    }
+}
+
+/// The compound begun with a call, as named in errors.
+struct Described(Begin);
+
+impl Display for Described {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self.0 {
+            Begin::Seq(_) => f.write_str("sequence"),
+            Begin::Tuple(_) => f.write_str("tuple"),
+            Begin::TupleStruct(name, _) => write!(f, "tuple struct `{name}`"),
+            Begin::TupleVariant(name, _, variant, _) => {
+                write!(f, "tuple variant `{name}::{variant}`")
+            }
+            Begin::Map(_) => f.write_str("map"),
+            Begin::Struct(name, _) => write!(f, "struct `{name}`"),
+            Begin::StructVariant(name, _, variant, _) => {
+                write!(f, "struct variant `{name}::{variant}`")
+            }
+        }
+    }
+}
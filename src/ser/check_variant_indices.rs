@@ -0,0 +1,126 @@
+//! A [`Serializer`] wrapper checking the indices of enum variants, see
+//! [`CheckVariantIndices`].
+
+use core::fmt::{self, Debug, Formatter};
+
+use serde::ser::Error as _;
+
+use crate::ser::middleware::{self, Begin, Call, Compound, Middleware, MiddlewareSerializer};
+use crate::ser::{SerializeError, SerializeResult, Serializer};
+
+/// The variants of an enum, by name of the enum and in the order of their
+/// indices.
+type Schema<'a> = [(&'a str, &'a [&'a str])];
+
+/// A [`Serializer`] wrapper which checks that the index of every variant
+/// serialized matches its name, against the variants of the enums given to
+/// [`CheckVariantIndices::new`].
+///
+/// Derived implementations always pass consistent indices, but serialization
+/// built by hand, e.g. from a table of enums known at runtime, may pass an
+/// index which names another variant. Formats reading the index rather than
+/// the name, such as most binary formats, then silently write the wrong
+/// variant. `CheckVariantIndices` fails the serialization instead, with the
+/// error ``variant `E::B` has index 1, not 0``, or ``unknown variant `E::C` ``
+/// if the enum has no such variant, which is raised by the serializer of the
+/// value being serialized.
+///
+/// Unit, newtype, tuple and struct variants are checked at every depth of the
+/// value. Variants of enums missing from the schema are forwarded unchecked.
+///
+/// # Examples
+///
+/// ```
+/// # use dyn_serde::{Serialize, Serializer};
+/// # use dyn_serde::ser::{CheckVariantIndices, InplaceSerializer};
+/// #
+/// struct Handmade;
+///
+/// impl serde::Serialize for Handmade {
+///     fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+///         serializer.serialize_unit_variant("Level", 0, "Warn")
+///     }
+/// }
+///
+/// let schema = [("Level", &["Info", "Warn"][..])];
+/// let mut buf = Vec::new();
+/// let mut serializer = serde_json::Serializer::new(&mut buf);
+/// let mut inplace = <dyn Serializer>::new(&mut serializer);
+/// let mut serializer = CheckVariantIndices::new(&mut inplace, &schema);
+///
+/// assert!((&[Handmade] as &dyn Serialize).dyn_serialize(&mut serializer).is_err());
+/// let InplaceSerializer::Error(error, _) = inplace else { unreachable!() };
+/// assert_eq!(error.to_string(), "variant `Level::Warn` has index 1, not 0");
+/// ```
+pub struct CheckVariantIndices<'a> {
+    serializer: MiddlewareSerializer<'a, Check<'a>>,
+}
+
+impl<'a> CheckVariantIndices<'a> {
+    /// Wraps `serializer`, checking variant indices against `schema`, which
+    /// lists the variants of each enum by name of the enum.
+    #[must_use]
+    pub fn new(serializer: &'a mut dyn Serializer, schema: &'a [(&'a str, &'a [&'a str])]) -> Self {
+        CheckVariantIndices {
+            serializer: MiddlewareSerializer::new(serializer, Check { schema }),
+        }
+    }
+}
+
+impl Debug for CheckVariantIndices<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CheckVariantIndices")
+            .field("schema", &self.serializer.middleware().schema)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Serializer for CheckVariantIndices<'_> {
+    middleware::forward_to_middleware_serializer!(serializer);
+}
+
+struct Check<'a> {
+    schema: &'a Schema<'a>,
+}
+
+impl Check<'_> {
+    fn check(&self, name: &str, variant_index: u32, variant: &str) -> SerializeResult<()> {
+        let Some((_, variants)) = self.schema.iter().find(|(enum_name, _)| *enum_name == name)
+        else {
+            return Ok(());
+        };
+        match variants.iter().position(|&v| v == variant) {
+            Some(index) if index == variant_index as usize => Ok(()),
+            Some(index) => Err(SerializeError::custom(format_args!(
+                "variant `{name}::{variant}` has index {index}, not {variant_index}"
+            ))),
+            None => Err(SerializeError::custom(format_args!(
+                "unknown variant `{name}::{variant}`"
+            ))),
+        }
+    }
+}
+
+impl Middleware for Check<'_> {
+    fn serialize(&self, call: Call<'_>, serializer: &mut dyn Serializer) -> SerializeResult<()> {
+        if let Call::UnitVariant(name, variant_index, variant)
+        | Call::NewtypeVariant(name, variant_index, variant, _) = call
+        {
+            self.check(name, variant_index, variant)?;
+        }
+        Ok(call.forward(serializer)?)
+    }
+
+    fn begin<'a>(
+        &self,
+        begin: Begin,
+        serializer: &'a mut dyn Serializer,
+    ) -> SerializeResult<Compound<'a>> {
+        if let Begin::TupleVariant(name, variant_index, variant, _)
+        | Begin::StructVariant(name, variant_index, variant, _) = begin
+        {
+            self.check(name, variant_index, variant)?;
+        }
+        Ok(begin.forward(serializer)?)
+    }
+}
@@ -0,0 +1,145 @@
+//! A [`Serializer`] wrapper calling back after every chunk of elements of a
+//! sequence, see [`ChunkedSeq`].
+
+use core::cell::{Cell, RefCell};
+use core::fmt::{self, Debug, Formatter};
+
+#[cfg(not(feature = "std"))]
+use alloc::boxed::Box;
+
+use crate::ser::middleware::{self, Begin, Compound, Element};
+use crate::ser::middleware::{Middleware, MiddlewareSerializer};
+use crate::ser::{SerializeResult, Serializer};
+
+/// A [`Serializer`] wrapper which calls a callback after every `chunk`
+/// elements of the sequence being serialized, e.g. to flush the output of the
+/// wrapped serializer to a network sink.
+///
+/// Streaming a large array through a buffered writer otherwise sends nothing
+/// until the buffer is full, and nothing tells the producer to slow down.
+/// The callback is given the number of elements serialized so far, and runs
+/// between two elements, so that it may flush the writer of the wrapped
+/// serializer, or block until the sink is ready for more. If it fails, the
+/// serialization stops with its error.
+///
+/// Only the elements of the value itself count, which must be a sequence,
+/// possibly behind `Some` or a newtype struct, rather than a tuple or an array
+/// of a fixed size, which serde serializes as a tuple. The callback is not called for
+/// the last elements if they are fewer than `chunk`, nor once the sequence
+/// ends, as the serializer is still to write the end of the sequence: the
+/// caller flushes the rest once the serialization has finished. A `chunk` of
+/// `0` never calls the callback.
+///
+/// # Examples
+///
+/// ```
+/// # use dyn_serde::{Serialize, Serializer};
+/// # use dyn_serde::ser::ChunkedSeq;
+/// #
+/// let mut counts = Vec::new();
+/// let mut serializer = serde_json::Serializer::new(Vec::new());
+/// let mut serializer = <dyn Serializer>::new(&mut serializer);
+/// let mut serializer = ChunkedSeq::new(&mut serializer, 2, |count| {
+///     counts.push(count);
+///     Ok(())
+/// });
+///
+/// (&vec![1, 2, 3, 4, 5] as &dyn Serialize).dyn_serialize(&mut serializer).unwrap();
+/// drop(serializer);
+/// assert_eq!(counts, [2, 4]);
+/// ```
+pub struct ChunkedSeq<'a> {
+    serializer: MiddlewareSerializer<'a, Chunks<'a>>,
+}
+
+type Callback<'a> = Box<dyn FnMut(usize) -> SerializeResult<()> + 'a>;
+
+impl<'a> ChunkedSeq<'a> {
+    /// Wraps `serializer`, calling `callback` after every `chunk` elements of
+    /// the sequence being serialized.
+    #[must_use]
+    pub fn new(
+        serializer: &'a mut dyn Serializer,
+        chunk: usize,
+        callback: impl FnMut(usize) -> SerializeResult<()> + 'a,
+    ) -> Self {
+        let chunks = Chunks {
+            chunk,
+            callback: RefCell::new(Box::new(callback)),
+            depth: Cell::new(0),
+            seq: Cell::new(false),
+            count: Cell::new(0),
+        };
+        ChunkedSeq {
+            serializer: MiddlewareSerializer::new(serializer, chunks),
+        }
+    }
+
+    /// Returns the number of elements of the sequence serialized so far.
+    #[must_use]
+    pub fn count(&self) -> usize {
+        self.serializer.middleware().count.get()
+    }
+}
+
+impl Debug for ChunkedSeq<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ChunkedSeq")
+            .field("chunk", &self.serializer.middleware().chunk)
+            .field("count", &self.count())
+            .finish_non_exhaustive()
+    }
+}
+
+impl Serializer for ChunkedSeq<'_> {
+    middleware::forward_to_middleware_serializer!(serializer);
+}
+
+struct Chunks<'a> {
+    chunk: usize,
+    callback: RefCell<Callback<'a>>,
+    /// The number of compounds being serialized.
+    depth: Cell<usize>,
+    /// Whether the value itself is a sequence.
+    seq: Cell<bool>,
+    /// The number of elements of the sequence serialized so far.
+    count: Cell<usize>,
+}
+
+impl Middleware for Chunks<'_> {
+    fn begin<'a>(
+        &self,
+        begin: Begin,
+        serializer: &'a mut dyn Serializer,
+    ) -> SerializeResult<Compound<'a>> {
+        let depth = self.depth.get();
+        if depth == 0 {
+            self.seq.set(matches!(begin, Begin::Seq(_)));
+            self.count.set(0);
+        }
+        let compound = begin.forward(serializer)?;
+        self.depth.set(depth + 1);
+        Ok(compound)
+    }
+
+    fn element(&self, element: Element<'_>, compound: &mut Compound<'_>) -> SerializeResult<()> {
+        let top = self.depth.get() == 1 && self.seq.get();
+        element.forward(compound)?;
+        if !top {
+            return Ok(());
+        }
+
+        let count = self.count.get() + 1;
+        self.count.set(count);
+        if count.is_multiple_of(self.chunk) {
+            (self.callback.borrow_mut())(count)?;
+        }
+        Ok(())
+    }
+
+    fn end(&self, compound: Compound<'_>) -> SerializeResult<()> {
+        compound.end()?;
+        self.depth.set(self.depth.get() - 1);
+        Ok(())
+    }
+}
@@ -0,0 +1,92 @@
+//! Serializing a [`Display`] value through a stack buffer, see
+//! [`collect_str`].
+
+use core::fmt::{self, Display, Write as _};
+use core::str;
+
+#[cfg(not(feature = "std"))]
+use alloc::string::String;
+
+use crate::ser::{InplaceSerializeResult, Serializer};
+
+/// The number of bytes formatted on the stack before spilling to the heap.
+const INLINE_LEN: usize = 128;
+
+/// Serializes `value` as a string with `dyn_serialize_str`, formatting it into
+/// a buffer on the stack.
+///
+/// This is what the default `dyn_collect_str` does. Unlike `ToString`, it only
+/// allocates if the formatted value is longer than 128 bytes, so that short
+/// values such as numbers, addresses or identifiers are serialized without
+/// touching the heap, which matters on constrained `no_std` targets. Longer
+/// values spill into a `String`.
+///
+/// # Errors
+///
+/// Returns an error if `dyn_serialize_str` fails.
+///
+/// # Panics
+///
+/// Panics if the `Display` implementation of `value` returns an error, as
+/// `ToString` does.
+///
+/// # Examples
+///
+/// ```
+/// # use std::net::Ipv4Addr;
+/// # use dyn_serde::Serializer;
+/// # use dyn_serde::ser::collect_str;
+/// #
+/// let mut buf = Vec::new();
+/// let mut serializer = serde_json::Serializer::new(&mut buf);
+/// let mut serializer = <dyn Serializer>::new(&mut serializer);
+///
+/// collect_str(&mut serializer, &Ipv4Addr::LOCALHOST).unwrap();
+/// assert_eq!(buf, br#""127.0.0.1""#);
+/// ```
+pub fn collect_str<S>(serializer: &mut S, value: &dyn Display) -> InplaceSerializeResult<()>
+where
+    S: Serializer + ?Sized,
+{
+    let mut buf = InlineBuf {
+        bytes: [0; INLINE_LEN],
+        len: 0,
+        spilled: None,
+    };
+    write!(buf, "{value}").expect("a Display implementation returned an error unexpectedly");
+    serializer.dyn_serialize_str(buf.as_str())
+}
+
+/// A string formatted on the stack, until it no longer fits.
+struct InlineBuf {
+    bytes: [u8; INLINE_LEN],
+    len: usize,
+    spilled: Option<String>,
+}
+
+impl InlineBuf {
+    fn as_str(&self) -> &str {
+        match &self.spilled {
+            Some(string) => string,
+            None => str::from_utf8(&self.bytes[..self.len])
+                .expect("only whole strings are copied into the inline buffer"),
+        }
+    }
+}
+
+impl fmt::Write for InlineBuf {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        if let Some(string) = &mut self.spilled {
+            string.push_str(s);
+        } else if let Some(bytes) = self.bytes.get_mut(self.len..self.len + s.len()) {
+            bytes.copy_from_slice(s.as_bytes());
+            self.len += s.len();
+        } else {
+            let mut string = String::with_capacity(2 * (self.len + s.len()));
+            string.push_str(self.as_str());
+            string.push_str(s);
+            self.spilled = Some(string);
+        }
+        Ok(())
+    }
+}
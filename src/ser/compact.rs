@@ -0,0 +1,64 @@
+//! A value serialized in compact form, see [`Compact`].
+
+use crate::ser::Serialize;
+use crate::ser::middleware::{self, Middleware};
+
+/// A value which serializes in the form of non-human-readable formats, e.g.
+/// a timestamp as a number rather than a string, inside an otherwise
+/// human-readable output.
+///
+/// Types such as IP addresses and timestamps branch on
+/// `is_human_readable()` to choose their form. `Compact` tells the value it
+/// wraps, and every value inside it, that the serializer is not
+/// human-readable, whatever the serializer actually is, while the rest of the
+/// output is left alone. This is finer-grained than [`WithHumanReadable`],
+/// which fixes the answer of a whole serializer, and only its own.
+///
+/// Only the answer given to the `Serialize` implementations changes: the
+/// format still writes the output as it does for a human-readable value, e.g.
+/// bytes as an array of numbers in JSON.
+///
+/// [`WithHumanReadable`]: crate::ser::WithHumanReadable
+///
+/// # Examples
+///
+/// ```
+/// # use std::net::Ipv4Addr;
+/// # use dyn_serde::ser::Compact;
+/// #
+/// let ip = Ipv4Addr::LOCALHOST;
+/// let value = (ip, Compact(&ip));
+/// assert_eq!(serde_json::to_string(&value).unwrap(), r#"["127.0.0.1",[127,0,0,1]]"#);
+/// ```
+#[derive(Debug)]
+pub struct Compact<'a, T: ?Sized>(pub &'a T);
+
+impl<T: ?Sized> Clone for Compact<'_, T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T: ?Sized> Copy for Compact<'_, T> {}
+
+impl<T> serde::Serialize for Compact<'_, T>
+where
+    T: serde::Serialize + ?Sized,
+{
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        let value = middleware::wrap(&self.0, &NotHumanReadable);
+        serde::Serialize::serialize(&value as &dyn Serialize, serializer)
+    }
+}
+
+struct NotHumanReadable;
+
+impl Middleware for NotHumanReadable {
+    fn human_readable(&self, human_readable: bool) -> bool {
+        let _ = human_readable;
+        false
+    }
+}
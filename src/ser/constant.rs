@@ -0,0 +1,69 @@
+//! A value always serialized as the same constant, see [`Const`].
+
+use core::fmt::{self, Debug, Formatter};
+
+use crate::ser::Serialize;
+
+/// A dynamically serializable value which always serializes as the constant it
+/// is created with, e.g. the `"$schema"` URL of a JSON document.
+///
+/// A field annotating every document with the same structure otherwise needs
+/// a type of its own, or a `serialize_with` function, per constant. `Const`
+/// holds a `&dyn Serialize` instead, so it can be created in a `const` item and
+/// used as the type of the field, or built into a dynamically assembled
+/// object. It has no state of its own: all values of `Const` created with the
+/// same constant serialize identically.
+///
+/// # Examples
+///
+/// ```
+/// # use dyn_serde::ser::Const;
+/// #
+/// const SCHEMA: Const<'static> = Const::new(&"https://example.com/user.json");
+///
+/// #[derive(serde::Serialize)]
+/// struct User {
+///     #[serde(rename = "$schema")]
+///     schema: Const<'static>,
+///     name: &'static str,
+/// }
+///
+/// let user = User { schema: SCHEMA, name: "ferris" };
+/// assert_eq!(
+///     serde_json::to_string(&user).unwrap(),
+///     r#"{"$schema":"https://example.com/user.json","name":"ferris"}"#,
+/// );
+/// ```
+#[derive(Clone, Copy)]
+pub struct Const<'a> {
+    value: &'a dyn Serialize,
+}
+
+impl<'a> Const<'a> {
+    /// Creates a value always serializing as `value`.
+    #[must_use]
+    pub const fn new(value: &'a dyn Serialize) -> Self {
+        Const { value }
+    }
+
+    /// Returns the constant.
+    #[must_use]
+    pub fn get(&self) -> &'a dyn Serialize {
+        self.value
+    }
+}
+
+impl Debug for Const<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Const").finish_non_exhaustive()
+    }
+}
+
+impl serde::Serialize for Const<'_> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serde::Serialize::serialize(self.value, serializer)
+    }
+}
@@ -0,0 +1,41 @@
+//! Writers counting the bytes written through them, see [`Counted`].
+
+use core::fmt;
+#[cfg(feature = "std")]
+use std::io;
+
+/// A writer forwarding to `inner`, which counts the bytes written so far, so
+/// that a serializer writing through it can report them with
+/// `dyn_bytes_written`.
+#[derive(Debug)]
+pub(crate) struct Counted<W> {
+    pub(crate) inner: W,
+    pub(crate) written: u64,
+}
+
+impl<W> Counted<W> {
+    pub(crate) fn new(inner: W) -> Self {
+        Counted { inner, written: 0 }
+    }
+}
+
+impl<W: fmt::Write> fmt::Write for Counted<W> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        self.inner.write_str(s)?;
+        self.written += s.len() as u64;
+        Ok(())
+    }
+}
+
+#[cfg(feature = "std")]
+impl<W: io::Write> io::Write for Counted<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let written = self.inner.write(buf)?;
+        self.written += written as u64;
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
@@ -0,0 +1,274 @@
+//! A [`Serializer`] wrapper replacing repeated objects with references, see
+//! [`DedupObjects`].
+
+use core::cell::{Cell, RefCell};
+use core::fmt::{self, Debug, Formatter};
+
+#[cfg(not(feature = "std"))]
+use alloc::collections::BTreeMap;
+#[cfg(not(feature = "std"))]
+use alloc::vec::Vec;
+#[cfg(feature = "std")]
+use std::collections::BTreeMap;
+
+use serde::ser::SerializeMap as _;
+
+use crate::ser::middleware::{self, Begin, Call, Compound, Element};
+use crate::ser::middleware::{Middleware, MiddlewareSerializer};
+use crate::ser::{Serialize, SerializeResult, Serializer, canonicalize};
+
+/// A [`Serializer`] wrapper which writes every object repeated within the
+/// value as a reference to its first occurrence, e.g. `{"$ref": 0}`.
+///
+/// Every map or struct inside the value, at every depth, is given an id on
+/// its first occurrence, written as an extra entry before its own, e.g.
+/// `{"$id": 0, "x": 1}`. Every later object structurally identical to it is
+/// written as a map of a single entry referencing that id instead, so that
+/// data with repeated structure shrinks to one copy of each distinct object.
+/// The ids are numbered from `0` in the order the objects begin. This is the
+/// `$id` and `$ref` convention of Json.NET, among others.
+///
+/// The format has no such notion, so the reader has to resolve the references
+/// itself, e.g. by sharing each object it has read under its id: a reader
+/// unaware of them sees the extra entries and the references as plain data.
+///
+/// Objects are identical if their [`canonicalize`]d JSON is, so that a struct
+/// equals a map with the same entries, and `1` equals `1.0`. Values which fail
+/// to canonicalize, e.g. maps with keys that are not strings or numbers, are
+/// written in full and never referenced, as are the value itself, sequences,
+/// and the variants of enums with the content of newtype variants, whose
+/// representation has no room for an id in externally tagged formats. The
+/// fields of tuple and struct variants are referenced as usual.
+///
+/// As the serializer cannot look ahead, objects which are not repeated are
+/// given an id as well. Each object is canonicalized once per object around
+/// it, which is quadratic in the depth of the value, and the canonical JSON
+/// of every distinct object is kept until the serializer is dropped.
+///
+/// # Examples
+///
+/// ```
+/// # use dyn_serde::{Serialize, Serializer};
+/// # use dyn_serde::ser::DedupObjects;
+/// #
+/// #[derive(serde::Serialize)]
+/// struct Point {
+///     x: i32,
+/// }
+///
+/// let mut buf = Vec::new();
+/// let mut serializer = serde_json::Serializer::new(&mut buf);
+/// let mut serializer = <dyn Serializer>::new(&mut serializer);
+/// let mut serializer = DedupObjects::new(&mut serializer, "$id", "$ref");
+///
+/// let points = [Point { x: 1 }, Point { x: 2 }, Point { x: 1 }];
+/// (&points as &dyn Serialize).dyn_serialize(&mut serializer).unwrap();
+/// assert_eq!(buf, br#"[{"$id":0,"x":1},{"$id":1,"x":2},{"$ref":0}]"#);
+/// ```
+pub struct DedupObjects<'a> {
+    serializer: MiddlewareSerializer<'a, Dedup>,
+}
+
+impl<'a> DedupObjects<'a> {
+    /// Wraps `serializer`, writing the id of every object in the entry `id`
+    /// and every reference to it in a map of the single entry `reference`.
+    #[must_use]
+    pub fn new(
+        serializer: &'a mut dyn Serializer,
+        id: &'static str,
+        reference: &'static str,
+    ) -> Self {
+        let dedup = Dedup {
+            id,
+            reference,
+            objects: RefCell::new(BTreeMap::new()),
+            pending: RefCell::new(None),
+            forwarding: Cell::new(false),
+        };
+        DedupObjects {
+            serializer: MiddlewareSerializer::new(serializer, dedup),
+        }
+    }
+
+    /// Returns the number of distinct objects serialized so far.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.serializer.middleware().objects.borrow().len()
+    }
+
+    /// Returns `true` if no object has been given an id yet.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl Debug for DedupObjects<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let dedup = self.serializer.middleware();
+        f.debug_struct("DedupObjects")
+            .field("id", &dedup.id)
+            .field("reference", &dedup.reference)
+            .field("len", &self.len())
+            .finish_non_exhaustive()
+    }
+}
+
+impl Serializer for DedupObjects<'_> {
+    middleware::forward_to_middleware_serializer!(serializer);
+}
+
+/// A reference to the object of an id.
+struct Reference {
+    key: &'static str,
+    id: usize,
+}
+
+impl serde::Serialize for Reference {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        let mut map = serializer.serialize_map(Some(1))?;
+        map.serialize_entry(self.key, &self.id)?;
+        map.end()
+    }
+}
+
+struct Dedup {
+    id: &'static str,
+    reference: &'static str,
+    /// The ids of the objects, by their canonical JSON.
+    objects: RefCell<BTreeMap<Vec<u8>, usize>>,
+    /// The canonical JSON of the element being serialized, until it begins as
+    /// an object.
+    pending: RefCell<Option<Vec<u8>>>,
+    /// Whether every call is forwarded unchanged, while an element is being
+    /// canonicalized or a key is being serialized.
+    forwarding: Cell<bool>,
+}
+
+impl Dedup {
+    /// Canonicalizes `value`, returning the id of the identical object if it
+    /// has been serialized already, and marking it pending otherwise.
+    fn lookup(&self, value: &dyn Serialize) -> Option<usize> {
+        let forwarding = self.forwarding.replace(true);
+        let canonical = canonicalize(value);
+        self.forwarding.set(forwarding);
+
+        let canonical = canonical.ok().filter(|json| json.first() == Some(&b'{'))?;
+        if let Some(&id) = self.objects.borrow().get(&canonical) {
+            return Some(id);
+        }
+        *self.pending.borrow_mut() = Some(canonical);
+        None
+    }
+}
+
+impl Middleware for Dedup {
+    fn serialize(&self, call: Call<'_>, serializer: &mut dyn Serializer) -> SerializeResult<()> {
+        if self.forwarding.get() {
+            return Ok(call.forward(serializer)?);
+        }
+        if !matches!(call, Call::Some(_) | Call::NewtypeStruct(..)) {
+            self.pending.borrow_mut().take();
+        }
+        Ok(call.forward(serializer)?)
+    }
+
+    fn begin<'a>(
+        &self,
+        begin: Begin,
+        serializer: &'a mut dyn Serializer,
+    ) -> SerializeResult<Compound<'a>> {
+        if self.forwarding.get() {
+            return Ok(begin.forward(serializer)?);
+        }
+        let pending = self.pending.borrow_mut().take();
+        let Some(canonical) =
+            pending.filter(|_| matches!(begin, Begin::Map(_) | Begin::Struct(..)))
+        else {
+            return Ok(begin.forward(serializer)?);
+        };
+
+        let id = {
+            let mut objects = self.objects.borrow_mut();
+            let id = objects.len();
+            objects.insert(canonical, id);
+            id
+        };
+        match begin {
+            Begin::Map(len) => {
+                let mut compound = Begin::Map(len.map(|len| len + 1)).forward(serializer)?;
+                Element::Entry(&self.id, &id).forward(&mut compound)?;
+                Ok(compound)
+            }
+            Begin::Struct(name, len) => {
+                let mut compound = Begin::Struct(name, len + 1).forward(serializer)?;
+                Element::StructField(self.id, &id).forward(&mut compound)?;
+                Ok(compound)
+            }
+            _ => unreachable!("only maps and structs are given an id"),
+        }
+    }
+
+    fn element(&self, element: Element<'_>, compound: &mut Compound<'_>) -> SerializeResult<()> {
+        if self.forwarding.get() {
+            return Ok(element.forward(compound)?);
+        }
+
+        let value = match element {
+            Element::Item(value)
+            | Element::Field(value)
+            | Element::Value(value)
+            | Element::Entry(_, value)
+            | Element::StructField(_, value) => Some(value),
+            Element::Key(_) | Element::SkipField(_) => None,
+        };
+        let Some(id) = value.and_then(|value| self.lookup(value)) else {
+            let result = match element {
+                // The key of an entry is serialized before its value, which
+                // must not discard the value pending.
+                Element::Entry(key, value) => {
+                    let key = Forwarded { dedup: self, key };
+                    Element::Entry(&key, value).forward(compound)
+                }
+                element => element.forward(compound),
+            };
+            self.pending.borrow_mut().take();
+            return Ok(result?);
+        };
+
+        let reference = Reference {
+            key: self.reference,
+            id,
+        };
+        let element = match element {
+            Element::Item(_) => Element::Item(&reference),
+            Element::Field(_) => Element::Field(&reference),
+            Element::Value(_) => Element::Value(&reference),
+            Element::Entry(key, _) => Element::Entry(key, &reference),
+            Element::StructField(key, _) => Element::StructField(key, &reference),
+            Element::Key(_) | Element::SkipField(_) => unreachable!("keys are never referenced"),
+        };
+        Ok(element.forward(compound)?)
+    }
+}
+
+/// The key of an entry, serialized with every call forwarded unchanged.
+struct Forwarded<'a> {
+    dedup: &'a Dedup,
+    key: &'a dyn Serialize,
+}
+
+impl serde::Serialize for Forwarded<'_> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        let forwarding = self.dedup.forwarding.replace(true);
+        let result = serde::Serialize::serialize(self.key, serializer);
+        self.dedup.forwarding.set(forwarding);
+        result
+    }
+}
@@ -0,0 +1,142 @@
+//! A [`Serializer`] wrapper enforcing a maximum depth, see [`DepthLimited`].
+
+use core::cell::Cell;
+use core::fmt::{self, Debug, Formatter};
+
+use serde::ser::Error as _;
+
+use crate::ser::middleware::{self, Begin, Compound};
+use crate::ser::middleware::{Middleware, MiddlewareSerializer};
+use crate::ser::{InplaceSerializer, NullSerializer};
+use crate::ser::{Serialize, SerializeError, SerializeResult, Serializer};
+
+/// A [`Serializer`] wrapper which aborts the serialization once the value
+/// nests deeper than a limit, e.g. a recursive structure built from
+/// untrusted input.
+///
+/// The depth of a value is the number of compounds it is nested in:
+/// sequences, tuples, maps, structs and the variants with fields. A scalar at
+/// the top level has depth `0`, and the elements of a top-level sequence have
+/// depth `1`. Beginning a compound at the depth of the limit, i.e. holding
+/// values deeper than it, fails with the error `the value exceeds the maximum
+/// depth of N`, which is raised by the serializer of the value being
+/// serialized, and [`DepthLimited::exceeded`] returns `true`.
+///
+/// To check a value before serializing it to a real format, see
+/// [`check_depth`].
+///
+/// # Examples
+///
+/// ```
+/// # use dyn_serde::{Serialize, Serializer};
+/// # use dyn_serde::ser::DepthLimited;
+/// #
+/// let mut buf = Vec::new();
+/// let mut serializer = serde_json::Serializer::new(&mut buf);
+/// let mut serializer = <dyn Serializer>::new(&mut serializer);
+/// let mut serializer = DepthLimited::new(&mut serializer, 2);
+///
+/// let value = vec![vec![vec![1]]];
+/// assert!((&value as &dyn Serialize).dyn_serialize(&mut serializer).is_err());
+/// assert!(serializer.exceeded());
+/// ```
+pub struct DepthLimited<'a> {
+    serializer: MiddlewareSerializer<'a, Limit>,
+}
+
+impl<'a> DepthLimited<'a> {
+    /// Wraps `serializer`, limiting the depth of the value to `max_depth`.
+    #[must_use]
+    pub fn new(serializer: &'a mut dyn Serializer, max_depth: usize) -> Self {
+        let limit = Limit {
+            max_depth,
+            depth: Cell::new(0),
+            exceeded: Cell::new(false),
+        };
+        DepthLimited {
+            serializer: MiddlewareSerializer::new(serializer, limit),
+        }
+    }
+
+    /// Returns `true` if the serialization has been aborted because the
+    /// value exceeds the maximum depth.
+    #[must_use]
+    pub fn exceeded(&self) -> bool {
+        self.serializer.middleware().exceeded.get()
+    }
+}
+
+impl Debug for DepthLimited<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let limit = self.serializer.middleware();
+        f.debug_struct("DepthLimited")
+            .field("max_depth", &limit.max_depth)
+            .field("depth", &limit.depth.get())
+            .field("exceeded", &limit.exceeded.get())
+            .finish_non_exhaustive()
+    }
+}
+
+impl Serializer for DepthLimited<'_> {
+    middleware::forward_to_middleware_serializer!(serializer);
+}
+
+/// Returns an error if `value` nests deeper than `max_depth`, without
+/// producing any output.
+///
+/// The value is serialized through a [`DepthLimited`] wrapping a
+/// [`NullSerializer`], which is a cheap check for untrusted values before
+/// committing to an expensive serialization. The check stops at the first
+/// compound beyond the limit, and the errors raised by the `Serialize`
+/// implementations of the value are returned as well.
+///
+/// # Examples
+///
+/// ```
+/// # use dyn_serde::ser::check_depth;
+/// #
+/// let value = vec![vec![vec![1]]];
+/// assert!(check_depth(&value, 3).is_ok());
+///
+/// let error = check_depth(&value, 2).unwrap_err();
+/// assert_eq!(error.to_string(), "the value exceeds the maximum depth of 2");
+/// ```
+pub fn check_depth(value: &dyn Serialize, max_depth: usize) -> SerializeResult<()> {
+    let mut serializer = <dyn Serializer>::new(NullSerializer);
+    let result = value.dyn_serialize(&mut DepthLimited::new(&mut serializer, max_depth));
+    result.map_err(|error| match serializer {
+        InplaceSerializer::Error(error, _) => error,
+        _ => error,
+    })
+}
+
+struct Limit {
+    max_depth: usize,
+    depth: Cell<usize>,
+    exceeded: Cell<bool>,
+}
+
+impl Middleware for Limit {
+    fn begin<'a>(
+        &self,
+        begin: Begin,
+        serializer: &'a mut dyn Serializer,
+    ) -> SerializeResult<Compound<'a>> {
+        let depth = self.depth.get();
+        if depth >= self.max_depth {
+            self.exceeded.set(true);
+            return Err(SerializeError::custom(format_args!(
+                "the value exceeds the maximum depth of {}",
+                self.max_depth
+            )));
+        }
+        let compound = begin.forward(serializer)?;
+        self.depth.set(depth + 1);
+        Ok(compound)
+    }
+
+    fn end(&self, compound: Compound<'_>) -> SerializeResult<()> {
+        self.depth.set(self.depth.get() - 1);
+        Ok(compound.end()?)
+    }
+}
@@ -0,0 +1,95 @@
+//! A [`Duration`] serialized as a number of seconds, see [`DurationSecs`].
+
+use core::fmt::{self, Formatter};
+use core::time::Duration;
+
+/// A [`Duration`] which serializes as an `f64` number of seconds, e.g. `1.5`,
+/// whatever the format.
+///
+/// The serde implementation of `Duration` writes a struct of `secs` and
+/// `nanos`, which is rarely what the consumer of a JSON document expects, and
+/// makes the output depend on the way each format encodes structs.
+/// `DurationSecs` always goes through `serialize_f64` instead, so transcoding
+/// pipelines get the same number in every format.
+///
+/// Deserializing accepts any number of seconds, floating-point or integral.
+/// Negative, overflowing and non-finite numbers are rejected. As `f64` keeps
+/// 53 bits of precision, durations longer than about 104 days lose some
+/// nanoseconds in the round trip.
+///
+/// # Examples
+///
+/// ```
+/// # use std::time::Duration;
+/// # use dyn_serde::ser::DurationSecs;
+/// #
+/// let timeout = DurationSecs(Duration::from_millis(1500));
+/// assert_eq!(serde_json::to_string(&timeout).unwrap(), "1.5");
+///
+/// let timeout: DurationSecs = serde_json::from_str("2").unwrap();
+/// assert_eq!(timeout.0, Duration::from_secs(2));
+/// ```
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct DurationSecs(pub Duration);
+
+impl From<Duration> for DurationSecs {
+    fn from(duration: Duration) -> Self {
+        DurationSecs(duration)
+    }
+}
+
+impl From<DurationSecs> for Duration {
+    fn from(duration: DurationSecs) -> Self {
+        duration.0
+    }
+}
+
+impl serde::Serialize for DurationSecs {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.serialize_f64(self.0.as_secs_f64())
+    }
+}
+
+impl<'de> serde::Deserialize<'de> for DurationSecs {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        deserializer.deserialize_f64(SecsVisitor)
+    }
+}
+
+struct SecsVisitor;
+
+impl serde::de::Visitor<'_> for SecsVisitor {
+    type Value = DurationSecs;
+
+    fn expecting(&self, formatter: &mut Formatter) -> fmt::Result {
+        formatter.write_str("a non-negative number of seconds")
+    }
+
+    fn visit_f64<E>(self, v: f64) -> Result<Self::Value, E>
+    where
+        E: serde::de::Error,
+    {
+        Duration::try_from_secs_f64(v)
+            .map(DurationSecs)
+            .map_err(|_| E::invalid_value(serde::de::Unexpected::Float(v), &self))
+    }
+
+    fn visit_i64<E>(self, v: i64) -> Result<Self::Value, E>
+    where
+        E: serde::de::Error,
+    {
+        u64::try_from(v)
+            .map(|secs| DurationSecs(Duration::from_secs(secs)))
+            .map_err(|_| E::invalid_value(serde::de::Unexpected::Signed(v), &self))
+    }
+
+    fn visit_u64<E>(self, v: u64) -> Result<Self::Value, E> {
+        Ok(DurationSecs(Duration::from_secs(v)))
+    }
+}
@@ -0,0 +1,488 @@
+//! A [`Serializer`] wrapper writing empty collections as `null`, see
+//! [`EmptyAsNull`].
+
+use core::fmt::{self, Debug, Display, Formatter};
+use core::mem;
+
+use serde::ser::Error as _;
+
+use crate::ser::middleware::{Begin, Compound, Element};
+use crate::ser::{InplaceSerializeError, InplaceSerializeResult, InplaceSerializer};
+use crate::ser::{InplaceSerializerState, Serialize, Serializer};
+use crate::ser::{SerializeMap, SerializeSeq, SerializeStruct, SerializeStructVariant};
+use crate::ser::{SerializeTuple, SerializeTupleStruct, SerializeTupleVariant};
+
+/// A [`Serializer`] wrapper which writes every sequence and map without
+/// elements as `None`, i.e. `null` in JSON, rather than `[]` or `{}`.
+///
+/// Some consumers treat an empty array or object as missing, or reject it
+/// where they expect `null`. A sequence or a map begun with a length of `0`,
+/// or without a length, is held back until its first element, so that it is
+/// only begun on the wrapped serializer once it is known to be non-empty, and
+/// one which ends before any element serializes as `None` instead. Nothing is
+/// buffered but the call beginning it, and the elements are written as they
+/// come. A sequence or a map begun with another length is forwarded as is,
+/// as are tuples, structs and variants, empty or not.
+///
+/// The collections are written as `null` at every depth of the value, e.g. in
+/// the fields of a struct, the entries of a map or behind `Some`.
+///
+/// # Examples
+///
+/// ```
+/// # use std::collections::HashMap;
+/// # use dyn_serde::{Serialize, Serializer};
+/// # use dyn_serde::ser::EmptyAsNull;
+/// #
+/// #[derive(serde::Serialize)]
+/// struct User {
+///     name: &'static str,
+///     roles: Vec<&'static str>,
+///     labels: HashMap<&'static str, &'static str>,
+/// }
+///
+/// let user = User { name: "ferris", roles: vec![], labels: HashMap::new() };
+///
+/// let mut buf = Vec::new();
+/// let mut serializer = serde_json::Serializer::new(&mut buf);
+/// let mut serializer = <dyn Serializer>::new(&mut serializer);
+/// (&user as &dyn Serialize)
+///     .dyn_serialize(&mut EmptyAsNull::new(&mut serializer))
+///     .unwrap();
+/// assert_eq!(buf, br#"{"name":"ferris","roles":null,"labels":null}"#);
+/// ```
+pub struct EmptyAsNull<'a> {
+    state: State<'a>,
+    /// Whether the wrapped serializer is human-readable, captured before it is
+    /// consumed.
+    human_readable: bool,
+}
+
+enum State<'a> {
+    None,
+    Ok,
+    Error,
+    Serializer(&'a mut dyn Serializer),
+    /// A sequence or a map without elements so far, not begun yet.
+    Pending(&'a mut dyn Serializer, Begin),
+    Compound(Compound<'a>),
+}
+
+impl<'a> EmptyAsNull<'a> {
+    /// Wraps `serializer`, writing the empty sequences and maps as `None`.
+    #[must_use]
+    pub fn new(serializer: &'a mut dyn Serializer) -> Self {
+        EmptyAsNull {
+            human_readable: serializer.dyn_is_human_readable(),
+            state: State::Serializer(serializer),
+        }
+    }
+
+    fn state(&self) -> InplaceSerializerState {
+        match self.state {
+            State::None => InplaceSerializerState::None,
+            State::Ok => InplaceSerializerState::Ok,
+            State::Error => InplaceSerializerState::Error,
+            State::Serializer(_) => InplaceSerializerState::Serializer,
+            State::Pending(_, Begin::Map(_)) => InplaceSerializerState::SerializeMap,
+            State::Pending(..) => InplaceSerializerState::SerializeSeq,
+            State::Compound(ref compound) => compound.state(),
+        }
+    }
+
+    fn take(&mut self) -> InplaceSerializeResult<&'a mut dyn Serializer> {
+        match mem::replace(&mut self.state, State::None) {
+            State::Serializer(serializer) => Ok(serializer),
+            other => {
+                self.state = other;
+                Err(InplaceSerializeError::NotSerializer)
+            }
+        }
+    }
+
+    /// Moves to the state of `result`, forwarding its error.
+    fn then(&mut self, result: InplaceSerializeResult<State<'a>>) -> InplaceSerializeResult<()> {
+        match result {
+            Ok(state) => {
+                self.state = state;
+                Ok(())
+            }
+            Err(error) => {
+                self.state = State::Error;
+                Err(error)
+            }
+        }
+    }
+
+    fn serialize(
+        &mut self,
+        call: impl FnOnce(&mut dyn Serializer) -> InplaceSerializeResult<()>,
+    ) -> InplaceSerializeResult<()> {
+        let serializer = self.take()?;
+        let result = (call)(serializer).map(|()| State::Ok);
+        self.then(result)
+    }
+
+    fn begin(&mut self, begin: Begin) -> InplaceSerializeResult<()> {
+        let serializer = self.take()?;
+        let result = match begin {
+            Begin::Seq(None | Some(0)) | Begin::Map(None | Some(0)) => {
+                Ok(State::Pending(serializer, begin))
+            }
+            begin => begin.forward(serializer).map(State::Compound),
+        };
+        self.then(result)
+    }
+
+    fn element(
+        &mut self,
+        not_ready: InplaceSerializeError,
+        element: Element<'_>,
+    ) -> InplaceSerializeResult<()> {
+        if let State::Pending(..) = self.state
+            && let State::Pending(serializer, begin) = mem::replace(&mut self.state, State::None)
+        {
+            let result = begin.forward(serializer).map(State::Compound);
+            self.then(result)?;
+        }
+        let State::Compound(ref mut compound) = self.state else {
+            return Err(not_ready);
+        };
+        let result = element.forward(compound);
+        if result.is_err() {
+            self.state = State::Error;
+        }
+        result
+    }
+
+    fn end(&mut self, not_ready: InplaceSerializeError) -> InplaceSerializeResult<()> {
+        let result = match mem::replace(&mut self.state, State::None) {
+            State::Pending(serializer, _) => serializer.dyn_serialize_none(),
+            State::Compound(compound) => compound.end(),
+            other => {
+                self.state = other;
+                return Err(not_ready);
+            }
+        };
+        self.then(result.map(|()| State::Ok))
+    }
+}
+
+impl Debug for EmptyAsNull<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("EmptyAsNull")
+            .field("state", &self.state())
+            .finish_non_exhaustive()
+    }
+}
+
+/// A value serialized through an [`EmptyAsNull`] as well.
+struct Nested<'a>(&'a dyn Serialize);
+
+impl serde::Serialize for Nested<'_> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        let mut serializer = InplaceSerializer::Serializer(serializer);
+        let result = self.0.dyn_serialize(&mut EmptyAsNull::new(&mut serializer));
+        match (serializer, result) {
+            (InplaceSerializer::Ok(ok, _), Ok(())) => Ok(ok),
+            (InplaceSerializer::Error(error, _), _) => Err(error),
+            (_, Err(error)) => Err(error.into_error()),
+            (_, Ok(())) => Err(S::Error::custom(InplaceSerializeError::NotSerializer)),
+        }
+    }
+}
+
+impl Serializer for EmptyAsNull<'_> {
+    fn dyn_serialize_bool(&mut self, v: bool) -> InplaceSerializeResult<()> {
+        self.serialize(|ser| ser.dyn_serialize_bool(v))
+    }
+
+    fn dyn_serialize_i8(&mut self, v: i8) -> InplaceSerializeResult<()> {
+        self.serialize(|ser| ser.dyn_serialize_i8(v))
+    }
+
+    fn dyn_serialize_i16(&mut self, v: i16) -> InplaceSerializeResult<()> {
+        self.serialize(|ser| ser.dyn_serialize_i16(v))
+    }
+
+    fn dyn_serialize_i32(&mut self, v: i32) -> InplaceSerializeResult<()> {
+        self.serialize(|ser| ser.dyn_serialize_i32(v))
+    }
+
+    fn dyn_serialize_i64(&mut self, v: i64) -> InplaceSerializeResult<()> {
+        self.serialize(|ser| ser.dyn_serialize_i64(v))
+    }
+
+    fn dyn_serialize_i128(&mut self, v: i128) -> InplaceSerializeResult<()> {
+        self.serialize(|ser| ser.dyn_serialize_i128(v))
+    }
+
+    fn dyn_serialize_u8(&mut self, v: u8) -> InplaceSerializeResult<()> {
+        self.serialize(|ser| ser.dyn_serialize_u8(v))
+    }
+
+    fn dyn_serialize_u16(&mut self, v: u16) -> InplaceSerializeResult<()> {
+        self.serialize(|ser| ser.dyn_serialize_u16(v))
+    }
+
+    fn dyn_serialize_u32(&mut self, v: u32) -> InplaceSerializeResult<()> {
+        self.serialize(|ser| ser.dyn_serialize_u32(v))
+    }
+
+    fn dyn_serialize_u64(&mut self, v: u64) -> InplaceSerializeResult<()> {
+        self.serialize(|ser| ser.dyn_serialize_u64(v))
+    }
+
+    fn dyn_serialize_u128(&mut self, v: u128) -> InplaceSerializeResult<()> {
+        self.serialize(|ser| ser.dyn_serialize_u128(v))
+    }
+
+    fn dyn_serialize_f32(&mut self, v: f32) -> InplaceSerializeResult<()> {
+        self.serialize(|ser| ser.dyn_serialize_f32(v))
+    }
+
+    fn dyn_serialize_f64(&mut self, v: f64) -> InplaceSerializeResult<()> {
+        self.serialize(|ser| ser.dyn_serialize_f64(v))
+    }
+
+    fn dyn_serialize_char(&mut self, v: char) -> InplaceSerializeResult<()> {
+        self.serialize(|ser| ser.dyn_serialize_char(v))
+    }
+
+    fn dyn_serialize_str(&mut self, v: &str) -> InplaceSerializeResult<()> {
+        self.serialize(|ser| ser.dyn_serialize_str(v))
+    }
+
+    fn dyn_serialize_bytes(&mut self, v: &[u8]) -> InplaceSerializeResult<()> {
+        self.serialize(|ser| ser.dyn_serialize_bytes(v))
+    }
+
+    fn dyn_serialize_none(&mut self) -> InplaceSerializeResult<()> {
+        self.serialize(|ser| ser.dyn_serialize_none())
+    }
+
+    fn dyn_serialize_some(&mut self, value: &dyn Serialize) -> InplaceSerializeResult<()> {
+        self.serialize(|ser| ser.dyn_serialize_some(&Nested(value)))
+    }
+
+    fn dyn_serialize_unit(&mut self) -> InplaceSerializeResult<()> {
+        self.serialize(|ser| ser.dyn_serialize_unit())
+    }
+
+    fn dyn_serialize_unit_struct(&mut self, name: &'static str) -> InplaceSerializeResult<()> {
+        self.serialize(|ser| ser.dyn_serialize_unit_struct(name))
+    }
+
+    fn dyn_serialize_unit_variant(
+        &mut self,
+        name: &'static str,
+        variant_index: u32,
+        variant: &'static str,
+    ) -> InplaceSerializeResult<()> {
+        self.serialize(|ser| ser.dyn_serialize_unit_variant(name, variant_index, variant))
+    }
+
+    fn dyn_serialize_newtype_struct(
+        &mut self,
+        name: &'static str,
+        value: &dyn Serialize,
+    ) -> InplaceSerializeResult<()> {
+        self.serialize(|ser| ser.dyn_serialize_newtype_struct(name, &Nested(value)))
+    }
+
+    fn dyn_serialize_newtype_variant(
+        &mut self,
+        name: &'static str,
+        variant_index: u32,
+        variant: &'static str,
+        value: &dyn Serialize,
+    ) -> InplaceSerializeResult<()> {
+        self.serialize(|ser| {
+            ser.dyn_serialize_newtype_variant(name, variant_index, variant, &Nested(value))
+        })
+    }
+
+    fn dyn_serialize_seq(
+        &mut self,
+        len: Option<usize>,
+    ) -> InplaceSerializeResult<&mut dyn SerializeSeq> {
+        self.begin(Begin::Seq(len))?;
+        Ok(self)
+    }
+
+    fn dyn_serialize_tuple(
+        &mut self,
+        len: usize,
+    ) -> InplaceSerializeResult<&mut dyn SerializeTuple> {
+        self.begin(Begin::Tuple(len))?;
+        Ok(self)
+    }
+
+    fn dyn_serialize_tuple_struct(
+        &mut self,
+        name: &'static str,
+        len: usize,
+    ) -> InplaceSerializeResult<&mut dyn SerializeTupleStruct> {
+        self.begin(Begin::TupleStruct(name, len))?;
+        Ok(self)
+    }
+
+    fn dyn_serialize_tuple_variant(
+        &mut self,
+        name: &'static str,
+        variant_index: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> InplaceSerializeResult<&mut dyn SerializeTupleVariant> {
+        self.begin(Begin::TupleVariant(name, variant_index, variant, len))?;
+        Ok(self)
+    }
+
+    fn dyn_serialize_map(
+        &mut self,
+        len: Option<usize>,
+    ) -> InplaceSerializeResult<&mut dyn SerializeMap> {
+        self.begin(Begin::Map(len))?;
+        Ok(self)
+    }
+
+    fn dyn_serialize_struct(
+        &mut self,
+        name: &'static str,
+        len: usize,
+    ) -> InplaceSerializeResult<&mut dyn SerializeStruct> {
+        self.begin(Begin::Struct(name, len))?;
+        Ok(self)
+    }
+
+    fn dyn_serialize_struct_variant(
+        &mut self,
+        name: &'static str,
+        variant_index: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> InplaceSerializeResult<&mut dyn SerializeStructVariant> {
+        self.begin(Begin::StructVariant(name, variant_index, variant, len))?;
+        Ok(self)
+    }
+
+    fn dyn_collect_str(&mut self, value: &dyn Display) -> InplaceSerializeResult<()> {
+        self.serialize(|ser| ser.dyn_collect_str(value))
+    }
+
+    fn dyn_is_human_readable(&self) -> bool {
+        self.human_readable
+    }
+}
+
+impl SerializeSeq for EmptyAsNull<'_> {
+    fn dyn_serialize_element(&mut self, value: &dyn Serialize) -> InplaceSerializeResult<()> {
+        let not_ready = InplaceSerializeError::NotSerializeSeq;
+        self.element(not_ready, Element::Item(&Nested(value)))
+    }
+
+    fn dyn_end(&mut self) -> InplaceSerializeResult<()> {
+        self.end(InplaceSerializeError::NotSerializeSeq)
+    }
+}
+
+impl SerializeTuple for EmptyAsNull<'_> {
+    fn dyn_serialize_element(&mut self, value: &dyn Serialize) -> InplaceSerializeResult<()> {
+        let not_ready = InplaceSerializeError::NotSerializeTuple;
+        self.element(not_ready, Element::Item(&Nested(value)))
+    }
+
+    fn dyn_end(&mut self) -> InplaceSerializeResult<()> {
+        self.end(InplaceSerializeError::NotSerializeTuple)
+    }
+}
+
+impl SerializeTupleStruct for EmptyAsNull<'_> {
+    fn dyn_serialize_field(&mut self, value: &dyn Serialize) -> InplaceSerializeResult<()> {
+        let not_ready = InplaceSerializeError::NotSerializeTupleStruct;
+        self.element(not_ready, Element::Field(&Nested(value)))
+    }
+
+    fn dyn_end(&mut self) -> InplaceSerializeResult<()> {
+        self.end(InplaceSerializeError::NotSerializeTupleStruct)
+    }
+}
+
+impl SerializeTupleVariant for EmptyAsNull<'_> {
+    fn dyn_serialize_field(&mut self, value: &dyn Serialize) -> InplaceSerializeResult<()> {
+        let not_ready = InplaceSerializeError::NotSerializeTupleVariant;
+        self.element(not_ready, Element::Field(&Nested(value)))
+    }
+
+    fn dyn_end(&mut self) -> InplaceSerializeResult<()> {
+        self.end(InplaceSerializeError::NotSerializeTupleVariant)
+    }
+}
+
+impl SerializeMap for EmptyAsNull<'_> {
+    fn dyn_serialize_key(&mut self, key: &dyn Serialize) -> InplaceSerializeResult<()> {
+        let not_ready = InplaceSerializeError::NotSerializeMap;
+        self.element(not_ready, Element::Key(&Nested(key)))
+    }
+
+    fn dyn_serialize_value(&mut self, value: &dyn Serialize) -> InplaceSerializeResult<()> {
+        let not_ready = InplaceSerializeError::NotSerializeMap;
+        self.element(not_ready, Element::Value(&Nested(value)))
+    }
+
+    fn dyn_serialize_entry(
+        &mut self,
+        key: &dyn Serialize,
+        value: &dyn Serialize,
+    ) -> InplaceSerializeResult<()> {
+        let not_ready = InplaceSerializeError::NotSerializeMap;
+        self.element(not_ready, Element::Entry(&Nested(key), &Nested(value)))
+    }
+
+    fn dyn_end(&mut self) -> InplaceSerializeResult<()> {
+        self.end(InplaceSerializeError::NotSerializeMap)
+    }
+}
+
+impl SerializeStruct for EmptyAsNull<'_> {
+    fn dyn_serialize_field(
+        &mut self,
+        key: &'static str,
+        value: &dyn Serialize,
+    ) -> InplaceSerializeResult<()> {
+        let not_ready = InplaceSerializeError::NotSerializeStruct;
+        self.element(not_ready, Element::StructField(key, &Nested(value)))
+    }
+
+    fn dyn_skip_field(&mut self, key: &'static str) -> InplaceSerializeResult<()> {
+        let not_ready = InplaceSerializeError::NotSerializeStruct;
+        self.element(not_ready, Element::SkipField(key))
+    }
+
+    fn dyn_end(&mut self) -> InplaceSerializeResult<()> {
+        self.end(InplaceSerializeError::NotSerializeStruct)
+    }
+}
+
+impl SerializeStructVariant for EmptyAsNull<'_> {
+    fn dyn_serialize_field(
+        &mut self,
+        key: &'static str,
+        value: &dyn Serialize,
+    ) -> InplaceSerializeResult<()> {
+        let not_ready = InplaceSerializeError::NotSerializeStructVariant;
+        self.element(not_ready, Element::StructField(key, &Nested(value)))
+    }
+
+    fn dyn_skip_field(&mut self, key: &'static str) -> InplaceSerializeResult<()> {
+        let not_ready = InplaceSerializeError::NotSerializeStructVariant;
+        self.element(not_ready, Element::SkipField(key))
+    }
+
+    fn dyn_end(&mut self) -> InplaceSerializeResult<()> {
+        self.end(InplaceSerializeError::NotSerializeStructVariant)
+    }
+}
@@ -0,0 +1,146 @@
+//! A [`Serializer`] wrapper measuring the output of every field, see
+//! [`FieldSizes`].
+
+use core::cell::{Cell, RefCell};
+use core::fmt::{self, Debug, Formatter};
+
+#[cfg(not(feature = "std"))]
+use alloc::collections::BTreeMap;
+#[cfg(feature = "std")]
+use std::collections::BTreeMap;
+
+use crate::ser::middleware::{self, Begin, Compound, Element};
+use crate::ser::middleware::{Middleware, MiddlewareSerializer};
+use crate::ser::{SerializeResult, Serializer};
+
+/// A [`Serializer`] wrapper which records how many bytes every field of the
+/// top-level struct contributes to the output, e.g. to find the fields that
+/// make a large response large.
+///
+/// Like [`SizeLimited`], the size of the output is the number of bytes
+/// actually written by the wrapped serializer, read from the `written` counter
+/// given to [`FieldSizes::new`], which the writer of the serializer is expected
+/// to update. The bytes written while a field is serialized are added to the
+/// entry of its name in the `sizes` map, including its key and separators as
+/// the format writes them. The bytes written when the struct begins and ends,
+/// e.g. its braces, belong to no field.
+///
+/// Only the fields of the top-level struct or struct variant are measured,
+/// possibly behind `Some` or a newtype struct; nested structs count towards the
+/// field containing them. Bytes buffered by the serializer are only counted
+/// once they are written, so the writer should not buffer.
+///
+/// # Examples
+///
+/// ```
+/// # use std::cell::Cell;
+/// # use std::collections::BTreeMap;
+/// # use std::io::{self, Write};
+/// # use dyn_serde::{Serialize, Serializer};
+/// # use dyn_serde::ser::FieldSizes;
+/// #
+/// struct Counter<'a>(Vec<u8>, &'a Cell<usize>);
+///
+/// impl Write for Counter<'_> {
+///     fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+///         self.1.set(self.1.get() + buf.len());
+///         self.0.write(buf)
+///     }
+///
+///     fn flush(&mut self) -> io::Result<()> {
+///         Ok(())
+///     }
+/// }
+///
+/// #[derive(serde::Serialize)]
+/// struct Page {
+///     id: u32,
+///     body: String,
+/// }
+///
+/// let page = Page { id: 1, body: "a".repeat(100) };
+/// let written = Cell::new(0);
+/// let mut sizes = BTreeMap::new();
+/// {
+///     let mut serializer = serde_json::Serializer::new(Counter(Vec::new(), &written));
+///     let mut serializer = <dyn Serializer>::new(&mut serializer);
+///     let mut serializer = FieldSizes::new(&mut serializer, &written, &mut sizes);
+///     (&page as &dyn Serialize).dyn_serialize(&mut serializer).unwrap();
+/// }
+/// assert_eq!(sizes, BTreeMap::from([("id", 6), ("body", 110)]));
+/// ```
+///
+/// [`SizeLimited`]: crate::ser::SizeLimited
+pub struct FieldSizes<'a> {
+    serializer: MiddlewareSerializer<'a, Measure<'a>>,
+}
+
+impl<'a> FieldSizes<'a> {
+    /// Wraps `serializer`, adding the bytes counted by `written` while each
+    /// field is serialized to `sizes`.
+    #[must_use]
+    pub fn new(
+        serializer: &'a mut dyn Serializer,
+        written: &'a Cell<usize>,
+        sizes: &'a mut BTreeMap<&'static str, usize>,
+    ) -> Self {
+        let measure = Measure {
+            written,
+            sizes: RefCell::new(sizes),
+            depth: Cell::new(0),
+        };
+        FieldSizes {
+            serializer: MiddlewareSerializer::new(serializer, measure),
+        }
+    }
+}
+
+impl Debug for FieldSizes<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("FieldSizes")
+            .field("sizes", &self.serializer.middleware().sizes.borrow())
+            .finish_non_exhaustive()
+    }
+}
+
+impl Serializer for FieldSizes<'_> {
+    middleware::forward_to_middleware_serializer!(serializer);
+}
+
+struct Measure<'a> {
+    written: &'a Cell<usize>,
+    sizes: RefCell<&'a mut BTreeMap<&'static str, usize>>,
+    /// The number of compounds being serialized.
+    depth: Cell<usize>,
+}
+
+impl Middleware for Measure<'_> {
+    fn begin<'a>(
+        &self,
+        begin: Begin,
+        serializer: &'a mut dyn Serializer,
+    ) -> SerializeResult<Compound<'a>> {
+        let compound = begin.forward(serializer)?;
+        self.depth.set(self.depth.get() + 1);
+        Ok(compound)
+    }
+
+    fn element(&self, element: Element<'_>, compound: &mut Compound<'_>) -> SerializeResult<()> {
+        let key = match element {
+            Element::StructField(key, _) if self.depth.get() == 1 => key,
+            _ => return Ok(element.forward(compound)?),
+        };
+
+        let before = self.written.get();
+        element.forward(compound)?;
+        let size = self.written.get().saturating_sub(before);
+        *self.sizes.borrow_mut().entry(key).or_default() += size;
+        Ok(())
+    }
+
+    fn end(&self, compound: Compound<'_>) -> SerializeResult<()> {
+        compound.end()?;
+        self.depth.set(self.depth.get() - 1);
+        Ok(())
+    }
+}
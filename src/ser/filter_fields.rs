@@ -0,0 +1,101 @@
+//! A [`Serializer`] wrapper dropping the struct fields rejected by a
+//! predicate, see [`FilterFields`].
+
+use core::fmt::{self, Debug, Formatter};
+
+use serde_json::Value;
+
+use crate::ser::middleware::{self, Compound, Element};
+use crate::ser::middleware::{Middleware, MiddlewareSerializer};
+use crate::ser::{SerializeResult, Serializer};
+
+/// A [`Serializer`] wrapper which writes only the struct fields for which a
+/// predicate, given the name of the field and its value, returns `true`.
+///
+/// Omitting `None` fields, empty strings or fields holding secrets are all
+/// decisions on a field and its value. `FilterFields` captures the value of
+/// every field of every struct and struct variant into a
+/// [`serde_json::Value`], at every depth, and hands it to `predicate` with the
+/// name of the field: the field is written if it returns `true`, and reported
+/// to the wrapped serializer as skipped otherwise. The value written is the
+/// field itself rather than the capture, so that nothing is lost on the way.
+///
+/// A field is captured as it is written, i.e. with the fields of the structs
+/// inside it already filtered, so that `predicate` sees what would be written.
+/// Every field is therefore serialized once to be captured for each struct
+/// around it, calling `predicate` again on its own fields each time, and once
+/// to the output. Fields which fail to be captured, e.g. maps with keys which
+/// are neither strings nor numbers, are written without calling `predicate`.
+/// The entries of maps are left unchanged.
+///
+/// # Examples
+///
+/// ```
+/// # use dyn_serde::{Serialize, Serializer};
+/// # use dyn_serde::ser::FilterFields;
+/// #
+/// #[derive(serde::Serialize)]
+/// struct User {
+///     name: &'static str,
+///     nickname: &'static str,
+/// }
+///
+/// let mut buf = Vec::new();
+/// let mut serializer = serde_json::Serializer::new(&mut buf);
+/// let mut serializer = <dyn Serializer>::new(&mut serializer);
+/// let mut serializer = FilterFields::new(&mut serializer, |_, value| value != "");
+///
+/// let user = User { name: "ferris", nickname: "" };
+/// (&user as &dyn Serialize).dyn_serialize(&mut serializer).unwrap();
+/// assert_eq!(buf, br#"{"name":"ferris"}"#);
+/// ```
+pub struct FilterFields<'a, F> {
+    serializer: MiddlewareSerializer<'a, Filter<F>>,
+}
+
+impl<'a, F> FilterFields<'a, F>
+where
+    F: Fn(&'static str, &Value) -> bool,
+{
+    /// Wraps `serializer`, writing only the fields for which `predicate`
+    /// returns `true`.
+    #[must_use]
+    pub fn new(serializer: &'a mut dyn Serializer, predicate: F) -> Self {
+        FilterFields {
+            serializer: MiddlewareSerializer::new(serializer, Filter { predicate }),
+        }
+    }
+}
+
+impl<F> Debug for FilterFields<'_, F> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("FilterFields").finish_non_exhaustive()
+    }
+}
+
+impl<F> Serializer for FilterFields<'_, F>
+where
+    F: Fn(&'static str, &Value) -> bool,
+{
+    middleware::forward_to_middleware_serializer!(serializer);
+}
+
+struct Filter<F> {
+    predicate: F,
+}
+
+impl<F> Middleware for Filter<F>
+where
+    F: Fn(&'static str, &Value) -> bool,
+{
+    fn element(&self, element: Element<'_>, compound: &mut Compound<'_>) -> SerializeResult<()> {
+        let element = match element {
+            Element::StructField(key, value) => match serde_json::to_value(value) {
+                Ok(captured) if !(self.predicate)(key, &captured) => Element::SkipField(key),
+                _ => Element::StructField(key, value),
+            },
+            element => element,
+        };
+        Ok(element.forward(compound)?)
+    }
+}
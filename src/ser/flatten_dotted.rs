@@ -0,0 +1,506 @@
+//! Flat maps of dotted keys, see [`FlattenDotted`].
+
+use core::fmt::{self, Debug, Formatter, Write as _};
+
+#[cfg(not(feature = "std"))]
+use alloc::string::String;
+#[cfg(not(feature = "std"))]
+use alloc::vec::Vec;
+
+use serde::ser::Error as _;
+
+use crate::ser::Serializer;
+use crate::ser::canonical::serialize_key;
+use crate::ser::{InplaceSerializeError, InplaceSerializeResult, InplaceSerializerState};
+use crate::ser::{Serialize, SerializeMap, SerializeSeq, SerializeStruct, SerializeStructVariant};
+use crate::ser::{SerializeError, SerializeTuple, SerializeTupleStruct, SerializeTupleVariant};
+
+/// A [`Serializer`] wrapper which writes a nested value as a single flat map
+/// from dotted paths to scalars, e.g. `{"a.b.c": 1}`, for systems which do
+/// not support nesting, such as environment variables.
+///
+/// The fields of structs, the entries of maps and the elements of sequences
+/// and tuples are walked at every depth, and every scalar found is written to
+/// the wrapped serializer as an entry of a map, keyed by the names of the
+/// fields, the keys of the entries and the indices of the elements leading to
+/// it, joined with dots. Map keys are written as strings, like `serde_json`
+/// does, and variants add their name to the path, like an externally tagged
+/// enum. Unit variants are written as their name, while `None`, `()` and
+/// empty compound values are written as nothing.
+///
+/// Only a compound value can be flattened. Dots inside names and keys are not
+/// escaped, so that such keys read back as deeper paths. The map is read back
+/// as a nested value by `UnflattenDotted`, which requires feature
+/// `serde_json`.
+///
+/// The serialization returns an error telling only that it has done
+/// unsuccessfully, while [`FlattenDotted::error`] tells why it has, unless
+/// the wrapped serializer has failed.
+///
+/// # Examples
+///
+/// ```
+/// # use dyn_serde::{Serialize, Serializer};
+/// # use dyn_serde::ser::FlattenDotted;
+/// #
+/// #[derive(serde::Serialize)]
+/// struct Config {
+///     server: Server,
+///     debug: bool,
+/// }
+///
+/// #[derive(serde::Serialize)]
+/// struct Server {
+///     host: &'static str,
+///     port: u16,
+/// }
+///
+/// let mut buf = Vec::new();
+/// let mut serializer = serde_json::Serializer::new(&mut buf);
+/// let mut serializer = <dyn Serializer>::new(&mut serializer);
+/// let mut serializer = FlattenDotted::new(&mut serializer);
+///
+/// let config = Config { server: Server { host: "localhost", port: 80 }, debug: false };
+/// (&config as &dyn Serialize).dyn_serialize(&mut serializer).unwrap();
+/// assert_eq!(buf, br#"{"server.host":"localhost","server.port":80,"debug":false}"#);
+/// ```
+pub struct FlattenDotted<'a> {
+    /// The wrapped serializer, until the map is begun.
+    serializer: Option<&'a mut dyn Serializer>,
+    /// The map being written, until it is ended.
+    map: Option<&'a mut dyn SerializeMap>,
+    human_readable: bool,
+    /// The path of the value being serialized.
+    path: String,
+    frames: Vec<Frame>,
+    error: Option<SerializeError>,
+}
+
+struct Frame {
+    state: InplaceSerializerState,
+    /// The length of the path before the variant, if any, was added to it.
+    outer: usize,
+    /// The length of the path of the compound value itself.
+    base: usize,
+    /// The index of the next element.
+    index: usize,
+}
+
+impl<'a> FlattenDotted<'a> {
+    /// Wraps `serializer`, writing the value as a flat map of dotted keys.
+    #[must_use]
+    pub fn new(serializer: &'a mut dyn Serializer) -> Self {
+        FlattenDotted {
+            human_readable: serializer.dyn_is_human_readable(),
+            serializer: Some(serializer),
+            map: None,
+            path: String::new(),
+            frames: Vec::new(),
+            error: None,
+        }
+    }
+
+    /// Returns the first error raised while serializing, if any.
+    #[must_use]
+    pub fn error(&self) -> Option<&SerializeError> {
+        self.error.as_ref()
+    }
+
+    #[cold]
+    fn fail(&mut self, error: SerializeError) -> InplaceSerializeError {
+        self.error.get_or_insert(error);
+        InplaceSerializeError::Error
+    }
+
+    /// Adds a segment to the path.
+    fn push(&mut self, segment: impl fmt::Display) {
+        if !self.path.is_empty() {
+            self.path.push('.');
+        }
+        // writing to a `String` never fails.
+        let _ = write!(self.path, "{segment}");
+    }
+
+    fn serialize_value(&mut self, value: &dyn Serialize) -> InplaceSerializeResult<()> {
+        value.dyn_serialize(self).map_err(|error| self.fail(error))
+    }
+
+    /// Writes a scalar as an entry keyed by the current path.
+    fn scalar(&mut self, value: &dyn Serialize) -> InplaceSerializeResult<()> {
+        match &mut self.map {
+            Some(map) if !self.frames.is_empty() => map.dyn_serialize_entry(&self.path, value),
+            _ => Err(self.fail(SerializeError::custom(
+                "only a compound value can be flattened",
+            ))),
+        }
+    }
+
+    fn begin(
+        &mut self,
+        variant: Option<&'static str>,
+        state: InplaceSerializerState,
+    ) -> InplaceSerializeResult<&mut Self> {
+        if self.frames.is_empty() {
+            let Some(serializer) = self.serializer.take() else {
+                return Err(InplaceSerializeError::NotSerializer);
+            };
+            self.map = Some(serializer.dyn_serialize_map(None)?);
+        }
+
+        let outer = self.path.len();
+        if let Some(variant) = variant {
+            self.push(variant);
+        }
+        self.frames.push(Frame {
+            state,
+            outer,
+            base: self.path.len(),
+            index: 0,
+        });
+        Ok(self)
+    }
+
+    /// Serializes a value of the innermost compound value under `segment`, or
+    /// under its index if `None`.
+    fn element(
+        &mut self,
+        segment: Option<&str>,
+        value: &dyn Serialize,
+        state: InplaceSerializerState,
+        error: InplaceSerializeError,
+    ) -> InplaceSerializeResult<()> {
+        let (base, index) = match self.frames.last_mut() {
+            Some(frame) if frame.state == state => {
+                frame.index += 1;
+                (frame.base, frame.index - 1)
+            }
+            _ => return Err(error),
+        };
+        match segment {
+            Some(segment) => self.push(segment),
+            None => self.push(index),
+        }
+        let result = self.serialize_value(value);
+        self.path.truncate(base);
+        result
+    }
+
+    fn end(
+        &mut self,
+        state: InplaceSerializerState,
+        error: InplaceSerializeError,
+    ) -> InplaceSerializeResult<()> {
+        let frame = match self.frames.pop() {
+            Some(frame) if frame.state == state => frame,
+            frame => {
+                self.frames.extend(frame);
+                return Err(error);
+            }
+        };
+
+        self.path.truncate(frame.outer);
+        match self.map.take() {
+            Some(map) if self.frames.is_empty() => map.dyn_end(),
+            map => {
+                self.map = map;
+                Ok(())
+            }
+        }
+    }
+}
+
+impl Debug for FlattenDotted<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("FlattenDotted")
+            .field("path", &self.path)
+            .field("error", &self.error)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Serializer for FlattenDotted<'_> {
+    fn dyn_serialize_bool(&mut self, v: bool) -> InplaceSerializeResult<()> {
+        self.scalar(&v)
+    }
+
+    fn dyn_serialize_i64(&mut self, v: i64) -> InplaceSerializeResult<()> {
+        self.scalar(&v)
+    }
+
+    fn dyn_serialize_i128(&mut self, v: i128) -> InplaceSerializeResult<()> {
+        self.scalar(&v)
+    }
+
+    fn dyn_serialize_u64(&mut self, v: u64) -> InplaceSerializeResult<()> {
+        self.scalar(&v)
+    }
+
+    fn dyn_serialize_u128(&mut self, v: u128) -> InplaceSerializeResult<()> {
+        self.scalar(&v)
+    }
+
+    fn dyn_serialize_f64(&mut self, v: f64) -> InplaceSerializeResult<()> {
+        self.scalar(&v)
+    }
+
+    fn dyn_serialize_char(&mut self, v: char) -> InplaceSerializeResult<()> {
+        self.scalar(&v)
+    }
+
+    fn dyn_serialize_str(&mut self, v: &str) -> InplaceSerializeResult<()> {
+        self.scalar(&v)
+    }
+
+    fn dyn_serialize_bytes(&mut self, v: &[u8]) -> InplaceSerializeResult<()> {
+        self.begin(None, InplaceSerializerState::SerializeSeq)?;
+        for byte in v {
+            SerializeSeq::dyn_serialize_element(self, byte)?;
+        }
+        SerializeSeq::dyn_end(self)
+    }
+
+    fn dyn_serialize_none(&mut self) -> InplaceSerializeResult<()> {
+        self.dyn_serialize_unit()
+    }
+
+    fn dyn_serialize_some(&mut self, value: &dyn Serialize) -> InplaceSerializeResult<()> {
+        self.serialize_value(value)
+    }
+
+    fn dyn_serialize_unit(&mut self) -> InplaceSerializeResult<()> {
+        if self.frames.is_empty() {
+            return Err(self.fail(SerializeError::custom(
+                "only a compound value can be flattened",
+            )));
+        }
+        Ok(())
+    }
+
+    fn dyn_serialize_unit_variant(
+        &mut self,
+        name: &'static str,
+        variant_index: u32,
+        variant: &'static str,
+    ) -> InplaceSerializeResult<()> {
+        let _ = (name, variant_index);
+        self.scalar(&variant)
+    }
+
+    fn dyn_serialize_newtype_struct(
+        &mut self,
+        name: &'static str,
+        value: &dyn Serialize,
+    ) -> InplaceSerializeResult<()> {
+        let _ = name;
+        self.serialize_value(value)
+    }
+
+    fn dyn_serialize_newtype_variant(
+        &mut self,
+        name: &'static str,
+        variant_index: u32,
+        variant: &'static str,
+        value: &dyn Serialize,
+    ) -> InplaceSerializeResult<()> {
+        let _ = (name, variant_index);
+        // the variant adds its name to the path like the field of a struct.
+        self.begin(None, InplaceSerializerState::SerializeStructVariant)?;
+        SerializeStructVariant::dyn_serialize_field(self, variant, value)?;
+        SerializeStructVariant::dyn_end(self)
+    }
+
+    fn dyn_serialize_seq(
+        &mut self,
+        len: Option<usize>,
+    ) -> InplaceSerializeResult<&mut dyn SerializeSeq> {
+        let _ = len;
+        Ok(self.begin(None, InplaceSerializerState::SerializeSeq)?)
+    }
+
+    fn dyn_serialize_tuple(
+        &mut self,
+        len: usize,
+    ) -> InplaceSerializeResult<&mut dyn SerializeTuple> {
+        let _ = len;
+        Ok(self.begin(None, InplaceSerializerState::SerializeTuple)?)
+    }
+
+    fn dyn_serialize_tuple_struct(
+        &mut self,
+        name: &'static str,
+        len: usize,
+    ) -> InplaceSerializeResult<&mut dyn SerializeTupleStruct> {
+        let _ = (name, len);
+        Ok(self.begin(None, InplaceSerializerState::SerializeTupleStruct)?)
+    }
+
+    fn dyn_serialize_tuple_variant(
+        &mut self,
+        name: &'static str,
+        variant_index: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> InplaceSerializeResult<&mut dyn SerializeTupleVariant> {
+        let _ = (name, variant_index, len);
+        Ok(self.begin(Some(variant), InplaceSerializerState::SerializeTupleVariant)?)
+    }
+
+    fn dyn_serialize_map(
+        &mut self,
+        len: Option<usize>,
+    ) -> InplaceSerializeResult<&mut dyn SerializeMap> {
+        let _ = len;
+        Ok(self.begin(None, InplaceSerializerState::SerializeMap)?)
+    }
+
+    fn dyn_serialize_struct(
+        &mut self,
+        name: &'static str,
+        len: usize,
+    ) -> InplaceSerializeResult<&mut dyn SerializeStruct> {
+        let _ = (name, len);
+        Ok(self.begin(None, InplaceSerializerState::SerializeStruct)?)
+    }
+
+    fn dyn_serialize_struct_variant(
+        &mut self,
+        name: &'static str,
+        variant_index: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> InplaceSerializeResult<&mut dyn SerializeStructVariant> {
+        let _ = (name, variant_index, len);
+        Ok(self.begin(
+            Some(variant),
+            InplaceSerializerState::SerializeStructVariant,
+        )?)
+    }
+
+    fn dyn_is_human_readable(&self) -> bool {
+        self.human_readable
+    }
+}
+
+impl SerializeSeq for FlattenDotted<'_> {
+    fn dyn_serialize_element(&mut self, value: &dyn Serialize) -> InplaceSerializeResult<()> {
+        let state = InplaceSerializerState::SerializeSeq;
+        self.element(None, value, state, InplaceSerializeError::NotSerializeSeq)
+    }
+
+    fn dyn_end(&mut self) -> InplaceSerializeResult<()> {
+        let state = InplaceSerializerState::SerializeSeq;
+        self.end(state, InplaceSerializeError::NotSerializeSeq)
+    }
+}
+
+impl SerializeTuple for FlattenDotted<'_> {
+    fn dyn_serialize_element(&mut self, value: &dyn Serialize) -> InplaceSerializeResult<()> {
+        let state = InplaceSerializerState::SerializeTuple;
+        self.element(None, value, state, InplaceSerializeError::NotSerializeTuple)
+    }
+
+    fn dyn_end(&mut self) -> InplaceSerializeResult<()> {
+        let state = InplaceSerializerState::SerializeTuple;
+        self.end(state, InplaceSerializeError::NotSerializeTuple)
+    }
+}
+
+impl SerializeTupleStruct for FlattenDotted<'_> {
+    fn dyn_serialize_field(&mut self, value: &dyn Serialize) -> InplaceSerializeResult<()> {
+        let state = InplaceSerializerState::SerializeTupleStruct;
+        self.element(
+            None,
+            value,
+            state,
+            InplaceSerializeError::NotSerializeTupleStruct,
+        )
+    }
+
+    fn dyn_end(&mut self) -> InplaceSerializeResult<()> {
+        let state = InplaceSerializerState::SerializeTupleStruct;
+        self.end(state, InplaceSerializeError::NotSerializeTupleStruct)
+    }
+}
+
+impl SerializeTupleVariant for FlattenDotted<'_> {
+    fn dyn_serialize_field(&mut self, value: &dyn Serialize) -> InplaceSerializeResult<()> {
+        let state = InplaceSerializerState::SerializeTupleVariant;
+        self.element(
+            None,
+            value,
+            state,
+            InplaceSerializeError::NotSerializeTupleVariant,
+        )
+    }
+
+    fn dyn_end(&mut self) -> InplaceSerializeResult<()> {
+        let state = InplaceSerializerState::SerializeTupleVariant;
+        self.end(state, InplaceSerializeError::NotSerializeTupleVariant)
+    }
+}
+
+impl SerializeMap for FlattenDotted<'_> {
+    fn dyn_serialize_key(&mut self, key: &dyn Serialize) -> InplaceSerializeResult<()> {
+        let base = match self.frames.last() {
+            Some(frame) if frame.state == InplaceSerializerState::SerializeMap => frame.base,
+            _ => return Err(InplaceSerializeError::NotSerializeMap),
+        };
+        let key = serialize_key(key).map_err(|error| self.fail(error))?;
+        self.path.truncate(base);
+        self.push(key);
+        Ok(())
+    }
+
+    fn dyn_serialize_value(&mut self, value: &dyn Serialize) -> InplaceSerializeResult<()> {
+        let base = match self.frames.last() {
+            Some(frame) if frame.state == InplaceSerializerState::SerializeMap => frame.base,
+            _ => return Err(InplaceSerializeError::NotSerializeMap),
+        };
+        let result = self.serialize_value(value);
+        self.path.truncate(base);
+        result
+    }
+
+    fn dyn_end(&mut self) -> InplaceSerializeResult<()> {
+        let state = InplaceSerializerState::SerializeMap;
+        self.end(state, InplaceSerializeError::NotSerializeMap)
+    }
+}
+
+impl SerializeStruct for FlattenDotted<'_> {
+    fn dyn_serialize_field(
+        &mut self,
+        key: &'static str,
+        value: &dyn Serialize,
+    ) -> InplaceSerializeResult<()> {
+        let state = InplaceSerializerState::SerializeStruct;
+        self.element(
+            Some(key),
+            value,
+            state,
+            InplaceSerializeError::NotSerializeStruct,
+        )
+    }
+
+    fn dyn_end(&mut self) -> InplaceSerializeResult<()> {
+        let state = InplaceSerializerState::SerializeStruct;
+        self.end(state, InplaceSerializeError::NotSerializeStruct)
+    }
+}
+
+impl SerializeStructVariant for FlattenDotted<'_> {
+    fn dyn_serialize_field(
+        &mut self,
+        key: &'static str,
+        value: &dyn Serialize,
+    ) -> InplaceSerializeResult<()> {
+        let state = InplaceSerializerState::SerializeStructVariant;
+        let error = InplaceSerializeError::NotSerializeStructVariant;
+        self.element(Some(key), value, state, error)
+    }
+
+    fn dyn_end(&mut self) -> InplaceSerializeResult<()> {
+        let state = InplaceSerializerState::SerializeStructVariant;
+        self.end(state, InplaceSerializeError::NotSerializeStructVariant)
+    }
+}
@@ -0,0 +1,120 @@
+//! Writers framing a payload between a magic header and a checksum, see
+//! [`Framed`].
+
+use std::io;
+
+/// An [`io::Write`] adapter framing the output of a serializer between a magic
+/// header and a trailing checksum, for binary container formats built on top
+/// of a serde backend.
+///
+/// A serializer only sees values, so it cannot write bytes of its own to the
+/// writer of its backend. `Framed` is handed to the backend as its writer
+/// instead: the magic bytes are written to `inner` right before the first
+/// bytes of the payload, and [`Framed::finish`] writes the CRC-32 (IEEE) of
+/// the payload, in little-endian order, once the serialization is complete.
+/// The header and the checksum are not part of the payload, so the checksum
+/// only covers the bytes written by the serializer.
+///
+/// # Examples
+///
+/// ```
+/// # use dyn_serde::{Serialize, Serializer};
+/// # use dyn_serde::ser::Framed;
+/// #
+/// let mut framed = Framed::new(Vec::new(), b"DYN\x01");
+/// let mut serializer = serde_json::Serializer::new(&mut framed);
+/// let mut serializer = <dyn Serializer>::new(&mut serializer);
+/// (&[1, 2] as &dyn Serialize).dyn_serialize(&mut serializer).unwrap();
+///
+/// let checksum = framed.checksum();
+/// let output = framed.finish().unwrap();
+/// assert_eq!(output[..4], *b"DYN\x01");
+/// assert_eq!(output[4..9], *b"[1,2]");
+/// assert_eq!(output[9..], checksum.to_le_bytes());
+/// ```
+#[derive(Debug)]
+pub struct Framed<'a, W> {
+    inner: W,
+    magic: &'a [u8],
+    /// Whether the magic bytes have been written.
+    started: bool,
+    /// The CRC-32 of the payload so far, not yet inverted.
+    crc: u32,
+}
+
+impl<'a, W: io::Write> Framed<'a, W> {
+    /// Creates a writer framing the payload written to `inner` between `magic`
+    /// and a checksum.
+    #[must_use]
+    pub fn new(inner: W, magic: &'a [u8]) -> Self {
+        Framed {
+            inner,
+            magic,
+            started: false,
+            crc: !0,
+        }
+    }
+
+    /// Returns the CRC-32 of the payload written so far.
+    #[must_use]
+    pub fn checksum(&self) -> u32 {
+        !self.crc
+    }
+
+    /// Writes the trailing checksum, and the magic bytes if the payload is
+    /// empty, then flushes and unwraps the writer.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the writer fails to write or to flush.
+    pub fn finish(mut self) -> io::Result<W> {
+        self.start()?;
+        self.inner.write_all(&self.checksum().to_le_bytes())?;
+        self.inner.flush()?;
+        Ok(self.inner)
+    }
+
+    fn start(&mut self) -> io::Result<()> {
+        if !self.started {
+            self.inner.write_all(self.magic)?;
+            self.started = true;
+        }
+        Ok(())
+    }
+}
+
+impl<W: io::Write> io::Write for Framed<'_, W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.start()?;
+        let written = self.inner.write(buf)?;
+        self.crc = buf[..written].iter().fold(self.crc, |crc, &byte| {
+            CRC32_TABLE[usize::from((crc as u8) ^ byte)] ^ (crc >> 8)
+        });
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+/// The lookup table of the reflected CRC-32 polynomial `0xEDB88320`.
+static CRC32_TABLE: [u32; 256] = {
+    let mut table = [0; 256];
+    let mut i = 0;
+    while i < 256 {
+        let mut crc = i as u32;
+        let mut bit = 0;
+        while bit < 8 {
+            crc = if crc & 1 == 1 {
+                (crc >> 1) ^ 0xEDB8_8320
+            } else {
+                crc >> 1
+            };
+            bit += 1;
+        }
+        table[i] = crc;
+        i += 1;
+    }
+    table
+};
@@ -0,0 +1,98 @@
+//! Bytes serialized as chunks of hexadecimal digits, see [`HexChunks`].
+
+use core::fmt::{self, Debug, Display, Formatter};
+
+/// Bytes which serialize as a sequence of strings of hexadecimal digits, each
+/// encoding a chunk of 16 bytes by default, e.g.
+/// `["000102030405060708090a0b0c0d0e0f", "1011"]`.
+///
+/// This is a diagnostic helper for looking at binary blobs in the output of
+/// human-readable formats. JSON writes bytes as an array of numbers, one per
+/// byte, which is long and hard to read; `HexChunks` writes the lowercase hex
+/// dump of the bytes instead, split into lines of a fixed size as a hex editor
+/// shows them. The last chunk is shorter when the size of the chunks does not
+/// divide the number of bytes, and empty bytes serialize as an empty
+/// sequence.
+///
+/// The output does not depend on whether the format is human-readable, and no
+/// counterpart reads it back.
+///
+/// # Examples
+///
+/// ```
+/// # use dyn_serde::ser::HexChunks;
+/// #
+/// let bytes = [0xde, 0xad, 0xbe, 0xef, 0x00];
+/// let json = serde_json::to_string(&HexChunks::with_chunk_size(&bytes, 2)).unwrap();
+/// assert_eq!(json, r#"["dead","beef","00"]"#);
+/// ```
+#[derive(Clone, Copy)]
+pub struct HexChunks<'a> {
+    bytes: &'a [u8],
+    chunk_size: usize,
+}
+
+impl<'a> HexChunks<'a> {
+    /// The number of bytes per chunk by default.
+    pub const CHUNK_SIZE: usize = 16;
+
+    /// Wraps `bytes`, serializing them in chunks of
+    /// [`HexChunks::CHUNK_SIZE`] bytes.
+    #[must_use]
+    pub fn new(bytes: &'a [u8]) -> Self {
+        HexChunks::with_chunk_size(bytes, HexChunks::CHUNK_SIZE)
+    }
+
+    /// Wraps `bytes`, serializing them in chunks of `chunk_size` bytes, or as
+    /// a single chunk if `chunk_size` is `0`.
+    #[must_use]
+    pub fn with_chunk_size(bytes: &'a [u8], chunk_size: usize) -> Self {
+        HexChunks { bytes, chunk_size }
+    }
+
+    /// Returns the number of bytes per chunk.
+    #[must_use]
+    pub fn chunk_size(&self) -> usize {
+        self.chunk_size
+    }
+}
+
+impl Debug for HexChunks<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("HexChunks")
+            .field("len", &self.bytes.len())
+            .field("chunk_size", &self.chunk_size)
+            .finish_non_exhaustive()
+    }
+}
+
+impl serde::Serialize for HexChunks<'_> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        let chunk_size = match self.chunk_size {
+            0 => self.bytes.len().max(1),
+            chunk_size => chunk_size,
+        };
+        serializer.collect_seq(self.bytes.chunks(chunk_size).map(Hex))
+    }
+}
+
+/// A chunk of bytes, written as their hexadecimal digits.
+struct Hex<'a>(&'a [u8]);
+
+impl Display for Hex<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        self.0.iter().try_for_each(|byte| write!(f, "{byte:02x}"))
+    }
+}
+
+impl serde::Serialize for Hex<'_> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.collect_str(self)
+    }
+}
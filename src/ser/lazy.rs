@@ -0,0 +1,73 @@
+//! Values computed when they are serialized, see [`Lazy`].
+
+use core::fmt::{self, Debug, Formatter};
+
+/// A serializable value computed by a closure only once the serialization
+/// reaches it, e.g. for a field which is expensive to compute.
+///
+/// A field whose value is computed up front costs as much whether or not it
+/// ends up in the output, e.g. when it is dropped by a wrapper filtering the
+/// fields, or when only [`present_fields`] are looked at. `Lazy` calls `f`
+/// every time it is serialized instead, and serializes the value returned,
+/// which is dropped right after. Nothing is computed when the value is not
+/// serialized, while serializing it twice computes it twice.
+///
+/// [`present_fields`]: crate::ser::present_fields
+///
+/// # Examples
+///
+/// ```
+/// # use dyn_serde::ser::Lazy;
+/// #
+/// #[derive(serde::Serialize)]
+/// struct Report {
+///     name: &'static str,
+///     checksum: Lazy<fn() -> u32>,
+/// }
+///
+/// let report = Report {
+///     name: "daily",
+///     checksum: Lazy::new(|| (1..=100).sum::<u32>()),
+/// };
+/// assert_eq!(
+///     serde_json::to_string(&report).unwrap(),
+///     r#"{"name":"daily","checksum":5050}"#,
+/// );
+/// ```
+#[derive(Clone, Copy)]
+pub struct Lazy<F> {
+    f: F,
+}
+
+impl<F> Lazy<F> {
+    /// Creates a value serializing whatever `f` returns when it is called.
+    #[must_use]
+    pub const fn new(f: F) -> Self {
+        Lazy { f }
+    }
+
+    /// Unwraps the closure.
+    #[must_use]
+    pub fn into_inner(self) -> F {
+        self.f
+    }
+}
+
+impl<F> Debug for Lazy<F> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Lazy").finish_non_exhaustive()
+    }
+}
+
+impl<F, T> serde::Serialize for Lazy<F>
+where
+    F: Fn() -> T,
+    T: serde::Serialize,
+{
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        (self.f)().serialize(serializer)
+    }
+}
@@ -0,0 +1,112 @@
+//! Writers prefixing every value with its length, see [`LengthPrefixed`].
+
+use std::io;
+
+/// An [`io::Write`] adapter writing the output of a serializer as a stream of
+/// frames, each prefixed with its length, for message-stream protocols.
+///
+/// A reader of a stream of concatenated values must parse each value to find
+/// where the next one starts, which not every format allows. `LengthPrefixed`
+/// is handed to the backend of the serializer as its writer: the bytes of a
+/// value are buffered until [`LengthPrefixed::end_frame`] is called once the
+/// value has been serialized, which writes their length as a little-endian
+/// `u32` followed by the bytes themselves. The frames are then read back with
+/// a length read and a read of that many bytes each, whatever the format, as
+/// [`FrameReader`] does.
+///
+/// A serializer only sees values, so it cannot tell its writer where a value
+/// ends: `end_frame` is called by the caller after each top-level
+/// serialization. When a serialization fails, the bytes the backend has
+/// already written are left in the frame, and [`LengthPrefixed::discard_frame`]
+/// drops them, so that the next value starts a frame of its own rather than
+/// ending a corrupt one.
+///
+/// [`FrameReader`]: crate::de::FrameReader
+///
+/// # Examples
+///
+/// ```
+/// # use dyn_serde::{Serialize, Serializer};
+/// # use dyn_serde::ser::LengthPrefixed;
+/// #
+/// let mut frames = LengthPrefixed::new(Vec::new());
+/// for value in [&1 as &dyn Serialize, &"two"] {
+///     let mut serializer = serde_json::Serializer::new(&mut frames);
+///     let mut serializer = <dyn Serializer>::new(&mut serializer);
+///     value.dyn_serialize(&mut serializer).unwrap();
+///     frames.end_frame().unwrap();
+/// }
+///
+/// let output = frames.finish().unwrap();
+/// assert_eq!(output, b"\x01\0\0\01\x05\0\0\0\"two\"");
+/// ```
+#[derive(Debug)]
+pub struct LengthPrefixed<W> {
+    inner: W,
+    /// The bytes of the frame being written.
+    frame: Vec<u8>,
+}
+
+impl<W: io::Write> LengthPrefixed<W> {
+    /// Creates a writer writing length-prefixed frames to `inner`.
+    #[must_use]
+    pub fn new(inner: W) -> Self {
+        LengthPrefixed {
+            inner,
+            frame: Vec::new(),
+        }
+    }
+
+    /// Writes the bytes written since the previous frame as a frame of their
+    /// own, prefixed with their length, and flushes the writer.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the frame is longer than `u32::MAX` bytes, or if
+    /// the writer fails to write or to flush.
+    pub fn end_frame(&mut self) -> io::Result<()> {
+        let len = u32::try_from(self.frame.len()).map_err(|_| {
+            io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "the frame is longer than u32::MAX bytes",
+            )
+        })?;
+        self.inner.write_all(&len.to_le_bytes())?;
+        self.inner.write_all(&self.frame)?;
+        self.frame.clear();
+        self.inner.flush()
+    }
+
+    /// Drops the bytes written since the previous frame, e.g. the partial
+    /// output of a value which has failed to serialize.
+    pub fn discard_frame(&mut self) {
+        self.frame.clear();
+    }
+
+    /// Ends the last frame if any byte has been written to it, then flushes
+    /// and unwraps the writer.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the last frame fails to be written, as
+    /// [`LengthPrefixed::end_frame`] does, or if the writer fails to flush.
+    pub fn finish(mut self) -> io::Result<W> {
+        if !self.frame.is_empty() {
+            self.end_frame()?;
+        }
+        self.inner.flush()?;
+        Ok(self.inner)
+    }
+}
+
+impl<W: io::Write> io::Write for LengthPrefixed<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.frame.extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        // the frame is only written once it has ended.
+        Ok(())
+    }
+}
@@ -0,0 +1,475 @@
+//! Lines of `key=value` pairs, see [`LineSerializer`].
+
+use std::io::{self, Write as _};
+
+use serde::ser::Error as _;
+
+use crate::ser::Serializer;
+use crate::ser::counted::Counted;
+use crate::ser::{InplaceSerializeError, InplaceSerializeResult, InplaceSerializerState};
+use crate::ser::{Serialize, SerializeMap, SerializeSeq, SerializeStruct, SerializeStructVariant};
+use crate::ser::{SerializeError, SerializeTuple, SerializeTupleStruct, SerializeTupleVariant};
+
+/// A [`Serializer`] writing every value as a line of space-separated
+/// `key=value` pairs, e.g. for lightweight logging.
+///
+/// A struct or a map is written as one pair per field or entry, and a
+/// sequence or a tuple as its elements. The values must be scalars: numbers
+/// and booleans are written as they display, strings are written bare unless
+/// they are empty or contain spaces, `=`, `"` or control characters, in which
+/// case they are quoted and escaped, and `None` and `()` are written as
+/// nothing, e.g. `key=`. Unit variants are written as their name, while
+/// nested compound values, bytes and variants with content are rejected.
+///
+/// Every line ends with `\n` and the writer is flushed after it, and the
+/// serializer is then ready to write the next line, so that it can serve a
+/// whole log.
+///
+/// The serialization returns an error telling only that it has done
+/// unsuccessfully, while [`LineSerializer::error`] tells why it has.
+///
+/// # Examples
+///
+/// ```
+/// # use dyn_serde::Serialize;
+/// # use dyn_serde::ser::LineSerializer;
+/// #
+/// #[derive(serde::Serialize)]
+/// struct Request<'a> {
+///     method: &'a str,
+///     path: &'a str,
+///     status: u16,
+/// }
+///
+/// let mut serializer = LineSerializer::new(Vec::new());
+/// let request = Request { method: "GET", path: "/a b", status: 200 };
+/// (&request as &dyn Serialize).dyn_serialize(&mut serializer).unwrap();
+/// assert_eq!(serializer.into_inner(), b"method=GET path=\"/a b\" status=200\n");
+/// ```
+#[derive(Debug)]
+pub struct LineSerializer<W> {
+    writer: Counted<W>,
+    state: InplaceSerializerState,
+    /// Whether the value being serialized is a key or a value of the line,
+    /// rather than the line itself.
+    nested: bool,
+    /// Whether nothing has been written to the line yet.
+    empty: bool,
+    error: Option<SerializeError>,
+}
+
+impl<W: io::Write> LineSerializer<W> {
+    /// Creates a serializer writing lines to `writer`.
+    #[must_use]
+    pub fn new(writer: W) -> Self {
+        LineSerializer {
+            writer: Counted::new(writer),
+            state: InplaceSerializerState::Serializer,
+            nested: false,
+            empty: true,
+            error: None,
+        }
+    }
+
+    /// Returns the first error raised while serializing, if any.
+    #[must_use]
+    pub fn error(&self) -> Option<&SerializeError> {
+        self.error.as_ref()
+    }
+
+    /// Unwraps the writer.
+    #[must_use]
+    pub fn into_inner(self) -> W {
+        self.writer.inner
+    }
+
+    #[cold]
+    fn fail(&mut self, error: SerializeError) -> InplaceSerializeError {
+        self.error.get_or_insert(error);
+        self.state = InplaceSerializerState::Error;
+        InplaceSerializeError::Error
+    }
+
+    #[cold]
+    fn unsupported(&mut self, what: &str) -> InplaceSerializeError {
+        if !self.nested && self.state != InplaceSerializerState::Serializer {
+            return InplaceSerializeError::NotSerializer;
+        }
+        self.fail(SerializeError::custom(format_args!(
+            "a line cannot hold {what}"
+        )))
+    }
+
+    fn write(&mut self, bytes: &[u8]) -> InplaceSerializeResult<()> {
+        self.writer
+            .write_all(bytes)
+            .map_err(|error| self.fail(SerializeError::custom(error)))
+    }
+
+    /// Writes a scalar, on a line of its own unless it is nested.
+    fn scalar(
+        &mut self,
+        write: impl FnOnce(&mut Counted<W>) -> io::Result<()>,
+    ) -> InplaceSerializeResult<()> {
+        if self.nested {
+            return write(&mut self.writer)
+                .map_err(|error| self.fail(SerializeError::custom(error)));
+        }
+        if self.state != InplaceSerializerState::Serializer {
+            return Err(InplaceSerializeError::NotSerializer);
+        }
+        write(&mut self.writer).map_err(|error| self.fail(SerializeError::custom(error)))?;
+        self.end_line()
+    }
+
+    fn write_display(&mut self, v: impl std::fmt::Display) -> InplaceSerializeResult<()> {
+        self.scalar(|writer| write!(writer, "{v}"))
+    }
+
+    fn serialize_nested(&mut self, value: &dyn Serialize) -> InplaceSerializeResult<()> {
+        self.nested = true;
+        let result = value.dyn_serialize(self);
+        self.nested = false;
+        result.map_err(|error| self.fail(error))
+    }
+
+    fn begin(&mut self, state: InplaceSerializerState) -> InplaceSerializeResult<&mut Self> {
+        if self.nested {
+            return Err(self.unsupported("nested compound values"));
+        }
+        if self.state != InplaceSerializerState::Serializer {
+            return Err(InplaceSerializeError::NotSerializer);
+        }
+        self.state = state;
+        self.empty = true;
+        Ok(self)
+    }
+
+    /// Writes the space before a pair or an element, unless it is the first
+    /// one of the line.
+    fn separate(
+        &mut self,
+        state: InplaceSerializerState,
+        error: InplaceSerializeError,
+    ) -> InplaceSerializeResult<()> {
+        if self.state != state {
+            return Err(error);
+        }
+        if !std::mem::replace(&mut self.empty, false) {
+            self.write(b" ")?;
+        }
+        Ok(())
+    }
+
+    fn element(
+        &mut self,
+        value: &dyn Serialize,
+        state: InplaceSerializerState,
+        error: InplaceSerializeError,
+    ) -> InplaceSerializeResult<()> {
+        self.separate(state, error)?;
+        self.serialize_nested(value)
+    }
+
+    fn field(
+        &mut self,
+        key: &'static str,
+        value: &dyn Serialize,
+        error: InplaceSerializeError,
+    ) -> InplaceSerializeResult<()> {
+        self.separate(InplaceSerializerState::SerializeStruct, error)?;
+        write_str(&mut self.writer, key)
+            .map_err(|error| self.fail(SerializeError::custom(error)))?;
+        self.write(b"=")?;
+        self.serialize_nested(value)
+    }
+
+    fn end(
+        &mut self,
+        state: InplaceSerializerState,
+        error: InplaceSerializeError,
+    ) -> InplaceSerializeResult<()> {
+        if self.state != state {
+            return Err(error);
+        }
+        self.end_line()
+    }
+
+    /// Ends the line, readying the serializer for the next one.
+    fn end_line(&mut self) -> InplaceSerializeResult<()> {
+        self.write(b"\n")?;
+        self.writer
+            .flush()
+            .map_err(|error| self.fail(SerializeError::custom(error)))?;
+        self.state = InplaceSerializerState::Serializer;
+        Ok(())
+    }
+}
+
+impl<W: io::Write> Serializer for LineSerializer<W> {
+    fn dyn_serialize_bool(&mut self, v: bool) -> InplaceSerializeResult<()> {
+        self.write_display(v)
+    }
+
+    fn dyn_serialize_i64(&mut self, v: i64) -> InplaceSerializeResult<()> {
+        self.write_display(v)
+    }
+
+    fn dyn_serialize_i128(&mut self, v: i128) -> InplaceSerializeResult<()> {
+        self.write_display(v)
+    }
+
+    fn dyn_serialize_u64(&mut self, v: u64) -> InplaceSerializeResult<()> {
+        self.write_display(v)
+    }
+
+    fn dyn_serialize_u128(&mut self, v: u128) -> InplaceSerializeResult<()> {
+        self.write_display(v)
+    }
+
+    fn dyn_serialize_f64(&mut self, v: f64) -> InplaceSerializeResult<()> {
+        self.write_display(v)
+    }
+
+    fn dyn_serialize_str(&mut self, v: &str) -> InplaceSerializeResult<()> {
+        self.scalar(|writer| write_str(writer, v))
+    }
+
+    fn dyn_serialize_bytes(&mut self, v: &[u8]) -> InplaceSerializeResult<()> {
+        let _ = v;
+        Err(self.unsupported("bytes"))
+    }
+
+    fn dyn_serialize_none(&mut self) -> InplaceSerializeResult<()> {
+        self.dyn_serialize_unit()
+    }
+
+    fn dyn_serialize_some(&mut self, value: &dyn Serialize) -> InplaceSerializeResult<()> {
+        value.dyn_serialize(self).map_err(|error| self.fail(error))
+    }
+
+    fn dyn_serialize_unit(&mut self) -> InplaceSerializeResult<()> {
+        self.scalar(|_| Ok(()))
+    }
+
+    fn dyn_serialize_unit_variant(
+        &mut self,
+        name: &'static str,
+        variant_index: u32,
+        variant: &'static str,
+    ) -> InplaceSerializeResult<()> {
+        let _ = (name, variant_index);
+        self.dyn_serialize_str(variant)
+    }
+
+    fn dyn_serialize_newtype_struct(
+        &mut self,
+        name: &'static str,
+        value: &dyn Serialize,
+    ) -> InplaceSerializeResult<()> {
+        let _ = name;
+        value.dyn_serialize(self).map_err(|error| self.fail(error))
+    }
+
+    fn dyn_serialize_newtype_variant(
+        &mut self,
+        name: &'static str,
+        variant_index: u32,
+        variant: &'static str,
+        value: &dyn Serialize,
+    ) -> InplaceSerializeResult<()> {
+        let _ = (name, variant_index, variant, value);
+        Err(self.unsupported("variants with content"))
+    }
+
+    fn dyn_serialize_seq(
+        &mut self,
+        len: Option<usize>,
+    ) -> InplaceSerializeResult<&mut dyn SerializeSeq> {
+        let _ = len;
+        Ok(self.begin(InplaceSerializerState::SerializeSeq)?)
+    }
+
+    fn dyn_serialize_tuple(
+        &mut self,
+        len: usize,
+    ) -> InplaceSerializeResult<&mut dyn SerializeTuple> {
+        let _ = len;
+        Ok(self.begin(InplaceSerializerState::SerializeTuple)?)
+    }
+
+    fn dyn_serialize_tuple_struct(
+        &mut self,
+        name: &'static str,
+        len: usize,
+    ) -> InplaceSerializeResult<&mut dyn SerializeTupleStruct> {
+        let _ = (name, len);
+        Ok(self.begin(InplaceSerializerState::SerializeTupleStruct)?)
+    }
+
+    fn dyn_serialize_tuple_variant(
+        &mut self,
+        name: &'static str,
+        variant_index: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> InplaceSerializeResult<&mut dyn SerializeTupleVariant> {
+        let _ = (name, variant_index, variant, len);
+        Err(self.unsupported("variants with content"))
+    }
+
+    fn dyn_serialize_map(
+        &mut self,
+        len: Option<usize>,
+    ) -> InplaceSerializeResult<&mut dyn SerializeMap> {
+        let _ = len;
+        Ok(self.begin(InplaceSerializerState::SerializeMap)?)
+    }
+
+    fn dyn_serialize_struct(
+        &mut self,
+        name: &'static str,
+        len: usize,
+    ) -> InplaceSerializeResult<&mut dyn SerializeStruct> {
+        let _ = (name, len);
+        Ok(self.begin(InplaceSerializerState::SerializeStruct)?)
+    }
+
+    fn dyn_serialize_struct_variant(
+        &mut self,
+        name: &'static str,
+        variant_index: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> InplaceSerializeResult<&mut dyn SerializeStructVariant> {
+        let _ = (name, variant_index, variant, len);
+        Err(self.unsupported("variants with content"))
+    }
+
+    fn dyn_bytes_written(&self) -> Option<u64> {
+        Some(self.writer.written)
+    }
+}
+
+impl<W: io::Write> SerializeSeq for LineSerializer<W> {
+    fn dyn_serialize_element(&mut self, value: &dyn Serialize) -> InplaceSerializeResult<()> {
+        let state = InplaceSerializerState::SerializeSeq;
+        self.element(value, state, InplaceSerializeError::NotSerializeSeq)
+    }
+
+    fn dyn_end(&mut self) -> InplaceSerializeResult<()> {
+        let state = InplaceSerializerState::SerializeSeq;
+        self.end(state, InplaceSerializeError::NotSerializeSeq)
+    }
+}
+
+impl<W: io::Write> SerializeTuple for LineSerializer<W> {
+    fn dyn_serialize_element(&mut self, value: &dyn Serialize) -> InplaceSerializeResult<()> {
+        let state = InplaceSerializerState::SerializeTuple;
+        self.element(value, state, InplaceSerializeError::NotSerializeTuple)
+    }
+
+    fn dyn_end(&mut self) -> InplaceSerializeResult<()> {
+        let state = InplaceSerializerState::SerializeTuple;
+        self.end(state, InplaceSerializeError::NotSerializeTuple)
+    }
+}
+
+impl<W: io::Write> SerializeTupleStruct for LineSerializer<W> {
+    fn dyn_serialize_field(&mut self, value: &dyn Serialize) -> InplaceSerializeResult<()> {
+        let state = InplaceSerializerState::SerializeTupleStruct;
+        self.element(value, state, InplaceSerializeError::NotSerializeTupleStruct)
+    }
+
+    fn dyn_end(&mut self) -> InplaceSerializeResult<()> {
+        let state = InplaceSerializerState::SerializeTupleStruct;
+        self.end(state, InplaceSerializeError::NotSerializeTupleStruct)
+    }
+}
+
+impl<W: io::Write> SerializeTupleVariant for LineSerializer<W> {
+    fn dyn_serialize_field(&mut self, value: &dyn Serialize) -> InplaceSerializeResult<()> {
+        let _ = value;
+        Err(InplaceSerializeError::NotSerializeTupleVariant)
+    }
+
+    fn dyn_end(&mut self) -> InplaceSerializeResult<()> {
+        Err(InplaceSerializeError::NotSerializeTupleVariant)
+    }
+}
+
+impl<W: io::Write> SerializeMap for LineSerializer<W> {
+    fn dyn_serialize_key(&mut self, key: &dyn Serialize) -> InplaceSerializeResult<()> {
+        let state = InplaceSerializerState::SerializeMap;
+        self.separate(state, InplaceSerializeError::NotSerializeMap)?;
+        self.serialize_nested(key)?;
+        self.write(b"=")
+    }
+
+    fn dyn_serialize_value(&mut self, value: &dyn Serialize) -> InplaceSerializeResult<()> {
+        if self.state != InplaceSerializerState::SerializeMap {
+            return Err(InplaceSerializeError::NotSerializeMap);
+        }
+        self.serialize_nested(value)
+    }
+
+    fn dyn_end(&mut self) -> InplaceSerializeResult<()> {
+        let state = InplaceSerializerState::SerializeMap;
+        self.end(state, InplaceSerializeError::NotSerializeMap)
+    }
+}
+
+impl<W: io::Write> SerializeStruct for LineSerializer<W> {
+    fn dyn_serialize_field(
+        &mut self,
+        key: &'static str,
+        value: &dyn Serialize,
+    ) -> InplaceSerializeResult<()> {
+        self.field(key, value, InplaceSerializeError::NotSerializeStruct)
+    }
+
+    fn dyn_end(&mut self) -> InplaceSerializeResult<()> {
+        let state = InplaceSerializerState::SerializeStruct;
+        self.end(state, InplaceSerializeError::NotSerializeStruct)
+    }
+}
+
+impl<W: io::Write> SerializeStructVariant for LineSerializer<W> {
+    fn dyn_serialize_field(
+        &mut self,
+        key: &'static str,
+        value: &dyn Serialize,
+    ) -> InplaceSerializeResult<()> {
+        let _ = (key, value);
+        Err(InplaceSerializeError::NotSerializeStructVariant)
+    }
+
+    fn dyn_end(&mut self) -> InplaceSerializeResult<()> {
+        Err(InplaceSerializeError::NotSerializeStructVariant)
+    }
+}
+
+/// Writes `v` bare if it reads back unambiguously, and quoted otherwise.
+fn write_str(writer: &mut impl io::Write, v: &str) -> io::Result<()> {
+    let bare = !v.is_empty()
+        && !v
+            .chars()
+            .any(|c| c == '=' || c == '"' || c.is_whitespace() || c.is_control());
+    if bare {
+        return writer.write_all(v.as_bytes());
+    }
+
+    writer.write_all(b"\"")?;
+    for c in v.chars() {
+        match c {
+            '"' => writer.write_all(b"\\\"")?,
+            '\\' => writer.write_all(b"\\\\")?,
+            '\n' => writer.write_all(b"\\n")?,
+            '\r' => writer.write_all(b"\\r")?,
+            '\t' => writer.write_all(b"\\t")?,
+            c if c.is_control() => write!(writer, "\\u{:04x}", u32::from(c))?,
+            c => write!(writer, "{c}")?,
+        }
+    }
+    writer.write_all(b"\"")
+}
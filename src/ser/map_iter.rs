@@ -0,0 +1,75 @@
+//! Serializing an iterator of entries as a map, see [`SerializeMapIter`].
+
+use core::cell::Cell;
+use core::fmt::{self, Debug, Formatter};
+
+use serde::ser::Error as _;
+use serde::ser::SerializeMap as _;
+
+/// A [`serde::Serialize`] adapter which serializes the entries produced by an
+/// iterator as a map, without collecting them first.
+///
+/// The length of the map is reported when the iterator knows its exact
+/// length, e.g. when it implements [`ExactSizeIterator`].
+///
+/// Since the iterator is consumed by the serialization, the value can only be
+/// serialized once; serializing it again returns an error.
+///
+/// # Examples
+///
+/// ```
+/// use dyn_serde::ser::SerializeMapIter;
+///
+/// let entries = (1..=3).map(|i| (i.to_string(), i * i));
+/// let value = SerializeMapIter::new(entries);
+/// let json = serde_json::to_string(&value).unwrap();
+/// assert_eq!(json, r#"{"1":1,"2":4,"3":9}"#);
+/// ```
+pub struct SerializeMapIter<I> {
+    iter: Cell<Option<I>>,
+}
+
+impl<I: Iterator> SerializeMapIter<I> {
+    /// Creates an adapter serializing the entries of `iter` as a map.
+    #[must_use]
+    pub fn new<T>(iter: T) -> Self
+    where
+        T: IntoIterator<IntoIter = I>,
+    {
+        SerializeMapIter {
+            iter: Cell::new(Some(iter.into_iter())),
+        }
+    }
+}
+
+impl<I> Debug for SerializeMapIter<I> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SerializeMapIter").finish_non_exhaustive()
+    }
+}
+
+impl<I, K, V> serde::Serialize for SerializeMapIter<I>
+where
+    I: Iterator<Item = (K, V)>,
+    K: serde::Serialize,
+    V: serde::Serialize,
+{
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        let Some(iter) = self.iter.take() else {
+            return Err(S::Error::custom("the iterator has already been serialized"));
+        };
+
+        let len = match iter.size_hint() {
+            (lower, Some(upper)) if lower == upper => Some(lower),
+            _ => None,
+        };
+        let mut map = serializer.serialize_map(len)?;
+        for (key, value) in iter {
+            map.serialize_entry(&key, &value)?;
+        }
+        map.end()
+    }
+}
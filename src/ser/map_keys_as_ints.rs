@@ -0,0 +1,171 @@
+//! A [`Serializer`] wrapper writing numeric map keys as integers, see
+//! [`MapKeysAsInts`].
+
+use core::cell::Cell;
+use core::fmt::{self, Debug, Formatter};
+
+use crate::ser::middleware::{self, Begin, Call, Compound, Element};
+use crate::ser::middleware::{Middleware, MiddlewareSerializer};
+use crate::ser::{Serialize, SerializeResult, Serializer};
+
+/// A [`Serializer`] wrapper which writes the keys of maps that are strings of
+/// decimal integers, e.g. `"42"` or `"-1"`, as integers.
+///
+/// Compact formats such as CBOR or MessagePack encode an integer key in fewer
+/// bytes than its string, but a map keyed by an enum whose variants are renamed
+/// to numbers, or by strings read from a text format, only ever serializes its
+/// keys as strings. `MapKeysAsInts` forwards such a key as an `i64` if it is
+/// negative and as a `u64` otherwise.
+///
+/// Only the strings which are the canonical form of an integer are converted,
+/// so that they read back as the same string: `"007"`, `"+1"` and `"-0"` are
+/// left unchanged, as are the keys out of range of a 64-bit integer. Keys
+/// behind `Some` or a newtype struct are converted too, while the keys of
+/// nested maps are converted at every depth.
+///
+/// The wrapped serializer must accept integer keys. Formats that require string
+/// keys may write them back as strings, like `serde_json`, or reject them.
+///
+/// # Examples
+///
+/// ```
+/// # use std::collections::BTreeMap;
+/// # use dyn_serde::{Serialize, Serializer};
+/// # use dyn_serde::ser::{InplaceSerializer, MapKeysAsInts};
+/// #
+/// struct IntKeys<'a>(&'a dyn Serialize);
+///
+/// impl serde::Serialize for IntKeys<'_> {
+///     fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+///         let mut serializer = <dyn Serializer>::new(serializer);
+///         let _ = self.0.dyn_serialize(&mut MapKeysAsInts::new(&mut serializer));
+///         match serializer {
+///             InplaceSerializer::Ok(ok, _) => Ok(ok),
+///             InplaceSerializer::Error(error, _) => Err(error),
+///             _ => unreachable!(),
+///         }
+///     }
+/// }
+///
+/// let value = BTreeMap::from([("1", "one")]);
+/// let mut cbor = Vec::new();
+/// ciborium::into_writer(&IntKeys(&value), &mut cbor).unwrap();
+/// // a map of 1 entry, whose key is the integer 1.
+/// assert_eq!(cbor, b"\xa1\x01\x63one");
+/// ```
+pub struct MapKeysAsInts<'a> {
+    serializer: MiddlewareSerializer<'a, IntKeys>,
+}
+
+impl<'a> MapKeysAsInts<'a> {
+    /// Wraps `serializer`, writing numeric map keys as integers.
+    #[must_use]
+    pub fn new(serializer: &'a mut dyn Serializer) -> Self {
+        let keys = IntKeys {
+            key: Cell::new(false),
+        };
+        MapKeysAsInts {
+            serializer: MiddlewareSerializer::new(serializer, keys),
+        }
+    }
+}
+
+impl Debug for MapKeysAsInts<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("MapKeysAsInts").finish_non_exhaustive()
+    }
+}
+
+impl Serializer for MapKeysAsInts<'_> {
+    middleware::forward_to_middleware_serializer!(serializer);
+}
+
+struct IntKeys {
+    /// Whether the next call serializes a key of a map.
+    key: Cell<bool>,
+}
+
+impl IntKeys {
+    fn key(&self, key: Element<'_>, compound: &mut Compound<'_>) -> SerializeResult<()> {
+        self.key.set(true);
+        let result = key.forward(compound);
+        self.key.set(false);
+        Ok(result?)
+    }
+}
+
+/// Returns the integer `v` is the canonical form of, if any.
+fn parse(v: &str) -> Option<Call<'static>> {
+    let digits = v.strip_prefix('-').unwrap_or(v);
+    let canonical = match digits.as_bytes() {
+        [b'0'] => digits.len() == v.len(),
+        [b'1'..=b'9', rest @ ..] => rest.iter().all(u8::is_ascii_digit),
+        _ => false,
+    };
+    if !canonical {
+        return None;
+    }
+    if digits.len() == v.len() {
+        v.parse().ok().map(Call::U64)
+    } else {
+        v.parse().ok().map(Call::I64)
+    }
+}
+
+impl Middleware for IntKeys {
+    fn serialize(&self, call: Call<'_>, serializer: &mut dyn Serializer) -> SerializeResult<()> {
+        if !self.key.take() {
+            return Ok(call.forward(serializer)?);
+        }
+
+        let call = match call {
+            Call::Str(v) | Call::UnitVariant(_, _, v) => parse(v).unwrap_or(call),
+            Call::Some(_) | Call::NewtypeStruct(..) => {
+                // the key is the value inside.
+                self.key.set(true);
+                call
+            }
+            call => call,
+        };
+        Ok(call.forward(serializer)?)
+    }
+
+    fn begin<'a>(
+        &self,
+        begin: Begin,
+        serializer: &'a mut dyn Serializer,
+    ) -> SerializeResult<Compound<'a>> {
+        self.key.set(false);
+        Ok(begin.forward(serializer)?)
+    }
+
+    fn element(&self, element: Element<'_>, compound: &mut Compound<'_>) -> SerializeResult<()> {
+        match element {
+            Element::Key(key) => self.key(Element::Key(key), compound),
+            Element::Entry(key, value) => {
+                let key = MapKey { keys: self, key };
+                Ok(Element::Entry(&key, value).forward(compound)?)
+            }
+            element => Ok(element.forward(compound)?),
+        }
+    }
+}
+
+/// The key of an entry, which tells the middleware it is a key while it is
+/// serialized, so that the entry is forwarded as a whole.
+struct MapKey<'a> {
+    keys: &'a IntKeys,
+    key: &'a dyn Serialize,
+}
+
+impl serde::Serialize for MapKey<'_> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        self.keys.key.set(true);
+        let result = serde::Serialize::serialize(self.key, serializer);
+        self.keys.key.set(false);
+        result
+    }
+}
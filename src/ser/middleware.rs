@@ -0,0 +1,954 @@
+//! The plumbing shared by the [`Serializer`] wrappers of this crate.
+//!
+//! A wrapper implements [`Middleware`] and delegates its [`Serializer`]
+//! implementation to a [`MiddlewareSerializer`]. From then on, every value
+//! reachable from that serializer is serialized through a
+//! `MiddlewareSerializer` as well, so that the hooks of the middleware observe
+//! the entire value rather than its outermost layer only.
+
+use core::fmt::Display;
+use core::mem;
+
+use crate::ser::{InplaceSerializeError, InplaceSerializeResult};
+use crate::ser::{InplaceSerializerState, Serialize, SerializeError, SerializeResult};
+use crate::ser::{SerializeMap, SerializeSeq, SerializeStruct, SerializeStructVariant};
+use crate::ser::{SerializeTuple, SerializeTupleStruct, SerializeTupleVariant, Serializer};
+
+/// Implements every method of [`Serializer`] by calling the same method of
+/// `self.$field`, which is a [`MiddlewareSerializer`].
+macro_rules! forward_to_middleware_serializer {
+    ($field:ident) => {
+        fn dyn_serialize_bool(&mut self, v: bool) -> $crate::ser::InplaceSerializeResult<()> {
+            self.$field.dyn_serialize_bool(v)
+        }
+
+        fn dyn_serialize_i8(&mut self, v: i8) -> $crate::ser::InplaceSerializeResult<()> {
+            self.$field.dyn_serialize_i8(v)
+        }
+
+        fn dyn_serialize_i16(&mut self, v: i16) -> $crate::ser::InplaceSerializeResult<()> {
+            self.$field.dyn_serialize_i16(v)
+        }
+
+        fn dyn_serialize_i32(&mut self, v: i32) -> $crate::ser::InplaceSerializeResult<()> {
+            self.$field.dyn_serialize_i32(v)
+        }
+
+        fn dyn_serialize_i64(&mut self, v: i64) -> $crate::ser::InplaceSerializeResult<()> {
+            self.$field.dyn_serialize_i64(v)
+        }
+
+        fn dyn_serialize_i128(&mut self, v: i128) -> $crate::ser::InplaceSerializeResult<()> {
+            self.$field.dyn_serialize_i128(v)
+        }
+
+        fn dyn_serialize_u8(&mut self, v: u8) -> $crate::ser::InplaceSerializeResult<()> {
+            self.$field.dyn_serialize_u8(v)
+        }
+
+        fn dyn_serialize_u16(&mut self, v: u16) -> $crate::ser::InplaceSerializeResult<()> {
+            self.$field.dyn_serialize_u16(v)
+        }
+
+        fn dyn_serialize_u32(&mut self, v: u32) -> $crate::ser::InplaceSerializeResult<()> {
+            self.$field.dyn_serialize_u32(v)
+        }
+
+        fn dyn_serialize_u64(&mut self, v: u64) -> $crate::ser::InplaceSerializeResult<()> {
+            self.$field.dyn_serialize_u64(v)
+        }
+
+        fn dyn_serialize_u128(&mut self, v: u128) -> $crate::ser::InplaceSerializeResult<()> {
+            self.$field.dyn_serialize_u128(v)
+        }
+
+        fn dyn_serialize_f32(&mut self, v: f32) -> $crate::ser::InplaceSerializeResult<()> {
+            self.$field.dyn_serialize_f32(v)
+        }
+
+        fn dyn_serialize_f64(&mut self, v: f64) -> $crate::ser::InplaceSerializeResult<()> {
+            self.$field.dyn_serialize_f64(v)
+        }
+
+        fn dyn_serialize_char(&mut self, v: char) -> $crate::ser::InplaceSerializeResult<()> {
+            self.$field.dyn_serialize_char(v)
+        }
+
+        fn dyn_serialize_str(&mut self, v: &str) -> $crate::ser::InplaceSerializeResult<()> {
+            self.$field.dyn_serialize_str(v)
+        }
+
+        fn dyn_serialize_bytes(&mut self, v: &[u8]) -> $crate::ser::InplaceSerializeResult<()> {
+            self.$field.dyn_serialize_bytes(v)
+        }
+
+        fn dyn_serialize_none(&mut self) -> $crate::ser::InplaceSerializeResult<()> {
+            self.$field.dyn_serialize_none()
+        }
+
+        fn dyn_serialize_some(
+            &mut self,
+            value: &dyn $crate::ser::Serialize,
+        ) -> $crate::ser::InplaceSerializeResult<()> {
+            self.$field.dyn_serialize_some(value)
+        }
+
+        fn dyn_serialize_unit(&mut self) -> $crate::ser::InplaceSerializeResult<()> {
+            self.$field.dyn_serialize_unit()
+        }
+
+        fn dyn_serialize_unit_struct(
+            &mut self,
+            name: &'static str,
+        ) -> $crate::ser::InplaceSerializeResult<()> {
+            self.$field.dyn_serialize_unit_struct(name)
+        }
+
+        fn dyn_serialize_unit_variant(
+            &mut self,
+            name: &'static str,
+            variant_index: u32,
+            variant: &'static str,
+        ) -> $crate::ser::InplaceSerializeResult<()> {
+            self.$field
+                .dyn_serialize_unit_variant(name, variant_index, variant)
+        }
+
+        fn dyn_serialize_newtype_struct(
+            &mut self,
+            name: &'static str,
+            value: &dyn $crate::ser::Serialize,
+        ) -> $crate::ser::InplaceSerializeResult<()> {
+            self.$field.dyn_serialize_newtype_struct(name, value)
+        }
+
+        fn dyn_serialize_newtype_variant(
+            &mut self,
+            name: &'static str,
+            variant_index: u32,
+            variant: &'static str,
+            value: &dyn $crate::ser::Serialize,
+        ) -> $crate::ser::InplaceSerializeResult<()> {
+            self.$field
+                .dyn_serialize_newtype_variant(name, variant_index, variant, value)
+        }
+
+        fn dyn_serialize_seq(
+            &mut self,
+            len: Option<usize>,
+        ) -> $crate::ser::InplaceSerializeResult<&mut dyn $crate::ser::SerializeSeq> {
+            self.$field.dyn_serialize_seq(len)
+        }
+
+        fn dyn_serialize_tuple(
+            &mut self,
+            len: usize,
+        ) -> $crate::ser::InplaceSerializeResult<&mut dyn $crate::ser::SerializeTuple> {
+            self.$field.dyn_serialize_tuple(len)
+        }
+
+        fn dyn_serialize_tuple_struct(
+            &mut self,
+            name: &'static str,
+            len: usize,
+        ) -> $crate::ser::InplaceSerializeResult<&mut dyn $crate::ser::SerializeTupleStruct> {
+            self.$field.dyn_serialize_tuple_struct(name, len)
+        }
+
+        fn dyn_serialize_tuple_variant(
+            &mut self,
+            name: &'static str,
+            variant_index: u32,
+            variant: &'static str,
+            len: usize,
+        ) -> $crate::ser::InplaceSerializeResult<&mut dyn $crate::ser::SerializeTupleVariant> {
+            self.$field
+                .dyn_serialize_tuple_variant(name, variant_index, variant, len)
+        }
+
+        fn dyn_serialize_map(
+            &mut self,
+            len: Option<usize>,
+        ) -> $crate::ser::InplaceSerializeResult<&mut dyn $crate::ser::SerializeMap> {
+            self.$field.dyn_serialize_map(len)
+        }
+
+        fn dyn_serialize_struct(
+            &mut self,
+            name: &'static str,
+            len: usize,
+        ) -> $crate::ser::InplaceSerializeResult<&mut dyn $crate::ser::SerializeStruct> {
+            self.$field.dyn_serialize_struct(name, len)
+        }
+
+        fn dyn_serialize_struct_variant(
+            &mut self,
+            name: &'static str,
+            variant_index: u32,
+            variant: &'static str,
+            len: usize,
+        ) -> $crate::ser::InplaceSerializeResult<&mut dyn $crate::ser::SerializeStructVariant> {
+            self.$field
+                .dyn_serialize_struct_variant(name, variant_index, variant, len)
+        }
+
+        fn dyn_collect_str(
+            &mut self,
+            value: &dyn ::core::fmt::Display,
+        ) -> $crate::ser::InplaceSerializeResult<()> {
+            self.$field.dyn_collect_str(value)
+        }
+
+        fn dyn_is_human_readable(&self) -> bool {
+            self.$field.dyn_is_human_readable()
+        }
+
+        fn dyn_bytes_written(&self) -> Option<u64> {
+            self.$field.dyn_bytes_written()
+        }
+    };
+}
+
+pub(crate) use forward_to_middleware_serializer;
+
+/// The hooks of a [`Serializer`] wrapper.
+///
+/// Every hook receives the arguments of the intercepted call, whose values
+/// have already been wrapped, and forwards the call unchanged by default.
+/// State is kept behind interior mutability since the hooks are shared by
+/// every layer of the value.
+///
+/// An error with a message returned by a hook is raised by the serializer of
+/// the value being serialized, so it reaches the caller in the same way as an
+/// error of the wrapped serializer.
+pub(crate) trait Middleware {
+    /// Intercepts a `dyn_serialize_*` call which does not begin a compound.
+    fn serialize(&self, call: Call<'_>, serializer: &mut dyn Serializer) -> SerializeResult<()> {
+        Ok(call.forward(serializer)?)
+    }
+
+    /// Intercepts a `dyn_serialize_*` call which begins a compound.
+    fn begin<'a>(
+        &self,
+        begin: Begin,
+        serializer: &'a mut dyn Serializer,
+    ) -> SerializeResult<Compound<'a>> {
+        Ok(begin.forward(serializer)?)
+    }
+
+    /// Intercepts a call serializing the content of a compound.
+    fn element(&self, element: Element<'_>, compound: &mut Compound<'_>) -> SerializeResult<()> {
+        Ok(element.forward(compound)?)
+    }
+
+    /// Intercepts a `dyn_end` call.
+    fn end(&self, compound: Compound<'_>) -> SerializeResult<()> {
+        Ok(compound.end()?)
+    }
+
+    /// Tells whether the value and the values inside it are serialized in
+    /// human-readable form, given whether the wrapped serializer is.
+    fn human_readable(&self, human_readable: bool) -> bool {
+        human_readable
+    }
+}
+
+// enum Call
+// ----------------------------------------------------------------------------
+/// A `dyn_serialize_*` call which does not begin a compound, without its
+/// serializer.
+pub(crate) enum Call<'a> {
+    Bool(bool),
+    I8(i8),
+    I16(i16),
+    I32(i32),
+    I64(i64),
+    I128(i128),
+    U8(u8),
+    U16(u16),
+    U32(u32),
+    U64(u64),
+    U128(u128),
+    F32(f32),
+    F64(f64),
+    Char(char),
+    Str(&'a str),
+    Bytes(&'a [u8]),
+    None,
+    Some(&'a dyn Serialize),
+    Unit,
+    UnitStruct(&'static str),
+    UnitVariant(&'static str, u32, &'static str),
+    NewtypeStruct(&'static str, &'a dyn Serialize),
+    NewtypeVariant(&'static str, u32, &'static str, &'a dyn Serialize),
+    CollectStr(&'a dyn Display),
+}
+
+impl Call<'_> {
+    /// Calls the `dyn_serialize_*` method of `serializer`.
+    pub(crate) fn forward(self, serializer: &mut dyn Serializer) -> InplaceSerializeResult<()> {
+        match self {
+            Call::Bool(v) => serializer.dyn_serialize_bool(v),
+            Call::I8(v) => serializer.dyn_serialize_i8(v),
+            Call::I16(v) => serializer.dyn_serialize_i16(v),
+            Call::I32(v) => serializer.dyn_serialize_i32(v),
+            Call::I64(v) => serializer.dyn_serialize_i64(v),
+            Call::I128(v) => serializer.dyn_serialize_i128(v),
+            Call::U8(v) => serializer.dyn_serialize_u8(v),
+            Call::U16(v) => serializer.dyn_serialize_u16(v),
+            Call::U32(v) => serializer.dyn_serialize_u32(v),
+            Call::U64(v) => serializer.dyn_serialize_u64(v),
+            Call::U128(v) => serializer.dyn_serialize_u128(v),
+            Call::F32(v) => serializer.dyn_serialize_f32(v),
+            Call::F64(v) => serializer.dyn_serialize_f64(v),
+            Call::Char(v) => serializer.dyn_serialize_char(v),
+            Call::Str(v) => serializer.dyn_serialize_str(v),
+            Call::Bytes(v) => serializer.dyn_serialize_bytes(v),
+            Call::None => serializer.dyn_serialize_none(),
+            Call::Some(value) => serializer.dyn_serialize_some(value),
+            Call::Unit => serializer.dyn_serialize_unit(),
+            Call::UnitStruct(name) => serializer.dyn_serialize_unit_struct(name),
+            Call::UnitVariant(name, variant_index, variant) => {
+                serializer.dyn_serialize_unit_variant(name, variant_index, variant)
+            }
+            Call::NewtypeStruct(name, value) => {
+                serializer.dyn_serialize_newtype_struct(name, value)
+            }
+            Call::NewtypeVariant(name, variant_index, variant, value) => {
+                serializer.dyn_serialize_newtype_variant(name, variant_index, variant, value)
+            }
+            Call::CollectStr(value) => serializer.dyn_collect_str(value),
+        }
+    }
+}
+
+// enum Begin
+// ----------------------------------------------------------------------------
+/// A `dyn_serialize_*` call which begins a compound, without its serializer.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum Begin {
+    Seq(Option<usize>),
+    Tuple(usize),
+    TupleStruct(&'static str, usize),
+    TupleVariant(&'static str, u32, &'static str, usize),
+    Map(Option<usize>),
+    Struct(&'static str, usize),
+    StructVariant(&'static str, u32, &'static str, usize),
+}
+
+impl Begin {
+    /// Calls the `dyn_serialize_*` method of `serializer`.
+    pub(crate) fn forward(
+        self,
+        serializer: &mut dyn Serializer,
+    ) -> InplaceSerializeResult<Compound<'_>> {
+        Ok(match self {
+            Begin::Seq(len) => Compound::Seq(serializer.dyn_serialize_seq(len)?),
+            Begin::Tuple(len) => Compound::Tuple(serializer.dyn_serialize_tuple(len)?),
+            Begin::TupleStruct(name, len) => {
+                Compound::TupleStruct(serializer.dyn_serialize_tuple_struct(name, len)?)
+            }
+            Begin::TupleVariant(name, variant_index, variant, len) => Compound::TupleVariant(
+                serializer.dyn_serialize_tuple_variant(name, variant_index, variant, len)?,
+            ),
+            Begin::Map(len) => Compound::Map(serializer.dyn_serialize_map(len)?),
+            Begin::Struct(name, len) => {
+                Compound::Struct(serializer.dyn_serialize_struct(name, len)?)
+            }
+            Begin::StructVariant(name, variant_index, variant, len) => Compound::StructVariant(
+                serializer.dyn_serialize_struct_variant(name, variant_index, variant, len)?,
+            ),
+        })
+    }
+}
+
+// enum Compound
+// ----------------------------------------------------------------------------
+/// A compound being serialized by the wrapped serializer.
+pub(crate) enum Compound<'a> {
+    Seq(&'a mut dyn SerializeSeq),
+    Tuple(&'a mut dyn SerializeTuple),
+    TupleStruct(&'a mut dyn SerializeTupleStruct),
+    TupleVariant(&'a mut dyn SerializeTupleVariant),
+    Map(&'a mut dyn SerializeMap),
+    Struct(&'a mut dyn SerializeStruct),
+    StructVariant(&'a mut dyn SerializeStructVariant),
+}
+
+impl Compound<'_> {
+    /// Returns the state a serializer serializing the compound is in.
+    pub(crate) fn state(&self) -> InplaceSerializerState {
+        match self {
+            Compound::Seq(_) => InplaceSerializerState::SerializeSeq,
+            Compound::Tuple(_) => InplaceSerializerState::SerializeTuple,
+            Compound::TupleStruct(_) => InplaceSerializerState::SerializeTupleStruct,
+            Compound::TupleVariant(_) => InplaceSerializerState::SerializeTupleVariant,
+            Compound::Map(_) => InplaceSerializerState::SerializeMap,
+            Compound::Struct(_) => InplaceSerializerState::SerializeStruct,
+            Compound::StructVariant(_) => InplaceSerializerState::SerializeStructVariant,
+        }
+    }
+
+    /// Calls the `dyn_end` method of the compound.
+    pub(crate) fn end(self) -> InplaceSerializeResult<()> {
+        match self {
+            Compound::Seq(compound) => compound.dyn_end(),
+            Compound::Tuple(compound) => compound.dyn_end(),
+            Compound::TupleStruct(compound) => compound.dyn_end(),
+            Compound::TupleVariant(compound) => compound.dyn_end(),
+            Compound::Map(compound) => compound.dyn_end(),
+            Compound::Struct(compound) => compound.dyn_end(),
+            Compound::StructVariant(compound) => compound.dyn_end(),
+        }
+    }
+}
+
+// enum Element
+// ----------------------------------------------------------------------------
+/// A call serializing the content of a compound, without the compound itself.
+pub(crate) enum Element<'a> {
+    /// An element of a sequence or a tuple.
+    Item(&'a dyn Serialize),
+    /// A field of a tuple struct or a tuple variant.
+    Field(&'a dyn Serialize),
+    /// A key of a map.
+    Key(&'a dyn Serialize),
+    /// A value of a map.
+    Value(&'a dyn Serialize),
+    /// An entry of a map.
+    Entry(&'a dyn Serialize, &'a dyn Serialize),
+    /// A field of a struct or a struct variant.
+    StructField(&'static str, &'a dyn Serialize),
+    /// A skipped field of a struct or a struct variant.
+    SkipField(&'static str),
+}
+
+impl Element<'_> {
+    /// Calls the method of `compound`.
+    pub(crate) fn forward(self, compound: &mut Compound<'_>) -> InplaceSerializeResult<()> {
+        match (self, compound) {
+            (Element::Item(value), Compound::Seq(seq)) => seq.dyn_serialize_element(value),
+            (Element::Item(value), Compound::Tuple(tuple)) => tuple.dyn_serialize_element(value),
+            (Element::Field(value), Compound::TupleStruct(tuple)) => {
+                tuple.dyn_serialize_field(value)
+            }
+            (Element::Field(value), Compound::TupleVariant(tuple)) => {
+                tuple.dyn_serialize_field(value)
+            }
+            (Element::Key(key), Compound::Map(map)) => map.dyn_serialize_key(key),
+            (Element::Value(value), Compound::Map(map)) => map.dyn_serialize_value(value),
+            (Element::Entry(key, value), Compound::Map(map)) => map.dyn_serialize_entry(key, value),
+            (Element::StructField(key, value), Compound::Struct(s)) => {
+                s.dyn_serialize_field(key, value)
+            }
+            (Element::StructField(key, value), Compound::StructVariant(s)) => {
+                s.dyn_serialize_field(key, value)
+            }
+            (Element::SkipField(key), Compound::Struct(s)) => s.dyn_skip_field(key),
+            (Element::SkipField(key), Compound::StructVariant(s)) => s.dyn_skip_field(key),
+            (Element::Item(_), _) => Err(InplaceSerializeError::NotSerializeSeq),
+            (Element::Field(_), _) => Err(InplaceSerializeError::NotSerializeTupleStruct),
+            (Element::Key(_) | Element::Value(_) | Element::Entry(..), _) => {
+                Err(InplaceSerializeError::NotSerializeMap)
+            }
+            (Element::StructField(..) | Element::SkipField(_), _) => {
+                Err(InplaceSerializeError::NotSerializeStruct)
+            }
+        }
+    }
+}
+
+// struct MiddlewareSerializer
+// ----------------------------------------------------------------------------
+/// A [`Serializer`] calling the hooks of a middleware before the wrapped
+/// serializer.
+///
+/// Like [`InplaceSerializer`], it also implements the compound traits, and
+/// returns itself when a compound begins.
+///
+/// [`InplaceSerializer`]: crate::ser::InplaceSerializer
+pub(crate) struct MiddlewareSerializer<'a, M> {
+    state: State<'a>,
+    middleware: MiddlewareRef<'a, M>,
+    /// Whether the wrapped serializer is human-readable, captured before it is
+    /// consumed.
+    human_readable: bool,
+    /// The bytes written by the wrapped serializer, captured after it has
+    /// serialized a value, or `None` once it is lent to a compound.
+    bytes_written: Option<u64>,
+}
+
+enum State<'a> {
+    None,
+    Ok,
+    Error(SerializeError),
+    Serializer(&'a mut dyn Serializer),
+    Compound(Compound<'a>),
+}
+
+enum MiddlewareRef<'a, M> {
+    Owned(M),
+    Borrowed(&'a M),
+}
+
+impl<'a, M: Middleware> MiddlewareSerializer<'a, M> {
+    /// Wraps `serializer`, calling the hooks of `middleware`.
+    pub(crate) fn new(serializer: &'a mut dyn Serializer, middleware: M) -> Self {
+        MiddlewareSerializer {
+            human_readable: middleware.human_readable(serializer.dyn_is_human_readable()),
+            bytes_written: None,
+            state: State::Serializer(serializer),
+            middleware: MiddlewareRef::Owned(middleware),
+        }
+    }
+
+    /// Overrides whether the serializer is human-readable.
+    pub(crate) fn set_human_readable(&mut self, human_readable: bool) {
+        self.human_readable = human_readable;
+    }
+
+    /// Returns the middleware.
+    pub(crate) fn middleware(&self) -> &M {
+        match self.middleware {
+            MiddlewareRef::Owned(ref middleware) => middleware,
+            MiddlewareRef::Borrowed(middleware) => middleware,
+        }
+    }
+    fn take(&mut self) -> InplaceSerializeResult<&'a mut dyn Serializer> {
+        match mem::replace(&mut self.state, State::None) {
+            State::Serializer(serializer) => Ok(serializer),
+            other => {
+                self.state = other;
+                Err(InplaceSerializeError::NotSerializer)
+            }
+        }
+    }
+
+    #[cold]
+    fn fail(&mut self, error: SerializeError) -> InplaceSerializeError {
+        let inplace = match error.0 {
+            Ok(_) => InplaceSerializeError::Error,
+            Err(ref error) => *error,
+        };
+        self.state = State::Error(error);
+        inplace
+    }
+
+    fn serialize(
+        &mut self,
+        call: impl FnOnce(&M, &mut dyn Serializer) -> SerializeResult<()>,
+    ) -> InplaceSerializeResult<()> {
+        let serializer = self.take()?;
+        let result = (call)(self.middleware(), &mut *serializer);
+        self.bytes_written = serializer.dyn_bytes_written();
+        match result {
+            Ok(()) => {
+                self.state = State::Ok;
+                Ok(())
+            }
+            Err(error) => Err(self.fail(error)),
+        }
+    }
+
+    fn begin(&mut self, begin: Begin) -> InplaceSerializeResult<()> {
+        let serializer = self.take()?;
+        // the serializer is lent to the compound, or lost if it fails to begin.
+        self.bytes_written = None;
+        match self.middleware().begin(begin, serializer) {
+            Ok(compound) => {
+                self.state = State::Compound(compound);
+                Ok(())
+            }
+            Err(error) => Err(self.fail(error)),
+        }
+    }
+
+    fn element(
+        &mut self,
+        not_ready: InplaceSerializeError,
+        element: impl FnOnce(&M, &mut Compound<'a>) -> SerializeResult<()>,
+    ) -> InplaceSerializeResult<()> {
+        let middleware = match self.middleware {
+            MiddlewareRef::Owned(ref middleware) => middleware,
+            MiddlewareRef::Borrowed(middleware) => middleware,
+        };
+        let State::Compound(ref mut compound) = self.state else {
+            return Err(not_ready);
+        };
+        match (element)(middleware, compound) {
+            Ok(()) => Ok(()),
+            Err(error) => Err(self.fail(error)),
+        }
+    }
+
+    fn end(&mut self, not_ready: InplaceSerializeError) -> InplaceSerializeResult<()> {
+        let compound = match mem::replace(&mut self.state, State::None) {
+            State::Compound(compound) => compound,
+            other => {
+                self.state = other;
+                return Err(not_ready);
+            }
+        };
+        match self.middleware().end(compound) {
+            Ok(()) => {
+                self.state = State::Ok;
+                Ok(())
+            }
+            Err(error) => Err(self.fail(error)),
+        }
+    }
+}
+
+/// Wraps `value` so that it is serialized through a [`MiddlewareSerializer`]
+/// calling the hooks of `middleware` as well.
+pub(crate) fn wrap<'a, M: Middleware>(
+    value: &'a dyn Serialize,
+    middleware: &'a M,
+) -> MiddlewareSerialize<'a, M> {
+    MiddlewareSerialize { value, middleware }
+}
+
+pub(crate) struct MiddlewareSerialize<'a, M> {
+    value: &'a dyn Serialize,
+    middleware: &'a M,
+}
+
+// A serializer of this crate serializes the value through the wrapper as is,
+// so that the wrapper answers for it, e.g. with `dyn_bytes_written`, while a
+// `serde::Serializer` goes through `dyn Serialize`.
+impl<M: Middleware> Serialize for MiddlewareSerialize<'_, M> {
+    fn dyn_serialize(&self, serializer: &mut dyn Serializer) -> SerializeResult<()> {
+        let mut wrapper = MiddlewareSerializer {
+            human_readable: (self.middleware).human_readable(serializer.dyn_is_human_readable()),
+            bytes_written: None,
+            state: State::Serializer(serializer),
+            middleware: MiddlewareRef::Borrowed(self.middleware),
+        };
+        let result = self.value.dyn_serialize(&mut wrapper);
+        match wrapper.state {
+            // An error raised by a hook, or the wrapped serializer.
+            State::Error(error) => Err(error),
+            State::Ok => result,
+            // The value has serialized nothing, or not entirely.
+            _ => result.and_then(|()| Err(InplaceSerializeError::NotSerializer.into())),
+        }
+    }
+}
+
+impl<M: Middleware> Serializer for MiddlewareSerializer<'_, M> {
+    fn dyn_serialize_bool(&mut self, v: bool) -> InplaceSerializeResult<()> {
+        self.serialize(|m, ser| m.serialize(Call::Bool(v), ser))
+    }
+
+    fn dyn_serialize_i8(&mut self, v: i8) -> InplaceSerializeResult<()> {
+        self.serialize(|m, ser| m.serialize(Call::I8(v), ser))
+    }
+
+    fn dyn_serialize_i16(&mut self, v: i16) -> InplaceSerializeResult<()> {
+        self.serialize(|m, ser| m.serialize(Call::I16(v), ser))
+    }
+
+    fn dyn_serialize_i32(&mut self, v: i32) -> InplaceSerializeResult<()> {
+        self.serialize(|m, ser| m.serialize(Call::I32(v), ser))
+    }
+
+    fn dyn_serialize_i64(&mut self, v: i64) -> InplaceSerializeResult<()> {
+        self.serialize(|m, ser| m.serialize(Call::I64(v), ser))
+    }
+
+    fn dyn_serialize_i128(&mut self, v: i128) -> InplaceSerializeResult<()> {
+        self.serialize(|m, ser| m.serialize(Call::I128(v), ser))
+    }
+
+    fn dyn_serialize_u8(&mut self, v: u8) -> InplaceSerializeResult<()> {
+        self.serialize(|m, ser| m.serialize(Call::U8(v), ser))
+    }
+
+    fn dyn_serialize_u16(&mut self, v: u16) -> InplaceSerializeResult<()> {
+        self.serialize(|m, ser| m.serialize(Call::U16(v), ser))
+    }
+
+    fn dyn_serialize_u32(&mut self, v: u32) -> InplaceSerializeResult<()> {
+        self.serialize(|m, ser| m.serialize(Call::U32(v), ser))
+    }
+
+    fn dyn_serialize_u64(&mut self, v: u64) -> InplaceSerializeResult<()> {
+        self.serialize(|m, ser| m.serialize(Call::U64(v), ser))
+    }
+
+    fn dyn_serialize_u128(&mut self, v: u128) -> InplaceSerializeResult<()> {
+        self.serialize(|m, ser| m.serialize(Call::U128(v), ser))
+    }
+
+    fn dyn_serialize_f32(&mut self, v: f32) -> InplaceSerializeResult<()> {
+        self.serialize(|m, ser| m.serialize(Call::F32(v), ser))
+    }
+
+    fn dyn_serialize_f64(&mut self, v: f64) -> InplaceSerializeResult<()> {
+        self.serialize(|m, ser| m.serialize(Call::F64(v), ser))
+    }
+
+    fn dyn_serialize_char(&mut self, v: char) -> InplaceSerializeResult<()> {
+        self.serialize(|m, ser| m.serialize(Call::Char(v), ser))
+    }
+
+    fn dyn_serialize_str(&mut self, v: &str) -> InplaceSerializeResult<()> {
+        self.serialize(|m, ser| m.serialize(Call::Str(v), ser))
+    }
+
+    fn dyn_serialize_bytes(&mut self, v: &[u8]) -> InplaceSerializeResult<()> {
+        self.serialize(|m, ser| m.serialize(Call::Bytes(v), ser))
+    }
+
+    fn dyn_serialize_none(&mut self) -> InplaceSerializeResult<()> {
+        self.serialize(|m, ser| m.serialize(Call::None, ser))
+    }
+
+    fn dyn_serialize_some(&mut self, value: &dyn Serialize) -> InplaceSerializeResult<()> {
+        self.serialize(|m, ser| m.serialize(Call::Some(&wrap(value, m)), ser))
+    }
+
+    fn dyn_serialize_unit(&mut self) -> InplaceSerializeResult<()> {
+        self.serialize(|m, ser| m.serialize(Call::Unit, ser))
+    }
+
+    fn dyn_serialize_unit_struct(&mut self, name: &'static str) -> InplaceSerializeResult<()> {
+        self.serialize(|m, ser| m.serialize(Call::UnitStruct(name), ser))
+    }
+
+    fn dyn_serialize_unit_variant(
+        &mut self,
+        name: &'static str,
+        variant_index: u32,
+        variant: &'static str,
+    ) -> InplaceSerializeResult<()> {
+        self.serialize(|m, ser| m.serialize(Call::UnitVariant(name, variant_index, variant), ser))
+    }
+
+    fn dyn_serialize_newtype_struct(
+        &mut self,
+        name: &'static str,
+        value: &dyn Serialize,
+    ) -> InplaceSerializeResult<()> {
+        self.serialize(|m, ser| m.serialize(Call::NewtypeStruct(name, &wrap(value, m)), ser))
+    }
+
+    fn dyn_serialize_newtype_variant(
+        &mut self,
+        name: &'static str,
+        variant_index: u32,
+        variant: &'static str,
+        value: &dyn Serialize,
+    ) -> InplaceSerializeResult<()> {
+        self.serialize(|m, ser| {
+            let value = wrap(value, m);
+            m.serialize(
+                Call::NewtypeVariant(name, variant_index, variant, &value),
+                ser,
+            )
+        })
+    }
+
+    fn dyn_serialize_seq(
+        &mut self,
+        len: Option<usize>,
+    ) -> InplaceSerializeResult<&mut dyn SerializeSeq> {
+        self.begin(Begin::Seq(len))?;
+        Ok(self)
+    }
+
+    fn dyn_serialize_tuple(
+        &mut self,
+        len: usize,
+    ) -> InplaceSerializeResult<&mut dyn SerializeTuple> {
+        self.begin(Begin::Tuple(len))?;
+        Ok(self)
+    }
+
+    fn dyn_serialize_tuple_struct(
+        &mut self,
+        name: &'static str,
+        len: usize,
+    ) -> InplaceSerializeResult<&mut dyn SerializeTupleStruct> {
+        self.begin(Begin::TupleStruct(name, len))?;
+        Ok(self)
+    }
+
+    fn dyn_serialize_tuple_variant(
+        &mut self,
+        name: &'static str,
+        variant_index: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> InplaceSerializeResult<&mut dyn SerializeTupleVariant> {
+        self.begin(Begin::TupleVariant(name, variant_index, variant, len))?;
+        Ok(self)
+    }
+
+    fn dyn_serialize_map(
+        &mut self,
+        len: Option<usize>,
+    ) -> InplaceSerializeResult<&mut dyn SerializeMap> {
+        self.begin(Begin::Map(len))?;
+        Ok(self)
+    }
+
+    fn dyn_serialize_struct(
+        &mut self,
+        name: &'static str,
+        len: usize,
+    ) -> InplaceSerializeResult<&mut dyn SerializeStruct> {
+        self.begin(Begin::Struct(name, len))?;
+        Ok(self)
+    }
+
+    fn dyn_serialize_struct_variant(
+        &mut self,
+        name: &'static str,
+        variant_index: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> InplaceSerializeResult<&mut dyn SerializeStructVariant> {
+        self.begin(Begin::StructVariant(name, variant_index, variant, len))?;
+        Ok(self)
+    }
+
+    fn dyn_collect_str(&mut self, value: &dyn Display) -> InplaceSerializeResult<()> {
+        self.serialize(|m, ser| m.serialize(Call::CollectStr(value), ser))
+    }
+
+    fn dyn_is_human_readable(&self) -> bool {
+        self.human_readable
+    }
+
+    fn dyn_bytes_written(&self) -> Option<u64> {
+        match self.state {
+            State::Serializer(ref serializer) => serializer.dyn_bytes_written(),
+            State::None | State::Ok | State::Error(_) | State::Compound(_) => self.bytes_written,
+        }
+    }
+}
+
+impl<M: Middleware> SerializeSeq for MiddlewareSerializer<'_, M> {
+    fn dyn_serialize_element(&mut self, value: &dyn Serialize) -> InplaceSerializeResult<()> {
+        self.element(InplaceSerializeError::NotSerializeSeq, |m, compound| {
+            m.element(Element::Item(&wrap(value, m)), compound)
+        })
+    }
+
+    fn dyn_end(&mut self) -> InplaceSerializeResult<()> {
+        self.end(InplaceSerializeError::NotSerializeSeq)
+    }
+}
+
+impl<M: Middleware> SerializeTuple for MiddlewareSerializer<'_, M> {
+    fn dyn_serialize_element(&mut self, value: &dyn Serialize) -> InplaceSerializeResult<()> {
+        self.element(InplaceSerializeError::NotSerializeTuple, |m, compound| {
+            m.element(Element::Item(&wrap(value, m)), compound)
+        })
+    }
+
+    fn dyn_end(&mut self) -> InplaceSerializeResult<()> {
+        self.end(InplaceSerializeError::NotSerializeTuple)
+    }
+}
+
+impl<M: Middleware> SerializeTupleStruct for MiddlewareSerializer<'_, M> {
+    fn dyn_serialize_field(&mut self, value: &dyn Serialize) -> InplaceSerializeResult<()> {
+        self.element(
+            InplaceSerializeError::NotSerializeTupleStruct,
+            |m, compound| m.element(Element::Field(&wrap(value, m)), compound),
+        )
+    }
+
+    fn dyn_end(&mut self) -> InplaceSerializeResult<()> {
+        self.end(InplaceSerializeError::NotSerializeTupleStruct)
+    }
+}
+
+impl<M: Middleware> SerializeTupleVariant for MiddlewareSerializer<'_, M> {
+    fn dyn_serialize_field(&mut self, value: &dyn Serialize) -> InplaceSerializeResult<()> {
+        self.element(
+            InplaceSerializeError::NotSerializeTupleVariant,
+            |m, compound| m.element(Element::Field(&wrap(value, m)), compound),
+        )
+    }
+
+    fn dyn_end(&mut self) -> InplaceSerializeResult<()> {
+        self.end(InplaceSerializeError::NotSerializeTupleVariant)
+    }
+}
+
+impl<M: Middleware> SerializeMap for MiddlewareSerializer<'_, M> {
+    fn dyn_serialize_key(&mut self, key: &dyn Serialize) -> InplaceSerializeResult<()> {
+        self.element(InplaceSerializeError::NotSerializeMap, |m, compound| {
+            m.element(Element::Key(&wrap(key, m)), compound)
+        })
+    }
+
+    fn dyn_serialize_value(&mut self, value: &dyn Serialize) -> InplaceSerializeResult<()> {
+        self.element(InplaceSerializeError::NotSerializeMap, |m, compound| {
+            m.element(Element::Value(&wrap(value, m)), compound)
+        })
+    }
+
+    fn dyn_serialize_entry(
+        &mut self,
+        key: &dyn Serialize,
+        value: &dyn Serialize,
+    ) -> InplaceSerializeResult<()> {
+        self.element(InplaceSerializeError::NotSerializeMap, |m, compound| {
+            m.element(Element::Entry(&wrap(key, m), &wrap(value, m)), compound)
+        })
+    }
+
+    fn dyn_end(&mut self) -> InplaceSerializeResult<()> {
+        self.end(InplaceSerializeError::NotSerializeMap)
+    }
+}
+
+impl<M: Middleware> SerializeStruct for MiddlewareSerializer<'_, M> {
+    fn dyn_serialize_field(
+        &mut self,
+        key: &'static str,
+        value: &dyn Serialize,
+    ) -> InplaceSerializeResult<()> {
+        self.element(InplaceSerializeError::NotSerializeStruct, |m, compound| {
+            m.element(Element::StructField(key, &wrap(value, m)), compound)
+        })
+    }
+
+    fn dyn_skip_field(&mut self, key: &'static str) -> InplaceSerializeResult<()> {
+        self.element(InplaceSerializeError::NotSerializeStruct, |m, compound| {
+            m.element(Element::SkipField(key), compound)
+        })
+    }
+
+    fn dyn_end(&mut self) -> InplaceSerializeResult<()> {
+        self.end(InplaceSerializeError::NotSerializeStruct)
+    }
+}
+
+impl<M: Middleware> SerializeStructVariant for MiddlewareSerializer<'_, M> {
+    fn dyn_serialize_field(
+        &mut self,
+        key: &'static str,
+        value: &dyn Serialize,
+    ) -> InplaceSerializeResult<()> {
+        self.element(
+            InplaceSerializeError::NotSerializeStructVariant,
+            |m, compound| m.element(Element::StructField(key, &wrap(value, m)), compound),
+        )
+    }
+
+    fn dyn_skip_field(&mut self, key: &'static str) -> InplaceSerializeResult<()> {
+        self.element(
+            InplaceSerializeError::NotSerializeStructVariant,
+            |m, compound| m.element(Element::SkipField(key), compound),
+        )
+    }
+
+    fn dyn_end(&mut self) -> InplaceSerializeResult<()> {
+        self.end(InplaceSerializeError::NotSerializeStructVariant)
+    }
+}
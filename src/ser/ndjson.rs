@@ -0,0 +1,338 @@
+//! Newline-delimited JSON serialization, see [`NdjsonSerializer`].
+
+use std::io::{self, Write as _};
+
+use serde::ser::Error as _;
+
+use crate::ser::Serializer;
+use crate::ser::counted::Counted;
+use crate::ser::{InplaceSerializeError, InplaceSerializeResult, InplaceSerializerState};
+use crate::ser::{Serialize, SerializeMap, SerializeSeq, SerializeStruct, SerializeStructVariant};
+use crate::ser::{SerializeError, SerializeTuple, SerializeTupleStruct, SerializeTupleVariant};
+
+/// A [`Serializer`] writing each element of a top-level sequence as its own
+/// line of JSON, also known as [NDJSON] or JSON Lines.
+///
+/// Every element is written as compact JSON followed by `\n`, and the writer
+/// is flushed after each line, so that the consumer of a log pipeline sees
+/// the elements as soon as they are serialized. The top-level value must be a
+/// sequence or a tuple; any other value is rejected.
+///
+/// The serialization returns an error telling only that it has done
+/// unsuccessfully, while [`NdjsonSerializer::error`] tells why it has.
+///
+/// # Examples
+///
+/// ```
+/// # use dyn_serde::Serialize;
+/// # use dyn_serde::ser::NdjsonSerializer;
+/// #
+/// let events = [("start", 1), ("stop", 2)];
+/// let mut serializer = NdjsonSerializer::new(Vec::new());
+/// (&events as &dyn Serialize).dyn_serialize(&mut serializer).unwrap();
+/// assert_eq!(serializer.into_inner(), b"[\"start\",1]\n[\"stop\",2]\n");
+/// ```
+///
+/// [NDJSON]: https://github.com/ndjson/ndjson-spec
+#[derive(Debug)]
+pub struct NdjsonSerializer<W> {
+    writer: Counted<W>,
+    state: InplaceSerializerState,
+    error: Option<SerializeError>,
+}
+
+impl<W: io::Write> NdjsonSerializer<W> {
+    /// Creates a serializer writing lines to `writer`.
+    #[must_use]
+    pub fn new(writer: W) -> Self {
+        NdjsonSerializer {
+            writer: Counted::new(writer),
+            state: InplaceSerializerState::Serializer,
+            error: None,
+        }
+    }
+
+    /// Returns the first error raised while serializing, if any.
+    #[must_use]
+    pub fn error(&self) -> Option<&SerializeError> {
+        self.error.as_ref()
+    }
+
+    /// Unwraps the writer.
+    #[must_use]
+    pub fn into_inner(self) -> W {
+        self.writer.inner
+    }
+
+    #[cold]
+    fn fail(&mut self, error: SerializeError) -> InplaceSerializeError {
+        self.error.get_or_insert(error);
+        self.state = InplaceSerializerState::Error;
+        InplaceSerializeError::Error
+    }
+
+    #[cold]
+    fn not_a_sequence(&mut self) -> InplaceSerializeError {
+        if self.state != InplaceSerializerState::Serializer {
+            return InplaceSerializeError::NotSerializer;
+        }
+        self.fail(SerializeError::custom(
+            "the top-level value of NDJSON must be a sequence",
+        ))
+    }
+
+    fn begin(&mut self, state: InplaceSerializerState) -> InplaceSerializeResult<&mut Self> {
+        if self.state != InplaceSerializerState::Serializer {
+            return Err(InplaceSerializeError::NotSerializer);
+        }
+        self.state = state;
+        Ok(self)
+    }
+
+    fn line(
+        &mut self,
+        value: &dyn Serialize,
+        error: InplaceSerializeError,
+    ) -> InplaceSerializeResult<()> {
+        if !matches!(
+            self.state,
+            InplaceSerializerState::SerializeSeq | InplaceSerializerState::SerializeTuple
+        ) {
+            return Err(error);
+        }
+
+        let result = match serde_json::to_writer(&mut self.writer, value) {
+            Ok(()) => self
+                .writer
+                .write_all(b"\n")
+                .and_then(|()| self.writer.flush())
+                .map_err(SerializeError::custom),
+            Err(error) => Err(SerializeError::custom(error)),
+        };
+        result.map_err(|error| self.fail(error))
+    }
+
+    fn end(&mut self, error: InplaceSerializeError) -> InplaceSerializeResult<()> {
+        if !matches!(
+            self.state,
+            InplaceSerializerState::SerializeSeq | InplaceSerializerState::SerializeTuple
+        ) {
+            return Err(error);
+        }
+        self.state = InplaceSerializerState::Ok;
+        Ok(())
+    }
+}
+
+/// Writes every item of `items` as its own line of JSON to `writer`, pulling
+/// the next item only once the previous one has been written and flushed.
+///
+/// This drives an [`NdjsonSerializer`] through the top-level sequence itself,
+/// so that an export of any size never holds more than one item in memory:
+/// each item is produced by the iterator, written, and dropped before the next
+/// one is produced. Unlike serializing the iterator as a sequence, no value
+/// needs to own the items or to know their number.
+///
+/// Returns the writer once every item has been written, or the first error
+/// raised while serializing or writing.
+///
+/// # Examples
+///
+/// ```
+/// let rows = (1..=3).map(|i| (i, i * i));
+/// let output = dyn_serde::ser::write_json_lines(Vec::new(), rows).unwrap();
+/// assert_eq!(output, b"[1,1]\n[2,4]\n[3,9]\n");
+/// ```
+pub fn write_json_lines<W, I>(writer: W, items: I) -> Result<W, SerializeError>
+where
+    W: io::Write,
+    I: IntoIterator,
+    I::Item: serde::Serialize,
+{
+    let mut serializer = NdjsonSerializer::new(writer);
+    let write = || {
+        let seq = serializer.dyn_serialize_seq(None)?;
+        for item in items {
+            seq.dyn_serialize_element(&item)?;
+        }
+        seq.dyn_end()
+    };
+    match write() {
+        Ok(()) => Ok(serializer.into_inner()),
+        Err(error) => Err(serializer.error.take().unwrap_or_else(|| error.into())),
+    }
+}
+
+impl<W: io::Write> Serializer for NdjsonSerializer<W> {
+    fn dyn_serialize_bool(&mut self, v: bool) -> InplaceSerializeResult<()> {
+        let _ = v;
+        Err(self.not_a_sequence())
+    }
+
+    fn dyn_serialize_i64(&mut self, v: i64) -> InplaceSerializeResult<()> {
+        let _ = v;
+        Err(self.not_a_sequence())
+    }
+
+    fn dyn_serialize_i128(&mut self, v: i128) -> InplaceSerializeResult<()> {
+        let _ = v;
+        Err(self.not_a_sequence())
+    }
+
+    fn dyn_serialize_u64(&mut self, v: u64) -> InplaceSerializeResult<()> {
+        let _ = v;
+        Err(self.not_a_sequence())
+    }
+
+    fn dyn_serialize_u128(&mut self, v: u128) -> InplaceSerializeResult<()> {
+        let _ = v;
+        Err(self.not_a_sequence())
+    }
+
+    fn dyn_serialize_f64(&mut self, v: f64) -> InplaceSerializeResult<()> {
+        let _ = v;
+        Err(self.not_a_sequence())
+    }
+
+    fn dyn_serialize_str(&mut self, v: &str) -> InplaceSerializeResult<()> {
+        let _ = v;
+        Err(self.not_a_sequence())
+    }
+
+    fn dyn_serialize_bytes(&mut self, v: &[u8]) -> InplaceSerializeResult<()> {
+        let _ = v;
+        Err(self.not_a_sequence())
+    }
+
+    fn dyn_serialize_none(&mut self) -> InplaceSerializeResult<()> {
+        Err(self.not_a_sequence())
+    }
+
+    fn dyn_serialize_some(&mut self, value: &dyn Serialize) -> InplaceSerializeResult<()> {
+        // `Some` is transparent in JSON.
+        value.dyn_serialize(self).map_err(|error| self.fail(error))
+    }
+
+    fn dyn_serialize_unit(&mut self) -> InplaceSerializeResult<()> {
+        Err(self.not_a_sequence())
+    }
+
+    fn dyn_serialize_unit_variant(
+        &mut self,
+        name: &'static str,
+        variant_index: u32,
+        variant: &'static str,
+    ) -> InplaceSerializeResult<()> {
+        let _ = (name, variant_index, variant);
+        Err(self.not_a_sequence())
+    }
+
+    fn dyn_serialize_newtype_struct(
+        &mut self,
+        name: &'static str,
+        value: &dyn Serialize,
+    ) -> InplaceSerializeResult<()> {
+        // Newtype structs are transparent in JSON.
+        let _ = name;
+        value.dyn_serialize(self).map_err(|error| self.fail(error))
+    }
+
+    fn dyn_serialize_newtype_variant(
+        &mut self,
+        name: &'static str,
+        variant_index: u32,
+        variant: &'static str,
+        value: &dyn Serialize,
+    ) -> InplaceSerializeResult<()> {
+        let _ = (name, variant_index, variant, value);
+        Err(self.not_a_sequence())
+    }
+
+    fn dyn_serialize_seq(
+        &mut self,
+        len: Option<usize>,
+    ) -> InplaceSerializeResult<&mut dyn SerializeSeq> {
+        let _ = len;
+        Ok(self.begin(InplaceSerializerState::SerializeSeq)?)
+    }
+
+    fn dyn_serialize_tuple(
+        &mut self,
+        len: usize,
+    ) -> InplaceSerializeResult<&mut dyn SerializeTuple> {
+        let _ = len;
+        Ok(self.begin(InplaceSerializerState::SerializeTuple)?)
+    }
+
+    fn dyn_serialize_tuple_struct(
+        &mut self,
+        name: &'static str,
+        len: usize,
+    ) -> InplaceSerializeResult<&mut dyn SerializeTupleStruct> {
+        let _ = (name, len);
+        Err(self.not_a_sequence())
+    }
+
+    fn dyn_serialize_tuple_variant(
+        &mut self,
+        name: &'static str,
+        variant_index: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> InplaceSerializeResult<&mut dyn SerializeTupleVariant> {
+        let _ = (name, variant_index, variant, len);
+        Err(self.not_a_sequence())
+    }
+
+    fn dyn_serialize_map(
+        &mut self,
+        len: Option<usize>,
+    ) -> InplaceSerializeResult<&mut dyn SerializeMap> {
+        let _ = len;
+        Err(self.not_a_sequence())
+    }
+
+    fn dyn_serialize_struct(
+        &mut self,
+        name: &'static str,
+        len: usize,
+    ) -> InplaceSerializeResult<&mut dyn SerializeStruct> {
+        let _ = (name, len);
+        Err(self.not_a_sequence())
+    }
+
+    fn dyn_serialize_struct_variant(
+        &mut self,
+        name: &'static str,
+        variant_index: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> InplaceSerializeResult<&mut dyn SerializeStructVariant> {
+        let _ = (name, variant_index, variant, len);
+        Err(self.not_a_sequence())
+    }
+
+    fn dyn_bytes_written(&self) -> Option<u64> {
+        Some(self.writer.written)
+    }
+}
+
+impl<W: io::Write> SerializeSeq for NdjsonSerializer<W> {
+    fn dyn_serialize_element(&mut self, value: &dyn Serialize) -> InplaceSerializeResult<()> {
+        self.line(value, InplaceSerializeError::NotSerializeSeq)
+    }
+
+    fn dyn_end(&mut self) -> InplaceSerializeResult<()> {
+        self.end(InplaceSerializeError::NotSerializeSeq)
+    }
+}
+
+impl<W: io::Write> SerializeTuple for NdjsonSerializer<W> {
+    fn dyn_serialize_element(&mut self, value: &dyn Serialize) -> InplaceSerializeResult<()> {
+        self.line(value, InplaceSerializeError::NotSerializeTuple)
+    }
+
+    fn dyn_end(&mut self) -> InplaceSerializeResult<()> {
+        self.end(InplaceSerializeError::NotSerializeTuple)
+    }
+}
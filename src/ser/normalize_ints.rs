@@ -0,0 +1,73 @@
+//! A [`Serializer`] wrapper normalizing the width of integers, see
+//! [`NormalizeInts`].
+
+use core::fmt::{self, Debug, Formatter};
+
+use crate::ser::middleware::{self, Call, Middleware, MiddlewareSerializer};
+use crate::ser::{SerializeResult, Serializer};
+
+/// A [`Serializer`] wrapper which forwards every signed integer as an `i64`
+/// and every unsigned integer as a `u64`, so that logically equal values
+/// serialize identically regardless of the width of their Rust type, e.g.
+/// `5u8` and `5u32`.
+///
+/// Widening never overflows. An `i128` or a `u128` is narrowed to an `i64` or
+/// a `u64` if it fits, and is forwarded unchanged otherwise, so no value is
+/// ever truncated. The same applies at every depth of the value.
+///
+/// # Examples
+///
+/// ```
+/// # use dyn_serde::{Serialize, Serializer};
+/// # use dyn_serde::ser::NormalizeInts;
+/// #
+/// let mut buf = Vec::new();
+/// let mut serializer = serde_json::Serializer::new(&mut buf);
+/// let mut serializer = <dyn Serializer>::new(&mut serializer);
+/// let mut serializer = NormalizeInts::new(&mut serializer);
+///
+/// (&(5u8, -5i16, 5u128) as &dyn Serialize).dyn_serialize(&mut serializer).unwrap();
+/// assert_eq!(buf, b"[5,-5,5]");
+/// ```
+pub struct NormalizeInts<'a> {
+    serializer: MiddlewareSerializer<'a, Normalize>,
+}
+
+impl<'a> NormalizeInts<'a> {
+    /// Wraps `serializer`, normalizing the width of integers.
+    #[must_use]
+    pub fn new(serializer: &'a mut dyn Serializer) -> Self {
+        NormalizeInts {
+            serializer: MiddlewareSerializer::new(serializer, Normalize),
+        }
+    }
+}
+
+impl Debug for NormalizeInts<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("NormalizeInts").finish_non_exhaustive()
+    }
+}
+
+impl Serializer for NormalizeInts<'_> {
+    middleware::forward_to_middleware_serializer!(serializer);
+}
+
+struct Normalize;
+
+impl Middleware for Normalize {
+    fn serialize(&self, call: Call<'_>, serializer: &mut dyn Serializer) -> SerializeResult<()> {
+        let call = match call {
+            Call::I8(v) => Call::I64(i64::from(v)),
+            Call::I16(v) => Call::I64(i64::from(v)),
+            Call::I32(v) => Call::I64(i64::from(v)),
+            Call::I128(v) => i64::try_from(v).map_or(Call::I128(v), Call::I64),
+            Call::U8(v) => Call::U64(u64::from(v)),
+            Call::U16(v) => Call::U64(u64::from(v)),
+            Call::U32(v) => Call::U64(u64::from(v)),
+            Call::U128(v) => u64::try_from(v).map_or(Call::U128(v), Call::U64),
+            call => call,
+        };
+        Ok(call.forward(serializer)?)
+    }
+}
@@ -0,0 +1,367 @@
+//! A serializer writing nothing, see [`NullSerializer`].
+
+use core::fmt::Display;
+
+use crate::ser::SerializeError;
+
+/// A [`serde::Serializer`] which writes nothing, while still serializing
+/// every part of the value, e.g. to run the checks of a [`Serializer`]
+/// wrapper without the cost of a real format.
+///
+/// Every element, field and entry is serialized with another
+/// `NullSerializer`, so the [`Serialize`] implementations of the value run
+/// entirely and their errors are returned, with the exception of the values
+/// given to `collect_str`, which are never formatted. It is human-readable,
+/// and it is wrapped into a [`Serializer`] with `<dyn Serializer>::new` as any
+/// other serializer.
+///
+/// [`Serializer`]: crate::Serializer
+/// [`Serialize`]: serde::Serialize
+///
+/// # Examples
+///
+/// ```
+/// # use dyn_serde::ser::NullSerializer;
+/// #
+/// struct Failing;
+///
+/// impl serde::Serialize for Failing {
+///     fn serialize<S: serde::Serializer>(&self, _: S) -> Result<S::Ok, S::Error> {
+///         Err(serde::ser::Error::custom("cannot serialize"))
+///     }
+/// }
+///
+/// assert!(serde::Serialize::serialize(&[1, 2, 3], NullSerializer).is_ok());
+/// let error = serde::Serialize::serialize(&[Failing], NullSerializer).unwrap_err();
+/// assert_eq!(error.to_string(), "cannot serialize");
+/// ```
+#[derive(Clone, Copy, Debug, Default)]
+pub struct NullSerializer;
+
+impl serde::Serializer for NullSerializer {
+    type Ok = ();
+    type Error = SerializeError;
+    type SerializeSeq = Self;
+    type SerializeTuple = Self;
+    type SerializeTupleStruct = Self;
+    type SerializeTupleVariant = Self;
+    type SerializeMap = Self;
+    type SerializeStruct = Self;
+    type SerializeStructVariant = Self;
+
+    fn serialize_bool(self, v: bool) -> Result<(), SerializeError> {
+        let _ = v;
+        Ok(())
+    }
+
+    fn serialize_i8(self, v: i8) -> Result<(), SerializeError> {
+        let _ = v;
+        Ok(())
+    }
+
+    fn serialize_i16(self, v: i16) -> Result<(), SerializeError> {
+        let _ = v;
+        Ok(())
+    }
+
+    fn serialize_i32(self, v: i32) -> Result<(), SerializeError> {
+        let _ = v;
+        Ok(())
+    }
+
+    fn serialize_i64(self, v: i64) -> Result<(), SerializeError> {
+        let _ = v;
+        Ok(())
+    }
+
+    fn serialize_i128(self, v: i128) -> Result<(), SerializeError> {
+        let _ = v;
+        Ok(())
+    }
+
+    fn serialize_u8(self, v: u8) -> Result<(), SerializeError> {
+        let _ = v;
+        Ok(())
+    }
+
+    fn serialize_u16(self, v: u16) -> Result<(), SerializeError> {
+        let _ = v;
+        Ok(())
+    }
+
+    fn serialize_u32(self, v: u32) -> Result<(), SerializeError> {
+        let _ = v;
+        Ok(())
+    }
+
+    fn serialize_u64(self, v: u64) -> Result<(), SerializeError> {
+        let _ = v;
+        Ok(())
+    }
+
+    fn serialize_u128(self, v: u128) -> Result<(), SerializeError> {
+        let _ = v;
+        Ok(())
+    }
+
+    fn serialize_f32(self, v: f32) -> Result<(), SerializeError> {
+        let _ = v;
+        Ok(())
+    }
+
+    fn serialize_f64(self, v: f64) -> Result<(), SerializeError> {
+        let _ = v;
+        Ok(())
+    }
+
+    fn serialize_char(self, v: char) -> Result<(), SerializeError> {
+        let _ = v;
+        Ok(())
+    }
+
+    fn serialize_str(self, v: &str) -> Result<(), SerializeError> {
+        let _ = v;
+        Ok(())
+    }
+
+    fn serialize_bytes(self, v: &[u8]) -> Result<(), SerializeError> {
+        let _ = v;
+        Ok(())
+    }
+
+    fn serialize_none(self) -> Result<(), SerializeError> {
+        Ok(())
+    }
+
+    fn serialize_some<T>(self, value: &T) -> Result<(), SerializeError>
+    where
+        T: ?Sized + serde::Serialize,
+    {
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> Result<(), SerializeError> {
+        Ok(())
+    }
+
+    fn serialize_unit_struct(self, name: &'static str) -> Result<(), SerializeError> {
+        let _ = name;
+        Ok(())
+    }
+
+    fn serialize_unit_variant(
+        self,
+        name: &'static str,
+        variant_index: u32,
+        variant: &'static str,
+    ) -> Result<(), SerializeError> {
+        let _ = (name, variant_index, variant);
+        Ok(())
+    }
+
+    fn serialize_newtype_struct<T>(
+        self,
+        name: &'static str,
+        value: &T,
+    ) -> Result<(), SerializeError>
+    where
+        T: ?Sized + serde::Serialize,
+    {
+        let _ = name;
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T>(
+        self,
+        name: &'static str,
+        variant_index: u32,
+        variant: &'static str,
+        value: &T,
+    ) -> Result<(), SerializeError>
+    where
+        T: ?Sized + serde::Serialize,
+    {
+        let _ = (name, variant_index, variant);
+        value.serialize(self)
+    }
+
+    fn serialize_seq(self, len: Option<usize>) -> Result<Self, SerializeError> {
+        let _ = len;
+        Ok(self)
+    }
+
+    fn serialize_tuple(self, len: usize) -> Result<Self, SerializeError> {
+        let _ = len;
+        Ok(self)
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        name: &'static str,
+        len: usize,
+    ) -> Result<Self, SerializeError> {
+        let _ = (name, len);
+        Ok(self)
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        name: &'static str,
+        variant_index: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> Result<Self, SerializeError> {
+        let _ = (name, variant_index, variant, len);
+        Ok(self)
+    }
+
+    fn serialize_map(self, len: Option<usize>) -> Result<Self, SerializeError> {
+        let _ = len;
+        Ok(self)
+    }
+
+    fn serialize_struct(self, name: &'static str, len: usize) -> Result<Self, SerializeError> {
+        let _ = (name, len);
+        Ok(self)
+    }
+
+    fn serialize_struct_variant(
+        self,
+        name: &'static str,
+        variant_index: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> Result<Self, SerializeError> {
+        let _ = (name, variant_index, variant, len);
+        Ok(self)
+    }
+
+    fn collect_str<T>(self, value: &T) -> Result<(), SerializeError>
+    where
+        T: ?Sized + Display,
+    {
+        let _ = value;
+        Ok(())
+    }
+}
+
+impl serde::ser::SerializeSeq for NullSerializer {
+    type Ok = ();
+    type Error = SerializeError;
+
+    fn serialize_element<T>(&mut self, value: &T) -> Result<(), SerializeError>
+    where
+        T: ?Sized + serde::Serialize,
+    {
+        value.serialize(NullSerializer)
+    }
+
+    fn end(self) -> Result<(), SerializeError> {
+        Ok(())
+    }
+}
+
+impl serde::ser::SerializeTuple for NullSerializer {
+    type Ok = ();
+    type Error = SerializeError;
+
+    fn serialize_element<T>(&mut self, value: &T) -> Result<(), SerializeError>
+    where
+        T: ?Sized + serde::Serialize,
+    {
+        value.serialize(NullSerializer)
+    }
+
+    fn end(self) -> Result<(), SerializeError> {
+        Ok(())
+    }
+}
+
+impl serde::ser::SerializeTupleStruct for NullSerializer {
+    type Ok = ();
+    type Error = SerializeError;
+
+    fn serialize_field<T>(&mut self, value: &T) -> Result<(), SerializeError>
+    where
+        T: ?Sized + serde::Serialize,
+    {
+        value.serialize(NullSerializer)
+    }
+
+    fn end(self) -> Result<(), SerializeError> {
+        Ok(())
+    }
+}
+
+impl serde::ser::SerializeTupleVariant for NullSerializer {
+    type Ok = ();
+    type Error = SerializeError;
+
+    fn serialize_field<T>(&mut self, value: &T) -> Result<(), SerializeError>
+    where
+        T: ?Sized + serde::Serialize,
+    {
+        value.serialize(NullSerializer)
+    }
+
+    fn end(self) -> Result<(), SerializeError> {
+        Ok(())
+    }
+}
+
+impl serde::ser::SerializeMap for NullSerializer {
+    type Ok = ();
+    type Error = SerializeError;
+
+    fn serialize_key<T>(&mut self, key: &T) -> Result<(), SerializeError>
+    where
+        T: ?Sized + serde::Serialize,
+    {
+        key.serialize(NullSerializer)
+    }
+
+    fn serialize_value<T>(&mut self, value: &T) -> Result<(), SerializeError>
+    where
+        T: ?Sized + serde::Serialize,
+    {
+        value.serialize(NullSerializer)
+    }
+
+    fn end(self) -> Result<(), SerializeError> {
+        Ok(())
+    }
+}
+
+impl serde::ser::SerializeStruct for NullSerializer {
+    type Ok = ();
+    type Error = SerializeError;
+
+    fn serialize_field<T>(&mut self, key: &'static str, value: &T) -> Result<(), SerializeError>
+    where
+        T: ?Sized + serde::Serialize,
+    {
+        let _ = key;
+        value.serialize(NullSerializer)
+    }
+
+    fn end(self) -> Result<(), SerializeError> {
+        Ok(())
+    }
+}
+
+impl serde::ser::SerializeStructVariant for NullSerializer {
+    type Ok = ();
+    type Error = SerializeError;
+
+    fn serialize_field<T>(&mut self, key: &'static str, value: &T) -> Result<(), SerializeError>
+    where
+        T: ?Sized + serde::Serialize,
+    {
+        let _ = key;
+        value.serialize(NullSerializer)
+    }
+
+    fn end(self) -> Result<(), SerializeError> {
+        Ok(())
+    }
+}
@@ -0,0 +1,577 @@
+//! [`Serializer`] wrappers omitting the entries of maps and structs whose
+//! value is empty, see [`OmitUnitValues`] and [`OmitUnitStructs`].
+
+use core::cell::RefCell;
+use core::fmt::{self, Debug, Formatter};
+
+#[cfg(not(feature = "std"))]
+use alloc::string::String;
+#[cfg(not(feature = "std"))]
+use alloc::vec::Vec;
+
+use crate::ser::middleware::{self, Begin, Compound, Element};
+use crate::ser::middleware::{Middleware, MiddlewareSerializer};
+use crate::ser::{InplaceSerializeError, InplaceSerializeResult, Serialize, SerializeResult};
+use crate::ser::{SerializeMap, SerializeSeq, SerializeStruct, SerializeStructVariant};
+use crate::ser::{SerializeTuple, SerializeTupleStruct, SerializeTupleVariant, Serializer};
+
+/// A [`Serializer`] wrapper which omits every entry of a map, and every field
+/// of a struct, whose value serializes as `None`, `()` or a unit struct.
+///
+/// `#[serde(skip_serializing_if = "Option::is_none")]` omits the `None` fields
+/// of a struct declaring it, but not the entries of maps, nor the values which
+/// are units rather than options. `OmitUnitValues` omits them all, at every
+/// depth of the value, for a terser output. A value behind a newtype struct is
+/// omitted if the value inside is, while `Some(())` is kept, being present.
+///
+/// Every value of a map or a struct is serialized twice: once to tell whether
+/// it is empty, which stops at its first call, and once to the output. The
+/// omitted fields of a struct are reported to the wrapped serializer as
+/// skipped. As a map cannot tell how many entries it keeps before they are
+/// serialized, its length is forwarded as unknown, which formats writing the
+/// length of maps up front may reject. A key serialized apart from its value
+/// is held back until its value is, which requires the key to be a scalar,
+/// e.g. a string or an integer; other keys are forwarded with their value
+/// whatever it is.
+///
+/// # Examples
+///
+/// ```
+/// # use std::collections::BTreeMap;
+/// # use dyn_serde::{Serialize, Serializer};
+/// # use dyn_serde::ser::OmitUnitValues;
+/// #
+/// let value = BTreeMap::from([("a", Some(1)), ("b", None)]);
+///
+/// let mut buf = Vec::new();
+/// let mut serializer = serde_json::Serializer::new(&mut buf);
+/// let mut serializer = <dyn Serializer>::new(&mut serializer);
+/// (&value as &dyn Serialize)
+///     .dyn_serialize(&mut OmitUnitValues::new(&mut serializer))
+///     .unwrap();
+/// assert_eq!(buf, br#"{"a":1}"#);
+/// ```
+pub struct OmitUnitValues<'a> {
+    serializer: MiddlewareSerializer<'a, Omit>,
+}
+
+impl<'a> OmitUnitValues<'a> {
+    /// Wraps `serializer`, omitting the entries whose value is empty.
+    #[must_use]
+    pub fn new(serializer: &'a mut dyn Serializer) -> Self {
+        let omit = Omit {
+            key: RefCell::new(None),
+        };
+        OmitUnitValues {
+            serializer: MiddlewareSerializer::new(serializer, omit),
+        }
+    }
+}
+
+impl Debug for OmitUnitValues<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("OmitUnitValues").finish_non_exhaustive()
+    }
+}
+
+impl Serializer for OmitUnitValues<'_> {
+    middleware::forward_to_middleware_serializer!(serializer);
+}
+
+/// A [`Serializer`] wrapper which omits every field of a struct whose value
+/// serializes as a unit struct, e.g. a `PhantomData` or another zero-sized
+/// marker.
+///
+/// Markers are fields for the type system rather than for the output, and
+/// `#[serde(skip)]` needs access to the type declaring them. `OmitUnitStructs`
+/// omits them at every depth of the value, from structs and struct variants,
+/// possibly behind a newtype struct, while leaving the fields which are
+/// `None` or `()` alone, unlike [`OmitUnitValues`]. The entries of maps are
+/// never omitted.
+///
+/// Every field is serialized twice: once to tell whether it is a unit struct,
+/// which stops at its first call, and once to the output. The omitted fields
+/// are reported to the wrapped serializer through `skip_field`, while the
+/// length given to `serialize_struct` still counts them, since it is known
+/// before the fields are. Self-describing formats such as JSON ignore the
+/// length, but a format writing it up front without handling `skip_field`
+/// writes a wrong one.
+///
+/// # Examples
+///
+/// ```
+/// # use std::marker::PhantomData;
+/// # use dyn_serde::{Serialize, Serializer};
+/// # use dyn_serde::ser::OmitUnitStructs;
+/// #
+/// #[derive(serde::Serialize)]
+/// struct Id<T> {
+///     value: u32,
+///     kind: PhantomData<T>,
+/// }
+///
+/// let id = Id::<String> { value: 1, kind: PhantomData };
+///
+/// let mut buf = Vec::new();
+/// let mut serializer = serde_json::Serializer::new(&mut buf);
+/// let mut serializer = <dyn Serializer>::new(&mut serializer);
+/// (&id as &dyn Serialize)
+///     .dyn_serialize(&mut OmitUnitStructs::new(&mut serializer))
+///     .unwrap();
+/// assert_eq!(buf, br#"{"value":1}"#);
+/// ```
+pub struct OmitUnitStructs<'a> {
+    serializer: MiddlewareSerializer<'a, OmitStructs>,
+}
+
+impl<'a> OmitUnitStructs<'a> {
+    /// Wraps `serializer`, omitting the fields whose value is a unit struct.
+    #[must_use]
+    pub fn new(serializer: &'a mut dyn Serializer) -> Self {
+        OmitUnitStructs {
+            serializer: MiddlewareSerializer::new(serializer, OmitStructs),
+        }
+    }
+}
+
+impl Debug for OmitUnitStructs<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("OmitUnitStructs").finish_non_exhaustive()
+    }
+}
+
+impl Serializer for OmitUnitStructs<'_> {
+    middleware::forward_to_middleware_serializer!(serializer);
+}
+
+struct OmitStructs;
+
+impl Middleware for OmitStructs {
+    fn element(&self, element: Element<'_>, compound: &mut Compound<'_>) -> SerializeResult<()> {
+        let element = match element {
+            Element::StructField(key, value) if is_unit_struct(value) => Element::SkipField(key),
+            element => element,
+        };
+        Ok(element.forward(compound)?)
+    }
+}
+
+struct Omit {
+    /// The key of a map serialized apart from its value, until the value is.
+    key: RefCell<Option<HeldKey>>,
+}
+
+enum HeldKey {
+    /// The key, captured to be forwarded with its value unless the value is
+    /// omitted.
+    Captured(Scalar),
+    /// The key, which could not be captured and has been forwarded already.
+    Forwarded,
+}
+
+impl Middleware for Omit {
+    fn begin<'a>(
+        &self,
+        begin: Begin,
+        serializer: &'a mut dyn Serializer,
+    ) -> SerializeResult<Compound<'a>> {
+        let begin = match begin {
+            Begin::Map(_) => Begin::Map(None),
+            begin => begin,
+        };
+        Ok(begin.forward(serializer)?)
+    }
+
+    fn element(&self, element: Element<'_>, compound: &mut Compound<'_>) -> SerializeResult<()> {
+        let element = match element {
+            Element::StructField(key, value) if is_unit(value) => Element::SkipField(key),
+            Element::Entry(_, value) if is_unit(value) => return Ok(()),
+            Element::Key(key) => {
+                let mut capture = Capture(None);
+                let held = match (key.dyn_serialize(&mut capture), capture.0) {
+                    (Ok(()), Some(key)) => HeldKey::Captured(key),
+                    _ => {
+                        Element::Key(key).forward(compound)?;
+                        HeldKey::Forwarded
+                    }
+                };
+                *self.key.borrow_mut() = Some(held);
+                return Ok(());
+            }
+            Element::Value(value) => {
+                let held = self.key.borrow_mut().take();
+                if let Some(HeldKey::Captured(key)) = held {
+                    if is_unit(value) {
+                        return Ok(());
+                    }
+                    Element::Key(&key).forward(compound)?;
+                }
+                Element::Value(value)
+            }
+            element => element,
+        };
+        Ok(element.forward(compound)?)
+    }
+}
+
+/// Returns whether `value` serializes as `None`, `()` or a unit struct.
+fn is_unit(value: &dyn Serialize) -> bool {
+    let mut probe = UnitProbe {
+        unit_structs_only: false,
+        unit: false,
+    };
+    value.dyn_serialize(&mut probe).is_ok() && probe.unit
+}
+
+/// Returns whether `value` serializes as a unit struct.
+fn is_unit_struct(value: &dyn Serialize) -> bool {
+    let mut probe = UnitProbe {
+        unit_structs_only: true,
+        unit: false,
+    };
+    value.dyn_serialize(&mut probe).is_ok() && probe.unit
+}
+
+/// A scalar key, captured to be serialized later.
+enum Scalar {
+    Bool(bool),
+    I8(i8),
+    I16(i16),
+    I32(i32),
+    I64(i64),
+    I128(i128),
+    U8(u8),
+    U16(u16),
+    U32(u32),
+    U64(u64),
+    U128(u128),
+    F32(f32),
+    F64(f64),
+    Char(char),
+    Str(String),
+    Bytes(Vec<u8>),
+    Unit,
+    UnitStruct(&'static str),
+    UnitVariant(&'static str, u32, &'static str),
+}
+
+impl serde::Serialize for Scalar {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        match *self {
+            Scalar::Bool(v) => serializer.serialize_bool(v),
+            Scalar::I8(v) => serializer.serialize_i8(v),
+            Scalar::I16(v) => serializer.serialize_i16(v),
+            Scalar::I32(v) => serializer.serialize_i32(v),
+            Scalar::I64(v) => serializer.serialize_i64(v),
+            Scalar::I128(v) => serializer.serialize_i128(v),
+            Scalar::U8(v) => serializer.serialize_u8(v),
+            Scalar::U16(v) => serializer.serialize_u16(v),
+            Scalar::U32(v) => serializer.serialize_u32(v),
+            Scalar::U64(v) => serializer.serialize_u64(v),
+            Scalar::U128(v) => serializer.serialize_u128(v),
+            Scalar::F32(v) => serializer.serialize_f32(v),
+            Scalar::F64(v) => serializer.serialize_f64(v),
+            Scalar::Char(v) => serializer.serialize_char(v),
+            Scalar::Str(ref v) => serializer.serialize_str(v),
+            Scalar::Bytes(ref v) => serializer.serialize_bytes(v),
+            Scalar::Unit => serializer.serialize_unit(),
+            Scalar::UnitStruct(name) => serializer.serialize_unit_struct(name),
+            Scalar::UnitVariant(name, variant_index, variant) => {
+                serializer.serialize_unit_variant(name, variant_index, variant)
+            }
+        }
+    }
+}
+
+/// Implements the methods of [`Serializer`] which serialize a value with
+/// content by failing.
+macro_rules! reject_compounds {
+    () => {
+        fn dyn_serialize_some(&mut self, value: &dyn Serialize) -> InplaceSerializeResult<()> {
+            let _ = value;
+            Err(InplaceSerializeError::Error)
+        }
+
+        fn dyn_serialize_newtype_variant(
+            &mut self,
+            name: &'static str,
+            variant_index: u32,
+            variant: &'static str,
+            value: &dyn Serialize,
+        ) -> InplaceSerializeResult<()> {
+            let _ = (name, variant_index, variant, value);
+            Err(InplaceSerializeError::Error)
+        }
+
+        fn dyn_serialize_seq(
+            &mut self,
+            len: Option<usize>,
+        ) -> InplaceSerializeResult<&mut dyn SerializeSeq> {
+            let _ = len;
+            Err(InplaceSerializeError::Error)
+        }
+
+        fn dyn_serialize_tuple(
+            &mut self,
+            len: usize,
+        ) -> InplaceSerializeResult<&mut dyn SerializeTuple> {
+            let _ = len;
+            Err(InplaceSerializeError::Error)
+        }
+
+        fn dyn_serialize_tuple_struct(
+            &mut self,
+            name: &'static str,
+            len: usize,
+        ) -> InplaceSerializeResult<&mut dyn SerializeTupleStruct> {
+            let _ = (name, len);
+            Err(InplaceSerializeError::Error)
+        }
+
+        fn dyn_serialize_tuple_variant(
+            &mut self,
+            name: &'static str,
+            variant_index: u32,
+            variant: &'static str,
+            len: usize,
+        ) -> InplaceSerializeResult<&mut dyn SerializeTupleVariant> {
+            let _ = (name, variant_index, variant, len);
+            Err(InplaceSerializeError::Error)
+        }
+
+        fn dyn_serialize_map(
+            &mut self,
+            len: Option<usize>,
+        ) -> InplaceSerializeResult<&mut dyn SerializeMap> {
+            let _ = len;
+            Err(InplaceSerializeError::Error)
+        }
+
+        fn dyn_serialize_struct(
+            &mut self,
+            name: &'static str,
+            len: usize,
+        ) -> InplaceSerializeResult<&mut dyn SerializeStruct> {
+            let _ = (name, len);
+            Err(InplaceSerializeError::Error)
+        }
+
+        fn dyn_serialize_struct_variant(
+            &mut self,
+            name: &'static str,
+            variant_index: u32,
+            variant: &'static str,
+            len: usize,
+        ) -> InplaceSerializeResult<&mut dyn SerializeStructVariant> {
+            let _ = (name, variant_index, variant, len);
+            Err(InplaceSerializeError::Error)
+        }
+    };
+}
+
+/// A serializer capturing a scalar, and failing on any other value.
+struct Capture(Option<Scalar>);
+
+impl Capture {
+    fn capture(&mut self, scalar: Scalar) -> InplaceSerializeResult<()> {
+        self.0 = Some(scalar);
+        Ok(())
+    }
+}
+
+impl Serializer for Capture {
+    fn dyn_serialize_bool(&mut self, v: bool) -> InplaceSerializeResult<()> {
+        self.capture(Scalar::Bool(v))
+    }
+
+    fn dyn_serialize_i8(&mut self, v: i8) -> InplaceSerializeResult<()> {
+        self.capture(Scalar::I8(v))
+    }
+
+    fn dyn_serialize_i16(&mut self, v: i16) -> InplaceSerializeResult<()> {
+        self.capture(Scalar::I16(v))
+    }
+
+    fn dyn_serialize_i32(&mut self, v: i32) -> InplaceSerializeResult<()> {
+        self.capture(Scalar::I32(v))
+    }
+
+    fn dyn_serialize_i64(&mut self, v: i64) -> InplaceSerializeResult<()> {
+        self.capture(Scalar::I64(v))
+    }
+
+    fn dyn_serialize_i128(&mut self, v: i128) -> InplaceSerializeResult<()> {
+        self.capture(Scalar::I128(v))
+    }
+
+    fn dyn_serialize_u8(&mut self, v: u8) -> InplaceSerializeResult<()> {
+        self.capture(Scalar::U8(v))
+    }
+
+    fn dyn_serialize_u16(&mut self, v: u16) -> InplaceSerializeResult<()> {
+        self.capture(Scalar::U16(v))
+    }
+
+    fn dyn_serialize_u32(&mut self, v: u32) -> InplaceSerializeResult<()> {
+        self.capture(Scalar::U32(v))
+    }
+
+    fn dyn_serialize_u64(&mut self, v: u64) -> InplaceSerializeResult<()> {
+        self.capture(Scalar::U64(v))
+    }
+
+    fn dyn_serialize_u128(&mut self, v: u128) -> InplaceSerializeResult<()> {
+        self.capture(Scalar::U128(v))
+    }
+
+    fn dyn_serialize_f32(&mut self, v: f32) -> InplaceSerializeResult<()> {
+        self.capture(Scalar::F32(v))
+    }
+
+    fn dyn_serialize_f64(&mut self, v: f64) -> InplaceSerializeResult<()> {
+        self.capture(Scalar::F64(v))
+    }
+
+    fn dyn_serialize_char(&mut self, v: char) -> InplaceSerializeResult<()> {
+        self.capture(Scalar::Char(v))
+    }
+
+    fn dyn_serialize_str(&mut self, v: &str) -> InplaceSerializeResult<()> {
+        self.capture(Scalar::Str(v.into()))
+    }
+
+    fn dyn_serialize_bytes(&mut self, v: &[u8]) -> InplaceSerializeResult<()> {
+        self.capture(Scalar::Bytes(v.into()))
+    }
+
+    fn dyn_serialize_none(&mut self) -> InplaceSerializeResult<()> {
+        Err(InplaceSerializeError::Error)
+    }
+
+    fn dyn_serialize_unit(&mut self) -> InplaceSerializeResult<()> {
+        self.capture(Scalar::Unit)
+    }
+
+    fn dyn_serialize_unit_struct(&mut self, name: &'static str) -> InplaceSerializeResult<()> {
+        self.capture(Scalar::UnitStruct(name))
+    }
+
+    fn dyn_serialize_unit_variant(
+        &mut self,
+        name: &'static str,
+        variant_index: u32,
+        variant: &'static str,
+    ) -> InplaceSerializeResult<()> {
+        self.capture(Scalar::UnitVariant(name, variant_index, variant))
+    }
+
+    fn dyn_serialize_newtype_struct(
+        &mut self,
+        name: &'static str,
+        value: &dyn Serialize,
+    ) -> InplaceSerializeResult<()> {
+        let _ = (name, value);
+        Err(InplaceSerializeError::Error)
+    }
+
+    reject_compounds!();
+}
+
+/// A serializer telling whether a value is `None`, `()` or a unit struct, or
+/// only a unit struct, and failing as soon as it is not.
+struct UnitProbe {
+    unit_structs_only: bool,
+    unit: bool,
+}
+
+impl UnitProbe {
+    fn unit(&mut self) -> InplaceSerializeResult<()> {
+        if self.unit_structs_only {
+            return Err(InplaceSerializeError::Error);
+        }
+        self.unit = true;
+        Ok(())
+    }
+}
+
+impl Serializer for UnitProbe {
+    fn dyn_serialize_bool(&mut self, v: bool) -> InplaceSerializeResult<()> {
+        let _ = v;
+        Err(InplaceSerializeError::Error)
+    }
+
+    fn dyn_serialize_i64(&mut self, v: i64) -> InplaceSerializeResult<()> {
+        let _ = v;
+        Err(InplaceSerializeError::Error)
+    }
+
+    fn dyn_serialize_i128(&mut self, v: i128) -> InplaceSerializeResult<()> {
+        let _ = v;
+        Err(InplaceSerializeError::Error)
+    }
+
+    fn dyn_serialize_u64(&mut self, v: u64) -> InplaceSerializeResult<()> {
+        let _ = v;
+        Err(InplaceSerializeError::Error)
+    }
+
+    fn dyn_serialize_u128(&mut self, v: u128) -> InplaceSerializeResult<()> {
+        let _ = v;
+        Err(InplaceSerializeError::Error)
+    }
+
+    fn dyn_serialize_f64(&mut self, v: f64) -> InplaceSerializeResult<()> {
+        let _ = v;
+        Err(InplaceSerializeError::Error)
+    }
+
+    fn dyn_serialize_str(&mut self, v: &str) -> InplaceSerializeResult<()> {
+        let _ = v;
+        Err(InplaceSerializeError::Error)
+    }
+
+    fn dyn_serialize_bytes(&mut self, v: &[u8]) -> InplaceSerializeResult<()> {
+        let _ = v;
+        Err(InplaceSerializeError::Error)
+    }
+
+    fn dyn_serialize_none(&mut self) -> InplaceSerializeResult<()> {
+        self.unit()
+    }
+
+    fn dyn_serialize_unit(&mut self) -> InplaceSerializeResult<()> {
+        self.unit()
+    }
+
+    fn dyn_serialize_unit_struct(&mut self, name: &'static str) -> InplaceSerializeResult<()> {
+        let _ = name;
+        self.unit = true;
+        Ok(())
+    }
+
+    fn dyn_serialize_unit_variant(
+        &mut self,
+        name: &'static str,
+        variant_index: u32,
+        variant: &'static str,
+    ) -> InplaceSerializeResult<()> {
+        let _ = (name, variant_index, variant);
+        Err(InplaceSerializeError::Error)
+    }
+
+    fn dyn_serialize_newtype_struct(
+        &mut self,
+        name: &'static str,
+        value: &dyn Serialize,
+    ) -> InplaceSerializeResult<()> {
+        let _ = name;
+        value
+            .dyn_serialize(self)
+            .map_err(|_| InplaceSerializeError::Error)
+    }
+
+    reject_compounds!();
+}
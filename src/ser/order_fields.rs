@@ -0,0 +1,166 @@
+//! A [`Serializer`] wrapper writing struct fields in a given order, see
+//! [`OrderFields`].
+
+use core::cell::RefCell;
+use core::fmt::{self, Debug, Formatter};
+
+#[cfg(not(feature = "std"))]
+use alloc::vec::Vec;
+
+use crate::ser::captured::{self, Captured};
+use crate::ser::middleware::{self, Begin, Compound, Element};
+use crate::ser::middleware::{Middleware, MiddlewareSerializer};
+use crate::ser::{Serialize, SerializeResult, Serializer};
+
+/// A [`Serializer`] wrapper which writes the fields of every struct in a
+/// given order, whatever the order they are declared in.
+///
+/// Some outputs must list their fields in an order fixed by a contract, e.g.
+/// an API specification, rather than by the declaration of the structs. The
+/// fields of every struct and struct variant named in `order` are written in
+/// that order, followed by the other fields in the order they are serialized.
+/// Names missing from a struct are ignored, so that one order serves several
+/// structs, and skipped fields are reported as skipped at their place.
+///
+/// The fields of a struct are only all known once it ends, so each of them is
+/// buffered meanwhile. The buffer keeps the whole data model, bytes and
+/// 128-bit integers included, and the fields are buffered in the form the
+/// wrapped serializer expects, e.g. an `IpAddr` as bytes for a binary format.
+/// The structs in sequences and maps are ordered as well.
+///
+/// # Examples
+///
+/// ```
+/// # use dyn_serde::{Serialize, Serializer};
+/// # use dyn_serde::ser::OrderFields;
+/// #
+/// #[derive(serde::Serialize)]
+/// struct User {
+///     name: &'static str,
+///     email: &'static str,
+///     id: u32,
+/// }
+///
+/// let mut buf = Vec::new();
+/// let mut serializer = serde_json::Serializer::new(&mut buf);
+/// let mut serializer = <dyn Serializer>::new(&mut serializer);
+/// let mut serializer = OrderFields::new(&mut serializer, &["id", "name"]);
+///
+/// let user = User { name: "ferris", email: "ferris@example.com", id: 1 };
+/// (&user as &dyn Serialize).dyn_serialize(&mut serializer).unwrap();
+/// assert_eq!(buf, br#"{"id":1,"name":"ferris","email":"ferris@example.com"}"#);
+/// ```
+pub struct OrderFields<'a> {
+    serializer: MiddlewareSerializer<'a, Order<'a>>,
+}
+
+impl<'a> OrderFields<'a> {
+    /// Wraps `serializer`, writing the fields named in `order` first, in that
+    /// order.
+    #[must_use]
+    pub fn new(serializer: &'a mut dyn Serializer, order: &'a [&'a str]) -> Self {
+        let order = Order {
+            order,
+            frames: RefCell::new(Vec::new()),
+        };
+        OrderFields {
+            serializer: MiddlewareSerializer::new(serializer, order),
+        }
+    }
+}
+
+impl Debug for OrderFields<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("OrderFields")
+            .field("order", &self.serializer.middleware().order)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Serializer for OrderFields<'_> {
+    middleware::forward_to_middleware_serializer!(serializer);
+}
+
+/// A compound being serialized.
+enum Frame {
+    /// A compound forwarded as is.
+    Forwarded,
+    /// The fields of a struct being buffered, `None` if skipped, and whether
+    /// the wrapped serializer is human-readable.
+    Fields(Vec<(&'static str, Option<Captured>)>, bool),
+}
+
+struct Order<'a> {
+    order: &'a [&'a str],
+    frames: RefCell<Vec<Frame>>,
+}
+
+impl Order<'_> {
+    /// Buffers a field of the struct being buffered.
+    fn buffer(&self, key: &'static str, value: Option<&dyn Serialize>) -> SerializeResult<()> {
+        let human_readable = match self.frames.borrow().last() {
+            Some(Frame::Fields(_, human_readable)) => *human_readable,
+            _ => unreachable!("the fields are being buffered"),
+        };
+        let value = match value {
+            Some(value) => Some(captured::capture(value, human_readable)?),
+            None => None,
+        };
+        match self.frames.borrow_mut().last_mut() {
+            Some(Frame::Fields(fields, _)) => fields.push((key, value)),
+            _ => unreachable!("the fields are being buffered"),
+        }
+        Ok(())
+    }
+}
+
+impl Middleware for Order<'_> {
+    fn begin<'a>(
+        &self,
+        begin: Begin,
+        serializer: &'a mut dyn Serializer,
+    ) -> SerializeResult<Compound<'a>> {
+        let frame = match begin {
+            Begin::Struct(_, len) | Begin::StructVariant(_, _, _, len) => {
+                Frame::Fields(Vec::with_capacity(len), serializer.dyn_is_human_readable())
+            }
+            _ => Frame::Forwarded,
+        };
+        let compound = begin.forward(serializer)?;
+        self.frames.borrow_mut().push(frame);
+        Ok(compound)
+    }
+
+    fn element(&self, element: Element<'_>, compound: &mut Compound<'_>) -> SerializeResult<()> {
+        let forwarded = matches!(self.frames.borrow().last(), Some(Frame::Forwarded));
+        if forwarded {
+            return Ok(element.forward(compound)?);
+        }
+        match element {
+            Element::StructField(key, value) => self.buffer(key, Some(value)),
+            Element::SkipField(key) => self.buffer(key, None),
+            element => Ok(element.forward(compound)?),
+        }
+    }
+
+    fn end(&self, mut compound: Compound<'_>) -> SerializeResult<()> {
+        let frame = self.frames.borrow_mut().pop();
+        if let Some(Frame::Fields(mut fields, _)) = frame {
+            let order = self.order;
+            // the sort is stable, which keeps the other fields in their order.
+            fields.sort_by_key(|&(key, _)| {
+                order
+                    .iter()
+                    .position(|name| *name == key)
+                    .unwrap_or(order.len())
+            });
+            for (key, value) in &fields {
+                match value {
+                    Some(value) => Element::StructField(key, value).forward(&mut compound)?,
+                    None => Element::SkipField(key).forward(&mut compound)?,
+                }
+            }
+        }
+        Ok(compound.end()?)
+    }
+}
@@ -0,0 +1,76 @@
+//! A [`Serializer`] wrapper writing unit variants as their index, see
+//! [`OrdinalVariants`].
+
+use core::fmt::{self, Debug, Formatter};
+
+use crate::ser::middleware::{self, Call, Middleware, MiddlewareSerializer};
+use crate::ser::{SerializeResult, Serializer};
+
+/// A [`Serializer`] wrapper which writes every unit variant as its index among
+/// the variants of the enum, e.g. `2` for the third one, rather than its name.
+///
+/// Compact protocols commonly encode enum variants as their index, which is
+/// shorter than the name and survives renaming the variants, but not
+/// reordering them. `OrdinalVariants` forwards the `variant_index` of every
+/// unit variant as a `u32`, at every depth of the value. The other variants
+/// carry content and are forwarded unchanged. [`de::OrdinalVariants`] reads
+/// the indices back.
+///
+/// [`de::OrdinalVariants`]: crate::de::OrdinalVariants
+///
+/// # Examples
+///
+/// ```
+/// # use dyn_serde::{Serialize, Serializer};
+/// # use dyn_serde::ser::OrdinalVariants;
+/// #
+/// #[derive(serde::Serialize)]
+/// enum Level {
+///     Low,
+///     Medium,
+///     High,
+/// }
+///
+/// let mut buf = Vec::new();
+/// let mut serializer = serde_json::Serializer::new(&mut buf);
+/// let mut serializer = <dyn Serializer>::new(&mut serializer);
+/// let mut serializer = OrdinalVariants::new(&mut serializer);
+///
+/// (&[Level::High, Level::Low] as &dyn Serialize).dyn_serialize(&mut serializer).unwrap();
+/// assert_eq!(buf, b"[2,0]");
+/// ```
+pub struct OrdinalVariants<'a> {
+    serializer: MiddlewareSerializer<'a, Ordinals>,
+}
+
+impl<'a> OrdinalVariants<'a> {
+    /// Wraps `serializer`, writing unit variants as their index.
+    #[must_use]
+    pub fn new(serializer: &'a mut dyn Serializer) -> Self {
+        OrdinalVariants {
+            serializer: MiddlewareSerializer::new(serializer, Ordinals),
+        }
+    }
+}
+
+impl Debug for OrdinalVariants<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("OrdinalVariants").finish_non_exhaustive()
+    }
+}
+
+impl Serializer for OrdinalVariants<'_> {
+    middleware::forward_to_middleware_serializer!(serializer);
+}
+
+struct Ordinals;
+
+impl Middleware for Ordinals {
+    fn serialize(&self, call: Call<'_>, serializer: &mut dyn Serializer) -> SerializeResult<()> {
+        let call = match call {
+            Call::UnitVariant(_, variant_index, _) => Call::U32(variant_index),
+            call => call,
+        };
+        Ok(call.forward(serializer)?)
+    }
+}
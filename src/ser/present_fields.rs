@@ -0,0 +1,308 @@
+//! Presence of struct fields, see [`present_fields`].
+
+#[cfg(not(feature = "std"))]
+use alloc::vec::Vec;
+
+use serde::ser::Error as _;
+
+use crate::ser::{InplaceSerializeError, InplaceSerializeResult, InplaceSerializerState};
+use crate::ser::{Serialize, SerializeMap, SerializeSeq, SerializeStruct, SerializeStructVariant};
+use crate::ser::{SerializeError, SerializeResult};
+use crate::ser::{SerializeTuple, SerializeTupleStruct, SerializeTupleVariant, Serializer};
+
+/// Returns the names of the fields of the struct `value` which are present,
+/// i.e. serialized rather than skipped, in the order they are serialized.
+///
+/// This is meant for change detection: comparing the presence of two values
+/// tells which optional fields have appeared or disappeared, e.g. those
+/// skipped with `#[serde(skip_serializing_if = "Option::is_none")]`. No output
+/// is produced and the values of the fields are not serialized at all, so the
+/// cost does not depend on their size. Fields are reported under their
+/// serialized names, after renaming.
+///
+/// `value` may be a struct or a struct variant, possibly behind `Some` or a
+/// newtype struct. Fields flattened with `#[serde(flatten)]` make the struct
+/// serialize as a map, which is rejected.
+///
+/// # Errors
+///
+/// Returns an error if `value` fails to serialize itself or is not a struct.
+///
+/// # Examples
+///
+/// ```
+/// # use dyn_serde::ser::present_fields;
+/// #
+/// #[derive(serde::Serialize)]
+/// struct User {
+///     name: String,
+///     #[serde(skip_serializing_if = "Option::is_none")]
+///     email: Option<String>,
+/// }
+///
+/// let user = User { name: "alice".into(), email: None };
+/// assert_eq!(present_fields(&user).unwrap(), ["name"]);
+/// ```
+pub fn present_fields(value: &dyn Serialize) -> SerializeResult<Vec<&'static str>> {
+    let mut serializer = PresenceSerializer {
+        fields: Vec::new(),
+        state: InplaceSerializerState::Serializer,
+        error: None,
+    };
+
+    match value.dyn_serialize(&mut serializer) {
+        Ok(()) => Ok(serializer.fields),
+        Err(error) => Err(serializer.error.unwrap_or(error)),
+    }
+}
+
+/// The serializer behind [`present_fields`], which records the fields of the
+/// top-level struct.
+struct PresenceSerializer {
+    fields: Vec<&'static str>,
+    state: InplaceSerializerState,
+    error: Option<SerializeError>,
+}
+
+impl PresenceSerializer {
+    #[cold]
+    fn not_a_struct(&mut self) -> InplaceSerializeError {
+        if self.state != InplaceSerializerState::Serializer {
+            return InplaceSerializeError::NotSerializer;
+        }
+        self.error
+            .get_or_insert_with(|| SerializeError::custom("expected a struct"));
+        self.state = InplaceSerializerState::Error;
+        InplaceSerializeError::Error
+    }
+
+    fn begin(&mut self, state: InplaceSerializerState) -> InplaceSerializeResult<&mut Self> {
+        if self.state != InplaceSerializerState::Serializer {
+            return Err(InplaceSerializeError::NotSerializer);
+        }
+        self.state = state;
+        Ok(self)
+    }
+
+    fn field(
+        &mut self,
+        key: &'static str,
+        state: InplaceSerializerState,
+        error: InplaceSerializeError,
+    ) -> InplaceSerializeResult<()> {
+        if self.state != state {
+            return Err(error);
+        }
+        self.fields.push(key);
+        Ok(())
+    }
+
+    fn end(
+        &mut self,
+        state: InplaceSerializerState,
+        error: InplaceSerializeError,
+    ) -> InplaceSerializeResult<()> {
+        if self.state != state {
+            return Err(error);
+        }
+        self.state = InplaceSerializerState::Ok;
+        Ok(())
+    }
+}
+
+impl Serializer for PresenceSerializer {
+    fn dyn_serialize_bool(&mut self, v: bool) -> InplaceSerializeResult<()> {
+        let _ = v;
+        Err(self.not_a_struct())
+    }
+
+    fn dyn_serialize_i64(&mut self, v: i64) -> InplaceSerializeResult<()> {
+        let _ = v;
+        Err(self.not_a_struct())
+    }
+
+    fn dyn_serialize_i128(&mut self, v: i128) -> InplaceSerializeResult<()> {
+        let _ = v;
+        Err(self.not_a_struct())
+    }
+
+    fn dyn_serialize_u64(&mut self, v: u64) -> InplaceSerializeResult<()> {
+        let _ = v;
+        Err(self.not_a_struct())
+    }
+
+    fn dyn_serialize_u128(&mut self, v: u128) -> InplaceSerializeResult<()> {
+        let _ = v;
+        Err(self.not_a_struct())
+    }
+
+    fn dyn_serialize_f64(&mut self, v: f64) -> InplaceSerializeResult<()> {
+        let _ = v;
+        Err(self.not_a_struct())
+    }
+
+    fn dyn_serialize_str(&mut self, v: &str) -> InplaceSerializeResult<()> {
+        let _ = v;
+        Err(self.not_a_struct())
+    }
+
+    fn dyn_serialize_bytes(&mut self, v: &[u8]) -> InplaceSerializeResult<()> {
+        let _ = v;
+        Err(self.not_a_struct())
+    }
+
+    fn dyn_serialize_none(&mut self) -> InplaceSerializeResult<()> {
+        Err(self.not_a_struct())
+    }
+
+    fn dyn_serialize_some(&mut self, value: &dyn Serialize) -> InplaceSerializeResult<()> {
+        value.dyn_serialize(self).map_err(|error| {
+            self.error.get_or_insert(error);
+            InplaceSerializeError::Error
+        })
+    }
+
+    fn dyn_serialize_unit(&mut self) -> InplaceSerializeResult<()> {
+        Err(self.not_a_struct())
+    }
+
+    fn dyn_serialize_unit_variant(
+        &mut self,
+        name: &'static str,
+        variant_index: u32,
+        variant: &'static str,
+    ) -> InplaceSerializeResult<()> {
+        let _ = (name, variant_index, variant);
+        Err(self.not_a_struct())
+    }
+
+    fn dyn_serialize_newtype_struct(
+        &mut self,
+        name: &'static str,
+        value: &dyn Serialize,
+    ) -> InplaceSerializeResult<()> {
+        let _ = name;
+        self.dyn_serialize_some(value)
+    }
+
+    fn dyn_serialize_newtype_variant(
+        &mut self,
+        name: &'static str,
+        variant_index: u32,
+        variant: &'static str,
+        value: &dyn Serialize,
+    ) -> InplaceSerializeResult<()> {
+        let _ = (name, variant_index, variant, value);
+        Err(self.not_a_struct())
+    }
+
+    fn dyn_serialize_seq(
+        &mut self,
+        len: Option<usize>,
+    ) -> InplaceSerializeResult<&mut dyn SerializeSeq> {
+        let _ = len;
+        Err(self.not_a_struct())
+    }
+
+    fn dyn_serialize_tuple(
+        &mut self,
+        len: usize,
+    ) -> InplaceSerializeResult<&mut dyn SerializeTuple> {
+        let _ = len;
+        Err(self.not_a_struct())
+    }
+
+    fn dyn_serialize_tuple_struct(
+        &mut self,
+        name: &'static str,
+        len: usize,
+    ) -> InplaceSerializeResult<&mut dyn SerializeTupleStruct> {
+        let _ = (name, len);
+        Err(self.not_a_struct())
+    }
+
+    fn dyn_serialize_tuple_variant(
+        &mut self,
+        name: &'static str,
+        variant_index: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> InplaceSerializeResult<&mut dyn SerializeTupleVariant> {
+        let _ = (name, variant_index, variant, len);
+        Err(self.not_a_struct())
+    }
+
+    fn dyn_serialize_map(
+        &mut self,
+        len: Option<usize>,
+    ) -> InplaceSerializeResult<&mut dyn SerializeMap> {
+        let _ = len;
+        Err(self.not_a_struct())
+    }
+
+    fn dyn_serialize_struct(
+        &mut self,
+        name: &'static str,
+        len: usize,
+    ) -> InplaceSerializeResult<&mut dyn SerializeStruct> {
+        let _ = name;
+        self.fields.reserve(len);
+        Ok(self.begin(InplaceSerializerState::SerializeStruct)?)
+    }
+
+    fn dyn_serialize_struct_variant(
+        &mut self,
+        name: &'static str,
+        variant_index: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> InplaceSerializeResult<&mut dyn SerializeStructVariant> {
+        let _ = (name, variant_index, variant);
+        self.fields.reserve(len);
+        Ok(self.begin(InplaceSerializerState::SerializeStructVariant)?)
+    }
+}
+
+impl SerializeStruct for PresenceSerializer {
+    fn dyn_serialize_field(
+        &mut self,
+        key: &'static str,
+        value: &dyn Serialize,
+    ) -> InplaceSerializeResult<()> {
+        let _ = value;
+        self.field(
+            key,
+            InplaceSerializerState::SerializeStruct,
+            InplaceSerializeError::NotSerializeStruct,
+        )
+    }
+
+    fn dyn_end(&mut self) -> InplaceSerializeResult<()> {
+        self.end(
+            InplaceSerializerState::SerializeStruct,
+            InplaceSerializeError::NotSerializeStruct,
+        )
+    }
+}
+
+impl SerializeStructVariant for PresenceSerializer {
+    fn dyn_serialize_field(
+        &mut self,
+        key: &'static str,
+        value: &dyn Serialize,
+    ) -> InplaceSerializeResult<()> {
+        let _ = value;
+        self.field(
+            key,
+            InplaceSerializerState::SerializeStructVariant,
+            InplaceSerializeError::NotSerializeStructVariant,
+        )
+    }
+
+    fn dyn_end(&mut self) -> InplaceSerializeResult<()> {
+        self.end(
+            InplaceSerializerState::SerializeStructVariant,
+            InplaceSerializeError::NotSerializeStructVariant,
+        )
+    }
+}
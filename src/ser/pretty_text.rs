@@ -0,0 +1,495 @@
+//! Indented JSON text, see [`PrettyText`].
+
+use core::fmt::{self, Debug, Formatter, Write};
+use core::mem;
+
+#[cfg(not(feature = "std"))]
+use alloc::vec::Vec;
+
+use serde::ser::Error as _;
+
+use crate::ser::Serializer;
+use crate::ser::counted::Counted;
+use crate::ser::{InplaceSerializeError, InplaceSerializeResult, InplaceSerializerState};
+use crate::ser::{Serialize, SerializeMap, SerializeSeq, SerializeStruct, SerializeStructVariant};
+use crate::ser::{SerializeError, SerializeTuple, SerializeTupleStruct, SerializeTupleVariant};
+
+/// A [`Serializer`] writing pretty-printed JSON to any [`fmt::Write`], with a
+/// configurable indentation.
+///
+/// Every element of an array and every member of an object is written on its
+/// own line, indented by one more level than the enclosing value, while empty
+/// arrays and objects are written as `[]` and `{}`. Variants are written like
+/// `serde_json` does, as an object with a single member. Map keys are written
+/// as they serialize, so that a string key is quoted while the text stays
+/// readable for keys that JSON could not represent. NaN and infinite floats
+/// are written as `null`.
+///
+/// As the output goes through [`fmt::Write`], the serializer works without
+/// `std`, and may write into a `String` or a [`Formatter`] directly, e.g. in a
+/// `Debug` implementation.
+///
+/// The serialization returns an error telling only that it has done
+/// unsuccessfully, while [`PrettyText::error`] tells why it has.
+///
+/// # Examples
+///
+/// ```
+/// # use std::collections::BTreeMap;
+/// # use dyn_serde::Serialize;
+/// # use dyn_serde::ser::PrettyText;
+/// #
+/// let value = BTreeMap::from([("a", vec![1, 2]), ("b", vec![])]);
+/// let mut output = String::new();
+/// let mut serializer = PrettyText::new(&mut output, "  ");
+/// (&value as &dyn Serialize).dyn_serialize(&mut serializer).unwrap();
+/// assert_eq!(output, "{\n  \"a\": [\n    1,\n    2\n  ],\n  \"b\": []\n}");
+/// ```
+pub struct PrettyText<'a> {
+    writer: Counted<&'a mut dyn Write>,
+    indent: &'a str,
+    depth: usize,
+    frames: Vec<Frame>,
+    error: Option<SerializeError>,
+}
+
+struct Frame {
+    object: bool,
+    empty: bool,
+    state: InplaceSerializerState,
+}
+
+impl Frame {
+    fn is_variant(&self) -> bool {
+        matches!(
+            self.state,
+            InplaceSerializerState::SerializeTupleVariant
+                | InplaceSerializerState::SerializeStructVariant
+        )
+    }
+}
+
+impl<'a> PrettyText<'a> {
+    /// Creates a serializer writing to `writer`, indenting each level by
+    /// `indent`, e.g. two spaces or a tab.
+    #[must_use]
+    pub fn new(writer: &'a mut dyn Write, indent: &'a str) -> Self {
+        PrettyText {
+            writer: Counted::new(writer),
+            indent,
+            depth: 0,
+            frames: Vec::new(),
+            error: None,
+        }
+    }
+
+    /// Returns the first error raised while serializing, if any.
+    #[must_use]
+    pub fn error(&self) -> Option<&SerializeError> {
+        self.error.as_ref()
+    }
+
+    #[cold]
+    fn fail(&mut self, error: SerializeError) -> InplaceSerializeError {
+        self.error.get_or_insert(error);
+        InplaceSerializeError::Error
+    }
+
+    fn write(&mut self, text: &str) -> InplaceSerializeResult<()> {
+        self.writer
+            .write_str(text)
+            .map_err(|error| self.fail(SerializeError::custom(error)))
+    }
+
+    fn write_display(&mut self, v: impl fmt::Display) -> InplaceSerializeResult<()> {
+        write!(self.writer, "{v}").map_err(|error| self.fail(SerializeError::custom(error)))
+    }
+
+    fn write_str(&mut self, v: &str) -> InplaceSerializeResult<()> {
+        write_str(&mut self.writer, v).map_err(|error| self.fail(SerializeError::custom(error)))
+    }
+
+    /// Starts a new line at the current depth.
+    fn newline(&mut self) -> InplaceSerializeResult<()> {
+        self.write("\n")?;
+        let indent = self.indent;
+        for _ in 0..self.depth {
+            self.write(indent)?;
+        }
+        Ok(())
+    }
+
+    fn serialize_value(&mut self, value: &dyn Serialize) -> InplaceSerializeResult<()> {
+        value.dyn_serialize(self).map_err(|error| self.fail(error))
+    }
+
+    fn begin_variant(&mut self, variant: &'static str) -> InplaceSerializeResult<()> {
+        self.write("{")?;
+        self.depth += 1;
+        self.newline()?;
+        self.write_str(variant)?;
+        self.write(": ")
+    }
+
+    fn end_variant(&mut self) -> InplaceSerializeResult<()> {
+        self.depth -= 1;
+        self.newline()?;
+        self.write("}")
+    }
+
+    fn begin(
+        &mut self,
+        object: bool,
+        state: InplaceSerializerState,
+    ) -> InplaceSerializeResult<&mut Self> {
+        self.write(if object { "{" } else { "[" })?;
+        self.depth += 1;
+        self.frames.push(Frame {
+            object,
+            empty: true,
+            state,
+        });
+        Ok(self)
+    }
+
+    /// Writes the separator and the line break before an element or a member
+    /// of the innermost compound value.
+    fn separate(
+        &mut self,
+        object: bool,
+        error: InplaceSerializeError,
+    ) -> InplaceSerializeResult<()> {
+        let empty = match self.frames.last_mut() {
+            Some(frame) if frame.object == object => mem::replace(&mut frame.empty, false),
+            _ => return Err(error),
+        };
+
+        if !empty {
+            self.write(",")?;
+        }
+        self.newline()
+    }
+
+    fn element(
+        &mut self,
+        value: &dyn Serialize,
+        error: InplaceSerializeError,
+    ) -> InplaceSerializeResult<()> {
+        self.separate(false, error)?;
+        self.serialize_value(value)
+    }
+
+    fn field(
+        &mut self,
+        key: &'static str,
+        value: &dyn Serialize,
+        error: InplaceSerializeError,
+    ) -> InplaceSerializeResult<()> {
+        self.separate(true, error)?;
+        self.write_str(key)?;
+        self.write(": ")?;
+        self.serialize_value(value)
+    }
+
+    fn end(&mut self, object: bool, error: InplaceSerializeError) -> InplaceSerializeResult<()> {
+        let frame = match self.frames.pop() {
+            Some(frame) if frame.object == object => frame,
+            frame => {
+                self.frames.extend(frame);
+                return Err(error);
+            }
+        };
+
+        self.depth -= 1;
+        if !frame.empty {
+            self.newline()?;
+        }
+        self.write(if object { "}" } else { "]" })?;
+        if frame.is_variant() {
+            self.end_variant()?;
+        }
+        Ok(())
+    }
+}
+
+impl Debug for PrettyText<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PrettyText")
+            .field("indent", &self.indent)
+            .field("depth", &self.depth)
+            .field("error", &self.error)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Serializer for PrettyText<'_> {
+    fn dyn_serialize_bool(&mut self, v: bool) -> InplaceSerializeResult<()> {
+        self.write(if v { "true" } else { "false" })
+    }
+
+    fn dyn_serialize_i64(&mut self, v: i64) -> InplaceSerializeResult<()> {
+        self.write_display(v)
+    }
+
+    fn dyn_serialize_i128(&mut self, v: i128) -> InplaceSerializeResult<()> {
+        self.write_display(v)
+    }
+
+    fn dyn_serialize_u64(&mut self, v: u64) -> InplaceSerializeResult<()> {
+        self.write_display(v)
+    }
+
+    fn dyn_serialize_u128(&mut self, v: u128) -> InplaceSerializeResult<()> {
+        self.write_display(v)
+    }
+
+    fn dyn_serialize_f64(&mut self, v: f64) -> InplaceSerializeResult<()> {
+        if v.is_finite() {
+            // `Debug` keeps the fractional part of integral floats, e.g. `1.0`.
+            self.write_display(format_args!("{v:?}"))
+        } else {
+            self.write("null")
+        }
+    }
+
+    fn dyn_serialize_str(&mut self, v: &str) -> InplaceSerializeResult<()> {
+        self.write_str(v)
+    }
+
+    fn dyn_serialize_bytes(&mut self, v: &[u8]) -> InplaceSerializeResult<()> {
+        self.begin(false, InplaceSerializerState::SerializeSeq)?;
+        for byte in v {
+            self.element(byte, InplaceSerializeError::NotSerializeSeq)?;
+        }
+        self.end(false, InplaceSerializeError::NotSerializeSeq)
+    }
+
+    fn dyn_serialize_none(&mut self) -> InplaceSerializeResult<()> {
+        self.dyn_serialize_unit()
+    }
+
+    fn dyn_serialize_some(&mut self, value: &dyn Serialize) -> InplaceSerializeResult<()> {
+        self.serialize_value(value)
+    }
+
+    fn dyn_serialize_unit(&mut self) -> InplaceSerializeResult<()> {
+        self.write("null")
+    }
+
+    fn dyn_serialize_unit_variant(
+        &mut self,
+        name: &'static str,
+        variant_index: u32,
+        variant: &'static str,
+    ) -> InplaceSerializeResult<()> {
+        let _ = (name, variant_index);
+        self.write_str(variant)
+    }
+
+    fn dyn_serialize_newtype_struct(
+        &mut self,
+        name: &'static str,
+        value: &dyn Serialize,
+    ) -> InplaceSerializeResult<()> {
+        let _ = name;
+        self.serialize_value(value)
+    }
+
+    fn dyn_serialize_newtype_variant(
+        &mut self,
+        name: &'static str,
+        variant_index: u32,
+        variant: &'static str,
+        value: &dyn Serialize,
+    ) -> InplaceSerializeResult<()> {
+        let _ = (name, variant_index);
+        self.begin_variant(variant)?;
+        self.serialize_value(value)?;
+        self.end_variant()
+    }
+
+    fn dyn_serialize_seq(
+        &mut self,
+        len: Option<usize>,
+    ) -> InplaceSerializeResult<&mut dyn SerializeSeq> {
+        let _ = len;
+        Ok(self.begin(false, InplaceSerializerState::SerializeSeq)?)
+    }
+
+    fn dyn_serialize_tuple(
+        &mut self,
+        len: usize,
+    ) -> InplaceSerializeResult<&mut dyn SerializeTuple> {
+        let _ = len;
+        Ok(self.begin(false, InplaceSerializerState::SerializeTuple)?)
+    }
+
+    fn dyn_serialize_tuple_struct(
+        &mut self,
+        name: &'static str,
+        len: usize,
+    ) -> InplaceSerializeResult<&mut dyn SerializeTupleStruct> {
+        let _ = (name, len);
+        Ok(self.begin(false, InplaceSerializerState::SerializeTupleStruct)?)
+    }
+
+    fn dyn_serialize_tuple_variant(
+        &mut self,
+        name: &'static str,
+        variant_index: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> InplaceSerializeResult<&mut dyn SerializeTupleVariant> {
+        let _ = (name, variant_index, len);
+        self.begin_variant(variant)?;
+        Ok(self.begin(false, InplaceSerializerState::SerializeTupleVariant)?)
+    }
+
+    fn dyn_serialize_map(
+        &mut self,
+        len: Option<usize>,
+    ) -> InplaceSerializeResult<&mut dyn SerializeMap> {
+        let _ = len;
+        Ok(self.begin(true, InplaceSerializerState::SerializeMap)?)
+    }
+
+    fn dyn_serialize_struct(
+        &mut self,
+        name: &'static str,
+        len: usize,
+    ) -> InplaceSerializeResult<&mut dyn SerializeStruct> {
+        let _ = (name, len);
+        Ok(self.begin(true, InplaceSerializerState::SerializeStruct)?)
+    }
+
+    fn dyn_serialize_struct_variant(
+        &mut self,
+        name: &'static str,
+        variant_index: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> InplaceSerializeResult<&mut dyn SerializeStructVariant> {
+        let _ = (name, variant_index, len);
+        self.begin_variant(variant)?;
+        Ok(self.begin(true, InplaceSerializerState::SerializeStructVariant)?)
+    }
+
+    fn dyn_bytes_written(&self) -> Option<u64> {
+        Some(self.writer.written)
+    }
+}
+
+impl SerializeSeq for PrettyText<'_> {
+    fn dyn_serialize_element(&mut self, value: &dyn Serialize) -> InplaceSerializeResult<()> {
+        self.element(value, InplaceSerializeError::NotSerializeSeq)
+    }
+
+    fn dyn_end(&mut self) -> InplaceSerializeResult<()> {
+        self.end(false, InplaceSerializeError::NotSerializeSeq)
+    }
+}
+
+impl SerializeTuple for PrettyText<'_> {
+    fn dyn_serialize_element(&mut self, value: &dyn Serialize) -> InplaceSerializeResult<()> {
+        self.element(value, InplaceSerializeError::NotSerializeTuple)
+    }
+
+    fn dyn_end(&mut self) -> InplaceSerializeResult<()> {
+        self.end(false, InplaceSerializeError::NotSerializeTuple)
+    }
+}
+
+impl SerializeTupleStruct for PrettyText<'_> {
+    fn dyn_serialize_field(&mut self, value: &dyn Serialize) -> InplaceSerializeResult<()> {
+        self.element(value, InplaceSerializeError::NotSerializeTupleStruct)
+    }
+
+    fn dyn_end(&mut self) -> InplaceSerializeResult<()> {
+        self.end(false, InplaceSerializeError::NotSerializeTupleStruct)
+    }
+}
+
+impl SerializeTupleVariant for PrettyText<'_> {
+    fn dyn_serialize_field(&mut self, value: &dyn Serialize) -> InplaceSerializeResult<()> {
+        self.element(value, InplaceSerializeError::NotSerializeTupleVariant)
+    }
+
+    fn dyn_end(&mut self) -> InplaceSerializeResult<()> {
+        self.end(false, InplaceSerializeError::NotSerializeTupleVariant)
+    }
+}
+
+impl SerializeMap for PrettyText<'_> {
+    fn dyn_serialize_key(&mut self, key: &dyn Serialize) -> InplaceSerializeResult<()> {
+        self.separate(true, InplaceSerializeError::NotSerializeMap)?;
+        self.serialize_value(key)?;
+        self.write(": ")
+    }
+
+    fn dyn_serialize_value(&mut self, value: &dyn Serialize) -> InplaceSerializeResult<()> {
+        match self.frames.last() {
+            Some(frame) if frame.object => self.serialize_value(value),
+            _ => Err(InplaceSerializeError::NotSerializeMap),
+        }
+    }
+
+    fn dyn_end(&mut self) -> InplaceSerializeResult<()> {
+        self.end(true, InplaceSerializeError::NotSerializeMap)
+    }
+}
+
+impl SerializeStruct for PrettyText<'_> {
+    fn dyn_serialize_field(
+        &mut self,
+        key: &'static str,
+        value: &dyn Serialize,
+    ) -> InplaceSerializeResult<()> {
+        self.field(key, value, InplaceSerializeError::NotSerializeStruct)
+    }
+
+    fn dyn_end(&mut self) -> InplaceSerializeResult<()> {
+        self.end(true, InplaceSerializeError::NotSerializeStruct)
+    }
+}
+
+impl SerializeStructVariant for PrettyText<'_> {
+    fn dyn_serialize_field(
+        &mut self,
+        key: &'static str,
+        value: &dyn Serialize,
+    ) -> InplaceSerializeResult<()> {
+        self.field(key, value, InplaceSerializeError::NotSerializeStructVariant)
+    }
+
+    fn dyn_end(&mut self) -> InplaceSerializeResult<()> {
+        self.end(true, InplaceSerializeError::NotSerializeStructVariant)
+    }
+}
+
+/// Writes `v` as a JSON string, escaping what JSON requires.
+fn write_str(writer: &mut dyn Write, v: &str) -> fmt::Result {
+    writer.write_char('"')?;
+    let mut start = 0;
+    for (index, c) in v.char_indices() {
+        let escape = match c {
+            '"' => "\\\"",
+            '\\' => "\\\\",
+            '\n' => "\\n",
+            '\r' => "\\r",
+            '\t' => "\\t",
+            '\u{8}' => "\\b",
+            '\u{c}' => "\\f",
+            '\0'..='\u{1f}' => "",
+            _ => continue,
+        };
+        writer.write_str(&v[start..index])?;
+        if escape.is_empty() {
+            write!(writer, "\\u{:04x}", u32::from(c))?;
+        } else {
+            writer.write_str(escape)?;
+        }
+        start = index + c.len_utf8();
+    }
+    writer.write_str(&v[start..])?;
+    writer.write_char('"')
+}
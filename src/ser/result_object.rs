@@ -0,0 +1,67 @@
+//! A [`Result`] serialized as an object, see [`ResultAsObject`].
+
+use core::fmt::{self, Debug, Formatter};
+
+use serde::ser::SerializeMap as _;
+
+use crate::ser::Serialize;
+
+/// A [`Result`] of dynamically serializable values which serializes as an
+/// object of a single entry, `{"Ok": v}` or `{"Err": e}`, whatever the format.
+///
+/// The serde implementation of `Result` goes through
+/// `serialize_newtype_variant`, which formats are free to encode as they like,
+/// e.g. as a variant index in binary formats or as a tag in YAML.
+/// `ResultAsObject` always goes through `serialize_map` instead, so the
+/// consumers of an RPC response see the same shape in every format. The output
+/// deserializes back into a `Result` from self-describing formats.
+///
+/// # Examples
+///
+/// ```
+/// # use dyn_serde::ser::ResultAsObject;
+/// #
+/// let result: Result<u32, String> = Err("not found".into());
+/// let json = serde_json::to_string(&ResultAsObject::from(&result)).unwrap();
+/// assert_eq!(json, r#"{"Err":"not found"}"#);
+/// ```
+#[derive(Clone, Copy)]
+pub struct ResultAsObject<'a>(pub Result<&'a dyn Serialize, &'a dyn Serialize>);
+
+impl<'a, T, E> From<&'a Result<T, E>> for ResultAsObject<'a>
+where
+    T: Serialize,
+    E: Serialize,
+{
+    fn from(result: &'a Result<T, E>) -> Self {
+        match result {
+            Ok(v) => ResultAsObject(Ok(v)),
+            Err(e) => ResultAsObject(Err(e)),
+        }
+    }
+}
+
+impl Debug for ResultAsObject<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let variant = match self.0 {
+            Ok(_) => "Ok",
+            Err(_) => "Err",
+        };
+        f.debug_tuple("ResultAsObject").field(&variant).finish()
+    }
+}
+
+impl serde::Serialize for ResultAsObject<'_> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        let (key, value) = match self.0 {
+            Ok(v) => ("Ok", v),
+            Err(e) => ("Err", e),
+        };
+        let mut map = serializer.serialize_map(Some(1))?;
+        map.serialize_entry(key, value)?;
+        map.end()
+    }
+}
@@ -0,0 +1,80 @@
+//! Floating-point numbers serialized in their shortest form, see
+//! [`ShortestFloats`].
+
+use core::fmt::{self, Debug, Formatter};
+
+use crate::ser::middleware::{self, Call, Middleware};
+use crate::ser::{Serialize, SerializeResult, Serializer, collect_str};
+
+/// A dynamically serializable value whose floating-point numbers serialize as
+/// the shortest strings which read back as the same numbers, e.g. `"0.1"` or
+/// `"1e300"`, so that the output does not depend on how the format writes
+/// them.
+///
+/// Formats write floats with algorithms of their own, which may differ in
+/// their digits, their exponents, or between versions, while reproducible
+/// output, e.g. to compare it with a snapshot, needs the same text every time.
+/// `ShortestFloats` formats every `f32` and `f64`, at every depth of the
+/// value, with the shortest round-trip algorithm of `core`, and writes the
+/// result through `serialize_str`, since serializers have no way to be given
+/// a number already formatted. Non-finite numbers are written as `"NaN"`,
+/// `"inf"` and `"-inf"`.
+///
+/// Only human-readable formats are affected: binary formats already write the
+/// exact bits of the numbers, which are left numeric.
+///
+/// # Examples
+///
+/// ```
+/// # use dyn_serde::ser::ShortestFloats;
+/// #
+/// let value = [0.1, 1e300, 0.1 + 0.2];
+/// let json = serde_json::to_string(&ShortestFloats::new(&value)).unwrap();
+/// assert_eq!(json, r#"["0.1","1e300","0.30000000000000004"]"#);
+/// ```
+#[derive(Clone, Copy)]
+pub struct ShortestFloats<'a> {
+    value: &'a dyn Serialize,
+}
+
+impl<'a> ShortestFloats<'a> {
+    /// Wraps `value`, serializing its floating-point numbers as their shortest
+    /// round-trip strings.
+    #[must_use]
+    pub fn new(value: &'a dyn Serialize) -> Self {
+        ShortestFloats { value }
+    }
+}
+
+impl Debug for ShortestFloats<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ShortestFloats").finish_non_exhaustive()
+    }
+}
+
+impl serde::Serialize for ShortestFloats<'_> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        let value = middleware::wrap(self.value, &Shortest);
+        serde::Serialize::serialize(&value as &dyn Serialize, serializer)
+    }
+}
+
+struct Shortest;
+
+impl Middleware for Shortest {
+    fn serialize(&self, call: Call<'_>, serializer: &mut dyn Serializer) -> SerializeResult<()> {
+        if !serializer.dyn_is_human_readable() {
+            return Ok(call.forward(serializer)?);
+        }
+        // the `Debug` format of floats is their shortest round-trip form, in
+        // scientific notation when their exponent is large.
+        match call {
+            Call::F32(v) => Ok(collect_str(serializer, &format_args!("{v:?}"))?),
+            Call::F64(v) => Ok(collect_str(serializer, &format_args!("{v:?}"))?),
+            call => Ok(call.forward(serializer)?),
+        }
+    }
+}
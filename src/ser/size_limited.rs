@@ -0,0 +1,156 @@
+//! A [`Serializer`] wrapper enforcing a maximum output size, see
+//! [`SizeLimited`].
+
+use core::cell::Cell;
+use core::fmt::{self, Debug, Formatter};
+
+use serde::ser::Error as _;
+
+use crate::ser::middleware::{self, Begin, Call, Compound, Element};
+use crate::ser::middleware::{Middleware, MiddlewareSerializer};
+use crate::ser::{SerializeError, SerializeResult, Serializer};
+
+/// A [`Serializer`] wrapper which aborts the serialization once the output of
+/// the wrapped serializer exceeds a limit, rather than producing an oversized
+/// payload.
+///
+/// The size of the output is the number of bytes actually written by the
+/// wrapped serializer, read from the `written` counter given to
+/// [`SizeLimited::new`], which the writer of the serializer is expected to
+/// update. A `serde::Serializer` does not report the bytes it writes, so its
+/// writer counts them instead, which works for any format writing to an
+/// `std::io::Write`. It is checked after every call, at every depth of the
+/// value, so the serialization stops at the first call crossing the limit.
+/// Bytes buffered by the serializer are only counted once they are written.
+///
+/// Past the limit, the call fails with the error `the output exceeds the limit
+/// of N bytes`, which is raised by the serializer of the value being
+/// serialized, and [`SizeLimited::exceeded`] returns `true`.
+///
+/// # Examples
+///
+/// ```
+/// # use std::cell::Cell;
+/// # use std::io::{self, Write};
+/// # use dyn_serde::{Serialize, Serializer};
+/// # use dyn_serde::ser::SizeLimited;
+/// #
+/// struct Counter<'a>(Vec<u8>, &'a Cell<usize>);
+///
+/// impl Write for Counter<'_> {
+///     fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+///         self.1.set(self.1.get() + buf.len());
+///         self.0.write(buf)
+///     }
+///
+///     fn flush(&mut self) -> io::Result<()> {
+///         Ok(())
+///     }
+/// }
+///
+/// let written = Cell::new(0);
+/// let mut serializer = serde_json::Serializer::new(Counter(Vec::new(), &written));
+/// let mut serializer = <dyn Serializer>::new(&mut serializer);
+/// let mut serializer = SizeLimited::new(&mut serializer, 16, &written);
+///
+/// let value = vec!["a long string"; 100];
+/// assert!((&value as &dyn Serialize).dyn_serialize(&mut serializer).is_err());
+/// assert!(serializer.exceeded());
+/// assert!(written.get() < 32);
+/// ```
+pub struct SizeLimited<'a> {
+    serializer: MiddlewareSerializer<'a, Limit<'a>>,
+}
+
+impl<'a> SizeLimited<'a> {
+    /// Wraps `serializer`, limiting its output to `limit` bytes as counted by
+    /// `written`.
+    #[must_use]
+    pub fn new(serializer: &'a mut dyn Serializer, limit: usize, written: &'a Cell<usize>) -> Self {
+        let limit = Limit {
+            limit,
+            written,
+            exceeded: Cell::new(false),
+        };
+        SizeLimited {
+            serializer: MiddlewareSerializer::new(serializer, limit),
+        }
+    }
+
+    /// Returns the number of bytes written so far.
+    #[must_use]
+    pub fn written(&self) -> usize {
+        self.serializer.middleware().written.get()
+    }
+
+    /// Returns `true` if the serialization has been aborted because the
+    /// output exceeds the limit.
+    #[must_use]
+    pub fn exceeded(&self) -> bool {
+        self.serializer.middleware().exceeded.get()
+    }
+}
+
+impl Debug for SizeLimited<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let limit = self.serializer.middleware();
+        f.debug_struct("SizeLimited")
+            .field("limit", &limit.limit)
+            .field("written", &limit.written.get())
+            .field("exceeded", &limit.exceeded.get())
+            .finish_non_exhaustive()
+    }
+}
+
+impl Serializer for SizeLimited<'_> {
+    middleware::forward_to_middleware_serializer!(serializer);
+}
+
+struct Limit<'a> {
+    limit: usize,
+    written: &'a Cell<usize>,
+    exceeded: Cell<bool>,
+}
+
+impl Limit<'_> {
+    fn check(&self) -> SerializeResult<()> {
+        if self.written.get() <= self.limit {
+            return Ok(());
+        }
+        self.exceeded.set(true);
+        Err(SerializeError::custom(format_args!(
+            "the output exceeds the limit of {} bytes",
+            self.limit
+        )))
+    }
+}
+
+impl Middleware for Limit<'_> {
+    fn serialize(&self, call: Call<'_>, serializer: &mut dyn Serializer) -> SerializeResult<()> {
+        self.check()?;
+        call.forward(serializer)?;
+        self.check()
+    }
+
+    fn begin<'a>(
+        &self,
+        begin: Begin,
+        serializer: &'a mut dyn Serializer,
+    ) -> SerializeResult<Compound<'a>> {
+        self.check()?;
+        let compound = begin.forward(serializer)?;
+        self.check()?;
+        Ok(compound)
+    }
+
+    fn element(&self, element: Element<'_>, compound: &mut Compound<'_>) -> SerializeResult<()> {
+        self.check()?;
+        element.forward(compound)?;
+        self.check()
+    }
+
+    fn end(&self, compound: Compound<'_>) -> SerializeResult<()> {
+        compound.end()?;
+        self.check()
+    }
+}
@@ -0,0 +1,167 @@
+//! A [`Serializer`] wrapper writing scalar map keys as strings, see
+//! [`StringifyKeys`].
+
+use core::cell::Cell;
+use core::fmt::{self, Debug, Display, Formatter};
+
+use crate::ser::middleware::{self, Begin, Call, Compound, Element};
+use crate::ser::middleware::{Middleware, MiddlewareSerializer};
+use crate::ser::{Serialize, SerializeResult, Serializer};
+
+/// A [`Serializer`] wrapper which writes the keys of maps that are booleans,
+/// integers, floats or chars as strings, e.g. `42` as `"42"`.
+///
+/// JSON and other text formats only have string keys, while maps keyed by
+/// integers are common in Rust. Some formats convert such keys themselves,
+/// others reject them. `StringifyKeys` forwards every such key as the string
+/// of its [`Display`] form, collected through `dyn_collect_str` so that
+/// nothing is allocated. The map reads back with a format parsing its string
+/// keys, or through [`ParseKeys`].
+///
+/// Strings and unit variants are already strings and are left unchanged, as
+/// are the other keys, e.g. tuples, which have no string form. Keys behind
+/// `Some` or a newtype struct are converted too, while the keys of nested maps
+/// are converted at every depth. This is the counterpart of
+/// [`MapKeysAsInts`].
+///
+/// [`MapKeysAsInts`]: crate::ser::MapKeysAsInts
+/// [`ParseKeys`]: crate::de::ParseKeys
+///
+/// # Examples
+///
+/// ```
+/// # use std::collections::BTreeMap;
+/// # use dyn_serde::{Serialize, Serializer};
+/// # use dyn_serde::ser::{InplaceSerializer, StringifyKeys};
+/// #
+/// struct StrKeys<'a>(&'a dyn Serialize);
+///
+/// impl serde::Serialize for StrKeys<'_> {
+///     fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+///         let mut serializer = <dyn Serializer>::new(serializer);
+///         let _ = self.0.dyn_serialize(&mut StringifyKeys::new(&mut serializer));
+///         match serializer {
+///             InplaceSerializer::Ok(ok, _) => Ok(ok),
+///             InplaceSerializer::Error(error, _) => Err(error),
+///             _ => unreachable!(),
+///         }
+///     }
+/// }
+///
+/// let value = BTreeMap::from([(1, "one")]);
+/// let mut cbor = Vec::new();
+/// ciborium::into_writer(&StrKeys(&value), &mut cbor).unwrap();
+/// // a map of 1 entry, whose key is the string "1".
+/// assert_eq!(cbor, b"\xa1\x611\x63one");
+/// ```
+pub struct StringifyKeys<'a> {
+    serializer: MiddlewareSerializer<'a, StrKeys>,
+}
+
+impl<'a> StringifyKeys<'a> {
+    /// Wraps `serializer`, writing scalar map keys as strings.
+    #[must_use]
+    pub fn new(serializer: &'a mut dyn Serializer) -> Self {
+        let keys = StrKeys {
+            key: Cell::new(false),
+        };
+        StringifyKeys {
+            serializer: MiddlewareSerializer::new(serializer, keys),
+        }
+    }
+}
+
+impl Debug for StringifyKeys<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("StringifyKeys").finish_non_exhaustive()
+    }
+}
+
+impl Serializer for StringifyKeys<'_> {
+    middleware::forward_to_middleware_serializer!(serializer);
+}
+
+struct StrKeys {
+    /// Whether the next call serializes a key of a map.
+    key: Cell<bool>,
+}
+
+impl StrKeys {
+    fn key(&self, key: Element<'_>, compound: &mut Compound<'_>) -> SerializeResult<()> {
+        self.key.set(true);
+        let result = key.forward(compound);
+        self.key.set(false);
+        Ok(result?)
+    }
+}
+
+impl Middleware for StrKeys {
+    fn serialize(&self, call: Call<'_>, serializer: &mut dyn Serializer) -> SerializeResult<()> {
+        if !self.key.take() {
+            return Ok(call.forward(serializer)?);
+        }
+
+        let display: &dyn Display = match call {
+            Call::Bool(ref v) => v,
+            Call::I8(ref v) => v,
+            Call::I16(ref v) => v,
+            Call::I32(ref v) => v,
+            Call::I64(ref v) => v,
+            Call::I128(ref v) => v,
+            Call::U8(ref v) => v,
+            Call::U16(ref v) => v,
+            Call::U32(ref v) => v,
+            Call::U64(ref v) => v,
+            Call::U128(ref v) => v,
+            Call::F32(ref v) => v,
+            Call::F64(ref v) => v,
+            Call::Char(ref v) => v,
+            Call::Some(_) | Call::NewtypeStruct(..) => {
+                // the key is the value inside.
+                self.key.set(true);
+                return Ok(call.forward(serializer)?);
+            }
+            call => return Ok(call.forward(serializer)?),
+        };
+        Ok(Call::CollectStr(display).forward(serializer)?)
+    }
+
+    fn begin<'a>(
+        &self,
+        begin: Begin,
+        serializer: &'a mut dyn Serializer,
+    ) -> SerializeResult<Compound<'a>> {
+        self.key.set(false);
+        Ok(begin.forward(serializer)?)
+    }
+
+    fn element(&self, element: Element<'_>, compound: &mut Compound<'_>) -> SerializeResult<()> {
+        match element {
+            Element::Key(key) => self.key(Element::Key(key), compound),
+            Element::Entry(key, value) => {
+                let key = MapKey { keys: self, key };
+                Ok(Element::Entry(&key, value).forward(compound)?)
+            }
+            element => Ok(element.forward(compound)?),
+        }
+    }
+}
+
+/// The key of an entry, which tells the middleware it is a key while it is
+/// serialized, so that the entry is forwarded as a whole.
+struct MapKey<'a> {
+    keys: &'a StrKeys,
+    key: &'a dyn Serialize,
+}
+
+impl serde::Serialize for MapKey<'_> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        self.keys.key.set(true);
+        let result = serde::Serialize::serialize(self.key, serializer);
+        self.keys.key.set(false);
+        result
+    }
+}
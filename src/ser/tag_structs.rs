@@ -0,0 +1,87 @@
+//! A [`Serializer`] wrapper tagging every struct with an extra field, see
+//! [`TagStructs`].
+
+use core::fmt::{self, Debug, Formatter};
+
+use crate::ser::middleware::{self, Begin, Compound, Element, Middleware, MiddlewareSerializer};
+use crate::ser::{Serialize, SerializeResult, Serializer};
+
+/// A [`Serializer`] wrapper which writes an extra field, e.g.
+/// `"type": "Foo"`, before the fields of every struct.
+///
+/// This is a dynamic alternative to `#[serde(tag = "...")]`, where the tag is
+/// chosen when serializing rather than on the type. The `len` passed to
+/// `dyn_serialize_struct` is incremented to account for the extra field. The
+/// same applies to the structs at every depth of the value, while struct
+/// variants, maps and the other values are left unchanged.
+///
+/// # Examples
+///
+/// ```
+/// # use dyn_serde::{Serialize, Serializer};
+/// # use dyn_serde::ser::TagStructs;
+/// #
+/// #[derive(serde::Serialize)]
+/// struct Circle {
+///     radius: f64,
+/// }
+///
+/// let mut buf = Vec::new();
+/// let mut serializer = serde_json::Serializer::new(&mut buf);
+/// let mut serializer = <dyn Serializer>::new(&mut serializer);
+/// let mut serializer = TagStructs::new(&mut serializer, "type", &"Circle");
+///
+/// (&Circle { radius: 1.0 } as &dyn Serialize).dyn_serialize(&mut serializer).unwrap();
+/// assert_eq!(buf, br#"{"type":"Circle","radius":1.0}"#);
+/// ```
+pub struct TagStructs<'a> {
+    serializer: MiddlewareSerializer<'a, Tag<'a>>,
+}
+
+impl<'a> TagStructs<'a> {
+    /// Wraps `serializer`, writing the field `key` with `value` first in
+    /// every struct.
+    #[must_use]
+    pub fn new(
+        serializer: &'a mut dyn Serializer,
+        key: &'static str,
+        value: &'a dyn Serialize,
+    ) -> Self {
+        TagStructs {
+            serializer: MiddlewareSerializer::new(serializer, Tag { key, value }),
+        }
+    }
+}
+
+impl Debug for TagStructs<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TagStructs")
+            .field("key", &self.serializer.middleware().key)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Serializer for TagStructs<'_> {
+    middleware::forward_to_middleware_serializer!(serializer);
+}
+
+struct Tag<'a> {
+    key: &'static str,
+    value: &'a dyn Serialize,
+}
+
+impl Middleware for Tag<'_> {
+    fn begin<'a>(
+        &self,
+        begin: Begin,
+        serializer: &'a mut dyn Serializer,
+    ) -> SerializeResult<Compound<'a>> {
+        let Begin::Struct(name, len) = begin else {
+            return Ok(begin.forward(serializer)?);
+        };
+
+        let mut compound = Begin::Struct(name, len + 1).forward(serializer)?;
+        Element::StructField(self.key, self.value).forward(&mut compound)?;
+        Ok(compound)
+    }
+}
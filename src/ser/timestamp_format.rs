@@ -0,0 +1,79 @@
+//! Timestamps serialized with a chosen format, see [`TimestampFormat`].
+
+use core::fmt::{self, Debug, Formatter};
+
+use serde::ser::Error as _;
+use time::OffsetDateTime;
+use time::formatting::Formattable;
+
+/// A dynamically serializable timestamp, which serializes as a string in a
+/// chosen format.
+///
+/// `OffsetDateTime` serializes in a format of its own, so systems exchanging
+/// timestamps in another one, e.g. RFC 2822 or a custom pattern, wrap every
+/// field with a `serialize_with` function per format. `TimestampFormat` takes
+/// the format as a value instead: any of the well-known formats of `time`, or
+/// a format description parsed at runtime. Together with the deserializing
+/// counterpart, [`de::TimestampFormat`], this transcodes timestamps from one
+/// format to another.
+///
+/// [`de::TimestampFormat`]: crate::de::TimestampFormat
+///
+/// # Examples
+///
+/// ```
+/// # use time::format_description::well_known::Rfc2822;
+/// # use time::macros::datetime;
+/// # use dyn_serde::ser::TimestampFormat;
+/// #
+/// let datetime = datetime!(2024-03-01 12:30:00 +01:00);
+/// let json = serde_json::to_string(&TimestampFormat::new(&datetime, &Rfc2822)).unwrap();
+/// assert_eq!(json, r#""Fri, 01 Mar 2024 12:30:00 +0100""#);
+/// ```
+pub struct TimestampFormat<'a, F: ?Sized> {
+    datetime: &'a OffsetDateTime,
+    format: &'a F,
+}
+
+impl<'a, F> TimestampFormat<'a, F>
+where
+    F: Formattable + ?Sized,
+{
+    /// Wraps `datetime`, serializing it formatted with `format`.
+    #[must_use]
+    pub fn new(datetime: &'a OffsetDateTime, format: &'a F) -> Self {
+        TimestampFormat { datetime, format }
+    }
+}
+
+impl<F: ?Sized> Clone for TimestampFormat<'_, F> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<F: ?Sized> Copy for TimestampFormat<'_, F> {}
+
+impl<F: ?Sized> Debug for TimestampFormat<'_, F> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TimestampFormat")
+            .field("datetime", self.datetime)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<F> serde::Serialize for TimestampFormat<'_, F>
+where
+    F: Formattable + ?Sized,
+{
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        let formatted = self
+            .datetime
+            .format(self.format)
+            .map_err(S::Error::custom)?;
+        serializer.serialize_str(&formatted)
+    }
+}
@@ -0,0 +1,96 @@
+//! A value serialized along with a version, see [`Versioned`].
+
+use core::fmt::{self, Debug, Formatter};
+
+use serde::ser::SerializeStruct as _;
+
+/// A value which serializes within an envelope tagging it with a version
+/// number given at runtime, as `{"v": 2, "data": value}`.
+///
+/// Versioned storage needs to tell which shape a stored value has before
+/// deserializing it, while the types stored seldom carry a version of their
+/// own. `Versioned` adds it around any value without editing its type, the
+/// same way for every type, and the version can come from a configuration or
+/// a schema registry rather than from the code. `de::Versioned` reads the
+/// envelope back, migrating the values of older versions, with feature
+/// `serde_json`.
+///
+/// The envelope is a struct named `Versioned` of the two fields
+/// [`Versioned::VERSION`] and [`Versioned::DATA`], so binary formats write it
+/// as a pair. `T` is any serializable type, including `dyn Serialize`.
+///
+/// # Examples
+///
+/// ```
+/// # use dyn_serde::ser::Versioned;
+/// #
+/// #[derive(serde::Serialize)]
+/// struct User {
+///     name: &'static str,
+/// }
+///
+/// let user = User { name: "ferris" };
+/// let json = serde_json::to_string(&Versioned::new(2, &user)).unwrap();
+/// assert_eq!(json, r#"{"v":2,"data":{"name":"ferris"}}"#);
+/// ```
+pub struct Versioned<'a, T: ?Sized> {
+    version: u32,
+    value: &'a T,
+}
+
+impl<'a, T: ?Sized> Versioned<'a, T> {
+    /// The name of the field holding the version.
+    pub const VERSION: &'static str = "v";
+
+    /// The name of the field holding the value.
+    pub const DATA: &'static str = "data";
+
+    /// Wraps `value`, tagging it with `version`.
+    #[must_use]
+    pub fn new(version: u32, value: &'a T) -> Self {
+        Versioned { version, value }
+    }
+
+    /// Returns the version.
+    #[must_use]
+    pub fn version(&self) -> u32 {
+        self.version
+    }
+
+    /// Returns the value.
+    #[must_use]
+    pub fn get(&self) -> &'a T {
+        self.value
+    }
+}
+
+impl<T: ?Sized> Clone for Versioned<'_, T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T: ?Sized> Copy for Versioned<'_, T> {}
+
+impl<T: ?Sized> Debug for Versioned<'_, T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Versioned")
+            .field("version", &self.version)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<T> serde::Serialize for Versioned<'_, T>
+where
+    T: ?Sized + serde::Serialize,
+{
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        let mut envelope = serializer.serialize_struct("Versioned", 2)?;
+        envelope.serialize_field(Self::VERSION, &self.version)?;
+        envelope.serialize_field(Self::DATA, self.value)?;
+        envelope.end()
+    }
+}
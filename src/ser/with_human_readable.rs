@@ -0,0 +1,73 @@
+//! A [`Serializer`] wrapper fixing whether it is human-readable, see
+//! [`WithHumanReadable`].
+
+use core::fmt::{self, Debug, Formatter};
+
+use crate::ser::Serializer;
+use crate::ser::middleware::{self, Middleware, MiddlewareSerializer};
+
+/// A [`Serializer`] wrapper which answers the same `dyn_is_human_readable`
+/// whatever the state of the wrapped serializer.
+///
+/// This overrides the answer of a format with the one given to
+/// [`WithHumanReadable::fixed`], or keeps the answer captured by
+/// [`WithHumanReadable::new`] for a serializer which does not once it has
+/// finished. An [`InplaceSerializer`] keeps it by itself.
+///
+/// Only the flag of the wrapper itself is fixed. The values inside the value
+/// being serialized are serialized by the wrapped serializer as usual.
+///
+/// [`InplaceSerializer`]: crate::ser::InplaceSerializer
+///
+/// # Examples
+///
+/// ```
+/// # use dyn_serde::{Serialize, Serializer};
+/// # use dyn_serde::ser::WithHumanReadable;
+/// #
+/// let mut serializer = <dyn Serializer>::new(serde_json::value::Serializer);
+/// let mut serializer = WithHumanReadable::fixed(&mut serializer, false);
+///
+/// (&[1, 2] as &dyn Serialize).dyn_serialize(&mut serializer).unwrap();
+/// assert!(!serializer.dyn_is_human_readable());
+/// ```
+pub struct WithHumanReadable<'a> {
+    serializer: MiddlewareSerializer<'a, Forward>,
+}
+
+impl<'a> WithHumanReadable<'a> {
+    /// Wraps `serializer`, capturing whether it is human-readable.
+    ///
+    /// The serializer is expected to be ready, as it may not know otherwise.
+    #[must_use]
+    pub fn new(serializer: &'a mut dyn Serializer) -> Self {
+        WithHumanReadable {
+            serializer: MiddlewareSerializer::new(serializer, Forward),
+        }
+    }
+
+    /// Wraps `serializer`, answering `human_readable` whether it is
+    /// human-readable.
+    #[must_use]
+    pub fn fixed(serializer: &'a mut dyn Serializer, human_readable: bool) -> Self {
+        let mut serializer = MiddlewareSerializer::new(serializer, Forward);
+        serializer.set_human_readable(human_readable);
+        WithHumanReadable { serializer }
+    }
+}
+
+impl Debug for WithHumanReadable<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("WithHumanReadable")
+            .field("human_readable", &self.serializer.dyn_is_human_readable())
+            .finish_non_exhaustive()
+    }
+}
+
+impl Serializer for WithHumanReadable<'_> {
+    middleware::forward_to_middleware_serializer!(serializer);
+}
+
+struct Forward;
+
+impl Middleware for Forward {}
@@ -0,0 +1,70 @@
+//! Helpers for testing dynamic serialization formats, see [`roundtrip`].
+//!
+//! This module requires feature `test-util`.
+
+use core::fmt::Debug;
+
+#[cfg(not(feature = "std"))]
+use alloc::vec::Vec;
+
+use crate::de::{DeserializeResult, Deserializer};
+use crate::ser::{Serialize, SerializeResult, Serializer};
+
+/// Serializes `value` through a `&mut dyn Serializer`, deserializes the output
+/// back through a `&mut dyn Deserializer`, asserts that the result is equal to
+/// `value`, and returns the output.
+///
+/// This is a conformance harness for format backends: `make_ser` is given the
+/// output buffer and a callback to call with a dynamic serializer writing to
+/// it, and `make_de` is given the output and a callback to call with a dynamic
+/// deserializer reading from it. Running it over values of every type of the
+/// serde data model, e.g. the structs, enums and collections deriving the
+/// serde traits, checks that the format survives the dynamic layer intact.
+///
+/// # Panics
+///
+/// Panics if either callback is not called, if the serialization or the
+/// deserialization fails, or if the round-tripped value is not equal to
+/// `value`.
+///
+/// # Examples
+///
+/// ```
+/// # use dyn_serde::{Deserializer, Serializer};
+/// # use dyn_serde::test_util::roundtrip;
+/// #
+/// let output = roundtrip(
+///     &vec![Some(1), None],
+///     |output, f| f(&mut <dyn Serializer>::new(&mut serde_json::Serializer::new(output))),
+///     |input, f| f(&mut <dyn Deserializer>::new(&mut serde_json::Deserializer::from_slice(input))),
+/// );
+/// assert_eq!(output, b"[1,null]");
+/// ```
+pub fn roundtrip<T, S, D>(value: &T, make_ser: S, make_de: D) -> Vec<u8>
+where
+    T: serde::Serialize + serde::de::DeserializeOwned + PartialEq + Debug,
+    S: FnOnce(&mut Vec<u8>, &mut dyn FnMut(&mut dyn Serializer)),
+    D: for<'de> FnOnce(&'de [u8], &mut dyn FnMut(&mut dyn Deserializer<'de>)),
+{
+    let mut output = Vec::new();
+    let mut serialized: Option<SerializeResult<()>> = None;
+    (make_ser)(&mut output, &mut |serializer| {
+        serialized = Some((value as &dyn Serialize).dyn_serialize(serializer));
+    });
+    match serialized {
+        Some(Ok(())) => {}
+        Some(Err(error)) => panic!("failed to serialize {value:?}: {error}"),
+        None => panic!("the serializer has not been made"),
+    }
+
+    let mut deserialized: Option<DeserializeResult<T>> = None;
+    (make_de)(&output, &mut |deserializer| {
+        deserialized = Some(T::deserialize(deserializer));
+    });
+    match deserialized {
+        Some(Ok(roundtrip)) => assert_eq!(roundtrip, *value, "the value changed in the round trip"),
+        Some(Err(error)) => panic!("failed to deserialize {value:?}: {error}"),
+        None => panic!("the deserializer has not been made"),
+    }
+    output
+}
@@ -0,0 +1,73 @@
+//! Test that `dyn_serde::ser::collect_str` formats short values without
+//! allocating, as `no_std` targets without a heap need.
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::cell::Cell;
+use std::fmt::Display;
+use std::net::Ipv6Addr;
+
+use dyn_serde::Serializer;
+use dyn_serde::ser::collect_str;
+
+/// Counts the allocations made by the current thread, so that tests running
+/// in parallel do not disturb each other.
+struct CountingAllocator;
+
+thread_local! {
+    static ALLOCATIONS: Cell<usize> = const { Cell::new(0) };
+}
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let _ = ALLOCATIONS.try_with(|n| n.set(n.get() + 1));
+        unsafe { System.alloc(layout) }
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        unsafe { System.dealloc(ptr, layout) }
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        let _ = ALLOCATIONS.try_with(|n| n.set(n.get() + 1));
+        unsafe { System.realloc(ptr, layout, new_size) }
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+/// Serializes `value` with `collect_str` into JSON written to a buffer on the
+/// stack, returning the allocations made and the output.
+fn allocations(value: &dyn Display) -> (usize, String) {
+    const CAPACITY: usize = 512;
+    let mut buf = [0; CAPACITY];
+    let mut writer = &mut buf[..];
+    let before = ALLOCATIONS.with(Cell::get);
+    {
+        let mut serializer = serde_json::Serializer::new(&mut writer);
+        let mut serializer = <dyn Serializer>::new(&mut serializer);
+        collect_str(&mut serializer, value).unwrap();
+    }
+    let allocations = ALLOCATIONS.with(Cell::get) - before;
+    let written = CAPACITY - writer.len();
+    (
+        allocations,
+        String::from_utf8(buf[..written].to_vec()).unwrap(),
+    )
+}
+
+#[test]
+fn test_collect_str_no_alloc() {
+    // values up to 128 bytes are formatted on the stack.
+    let addr = Ipv6Addr::new(0x2001, 0xdb8, 0, 0, 0, 0, 0, 1);
+    assert_eq!(allocations(&addr), (0, r#""2001:db8::1""#.to_owned()));
+    assert_eq!(allocations(&u128::MAX).0, 0);
+    let full = "x".repeat(128);
+    assert_eq!(allocations(&full), (0, format!("\"{full}\"")));
+
+    // longer values spill to the heap.
+    let long = "x".repeat(129);
+    let (count, output) = allocations(&long);
+    assert!(count > 0);
+    assert_eq!(output, format!("\"{long}\""));
+}
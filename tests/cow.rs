@@ -0,0 +1,121 @@
+//! Test that `Cow<str>` and `Cow<[u8]>` go through `dyn_serde::Serializer`
+//! as strings and bytes, whether borrowed or owned, without being copied.
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::borrow::Cow;
+use std::cell::Cell;
+
+use dyn_serde::ser::{NormalizeInts, TagStructs};
+use dyn_serde::{Serialize, Serializer};
+
+/// Counts the allocations made by the current thread, so that tests running
+/// in parallel do not disturb each other.
+struct CountingAllocator;
+
+thread_local! {
+    static ALLOCATIONS: Cell<usize> = const { Cell::new(0) };
+}
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let _ = ALLOCATIONS.try_with(|n| n.set(n.get() + 1));
+        unsafe { System.alloc(layout) }
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        unsafe { System.dealloc(ptr, layout) }
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        let _ = ALLOCATIONS.try_with(|n| n.set(n.get() + 1));
+        unsafe { System.realloc(ptr, layout, new_size) }
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+#[derive(serde::Serialize)]
+struct Message<'a> {
+    text: Cow<'a, str>,
+    #[serde(with = "serde_bytes")]
+    data: Cow<'a, [u8]>,
+}
+
+fn message(owned: bool) -> Message<'static> {
+    if owned {
+        Message {
+            text: Cow::Owned("hello".to_owned()),
+            data: Cow::Owned(vec![1, 2, 3]),
+        }
+    } else {
+        Message {
+            text: Cow::Borrowed("hello"),
+            data: Cow::Borrowed(&[1, 2, 3]),
+        }
+    }
+}
+
+/// Serializes `value` into CBOR through `dyn_serde::Serializer`.
+fn to_cbor(value: &dyn Serialize) -> Vec<u8> {
+    let mut output = Vec::new();
+    ciborium::into_writer(value, &mut output).unwrap();
+    output
+}
+
+#[test]
+fn test_cow_str() {
+    let borrowed: Cow<str> = Cow::Borrowed("hello");
+    let owned: Cow<str> = Cow::Owned("hello".to_owned());
+
+    // a text string of 5 bytes, whatever the variant.
+    let expected = [&[0x65][..], b"hello"].concat();
+    assert_eq!(to_cbor(&borrowed), expected);
+    assert_eq!(to_cbor(&owned), expected);
+}
+
+#[test]
+fn test_cow_bytes() {
+    let borrowed = serde_bytes::Bytes::new(&[1, 2, 3]);
+    let owned = serde_bytes::ByteBuf::from(vec![1, 2, 3]);
+    let message_borrowed = message(false);
+    let message_owned = message(true);
+
+    // a byte string of 3 bytes, whatever the variant.
+    let expected = [0x43, 1, 2, 3];
+    assert_eq!(to_cbor(&borrowed), expected);
+    assert_eq!(to_cbor(&owned), expected);
+    assert_eq!(to_cbor(&message_borrowed), to_cbor(&message_owned));
+    assert!(to_cbor(&message_borrowed).ends_with(&expected));
+
+    // without `serde_bytes`, serde serializes `[u8]` as a sequence.
+    let seq: Cow<[u8]> = Cow::Borrowed(&[1, 2, 3]);
+    assert_eq!(to_cbor(&seq), [0x83, 1, 2, 3]);
+}
+
+#[test]
+fn test_cow_no_copy() {
+    fn allocations(value: &dyn Serialize, output: &mut Vec<u8>) -> usize {
+        let mut serializer = serde_json::Serializer::new(output);
+        let mut serializer = <dyn Serializer>::new(&mut serializer);
+        let mut serializer = NormalizeInts::new(&mut serializer);
+        let mut serializer = TagStructs::new(&mut serializer, "type", &"Message");
+
+        let before = ALLOCATIONS.with(Cell::get);
+        value.dyn_serialize(&mut serializer).unwrap();
+        ALLOCATIONS.with(Cell::get) - before
+    }
+
+    for owned in [false, true] {
+        let value = message(owned);
+        let mut output = Vec::with_capacity(100);
+        assert_eq!(allocations(&value, &mut output), 0);
+        assert_eq!(
+            output,
+            br#"{"type":"Message","text":"hello","data":[1,2,3]}"#
+        );
+    }
+
+    // the allocations of the writer are counted.
+    assert!(allocations(&message(false), &mut Vec::new()) > 0);
+}
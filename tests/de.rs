@@ -1,3 +1,10 @@
+// the assertions spell out the values the format reads.
+#![allow(
+    clippy::approx_constant,
+    clippy::bool_assert_comparison,
+    clippy::let_unit_value
+)]
+
 macro_rules! make_deserializer {
     ($deserializer:ident = $str:literal) => {
         let mut $deserializer = serde_json::Deserializer::from_str($str);
@@ -221,3 +228,2752 @@ fn test_serialize_struct_variant() {
     let value = <Enum as serde::Deserialize<'_>>::deserialize(deserializer).unwrap();
     assert_eq!(value, Enum::StructVariant { a: true });
 }
+
+#[test]
+fn test_required_methods_only() {
+    use dyn_serde::de::{DeserializeError, InplaceDeserializeError, InplaceDeserializeResult};
+    use dyn_serde::de::{Deserializer, Visitor};
+
+    struct Number {
+        value: u64,
+        error: Option<DeserializeError>,
+    }
+
+    impl<'de> Deserializer<'de> for Number {
+        fn dyn_deserialize_any(
+            &mut self,
+            visitor: &mut dyn Visitor<'de>,
+        ) -> InplaceDeserializeResult<()> {
+            visitor.dyn_visit_u64(self.value).map_err(|error| {
+                self.error = Some(error);
+                InplaceDeserializeError::Error
+            })
+        }
+    }
+
+    let mut deserializer = Number {
+        value: 255,
+        error: None,
+    };
+    let deserializer = &mut deserializer as &mut dyn Deserializer<'_>;
+    assert!(deserializer.dyn_is_human_readable());
+    let value = <u8 as serde::Deserialize<'_>>::deserialize(&mut *deserializer).unwrap();
+    assert_eq!(value, u8::MAX);
+    let value = <u64 as serde::Deserialize<'_>>::deserialize(&mut *deserializer).unwrap();
+    assert_eq!(value, u64::from(u8::MAX));
+
+    let mut deserializer = Number {
+        value: 256,
+        error: None,
+    };
+    let result =
+        <u8 as serde::Deserialize<'_>>::deserialize(&mut deserializer as &mut dyn Deserializer<'_>);
+    assert!(result.is_err());
+    assert!(deserializer.error.is_some());
+}
+
+#[test]
+fn test_trace_deserializer() {
+    use dyn_serde::de::TraceDeserializer;
+
+    #[derive(Debug, PartialEq, serde::Deserialize)]
+    struct Point {
+        x: i32,
+        y: Option<String>,
+        z: char,
+    }
+
+    let mut trace = Vec::new();
+    let mut push = |call: &str| trace.push(call.to_owned());
+    make_deserializer!(deserializer = "{\"x\":1,\"y\":\"a\",\"z\":\"bc\"}");
+    let mut deserializer = TraceDeserializer::new(deserializer, &mut push);
+    let deserializer = &mut deserializer as &mut dyn dyn_serde::Deserializer<'_>;
+    assert!(<Point as serde::Deserialize<'_>>::deserialize(deserializer).is_err());
+    assert_eq!(
+        trace,
+        [
+            "deserialize_struct(\"Point\", [\"x\", \"y\", \"z\"])",
+            "visit_map",
+            "next_key",
+            "deserialize_identifier",
+            "visit_borrowed_str(\"x\")",
+            "next_value",
+            "deserialize_i32",
+            "visit_u64(1)",
+            "next_key",
+            "deserialize_identifier",
+            "visit_borrowed_str(\"y\")",
+            "next_value",
+            "deserialize_option",
+            "visit_some",
+            "deserialize_string",
+            "visit_borrowed_str(\"a\")",
+            "next_key",
+            "deserialize_identifier",
+            "visit_borrowed_str(\"z\")",
+            "next_value",
+            "deserialize_char",
+            "visit_borrowed_str(\"bc\")",
+            "error: invalid value: string \"bc\", expected a character",
+        ],
+    );
+}
+
+#[test]
+fn test_inplace_deserialize_error_state() {
+    use dyn_serde::Deserializer;
+    use dyn_serde::de::{InplaceDeserializeError, InplaceDeserializerState, InplaceVisitor};
+
+    let mut deserializer = serde_json::Deserializer::from_str("true");
+    let mut deserializer = <dyn Deserializer>::new(&mut deserializer);
+    let mut visitor = InplaceVisitor::Visitor(serde::de::IgnoredAny);
+    deserializer.dyn_deserialize_any(&mut visitor).unwrap();
+    assert_eq!(visitor.state(), InplaceDeserializerState::Value);
+
+    let mut visitor = InplaceVisitor::Visitor(serde::de::IgnoredAny);
+    let error = deserializer.dyn_deserialize_any(&mut visitor).unwrap_err();
+    assert_eq!(error, InplaceDeserializeError::NotDeserializer);
+    assert_eq!(
+        error.expected(),
+        Some(InplaceDeserializerState::Deserializer)
+    );
+    assert_eq!(deserializer.state(), InplaceDeserializerState::Value);
+    assert_eq!(
+        error.in_state(deserializer.state()).to_string(),
+        "the deserializer is not ready: expected Deserializer but it is in Value state"
+    );
+    // the error alone keeps its message.
+    assert_eq!(error.to_string(), "the deserializer is not ready");
+
+    assert_eq!(
+        InplaceDeserializeError::NotVisitor
+            .in_state(InplaceDeserializerState::Value)
+            .to_string(),
+        "the visitor is not ready: expected Visitor but it is in Value state"
+    );
+    assert_eq!(InplaceDeserializeError::Error.expected(), None);
+}
+
+#[test]
+fn test_inplace_access_errors() {
+    use dyn_serde::de::{InplaceDeserializerState, InplaceMapAccess, InplaceSeqAccess};
+    use dyn_serde::de::{MapAccess, SeqAccess};
+    use serde::de::value::{Error, MapDeserializer, SeqDeserializer};
+    use serde::de::{MapAccess as _, SeqAccess as _};
+
+    let access = SeqDeserializer::<_, Error>::new([1, 2, 3].into_iter());
+    let mut access = InplaceSeqAccess::new(access);
+    let mut dyn_access = &mut access as &mut dyn SeqAccess;
+    assert_eq!(dyn_access.next_element::<u8>().unwrap(), Some(1));
+    assert!(dyn_access.next_element::<bool>().is_err());
+    // the access is done once an element has failed.
+    let error = dyn_access.next_element::<u8>().unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "the visitor is not ready to deserialize the contents of the sequence"
+    );
+    assert_eq!(access.state(), InplaceDeserializerState::Error);
+    let error: Error = access.try_into_error().unwrap();
+    assert_eq!(
+        error.to_string(),
+        "invalid type: integer `2`, expected a boolean"
+    );
+
+    // an access which has not failed is given back.
+    let access = SeqDeserializer::<_, Error>::new([1].into_iter());
+    let access = InplaceSeqAccess::new(access).try_into_error().unwrap_err();
+    assert_eq!(access.state(), InplaceDeserializerState::SeqAccess);
+
+    let access = MapDeserializer::<_, Error>::new([("a", 1), ("b", -1)].into_iter());
+    let mut access = InplaceMapAccess::new(access);
+    let mut dyn_access = &mut access as &mut dyn MapAccess;
+    assert_eq!(
+        dyn_access.next_entry::<String, u8>().unwrap(),
+        Some(("a".to_owned(), 1))
+    );
+    assert_eq!(
+        dyn_access.next_key::<String>().unwrap(),
+        Some("b".to_owned())
+    );
+    assert!(dyn_access.next_value::<u8>().is_err());
+    let error: Error = access.try_into_error().unwrap();
+    assert_eq!(
+        error.to_string(),
+        "invalid value: integer `-1`, expected u8"
+    );
+}
+
+#[test]
+fn test_inplace_access_take_error() {
+    use dyn_serde::de::{InplaceDeserializerState, InplaceMapAccess, InplaceSeqAccess};
+    use dyn_serde::de::{MapAccess, SeqAccess};
+    use serde::de::value::{Error, MapDeserializer, SeqDeserializer};
+    use serde::de::{MapAccess as _, SeqAccess as _};
+
+    /// Reads booleans until one fails, as a visitor only borrowing the access.
+    fn read_seq(access: &mut InplaceSeqAccess<'_, SeqDeserializer<std::vec::IntoIter<u8>, Error>>) {
+        let mut access = access as &mut dyn SeqAccess;
+        while let Ok(Some(_)) = access.next_element::<bool>() {}
+    }
+
+    let mut access = InplaceSeqAccess::new(SeqDeserializer::new(vec![1u8].into_iter()));
+    read_seq(&mut access);
+    let error = access.take_error().unwrap();
+    assert_eq!(
+        error.to_string(),
+        "invalid type: integer `1`, expected a boolean"
+    );
+    // the error is taken once, leaving the access not ready.
+    assert_eq!(access.state(), InplaceDeserializerState::None);
+    assert!(access.take_error().is_none());
+    let mut dyn_access = &mut access as &mut dyn SeqAccess;
+    assert!(dyn_access.next_element::<bool>().is_err());
+    assert!(access.take_error().is_none());
+
+    // an access which has not failed keeps its state.
+    let mut access = InplaceSeqAccess::new(SeqDeserializer::<_, Error>::new([1].into_iter()));
+    assert!(access.take_error().is_none());
+    assert_eq!(access.state(), InplaceDeserializerState::SeqAccess);
+
+    let access = MapDeserializer::<_, Error>::new([("a", 1)].into_iter());
+    let mut access = InplaceMapAccess::new(access);
+    let mut dyn_access = &mut access as &mut dyn MapAccess;
+    assert!(dyn_access.next_key::<u8>().is_err());
+    let error = access.take_error().unwrap();
+    assert_eq!(error.to_string(), "invalid type: string \"a\", expected u8");
+    assert_eq!(access.state(), InplaceDeserializerState::None);
+    assert!(access.take_error().is_none());
+}
+
+#[cfg(feature = "serde_json")]
+#[test]
+fn test_from_json_value() {
+    use dyn_serde::Deserializer;
+
+    #[derive(Debug, PartialEq, serde::Deserialize)]
+    enum Shape {
+        Empty,
+        Circle(f64),
+        Rect { w: u32, h: u32 },
+    }
+
+    #[derive(Debug, PartialEq, serde::Deserialize)]
+    struct Drawing<'a> {
+        name: &'a str,
+        tags: Vec<String>,
+        owner: Option<String>,
+        shapes: Vec<Shape>,
+    }
+
+    let value = serde_json::json!({
+        "name": "sketch",
+        "tags": ["a", "b"],
+        "owner": null,
+        "shapes": ["Empty", { "Circle": 1.5 }, { "Rect": { "w": 2, "h": 3 } }],
+    });
+    let mut deserializer = dyn_serde::de::from_json_value(&value);
+    let drawing = <Drawing<'_> as serde::Deserialize<'_>>::deserialize(
+        &mut deserializer as &mut dyn Deserializer<'_>,
+    )
+    .unwrap();
+    assert_eq!(
+        drawing,
+        Drawing {
+            name: "sketch",
+            tags: vec!["a".to_owned(), "b".to_owned()],
+            owner: None,
+            shapes: vec![Shape::Empty, Shape::Circle(1.5), Shape::Rect { w: 2, h: 3 }],
+        }
+    );
+    assert!(deserializer.error().is_none());
+
+    let value = serde_json::json!({ "name": "sketch", "tags": [1] });
+    let mut deserializer = dyn_serde::de::from_json_value(&value);
+    let result = <Drawing<'_> as serde::Deserialize<'_>>::deserialize(
+        &mut deserializer as &mut dyn Deserializer<'_>,
+    );
+    assert!(result.is_err());
+    assert_eq!(
+        deserializer.error().unwrap().to_string(),
+        "invalid type: integer `1`, expected a string"
+    );
+}
+
+#[cfg(feature = "serde_json")]
+#[test]
+fn test_from_json_str() {
+    use std::borrow::Cow;
+
+    #[derive(Debug, PartialEq, serde::Deserialize)]
+    struct Entry<'a> {
+        key: &'a str,
+        #[serde(borrow)]
+        value: Cow<'a, str>,
+    }
+
+    let json = String::from(r#" {"key": "k", "value": "v"} "#);
+    let entry: Entry<'_> = dyn_serde::de::from_json_str(&json).unwrap();
+    assert_eq!(entry.key, "k");
+    assert!(matches!(entry.value, Cow::Borrowed("v")));
+
+    // escaped strings cannot be borrowed, but may still be owned.
+    let entry: Entry<'_> = dyn_serde::de::from_json_str(r#"{"key": "k", "value": "\\"}"#).unwrap();
+    assert!(matches!(entry.value, Cow::Owned(ref v) if v == "\\"));
+
+    assert_eq!(
+        dyn_serde::de::from_json_str::<Vec<u8>>("[1, 2] 3")
+            .unwrap_err()
+            .to_string(),
+        "trailing characters at line 1 column 8"
+    );
+    assert_eq!(
+        dyn_serde::de::from_json_str::<Vec<u8>>("[1, 256]")
+            .unwrap_err()
+            .to_string(),
+        "invalid value: integer `256`, expected u8 at line 1 column 7"
+    );
+    assert_eq!(
+        dyn_serde::de::from_json_str::<Vec<u8>>("[1,")
+            .unwrap_err()
+            .to_string(),
+        "EOF while parsing a value at line 1 column 3"
+    );
+}
+
+#[cfg(feature = "serde_json")]
+#[test]
+fn test_from_relaxed_json_str() {
+    use dyn_serde::de::{from_relaxed_json_str, relax_json};
+
+    #[derive(Debug, PartialEq, serde::Deserialize)]
+    struct Config {
+        name: String,
+        ports: Vec<u16>,
+        limits: Limits,
+    }
+
+    #[derive(Debug, PartialEq, serde::Deserialize)]
+    struct Limits {
+        connections: u32,
+    }
+
+    let json = r#"{
+        // the name of the service.
+        "name": "a // b, /* c */",
+        /* ports
+           to listen on */
+        "ports": [80, 443,],
+        "limits": {
+            "connections": 100, // per port
+        },
+    }"#;
+    assert_eq!(
+        from_relaxed_json_str::<Config>(json).unwrap(),
+        Config {
+            name: "a // b, /* c */".to_owned(),
+            ports: vec![80, 443],
+            limits: Limits { connections: 100 },
+        }
+    );
+
+    // strings are left alone, escaped quotes included.
+    assert_eq!(relax_json(r#"["\"//", /**/ 1,]"#), r#"["\"//",      1 ]"#);
+    assert_eq!(relax_json("1 /* unterminated"), "1                ");
+
+    // errors point into the input.
+    let error = from_relaxed_json_str::<Config>("{\n  // no name\n  \"name\": 1,\n}").unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "invalid type: integer `1`, expected a string at line 3 column 11"
+    );
+
+    // only the last comma is removed.
+    let error = from_relaxed_json_str::<Vec<u8>>("[1, 2,, ]").unwrap_err();
+    assert_eq!(error.to_string(), "trailing comma at line 1 column 9");
+}
+
+#[test]
+fn test_ordinal_variants() {
+    use dyn_serde::Deserializer;
+    use dyn_serde::de::{InplaceDeserializer, OrdinalVariants};
+
+    #[derive(Debug, PartialEq, serde::Deserialize)]
+    enum Level {
+        Low,
+        Medium,
+        High,
+        Custom(u8),
+    }
+
+    #[derive(Debug, PartialEq, serde::Deserialize)]
+    struct Alert {
+        level: Level,
+        fallback: Option<Level>,
+    }
+
+    fn deserialize<'de, T: serde::Deserialize<'de>>(json: &'de str) -> Result<T, String> {
+        let mut deserializer = serde_json::Deserializer::from_str(json);
+        let mut inplace = <dyn Deserializer>::new(&mut deserializer);
+        let mut deserializer = OrdinalVariants::new(&mut inplace);
+        let result = T::deserialize(&mut deserializer as &mut dyn Deserializer);
+        result.map_err(|error| match inplace {
+            InplaceDeserializer::Error(error, _) => error.to_string(),
+            _ => error.to_string(),
+        })
+    }
+
+    // the index of the third variant.
+    assert_eq!(deserialize::<Level>("2"), Ok(Level::High));
+
+    // names and maps are read as usual, at every depth.
+    assert_eq!(
+        deserialize::<Vec<Level>>(r#"[0, "Medium", {"Custom": 7}]"#),
+        Ok(vec![Level::Low, Level::Medium, Level::Custom(7)])
+    );
+    assert_eq!(
+        deserialize::<Alert>(r#"{"level": 1, "fallback": 0}"#),
+        Ok(Alert {
+            level: Level::Medium,
+            fallback: Some(Level::Low),
+        })
+    );
+
+    // an index out of range, or of a variant which is not a unit variant.
+    assert_eq!(
+        deserialize::<Level>("4").unwrap_err(),
+        "invalid value: integer `4`, expected variant index 0 <= i < 4 at line 1 column 1"
+    );
+    assert_eq!(
+        deserialize::<Level>("-1").unwrap_err(),
+        "invalid value: integer `-1`, expected variant index 0 <= i < 4 at line 1 column 2"
+    );
+    assert!(deserialize::<Level>("3").is_err());
+}
+
+#[test]
+fn test_case_insensitive_variants() {
+    use dyn_serde::Deserializer;
+    use dyn_serde::de::{CaseInsensitiveVariants, InplaceDeserializer};
+
+    #[derive(Debug, PartialEq, serde::Deserialize)]
+    enum Status {
+        Active,
+        #[serde(rename = "on_hold")]
+        OnHold,
+        Limited(u8),
+    }
+
+    #[derive(Debug, PartialEq, serde::Deserialize)]
+    enum Exact {
+        On,
+        ON,
+    }
+
+    #[derive(Debug, PartialEq, serde::Deserialize)]
+    struct Account {
+        status: Status,
+        previous: Option<Status>,
+    }
+
+    fn deserialize<'de, T: serde::Deserialize<'de>>(json: &'de str) -> Result<T, String> {
+        let mut deserializer = serde_json::Deserializer::from_str(json);
+        let mut inplace = <dyn Deserializer>::new(&mut deserializer);
+        let mut deserializer = CaseInsensitiveVariants::new(&mut inplace);
+        let result = T::deserialize(&mut deserializer as &mut dyn Deserializer);
+        result.map_err(|error| match inplace {
+            InplaceDeserializer::Error(error, _) => error.to_string(),
+            _ => error.to_string(),
+        })
+    }
+
+    assert_eq!(deserialize::<Status>(r#""ACTIVE""#), Ok(Status::Active));
+
+    // renamed variants, variants with content and nested enums.
+    assert_eq!(
+        deserialize::<Vec<Status>>(r#"["active", "On_Hold", {"LIMITED": 3}]"#),
+        Ok(vec![Status::Active, Status::OnHold, Status::Limited(3)])
+    );
+    assert_eq!(
+        deserialize::<Account>(r#"{"status": "aCtIvE", "previous": "ON_HOLD"}"#),
+        Ok(Account {
+            status: Status::Active,
+            previous: Some(Status::OnHold),
+        })
+    );
+
+    // an exact match wins over a match ignoring case.
+    assert_eq!(deserialize::<Exact>(r#""ON""#), Ok(Exact::ON));
+    assert_eq!(deserialize::<Exact>(r#""on""#), Ok(Exact::On));
+
+    // the keys of structs are still matched exactly.
+    assert!(deserialize::<Account>(r#"{"STATUS": "Active", "previous": null}"#).is_err());
+    assert_eq!(
+        deserialize::<Status>(r#""Inactive""#).unwrap_err(),
+        "unknown variant `Inactive`, expected one of `Active`, `on_hold`, `Limited` at line 1 column 10"
+    );
+}
+
+#[cfg(feature = "serde_json")]
+#[test]
+fn test_parse_keys() {
+    use std::collections::{BTreeMap, HashMap};
+
+    use dyn_serde::Deserializer;
+    use dyn_serde::de::ParseKeys;
+
+    #[derive(Debug, PartialEq, serde::Deserialize)]
+    struct Config {
+        ports: BTreeMap<u16, String>,
+        flags: BTreeMap<bool, BTreeMap<i8, char>>,
+        names: BTreeMap<String, u8>,
+    }
+
+    fn deserialize<'de, T: serde::Deserialize<'de>>(
+        value: &'de serde_json::Value,
+    ) -> Result<T, String> {
+        let mut deserializer = dyn_serde::de::from_json_value(value);
+        let mut parse_keys = ParseKeys::new(&mut deserializer);
+        T::deserialize(&mut parse_keys as &mut dyn Deserializer)
+            .map_err(|_| deserializer.error().unwrap().to_string())
+    }
+
+    let value = serde_json::json!({"1": "a"});
+    let mut deserializer = dyn_serde::de::from_json_value(&value);
+    assert!(
+        <HashMap<u32, String> as serde::Deserialize>::deserialize(
+            &mut deserializer as &mut dyn Deserializer
+        )
+        .is_err()
+    );
+    assert_eq!(
+        deserialize::<HashMap<u32, String>>(&value),
+        Ok(HashMap::from([(1, "a".to_owned())]))
+    );
+
+    let value = serde_json::json!({
+        "ports": {"80": "http", "443": "https"},
+        "flags": {"true": {"-1": "x"}},
+        "names": {"1": 1},
+    });
+    assert_eq!(
+        deserialize::<Config>(&value),
+        Ok(Config {
+            ports: BTreeMap::from([(80, "http".to_owned()), (443, "https".to_owned())]),
+            flags: BTreeMap::from([(true, BTreeMap::from([(-1, 'x')]))]),
+            names: BTreeMap::from([("1".to_owned(), 1)]),
+        })
+    );
+
+    // a key which does not parse fails with the usual type error.
+    let value = serde_json::json!({"256": "a"});
+    assert_eq!(
+        deserialize::<HashMap<u8, String>>(&value),
+        Err("invalid type: string \"256\", expected u8".to_owned())
+    );
+}
+
+#[test]
+fn test_scalar_or_seq() {
+    use dyn_serde::Deserializer;
+    use dyn_serde::de::{InplaceDeserializer, ScalarOrSeq};
+
+    #[derive(Debug, PartialEq, serde::Deserialize)]
+    struct Mail {
+        to: Vec<String>,
+        from: String,
+        priority: Option<u8>,
+    }
+
+    fn deserialize<T: serde::de::DeserializeOwned>(json: &str) -> Result<T, String> {
+        let mut deserializer = serde_json::Deserializer::from_str(json);
+        let mut inplace = <dyn Deserializer>::new(&mut deserializer);
+        let mut deserializer = ScalarOrSeq::new(&mut inplace);
+        T::deserialize(&mut deserializer as &mut dyn Deserializer).map_err(|e| match inplace {
+            InplaceDeserializer::Error(e, _) => e.to_string(),
+            _ => e.to_string(),
+        })
+    }
+
+    assert_eq!(
+        deserialize::<Vec<String>>(r#""x""#),
+        Ok(vec!["x".to_owned()])
+    );
+    assert_eq!(
+        deserialize::<Vec<String>>(r#"["x"]"#),
+        Ok(vec!["x".to_owned()])
+    );
+    assert_eq!(deserialize::<String>(r#"["x"]"#), Ok("x".to_owned()));
+    assert_eq!(deserialize::<String>(r#""x""#), Ok("x".to_owned()));
+    assert_eq!(deserialize::<Vec<u8>>("1"), Ok(vec![1]));
+
+    let expected = Mail {
+        to: vec!["ferris@example.com".to_owned()],
+        from: "crab@example.com".to_owned(),
+        priority: Some(1),
+    };
+    assert_eq!(
+        deserialize::<Mail>(
+            r#"{"to": "ferris@example.com", "from": ["crab@example.com"], "priority": [1]}"#
+        ),
+        Ok(expected)
+    );
+    assert_eq!(
+        deserialize::<Mail>(r#"{"to": ["a", "b"], "from": "c", "priority": null}"#),
+        Ok(Mail {
+            to: vec!["a".to_owned(), "b".to_owned()],
+            from: "c".to_owned(),
+            priority: None,
+        })
+    );
+
+    assert_eq!(
+        deserialize::<String>("[]"),
+        Err("invalid length 0, expected a single element at line 1 column 2".to_owned())
+    );
+    assert_eq!(
+        deserialize::<String>(r#"["x", "y"]"#),
+        Err("invalid length 2, expected a single element at line 1 column 10".to_owned())
+    );
+    assert_eq!(
+        deserialize::<String>("[1]"),
+        Err("invalid type: integer `1`, expected a string at line 1 column 3".to_owned())
+    );
+    assert_eq!(
+        deserialize::<Vec<u8>>(r#""x""#),
+        Err("invalid type: string \"x\", expected u8 at line 1 column 3".to_owned())
+    );
+}
+
+#[test]
+fn test_one_or_many() {
+    use dyn_serde::Deserializer;
+    use dyn_serde::de::{InplaceDeserializer, OneOrMany};
+
+    #[derive(Debug, PartialEq, serde::Deserialize)]
+    struct Query {
+        #[serde(default)]
+        tag: Vec<String>,
+        page: Option<u32>,
+    }
+
+    fn deserialize<T: serde::de::DeserializeOwned>(json: &str) -> Result<T, String> {
+        let mut deserializer = serde_json::Deserializer::from_str(json);
+        let mut inplace = <dyn Deserializer>::new(&mut deserializer);
+        let mut deserializer = OneOrMany::new(&mut inplace);
+        T::deserialize(&mut deserializer as &mut dyn Deserializer).map_err(|e| match inplace {
+            InplaceDeserializer::Error(e, _) => e.to_string(),
+            _ => e.to_string(),
+        })
+    }
+
+    // `tag=x`, `tag=x&tag=y` and no tag at all.
+    assert_eq!(
+        deserialize::<Query>(r#"{"tag": "x"}"#),
+        Ok(Query {
+            tag: vec!["x".to_owned()],
+            page: None,
+        })
+    );
+    assert_eq!(
+        deserialize::<Query>(r#"{"tag": ["x", "y"], "page": 2}"#),
+        Ok(Query {
+            tag: vec!["x".to_owned(), "y".to_owned()],
+            page: Some(2),
+        })
+    );
+    assert_eq!(
+        deserialize::<Query>(r#"{"page": 2}"#),
+        Ok(Query {
+            tag: vec![],
+            page: Some(2),
+        })
+    );
+
+    // a scalar sent several times is still rejected.
+    assert_eq!(
+        deserialize::<Query>(r#"{"page": [2]}"#),
+        Err("invalid type: sequence, expected u32 at line 1 column 9".to_owned())
+    );
+    assert_eq!(
+        deserialize::<String>(r#"["x"]"#),
+        Err("invalid type: sequence, expected a string at line 1 column 0".to_owned())
+    );
+}
+
+#[test]
+fn test_null_as_empty() {
+    use std::collections::{BTreeMap, HashMap};
+
+    use dyn_serde::Deserializer;
+    use dyn_serde::de::{InplaceDeserializer, NullAsEmpty};
+
+    #[derive(Debug, PartialEq, serde::Deserialize)]
+    struct Response {
+        items: Vec<u32>,
+        meta: BTreeMap<String, Vec<String>>,
+        next: Option<Vec<u32>>,
+    }
+
+    fn deserialize<T: serde::de::DeserializeOwned>(json: &str) -> Result<T, String> {
+        let mut deserializer = serde_json::Deserializer::from_str(json);
+        let mut inplace = <dyn Deserializer>::new(&mut deserializer);
+        let mut deserializer = NullAsEmpty::new(&mut inplace);
+        T::deserialize(&mut deserializer as &mut dyn Deserializer).map_err(|e| match inplace {
+            InplaceDeserializer::Error(e, _) => e.to_string(),
+            _ => e.to_string(),
+        })
+    }
+
+    assert_eq!(deserialize::<Vec<u32>>("null"), Ok(vec![]));
+    assert_eq!(
+        deserialize::<HashMap<String, u32>>("null"),
+        Ok(HashMap::new())
+    );
+    assert_eq!(deserialize::<Vec<u32>>("[1, 2]"), Ok(vec![1, 2]));
+    assert_eq!(
+        deserialize::<HashMap<String, u32>>(r#"{"a": 1}"#),
+        Ok(HashMap::from([("a".to_owned(), 1)]))
+    );
+
+    // at every depth, while options still read `null` as `None`.
+    assert_eq!(
+        deserialize::<Response>(r#"{"items": null, "meta": {"a": null}, "next": null}"#),
+        Ok(Response {
+            items: vec![],
+            meta: BTreeMap::from([("a".to_owned(), vec![])]),
+            next: None,
+        })
+    );
+
+    // scalars are never read from `null`, and other values still fail.
+    assert_eq!(
+        deserialize::<Vec<u32>>("[null]"),
+        Err("invalid type: null, expected u32 at line 1 column 5".to_owned())
+    );
+    assert_eq!(
+        deserialize::<Vec<u32>>(r#""x""#),
+        Err("invalid type: string \"x\", expected a sequence at line 1 column 3".to_owned())
+    );
+}
+
+#[test]
+fn test_type_constrained() {
+    use dyn_serde::Deserializer;
+    use dyn_serde::de::{InplaceDeserializer, TypeConstrained, ValueKind};
+
+    #[derive(Debug, PartialEq, serde::Deserialize)]
+    struct Port(u16);
+
+    #[derive(Debug, PartialEq, serde::Deserialize)]
+    struct Config {
+        port: u16,
+    }
+
+    fn deserialize<T: serde::de::DeserializeOwned>(
+        json: &str,
+        kind: ValueKind,
+    ) -> Result<T, String> {
+        let mut deserializer = serde_json::Deserializer::from_str(json);
+        let mut inplace = <dyn Deserializer>::new(&mut deserializer);
+        let mut deserializer = TypeConstrained::new(&mut inplace, kind);
+        T::deserialize(&mut deserializer as &mut dyn Deserializer).map_err(|e| match inplace {
+            InplaceDeserializer::Error(e, _) => e.to_string(),
+            _ => e.to_string(),
+        })
+    }
+
+    assert_eq!(
+        deserialize::<u16>(r#""80""#, ValueKind::Number),
+        Err(r#"invalid type: string "80", expected a number at line 1 column 4"#.to_owned())
+    );
+    assert_eq!(deserialize::<u16>("80", ValueKind::Number), Ok(80));
+    assert_eq!(
+        deserialize::<Option<Port>>("80", ValueKind::Number),
+        Ok(Some(Port(80)))
+    );
+    assert_eq!(
+        deserialize::<Option<Port>>("null", ValueKind::Number),
+        Ok(None)
+    );
+    assert_eq!(
+        deserialize::<Option<Port>>("true", ValueKind::Number),
+        Err("invalid type: boolean `true`, expected a number at line 1 column 4".to_owned())
+    );
+
+    // values of the right kind are still checked by the type.
+    assert_eq!(
+        deserialize::<u16>("-1", ValueKind::Number),
+        Err("invalid value: integer `-1`, expected u16 at line 1 column 2".to_owned())
+    );
+
+    // only the value itself is checked.
+    assert_eq!(
+        deserialize::<Config>(r#"{"port": 80}"#, ValueKind::Object),
+        Ok(Config { port: 80 })
+    );
+    assert_eq!(
+        deserialize::<Config>("[80]", ValueKind::Object),
+        Err("invalid type: sequence, expected an object at line 1 column 1".to_owned())
+    );
+    assert_eq!(
+        deserialize::<Vec<String>>(r#"["a"]"#, ValueKind::Array),
+        Ok(vec!["a".to_owned()])
+    );
+    assert_eq!(
+        deserialize::<String>("{}", ValueKind::String),
+        Err("invalid type: map, expected a string at line 1 column 2".to_owned())
+    );
+    assert_eq!(
+        deserialize::<bool>("1", ValueKind::Bool),
+        Err("invalid type: integer `1`, expected a boolean at line 1 column 1".to_owned())
+    );
+}
+
+#[test]
+fn test_index_elements() {
+    use std::collections::BTreeMap;
+
+    use dyn_serde::Deserializer;
+    use dyn_serde::de::{IndexElements, InplaceDeserializer};
+
+    #[derive(Debug, PartialEq, serde::Deserialize)]
+    struct Item {
+        #[serde(rename = "_index")]
+        index: usize,
+        a: u32,
+    }
+
+    #[derive(Debug, PartialEq, serde::Deserialize)]
+    struct List {
+        name: String,
+        items: Vec<Option<Item>>,
+    }
+
+    fn deserialize<T: serde::de::DeserializeOwned>(json: &str) -> Result<T, String> {
+        let mut deserializer = serde_json::Deserializer::from_str(json);
+        let mut inplace = <dyn Deserializer>::new(&mut deserializer);
+        let mut deserializer = IndexElements::new(&mut inplace, "_index");
+        T::deserialize(&mut deserializer as &mut dyn Deserializer).map_err(|e| match inplace {
+            InplaceDeserializer::Error(e, _) => e.to_string(),
+            _ => e.to_string(),
+        })
+    }
+
+    assert_eq!(
+        deserialize::<Vec<Item>>(r#"[{"a":1},{"a":2}]"#),
+        Ok(vec![Item { index: 0, a: 1 }, Item { index: 1, a: 2 }])
+    );
+
+    // every sequence counts its own elements, through options.
+    assert_eq!(
+        deserialize::<Vec<List>>(
+            r#"[{"name": "x", "items": [{"a": 1}]}, {"name": "y", "items": [null, {"a": 2}]}]"#
+        ),
+        Ok(vec![
+            List {
+                name: "x".to_owned(),
+                items: vec![Some(Item { index: 0, a: 1 })],
+            },
+            List {
+                name: "y".to_owned(),
+                items: vec![None, Some(Item { index: 1, a: 2 })],
+            },
+        ])
+    );
+
+    // maps which are elements read the entry as well, other values do not.
+    assert_eq!(
+        deserialize::<Vec<BTreeMap<String, u32>>>(r#"[{"a": 5}]"#),
+        Ok(vec![BTreeMap::from([
+            ("_index".to_owned(), 0),
+            ("a".to_owned(), 5)
+        ])])
+    );
+    assert_eq!(
+        deserialize::<BTreeMap<String, u32>>(r#"{"a": 5}"#),
+        Ok(BTreeMap::from([("a".to_owned(), 5)]))
+    );
+    assert_eq!(deserialize::<Vec<u32>>("[7, 8]"), Ok(vec![7, 8]));
+
+    assert_eq!(
+        deserialize::<Vec<Item>>(r#"[{"_index": 3, "a": 1}]"#),
+        Err("duplicate field `_index` at line 1 column 10".to_owned())
+    );
+}
+
+#[test]
+fn test_bytes_as_seq() {
+    use dyn_serde::de::BytesAsSeq;
+
+    #[derive(Debug, PartialEq)]
+    struct Bytes(Vec<u8>);
+
+    impl serde::Serialize for Bytes {
+        fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            serializer.serialize_bytes(&self.0)
+        }
+    }
+
+    impl<'de> serde::Deserialize<'de> for Bytes {
+        fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+            struct BytesVisitor;
+
+            impl serde::de::Visitor<'_> for BytesVisitor {
+                type Value = Bytes;
+
+                fn expecting(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+                    f.write_str("bytes")
+                }
+
+                fn visit_bytes<E>(self, v: &[u8]) -> Result<Bytes, E> {
+                    Ok(Bytes(v.to_vec()))
+                }
+
+                fn visit_byte_buf<E>(self, v: Vec<u8>) -> Result<Bytes, E> {
+                    Ok(Bytes(v))
+                }
+            }
+
+            deserializer.deserialize_byte_buf(BytesVisitor)
+        }
+    }
+
+    #[derive(Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+    struct Blob {
+        name: String,
+        data: Bytes,
+        chunks: Vec<Bytes>,
+    }
+
+    let blob = Blob {
+        name: "blob".to_owned(),
+        data: Bytes(vec![0, 1, 255]),
+        chunks: vec![Bytes(vec![]), Bytes(vec![7])],
+    };
+    let json = serde_json::to_string(&blob).unwrap();
+    assert_eq!(
+        json,
+        r#"{"name":"blob","data":[0,1,255],"chunks":[[],[7]]}"#
+    );
+
+    make_deserializer!(deserializer = r#"{"name":"blob","data":[0,1,255],"chunks":[[],[7]]}"#);
+    let mut deserializer = BytesAsSeq::new(deserializer);
+    let deserializer = &mut deserializer as &mut dyn dyn_serde::Deserializer<'_>;
+    assert_eq!(
+        <Blob as serde::Deserialize<'_>>::deserialize(deserializer).unwrap(),
+        blob
+    );
+
+    // without the wrapper, the sequence is rejected.
+    make_deserializer!(deserializer = "[0,1,255]");
+    let error = <Bytes as serde::Deserialize<'_>>::deserialize(deserializer).unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "the deserialization has done unsuccessfully"
+    );
+
+    // elements must be bytes.
+    make_deserializer!(deserializer = "[0,1,256]");
+    let mut deserializer = BytesAsSeq::new(deserializer);
+    let deserializer = &mut deserializer as &mut dyn dyn_serde::Deserializer<'_>;
+    assert!(<Bytes as serde::Deserialize<'_>>::deserialize(deserializer).is_err());
+}
+
+#[test]
+fn test_seq_or_map() {
+    use dyn_serde::de::SeqOrMap;
+    use serde::de::Error as _;
+
+    #[derive(Debug, PartialEq)]
+    struct Pair {
+        a: i32,
+        b: Option<String>,
+    }
+
+    impl<'de> serde::Deserialize<'de> for Pair {
+        fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+            let (mut a, mut b) = (None, None);
+            let mut fill = |index, value: &mut dyn dyn_serde::Deserializer<'de>| {
+                match index {
+                    0 => a = Some(serde::Deserialize::deserialize(value)?),
+                    _ => b = Some(serde::Deserialize::deserialize(value)?),
+                }
+                Ok(())
+            };
+            deserializer.deserialize_any(SeqOrMap::new(&["a", "b"], &mut fill))?;
+            Ok(Pair {
+                a: a.ok_or_else(|| D::Error::missing_field("a"))?,
+                b: b.unwrap_or_default(),
+            })
+        }
+    }
+
+    let expected = Pair {
+        a: 1,
+        b: Some("x".to_owned()),
+    };
+    make_deserializer!(deserializer = r#"[1, "x"]"#);
+    assert_eq!(
+        <Pair as serde::Deserialize<'_>>::deserialize(deserializer).unwrap(),
+        expected
+    );
+    make_deserializer!(deserializer = r#"{"a": 1, "b": "x"}"#);
+    assert_eq!(
+        <Pair as serde::Deserialize<'_>>::deserialize(deserializer).unwrap(),
+        expected
+    );
+    make_deserializer!(deserializer = r#"{"b": "x", "c": [], "a": 1}"#);
+    assert_eq!(
+        <Pair as serde::Deserialize<'_>>::deserialize(deserializer).unwrap(),
+        Pair {
+            a: 1,
+            b: Some("x".to_owned())
+        }
+    );
+
+    // a missing trailing field is left to the caller.
+    assert_eq!(
+        serde_json::from_str::<Pair>("[2]").unwrap(),
+        Pair { a: 2, b: None }
+    );
+    assert_eq!(
+        serde_json::from_str::<Pair>("[1, null, 3]")
+            .unwrap_err()
+            .to_string(),
+        "invalid length 3, expected a sequence or a map of fields [\"a\", \"b\"] at line 1 column 12"
+    );
+    assert_eq!(
+        serde_json::from_str::<Pair>(r#"{"a": 1, "a": 2}"#)
+            .unwrap_err()
+            .to_string(),
+        "duplicate field `a` at line 1 column 12"
+    );
+}
+
+#[test]
+fn test_deprecate_fields() {
+    use std::collections::BTreeMap;
+
+    use dyn_serde::Deserializer;
+    use dyn_serde::de::DeprecateFields;
+
+    #[derive(Debug, PartialEq, serde::Deserialize)]
+    struct Config {
+        name: String,
+        #[serde(default)]
+        title: Option<String>,
+        server: Server,
+        mode: Mode,
+        env: BTreeMap<String, String>,
+    }
+
+    #[derive(Debug, PartialEq, serde::Deserialize)]
+    struct Server {
+        host: String,
+        #[serde(default)]
+        title: Option<String>,
+    }
+
+    #[derive(Debug, PartialEq, serde::Deserialize)]
+    enum Mode {
+        Fast { threads: u8, workers: Option<u8> },
+    }
+
+    let json = r#"{
+        "name": "app",
+        "title": "old",
+        "server": {"host": "localhost", "title": "older"},
+        "mode": {"Fast": {"threads": 4, "workers": 2}},
+        "env": {"title": "not a field", "workers": "neither"}
+    }"#;
+
+    let mut deprecated = Vec::new();
+    let mut warn = |field: &str| deprecated.push(field.to_owned());
+    let mut deserializer = serde_json::Deserializer::from_str(json);
+    let mut deserializer = <dyn Deserializer>::new(&mut deserializer);
+    let mut deserializer =
+        DeprecateFields::new(&mut deserializer, &["title", "workers", "gone"], &mut warn);
+    let config =
+        <Config as serde::Deserialize>::deserialize(&mut deserializer as &mut dyn Deserializer)
+            .unwrap();
+    assert_eq!(
+        config,
+        Config {
+            name: "app".to_owned(),
+            title: Some("old".to_owned()),
+            server: Server {
+                host: "localhost".to_owned(),
+                title: Some("older".to_owned()),
+            },
+            mode: Mode::Fast {
+                threads: 4,
+                workers: Some(2),
+            },
+            env: BTreeMap::from([
+                ("title".to_owned(), "not a field".to_owned()),
+                ("workers".to_owned(), "neither".to_owned()),
+            ]),
+        },
+    );
+    assert_eq!(deprecated, ["title", "title", "workers"]);
+}
+
+#[test]
+fn test_collect_unknown_fields() {
+    use std::collections::BTreeMap;
+
+    use dyn_serde::Deserializer;
+    use dyn_serde::de::CollectUnknownFields;
+
+    #[derive(Debug, PartialEq, serde::Deserialize)]
+    struct Config {
+        name: String,
+        server: Server,
+        mode: Mode,
+        env: BTreeMap<String, String>,
+    }
+
+    #[derive(Debug, PartialEq, serde::Deserialize)]
+    struct Server {
+        host: String,
+        port: u16,
+    }
+
+    #[derive(Debug, PartialEq, serde::Deserialize)]
+    enum Mode {
+        Fast { threads: u8 },
+    }
+
+    let json = r#"{
+        "name": "app",
+        "nmae": "typo",
+        "server": {"host": "localhost", "port": 80, "timeout": {"secs": 1}},
+        "mode": {"Fast": {"threads": 4, "affinity": true}},
+        "env": {"PATH": "/bin"},
+        "debug": [1, 2]
+    }"#;
+
+    let mut unknown = Vec::new();
+    let mut deserializer = serde_json::Deserializer::from_str(json);
+    let mut deserializer = <dyn Deserializer>::new(&mut deserializer);
+    let mut deserializer = CollectUnknownFields::new(&mut deserializer, &mut unknown);
+    let config =
+        <Config as serde::Deserialize>::deserialize(&mut deserializer as &mut dyn Deserializer)
+            .unwrap();
+    assert_eq!(
+        config,
+        Config {
+            name: "app".to_owned(),
+            server: Server {
+                host: "localhost".to_owned(),
+                port: 80,
+            },
+            mode: Mode::Fast { threads: 4 },
+            env: BTreeMap::from([("PATH".to_owned(), "/bin".to_owned())]),
+        }
+    );
+    // the keys of a plain map are not fields.
+    assert_eq!(unknown, ["nmae", "timeout", "affinity", "debug"]);
+}
+
+#[test]
+fn test_deny_unknown_fields() {
+    use std::collections::BTreeMap;
+
+    use dyn_serde::Deserializer;
+    use dyn_serde::de::{DenyUnknownFields, InplaceDeserializer};
+
+    #[derive(Debug, PartialEq, serde::Deserialize)]
+    struct Config {
+        name: String,
+        server: Option<Server>,
+        mode: Option<Mode>,
+        env: BTreeMap<String, String>,
+    }
+
+    #[derive(Debug, PartialEq, serde::Deserialize)]
+    struct Server {
+        host: String,
+    }
+
+    #[derive(Debug, PartialEq, serde::Deserialize)]
+    enum Mode {
+        Fast { threads: u8 },
+    }
+
+    fn deny(json: &str) -> Result<Config, String> {
+        let mut deserializer = serde_json::Deserializer::from_str(json);
+        let mut inplace = <dyn Deserializer>::new(&mut deserializer);
+        let mut deserializer = DenyUnknownFields::new(&mut inplace);
+        let result =
+            <Config as serde::Deserialize>::deserialize(&mut deserializer as &mut dyn Deserializer);
+        result.map_err(|error| match inplace {
+            InplaceDeserializer::Error(error, _) => error.to_string(),
+            _ => error.to_string(),
+        })
+    }
+
+    // the keys of a plain map are not fields.
+    assert_eq!(
+        deny(r#"{"name": "app", "env": {"PATH": "/bin"}}"#),
+        Ok(Config {
+            name: "app".to_owned(),
+            server: None,
+            mode: None,
+            env: BTreeMap::from([("PATH".to_owned(), "/bin".to_owned())]),
+        })
+    );
+
+    // at every depth, and in struct variants.
+    assert_eq!(
+        deny(r#"{"name": "app", "nmae": "typo", "env": {}}"#).unwrap_err(),
+        "unknown field `nmae`, expected one of `name`, `server`, `mode`, `env` at line 1 column 22"
+    );
+    assert_eq!(
+        deny(r#"{"server": {"host": "localhost", "port": 80}}"#).unwrap_err(),
+        "unknown field `port`, expected `host` at line 1 column 39"
+    );
+    assert_eq!(
+        deny(r#"{"mode": {"Fast": {"threads": 4, "affinity": true}}}"#).unwrap_err(),
+        "unknown field `affinity`, expected `threads` at line 1 column 43"
+    );
+}
+
+#[test]
+fn test_untag_structs() {
+    use dyn_serde::Deserializer;
+    use dyn_serde::de::{InplaceDeserializer, UntagStructs};
+
+    #[derive(Debug, PartialEq, serde::Deserialize)]
+    #[serde(deny_unknown_fields)]
+    struct Foo {
+        x: i32,
+    }
+
+    #[derive(Debug, PartialEq, serde::Deserialize)]
+    #[serde(deny_unknown_fields)]
+    struct Bar {
+        foo: Foo,
+        map: std::collections::BTreeMap<String, i32>,
+        #[serde(rename = "type")]
+        kind: Option<String>,
+    }
+
+    fn untag<'de, T: serde::Deserialize<'de>>(json: &'de str) -> Result<T, String> {
+        let mut deserializer = serde_json::Deserializer::from_str(json);
+        let mut inplace = <dyn Deserializer>::new(&mut deserializer);
+        let mut deserializer = UntagStructs::new(&mut inplace, "type");
+        let result = T::deserialize(&mut deserializer as &mut dyn Deserializer);
+        result.map_err(|error| match inplace {
+            InplaceDeserializer::Error(error, _) => error.to_string(),
+            _ => error.to_string(),
+        })
+    }
+
+    assert_eq!(
+        untag::<Foo>(r#"{"type":"Foo","x":1}"#).unwrap(),
+        Foo { x: 1 }
+    );
+    assert_eq!(
+        untag::<Foo>(r#"{"x":1,"type":"Foo"}"#).unwrap(),
+        Foo { x: 1 }
+    );
+    assert_eq!(untag::<Foo>(r#"{"x":1}"#).unwrap(), Foo { x: 1 });
+    assert_eq!(untag::<Foo>("[1]").unwrap(), Foo { x: 1 });
+
+    // nested structs are untagged, maps are not, and only the first tag is
+    // discarded.
+    assert_eq!(
+        untag::<Bar>(
+            r#"{"type":"Bar","foo":{"type":{"any":[]},"x":2},"map":{"type":3},"type":"kept"}"#
+        )
+        .unwrap(),
+        Bar {
+            foo: Foo { x: 2 },
+            map: [("type".to_owned(), 3)].into(),
+            kind: Some("kept".to_owned()),
+        }
+    );
+
+    // errors keep their message, whether the tag has been found or not.
+    assert_eq!(
+        untag::<Foo>(r#"{"y":2,"type":"Foo","x":1}"#).unwrap_err(),
+        "unknown field `y`, expected `x` at line 1 column 4"
+    );
+    assert_eq!(
+        untag::<Foo>(r#"{"type":"Foo","x":1,"y":2}"#).unwrap_err(),
+        "unknown field `y`, expected `x` at line 1 column 23"
+    );
+    assert_eq!(
+        untag::<Foo>(r#"{"type":"Foo" "x":1}"#).unwrap_err(),
+        "expected `,` or `}` at line 1 column 15"
+    );
+}
+
+#[test]
+fn test_in_place_seed() {
+    use dyn_serde::de::{DeserializeSeed, InPlaceSeed};
+
+    #[derive(Debug, Default, PartialEq, serde::Deserialize)]
+    struct Batch {
+        id: u32,
+        items: Vec<String>,
+    }
+
+    // the buffers of the existing value are reused.
+    let mut batch = (0, Vec::<String>::with_capacity(8));
+    let items = batch.1.as_ptr();
+    for (id, json) in [(1, r#"[1, ["a"]]"#), (2, r#"[2, ["b", "c"]]"#)] {
+        let mut deserializer = serde_json::Deserializer::from_str(json);
+        let mut deserializer = <dyn dyn_serde::Deserializer>::new(&mut deserializer);
+        InPlaceSeed::new(&mut batch)
+            .dyn_deserialize(&mut deserializer)
+            .unwrap();
+        assert_eq!(batch.0, id);
+    }
+    assert_eq!(batch.1, ["b", "c"]);
+    assert_eq!(batch.1.as_ptr(), items);
+
+    // a derived struct is updated as a whole.
+    let mut batch = Batch::default();
+    make_deserializer!(deserializer = r#"{"id": 3, "items": ["d"]}"#);
+    InPlaceSeed::new(&mut batch)
+        .dyn_deserialize(deserializer)
+        .unwrap();
+    assert_eq!(
+        batch,
+        Batch {
+            id: 3,
+            items: vec!["d".to_owned()],
+        }
+    );
+
+    // as a serde seed, e.g. for the elements of a sequence.
+    let mut last = String::new();
+    let mut deserializer = serde_json::Deserializer::from_str(r#"["x", "y"]"#);
+    serde::Deserializer::deserialize_seq(&mut deserializer, LastVisitor(&mut last)).unwrap();
+    assert_eq!(last, "y");
+
+    struct LastVisitor<'a>(&'a mut String);
+
+    impl<'de> serde::de::Visitor<'de> for LastVisitor<'_> {
+        type Value = ();
+
+        fn expecting(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+            f.write_str("a sequence of strings")
+        }
+
+        fn visit_seq<A: serde::de::SeqAccess<'de>>(self, mut seq: A) -> Result<(), A::Error> {
+            while seq.next_element_seed(InPlaceSeed::new(self.0))?.is_some() {}
+            Ok(())
+        }
+    }
+}
+
+#[test]
+fn test_deserialize_ordered_map() {
+    use serde::de::Error as _;
+
+    use dyn_serde::de::{InplaceMapAccess, deserialize_ordered_map};
+
+    #[derive(Debug, PartialEq)]
+    struct Ordered(Vec<(String, i32)>);
+
+    impl<'de> serde::Deserialize<'de> for Ordered {
+        fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+            struct OrderedVisitor;
+
+            impl<'de> serde::de::Visitor<'de> for OrderedVisitor {
+                type Value = Ordered;
+
+                fn expecting(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+                    f.write_str("a map")
+                }
+
+                fn visit_map<A>(self, map: A) -> Result<Ordered, A::Error>
+                where
+                    A: serde::de::MapAccess<'de>,
+                {
+                    let mut access = InplaceMapAccess::MapAccess(map);
+                    deserialize_ordered_map(&mut access).map(Ordered).map_err(
+                        |error| match access {
+                            InplaceMapAccess::Error(error) => error,
+                            _ => A::Error::custom(error),
+                        },
+                    )
+                }
+            }
+
+            deserializer.deserialize_map(OrderedVisitor)
+        }
+    }
+
+    make_deserializer!(deserializer = r#"{"z": 1, "a": 2, "m": 3, "a": 4}"#);
+    assert_eq!(
+        <Ordered as serde::Deserialize<'_>>::deserialize(deserializer).unwrap(),
+        Ordered(vec![
+            ("z".to_owned(), 1),
+            ("a".to_owned(), 2),
+            ("m".to_owned(), 3),
+            ("a".to_owned(), 4),
+        ])
+    );
+
+    assert_eq!(
+        serde_json::from_str::<Ordered>(r#"{"z": 1, "a": "2"}"#)
+            .unwrap_err()
+            .to_string(),
+        "invalid type: string \"2\", expected i32 at line 1 column 17"
+    );
+}
+
+#[test]
+fn test_deserialize_multimap() {
+    use serde::de::Error as _;
+    use serde_json::{Value, json};
+
+    use dyn_serde::de::{InplaceMapAccess, deserialize_ordered_map};
+
+    struct Multimap(Vec<(String, Value)>);
+
+    impl<'de> serde::Deserialize<'de> for Multimap {
+        fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+            struct MultimapVisitor;
+
+            impl<'de> serde::de::Visitor<'de> for MultimapVisitor {
+                type Value = Multimap;
+
+                fn expecting(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+                    f.write_str("a map")
+                }
+
+                fn visit_map<A>(self, map: A) -> Result<Multimap, A::Error>
+                where
+                    A: serde::de::MapAccess<'de>,
+                {
+                    let mut access = InplaceMapAccess::MapAccess(map);
+                    deserialize_ordered_map(&mut access)
+                        .map(Multimap)
+                        .map_err(|error| match access {
+                            InplaceMapAccess::Error(error) => error,
+                            _ => A::Error::custom(error),
+                        })
+                }
+            }
+
+            deserializer.deserialize_map(MultimapVisitor)
+        }
+    }
+
+    make_deserializer!(
+        deserializer = r#"{
+        "accept": "text/html",
+        "set-cookie": "a=1",
+        "vary": ["accept"],
+        "set-cookie": "b=2",
+        "set-cookie": null
+    }"#
+    );
+    let Multimap(headers) =
+        <Multimap as serde::Deserialize<'_>>::deserialize(deserializer).unwrap();
+    assert_eq!(
+        headers,
+        [
+            ("accept".to_owned(), json!("text/html")),
+            ("set-cookie".to_owned(), json!("a=1")),
+            ("vary".to_owned(), json!(["accept"])),
+            ("set-cookie".to_owned(), json!("b=2")),
+            ("set-cookie".to_owned(), Value::Null),
+        ]
+    );
+
+    let cookies: Vec<_> = headers
+        .iter()
+        .filter(|(name, _)| name == "set-cookie")
+        .map(|(_, value)| value)
+        .collect();
+    assert_eq!(cookies, [&json!("a=1"), &json!("b=2"), &Value::Null]);
+}
+
+#[test]
+fn test_deserialize_borrowed_bytes() {
+    use dyn_serde::Deserializer;
+
+    #[derive(serde::Deserialize)]
+    struct Message<'a> {
+        #[serde(borrow)]
+        payload: &'a serde_bytes::Bytes,
+    }
+
+    // the bytes are borrowed from the input rather than copied, which fails
+    // unless the visitor is given them with `visit_borrowed_bytes`.
+    let input: &[u8] = b"\x01\x02\x03";
+    let deserializer =
+        serde::de::value::BorrowedBytesDeserializer::<serde::de::value::Error>::new(input);
+    let mut deserializer = <dyn Deserializer>::new(deserializer);
+    let bytes = <&serde_bytes::Bytes as serde::Deserialize>::deserialize(
+        &mut deserializer as &mut dyn Deserializer,
+    )
+    .unwrap();
+    assert_eq!(bytes.as_ptr(), input.as_ptr());
+    assert_eq!(&**bytes, input);
+
+    let json = r#"{"payload": "abc"}"#;
+    let mut deserializer = serde_json::Deserializer::from_str(json);
+    let mut deserializer = <dyn Deserializer>::new(&mut deserializer);
+    let deserializer = &mut deserializer as &mut dyn Deserializer;
+    let message = <Message as serde::Deserialize>::deserialize(deserializer).unwrap();
+    assert_eq!(&**message.payload, b"abc");
+    assert!(
+        json.as_bytes()
+            .as_ptr_range()
+            .contains(&message.payload.as_ptr())
+    );
+}
+
+#[test]
+fn test_null_like() {
+    use dyn_serde::Deserializer;
+    use dyn_serde::de::{InplaceDeserializer, NullLike};
+
+    #[derive(Debug, PartialEq, serde::Deserialize)]
+    enum Unit {
+        A,
+        B(i32),
+    }
+
+    #[derive(Debug, PartialEq, serde::Deserialize)]
+    struct Row {
+        count: Option<i32>,
+        name: String,
+        unit: Option<Unit>,
+        nested: Vec<Option<i32>>,
+    }
+
+    fn null_like<'de, T: serde::Deserialize<'de>>(json: &'de str) -> Result<T, String> {
+        let mut deserializer = serde_json::Deserializer::from_str(json);
+        let mut inplace = <dyn Deserializer>::new(&mut deserializer);
+        let mut deserializer = NullLike::new(&mut inplace, &["", "null", "N/A"]);
+        let result = T::deserialize(&mut deserializer as &mut dyn Deserializer);
+        result.map_err(|error| match inplace {
+            InplaceDeserializer::Error(error, _) => error.to_string(),
+            _ => error.to_string(),
+        })
+    }
+
+    assert_eq!(
+        null_like::<Row>(r#"{"count":"N/A","name":"N/A","unit":"","nested":[1,"null",null]}"#)
+            .unwrap(),
+        Row {
+            count: None,
+            name: "N/A".to_owned(),
+            unit: None,
+            nested: vec![Some(1), None, None],
+        }
+    );
+    assert_eq!(
+        null_like::<Row>(r#"{"count":7,"name":"","unit":"A","nested":[]}"#).unwrap(),
+        Row {
+            count: Some(7),
+            name: String::new(),
+            unit: Some(Unit::A),
+            nested: vec![],
+        }
+    );
+    assert_eq!(
+        null_like::<Option<Unit>>(r#"{"B":2}"#).unwrap(),
+        Some(Unit::B(2))
+    );
+
+    // other strings are still handed to the visitor of the option's value.
+    assert_eq!(
+        null_like::<Option<i32>>(r#""none""#).unwrap_err(),
+        "invalid type: string \"none\", expected i32 at line 1 column 6"
+    );
+}
+
+#[test]
+fn test_empty_string_as_none() {
+    use dyn_serde::Deserializer;
+    use dyn_serde::de::EmptyStringAsNone;
+
+    #[derive(Debug, PartialEq, serde::Deserialize)]
+    struct Form {
+        x: Option<String>,
+        y: Option<u32>,
+        name: String,
+    }
+
+    fn from<T: serde::de::DeserializeOwned>(json: &str) -> Result<T, String> {
+        let mut deserializer = serde_json::Deserializer::from_str(json);
+        let mut deserializer = <dyn Deserializer>::new(&mut deserializer);
+        let mut deserializer = EmptyStringAsNone::new(&mut deserializer);
+        T::deserialize(&mut deserializer as &mut dyn Deserializer).map_err(|e| e.to_string())
+    }
+
+    assert_eq!(
+        from::<Form>(r#"{"x":"","y":"","name":""}"#).unwrap(),
+        Form {
+            x: None,
+            y: None,
+            name: String::new(),
+        }
+    );
+    assert_eq!(
+        from::<Form>(r#"{"x":" ","y":7,"name":"a"}"#).unwrap(),
+        Form {
+            x: Some(" ".to_owned()),
+            y: Some(7),
+            name: "a".to_owned(),
+        }
+    );
+    // values which are not options pass through.
+    assert!(from::<u32>(r#""""#).is_err());
+    assert_eq!(
+        from::<Vec<Option<String>>>(r#"["", "a"]"#).unwrap(),
+        [None, Some("a".to_owned())]
+    );
+}
+
+#[cfg(feature = "serde_json")]
+#[test]
+fn test_map_deserializer() {
+    use serde::Deserialize as _;
+
+    use dyn_serde::Deserializer;
+    use dyn_serde::de::MapDeserializer;
+    use serde_json::Value;
+
+    #[derive(Debug, PartialEq, serde::Deserialize)]
+    struct Order {
+        customer: String,
+        items: Vec<Item>,
+        note: Option<String>,
+    }
+
+    #[derive(Debug, PartialEq, serde::Deserialize)]
+    struct Item {
+        sku: String,
+        count: u32,
+    }
+
+    fn uppercase(value: Value) -> Value {
+        match value {
+            Value::String(v) => Value::String(v.to_uppercase()),
+            Value::Array(v) => v.into_iter().map(uppercase).collect(),
+            Value::Object(v) => v.into_iter().map(|(k, v)| (k, uppercase(v))).collect(),
+            value => value,
+        }
+    }
+
+    let json = r#"{"customer": "alice", "items": [{"sku": "ab-1", "count": 2}], "note": "asap"}"#;
+    let mut deserializer = serde_json::Deserializer::from_str(json);
+    let mut deserializer = <dyn Deserializer>::new(&mut deserializer);
+    let mut deserializer = MapDeserializer::new(&mut deserializer, uppercase);
+    let order = Order::deserialize(&mut deserializer as &mut dyn Deserializer).unwrap();
+    assert_eq!(
+        order,
+        Order {
+            customer: "ALICE".into(),
+            items: vec![Item {
+                sku: "AB-1".into(),
+                count: 2,
+            }],
+            note: Some("ASAP".into()),
+        }
+    );
+
+    // the transformed value may be rejected, and the deserializer is spent.
+    let mut deserializer = serde_json::Deserializer::from_str("[1]");
+    let mut deserializer = <dyn Deserializer>::new(&mut deserializer);
+    let mut deserializer = MapDeserializer::new(&mut deserializer, |_| Value::Bool(true));
+    assert!(Vec::<u8>::deserialize(&mut deserializer as &mut dyn Deserializer).is_err());
+    assert_eq!(
+        deserializer.error().unwrap().to_string(),
+        "invalid type: boolean `true`, expected a sequence"
+    );
+    assert!(Vec::<u8>::deserialize(&mut deserializer as &mut dyn Deserializer).is_err());
+}
+
+#[cfg(feature = "serde_json")]
+#[test]
+fn test_unflatten_dotted() {
+    use std::collections::BTreeMap;
+
+    use dyn_serde::de::UnflattenDotted;
+    use dyn_serde::ser::FlattenDotted;
+    use dyn_serde::{Deserializer, Serialize, Serializer};
+
+    #[derive(Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+    struct Config {
+        name: String,
+        server: Server,
+        ports: Vec<u16>,
+        labels: BTreeMap<String, String>,
+        mode: Mode,
+        backup: Option<Server>,
+    }
+
+    #[derive(Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+    struct Server {
+        host: String,
+        tls: bool,
+    }
+
+    #[derive(Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+    enum Mode {
+        Fast { threads: u8 },
+    }
+
+    fn unflatten<T: serde::de::DeserializeOwned>(json: &str) -> Result<T, String> {
+        let mut deserializer = serde_json::Deserializer::from_str(json);
+        let mut deserializer = <dyn Deserializer>::new(&mut deserializer);
+        let mut deserializer = UnflattenDotted::new(&mut deserializer);
+        let result = T::deserialize(&mut deserializer as &mut dyn Deserializer);
+        result.map_err(|error| match deserializer.error() {
+            Some(error) => error.to_string(),
+            None => error.to_string(),
+        })
+    }
+
+    let config = Config {
+        name: "app".to_owned(),
+        server: Server {
+            host: "localhost".to_owned(),
+            tls: true,
+        },
+        ports: vec![80, 443],
+        labels: BTreeMap::from([("0".to_owned(), "zero".to_owned())]),
+        mode: Mode::Fast { threads: 4 },
+        backup: None,
+    };
+    let mut buf = Vec::new();
+    {
+        let mut serializer = serde_json::Serializer::new(&mut buf);
+        let mut serializer = <dyn Serializer>::new(&mut serializer);
+        let mut serializer = FlattenDotted::new(&mut serializer);
+        (&config as &dyn Serialize)
+            .dyn_serialize(&mut serializer)
+            .unwrap();
+    }
+    let json = String::from_utf8(buf).unwrap();
+    assert_eq!(
+        json,
+        r#"{"name":"app","server.host":"localhost","server.tls":true,"ports.0":80,"ports.1":443,"labels.0":"zero","mode.Fast.threads":4}"#
+    );
+    // a map keyed by its indices is read as a sequence.
+    assert_eq!(
+        unflatten::<Config>(&json).unwrap_err(),
+        "invalid type: sequence, expected a map"
+    );
+    let json = json.replace(r#""labels.0":"zero""#, r#""labels.a":"zero""#);
+    assert_eq!(
+        unflatten::<Config>(&json).unwrap(),
+        Config {
+            labels: BTreeMap::from([("a".to_owned(), "zero".to_owned())]),
+            ..config
+        }
+    );
+
+    assert_eq!(
+        unflatten::<serde_json::Value>(r#"{"a.b": 1, "a.1": 2, "c": [3]}"#).unwrap(),
+        serde_json::json!({"a": {"b": 1, "1": 2}, "c": [3]})
+    );
+    assert_eq!(
+        unflatten::<serde_json::Value>(r#"{"a.b": 1, "a": 2}"#).unwrap_err(),
+        "key `a` is both a value and a prefix of another key"
+    );
+    assert_eq!(
+        unflatten::<serde_json::Value>(r#"{"a.b.c": 1, "a.b.c.d": 2}"#).unwrap_err(),
+        "key `a.b.c` is both a value and a prefix of another key"
+    );
+}
+
+#[cfg(feature = "serde_json")]
+#[test]
+fn test_overlay() {
+    use dyn_serde::Deserializer;
+    use dyn_serde::de::{InplaceDeserializer, Overlay};
+
+    #[derive(Debug, PartialEq, serde::Deserialize)]
+    struct Pair {
+        a: i32,
+        b: i32,
+    }
+
+    fn overlay<T: serde::de::DeserializeOwned>(base: &str, overrides: &str) -> Result<T, String> {
+        let mut base = serde_json::Deserializer::from_str(base);
+        let mut base = <dyn Deserializer>::new(&mut base);
+        let mut overrides = serde_json::Deserializer::from_str(overrides);
+        let mut overrides = <dyn Deserializer>::new(&mut overrides);
+        let mut deserializer = Overlay::new(&mut base, &mut overrides);
+        let result = T::deserialize(&mut deserializer as &mut dyn Deserializer);
+        let error = deserializer.error().map(ToString::to_string);
+        result.map_err(|_| match (base, overrides) {
+            (InplaceDeserializer::Error(error, _), _)
+            | (_, InplaceDeserializer::Error(error, _)) => error.to_string(),
+            _ => error.unwrap(),
+        })
+    }
+
+    assert_eq!(
+        overlay::<Pair>(r#"{"a": 1, "b": 2}"#, r#"{"b": 3}"#),
+        Ok(Pair { a: 1, b: 3 })
+    );
+
+    // maps are merged at every depth, other values are replaced.
+    assert_eq!(
+        overlay::<serde_json::Value>(
+            r#"{"server": {"host": "localhost", "port": 80}, "ports": [1, 2], "debug": true}"#,
+            r#"{"server": {"port": 8080}, "ports": [3], "debug": null, "name": "app"}"#,
+        ),
+        Ok(serde_json::json!({
+            "server": {"host": "localhost", "port": 8080},
+            "ports": [3],
+            "debug": null,
+            "name": "app",
+        }))
+    );
+    assert_eq!(
+        overlay::<serde_json::Value>(r#"{"a": 1}"#, "2"),
+        Ok(serde_json::json!(2))
+    );
+
+    // the errors of an input, and of the merged value.
+    assert_eq!(
+        overlay::<Pair>(r#"{"a": 1, "b": 2}"#, "{").unwrap_err(),
+        "EOF while parsing an object at line 1 column 1"
+    );
+    assert_eq!(
+        overlay::<Pair>(r#"{"a": 1}"#, r#"{"b": "3"}"#).unwrap_err(),
+        "invalid type: string \"3\", expected i32"
+    );
+}
+
+#[cfg(feature = "serde_json")]
+#[test]
+fn test_versioned() {
+    use dyn_serde::Deserializer;
+    use dyn_serde::de::Versioned;
+    use serde::Deserialize as _;
+
+    /// The current shape of a user, whose name has been split in version 2
+    /// and which has gained a role in version 3.
+    #[derive(Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+    struct User {
+        first: String,
+        last: String,
+        role: String,
+    }
+
+    fn deserialize(json: &str) -> (Result<User, String>, Option<u32>) {
+        let mut deserializer = serde_json::Deserializer::from_str(json);
+        let mut deserializer = <dyn Deserializer>::new(&mut deserializer);
+        let mut deserializer = Versioned::new(&mut deserializer, 3);
+        deserializer
+            .migration(1, |mut user| {
+                let name = user["name"].take();
+                let (first, last) = name.as_str().unwrap().split_once(' ').unwrap();
+                serde_json::json!({"first": first, "last": last})
+            })
+            .migration(2, |mut user| {
+                user["role"] = "member".into();
+                user
+            });
+        let result = User::deserialize(&mut deserializer as &mut dyn Deserializer);
+        let result = result.map_err(|_| deserializer.error().unwrap().to_string());
+        (result, deserializer.version())
+    }
+
+    let ferris = User {
+        first: "Ferris".into(),
+        last: "Crab".into(),
+        role: "member".into(),
+    };
+
+    // a v1 payload goes through both migrations.
+    let v1 = r#"{"v": 1, "data": {"name": "Ferris Crab"}}"#;
+    assert_eq!(deserialize(v1), (Ok(ferris), Some(1)));
+
+    // the current version is read as is, e.g. as written by `ser::Versioned`.
+    let admin = User {
+        first: "Ada".into(),
+        last: "Lovelace".into(),
+        role: "admin".into(),
+    };
+    let v3 = serde_json::to_string(&dyn_serde::ser::Versioned::new(3, &admin)).unwrap();
+    assert_eq!(deserialize(&v3), (Ok(admin), Some(3)));
+
+    // versions which cannot be upgraded, and malformed envelopes.
+    assert_eq!(
+        deserialize(r#"{"v": 4, "data": {}}"#),
+        (
+            Err("unsupported version 4, expected at most 3".to_owned()),
+            Some(4)
+        )
+    );
+    assert_eq!(
+        deserialize(r#"{"v": 0, "data": {}}"#).0,
+        Err("no migration from version 0 to version 1".to_owned())
+    );
+    assert_eq!(
+        deserialize(r#"{"data": {}}"#),
+        (Err("missing field `v`".to_owned()), None)
+    );
+    assert_eq!(
+        deserialize(r#"[1, {}]"#).0,
+        Err("invalid type: sequence, expected a versioned envelope".to_owned())
+    );
+    assert_eq!(
+        deserialize(r#"{"v": 3, "data": {"first": "Ada"}}"#).0,
+        Err("missing field `last`".to_owned())
+    );
+}
+
+#[test]
+fn test_with_expecting() {
+    use dyn_serde::Deserializer;
+    use dyn_serde::de::{InplaceDeserializer, InplaceVisitor, WithExpecting};
+
+    struct PortVisitor;
+
+    impl serde::de::Visitor<'_> for PortVisitor {
+        type Value = u16;
+
+        fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+            formatter.write_str("a u16")
+        }
+
+        fn visit_u64<E: serde::de::Error>(self, v: u64) -> Result<u16, E> {
+            u16::try_from(v).map_err(|_| E::custom("out of range"))
+        }
+    }
+
+    fn deserialize(json: &str) -> Result<u16, String> {
+        let mut deserializer = serde_json::Deserializer::from_str(json);
+        let mut deserializer = <dyn Deserializer>::new(&mut deserializer);
+        let mut inplace = InplaceVisitor::Visitor(PortVisitor);
+        let mut visitor = WithExpecting::new(&mut inplace, "a port number");
+        assert_eq!(
+            format!("{visitor:?}"),
+            r#"WithExpecting { expecting: "a port number", .. }"#
+        );
+        match deserializer.dyn_deserialize_u16(&mut visitor) {
+            Ok(()) => match inplace {
+                InplaceVisitor::Value(port) => Ok(port),
+                _ => panic!("expected a value"),
+            },
+            Err(_) => match deserializer {
+                InplaceDeserializer::Error(error, _) => Err(error.to_string()),
+                _ => panic!("expected an error"),
+            },
+        }
+    }
+
+    assert_eq!(deserialize("8080"), Ok(8080));
+    assert_eq!(
+        deserialize(r#""http""#),
+        Err(r#"invalid type: string "http", expected a port number at line 1 column 6"#.into())
+    );
+    assert_eq!(
+        deserialize("[80]"),
+        Err("invalid type: sequence, expected a port number at line 1 column 0".into())
+    );
+    // the errors of the wrapped visitor are left unchanged.
+    assert_eq!(
+        deserialize("65536"),
+        Err("out of range at line 1 column 5".into())
+    );
+}
+
+#[test]
+fn test_path_tracking() {
+    use std::collections::BTreeMap;
+
+    use serde::Deserialize as _;
+
+    use dyn_serde::Deserializer;
+    use dyn_serde::de::{InplaceDeserializer, PathTracking};
+
+    #[derive(Debug, serde::Deserialize)]
+    #[allow(dead_code)]
+    struct Root {
+        a: A,
+    }
+
+    #[derive(Debug, serde::Deserialize)]
+    #[allow(dead_code)]
+    struct A {
+        #[serde(default)]
+        g: Vec<G>,
+        #[serde(default)]
+        m: BTreeMap<String, Shape>,
+    }
+
+    #[derive(Debug, serde::Deserialize)]
+    #[allow(dead_code)]
+    struct G {
+        x: u8,
+    }
+
+    #[derive(Debug, serde::Deserialize)]
+    #[allow(dead_code)]
+    enum Shape {
+        Circle { radius: f64 },
+        Square(f64),
+    }
+
+    fn deserialize(json: &str) -> (Option<String>, String) {
+        let mut deserializer = serde_json::Deserializer::from_str(json);
+        let mut deserializer = <dyn Deserializer>::new(&mut deserializer);
+        let mut tracking = PathTracking::new(&mut deserializer);
+        let result = Root::deserialize(&mut tracking as &mut dyn Deserializer);
+        let path = tracking.path().map(str::to_owned);
+        match (result, deserializer) {
+            (Ok(_), _) => (path, String::new()),
+            (Err(_), InplaceDeserializer::Error(error, _)) => (path, error.to_string()),
+            (Err(error), _) => panic!("unexpected error: {error}"),
+        }
+    }
+
+    // an error raised by the deserializer.
+    let (path, error) = deserialize(r#"{"a": {"g": [{"x": 1}, {"x": 2}, {"x": 3}, {"x": "4"}]}}"#);
+    assert_eq!(path.as_deref(), Some("a.g[3].x"));
+    assert!(error.starts_with(r#"invalid type: string "4", expected u8"#));
+
+    // an error raised by the visitor.
+    let (path, error) = deserialize(r#"{"a": {"g": [{"x": 1}, {}]}}"#);
+    assert_eq!(path.as_deref(), Some("a.g[1]"));
+    assert!(error.starts_with("missing field `x`"));
+
+    let (path, _) = deserialize(r#"{"a": {"g": [{"x": 256}]}}"#);
+    assert_eq!(path.as_deref(), Some("a.g[0].x"));
+
+    // keys of maps and variants of enums.
+    let json = r#"{"a": {"m": {"one": {"Square": 1.0}, "two": {"Circle": {"radius": null}}}}}"#;
+    let (path, _) = deserialize(json);
+    assert_eq!(path.as_deref(), Some("a.m.two.Circle.radius"));
+
+    let (path, _) = deserialize(r#"{"a": {"m": {"one": {"Triangle": 1.0}}}}"#);
+    assert_eq!(path.as_deref(), Some("a.m.one"));
+
+    // the value itself, past the end of the sequence.
+    let (path, _) = deserialize("[]");
+    assert_eq!(path.as_deref(), Some("."));
+
+    let (path, error) = deserialize(r#"{"a": {"g": [{"x": 1}], "m": {"k": {"Square": 2}}}}"#);
+    assert_eq!((path, error), (None, String::new()));
+}
+
+#[test]
+fn test_from_record() {
+    use dyn_serde::Deserializer;
+    use dyn_serde::de::from_record;
+
+    #[derive(Debug, PartialEq, serde::Deserialize)]
+    enum Kind {
+        City,
+        Town,
+    }
+
+    #[derive(Debug, PartialEq, serde::Deserialize)]
+    struct Place {
+        name: String,
+        kind: Kind,
+        population: u32,
+        capital: bool,
+        area: Option<f64>,
+    }
+
+    fn from<'de, T: serde::Deserialize<'de>>(record: &[&'de str]) -> Result<T, String> {
+        let header = ["name", "kind", "population", "capital", "area", "country"];
+        let mut deserializer = from_record(&header, record);
+        let result = T::deserialize(&mut deserializer as &mut dyn Deserializer);
+        result.map_err(|error| match deserializer.error() {
+            Some(error) => error.to_string(),
+            None => error.to_string(),
+        })
+    }
+
+    assert_eq!(
+        from::<Place>(&["Lyon", "City", "522250", "false", "47.87", "France"]).unwrap(),
+        Place {
+            name: "Lyon".to_owned(),
+            kind: Kind::City,
+            population: 522_250,
+            capital: false,
+            area: Some(47.87),
+        }
+    );
+    assert_eq!(
+        from::<Place>(&["Vaduz", "Town", "5696", "true", "", "Liechtenstein"])
+            .unwrap()
+            .area,
+        None
+    );
+    assert_eq!(
+        from::<Vec<(String, String)>>(&["a", "b", "c", "d", "e", "f"]).unwrap_err(),
+        "invalid type: map, expected a sequence"
+    );
+    assert_eq!(
+        from::<Place>(&["Lyon", "City", "many", "false", "", ""]).unwrap_err(),
+        "invalid type: string \"many\", expected u32"
+    );
+    assert_eq!(
+        from::<Place>(&["Lyon", "City"]).unwrap_err(),
+        "invalid length 2, expected as many values as columns in the header"
+    );
+}
+
+#[test]
+fn test_require_fields() {
+    use serde::Deserialize as _;
+
+    use dyn_serde::Deserializer;
+    use dyn_serde::de::{InplaceDeserializer, RequireFields};
+
+    #[derive(Debug, serde::Deserialize)]
+    #[allow(dead_code)]
+    struct Config {
+        host: Option<String>,
+        port: Option<u16>,
+        #[serde(rename = "user-name")]
+        user: Option<String>,
+        nested: Option<Nested>,
+    }
+
+    #[derive(Debug, serde::Deserialize)]
+    #[allow(dead_code)]
+    struct Nested {
+        host: Option<String>,
+    }
+
+    fn deserialize(json: &str) -> Result<Config, String> {
+        let required = ["host", "port", "user-name"];
+        let mut deserializer = serde_json::Deserializer::from_str(json);
+        let mut deserializer = <dyn Deserializer>::new(&mut deserializer);
+        let mut require = RequireFields::new(&mut deserializer, &required);
+        let result = Config::deserialize(&mut require as &mut dyn Deserializer);
+        match (result, deserializer) {
+            (Ok(config), _) => Ok(config),
+            (Err(_), InplaceDeserializer::Error(error, _)) => Err(error.to_string()),
+            (Err(error), _) => panic!("unexpected error: {error}"),
+        }
+    }
+
+    let config = deserialize(r#"{"host": "localhost", "port": 80, "user-name": "root"}"#);
+    assert_eq!(config.unwrap().port, Some(80));
+
+    // all of the missing fields are reported, not only the first one.
+    let error = deserialize(r#"{"port": 80}"#).unwrap_err();
+    assert!(error.starts_with("missing fields `host`, `user-name`"));
+
+    let error = deserialize(r#"{"host": null, "user-name": null}"#).unwrap_err();
+    assert!(error.starts_with("missing field `port`"));
+
+    // the fields of nested structs do not count.
+    let error = deserialize(r#"{"nested": {"host": "localhost", "port": 80}}"#).unwrap_err();
+    assert!(error.starts_with("missing fields `host`, `port`, `user-name`"));
+
+    // the value may be behind `Some`.
+    let mut deserializer = serde_json::Deserializer::from_str("{}");
+    let mut deserializer = <dyn Deserializer>::new(&mut deserializer);
+    let mut require = RequireFields::new(&mut deserializer, &["host"]);
+    assert!(Option::<Config>::deserialize(&mut require as &mut dyn Deserializer).is_err());
+}
+
+#[test]
+fn test_with_human_readable() {
+    use dyn_serde::Deserializer;
+    use dyn_serde::de::WithHumanReadable;
+
+    /// Deserializes a sequence, then records whether the wrapper and the
+    /// in-place deserializer are human-readable once it has finished.
+    #[derive(Debug, PartialEq)]
+    struct Probe(Vec<u8>, bool, bool);
+
+    impl<'de> serde::Deserialize<'de> for Probe {
+        fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+            let mut deserializer = <dyn Deserializer>::new(deserializer);
+            let mut fixed = WithHumanReadable::new(&mut deserializer);
+            let values = Vec::deserialize(&mut fixed as &mut dyn Deserializer).unwrap();
+            let human_readable = fixed.dyn_is_human_readable();
+            Ok(Probe(
+                values,
+                human_readable,
+                deserializer.dyn_is_human_readable(),
+            ))
+        }
+    }
+
+    // a binary format.
+    let probe: Probe = ciborium::from_reader(&b"\x82\x01\x02"[..]).unwrap();
+    assert_eq!(probe, Probe(vec![1, 2], false, false));
+
+    // a text format.
+    let probe: Probe = serde_json::from_str("[1, 2]").unwrap();
+    assert_eq!(probe, Probe(vec![1, 2], true, true));
+
+    // an explicit answer.
+    let mut deserializer = serde_json::Deserializer::from_str("[]");
+    let mut deserializer = <dyn Deserializer>::new(&mut deserializer);
+    let deserializer = WithHumanReadable::fixed(&mut deserializer, false);
+    assert!(!deserializer.dyn_is_human_readable());
+}
+
+#[test]
+fn test_inplace_is_human_readable() {
+    use dyn_serde::Deserializer;
+    use dyn_serde::de::InplaceDeserializerState;
+
+    /// Deserializes a `u8`, then records the state of the in-place
+    /// deserializer and whether it is human-readable once it has finished.
+    #[derive(Debug, PartialEq)]
+    struct Probe(InplaceDeserializerState, bool);
+
+    impl<'de> serde::Deserialize<'de> for Probe {
+        fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+            let mut deserializer = <dyn Deserializer>::new(deserializer);
+            let _ = u8::deserialize(deserializer.as_mut());
+            Ok(Probe(
+                deserializer.state(),
+                deserializer.dyn_is_human_readable(),
+            ))
+        }
+    }
+
+    // a binary format keeps its answer once it has finished.
+    let probe: Probe = ciborium::from_reader(&b"\x01"[..]).unwrap();
+    assert_eq!(probe, Probe(InplaceDeserializerState::Value, false));
+    let probe: Probe = ciborium::from_reader(&b"\x61a"[..]).unwrap();
+    assert_eq!(probe, Probe(InplaceDeserializerState::Error, false));
+
+    // a text format.
+    let probe: Probe = serde_json::from_str("1").unwrap();
+    assert_eq!(probe, Probe(InplaceDeserializerState::Value, true));
+    let probe: Probe = serde_json::from_str(r#""a""#).unwrap();
+    assert_eq!(probe, Probe(InplaceDeserializerState::Error, true));
+}
+
+#[test]
+fn test_deserialize_seq_into_vec() {
+    use serde::de::Error as _;
+    use serde::de::value::{Error, SeqDeserializer};
+
+    use dyn_serde::Deserializer;
+    use dyn_serde::de::{InplaceSeqAccess, deserialize_seq_into_vec};
+
+    /// The elements, the capacity of their vector and the size hint of the
+    /// in-place access.
+    #[derive(Debug, PartialEq)]
+    struct Collected(Vec<u32>, usize, Option<usize>);
+
+    impl<'de> serde::Deserialize<'de> for Collected {
+        fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+            struct CollectedVisitor;
+
+            impl<'de> serde::de::Visitor<'de> for CollectedVisitor {
+                type Value = Collected;
+
+                fn expecting(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+                    f.write_str("a sequence")
+                }
+
+                fn visit_seq<A>(self, seq: A) -> Result<Collected, A::Error>
+                where
+                    A: serde::de::SeqAccess<'de>,
+                {
+                    use dyn_serde::de::SeqAccess as _;
+
+                    let mut access = InplaceSeqAccess::SeqAccess(seq);
+                    let hint = access.dyn_size_hint();
+                    match deserialize_seq_into_vec::<u32>(&mut access) {
+                        Ok(elements) => Ok(Collected(elements.clone(), elements.capacity(), hint)),
+                        Err(error) => Err(match access {
+                            InplaceSeqAccess::Error(error) => error,
+                            _ => A::Error::custom(error),
+                        }),
+                    }
+                }
+            }
+
+            deserializer.deserialize_seq(CollectedVisitor)
+        }
+    }
+
+    // the hint of the format is forwarded, and reserved exactly.
+    let deserializer = SeqDeserializer::<_, Error>::new(0..1000u32);
+    let mut deserializer = <dyn Deserializer>::new(deserializer);
+    let collected =
+        <Collected as serde::Deserialize>::deserialize(&mut deserializer as &mut dyn Deserializer)
+            .unwrap();
+    assert_eq!(collected, Collected((0..1000).collect(), 1000, Some(1000)));
+
+    // without a hint, the vector grows as usual.
+    make_deserializer!(deserializer = "[1, 2, 3]");
+    let collected = <Collected as serde::Deserialize>::deserialize(deserializer).unwrap();
+    assert_eq!(collected.0, [1, 2, 3]);
+    assert_eq!(collected.2, None);
+
+    assert_eq!(
+        serde_json::from_str::<Collected>(r#"[1, "2"]"#)
+            .unwrap_err()
+            .to_string(),
+        "invalid type: string \"2\", expected u32 at line 1 column 7"
+    );
+}
+
+#[test]
+#[cfg(feature = "serde_json")]
+fn test_adjacently_tagged() {
+    use dyn_serde::de::{AdjacentlyTagged, InplaceDeserializer};
+    use dyn_serde::{Deserializer, Serialize, Serializer};
+
+    #[derive(Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+    enum Shape {
+        Empty,
+        Circle(f64),
+        Rect(u32, u32),
+        Group { name: String, shapes: Vec<Shape> },
+    }
+
+    fn untag<'de, T: serde::Deserialize<'de>>(json: &'de str) -> Result<T, String> {
+        let mut deserializer = serde_json::Deserializer::from_str(json);
+        let mut inplace = <dyn Deserializer>::new(&mut deserializer);
+        let mut deserializer = AdjacentlyTagged::new(&mut inplace, "t", "c");
+        let result = T::deserialize(&mut deserializer as &mut dyn Deserializer);
+        result.map_err(|error| match inplace {
+            InplaceDeserializer::Error(error, _) => error.to_string(),
+            _ => error.to_string(),
+        })
+    }
+
+    fn tag(value: &dyn Serialize) -> String {
+        let mut buf = Vec::new();
+        let mut serializer = serde_json::Serializer::new(&mut buf);
+        let mut serializer = <dyn Serializer>::new(&mut serializer);
+        let mut serializer = dyn_serde::ser::AdjacentlyTagged::new(&mut serializer, "t", "c");
+        value.dyn_serialize(&mut serializer).unwrap();
+        String::from_utf8(buf).unwrap()
+    }
+
+    // every kind of variant round-trips, at every depth.
+    let shapes = vec![
+        Shape::Empty,
+        Shape::Circle(1.5),
+        Shape::Rect(2, 3),
+        Shape::Group {
+            name: "outer".into(),
+            shapes: vec![
+                Shape::Rect(1, 2),
+                Shape::Group {
+                    name: "inner".into(),
+                    shapes: vec![Shape::Empty, Shape::Circle(0.5)],
+                },
+            ],
+        },
+    ];
+    assert_eq!(untag::<Vec<Shape>>(&tag(&shapes)).unwrap(), shapes);
+
+    // the content may precede the tag, and other entries are skipped.
+    assert_eq!(
+        untag::<Shape>(r#"{"c":{"shapes":[{"c":[4,5],"t":"Rect"}],"name":"g"},"t":"Group"}"#)
+            .unwrap(),
+        Shape::Group {
+            name: "g".into(),
+            shapes: vec![Shape::Rect(4, 5)],
+        }
+    );
+    assert_eq!(
+        untag::<Shape>(r#"{"id":7,"t":"Circle","c":2.5,"x":null}"#).unwrap(),
+        Shape::Circle(2.5)
+    );
+    assert_eq!(
+        untag::<Shape>(r#"{"t":"Empty","c":null}"#).unwrap(),
+        Shape::Empty
+    );
+
+    // values which are not enums are left unchanged.
+    assert_eq!(
+        untag::<(u8, Option<Shape>)>(r#"[1, null]"#).unwrap(),
+        (1, None)
+    );
+
+    assert_eq!(
+        untag::<Shape>(r#"{"c":1.5}"#),
+        Err("missing field `t` at line 1 column 9".to_owned())
+    );
+    assert_eq!(
+        untag::<Shape>(r#"{"t":"Circle"}"#),
+        Err("missing field `c` at line 1 column 14".to_owned())
+    );
+    assert_eq!(
+        untag::<Shape>(r#"{"t":"Square"}"#),
+        Err(
+            "unknown variant `Square`, expected one of `Empty`, `Circle`, `Rect`, `Group` \
+             at line 1 column 14"
+                .to_owned()
+        )
+    );
+}
+
+#[test]
+#[cfg(feature = "serde_json")]
+fn test_cached() {
+    use dyn_serde::Deserializer;
+    use dyn_serde::de::Cached;
+
+    #[derive(Debug, PartialEq, serde::Deserialize)]
+    struct Summary {
+        name: String,
+    }
+
+    #[derive(Debug, PartialEq, serde::Deserialize)]
+    struct Detail<'a> {
+        name: &'a str,
+        tags: Vec<&'a str>,
+        size: Option<u32>,
+    }
+
+    let mut deserializer =
+        serde_json::Deserializer::from_str(r#"{"name": "crate", "tags": ["a", "b"], "size": 3}"#);
+    let mut deserializer = <dyn Deserializer>::new(&mut deserializer);
+    let cached = Cached::new(&mut deserializer).unwrap();
+
+    // one parse, several types, with strings borrowed from the cache.
+    assert_eq!(
+        cached.deserialize::<Summary>().unwrap(),
+        Summary {
+            name: "crate".into()
+        }
+    );
+    assert_eq!(
+        cached.deserialize::<Detail>().unwrap(),
+        Detail {
+            name: "crate",
+            tags: vec!["a", "b"],
+            size: Some(3),
+        }
+    );
+    assert_eq!(cached.value()["size"], 3);
+
+    // the deserializer can be driven directly, any number of times.
+    for _ in 0..2 {
+        let mut deserializer = cached.deserializer();
+        let summary = <Summary as serde::Deserialize>::deserialize(
+            &mut deserializer as &mut dyn Deserializer,
+        )
+        .unwrap();
+        assert_eq!(summary.name, "crate");
+    }
+
+    assert_eq!(
+        cached.deserialize::<(u8,)>().unwrap_err().to_string(),
+        "invalid type: map, expected a tuple of size 1"
+    );
+}
+
+#[test]
+fn test_checked_numbers() {
+    use dyn_serde::Deserializer;
+    use dyn_serde::de::{CheckedNumbers, InplaceDeserializer};
+    use std::collections::BTreeMap;
+
+    #[derive(Debug, PartialEq, serde::Deserialize)]
+    struct Config {
+        retries: u8,
+        offset: Option<i16>,
+        limits: BTreeMap<u8, i128>,
+    }
+
+    fn checked<'de, T: serde::Deserialize<'de>>(json: &'de str) -> Result<T, String> {
+        let mut deserializer = serde_json::Deserializer::from_str(json);
+        let mut inplace = <dyn Deserializer>::new(&mut deserializer);
+        let mut deserializer = CheckedNumbers::new(&mut inplace);
+        let result = T::deserialize(&mut deserializer as &mut dyn Deserializer);
+        result.map_err(|error| match inplace {
+            InplaceDeserializer::Error(error, _) => error.to_string(),
+            _ => error.to_string(),
+        })
+    }
+
+    // values in range, up to the bounds, are unchanged.
+    assert_eq!(
+        checked::<Config>(r#"{"retries": 255, "offset": -32768, "limits": {"0": -1}}"#).unwrap(),
+        Config {
+            retries: 255,
+            offset: Some(-32768),
+            limits: BTreeMap::from([(0, -1)]),
+        }
+    );
+
+    // every integer is checked, at every depth.
+    assert_eq!(
+        checked::<Config>(r#"{"retries": 256}"#).unwrap_err(),
+        "invalid value: integer `256`, expected a u8 between 0 and 255 at line 1 column 15"
+    );
+    assert_eq!(
+        checked::<Config>(r#"{"retries": -1}"#).unwrap_err(),
+        "invalid value: integer `-1`, expected a u8 between 0 and 255 at line 1 column 14"
+    );
+    assert_eq!(
+        checked::<Config>(r#"{"retries": 1, "offset": 40000}"#).unwrap_err(),
+        "invalid value: integer `40000`, expected an i16 between -32768 and 32767 \
+         at line 1 column 30"
+    );
+    assert_eq!(
+        checked::<Config>(r#"{"retries": 1, "limits": {"300": 0}}"#).unwrap_err(),
+        "invalid value: integer `300`, expected a u8 between 0 and 255 at line 1 column 30"
+    );
+    assert_eq!(checked::<u64>("18446744073709551615").unwrap(), u64::MAX);
+    assert_eq!(
+        checked::<i64>("9223372036854775808").unwrap_err(),
+        "invalid value: integer `9223372036854775808`, expected an i64 between \
+         -9223372036854775808 and 9223372036854775807 at line 1 column 19"
+    );
+
+    // other values are left to the visitor.
+    assert_eq!(
+        checked::<u8>("1.5").unwrap_err(),
+        "invalid type: floating point `1.5`, expected u8 at line 1 column 3"
+    );
+    assert_eq!(checked::<f32>("300").unwrap(), 300.0);
+}
+
+#[test]
+#[cfg(feature = "time")]
+fn test_timestamp_format() {
+    use serde::de::DeserializeSeed as _;
+    use time::format_description::well_known::{Rfc2822, Rfc3339};
+    use time::macros::datetime;
+
+    use dyn_serde::de::TimestampFormat;
+    use dyn_serde::ser;
+
+    let expected = datetime!(2024-03-01 12:30:00 +01:00);
+
+    make_deserializer!(deserializer = r#""2024-03-01T12:30:00+01:00""#);
+    let datetime = TimestampFormat::new(&Rfc3339)
+        .deserialize(deserializer)
+        .unwrap();
+    assert_eq!(datetime, expected);
+
+    // transcoding from one format to another.
+    make_deserializer!(deserializer = r#""Fri, 01 Mar 2024 12:30:00 +0100""#);
+    let datetime = TimestampFormat::new(&Rfc2822)
+        .deserialize(deserializer)
+        .unwrap();
+    let json = serde_json::to_string(&ser::TimestampFormat::new(&datetime, &Rfc3339)).unwrap();
+    assert_eq!(json, r#""2024-03-01T12:30:00+01:00""#);
+
+    // a format description parsed at runtime.
+    let format = time::format_description::parse_borrowed::<2>(
+        "[day]/[month]/[year] [hour]:[minute] [offset_hour sign:mandatory]:[offset_minute]",
+    )
+    .unwrap();
+    let json = serde_json::to_string(&ser::TimestampFormat::new(&expected, &format[..])).unwrap();
+    assert_eq!(json, r#""01/03/2024 12:30 +01:00""#);
+    make_deserializer!(deserializer = r#""01/03/2024 12:30 +01:00""#);
+    let datetime = TimestampFormat::new(&format[..])
+        .deserialize(deserializer)
+        .unwrap();
+    assert_eq!(datetime, expected);
+
+    let mut deserializer = serde_json::Deserializer::from_str(r#""yesterday""#);
+    let error = TimestampFormat::new(&Rfc3339)
+        .deserialize(&mut deserializer)
+        .unwrap_err();
+    assert!(
+        error
+            .to_string()
+            .starts_with("invalid timestamp `yesterday`: ")
+    );
+}
+
+#[test]
+#[cfg(feature = "std")]
+fn test_frame_reader() {
+    use dyn_serde::de::FrameReader;
+    use dyn_serde::ser::LengthPrefixed;
+    use dyn_serde::{Deserializer, Serialize, Serializer};
+
+    #[derive(Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+    struct Message {
+        id: u32,
+        body: String,
+    }
+
+    let messages = [
+        Message {
+            id: 1,
+            body: "hello".to_owned(),
+        },
+        Message {
+            id: 2,
+            body: String::new(),
+        },
+    ];
+    let mut frames = LengthPrefixed::new(Vec::new());
+    for message in &messages {
+        let mut serializer = serde_json::Serializer::new(&mut frames);
+        let mut serializer = <dyn Serializer>::new(&mut serializer);
+        (message as &dyn Serialize)
+            .dyn_serialize(&mut serializer)
+            .unwrap();
+        frames.end_frame().unwrap();
+    }
+    let stream = frames.finish().unwrap();
+
+    let mut reader = FrameReader::new(&stream[..]);
+    let mut read = Vec::new();
+    while let Some(frame) = reader.next_frame().unwrap() {
+        let mut deserializer = serde_json::Deserializer::from_slice(frame);
+        let mut deserializer = <dyn Deserializer>::new(&mut deserializer);
+        let deserializer = &mut deserializer as &mut dyn Deserializer;
+        read.push(<Message as serde::Deserialize>::deserialize(deserializer).unwrap());
+    }
+    assert_eq!(read, messages);
+    assert!(reader.next_frame().unwrap().is_none());
+
+    // a frame is skipped by reading the next one.
+    let mut reader = FrameReader::new(&stream[..]);
+    reader.next_frame().unwrap();
+    let frame = reader.next_frame().unwrap().unwrap();
+    assert_eq!(frame, br#"{"id":2,"body":""}"#);
+
+    // the stream ends in the middle of the length, then of the bytes.
+    for stream in [&stream[..2], &stream[..10]] {
+        let error = FrameReader::new(stream).next_frame().unwrap_err();
+        assert_eq!(error.kind(), std::io::ErrorKind::UnexpectedEof);
+        assert_eq!(
+            error.to_string(),
+            "the stream ends in the middle of a frame"
+        );
+    }
+}
+
+#[test]
+#[cfg(feature = "std")]
+fn test_rename_keys() {
+    use dyn_serde::Deserializer;
+    use dyn_serde::de::{InplaceDeserializer, RenameKeys};
+    use std::collections::{BTreeMap, HashMap};
+
+    #[derive(Debug, PartialEq, serde::Deserialize)]
+    #[serde(deny_unknown_fields)]
+    struct Inner {
+        new_name: i32,
+    }
+
+    #[derive(Debug, PartialEq, serde::Deserialize)]
+    #[serde(deny_unknown_fields)]
+    struct Outer {
+        new_name: i32,
+        inner: Option<Inner>,
+        map: Option<BTreeMap<String, i32>>,
+    }
+
+    fn rename<'de, T: serde::Deserialize<'de>>(json: &'de str) -> Result<T, String> {
+        let renames = HashMap::from([("old_name", "new_name"), ("legacy", "inner")]);
+        let mut deserializer = serde_json::Deserializer::from_str(json);
+        let mut inplace = <dyn Deserializer>::new(&mut deserializer);
+        let mut deserializer = RenameKeys::new(&mut inplace, renames);
+        let result = T::deserialize(&mut deserializer as &mut dyn Deserializer);
+        result.map_err(|error| match inplace {
+            InplaceDeserializer::Error(error, _) => error.to_string(),
+            _ => error.to_string(),
+        })
+    }
+
+    assert_eq!(
+        rename::<Inner>(r#"{"old_name": 1}"#).unwrap(),
+        Inner { new_name: 1 }
+    );
+    // new names are read as they are.
+    assert_eq!(
+        rename::<Inner>(r#"{"new_name": 1}"#).unwrap(),
+        Inner { new_name: 1 }
+    );
+
+    // keys are renamed at every depth, but only those of structs.
+    assert_eq!(
+        rename::<Outer>(r#"{"old_name": 1, "legacy": {"old_name": 2}, "map": {"old_name": 3}}"#)
+            .unwrap(),
+        Outer {
+            new_name: 1,
+            inner: Some(Inner { new_name: 2 }),
+            map: Some(BTreeMap::from([("old_name".to_owned(), 3)])),
+        }
+    );
+
+    assert_eq!(
+        rename::<Inner>(r#"{"old_name": 1, "new_name": 2}"#).unwrap_err(),
+        "duplicate field `new_name` at line 1 column 26"
+    );
+}
+
+#[test]
+#[cfg(feature = "std")]
+fn test_field_timings() {
+    use std::collections::BTreeMap;
+    use std::thread;
+    use std::time::Duration;
+
+    use dyn_serde::Deserializer;
+    use dyn_serde::de::FieldTimings;
+
+    const DELAY: Duration = Duration::from_millis(20);
+
+    /// A value taking at least `DELAY` to deserialize.
+    struct Slow;
+
+    impl<'de> serde::Deserialize<'de> for Slow {
+        fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+            thread::sleep(DELAY);
+            serde::de::IgnoredAny::deserialize(deserializer)?;
+            Ok(Slow)
+        }
+    }
+
+    #[derive(serde::Deserialize)]
+    struct Inner {
+        #[allow(dead_code)]
+        slow: Slow,
+    }
+
+    #[derive(serde::Deserialize)]
+    struct Outer {
+        #[allow(dead_code)]
+        fast: u8,
+        #[allow(dead_code)]
+        slow: Slow,
+        #[allow(dead_code)]
+        inner: Vec<Inner>,
+    }
+
+    fn measure<'de, T: serde::Deserialize<'de>>(json: &'de str) -> BTreeMap<String, Duration> {
+        let mut timings = BTreeMap::new();
+        let mut deserializer = serde_json::Deserializer::from_str(json);
+        let mut deserializer = <dyn Deserializer>::new(&mut deserializer);
+        let mut deserializer = FieldTimings::new(&mut deserializer, &mut timings);
+        T::deserialize(&mut deserializer as &mut dyn Deserializer).unwrap();
+        timings
+    }
+
+    // only the fields of the top-level struct, including ignored ones.
+    let json = r#"{"fast": 1, "slow": null, "inner": [{"slow": null}], "extra": {"a": 1}}"#;
+    let timings = measure::<Outer>(json);
+    assert!(timings.keys().eq(["extra", "fast", "inner", "slow"]));
+    assert!(timings["slow"] >= DELAY);
+    assert!(timings["inner"] >= DELAY);
+    assert!(timings["fast"] < DELAY);
+
+    // behind `Some`, with a field found twice.
+    let json = r#"{"slow": null, "extra": 1, "extra": 2}"#;
+    let timings = measure::<Option<Inner>>(json);
+    assert!(timings.keys().eq(["extra", "slow"]));
+    assert!(timings["slow"] >= DELAY);
+
+    // a map which is not a struct.
+    assert!(measure::<BTreeMap<String, u8>>(r#"{"a": 1}"#).is_empty());
+}
@@ -0,0 +1,209 @@
+//! Test that types deriving `serde::Serialize` and `serde::Deserialize` round
+//! trip through `dyn_serde::Serializer` and `dyn_serde::Deserializer`.
+
+use std::collections::BTreeMap;
+
+use dyn_serde::de::TraceDeserializer;
+use dyn_serde::{Deserializer, Serialize, Serializer};
+
+#[derive(Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Record {
+    flag: bool,
+    small: i8,
+    wide: i128,
+    huge: u128,
+    ratio: f32,
+    letter: char,
+    #[serde(rename = "label")]
+    name: String,
+    #[serde(with = "serde_bytes")]
+    payload: Vec<u8>,
+    #[serde(skip)]
+    cache: u32,
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    note: Option<String>,
+    #[serde(default = "default_retries", alias = "tries")]
+    retries: u16,
+    id: Id,
+    point: Point,
+    marker: Marker,
+    unit: (),
+    shapes: Vec<Shape>,
+    event: Event,
+    either: Either,
+    scores: BTreeMap<String, f64>,
+    #[serde(flatten)]
+    extra: Extra,
+}
+
+fn default_retries() -> u16 {
+    3
+}
+
+#[derive(Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+struct Id(u64);
+
+#[derive(Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+struct Point(i32, i32);
+
+#[derive(Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+struct Marker;
+
+#[derive(Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+enum Shape {
+    Empty,
+    #[serde(rename = "circle")]
+    Circle(f64),
+    Line(Point, Point),
+    Rect {
+        width: u32,
+        height: u32,
+    },
+}
+
+/// Internally tagged, buffering its content before choosing the variant.
+#[derive(Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(tag = "type")]
+enum Event {
+    Start { at: u64 },
+    Stop,
+}
+
+#[derive(Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(untagged)]
+enum Either {
+    Number(i64),
+    Text(String),
+}
+
+#[derive(Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+struct Extra {
+    origin: String,
+    depth: Option<u8>,
+}
+
+fn record() -> Record {
+    Record {
+        flag: true,
+        small: -8,
+        wide: i128::MIN,
+        huge: u128::MAX,
+        ratio: 0.5,
+        letter: 'λ',
+        name: "a \"record\"".into(),
+        payload: vec![0, 1, 255],
+        cache: 0,
+        note: None,
+        retries: 5,
+        id: Id(42),
+        point: Point(-1, 2),
+        marker: Marker,
+        unit: (),
+        shapes: vec![
+            Shape::Empty,
+            Shape::Circle(1.5),
+            Shape::Line(Point(0, 0), Point(3, 4)),
+            Shape::Rect {
+                width: 2,
+                height: 3,
+            },
+        ],
+        event: Event::Start { at: 7 },
+        either: Either::Text("left".into()),
+        scores: BTreeMap::from([("a".into(), 1.0), ("b".into(), -2.25)]),
+        extra: Extra {
+            origin: "test".into(),
+            depth: Some(9),
+        },
+    }
+}
+
+/// Serializes `value` through `dyn_serde::Serializer`.
+fn to_json(value: &dyn Serialize) -> String {
+    let mut output = Vec::new();
+    let mut serializer = serde_json::Serializer::new(&mut output);
+    let mut serializer = <dyn Serializer>::new(&mut serializer);
+    value.dyn_serialize(&mut serializer).unwrap();
+    String::from_utf8(output).unwrap()
+}
+
+/// Deserializes `json` through `dyn_serde::Deserializer`, returning the hints
+/// requested along the way.
+fn from_json<'de, T: serde::Deserialize<'de>>(json: &'de str) -> (T, Vec<String>) {
+    let mut trace = Vec::new();
+    let mut push = |call: &str| trace.push(call.to_owned());
+    let mut deserializer = serde_json::Deserializer::from_str(json);
+    let mut deserializer = <dyn Deserializer>::new(&mut deserializer);
+    let value = {
+        let mut deserializer = TraceDeserializer::new(&mut deserializer, &mut push);
+        T::deserialize(&mut deserializer as &mut dyn Deserializer).unwrap()
+    };
+
+    let hints = trace
+        .into_iter()
+        .filter(|call| call.starts_with("deserialize_"))
+        .collect();
+    (value, hints)
+}
+
+#[test]
+fn test_derive_roundtrip() {
+    let value = record();
+    let json = to_json(&value);
+    assert_eq!(json, serde_json::to_string(&value).unwrap());
+    assert_eq!(
+        serde_json::to_string(&value as &dyn Serialize).unwrap(),
+        json
+    );
+
+    let (roundtrip, hints) = from_json::<Record>(&json);
+    assert_eq!(roundtrip, value);
+
+    // every hint of the derived implementation reaches the deserializer as it
+    // has been requested.
+    for hint in [
+        "deserialize_bool",
+        "deserialize_i8",
+        "deserialize_i128",
+        "deserialize_u128",
+        "deserialize_f32",
+        "deserialize_char",
+        "deserialize_string",
+        "deserialize_byte_buf",
+        "deserialize_u16",
+        "deserialize_newtype_struct(\"Id\")",
+        "deserialize_tuple_struct(\"Point\", 2)",
+        "deserialize_unit_struct(\"Marker\")",
+        "deserialize_unit",
+        "deserialize_seq",
+        "deserialize_enum(\"Shape\", [\"Empty\", \"circle\", \"Line\", \"Rect\"])",
+        "deserialize_map",
+    ] {
+        assert!(hints.iter().any(|call| call == hint), "missing {hint}");
+    }
+}
+
+#[test]
+fn test_derive_roundtrip_defaults() {
+    let json = r#"{
+        "flag": false, "small": 0, "wide": 1, "huge": 2, "ratio": 0.0, "letter": "x",
+        "label": "", "payload": [], "cache": 1, "note": "kept", "tries": 4,
+        "id": 0, "point": [0, 0], "marker": null, "unit": null, "shapes": [],
+        "event": {"type": "Stop"}, "either": -1, "scores": {}, "origin": "", "depth": null
+    }"#;
+    let (value, _) = from_json::<Record>(json);
+
+    // skipped fields take their default, while aliases are accepted.
+    assert_eq!(value.cache, 0);
+    assert_eq!(value.note.as_deref(), Some("kept"));
+    assert_eq!(value.retries, 4);
+    assert_eq!(value.event, Event::Stop);
+    assert_eq!(value.either, Either::Number(-1));
+    assert_eq!(value.extra.depth, None);
+
+    let json = json.replace(r#""tries": 4,"#, "");
+    let (value, _) = from_json::<Record>(&json);
+    assert_eq!(value.retries, default_retries());
+    assert_eq!(to_json(&value), serde_json::to_string(&value).unwrap());
+}
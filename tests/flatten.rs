@@ -0,0 +1,124 @@
+//! Test that structs with `#[serde(flatten)]` fields deserialize through
+//! `dyn_serde::Deserializer`.
+
+use std::collections::{BTreeMap, HashMap};
+
+use serde::Deserialize as _;
+use serde_json::{Value, json};
+
+use dyn_serde::Deserializer;
+use dyn_serde::de::{InplaceDeserializer, UntagStructs};
+
+#[derive(Debug, PartialEq, serde::Deserialize)]
+struct Item {
+    id: u32,
+    #[serde(flatten)]
+    rest: HashMap<String, Value>,
+}
+
+#[derive(Debug, PartialEq, serde::Deserialize)]
+struct Meta {
+    owner: String,
+}
+
+#[derive(Debug, PartialEq, serde::Deserialize)]
+struct Nested {
+    id: u32,
+    #[serde(flatten)]
+    meta: Meta,
+    #[serde(flatten)]
+    rest: BTreeMap<String, Value>,
+}
+
+fn deserialize<'de, T: serde::Deserialize<'de>>(json: &'de str) -> Result<T, String> {
+    let mut deserializer = serde_json::Deserializer::from_str(json);
+    let mut inplace = <dyn Deserializer>::new(&mut deserializer);
+    let result = T::deserialize(&mut inplace as &mut dyn Deserializer);
+    result.map_err(|error| match inplace {
+        InplaceDeserializer::Error(error, _) => error.to_string(),
+        _ => error.to_string(),
+    })
+}
+
+#[test]
+fn test_flatten_catch_all() {
+    let item: Item =
+        deserialize(r#"{"name": "a", "id": 1, "tags": [1, 2], "meta": {"x": null}}"#).unwrap();
+    assert_eq!(
+        item,
+        Item {
+            id: 1,
+            rest: HashMap::from([
+                ("name".to_owned(), json!("a")),
+                ("tags".to_owned(), json!([1, 2])),
+                ("meta".to_owned(), json!({"x": null})),
+            ]),
+        }
+    );
+
+    let item: Item = deserialize(r#"{"id": 2}"#).unwrap();
+    assert_eq!(
+        item,
+        Item {
+            id: 2,
+            rest: HashMap::new(),
+        }
+    );
+
+    assert_eq!(
+        deserialize::<Item>(r#"{"name": "a"}"#).unwrap_err(),
+        "missing field `id` at line 1 column 13"
+    );
+    assert_eq!(
+        deserialize::<Item>(r#"{"id": "1"}"#).unwrap_err(),
+        "invalid type: string \"1\", expected u32 at line 1 column 10"
+    );
+}
+
+#[test]
+fn test_flatten_struct_and_catch_all() {
+    let nested: Nested = deserialize(r#"{"id": 1, "owner": "ferris", "extra": true}"#).unwrap();
+    assert_eq!(
+        nested,
+        Nested {
+            id: 1,
+            meta: Meta {
+                owner: "ferris".to_owned(),
+            },
+            rest: BTreeMap::from([("extra".to_owned(), json!(true))]),
+        }
+    );
+}
+
+#[cfg(feature = "serde_json")]
+#[test]
+fn test_flatten_from_value() {
+    use dyn_serde::de::{Cached, from_json_value};
+
+    let value = json!({"id": 1, "name": "a"});
+    let mut deserializer = from_json_value(&value);
+    let item = Item::deserialize(&mut deserializer as &mut dyn Deserializer).unwrap();
+    assert_eq!(item.id, 1);
+    assert_eq!(item.rest, HashMap::from([("name".to_owned(), json!("a"))]));
+
+    // the entries are parsed once and read both as the item and as a map.
+    let mut deserializer = serde_json::Deserializer::from_str(r#"{"id": 1, "name": "a"}"#);
+    let mut deserializer = <dyn Deserializer>::new(&mut deserializer);
+    let cached = Cached::new(&mut deserializer).unwrap();
+    assert_eq!(cached.deserialize::<Item>().unwrap(), item);
+    let map: BTreeMap<String, Value> = cached.deserialize().unwrap();
+    assert_eq!(map.len(), 2);
+}
+
+#[test]
+fn test_flatten_through_struct_wrappers() {
+    // a flattened struct asks for a map, which `UntagStructs` leaves unchanged.
+    let mut deserializer = serde_json::Deserializer::from_str(r#"{"type": "Item", "id": 1}"#);
+    let mut deserializer = <dyn Deserializer>::new(&mut deserializer);
+    let mut deserializer = UntagStructs::new(&mut deserializer, "type");
+    let item = Item::deserialize(&mut deserializer as &mut dyn Deserializer).unwrap();
+    assert_eq!(
+        item.rest,
+        HashMap::from([("type".to_owned(), json!("Item"))])
+    );
+}
@@ -1,5 +1,12 @@
 //! Test `serde_dyn::Serialize` and `serde_dyn::Serializer`.
 
+// the assertions spell out the values the format writes.
+#![allow(
+    clippy::approx_constant,
+    clippy::enum_variant_names,
+    clippy::excessive_precision
+)]
+
 use dyn_serde::{Serialize, Serializer};
 
 macro_rules! make_serializer {
@@ -317,3 +324,3489 @@ fn test_collect_str() {
         .unwrap();
     assert_eq!(buf, b"\"entity not found\"");
 }
+
+#[test]
+fn test_required_methods_only() {
+    use dyn_serde::ser::{
+        InplaceSerializeResult, SerializeMap, SerializeSeq, SerializeStruct,
+        SerializeStructVariant, SerializeTuple, SerializeTupleStruct, SerializeTupleVariant,
+    };
+
+    /// Renders the serialized value as a whitespace separated list of tokens.
+    #[derive(Default)]
+    struct Tokens(String);
+
+    impl Tokens {
+        fn push(&mut self, token: impl std::fmt::Display) -> InplaceSerializeResult<()> {
+            self.0.push_str(&format!("{token} "));
+            Ok(())
+        }
+
+        fn push_value(&mut self, value: &dyn Serialize) -> InplaceSerializeResult<()> {
+            value.dyn_serialize(self).unwrap();
+            Ok(())
+        }
+    }
+
+    impl Serializer for Tokens {
+        fn dyn_serialize_bool(&mut self, v: bool) -> InplaceSerializeResult<()> {
+            self.push(v)
+        }
+
+        fn dyn_serialize_i64(&mut self, v: i64) -> InplaceSerializeResult<()> {
+            self.push(format_args!("{v}i64"))
+        }
+
+        fn dyn_serialize_i128(&mut self, v: i128) -> InplaceSerializeResult<()> {
+            self.push(format_args!("{v}i128"))
+        }
+
+        fn dyn_serialize_u64(&mut self, v: u64) -> InplaceSerializeResult<()> {
+            self.push(format_args!("{v}u64"))
+        }
+
+        fn dyn_serialize_u128(&mut self, v: u128) -> InplaceSerializeResult<()> {
+            self.push(format_args!("{v}u128"))
+        }
+
+        fn dyn_serialize_f64(&mut self, v: f64) -> InplaceSerializeResult<()> {
+            self.push(format_args!("{v}f64"))
+        }
+
+        fn dyn_serialize_str(&mut self, v: &str) -> InplaceSerializeResult<()> {
+            self.push(format_args!("{v:?}"))
+        }
+
+        fn dyn_serialize_bytes(&mut self, v: &[u8]) -> InplaceSerializeResult<()> {
+            self.push(format_args!("{v:?}"))
+        }
+
+        fn dyn_serialize_none(&mut self) -> InplaceSerializeResult<()> {
+            self.push("none")
+        }
+
+        fn dyn_serialize_some(&mut self, value: &dyn Serialize) -> InplaceSerializeResult<()> {
+            self.push("some")?;
+            self.push_value(value)
+        }
+
+        fn dyn_serialize_unit(&mut self) -> InplaceSerializeResult<()> {
+            self.push("unit")
+        }
+
+        fn dyn_serialize_unit_variant(
+            &mut self,
+            _name: &'static str,
+            _variant_index: u32,
+            variant: &'static str,
+        ) -> InplaceSerializeResult<()> {
+            self.push(variant)
+        }
+
+        fn dyn_serialize_newtype_struct(
+            &mut self,
+            name: &'static str,
+            value: &dyn Serialize,
+        ) -> InplaceSerializeResult<()> {
+            self.push(name)?;
+            self.push_value(value)
+        }
+
+        fn dyn_serialize_newtype_variant(
+            &mut self,
+            _name: &'static str,
+            _variant_index: u32,
+            variant: &'static str,
+            value: &dyn Serialize,
+        ) -> InplaceSerializeResult<()> {
+            self.push(variant)?;
+            self.push_value(value)
+        }
+
+        fn dyn_serialize_seq(
+            &mut self,
+            _len: Option<usize>,
+        ) -> InplaceSerializeResult<&mut dyn SerializeSeq> {
+            self.push("[")?;
+            Ok(self)
+        }
+
+        fn dyn_serialize_tuple(
+            &mut self,
+            _len: usize,
+        ) -> InplaceSerializeResult<&mut dyn SerializeTuple> {
+            self.push("[")?;
+            Ok(self)
+        }
+
+        fn dyn_serialize_tuple_struct(
+            &mut self,
+            _name: &'static str,
+            _len: usize,
+        ) -> InplaceSerializeResult<&mut dyn SerializeTupleStruct> {
+            self.push("[")?;
+            Ok(self)
+        }
+
+        fn dyn_serialize_tuple_variant(
+            &mut self,
+            _name: &'static str,
+            _variant_index: u32,
+            _variant: &'static str,
+            _len: usize,
+        ) -> InplaceSerializeResult<&mut dyn SerializeTupleVariant> {
+            self.push("[")?;
+            Ok(self)
+        }
+
+        fn dyn_serialize_map(
+            &mut self,
+            _len: Option<usize>,
+        ) -> InplaceSerializeResult<&mut dyn SerializeMap> {
+            self.push("{")?;
+            Ok(self)
+        }
+
+        fn dyn_serialize_struct(
+            &mut self,
+            _name: &'static str,
+            _len: usize,
+        ) -> InplaceSerializeResult<&mut dyn SerializeStruct> {
+            self.push("{")?;
+            Ok(self)
+        }
+
+        fn dyn_serialize_struct_variant(
+            &mut self,
+            _name: &'static str,
+            _variant_index: u32,
+            _variant: &'static str,
+            _len: usize,
+        ) -> InplaceSerializeResult<&mut dyn SerializeStructVariant> {
+            self.push("{")?;
+            Ok(self)
+        }
+    }
+
+    impl SerializeSeq for Tokens {
+        fn dyn_serialize_element(&mut self, value: &dyn Serialize) -> InplaceSerializeResult<()> {
+            self.push_value(value)
+        }
+
+        fn dyn_end(&mut self) -> InplaceSerializeResult<()> {
+            self.push("]")
+        }
+    }
+
+    impl SerializeTuple for Tokens {
+        fn dyn_serialize_element(&mut self, value: &dyn Serialize) -> InplaceSerializeResult<()> {
+            self.push_value(value)
+        }
+
+        fn dyn_end(&mut self) -> InplaceSerializeResult<()> {
+            self.push("]")
+        }
+    }
+
+    impl SerializeTupleStruct for Tokens {
+        fn dyn_serialize_field(&mut self, value: &dyn Serialize) -> InplaceSerializeResult<()> {
+            self.push_value(value)
+        }
+
+        fn dyn_end(&mut self) -> InplaceSerializeResult<()> {
+            self.push("]")
+        }
+    }
+
+    impl SerializeTupleVariant for Tokens {
+        fn dyn_serialize_field(&mut self, value: &dyn Serialize) -> InplaceSerializeResult<()> {
+            self.push_value(value)
+        }
+
+        fn dyn_end(&mut self) -> InplaceSerializeResult<()> {
+            self.push("]")
+        }
+    }
+
+    impl SerializeMap for Tokens {
+        fn dyn_serialize_key(&mut self, key: &dyn Serialize) -> InplaceSerializeResult<()> {
+            self.push_value(key)
+        }
+
+        fn dyn_serialize_value(&mut self, value: &dyn Serialize) -> InplaceSerializeResult<()> {
+            self.push_value(value)
+        }
+
+        fn dyn_end(&mut self) -> InplaceSerializeResult<()> {
+            self.push("}")
+        }
+    }
+
+    impl SerializeStruct for Tokens {
+        fn dyn_serialize_field(
+            &mut self,
+            key: &'static str,
+            value: &dyn Serialize,
+        ) -> InplaceSerializeResult<()> {
+            self.push(key)?;
+            self.push_value(value)
+        }
+
+        fn dyn_end(&mut self) -> InplaceSerializeResult<()> {
+            self.push("}")
+        }
+    }
+
+    impl SerializeStructVariant for Tokens {
+        fn dyn_serialize_field(
+            &mut self,
+            key: &'static str,
+            value: &dyn Serialize,
+        ) -> InplaceSerializeResult<()> {
+            self.push(key)?;
+            self.push_value(value)
+        }
+
+        fn dyn_end(&mut self) -> InplaceSerializeResult<()> {
+            self.push("}")
+        }
+    }
+
+    #[derive(serde::Serialize)]
+    struct Struct {
+        a: u8,
+        b: (i16, f32),
+        c: char,
+        d: Option<std::marker::PhantomData<()>>,
+    }
+
+    let mut serializer = Tokens::default();
+    let value = Struct {
+        a: 1,
+        b: (-2, 0.5),
+        c: 'x',
+        d: Some(std::marker::PhantomData),
+    };
+    value.dyn_serialize(&mut serializer).unwrap();
+    serializer
+        .dyn_collect_str(&format_args!("{}-{}", 3, 4))
+        .unwrap();
+    assert!(serializer.dyn_is_human_readable());
+    assert_eq!(
+        serializer.0,
+        "{ a 1u64 b [ -2i64 0.5f64 ] c \"x\" d some unit } \"3-4\" "
+    );
+}
+
+#[test]
+fn test_lazy() {
+    use std::cell::Cell;
+
+    use dyn_serde::ser::{Lazy, present_fields};
+
+    #[derive(serde::Serialize)]
+    struct Report<'a> {
+        name: &'static str,
+        total: Lazy<&'a dyn Fn() -> u32>,
+    }
+
+    let calls = Cell::new(0);
+    let total = || {
+        calls.set(calls.get() + 1);
+        42
+    };
+    let report = Report {
+        name: "daily",
+        total: Lazy::new(&total),
+    };
+
+    // the field is present, but its value is not serialized.
+    assert_eq!(present_fields(&report).unwrap(), ["name", "total"]);
+    assert_eq!(calls.get(), 0);
+
+    assert_eq!(
+        serde_json::to_string(&report).unwrap(),
+        r#"{"name":"daily","total":42}"#
+    );
+    assert_eq!(calls.get(), 1);
+    assert_eq!(
+        serde_json::to_string(&report as &dyn Serialize).unwrap(),
+        r#"{"name":"daily","total":42}"#
+    );
+    assert_eq!(calls.get(), 2);
+}
+
+#[test]
+fn test_canonicalize() {
+    use std::collections::{BTreeMap, HashMap};
+
+    #[derive(serde::Serialize)]
+    struct Struct {
+        z: Vec<f64>,
+        a: &'static str,
+        é: Option<bool>,
+        b: BTreeMap<u8, ()>,
+    }
+
+    let value = Struct {
+        z: vec![1.0, -0.0, 1e21, 1e-7, 0.000001, 123.456, f64::MAX],
+        a: "\"\\\u{1}\n\u{7f}€",
+        é: Some(true),
+        b: BTreeMap::from([(10, ()), (2, ())]),
+    };
+    let expected = concat!(
+        r#"{"a":"\"\\\u0001\n"#,
+        "\u{7f}€",
+        r#"","b":{"10":null,"2":null},"#,
+        r#""z":[1,0,1e+21,1e-7,0.000001,123.456,1.7976931348623157e+308],"#,
+        r#""é":true}"#,
+    );
+    assert_eq!(
+        dyn_serde::ser::canonicalize(&value).unwrap(),
+        expected.as_bytes()
+    );
+
+    // structurally-equal inputs produce byte-identical outputs.
+    let mut map = HashMap::new();
+    map.insert("é", serde_json::json!(true));
+    map.insert(
+        "z",
+        serde_json::json!([1, 0, 1e21, 1e-7, 0.000001, 123.456, f64::MAX]),
+    );
+    map.insert("b", serde_json::json!({ "2": null, "10": null }));
+    map.insert("a", serde_json::json!("\"\\\u{1}\n\u{7f}€"));
+    assert_eq!(
+        dyn_serde::ser::canonicalize(&map).unwrap(),
+        expected.as_bytes()
+    );
+}
+
+#[test]
+fn test_canonicalize_error() {
+    use std::collections::BTreeMap;
+
+    let error = dyn_serde::ser::canonicalize(&f64::NAN).unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "canonical JSON cannot represent NaN or infinity"
+    );
+
+    let value = BTreeMap::from([((1, 2), 3)]);
+    let error = dyn_serde::ser::canonicalize(&value).unwrap_err();
+    assert_eq!(error.to_string(), "key must be a string");
+
+    struct Duplicate;
+
+    impl serde::Serialize for Duplicate {
+        fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            serializer.collect_map([("a", 1), ("b", 2), ("a", 3)])
+        }
+    }
+
+    let error = dyn_serde::ser::canonicalize(&Duplicate).unwrap_err();
+    assert_eq!(error.to_string(), "duplicate key `a`");
+}
+
+#[test]
+fn test_canonical_hashes() {
+    use std::collections::BTreeMap;
+
+    #[derive(serde::Serialize)]
+    struct Document {
+        id: u32,
+        tags: Vec<&'static str>,
+        meta: BTreeMap<&'static str, &'static str>,
+    }
+
+    let before = Document {
+        id: 1,
+        tags: vec!["a", "b"],
+        meta: BTreeMap::from([("owner", "ferris")]),
+    };
+    let after = Document {
+        id: 1,
+        tags: vec!["a", "b"],
+        meta: BTreeMap::from([("owner", "corro")]),
+    };
+    let before = dyn_serde::ser::canonical_hashes(&before).unwrap();
+    let after = dyn_serde::ser::canonical_hashes(&after).unwrap();
+
+    assert_ne!(before.value, after.value);
+    assert_eq!(
+        before.members.keys().collect::<Vec<_>>(),
+        ["id", "meta", "tags"]
+    );
+    let changed: Vec<_> = before
+        .members
+        .iter()
+        .filter(|&(name, hash)| after.members[name] != *hash)
+        .map(|(name, _)| name.as_str())
+        .collect();
+    assert_eq!(changed, ["meta"]);
+
+    // the hashes only depend on the canonical JSON.
+    let json = serde_json::json!({ "tags": ["a", "b"], "id": 1, "meta": { "owner": "ferris" } });
+    assert_eq!(dyn_serde::ser::canonical_hashes(&json).unwrap(), before);
+    assert_eq!(
+        before.members["id"],
+        dyn_serde::ser::canonical_hashes(&1).unwrap().value
+    );
+
+    let hashes = dyn_serde::ser::canonical_hashes(&[1, 2]).unwrap();
+    assert!(hashes.members.is_empty());
+    // the 64-bit FNV-1a hash of `[1,2]`.
+    assert_eq!(hashes.value, 0x6a12_f12d_4705_a9b6);
+}
+
+#[test]
+fn test_inplace_serialize_error_state() {
+    use dyn_serde::ser::{InplaceSerializeError, InplaceSerializerState};
+    use dyn_serde::ser::{SerializeMap, SerializeSeq};
+
+    let mut buf = Vec::new();
+    let mut serializer = serde_json::Serializer::new(&mut buf);
+    let mut serializer = <dyn Serializer>::new(&mut serializer);
+    serializer.dyn_serialize_map(None).unwrap();
+    assert_eq!(serializer.state(), InplaceSerializerState::SerializeMap);
+
+    let error = SerializeSeq::dyn_serialize_element(&mut serializer, &0).unwrap_err();
+    assert_eq!(error, InplaceSerializeError::NotSerializeSeq);
+    assert_eq!(error.expected(), Some(InplaceSerializerState::SerializeSeq));
+    assert_eq!(serializer.state(), InplaceSerializerState::SerializeMap);
+    assert_eq!(
+        error.in_state(serializer.state()).to_string(),
+        "the in-place serializer is not ready to serialize the content of the sequence: \
+         expected SerializeSeq but the serializer is in SerializeMap state"
+    );
+
+    SerializeMap::dyn_end(&mut serializer).unwrap();
+    let error = serializer.dyn_serialize_bool(true).unwrap_err();
+    assert_eq!(
+        error.in_state(serializer.state()).to_string(),
+        "the in-place serializer is not ready: expected Serializer but the serializer is in Ok state"
+    );
+    // the error alone keeps its message.
+    assert_eq!(error.to_string(), "the in-place serializer is not ready");
+    assert_eq!(
+        InplaceSerializeError::Error
+            .in_state(InplaceSerializerState::Error)
+            .to_string(),
+        "the in-place serialization has done unsuccessfually"
+    );
+}
+
+#[test]
+fn test_serialize_tuple_of_trait_objects() {
+    let flag = true;
+    let name = String::from("x");
+    let record: (&dyn Serialize, &dyn Serialize, &dyn Serialize) = (&1u8, &name, &[flag]);
+
+    make_serializer!(buf, serializer);
+    (&record as &dyn Serialize)
+        .dyn_serialize(serializer)
+        .unwrap();
+    assert_eq!(buf, br#"[1,"x",[true]]"#);
+
+    let boxed: (Box<dyn Serialize>, Box<dyn Serialize>) = (Box::new(-1), Box::new(None::<()>));
+    assert_eq!(serde_json::to_string(&boxed).unwrap(), "[-1,null]");
+}
+
+#[test]
+fn test_serialize_map_iter() {
+    use dyn_serde::ser::SerializeMapIter;
+
+    let names = ["a", "b", "c"];
+    let values: [&dyn Serialize; 3] = [&1, &"x", &[true]];
+    let entries = names
+        .iter()
+        .zip(values)
+        .filter(|(name, _)| **name != "b")
+        .map(|(name, value)| (name as &dyn Serialize, value));
+    let value = SerializeMapIter::new(entries);
+
+    make_serializer!(buf, serializer);
+    (&value as &dyn Serialize)
+        .dyn_serialize(serializer)
+        .unwrap();
+    assert_eq!(buf, br#"{"a":1,"c":[true]}"#);
+
+    // the iterator has been consumed.
+    let error = serde_json::to_string(&value).unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "the iterator has already been serialized"
+    );
+}
+
+#[test]
+fn test_size_limited() {
+    use std::cell::Cell;
+    use std::io::{self, Write};
+
+    use dyn_serde::ser::{InplaceSerializer, SizeLimited};
+
+    struct Counter<'a>(Vec<u8>, &'a Cell<usize>);
+
+    impl Write for Counter<'_> {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.1.set(self.1.get() + buf.len());
+            self.0.write(buf)
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[derive(serde::Serialize)]
+    struct Record {
+        id: u32,
+        name: String,
+        tags: Vec<String>,
+    }
+
+    let records: Vec<Record> = (0..1000)
+        .map(|id| Record {
+            id,
+            name: format!("record #{id}"),
+            tags: vec!["a".to_owned(), "b".to_owned()],
+        })
+        .collect();
+    let full = serde_json::to_vec(&records).unwrap();
+
+    let written = Cell::new(0);
+    let mut serializer = serde_json::Serializer::new(Counter(Vec::new(), &written));
+    let mut inplace = <dyn Serializer>::new(&mut serializer);
+    let mut limited = SizeLimited::new(&mut inplace, 100, &written);
+    assert!(
+        (&records as &dyn Serialize)
+            .dyn_serialize(&mut limited)
+            .is_err()
+    );
+    assert!(limited.exceeded());
+    assert!(limited.written() > 100);
+    let InplaceSerializer::Error(error, _) = inplace else {
+        panic!("expected an error");
+    };
+    assert_eq!(
+        error.to_string(),
+        "the output exceeds the limit of 100 bytes"
+    );
+    // the payload is cut off at the first call crossing the limit.
+    let output = serializer.into_inner().0;
+    assert_eq!(output.len(), written.get());
+    assert!(output.len() > 100 && output.len() < 120);
+    assert!(full.starts_with(&output));
+
+    // within the limit, the output is left untouched.
+    let written = Cell::new(0);
+    let mut serializer = serde_json::Serializer::new(Counter(Vec::new(), &written));
+    let mut inplace = <dyn Serializer>::new(&mut serializer);
+    let mut limited = SizeLimited::new(&mut inplace, 100, &written);
+    (&&records[..1] as &dyn Serialize)
+        .dyn_serialize(&mut limited)
+        .unwrap();
+    assert!(!limited.exceeded());
+    assert_eq!(
+        serializer.into_inner().0,
+        br#"[{"id":0,"name":"record #0","tags":["a","b"]}]"#
+    );
+}
+
+#[test]
+fn test_check_depth() {
+    use std::collections::BTreeMap;
+
+    use dyn_serde::ser::{DepthLimited, check_depth};
+
+    #[derive(serde::Serialize)]
+    struct Node {
+        children: Vec<Node>,
+    }
+
+    #[derive(serde::Serialize)]
+    enum Shape {
+        Point,
+        Line(u8, u8),
+    }
+
+    struct Failing;
+
+    impl serde::Serialize for Failing {
+        fn serialize<S: serde::Serializer>(&self, _: S) -> Result<S::Ok, S::Error> {
+            Err(serde::ser::Error::custom("cannot serialize"))
+        }
+    }
+
+    /// A chain of nodes, each one the only child of the previous one.
+    fn chain(len: usize) -> Node {
+        (1..len).fold(Node { children: vec![] }, |node, _| Node {
+            children: vec![node],
+        })
+    }
+
+    // a scalar, and the compounds holding scalars only.
+    assert!(check_depth(&1, 0).is_ok());
+    assert!(check_depth(&Shape::Point, 0).is_ok());
+    assert!(check_depth(&Some("a"), 0).is_ok());
+    assert!(check_depth(&[1], 0).is_err());
+    assert!(check_depth(&Shape::Line(1, 2), 0).is_err());
+    assert!(check_depth(&Shape::Line(1, 2), 1).is_ok());
+
+    // every node nests two compounds, its struct and its children.
+    let node = chain(3);
+    assert!(check_depth(&node, 6).is_ok());
+    assert_eq!(
+        check_depth(&node, 5).unwrap_err().to_string(),
+        "the value exceeds the maximum depth of 5"
+    );
+    assert!(check_depth(&chain(1000), 64).is_err());
+
+    // siblings do not add up.
+    let map = BTreeMap::from([("a", vec![1]), ("b", vec![2]), ("c", vec![3])]);
+    assert!(check_depth(&map, 2).is_ok());
+    assert!(check_depth(&map, 1).is_err());
+
+    // the errors of the value itself.
+    assert_eq!(
+        check_depth(&[Failing], 8).unwrap_err().to_string(),
+        "cannot serialize"
+    );
+
+    // the wrapper, in front of a real format.
+    make_serializer!(buf, serializer);
+    let mut serializer = DepthLimited::new(serializer, 1);
+    assert!(
+        (&map as &dyn Serialize)
+            .dyn_serialize(&mut serializer)
+            .is_err()
+    );
+    assert!(serializer.exceeded());
+    assert_eq!(buf, br#"{"a":"#);
+}
+
+#[cfg(all(feature = "std", feature = "serde_json"))]
+#[test]
+fn test_write_json_lines() {
+    use std::cell::Cell;
+    use std::collections::BTreeMap;
+    use std::io::{self, Write};
+    use std::rc::Rc;
+
+    use dyn_serde::ser::write_json_lines;
+
+    /// A large item, counting how many of them are alive.
+    struct Row {
+        payload: String,
+        live: Rc<Cell<usize>>,
+    }
+
+    impl Drop for Row {
+        fn drop(&mut self) {
+            self.live.set(self.live.get() - 1);
+        }
+    }
+
+    impl serde::Serialize for Row {
+        fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            serializer.serialize_str(&self.payload)
+        }
+    }
+
+    /// A writer recording how many lines have been flushed.
+    struct Sink {
+        buf: Vec<u8>,
+        lines: usize,
+        flushed: Rc<Cell<usize>>,
+    }
+
+    impl Write for Sink {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.lines += buf.iter().filter(|&&b| b == b'\n').count();
+            self.buf.write(buf)
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            self.flushed.set(self.lines);
+            Ok(())
+        }
+    }
+
+    const ROWS: usize = 1000;
+
+    let live = Rc::new(Cell::new(0));
+    let peak = Rc::new(Cell::new(0));
+    let flushed = Rc::new(Cell::new(0));
+    let rows = (0..ROWS).map(|i| {
+        // every previous row has been written, flushed and dropped.
+        assert_eq!(flushed.get(), i);
+        assert_eq!(live.get(), 0);
+        live.set(live.get() + 1);
+        peak.set(peak.get().max(live.get()));
+        Row {
+            payload: "x".repeat(1 << 10),
+            live: live.clone(),
+        }
+    });
+    let sink = Sink {
+        buf: Vec::new(),
+        lines: 0,
+        flushed: flushed.clone(),
+    };
+    let sink = write_json_lines(sink, rows).unwrap();
+    assert_eq!(peak.get(), 1);
+    assert_eq!(live.get(), 0);
+    assert_eq!(flushed.get(), ROWS);
+    assert_eq!(sink.buf.len(), ROWS * ((1 << 10) + 3));
+
+    // items borrowed from a collection, and the first error, telling why.
+    let items = [BTreeMap::from([("a", 1)]), BTreeMap::from([("b", 2)])];
+    let output = write_json_lines(Vec::new(), &items).unwrap();
+    assert_eq!(output, b"{\"a\":1}\n{\"b\":2}\n");
+    let items = [BTreeMap::from([(vec![1], 2)])];
+    let error = write_json_lines(Vec::new(), items).unwrap_err();
+    assert_eq!(error.to_string(), "key must be a string");
+}
+
+#[cfg(all(feature = "std", feature = "serde_json"))]
+#[test]
+fn test_ndjson_serializer() {
+    use dyn_serde::ser::NdjsonSerializer;
+
+    #[derive(serde::Serialize)]
+    struct Event {
+        level: &'static str,
+        message: String,
+    }
+
+    let events: Vec<Event> = (0..3)
+        .map(|i| Event {
+            level: if i == 1 { "warn" } else { "info" },
+            message: format!("line\n{i}"),
+        })
+        .collect();
+    let mut serializer = NdjsonSerializer::new(Vec::new());
+    (&events as &dyn Serialize)
+        .dyn_serialize(&mut serializer)
+        .unwrap();
+    let output = String::from_utf8(serializer.into_inner()).unwrap();
+    assert_eq!(output.lines().count(), 3);
+    assert_eq!(
+        output,
+        concat!(
+            "{\"level\":\"info\",\"message\":\"line\\n0\"}\n",
+            "{\"level\":\"warn\",\"message\":\"line\\n1\"}\n",
+            "{\"level\":\"info\",\"message\":\"line\\n2\"}\n",
+        )
+    );
+
+    let mut serializer = NdjsonSerializer::new(Vec::new());
+    assert!(
+        (&events[0] as &dyn Serialize)
+            .dyn_serialize(&mut serializer)
+            .is_err()
+    );
+    assert_eq!(
+        serializer.error().unwrap().to_string(),
+        "the top-level value of NDJSON must be a sequence"
+    );
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn test_line_serializer() {
+    use std::collections::BTreeMap;
+
+    use dyn_serde::ser::LineSerializer;
+
+    #[derive(serde::Serialize)]
+    struct Flat {
+        a: i32,
+        b: bool,
+    }
+
+    #[derive(serde::Serialize)]
+    enum Level {
+        Warn,
+    }
+
+    #[derive(serde::Serialize)]
+    struct Event {
+        level: Level,
+        message: &'static str,
+        user: Option<&'static str>,
+        ratio: f64,
+    }
+
+    let mut serializer = LineSerializer::new(Vec::new());
+    (&Flat { a: 1, b: true } as &dyn Serialize)
+        .dyn_serialize(&mut serializer)
+        .unwrap();
+    assert_eq!(serializer.into_inner(), b"a=1 b=true\n");
+
+    // one line per value, with strings quoted where they would be ambiguous.
+    let mut serializer = LineSerializer::new(Vec::new());
+    let event = Event {
+        level: Level::Warn,
+        message: "disk \"full\"\n",
+        user: None,
+        ratio: 0.5,
+    };
+    (&event as &dyn Serialize)
+        .dyn_serialize(&mut serializer)
+        .unwrap();
+    (&BTreeMap::from([("k", "a=b"), ("", "v")]) as &dyn Serialize)
+        .dyn_serialize(&mut serializer)
+        .unwrap();
+    (&(1, "x") as &dyn Serialize)
+        .dyn_serialize(&mut serializer)
+        .unwrap();
+    (&"scalar" as &dyn Serialize)
+        .dyn_serialize(&mut serializer)
+        .unwrap();
+    assert_eq!(
+        String::from_utf8(serializer.into_inner()).unwrap(),
+        concat!(
+            "level=Warn message=\"disk \\\"full\\\"\\n\" user= ratio=0.5\n",
+            "\"\"=v k=\"a=b\"\n",
+            "1 x\n",
+            "scalar\n",
+        )
+    );
+
+    let mut serializer = LineSerializer::new(Vec::new());
+    assert!(
+        (&BTreeMap::from([("a", vec![1])]) as &dyn Serialize)
+            .dyn_serialize(&mut serializer)
+            .is_err()
+    );
+    assert_eq!(
+        serializer.error().unwrap().to_string(),
+        "a line cannot hold nested compound values"
+    );
+}
+
+#[test]
+fn test_omit_unit_values() {
+    use std::collections::BTreeMap;
+
+    use dyn_serde::ser::OmitUnitValues;
+    use serde::ser::SerializeMap as _;
+
+    #[derive(serde::Serialize)]
+    struct Marker;
+
+    #[derive(serde::Serialize)]
+    struct Wrapper(Option<u8>);
+
+    #[derive(serde::Serialize)]
+    struct Record {
+        a: Option<u8>,
+        b: (),
+        c: Marker,
+        d: Wrapper,
+        e: Option<()>,
+        f: BTreeMap<&'static str, ()>,
+    }
+
+    #[derive(serde::Serialize)]
+    struct Name(&'static str);
+
+    /// A map serializing its keys apart from its values.
+    struct Split(Vec<(Box<dyn Serialize>, Box<dyn Serialize>)>);
+
+    impl serde::Serialize for Split {
+        fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            let mut map = serializer.serialize_map(Some(self.0.len()))?;
+            for (key, value) in &self.0 {
+                map.serialize_key(key)?;
+                map.serialize_value(value)?;
+            }
+            map.end()
+        }
+    }
+
+    fn omit(value: &dyn Serialize) -> String {
+        make_serializer!(buf, serializer);
+        value
+            .dyn_serialize(&mut OmitUnitValues::new(serializer))
+            .unwrap();
+        String::from_utf8(buf).unwrap()
+    }
+
+    let map = BTreeMap::from([("a", None), ("b", Some(1)), ("c", None)]);
+    assert_eq!(omit(&map), r#"{"b":1}"#);
+
+    let record = Record {
+        a: None,
+        b: (),
+        c: Marker,
+        d: Wrapper(None),
+        e: Some(()),
+        f: BTreeMap::from([("x", ())]),
+    };
+    assert_eq!(omit(&record), r#"{"e":null,"f":{}}"#);
+
+    // keys serialized apart from their values are held back.
+    let split = Split(vec![
+        (Box::new(1), Box::new(())),
+        (Box::new("k"), Box::new([None::<u8>])),
+        (Box::new('c'), Box::new(None::<u8>)),
+        (Box::new(Name("n")), Box::new(())),
+    ]);
+    assert_eq!(omit(&split), r#"{"k":[null],"n":null}"#);
+
+    // a sequence keeps its elements.
+    assert_eq!(omit(&[None, Some(())]), "[null,null]");
+}
+
+#[test]
+fn test_omit_unit_structs() {
+    use std::collections::BTreeMap;
+    use std::marker::PhantomData;
+
+    use dyn_serde::ser::OmitUnitStructs;
+
+    #[derive(serde::Serialize)]
+    struct Marker;
+
+    #[derive(serde::Serialize)]
+    struct Tagged(Marker);
+
+    #[derive(serde::Serialize)]
+    struct Inner {
+        value: u8,
+        kind: PhantomData<String>,
+    }
+
+    #[derive(serde::Serialize)]
+    struct Record {
+        a: PhantomData<u8>,
+        b: Option<u8>,
+        c: (),
+        d: Marker,
+        e: Tagged,
+        f: Inner,
+        g: BTreeMap<&'static str, Marker>,
+        h: Vec<PhantomData<u8>>,
+    }
+
+    #[derive(serde::Serialize)]
+    enum Shape {
+        Rect { w: u8, marker: Marker },
+    }
+
+    fn omit(value: &dyn Serialize) -> String {
+        make_serializer!(buf, serializer);
+        value
+            .dyn_serialize(&mut OmitUnitStructs::new(serializer))
+            .unwrap();
+        String::from_utf8(buf).unwrap()
+    }
+
+    let record = Record {
+        a: PhantomData,
+        b: None,
+        c: (),
+        d: Marker,
+        e: Tagged(Marker),
+        f: Inner {
+            value: 1,
+            kind: PhantomData,
+        },
+        g: BTreeMap::from([("x", Marker)]),
+        h: vec![PhantomData],
+    };
+    assert_eq!(
+        omit(&record),
+        r#"{"b":null,"c":null,"f":{"value":1},"g":{"x":null},"h":[null]}"#
+    );
+
+    let shape = Shape::Rect {
+        w: 2,
+        marker: Marker,
+    };
+    assert_eq!(omit(&shape), r#"{"Rect":{"w":2}}"#);
+}
+
+#[test]
+fn test_empty_as_null() {
+    use std::collections::{BTreeMap, HashMap};
+
+    use dyn_serde::ser::EmptyAsNull;
+
+    #[derive(serde::Serialize)]
+    struct Record {
+        tags: Vec<&'static str>,
+        labels: BTreeMap<&'static str, Vec<u8>>,
+        parent: Option<Vec<u8>>,
+        empty: [u8; 0],
+    }
+
+    /// A sequence whose length is not known up front.
+    struct Filtered(u8);
+
+    impl serde::Serialize for Filtered {
+        fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            serializer.collect_seq((1..=5).filter(|n| n % self.0 == 0))
+        }
+    }
+
+    fn nullify(value: &dyn Serialize) -> String {
+        make_serializer!(buf, serializer);
+        value
+            .dyn_serialize(&mut EmptyAsNull::new(serializer))
+            .unwrap();
+        String::from_utf8(buf).unwrap()
+    }
+
+    assert_eq!(nullify(&Vec::<u8>::new()), "null");
+    assert_eq!(nullify(&HashMap::<String, u8>::new()), "null");
+    assert_eq!(nullify(&vec![1, 2]), "[1,2]");
+    assert_eq!(nullify(&HashMap::from([("a", 1)])), r#"{"a":1}"#);
+
+    // sequences without a length are held back until their first element.
+    assert_eq!(nullify(&Filtered(6)), "null");
+    assert_eq!(nullify(&Filtered(2)), "[2,4]");
+
+    let record = Record {
+        tags: vec![],
+        labels: BTreeMap::from([("a", vec![]), ("b", vec![1])]),
+        parent: Some(vec![]),
+        empty: [],
+    };
+    assert_eq!(
+        nullify(&record),
+        r#"{"tags":null,"labels":{"a":null,"b":[1]},"parent":null,"empty":[]}"#
+    );
+    assert_eq!(nullify(&[vec![], vec![1]]), "[null,[1]]");
+}
+
+#[test]
+fn test_check_variant_indices() {
+    use dyn_serde::ser::{CheckVariantIndices, InplaceSerializer};
+
+    #[derive(serde::Serialize)]
+    enum Shape {
+        Point,
+        Circle(u8),
+        Line(u8, u8),
+        Rect { w: u8, h: u8 },
+    }
+
+    /// A variant serialized by hand, with the index of another variant.
+    struct Mismatched(Shape);
+
+    impl serde::Serialize for Mismatched {
+        fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            use serde::ser::SerializeStructVariant as _;
+
+            match self.0 {
+                Shape::Point => serializer.serialize_unit_variant("Shape", 1, "Point"),
+                Shape::Circle(r) => serializer.serialize_newtype_variant("Shape", 2, "Circle", &r),
+                Shape::Line(..) => serializer.serialize_unit_variant("Shape", 2, "Curve"),
+                Shape::Rect { w, h } => {
+                    let mut rect = serializer.serialize_struct_variant("Shape", 0, "Rect", 2)?;
+                    rect.serialize_field("w", &w)?;
+                    rect.serialize_field("h", &h)?;
+                    rect.end()
+                }
+            }
+        }
+    }
+
+    let schema = [("Shape", &["Point", "Circle", "Line", "Rect"][..])];
+    let check = |value: &dyn Serialize| {
+        let mut buf = Vec::new();
+        let mut serializer = serde_json::Serializer::new(&mut buf);
+        let mut inplace = <dyn Serializer>::new(&mut serializer);
+        let mut serializer = CheckVariantIndices::new(&mut inplace, &schema);
+        match value.dyn_serialize(&mut serializer) {
+            Ok(()) => Ok(String::from_utf8(buf).unwrap()),
+            Err(_) => match inplace {
+                InplaceSerializer::Error(error, _) => Err(error.to_string()),
+                _ => panic!("expected an error"),
+            },
+        }
+    };
+
+    let shapes = [
+        Shape::Point,
+        Shape::Circle(1),
+        Shape::Line(1, 2),
+        Shape::Rect { w: 1, h: 2 },
+    ];
+    assert_eq!(
+        check(&shapes),
+        Ok(r#"["Point",{"Circle":1},{"Line":[1,2]},{"Rect":{"w":1,"h":2}}]"#.to_owned()),
+    );
+
+    let errors = shapes.map(|shape| check(&[Mismatched(shape)]).unwrap_err());
+    assert_eq!(
+        errors,
+        [
+            "variant `Shape::Point` has index 0, not 1",
+            "variant `Shape::Circle` has index 1, not 2",
+            "unknown variant `Shape::Curve`",
+            "variant `Shape::Rect` has index 3, not 0",
+        ],
+    );
+
+    // enums missing from the schema are not checked.
+    let mut buf = Vec::new();
+    let mut serializer = serde_json::Serializer::new(&mut buf);
+    let mut serializer = <dyn Serializer>::new(&mut serializer);
+    let mut serializer = CheckVariantIndices::new(&mut serializer, &[]);
+    (&[Mismatched(Shape::Point)] as &dyn Serialize)
+        .dyn_serialize(&mut serializer)
+        .unwrap();
+    assert_eq!(buf, br#"["Point"]"#);
+}
+
+#[test]
+fn test_check_lengths() {
+    use std::collections::BTreeMap;
+
+    use dyn_serde::ser::{CheckLengths, InplaceSerializer};
+    use serde::ser::{SerializeSeq as _, SerializeStruct as _, SerializeTupleVariant as _};
+
+    #[derive(serde::Serialize)]
+    struct Point {
+        x: u8,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        y: Option<u8>,
+    }
+
+    /// A sequence whose length is not known up front.
+    struct Odd;
+
+    impl serde::Serialize for Odd {
+        fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            serializer.collect_seq((1..=5).filter(|n| n % 2 == 1))
+        }
+    }
+
+    /// Values serialized by hand, each one declaring a length off by one.
+    enum Handmade {
+        Struct,
+        Skipped,
+        Variant,
+        Seq,
+    }
+
+    impl serde::Serialize for Handmade {
+        fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            match self {
+                Handmade::Struct => {
+                    let mut point = serializer.serialize_struct("Point", 3)?;
+                    point.serialize_field("x", &1)?;
+                    point.serialize_field("y", &2)?;
+                    point.end()
+                }
+                Handmade::Skipped => {
+                    let mut point = serializer.serialize_struct("Point", 2)?;
+                    point.serialize_field("x", &1)?;
+                    point.skip_field("y")?;
+                    point.end()
+                }
+                Handmade::Variant => {
+                    let mut line = serializer.serialize_tuple_variant("Shape", 2, "Line", 1)?;
+                    line.serialize_field(&1)?;
+                    line.serialize_field(&2)?;
+                    line.end()
+                }
+                Handmade::Seq => {
+                    let mut seq = serializer.serialize_seq(Some(3))?;
+                    seq.serialize_element(&1)?;
+                    seq.serialize_element(&2)?;
+                    seq.end()
+                }
+            }
+        }
+    }
+
+    fn check(value: &dyn Serialize) -> Result<String, String> {
+        let mut buf = Vec::new();
+        let mut serializer = serde_json::Serializer::new(&mut buf);
+        let mut inplace = <dyn Serializer>::new(&mut serializer);
+        let mut serializer = CheckLengths::new(&mut inplace);
+        match value.dyn_serialize(&mut serializer) {
+            Ok(()) => Ok(String::from_utf8(buf).unwrap()),
+            Err(error) => match inplace {
+                InplaceSerializer::Error(error, _) => Err(error.to_string()),
+                _ => Err(error.to_string()),
+            },
+        }
+    }
+
+    // derived implementations, including skipped fields and nested values.
+    let points = [Point { x: 1, y: None }, Point { x: 2, y: Some(3) }];
+    let map = BTreeMap::from([("a", &points[..]), ("b", &[])]);
+    assert_eq!(
+        check(&map),
+        Ok(r#"{"a":[{"x":1},{"x":2,"y":3}],"b":[]}"#.to_owned())
+    );
+
+    // a sequence without a length is not checked.
+    assert_eq!(check(&Odd), Ok("[1,3,5]".to_owned()));
+
+    assert_eq!(
+        check(&[Handmade::Struct]),
+        Err("the length of struct `Point` is 2, not 3".to_owned())
+    );
+    assert_eq!(
+        check(&[Handmade::Skipped]),
+        Err("the length of struct `Point` is 1, not 2".to_owned())
+    );
+    assert_eq!(
+        check(&BTreeMap::from([("line", Handmade::Variant)])),
+        Err("the length of tuple variant `Shape::Line` is 2, not 1".to_owned())
+    );
+    assert_eq!(
+        check(&(0, Handmade::Seq)),
+        Err("the length of sequence is 2, not 3".to_owned())
+    );
+}
+
+#[test]
+fn test_const() {
+    use dyn_serde::ser::Const;
+
+    #[derive(serde::Serialize)]
+    struct Meta {
+        version: u8,
+        tags: &'static [&'static str],
+    }
+
+    const META: Const<'static> = Const::new(&Meta {
+        version: 2,
+        tags: &["a", "b"],
+    });
+
+    #[derive(serde::Serialize)]
+    struct Document<'a> {
+        #[serde(rename = "$meta")]
+        meta: Const<'static>,
+        body: &'a dyn Serialize,
+    }
+
+    let documents = [
+        Document {
+            meta: META,
+            body: &1,
+        },
+        Document {
+            meta: META,
+            body: &"two",
+        },
+    ];
+    make_serializer!(buf, serializer);
+    (&documents as &dyn Serialize)
+        .dyn_serialize(serializer)
+        .unwrap();
+    assert_eq!(
+        String::from_utf8(buf).unwrap(),
+        r#"[{"$meta":{"version":2,"tags":["a","b"]},"body":1},{"$meta":{"version":2,"tags":["a","b"]},"body":"two"}]"#
+    );
+
+    // the constant may also be built into a dynamically assembled object.
+    let object: Vec<(&str, &dyn Serialize)> = vec![("$meta", &META), ("id", &7)];
+    let object = dyn_serde::ser::SerializeMapIter::new(object.iter().copied());
+    assert_eq!(
+        serde_json::to_string(&object).unwrap(),
+        r#"{"$meta":{"version":2,"tags":["a","b"]},"id":7}"#
+    );
+}
+
+#[test]
+fn test_bytes_written() {
+    use std::cell::RefCell;
+
+    use dyn_serde::ser::{NormalizeInts, PrettyText, SerializeResult};
+
+    let mut output = String::new();
+    let mut serializer = PrettyText::new(&mut output, "\t");
+    assert_eq!(serializer.dyn_bytes_written(), Some(0));
+
+    // a wrapper answers for the serializer it wraps.
+    let wrapper = NormalizeInts::new(&mut serializer);
+    assert_eq!(wrapper.dyn_bytes_written(), Some(0));
+
+    (&["é", "b"] as &dyn Serialize)
+        .dyn_serialize(&mut serializer)
+        .unwrap();
+    assert_eq!(serializer.dyn_bytes_written(), Some(15));
+    assert_eq!(output, "[\n\t\"é\",\n\t\"b\"\n]");
+
+    // a serializer wrapping a `serde::Serializer` cannot tell.
+    make_serializer!(buf, serializer);
+    assert_eq!(serializer.dyn_bytes_written(), None);
+
+    /// Records the bytes written before beginning each of `.0` sequences
+    /// nested in each other.
+    struct Probe<'a>(usize, &'a RefCell<Vec<Option<u64>>>);
+
+    impl Serialize for Probe<'_> {
+        fn dyn_serialize(&self, serializer: &mut dyn Serializer) -> SerializeResult<()> {
+            self.1.borrow_mut().push(serializer.dyn_bytes_written());
+            let seq = serializer.dyn_serialize_seq(Some(1))?;
+            match self.0 {
+                0 => seq.dyn_serialize_element(&"a")?,
+                depth => seq.dyn_serialize_element(&Probe(depth - 1, self.1))?,
+            }
+            seq.dyn_end()?;
+            // the serializer has been lent to the sequence.
+            assert_eq!(serializer.dyn_bytes_written(), None);
+            Ok(())
+        }
+    }
+
+    // a wrapper answers for the serializer it wraps at every depth of the
+    // value, until it begins a compound.
+    let begun = RefCell::new(Vec::new());
+    let mut output = String::new();
+    let mut serializer = PrettyText::new(&mut output, "\t");
+    let mut wrapper = NormalizeInts::new(&mut serializer);
+    (&Probe(1, &begun) as &dyn Serialize)
+        .dyn_serialize(&mut wrapper)
+        .unwrap();
+    assert_eq!(begun.into_inner(), [Some(0), Some(3)]);
+    assert_eq!(wrapper.dyn_bytes_written(), None);
+    assert_eq!(serializer.dyn_bytes_written(), Some(15));
+    assert_eq!(output, "[\n\t[\n\t\t\"a\"\n\t]\n]");
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn test_framed() {
+    use std::io::Write as _;
+
+    use dyn_serde::ser::Framed;
+
+    // the check value of CRC-32.
+    let mut framed = Framed::new(Vec::new(), b"");
+    framed.write_all(b"123456789").unwrap();
+    assert_eq!(framed.checksum(), 0xCBF4_3926);
+
+    let value = ("frame", [1, 2, 3]);
+    let mut payload = Vec::new();
+    ciborium::into_writer(&value, &mut payload).unwrap();
+
+    let mut framed = Framed::new(Vec::new(), b"\x89DYN");
+    ciborium::into_writer(&value as &dyn Serialize, &mut framed).unwrap();
+    let checksum = framed.checksum();
+    let output = framed.finish().unwrap();
+    let (magic, rest) = output.split_at(4);
+    let (body, trailer) = rest.split_at(rest.len() - 4);
+    assert_eq!(magic, b"\x89DYN");
+    assert_eq!(body, payload);
+    assert_eq!(trailer, checksum.to_le_bytes());
+
+    // an empty payload is framed all the same.
+    let output = Framed::new(Vec::new(), b"\x89DYN").finish().unwrap();
+    assert_eq!(output, b"\x89DYN\0\0\0\0");
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn test_length_prefixed() {
+    use std::io::Read as _;
+
+    use dyn_serde::ser::LengthPrefixed;
+
+    #[derive(Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+    struct Message {
+        id: u32,
+        body: String,
+    }
+
+    let messages = [
+        Message {
+            id: 1,
+            body: "hello".to_owned(),
+        },
+        Message {
+            id: 2,
+            body: String::new(),
+        },
+    ];
+    let mut frames = LengthPrefixed::new(Vec::new());
+    for message in &messages {
+        let mut serializer = serde_json::Serializer::new(&mut frames);
+        let mut serializer = <dyn Serializer>::new(&mut serializer);
+        (message as &dyn Serialize)
+            .dyn_serialize(&mut serializer)
+            .unwrap();
+        frames.end_frame().unwrap();
+    }
+    let output = frames.finish().unwrap();
+
+    let mut reader = &output[..];
+    let mut read = Vec::new();
+    let mut len = [0; 4];
+    while reader.read_exact(&mut len).is_ok() {
+        let mut frame = vec![0; u32::from_le_bytes(len) as usize];
+        reader.read_exact(&mut frame).unwrap();
+        read.push(serde_json::from_slice::<Message>(&frame).unwrap());
+    }
+    assert_eq!(read, messages);
+
+    // the last frame is ended when finishing, unless it is empty.
+    let mut frames = LengthPrefixed::new(Vec::new());
+    ciborium::into_writer(&[1, 2] as &dyn Serialize, &mut frames).unwrap();
+    assert_eq!(frames.finish().unwrap(), b"\x03\0\0\0\x82\x01\x02");
+    assert_eq!(LengthPrefixed::new(Vec::new()).finish().unwrap(), b"");
+
+    // the partial output of a value which fails is discarded, not framed.
+    let values = [
+        &std::collections::BTreeMap::from([((1, 2), 3)]) as &dyn Serialize,
+        &"ok",
+    ];
+    let mut frames = LengthPrefixed::new(Vec::new());
+    for value in values {
+        let mut serializer = serde_json::Serializer::new(&mut frames);
+        let mut serializer = <dyn Serializer>::new(&mut serializer);
+        match value.dyn_serialize(&mut serializer) {
+            Ok(()) => frames.end_frame().unwrap(),
+            Err(_) => frames.discard_frame(),
+        }
+    }
+    assert_eq!(frames.finish().unwrap(), b"\x04\0\0\0\"ok\"");
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn test_line_serializer_bytes_written() {
+    use dyn_serde::ser::LineSerializer;
+
+    let mut serializer = LineSerializer::new(Vec::new());
+    (&(1, "two") as &dyn Serialize)
+        .dyn_serialize(&mut serializer)
+        .unwrap();
+    assert_eq!(serializer.dyn_bytes_written(), Some(6));
+    (&3 as &dyn Serialize)
+        .dyn_serialize(&mut serializer)
+        .unwrap();
+    assert_eq!(serializer.dyn_bytes_written(), Some(8));
+    assert_eq!(serializer.into_inner(), b"1 two\n3\n");
+}
+
+#[test]
+fn test_normalize_ints() {
+    use dyn_serde::ser::{
+        InplaceSerializeResult, NormalizeInts, SerializeMap, SerializeSeq, SerializeStruct,
+        SerializeStructVariant, SerializeTuple, SerializeTupleStruct, SerializeTupleVariant,
+    };
+
+    /// Records the type of every integer it is given.
+    #[derive(Default)]
+    struct Widths(Vec<String>);
+
+    impl Widths {
+        fn record(&mut self, width: String) -> InplaceSerializeResult<()> {
+            self.0.push(width);
+            Ok(())
+        }
+    }
+
+    impl Serializer for Widths {
+        fn dyn_serialize_bool(&mut self, _: bool) -> InplaceSerializeResult<()> {
+            unreachable!()
+        }
+
+        fn dyn_serialize_i8(&mut self, v: i8) -> InplaceSerializeResult<()> {
+            self.record(format!("{v}i8"))
+        }
+
+        fn dyn_serialize_i32(&mut self, v: i32) -> InplaceSerializeResult<()> {
+            self.record(format!("{v}i32"))
+        }
+
+        fn dyn_serialize_i64(&mut self, v: i64) -> InplaceSerializeResult<()> {
+            self.record(format!("{v}i64"))
+        }
+
+        fn dyn_serialize_i128(&mut self, v: i128) -> InplaceSerializeResult<()> {
+            self.record(format!("{v}i128"))
+        }
+
+        fn dyn_serialize_u8(&mut self, v: u8) -> InplaceSerializeResult<()> {
+            self.record(format!("{v}u8"))
+        }
+
+        fn dyn_serialize_u32(&mut self, v: u32) -> InplaceSerializeResult<()> {
+            self.record(format!("{v}u32"))
+        }
+
+        fn dyn_serialize_u64(&mut self, v: u64) -> InplaceSerializeResult<()> {
+            self.record(format!("{v}u64"))
+        }
+
+        fn dyn_serialize_u128(&mut self, v: u128) -> InplaceSerializeResult<()> {
+            self.record(format!("{v}u128"))
+        }
+
+        fn dyn_serialize_f64(&mut self, _: f64) -> InplaceSerializeResult<()> {
+            unreachable!()
+        }
+
+        fn dyn_serialize_str(&mut self, _: &str) -> InplaceSerializeResult<()> {
+            unreachable!()
+        }
+
+        fn dyn_serialize_bytes(&mut self, _: &[u8]) -> InplaceSerializeResult<()> {
+            unreachable!()
+        }
+
+        fn dyn_serialize_none(&mut self) -> InplaceSerializeResult<()> {
+            unreachable!()
+        }
+
+        fn dyn_serialize_some(&mut self, _: &dyn Serialize) -> InplaceSerializeResult<()> {
+            unreachable!()
+        }
+
+        fn dyn_serialize_unit(&mut self) -> InplaceSerializeResult<()> {
+            unreachable!()
+        }
+
+        fn dyn_serialize_unit_variant(
+            &mut self,
+            _: &'static str,
+            _: u32,
+            _: &'static str,
+        ) -> InplaceSerializeResult<()> {
+            unreachable!()
+        }
+
+        fn dyn_serialize_newtype_struct(
+            &mut self,
+            _: &'static str,
+            _: &dyn Serialize,
+        ) -> InplaceSerializeResult<()> {
+            unreachable!()
+        }
+
+        fn dyn_serialize_newtype_variant(
+            &mut self,
+            _: &'static str,
+            _: u32,
+            _: &'static str,
+            _: &dyn Serialize,
+        ) -> InplaceSerializeResult<()> {
+            unreachable!()
+        }
+
+        fn dyn_serialize_seq(
+            &mut self,
+            _: Option<usize>,
+        ) -> InplaceSerializeResult<&mut dyn SerializeSeq> {
+            unreachable!()
+        }
+
+        fn dyn_serialize_tuple(
+            &mut self,
+            _: usize,
+        ) -> InplaceSerializeResult<&mut dyn SerializeTuple> {
+            unreachable!()
+        }
+
+        fn dyn_serialize_tuple_struct(
+            &mut self,
+            _: &'static str,
+            _: usize,
+        ) -> InplaceSerializeResult<&mut dyn SerializeTupleStruct> {
+            unreachable!()
+        }
+
+        fn dyn_serialize_tuple_variant(
+            &mut self,
+            _: &'static str,
+            _: u32,
+            _: &'static str,
+            _: usize,
+        ) -> InplaceSerializeResult<&mut dyn SerializeTupleVariant> {
+            unreachable!()
+        }
+
+        fn dyn_serialize_map(
+            &mut self,
+            _: Option<usize>,
+        ) -> InplaceSerializeResult<&mut dyn SerializeMap> {
+            unreachable!()
+        }
+
+        fn dyn_serialize_struct(
+            &mut self,
+            _: &'static str,
+            _: usize,
+        ) -> InplaceSerializeResult<&mut dyn SerializeStruct> {
+            unreachable!()
+        }
+
+        fn dyn_serialize_struct_variant(
+            &mut self,
+            _: &'static str,
+            _: u32,
+            _: &'static str,
+            _: usize,
+        ) -> InplaceSerializeResult<&mut dyn SerializeStructVariant> {
+            unreachable!()
+        }
+    }
+
+    let mut widths = Widths::default();
+    5u8.dyn_serialize(&mut widths).unwrap();
+    5u32.dyn_serialize(&mut widths).unwrap();
+    assert_eq!(widths.0, ["5u8", "5u32"]);
+
+    let mut widths = Widths::default();
+    let values: [&dyn Serialize; 8] = [
+        &5u8,
+        &5u32,
+        &-5i8,
+        &-5i32,
+        &5u128,
+        &-5i128,
+        &u128::MAX,
+        &i128::MIN,
+    ];
+    for value in values {
+        value
+            .dyn_serialize(&mut NormalizeInts::new(&mut widths))
+            .unwrap();
+    }
+    assert_eq!(
+        widths.0,
+        [
+            "5u64".to_owned(),
+            "5u64".to_owned(),
+            "-5i64".to_owned(),
+            "-5i64".to_owned(),
+            "5u64".to_owned(),
+            "-5i64".to_owned(),
+            format!("{}u128", u128::MAX),
+            format!("{}i128", i128::MIN),
+        ]
+    );
+
+    // nested values are normalized too.
+    make_serializer!(buf, serializer);
+    let mut normalized = NormalizeInts::new(serializer);
+    (&(5u8, [5u32], Some(5u16)) as &dyn Serialize)
+        .dyn_serialize(&mut normalized)
+        .unwrap();
+    assert_eq!(buf, b"[5,[5],5]");
+}
+
+#[test]
+fn test_inplace_serializer_try_into_ok() {
+    use dyn_serde::ser::{InplaceSerializer, InplaceSerializerState};
+
+    let mut serializer = <dyn Serializer>::new(serde_json::value::Serializer);
+    (&("x", 1) as &dyn Serialize)
+        .dyn_serialize(&mut serializer)
+        .unwrap();
+    assert_eq!(
+        serializer.try_into_ok().ok(),
+        Some(serde_json::json!(["x", 1]))
+    );
+
+    // not done yet.
+    let serializer = <dyn Serializer>::new(serde_json::value::Serializer);
+    let serializer = serializer.try_into_ok().unwrap_err();
+    assert_eq!(serializer.state(), InplaceSerializerState::Serializer);
+
+    // done unsuccessfully.
+    let mut serializer = <dyn Serializer>::new(serde_json::value::Serializer);
+    let map = std::collections::BTreeMap::from([((1, 2), 3)]);
+    (&map as &dyn Serialize)
+        .dyn_serialize(&mut serializer)
+        .unwrap_err();
+    let Err(InplaceSerializer::Error(error, _)) = serializer.try_into_ok() else {
+        panic!("expected an error");
+    };
+    assert_eq!(error.to_string(), "key must be a string");
+}
+
+#[test]
+fn test_tag_structs() {
+    use dyn_serde::ser::TagStructs;
+
+    #[derive(serde::Serialize)]
+    struct Shape {
+        name: &'static str,
+        center: Point,
+        points: Vec<Point>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        color: Option<&'static str>,
+    }
+
+    #[derive(serde::Serialize)]
+    struct Point {
+        x: i32,
+        y: i32,
+    }
+
+    let shape = Shape {
+        name: "line",
+        center: Point { x: 0, y: 0 },
+        points: vec![Point { x: -1, y: 0 }, Point { x: 1, y: 0 }],
+        color: None,
+    };
+
+    make_serializer!(buf, serializer);
+    let mut tagged = TagStructs::new(serializer, "type", &"shape");
+    (&shape as &dyn Serialize)
+        .dyn_serialize(&mut tagged)
+        .unwrap();
+    assert_eq!(
+        std::str::from_utf8(&buf).unwrap(),
+        concat!(
+            r#"{"type":"shape","name":"line","center":{"type":"shape","x":0,"y":0},"#,
+            r#""points":[{"type":"shape","x":-1,"y":0},{"type":"shape","x":1,"y":0}]}"#,
+        )
+    );
+
+    // the tag may be any value.
+    let mut serializer = <dyn Serializer>::new(serde_json::value::Serializer);
+    let mut tagged = TagStructs::new(&mut serializer, "v", &2);
+    (&Point { x: 1, y: 2 } as &dyn Serialize)
+        .dyn_serialize(&mut tagged)
+        .unwrap();
+    let value = serializer.try_into_ok().ok().unwrap();
+    assert_eq!(value, serde_json::json!({"v": 2, "x": 1, "y": 2}));
+}
+
+#[test]
+fn test_duration_secs() {
+    use std::time::Duration;
+
+    use dyn_serde::ser::DurationSecs;
+
+    let durations = [
+        Duration::ZERO,
+        Duration::from_millis(1500),
+        Duration::from_secs(86400),
+    ];
+    for duration in durations.map(DurationSecs) {
+        // through the dynamic serializer.
+        make_serializer!(buf, serializer);
+        (&duration as &dyn Serialize)
+            .dyn_serialize(serializer)
+            .unwrap();
+        assert_eq!(buf, serde_json::to_vec(&duration.0.as_secs_f64()).unwrap());
+        assert_eq!(
+            serde_json::from_slice::<DurationSecs>(&buf).unwrap(),
+            duration
+        );
+
+        // a binary format encodes the same float.
+        let mut cbor = Vec::new();
+        ciborium::into_writer(&duration as &dyn Serialize, &mut cbor).unwrap();
+        let mut expected = Vec::new();
+        ciborium::into_writer(&duration.0.as_secs_f64(), &mut expected).unwrap();
+        assert_eq!(cbor, expected);
+        assert_eq!(
+            ciborium::from_reader::<DurationSecs, _>(&cbor[..]).unwrap(),
+            duration
+        );
+    }
+
+    assert_eq!(
+        serde_json::from_str::<DurationSecs>("3").unwrap(),
+        DurationSecs(Duration::from_secs(3))
+    );
+    assert_eq!(
+        serde_json::from_str::<DurationSecs>("-1.5")
+            .unwrap_err()
+            .to_string(),
+        "invalid value: floating point `-1.5`, expected a non-negative number of seconds at line 1 column 4"
+    );
+    assert_eq!(
+        serde_json::from_str::<DurationSecs>("-1")
+            .unwrap_err()
+            .to_string(),
+        "invalid value: integer `-1`, expected a non-negative number of seconds at line 1 column 2"
+    );
+}
+
+#[test]
+fn test_serialize_some_stateful() {
+    use std::cell::{Cell, OnceCell};
+
+    /// Computes its content on the first serialization only.
+    struct Lazy<'a> {
+        value: OnceCell<Vec<u32>>,
+        computed: &'a Cell<usize>,
+    }
+
+    impl serde::Serialize for Lazy<'_> {
+        fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            let value = self.value.get_or_init(|| {
+                self.computed.set(self.computed.get() + 1);
+                (1..=3).collect()
+            });
+            value.serialize(serializer)
+        }
+    }
+
+    let computed = Cell::new(0);
+    let lazy = Lazy {
+        value: OnceCell::new(),
+        computed: &computed,
+    };
+    for _ in 0..2 {
+        make_serializer!(buf, serializer);
+        serializer.dyn_serialize_some(&lazy).unwrap();
+        assert_eq!(buf, b"[1,2,3]");
+    }
+    assert_eq!(computed.get(), 1);
+}
+
+#[test]
+fn test_pretty_text() {
+    use dyn_serde::ser::PrettyText;
+
+    #[derive(serde::Serialize)]
+    struct Config {
+        name: &'static str,
+        server: Server,
+        tags: Vec<&'static str>,
+        mode: Mode,
+    }
+
+    #[derive(serde::Serialize)]
+    struct Server {
+        host: &'static str,
+        ports: Vec<u16>,
+        ratio: f64,
+    }
+
+    #[derive(serde::Serialize)]
+    enum Mode {
+        Fixed { retries: u8 },
+    }
+
+    fn pretty(value: &dyn Serialize, indent: &str) -> String {
+        let mut output = String::new();
+        let mut serializer = PrettyText::new(&mut output, indent);
+        value.dyn_serialize(&mut serializer).unwrap();
+        assert!(serializer.error().is_none());
+        output
+    }
+
+    let config = Config {
+        name: "a \"quoted\"\tname",
+        server: Server {
+            host: "localhost",
+            ports: vec![80, 443],
+            ratio: 1.0,
+        },
+        tags: vec![],
+        mode: Mode::Fixed { retries: 3 },
+    };
+
+    // two spaces are the indentation of `serde_json::to_string_pretty`.
+    let expected = serde_json::to_string_pretty(&config).unwrap();
+    assert_eq!(pretty(&config, "  "), expected);
+    assert_eq!(pretty(&config, "\t"), expected.replace("  ", "\t"));
+
+    let server = Server {
+        host: "h",
+        ports: vec![1],
+        ratio: f64::NAN,
+    };
+    assert_eq!(
+        pretty(&server, "    "),
+        "{\n    \"host\": \"h\",\n    \"ports\": [\n        1\n    ],\n    \"ratio\": null\n}"
+    );
+}
+
+#[test]
+fn test_present_fields() {
+    use dyn_serde::ser::present_fields;
+
+    #[derive(serde::Serialize)]
+    struct Profile {
+        id: u64,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        email: Option<String>,
+        #[serde(rename = "phoneNumber", skip_serializing_if = "Option::is_none")]
+        phone: Option<String>,
+        #[serde(skip)]
+        #[allow(dead_code)]
+        cache: Vec<u8>,
+        tags: Vec<String>,
+    }
+
+    #[derive(serde::Serialize)]
+    enum Change {
+        Rename {
+            #[serde(skip_serializing_if = "Option::is_none")]
+            from: Option<&'static str>,
+            to: &'static str,
+        },
+    }
+
+    let before = Profile {
+        id: 1,
+        email: None,
+        phone: Some("555".into()),
+        cache: vec![0; 1024],
+        tags: vec![],
+    };
+    let after = Profile {
+        id: 1,
+        email: Some("a@b.c".into()),
+        phone: None,
+        cache: vec![],
+        tags: vec![],
+    };
+    assert_eq!(
+        present_fields(&before).unwrap(),
+        ["id", "phoneNumber", "tags"]
+    );
+    assert_eq!(present_fields(&after).unwrap(), ["id", "email", "tags"]);
+    assert_eq!(
+        present_fields(&Some(&after)).unwrap(),
+        ["id", "email", "tags"]
+    );
+
+    let change = Change::Rename {
+        from: None,
+        to: "b",
+    };
+    assert_eq!(present_fields(&change).unwrap(), ["to"]);
+
+    let error = present_fields(&[1, 2]).unwrap_err();
+    assert_eq!(error.to_string(), "expected a struct");
+}
+
+#[test]
+#[cfg(feature = "serde_json")]
+fn test_buffered() {
+    use std::collections::BTreeMap;
+    use std::net::Ipv4Addr;
+
+    use dyn_serde::ser::{Buffered, InplaceSerializer};
+
+    #[derive(serde::Serialize)]
+    struct Packet {
+        id: u32,
+        payload: Vec<u8>,
+    }
+
+    fn buffered(
+        value: &dyn Serialize,
+        transform: impl FnOnce(&mut serde_json::Value),
+    ) -> Result<String, String> {
+        let mut output = Vec::new();
+        {
+            let mut serializer = serde_json::Serializer::new(&mut output);
+            let mut serializer = <dyn Serializer>::new(&mut serializer);
+            let mut serializer = Buffered::new(&mut serializer, transform);
+            if value.dyn_serialize(&mut serializer).is_err() {
+                return Err(serializer.error().unwrap().to_string());
+            }
+            assert!(serializer.error().is_none());
+        }
+        Ok(String::from_utf8(output).unwrap())
+    }
+
+    let packet = Packet {
+        id: 7,
+        payload: vec![1, 2, 250],
+    };
+    let output = buffered(&packet, |value| {
+        let object = value.as_object_mut().unwrap();
+        let checksum = object["payload"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|byte| byte.as_u64().unwrap())
+            .fold(0u8, |sum, byte| sum.wrapping_add(byte as u8));
+        object.insert("checksum".into(), checksum.into());
+    });
+    assert_eq!(
+        output.unwrap(),
+        r#"{"checksum":253,"id":7,"payload":[1,2,250]}"#
+    );
+
+    // errors raised while buffering are kept, and the transform is not called.
+    let value = BTreeMap::from([((1, 2), 3)]);
+    assert_eq!(
+        buffered(&value, |_| unreachable!()).unwrap_err(),
+        "key must be a string"
+    );
+
+    /// Serializes the value through `Buffered` into any format.
+    struct Binary<'a>(&'a dyn Serialize);
+
+    impl serde::Serialize for Binary<'_> {
+        fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            let mut serializer = <dyn Serializer>::new(serializer);
+            let result = self
+                .0
+                .dyn_serialize(&mut Buffered::new(&mut serializer, |_| {}));
+            match serializer {
+                InplaceSerializer::Ok(ok, _) => Ok(ok),
+                InplaceSerializer::Error(error, _) => Err(error),
+                _ => panic!("unexpected error: {}", result.unwrap_err()),
+            }
+        }
+    }
+
+    // the values at every depth are buffered in the form of the wrapped
+    // format, e.g. addresses as octets rather than strings for CBOR.
+    let addrs = BTreeMap::from([(1, vec![Some(Ipv4Addr::new(10, 0, 0, 1))])]);
+    let mut cbor = Vec::new();
+    ciborium::into_writer(&Binary(&addrs), &mut cbor).unwrap();
+    let value: ciborium::Value = ciborium::from_reader(&cbor[..]).unwrap();
+    let (_, addrs) = &value.as_map().unwrap()[0];
+    assert!(addrs.as_array().unwrap()[0].is_array());
+}
+
+#[test]
+fn test_result_as_object() {
+    use dyn_serde::ser::ResultAsObject;
+
+    #[derive(Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+    struct Reply {
+        id: u32,
+        tags: Vec<String>,
+    }
+
+    #[derive(Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+    struct Failure {
+        code: i16,
+    }
+
+    let reply = Reply {
+        id: 7,
+        tags: vec!["a".into()],
+    };
+    let failure = Failure { code: -2 };
+    let cases: [(ResultAsObject, &[u8]); 2] = [
+        (
+            ResultAsObject(Ok(&reply)),
+            br#"{"Ok":{"id":7,"tags":["a"]}}"#,
+        ),
+        (ResultAsObject(Err(&failure)), br#"{"Err":{"code":-2}}"#),
+    ];
+    for (value, expected) in cases {
+        make_serializer!(buf, serializer);
+        (&value as &dyn Serialize)
+            .dyn_serialize(serializer)
+            .unwrap();
+        assert_eq!(buf, expected);
+    }
+
+    // the objects deserialize back into a `Result`.
+    let results = [Ok(reply), Err(failure)];
+    for result in results {
+        let json = serde_json::to_vec(&ResultAsObject::from(&result)).unwrap();
+        let roundtrip: Result<Reply, Failure> = serde_json::from_slice(&json).unwrap();
+        assert_eq!(roundtrip, result);
+    }
+
+    let result: Result<(), &str> = Err("boom");
+    assert_eq!(
+        format!("{:?}", ResultAsObject::from(&result)),
+        r#"ResultAsObject("Err")"#
+    );
+}
+
+#[test]
+fn test_versioned() {
+    use dyn_serde::ser::Versioned;
+
+    #[derive(serde::Serialize)]
+    struct User {
+        name: &'static str,
+        admin: bool,
+    }
+
+    let user = User {
+        name: "ferris",
+        admin: false,
+    };
+    let values: [&dyn Serialize; 3] = [&user, &[1, 2], &"x"];
+    let expected = [
+        r#"{"v":3,"data":{"name":"ferris","admin":false}}"#,
+        r#"{"v":3,"data":[1,2]}"#,
+        r#"{"v":3,"data":"x"}"#,
+    ];
+    for (value, expected) in values.into_iter().zip(expected) {
+        let versioned = Versioned::new(3, value);
+        assert_eq!(versioned.version(), 3);
+        make_serializer!(buf, serializer);
+        (&versioned as &dyn Serialize)
+            .dyn_serialize(serializer)
+            .unwrap();
+        assert_eq!(String::from_utf8(buf).unwrap(), expected);
+    }
+
+    // binary formats write the envelope as well.
+    let mut buf = Vec::new();
+    ciborium::into_writer(&Versioned::new(1, &user), &mut buf).unwrap();
+    let value: ciborium::Value = ciborium::from_reader(&buf[..]).unwrap();
+    let entries = value.into_map().unwrap();
+    assert_eq!(entries[0].0.as_text(), Some("v"));
+    assert_eq!(entries[0].1.as_integer(), Some(1.into()));
+    assert_eq!(entries[1].0.as_text(), Some("data"));
+}
+
+#[test]
+fn test_field_sizes() {
+    use std::cell::Cell;
+    use std::collections::BTreeMap;
+    use std::io::{self, Write};
+
+    use dyn_serde::ser::FieldSizes;
+
+    struct Counter<'a>(Vec<u8>, &'a Cell<usize>);
+
+    impl Write for Counter<'_> {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.1.set(self.1.get() + buf.len());
+            self.0.write(buf)
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[derive(serde::Serialize)]
+    struct Response {
+        id: u64,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        cursor: Option<String>,
+        author: Author,
+        items: Vec<String>,
+    }
+
+    #[derive(serde::Serialize)]
+    struct Author {
+        name: String,
+    }
+
+    let response = Response {
+        id: 12345,
+        cursor: None,
+        author: Author {
+            name: "alice".into(),
+        },
+        items: (0..50).map(|i| format!("item {i}")).collect(),
+    };
+
+    let written = Cell::new(0);
+    let mut sizes = BTreeMap::new();
+    let output = {
+        let mut serializer = serde_json::Serializer::new(Counter(Vec::new(), &written));
+        let mut inplace = <dyn Serializer>::new(&mut serializer);
+        let mut measured = FieldSizes::new(&mut inplace, &written, &mut sizes);
+        (&response as &dyn Serialize)
+            .dyn_serialize(&mut measured)
+            .unwrap();
+        serializer.into_inner().0
+    };
+    assert_eq!(output, serde_json::to_vec(&response).unwrap());
+
+    // the fields of nested structs are not measured, nor are skipped fields.
+    assert_eq!(
+        sizes.keys().copied().collect::<Vec<_>>(),
+        ["author", "id", "items"]
+    );
+    assert_eq!(sizes["id"], r#""id":12345"#.len());
+    assert_eq!(sizes["author"], r#","author":{"name":"alice"}"#.len());
+    let largest = sizes.iter().max_by_key(|(_, size)| **size).unwrap();
+    assert_eq!(*largest.0, "items");
+
+    // only the braces of the struct belong to no field.
+    assert_eq!(sizes.values().sum::<usize>() + 2, output.len());
+    assert_eq!(written.get(), output.len());
+}
+
+#[test]
+fn test_stringify_keys() {
+    use std::collections::{BTreeMap, HashMap};
+
+    use ciborium::Value;
+    use dyn_serde::ser::{InplaceSerializer, StringifyKeys};
+
+    #[derive(serde::Serialize, PartialEq, Eq, PartialOrd, Ord)]
+    struct Id(u8);
+
+    /// Serializes the value through `StringifyKeys` into any format.
+    struct StrKeys<'a>(&'a dyn Serialize);
+
+    impl serde::Serialize for StrKeys<'_> {
+        fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            let mut serializer = <dyn Serializer>::new(serializer);
+            let result = self
+                .0
+                .dyn_serialize(&mut StringifyKeys::new(&mut serializer));
+            match serializer {
+                InplaceSerializer::Ok(ok, _) => Ok(ok),
+                InplaceSerializer::Error(error, _) => Err(error),
+                _ => panic!("unexpected error: {}", result.unwrap_err()),
+            }
+        }
+    }
+
+    let value = HashMap::from([(7u32, "seven".to_owned())]);
+    make_serializer!(buf, serializer);
+    (&value as &dyn Serialize)
+        .dyn_serialize(&mut StringifyKeys::new(serializer))
+        .unwrap();
+    assert_eq!(buf, br#"{"7":"seven"}"#);
+    let value: HashMap<u32, String> = serde_json::from_slice(&buf).unwrap();
+    assert_eq!(value, HashMap::from([(7, "seven".to_owned())]));
+
+    fn to_cbor(value: &dyn Serialize) -> Value {
+        let mut cbor = Vec::new();
+        ciborium::into_writer(&StrKeys(value), &mut cbor).unwrap();
+        ciborium::from_reader(&cbor[..]).unwrap()
+    }
+
+    assert_eq!(
+        to_cbor(&BTreeMap::from([(-1i128, 1), (u64::MAX.into(), 2)])),
+        Value::Map(vec![
+            (Value::from("-1"), Value::from(1)),
+            (Value::from("18446744073709551615"), Value::from(2)),
+        ]),
+    );
+    assert_eq!(
+        to_cbor(&BTreeMap::from([(false, 'a'), (true, 'b')])),
+        Value::Map(vec![
+            (Value::from("false"), Value::from("a")),
+            (Value::from("true"), Value::from("b")),
+        ]),
+    );
+    // keys behind a newtype are converted, nested maps at every depth, values
+    // and keys which have no string form are left unchanged.
+    let nested = BTreeMap::from([(Some(Id(1)), BTreeMap::from([('x', 2)]))]);
+    assert_eq!(
+        to_cbor(&nested),
+        Value::Map(vec![(
+            Value::from("1"),
+            Value::Map(vec![(Value::from("x"), Value::from(2))]),
+        )]),
+    );
+    assert_eq!(
+        to_cbor(&BTreeMap::from([((1, 2), 3)])),
+        Value::Map(vec![(
+            Value::Array(vec![Value::from(1), Value::from(2)]),
+            Value::from(3),
+        )]),
+    );
+}
+
+#[test]
+fn test_map_keys_as_ints() {
+    use std::collections::BTreeMap;
+
+    use ciborium::Value;
+    use dyn_serde::ser::{InplaceSerializer, MapKeysAsInts};
+
+    #[derive(serde::Serialize, PartialEq, Eq, PartialOrd, Ord)]
+    enum Code {
+        #[serde(rename = "200")]
+        Ok,
+        #[serde(rename = "404")]
+        NotFound,
+        Other,
+    }
+
+    #[derive(serde::Serialize, PartialEq, Eq, PartialOrd, Ord)]
+    struct Id(&'static str);
+
+    /// Serializes the value through `MapKeysAsInts` into any format.
+    struct IntKeys<'a>(&'a dyn Serialize);
+
+    impl serde::Serialize for IntKeys<'_> {
+        fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            let mut serializer = <dyn Serializer>::new(serializer);
+            let result = self
+                .0
+                .dyn_serialize(&mut MapKeysAsInts::new(&mut serializer));
+            match serializer {
+                InplaceSerializer::Ok(ok, _) => Ok(ok),
+                InplaceSerializer::Error(error, _) => Err(error),
+                _ => panic!("unexpected error: {}", result.unwrap_err()),
+            }
+        }
+    }
+
+    fn to_cbor(value: &dyn Serialize) -> Value {
+        let mut cbor = Vec::new();
+        ciborium::into_writer(&IntKeys(value), &mut cbor).unwrap();
+        ciborium::from_reader(&cbor[..]).unwrap()
+    }
+
+    let strings = BTreeMap::from([
+        ("1", 1),
+        ("-2", 2),
+        ("0", 3),
+        ("x", 4),
+        ("007", 5),
+        ("-0", 6),
+        ("+8", 7),
+        ("18446744073709551616", 8),
+    ]);
+    let Value::Map(entries) = to_cbor(&strings) else {
+        panic!("expected a map");
+    };
+    let keys: Vec<_> = entries.into_iter().map(|(key, _)| key).collect();
+    assert_eq!(
+        keys,
+        [
+            Value::from("+8"),
+            Value::from("-0"),
+            Value::from(-2),
+            Value::from(0),
+            Value::from("007"),
+            Value::from(1),
+            Value::from("18446744073709551616"),
+            Value::from("x"),
+        ]
+    );
+
+    // keys of enums, newtypes and nested maps, but not values.
+    let enums = BTreeMap::from([(Code::Ok, "1"), (Code::NotFound, "2"), (Code::Other, "3")]);
+    let nested = BTreeMap::from([(Id("10"), enums)]);
+    assert_eq!(
+        to_cbor(&nested),
+        Value::Map(vec![(
+            Value::from(10),
+            Value::Map(vec![
+                (Value::from(200), Value::from("1")),
+                (Value::from(404), Value::from("2")),
+                (Value::from("Other"), Value::from("3")),
+            ])
+        )])
+    );
+
+    // formats with string keys write them back as strings.
+    let json = serde_json::to_string(&IntKeys(&strings)).unwrap();
+    assert_eq!(json, serde_json::to_string(&strings).unwrap());
+}
+
+#[test]
+fn test_ordinal_variants() {
+    use dyn_serde::Deserializer;
+    use dyn_serde::ser::OrdinalVariants;
+
+    #[derive(Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+    enum Level {
+        Low,
+        Medium,
+        High,
+        Custom(u8),
+    }
+
+    fn to_json(value: &dyn Serialize) -> String {
+        let mut buf = Vec::new();
+        let mut serializer = serde_json::Serializer::new(&mut buf);
+        let mut serializer = <dyn Serializer>::new(&mut serializer);
+        let mut serializer = OrdinalVariants::new(&mut serializer);
+        value.dyn_serialize(&mut serializer).unwrap();
+        String::from_utf8(buf).unwrap()
+    }
+
+    // every unit variant as its index, at every depth.
+    let levels = vec![Level::Low, Level::Medium, Level::High];
+    assert_eq!(to_json(&levels), "[0,1,2]");
+    assert_eq!(to_json(&(Some(Level::High), "Low")), r#"[2,"Low"]"#);
+
+    // variants with content are left unchanged.
+    assert_eq!(to_json(&Level::Custom(7)), r#"{"Custom":7}"#);
+
+    // read back by the deserializer.
+    let json = to_json(&(&levels, Level::Custom(7)));
+    let mut deserializer = serde_json::Deserializer::from_str(&json);
+    let mut deserializer = <dyn Deserializer>::new(&mut deserializer);
+    let mut deserializer = dyn_serde::de::OrdinalVariants::new(&mut deserializer);
+    let value: (Vec<Level>, Level) =
+        serde::Deserialize::deserialize(&mut deserializer as &mut dyn Deserializer).unwrap();
+    assert_eq!(value, (levels, Level::Custom(7)));
+}
+
+#[test]
+fn test_big_num_as_string() {
+    use dyn_serde::ser::BigNumAsString;
+
+    fn to_json(value: &BigNumAsString) -> String {
+        serde_json::to_string(value).unwrap()
+    }
+
+    const SAFE: u64 = (1 << 53) - 1;
+
+    // around the boundary of the safe range of JavaScript.
+    let value = [SAFE - 1, SAFE, SAFE + 1, SAFE + 2];
+    assert_eq!(
+        to_json(&BigNumAsString::new(&value)),
+        r#"[9007199254740990,9007199254740991,"9007199254740992","9007199254740993"]"#
+    );
+    let value = [-(SAFE as i64), -(SAFE as i64) - 1];
+    assert_eq!(
+        to_json(&BigNumAsString::new(&value)),
+        r#"[-9007199254740991,"-9007199254740992"]"#
+    );
+
+    // every width, at every depth, but not floats.
+    let value = (
+        u64::MAX,
+        i64::MIN,
+        u128::MAX,
+        i128::MIN,
+        Some(vec![1u8, 2]),
+        1.5,
+    );
+    assert_eq!(
+        to_json(&BigNumAsString::new(&value)),
+        format!(
+            r#"["{}","{}","{}","{}",[1,2],1.5]"#,
+            u64::MAX,
+            i64::MIN,
+            u128::MAX,
+            i128::MIN
+        )
+    );
+
+    // a custom threshold.
+    let value = std::collections::BTreeMap::from([("a", 100u16), ("b", 101)]);
+    let big_nums = BigNumAsString::with_threshold(&value, 100);
+    assert_eq!(big_nums.threshold(), 100);
+    assert_eq!(to_json(&big_nums), r#"{"a":100,"b":"101"}"#);
+    assert_eq!(
+        to_json(&BigNumAsString::with_threshold(&[-1i8, 0], 0)),
+        r#"["-1",0]"#
+    );
+}
+
+#[test]
+fn test_with_human_readable() {
+    use std::cell::Cell;
+
+    use dyn_serde::ser::{InplaceSerializer, NormalizeInts, WithHumanReadable};
+    use dyn_serde::{Serialize, Serializer};
+
+    /// Serializes the value, then records whether the wrappers are
+    /// human-readable once the serialization has finished.
+    struct Probe<'a>(&'a dyn Serialize, &'a Cell<Option<(bool, bool, bool)>>);
+
+    impl serde::Serialize for Probe<'_> {
+        fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            let mut serializer = <dyn Serializer>::new(serializer);
+            let mut fixed = WithHumanReadable::new(&mut serializer);
+            let mut normalized = NormalizeInts::new(&mut fixed);
+            self.0.dyn_serialize(&mut normalized).unwrap();
+            self.1.set(Some((
+                normalized.dyn_is_human_readable(),
+                fixed.dyn_is_human_readable(),
+                serializer.dyn_is_human_readable(),
+            )));
+            match serializer {
+                InplaceSerializer::Ok(ok, _) => Ok(ok),
+                InplaceSerializer::Error(error, _) => Err(error),
+                _ => unreachable!(),
+            }
+        }
+    }
+
+    // a binary format.
+    let flags = Cell::new(None);
+    let mut cbor = Vec::new();
+    ciborium::into_writer(&Probe(&[1, 2], &flags), &mut cbor).unwrap();
+    assert_eq!(cbor, b"\x82\x01\x02");
+    assert_eq!(flags.get(), Some((false, false, false)));
+
+    // a text format.
+    let json = serde_json::to_string(&Probe(&[1, 2], &flags)).unwrap();
+    assert_eq!(json, "[1,2]");
+    assert_eq!(flags.get(), Some((true, true, true)));
+
+    // an explicit answer.
+    let mut serializer = <dyn Serializer>::new(serde_json::value::Serializer);
+    let serializer = WithHumanReadable::fixed(&mut serializer, false);
+    assert!(!serializer.dyn_is_human_readable());
+}
+
+#[test]
+fn test_inplace_is_human_readable() {
+    use std::cell::Cell;
+    use std::io;
+
+    use dyn_serde::ser::{InplaceSerializer, InplaceSerializerState, SerializeSeq};
+
+    type Flags = (bool, InplaceSerializerState, bool);
+
+    /// Serializes `[1]`, then records whether the in-place serializer is
+    /// human-readable within the sequence, and in which state it has finished.
+    struct Probe<'a>(&'a Cell<Option<Flags>>);
+
+    impl serde::Serialize for Probe<'_> {
+        fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            let mut serializer = <dyn Serializer>::new(serializer);
+            let _ = serializer
+                .dyn_serialize_seq(Some(1))
+                .and_then(|seq| seq.dyn_serialize_element(&1));
+            let within = Serializer::dyn_is_human_readable(&serializer);
+            let _ = SerializeSeq::dyn_end(&mut serializer);
+            self.0.set(Some((
+                within,
+                serializer.state(),
+                serializer.dyn_is_human_readable(),
+            )));
+            match serializer {
+                InplaceSerializer::Ok(ok, _) => Ok(ok),
+                InplaceSerializer::Error(error, _) => Err(error),
+                _ => unreachable!(),
+            }
+        }
+    }
+
+    /// A writer failing on every write.
+    struct Broken;
+
+    impl io::Write for Broken {
+        fn write(&mut self, _: &[u8]) -> io::Result<usize> {
+            Err(io::ErrorKind::BrokenPipe.into())
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    // a binary format keeps its answer in every state.
+    let flags = Cell::new(None);
+    let mut cbor = Vec::new();
+    ciborium::into_writer(&Probe(&flags), &mut cbor).unwrap();
+    assert_eq!(cbor, b"\x81\x01");
+    assert_eq!(
+        flags.get(),
+        Some((false, InplaceSerializerState::Ok, false))
+    );
+
+    assert!(ciborium::into_writer(&Probe(&flags), Broken).is_err());
+    assert_eq!(
+        flags.get(),
+        Some((false, InplaceSerializerState::Error, false))
+    );
+
+    // a text format.
+    serde_json::to_string(&Probe(&flags)).unwrap();
+    assert_eq!(flags.get(), Some((true, InplaceSerializerState::Ok, true)));
+}
+
+#[test]
+fn test_compact() {
+    use dyn_serde::ser::Compact;
+
+    /// A timestamp written as text for humans, and as seconds otherwise.
+    struct Stamp(u64);
+
+    impl serde::Serialize for Stamp {
+        fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            if serializer.is_human_readable() {
+                serializer.collect_str(&format_args!("t+{}s", self.0))
+            } else {
+                serializer.serialize_u64(self.0)
+            }
+        }
+    }
+
+    #[derive(serde::Serialize)]
+    struct Event<'a> {
+        at: Stamp,
+        history: Compact<'a, [Stamp]>,
+        next: Option<Compact<'a, Stamp>>,
+    }
+
+    let event = Event {
+        at: Stamp(3),
+        history: Compact(&[Stamp(1), Stamp(2)]),
+        next: Some(Compact(&Stamp(4))),
+    };
+    let expected = r#"{"at":"t+3s","history":[1,2],"next":4}"#;
+    assert_eq!(serde_json::to_string(&event).unwrap(), expected);
+
+    make_serializer!(buf, serializer);
+    (&event as &dyn Serialize)
+        .dyn_serialize(serializer)
+        .unwrap();
+    assert_eq!(String::from_utf8(buf).unwrap(), expected);
+
+    // a binary format is not human-readable anyway.
+    let mut cbor = Vec::new();
+    ciborium::into_writer(&(Stamp(1), Compact(&Stamp(1))), &mut cbor).unwrap();
+    assert_eq!(cbor, b"\x82\x01\x01");
+}
+
+#[test]
+fn test_shortest_floats() {
+    use dyn_serde::ser::ShortestFloats;
+
+    fn to_json(value: &dyn Serialize) -> String {
+        serde_json::to_string(&ShortestFloats::new(value)).unwrap()
+    }
+
+    // values whose decimal form is not exact, at the edges of the range.
+    assert_eq!(to_json(&0.1), r#""0.1""#);
+    assert_eq!(to_json(&(0.1 + 0.2)), r#""0.30000000000000004""#);
+    assert_eq!(to_json(&1e300), r#""1e300""#);
+    assert_eq!(to_json(&-1e-300), r#""-1e-300""#);
+    assert_eq!(to_json(&5e-324), r#""5e-324""#);
+    assert_eq!(to_json(&f64::MAX), r#""1.7976931348623157e308""#);
+    assert_eq!(to_json(&1.0), r#""1.0""#);
+    assert_eq!(to_json(&-0.0), r#""-0.0""#);
+    assert_eq!(to_json(&0.1f32), r#""0.1""#);
+    assert_eq!(to_json(&16_777_217f32), r#""16777216.0""#);
+    assert_eq!(
+        to_json(&[f64::NAN, f64::INFINITY, f64::NEG_INFINITY]),
+        r#"["NaN","inf","-inf"]"#
+    );
+
+    // at every depth, leaving integers alone.
+    let value = (1u8, Some(vec![(2.5f32, "x")]));
+    assert_eq!(to_json(&value), r#"[1,[["2.5","x"]]]"#);
+
+    // a binary format keeps the exact bits.
+    let mut cbor = Vec::new();
+    ciborium::into_writer(&ShortestFloats::new(&0.1f32), &mut cbor).unwrap();
+    assert_eq!(cbor, b"\xfa\x3d\xcc\xcc\xcd");
+}
+
+#[test]
+fn test_hex_chunks() {
+    use dyn_serde::ser::HexChunks;
+
+    // 40 bytes, in chunks of 16 by default.
+    let bytes: Vec<u8> = (0..40).collect();
+    let chunks = HexChunks::new(&bytes);
+    assert_eq!(chunks.chunk_size(), 16);
+    assert_eq!(
+        serde_json::to_string(&chunks).unwrap(),
+        concat!(
+            r#"["000102030405060708090a0b0c0d0e0f","#,
+            r#""101112131415161718191a1b1c1d1e1f","#,
+            r#""2021222324252627"]"#,
+        )
+    );
+
+    // a chunk size dividing the length, a single chunk, and no bytes.
+    let chunks = HexChunks::with_chunk_size(&[0xff, 0xab, 0x0c, 0x00], 2);
+    assert_eq!(
+        serde_json::to_string(&chunks).unwrap(),
+        r#"["ffab","0c00"]"#
+    );
+    let chunks = HexChunks::with_chunk_size(&[0xff, 0xab, 0x0c], 0);
+    assert_eq!(serde_json::to_string(&chunks).unwrap(), r#"["ffab0c"]"#);
+    assert_eq!(serde_json::to_string(&HexChunks::new(&[])).unwrap(), "[]");
+
+    // through a dynamic serializer.
+    make_serializer!(buf, serializer);
+    (&HexChunks::with_chunk_size(b"dyn", 1) as &dyn Serialize)
+        .dyn_serialize(serializer)
+        .unwrap();
+    assert_eq!(buf, br#"["64","79","6e"]"#);
+}
+
+#[test]
+fn test_chunked_seq() {
+    use std::cell::RefCell;
+    use std::collections::BTreeMap;
+    use std::io::{self, Write};
+    use std::rc::Rc;
+
+    use serde::ser::Error as _;
+
+    use dyn_serde::ser::{ChunkedSeq, SerializeError};
+    use dyn_serde::{Serialize, Serializer};
+
+    /// A writer whose output is shared with the callback.
+    #[derive(Clone, Default)]
+    struct Sink(Rc<RefCell<Vec<u8>>>);
+
+    impl Write for Sink {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.borrow_mut().write(buf)
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    /// Serializes `value` in chunks of `chunk` elements, returning the count
+    /// and the output at every call of the callback.
+    fn chunks(value: &dyn Serialize, chunk: usize) -> Vec<(usize, String)> {
+        let sink = Sink::default();
+        let mut calls = Vec::new();
+        let mut serializer = serde_json::Serializer::new(sink.clone());
+        let mut serializer = <dyn Serializer>::new(&mut serializer);
+        let mut serializer = ChunkedSeq::new(&mut serializer, chunk, |count| {
+            let output = String::from_utf8(sink.0.borrow().clone()).unwrap();
+            calls.push((count, output));
+            Ok(())
+        });
+        value.dyn_serialize(&mut serializer).unwrap();
+        drop(serializer);
+        calls
+    }
+
+    let value: Vec<u32> = (1..=10).collect();
+    assert_eq!(
+        chunks(&value, 4),
+        [(4, "[1,2,3,4".into()), (8, "[1,2,3,4,5,6,7,8".into())]
+    );
+    let counts: Vec<_> = chunks(&value, 5).into_iter().map(|(c, _)| c).collect();
+    assert_eq!(counts, [5, 10]);
+    assert_eq!(chunks(&value, 11), []);
+    assert_eq!(chunks(&value, 0), []);
+
+    // only the elements of the value itself count.
+    let nested = Some(vec![vec![1, 2, 3], vec![], vec![4]]);
+    let counts: Vec<_> = chunks(&nested, 1).into_iter().map(|(c, _)| c).collect();
+    assert_eq!(counts, [1, 2, 3]);
+    assert_eq!(chunks(&(1, 2, 3), 1), []);
+    assert_eq!(chunks(&BTreeMap::from([(1, [2, 3])]), 1), []);
+
+    // a failing callback stops the serialization.
+    let mut output = Vec::new();
+    let mut serializer = serde_json::Serializer::new(&mut output);
+    let mut serializer = <dyn Serializer>::new(&mut serializer);
+    let mut serializer = ChunkedSeq::new(&mut serializer, 3, |count| {
+        Err(SerializeError::custom(format!("full at {count}")))
+    });
+    assert!(
+        (&value as &dyn Serialize)
+            .dyn_serialize(&mut serializer)
+            .is_err()
+    );
+    assert_eq!(serializer.count(), 3);
+    drop(serializer);
+    assert_eq!(output, b"[1,2,3");
+}
+
+#[test]
+fn test_flatten_dotted() {
+    use std::collections::BTreeMap;
+
+    use dyn_serde::ser::{FlattenDotted, PrettyText};
+
+    #[derive(serde::Serialize)]
+    struct Config {
+        name: &'static str,
+        server: Server,
+        limits: BTreeMap<u16, Vec<u32>>,
+        mode: Mode,
+        backup: Option<Server>,
+    }
+
+    #[derive(serde::Serialize)]
+    struct Server {
+        host: &'static str,
+        port: u16,
+        tls: Option<bool>,
+    }
+
+    #[derive(serde::Serialize)]
+    enum Mode {
+        Fast { threads: u8 },
+    }
+
+    fn flatten(value: &dyn Serialize) -> Result<String, String> {
+        let mut output = String::new();
+        let mut serializer = PrettyText::new(&mut output, "");
+        let mut serializer = FlattenDotted::new(&mut serializer);
+        match value.dyn_serialize(&mut serializer) {
+            Ok(()) => Ok(output),
+            Err(error) => Err(serializer.error().unwrap_or(&error).to_string()),
+        }
+    }
+
+    let config = Config {
+        name: "app",
+        server: Server {
+            host: "localhost",
+            port: 8080,
+            tls: None,
+        },
+        limits: BTreeMap::from([(1, vec![10, 20]), (2, vec![])]),
+        mode: Mode::Fast { threads: 4 },
+        backup: None,
+    };
+    assert_eq!(
+        flatten(&config).unwrap(),
+        "{\n\"name\": \"app\",\n\"server.host\": \"localhost\",\n\"server.port\": 8080,\n\
+         \"limits.1.0\": 10,\n\"limits.1.1\": 20,\n\"mode.Fast.threads\": 4\n}"
+    );
+    assert_eq!(
+        flatten(&Some(BTreeMap::from([("a", 1)]))).unwrap(),
+        "{\n\"a\": 1\n}"
+    );
+    assert_eq!(
+        flatten(&1).unwrap_err(),
+        "only a compound value can be flattened"
+    );
+}
+
+#[test]
+#[cfg(feature = "serde_json")]
+fn test_filter_fields() {
+    use std::cell::RefCell;
+    use std::collections::BTreeMap;
+
+    use dyn_serde::ser::FilterFields;
+
+    #[derive(serde::Serialize)]
+    struct Profile {
+        name: &'static str,
+        bio: &'static str,
+        age: u8,
+        address: Address,
+        tags: BTreeMap<&'static str, &'static str>,
+    }
+
+    #[derive(serde::Serialize)]
+    struct Address {
+        city: &'static str,
+        street: &'static str,
+    }
+
+    let profile = Profile {
+        name: "ferris",
+        bio: "",
+        age: 0,
+        address: Address {
+            city: "",
+            street: "main",
+        },
+        tags: BTreeMap::from([("empty", "")]),
+    };
+
+    let fields = RefCell::new(Vec::new());
+    let mut buf = Vec::new();
+    {
+        let predicate = |key: &'static str, value: &serde_json::Value| {
+            fields.borrow_mut().push(key);
+            value != ""
+        };
+        let mut serializer = serde_json::Serializer::new(&mut buf);
+        let mut serializer = <dyn Serializer>::new(&mut serializer);
+        let mut serializer = FilterFields::new(&mut serializer, predicate);
+        (&profile as &dyn Serialize)
+            .dyn_serialize(&mut serializer)
+            .unwrap();
+    }
+    // the fields of nested structs are filtered too, the entries of maps are
+    // not.
+    assert_eq!(
+        String::from_utf8(buf).unwrap(),
+        r#"{"name":"ferris","age":0,"address":{"street":"main"},"tags":{"empty":""}}"#
+    );
+    // the fields of a nested struct are filtered when it is captured too.
+    assert_eq!(
+        fields.into_inner(),
+        [
+            "name", "bio", "age", "city", "street", "address", "city", "street", "tags"
+        ]
+    );
+}
+
+#[test]
+fn test_order_fields() {
+    use std::net::Ipv4Addr;
+
+    use dyn_serde::ser::{InplaceSerializer, OrderFields};
+
+    #[derive(serde::Serialize)]
+    struct Declared {
+        c: u8,
+        b: u8,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        a: Option<u8>,
+        x: u8,
+    }
+
+    #[derive(serde::Serialize)]
+    struct Reversed {
+        x: u8,
+        a: Option<u8>,
+        b: u8,
+        c: u8,
+    }
+
+    #[derive(serde::Serialize)]
+    enum Event {
+        Point { y: i32, x: i32 },
+    }
+
+    fn ordered(value: &dyn Serialize, order: &[&str]) -> String {
+        let mut buf = Vec::new();
+        let mut serializer = serde_json::Serializer::new(&mut buf);
+        let mut serializer = <dyn Serializer>::new(&mut serializer);
+        let mut serializer = OrderFields::new(&mut serializer, order);
+        value.dyn_serialize(&mut serializer).unwrap();
+        String::from_utf8(buf).unwrap()
+    }
+
+    let order = ["a", "b", "c", "x", "y"];
+    let declared = Declared {
+        c: 3,
+        b: 2,
+        a: Some(1),
+        x: 0,
+    };
+    let reversed = Reversed {
+        x: 0,
+        a: Some(1),
+        b: 2,
+        c: 3,
+    };
+    let expected = r#"{"a":1,"b":2,"c":3,"x":0}"#;
+    assert_eq!(ordered(&declared, &order), expected);
+    assert_eq!(ordered(&reversed, &order), expected);
+
+    // unlisted fields follow in their order, skipped fields are left out.
+    let declared = Declared {
+        a: None,
+        ..declared
+    };
+    assert_eq!(ordered(&declared, &["x"]), r#"{"x":0,"c":3,"b":2}"#);
+    assert_eq!(ordered(&declared, &[]), r#"{"c":3,"b":2,"x":0}"#);
+
+    // struct variants and the structs in sequences are ordered too.
+    let events = [Event::Point { y: 2, x: 1 }];
+    assert_eq!(ordered(&events, &order), r#"[{"Point":{"x":1,"y":2}}]"#);
+    assert_eq!(
+        ordered(&vec![reversed], &order),
+        r#"[{"a":1,"b":2,"c":3,"x":0}]"#
+    );
+
+    #[derive(serde::Serialize, serde::Deserialize, PartialEq, Debug)]
+    struct Peer {
+        id: u128,
+        #[serde(with = "serde_bytes")]
+        key: Vec<u8>,
+        addr: Ipv4Addr,
+    }
+
+    /// Serializes the value through `OrderFields` into any format.
+    struct Ordered<'a>(&'a dyn Serialize, &'a [&'a str]);
+
+    impl serde::Serialize for Ordered<'_> {
+        fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            let mut serializer = <dyn Serializer>::new(serializer);
+            let result = self
+                .0
+                .dyn_serialize(&mut OrderFields::new(&mut serializer, self.1));
+            match serializer {
+                InplaceSerializer::Ok(ok, _) => Ok(ok),
+                InplaceSerializer::Error(error, _) => Err(error),
+                _ => panic!("unexpected error: {}", result.unwrap_err()),
+            }
+        }
+    }
+
+    // binary formats get the buffered fields in their own form, addresses as
+    // octets, bytes and 128-bit integers unchanged.
+    let peer = Peer {
+        id: u128::MAX,
+        key: vec![0, 255],
+        addr: Ipv4Addr::new(10, 0, 0, 1),
+    };
+    let mut cbor = Vec::new();
+    ciborium::into_writer(&Ordered(&peer, &["addr", "key"]), &mut cbor).unwrap();
+    let value: ciborium::Value = ciborium::from_reader(&cbor[..]).unwrap();
+    let keys: Vec<_> = value
+        .as_map()
+        .unwrap()
+        .iter()
+        .map(|(key, _)| key.as_text().unwrap())
+        .collect();
+    assert_eq!(keys, ["addr", "key", "id"]);
+    assert!(value.as_map().unwrap()[0].1.is_array());
+    assert_eq!(ciborium::from_reader::<Peer, _>(&cbor[..]).unwrap(), peer);
+}
+
+#[test]
+fn test_adjacently_tagged() {
+    use std::net::Ipv4Addr;
+
+    use dyn_serde::ser::{AdjacentlyTagged, InplaceSerializer};
+    use serde_bytes::ByteBuf;
+
+    #[derive(serde::Serialize)]
+    enum Shape {
+        Empty,
+        Circle(f64),
+        Rect(u32, u32),
+        Group { name: String, shapes: Vec<Shape> },
+    }
+
+    /// The same enum, adjacently tagged by serde.
+    #[derive(serde::Serialize)]
+    #[serde(tag = "kind", content = "value")]
+    enum Tagged {
+        Empty,
+        Circle(f64),
+        Rect(u32, u32),
+        Group { name: String, shapes: Vec<Tagged> },
+    }
+
+    fn tagged(value: &dyn Serialize, tag: &'static str, content: &'static str) -> String {
+        let mut buf = Vec::new();
+        let mut serializer = serde_json::Serializer::new(&mut buf);
+        let mut serializer = <dyn Serializer>::new(&mut serializer);
+        let mut serializer = AdjacentlyTagged::new(&mut serializer, tag, content);
+        value.dyn_serialize(&mut serializer).unwrap();
+        String::from_utf8(buf).unwrap()
+    }
+
+    // every kind of variant.
+    assert_eq!(tagged(&Shape::Empty, "t", "c"), r#"{"t":"Empty"}"#);
+    assert_eq!(
+        tagged(&Shape::Circle(1.5), "t", "c"),
+        r#"{"t":"Circle","c":1.5}"#
+    );
+    assert_eq!(
+        tagged(&Shape::Rect(2, 3), "t", "c"),
+        r#"{"t":"Rect","c":[2,3]}"#
+    );
+    assert_eq!(
+        tagged(
+            &Shape::Group {
+                name: "g".into(),
+                shapes: vec![],
+            },
+            "t",
+            "c"
+        ),
+        r#"{"t":"Group","c":{"name":"g","shapes":[]}}"#
+    );
+
+    // variants at every depth, and the same output as serde.
+    let shapes = vec![
+        Shape::Group {
+            name: "outer".into(),
+            shapes: vec![Shape::Rect(1, 2), Shape::Empty, Shape::Circle(0.5)],
+        },
+        Shape::Empty,
+    ];
+    let expected = vec![
+        Tagged::Group {
+            name: "outer".into(),
+            shapes: vec![Tagged::Rect(1, 2), Tagged::Empty, Tagged::Circle(0.5)],
+        },
+        Tagged::Empty,
+    ];
+    assert_eq!(
+        tagged(&shapes, "kind", "value"),
+        serde_json::to_string(&expected).unwrap()
+    );
+
+    // custom keys, while the other values are left unchanged.
+    let value = (Some(Shape::Circle(2.0)), [1, 2], "Empty");
+    assert_eq!(
+        tagged(&value, "type", "data"),
+        r#"[{"type":"Circle","data":2.0},[1,2],"Empty"]"#
+    );
+
+    #[derive(serde::Serialize)]
+    enum Packet {
+        Route(Ipv4Addr, #[serde(with = "serde_bytes")] Vec<u8>, u128),
+        Host { addr: Ipv4Addr, id: u128 },
+    }
+
+    #[derive(serde::Deserialize, PartialEq, Debug)]
+    #[serde(tag = "t", content = "c")]
+    enum TaggedPacket {
+        Route(Ipv4Addr, ByteBuf, u128),
+        Host { addr: Ipv4Addr, id: u128 },
+    }
+
+    /// Serializes the value through `AdjacentlyTagged` into any format.
+    struct Adjacent<'a>(&'a dyn Serialize);
+
+    impl serde::Serialize for Adjacent<'_> {
+        fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            let mut serializer = <dyn Serializer>::new(serializer);
+            let result =
+                self.0
+                    .dyn_serialize(&mut AdjacentlyTagged::new(&mut serializer, "t", "c"));
+            match serializer {
+                InplaceSerializer::Ok(ok, _) => Ok(ok),
+                InplaceSerializer::Error(error, _) => Err(error),
+                _ => panic!("unexpected error: {}", result.unwrap_err()),
+            }
+        }
+    }
+
+    // binary formats get the buffered content in their own form, addresses as
+    // octets, bytes and 128-bit integers unchanged.
+    let addr = Ipv4Addr::new(10, 0, 0, 1);
+    let packets = [
+        Packet::Route(addr, vec![0, 255], u128::MAX),
+        Packet::Host { addr, id: 1 << 100 },
+    ];
+    let mut cbor = Vec::new();
+    ciborium::into_writer(&Adjacent(&packets), &mut cbor).unwrap();
+    let packets: Vec<TaggedPacket> = ciborium::from_reader(&cbor[..]).unwrap();
+    assert_eq!(
+        packets,
+        [
+            TaggedPacket::Route(addr, ByteBuf::from([0, 255]), u128::MAX),
+            TaggedPacket::Host { addr, id: 1 << 100 },
+        ]
+    );
+}
+
+#[test]
+fn test_collect_str_inline_buffer() {
+    use dyn_serde::ser::collect_str;
+    use std::fmt;
+
+    /// Writes `piece` `count` times, one `write_str` at a time.
+    struct Repeat(&'static str, usize);
+
+    impl fmt::Display for Repeat {
+        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            (0..self.1).try_for_each(|_| f.write_str(self.0))
+        }
+    }
+
+    fn collect(value: &dyn fmt::Display) -> String {
+        make_serializer!(buf, serializer);
+        collect_str(serializer, value).unwrap();
+        serde_json::from_slice(&buf).unwrap()
+    }
+
+    // short values stay on the stack, up to the last byte of the buffer.
+    assert_eq!(collect(&Repeat("ab", 3)), "ababab");
+    assert_eq!(collect(&Repeat("abcd", 32)), "abcd".repeat(32));
+    assert_eq!(collect(&""), "");
+
+    // longer values spill, even when a piece straddles the end of the buffer.
+    assert_eq!(collect(&Repeat("abc", 43)), "abc".repeat(43));
+    assert_eq!(collect(&Repeat("é", 100)), "é".repeat(100));
+    assert_eq!(collect(&"x".repeat(1000)), "x".repeat(1000));
+}
+
+#[test]
+fn test_dedup_objects() {
+    use dyn_serde::ser::DedupObjects;
+    use std::collections::BTreeMap;
+
+    #[derive(Clone, serde::Serialize)]
+    struct Address {
+        city: &'static str,
+        zip: u32,
+    }
+
+    #[derive(serde::Serialize)]
+    struct Person {
+        name: &'static str,
+        home: Address,
+        work: Option<Address>,
+    }
+
+    #[derive(serde::Serialize)]
+    enum Event {
+        Moved(Address),
+        Visited { at: Address },
+    }
+
+    fn dedup(value: &dyn Serialize) -> (String, usize) {
+        make_serializer!(buf, serializer);
+        let mut serializer = DedupObjects::new(serializer, "$id", "$ref");
+        value.dyn_serialize(&mut serializer).unwrap();
+        let len = serializer.len();
+        drop(serializer);
+        (String::from_utf8(buf).unwrap(), len)
+    }
+
+    let paris = Address {
+        city: "Paris",
+        zip: 75000,
+    };
+    let lyon = Address {
+        city: "Lyon",
+        zip: 69000,
+    };
+
+    // repeated objects are referenced, at every depth.
+    let people = vec![
+        Person {
+            name: "a",
+            home: paris.clone(),
+            work: Some(lyon.clone()),
+        },
+        Person {
+            name: "b",
+            home: lyon.clone(),
+            work: Some(paris.clone()),
+        },
+        Person {
+            name: "a",
+            home: paris.clone(),
+            work: Some(lyon.clone()),
+        },
+    ];
+    assert_eq!(
+        dedup(&people),
+        (
+            concat!(
+                r#"[{"$id":0,"name":"a","home":{"$id":1,"city":"Paris","zip":75000},"#,
+                r#""work":{"$id":2,"city":"Lyon","zip":69000}},"#,
+                r#"{"$id":3,"name":"b","home":{"$ref":2},"work":{"$ref":1}},"#,
+                r#"{"$ref":0}]"#,
+            )
+            .to_owned(),
+            4
+        )
+    );
+
+    // maps are objects, identical to the structs of the same entries, and
+    // their keys are left unchanged.
+    let map = BTreeMap::from([
+        ("x", BTreeMap::from([("city", "Paris")])),
+        ("y", BTreeMap::new()),
+    ]);
+    let value = (
+        &map,
+        Address {
+            city: "Paris",
+            zip: 1,
+        },
+        BTreeMap::<&str, u8>::new(),
+    );
+    assert_eq!(
+        dedup(&value).0,
+        concat!(
+            r#"[{"$id":0,"x":{"$id":1,"city":"Paris"},"y":{"$id":2}},"#,
+            r#"{"$id":3,"city":"Paris","zip":1},{"$ref":2}]"#,
+        )
+    );
+
+    // the value itself, sequences, variants and their content are never
+    // referenced, while the fields of struct variants are.
+    let events = vec![
+        vec![
+            Event::Moved(paris.clone()),
+            Event::Visited { at: paris.clone() },
+        ],
+        vec![
+            Event::Moved(paris.clone()),
+            Event::Visited { at: paris.clone() },
+        ],
+    ];
+    assert_eq!(
+        dedup(&events),
+        (
+            concat!(
+                r#"[[{"Moved":{"city":"Paris","zip":75000}},"#,
+                r#"{"Visited":{"at":{"$id":0,"city":"Paris","zip":75000}}}],"#,
+                r#"[{"Moved":{"city":"Paris","zip":75000}},{"Visited":{"at":{"$ref":0}}}]]"#,
+            )
+            .to_owned(),
+            1
+        )
+    );
+    assert_eq!(
+        dedup(&paris),
+        (r#"{"city":"Paris","zip":75000}"#.to_owned(), 0)
+    );
+
+    // objects which fail to canonicalize are written in full.
+    let nan = [
+        BTreeMap::from([("x", f64::NAN)]),
+        BTreeMap::from([("x", f64::NAN)]),
+    ];
+    assert_eq!(dedup(&nan), (r#"[{"x":null},{"x":null}]"#.to_owned(), 0));
+}
+
+#[test]
+fn test_serialize_entry_forwarded() {
+    use dyn_serde::ser::{DedupObjects, InplaceSerializer, MapKeysAsInts};
+    use serde::ser::{Error as _, Impossible};
+    use std::cell::RefCell;
+    use std::collections::BTreeMap;
+    use std::rc::Rc;
+
+    type Error = serde::de::value::Error;
+    type Calls = Rc<RefCell<Vec<String>>>;
+
+    /// A backend which only permits maps of scalars, one entry at a time, and
+    /// records the calls on its maps.
+    struct EntryOnly(Calls);
+
+    struct EntryOnlyMap(Calls);
+
+    macro_rules! scalars {
+        ($($method:ident($ty:ty)),*) => {
+            $(fn $method(self, v: $ty) -> Result<String, Error> {
+                Ok(format!("{v:?}"))
+            })*
+        };
+    }
+
+    macro_rules! unsupported {
+        ($($method:ident($($arg:ty),*) -> $ret:ty),*) => {
+            $(fn $method(self, $(_: $arg),*) -> Result<$ret, Error> {
+                Err(Error::custom(stringify!($method)))
+            })*
+        };
+    }
+
+    impl serde::Serializer for EntryOnly {
+        type Ok = String;
+        type Error = Error;
+        type SerializeSeq = Impossible<String, Error>;
+        type SerializeTuple = Impossible<String, Error>;
+        type SerializeTupleStruct = Impossible<String, Error>;
+        type SerializeTupleVariant = Impossible<String, Error>;
+        type SerializeMap = EntryOnlyMap;
+        type SerializeStruct = Impossible<String, Error>;
+        type SerializeStructVariant = Impossible<String, Error>;
+
+        scalars!(
+            serialize_bool(bool),
+            serialize_i8(i8),
+            serialize_i16(i16),
+            serialize_i32(i32),
+            serialize_i64(i64),
+            serialize_u8(u8),
+            serialize_u16(u16),
+            serialize_u32(u32),
+            serialize_u64(u64),
+            serialize_f32(f32),
+            serialize_f64(f64),
+            serialize_char(char),
+            serialize_str(&str),
+            serialize_bytes(&[u8])
+        );
+
+        unsupported!(
+            serialize_none() -> String,
+            serialize_unit() -> String,
+            serialize_unit_struct(&'static str) -> String,
+            serialize_unit_variant(&'static str, u32, &'static str) -> String,
+            serialize_seq(Option<usize>) -> Self::SerializeSeq,
+            serialize_tuple(usize) -> Self::SerializeTuple,
+            serialize_tuple_struct(&'static str, usize) -> Self::SerializeTupleStruct,
+            serialize_tuple_variant(&'static str, u32, &'static str, usize)
+                -> Self::SerializeTupleVariant,
+            serialize_struct(&'static str, usize) -> Self::SerializeStruct,
+            serialize_struct_variant(&'static str, u32, &'static str, usize)
+                -> Self::SerializeStructVariant
+        );
+
+        fn serialize_some<T: ?Sized + serde::Serialize>(self, v: &T) -> Result<String, Error> {
+            v.serialize(self)
+        }
+
+        fn serialize_newtype_struct<T: ?Sized + serde::Serialize>(
+            self,
+            _: &'static str,
+            v: &T,
+        ) -> Result<String, Error> {
+            v.serialize(self)
+        }
+
+        fn serialize_newtype_variant<T: ?Sized + serde::Serialize>(
+            self,
+            _: &'static str,
+            _: u32,
+            _: &'static str,
+            _: &T,
+        ) -> Result<String, Error> {
+            Err(Error::custom("serialize_newtype_variant"))
+        }
+
+        fn serialize_map(self, _: Option<usize>) -> Result<EntryOnlyMap, Error> {
+            Ok(EntryOnlyMap(self.0))
+        }
+    }
+
+    impl serde::ser::SerializeMap for EntryOnlyMap {
+        type Ok = String;
+        type Error = Error;
+
+        fn serialize_key<T: ?Sized + serde::Serialize>(&mut self, _: &T) -> Result<(), Error> {
+            self.0.borrow_mut().push("serialize_key".into());
+            Err(Error::custom("entries only"))
+        }
+
+        fn serialize_value<T: ?Sized + serde::Serialize>(&mut self, _: &T) -> Result<(), Error> {
+            self.0.borrow_mut().push("serialize_value".into());
+            Err(Error::custom("entries only"))
+        }
+
+        fn serialize_entry<K, V>(&mut self, key: &K, value: &V) -> Result<(), Error>
+        where
+            K: ?Sized + serde::Serialize,
+            V: ?Sized + serde::Serialize,
+        {
+            let key = key.serialize(EntryOnly(self.0.clone()))?;
+            let value = value.serialize(EntryOnly(self.0.clone()))?;
+            self.0
+                .borrow_mut()
+                .push(format!("serialize_entry({key}, {value})"));
+            Ok(())
+        }
+
+        fn end(self) -> Result<String, Error> {
+            Ok("end".into())
+        }
+    }
+
+    fn calls(wrap: impl FnOnce(&mut dyn Serializer) -> Result<(), String>) -> Vec<String> {
+        let calls = Calls::default();
+        let mut serializer = <dyn Serializer>::new(EntryOnly(calls.clone()));
+        wrap(&mut serializer).unwrap();
+        assert!(matches!(serializer, InplaceSerializer::Ok(ok, _) if ok == "end"));
+        calls.take()
+    }
+
+    let map = BTreeMap::from([("1", Some(10)), ("b", Some(20))]);
+    let expected = [r#"serialize_entry("1", 10)"#, r#"serialize_entry("b", 20)"#];
+
+    // the entries reach the backend as such, whether they are serialized by
+    // serde or with the dynamic methods.
+    let entries = calls(|serializer| {
+        (&map as &dyn Serialize)
+            .dyn_serialize(serializer)
+            .map_err(|error| error.to_string())
+    });
+    assert_eq!(entries, expected);
+    let entries = calls(|serializer| {
+        let map = serializer.dyn_serialize_map(Some(2)).unwrap();
+        map.dyn_serialize_entry(&"1", &10).unwrap();
+        map.dyn_serialize_entry(&"b", &20).unwrap();
+        map.dyn_end().map_err(|error| error.to_string())
+    });
+    assert_eq!(entries, expected);
+
+    // and through the wrappers rewriting keys or values.
+    let entries = calls(|serializer| {
+        (&map as &dyn Serialize)
+            .dyn_serialize(&mut MapKeysAsInts::new(serializer))
+            .map_err(|error| error.to_string())
+    });
+    assert_eq!(
+        entries,
+        ["serialize_entry(1, 10)", r#"serialize_entry("b", 20)"#]
+    );
+    let entries = calls(|serializer| {
+        (&map as &dyn Serialize)
+            .dyn_serialize(&mut DedupObjects::new(serializer, "$id", "$ref"))
+            .map_err(|error| error.to_string())
+    });
+    assert_eq!(entries, expected);
+}
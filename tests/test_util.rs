@@ -0,0 +1,156 @@
+//! Test `dyn_serde::test_util::roundtrip` with `serde_json` as the backend.
+
+#![cfg(feature = "test-util")]
+
+use std::collections::BTreeMap;
+
+use dyn_serde::test_util::roundtrip;
+use dyn_serde::{Deserializer, Serializer};
+
+fn json<T>(value: &T) -> Vec<u8>
+where
+    T: serde::Serialize + serde::de::DeserializeOwned + PartialEq + std::fmt::Debug,
+{
+    roundtrip(
+        value,
+        |output, f| {
+            f(&mut <dyn Serializer>::new(
+                &mut serde_json::Serializer::new(output),
+            ))
+        },
+        |input, f| {
+            f(&mut <dyn Deserializer>::new(
+                &mut serde_json::Deserializer::from_slice(input),
+            ));
+        },
+    )
+}
+
+#[derive(Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+struct Unit;
+
+#[derive(Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+struct Newtype(u32);
+
+#[derive(Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+struct Tuple(i8, String);
+
+#[derive(Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+struct Struct {
+    x: f64,
+    y: Option<char>,
+}
+
+#[derive(Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+enum Enum {
+    Unit,
+    Newtype(bool),
+    Tuple(u8, u8),
+    Struct { z: i64 },
+}
+
+/// A value of every type of the serde data model.
+#[derive(Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+struct DataModel {
+    bool: bool,
+    i8: i8,
+    i16: i16,
+    i32: i32,
+    i64: i64,
+    i128: i128,
+    u8: u8,
+    u16: u16,
+    u32: u32,
+    u64: u64,
+    u128: u128,
+    f32: f32,
+    f64: f64,
+    char: char,
+    string: String,
+    #[serde(with = "serde_bytes")]
+    bytes: Vec<u8>,
+    none: Option<u8>,
+    some: Option<u8>,
+    unit: (),
+    unit_struct: Unit,
+    unit_variant: Enum,
+    newtype_struct: Newtype,
+    newtype_variant: Enum,
+    seq: Vec<u16>,
+    tuple: (u8, String, bool),
+    tuple_struct: Tuple,
+    tuple_variant: Enum,
+    map: BTreeMap<String, i32>,
+    r#struct: Struct,
+    struct_variant: Enum,
+}
+
+#[test]
+fn test_roundtrip_data_model() {
+    let value = DataModel {
+        bool: true,
+        i8: i8::MIN,
+        i16: i16::MIN,
+        i32: i32::MIN,
+        i64: i64::MIN,
+        i128: i128::MIN,
+        u8: u8::MAX,
+        u16: u16::MAX,
+        u32: u32::MAX,
+        u64: u64::MAX,
+        u128: u128::MAX,
+        f32: 1.5,
+        f64: -0.25,
+        char: 'λ',
+        string: "a \"string\"".into(),
+        bytes: vec![0, 1, 255],
+        none: None,
+        some: Some(7),
+        unit: (),
+        unit_struct: Unit,
+        unit_variant: Enum::Unit,
+        newtype_struct: Newtype(42),
+        newtype_variant: Enum::Newtype(false),
+        seq: vec![1, 2, 3],
+        tuple: (1, "two".into(), true),
+        tuple_struct: Tuple(-1, "x".into()),
+        tuple_variant: Enum::Tuple(3, 4),
+        map: BTreeMap::from([("a".into(), 1), ("b".into(), -2)]),
+        r#struct: Struct {
+            x: 0.5,
+            y: Some('y'),
+        },
+        struct_variant: Enum::Struct { z: -9 },
+    };
+    let output = json(&value);
+    assert_eq!(output, serde_json::to_vec(&value).unwrap());
+}
+
+#[test]
+fn test_roundtrip_values() {
+    assert_eq!(json(&String::from("text")), br#""text""#);
+    assert_eq!(json(&Some(Enum::Unit)), br#""Unit""#);
+    assert_eq!(json(&Vec::<Struct>::new()), b"[]");
+    assert_eq!(json(&BTreeMap::from([(1, Unit)])), br#"{"1":null}"#);
+}
+
+#[test]
+#[should_panic(expected = "failed to deserialize NaN")]
+fn test_roundtrip_lossy() {
+    // JSON writes NaN as `null`, which does not read back as a float.
+    json(&f64::NAN);
+}
+
+#[test]
+#[should_panic(expected = "the deserializer has not been made")]
+fn test_roundtrip_not_made() {
+    roundtrip(
+        &1,
+        |output, f| {
+            f(&mut <dyn Serializer>::new(
+                &mut serde_json::Serializer::new(output),
+            ))
+        },
+        |_, _| {},
+    );
+}